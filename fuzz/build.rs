@@ -0,0 +1,15 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+fn main() {
+    protoc::Protoc::new()
+        .include("../src/interchange/testdata")
+        .input("../src/interchange/testdata/fuzz.proto")
+        .build_script_exec();
+}
@@ -0,0 +1,72 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Decodes arbitrary bytes as Avro data, both with the generic value decoder
+//! and with the decoder that Avro sources use.
+//!
+//! Decoding errors are expected; only panics and runaway allocations are bugs.
+
+use honggfuzz::fuzz;
+
+use interchange::avro::{parse_schema, Decoder, EnvelopeType};
+
+const SCHEMA: &str = r#"{
+    "type": "record",
+    "name": "row",
+    "fields": [
+        {"name": "b", "type": "boolean"},
+        {"name": "i", "type": "int"},
+        {"name": "l", "type": "long"},
+        {"name": "f", "type": "float"},
+        {"name": "d", "type": "double"},
+        {"name": "s", "type": ["null", "string"]},
+        {"name": "by", "type": "bytes"},
+        {"name": "fx", "type": {"type": "fixed", "name": "fx", "size": 4}},
+        {"name": "e", "type": {"type": "enum", "name": "e", "symbols": ["A", "B", "C"]}},
+        {"name": "date", "type": {"type": "int", "logicalType": "date"}},
+        {"name": "ts_ms", "type": {"type": "long", "logicalType": "timestamp-millis"}},
+        {"name": "ts_us", "type": {"type": "long", "logicalType": "timestamp-micros"}},
+        {"name": "dec", "type": {"type": "bytes", "logicalType": "decimal", "precision": 10, "scale": 2}},
+        {"name": "uuid", "type": {"type": "string", "logicalType": "uuid"}},
+        {"name": "a", "type": {"type": "array", "items": ["null", "long"]}},
+        {"name": "m", "type": {"type": "map", "values": "string"}},
+        {
+            "name": "r",
+            "type": {
+                "type": "record",
+                "name": "inner",
+                "fields": [
+                    {"name": "x", "type": "int"},
+                    {"name": "y", "type": {"type": "array", "items": "string"}}
+                ]
+            }
+        }
+    ]
+}"#;
+
+fn main() {
+    let schema = parse_schema(SCHEMA).expect("valid schema");
+    let mut decoder = Decoder::new(
+        SCHEMA,
+        None,
+        EnvelopeType::None,
+        "fuzz".into(),
+        0,
+        None,
+        None,
+        false,
+    )
+    .expect("valid schema");
+    loop {
+        fuzz!(|data: &[u8]| {
+            let _ = mz_avro::from_avro_datum(&schema, &mut &data[..]);
+            let _ = futures::executor::block_on(decoder.decode(data, None, None));
+        });
+    }
+}
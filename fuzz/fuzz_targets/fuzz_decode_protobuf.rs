@@ -0,0 +1,49 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Decodes arbitrary bytes as protobuf messages with the decoder that protobuf
+//! sources use.
+//!
+//! The first byte of the input selects the message type to decode, from the
+//! types in `src/interchange/testdata/fuzz.proto`. Decoding errors are
+//! expected; only panics and runaway allocations are bugs.
+
+use honggfuzz::fuzz;
+
+use interchange::protobuf::{decode_descriptors, Decoder};
+
+mod gen {
+    include!(concat!(env!("OUT_DIR"), "/protobuf/mod.rs"));
+}
+
+const MESSAGES: &[&str] = &[
+    ".TestRecord",
+    ".TestRepeatedRecord",
+    ".TestNestedRecord",
+    ".TestRepeatedNestedRecord",
+];
+
+fn main() {
+    let mut decoders: Vec<_> = MESSAGES
+        .iter()
+        .map(|message_name| {
+            let descriptors =
+                decode_descriptors(gen::FILE_DESCRIPTOR_SET_DATA).expect("valid descriptors");
+            Decoder::new(descriptors, message_name)
+        })
+        .collect();
+    loop {
+        fuzz!(|data: &[u8]| {
+            if let Some((selector, bytes)) = data.split_first() {
+                let decoder = &mut decoders[usize::from(*selector) % decoders.len()];
+                let _ = decoder.decode(bytes, None);
+            }
+        });
+    }
+}
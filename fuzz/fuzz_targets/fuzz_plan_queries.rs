@@ -0,0 +1,94 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Plans and optimizes generated queries against the tables in
+//! `fuzz::SCHEMA`.
+//!
+//! Planning and optimization errors are expected; only panics are bugs.
+
+use arbitrary::Unstructured;
+use honggfuzz::fuzz;
+use tempfile::NamedTempFile;
+
+use coord::catalog::{Catalog, CatalogItem, Table};
+use coord::session::Session;
+use ore::collections::CollectionExt;
+use sql::plan::{Params, Plan, PlanContext};
+use sql_parser::parser::parse_statements;
+use transform::Optimizer;
+
+/// Opens a catalog that contains the tables in `fuzz::SCHEMA`.
+fn open_catalog(path: &std::path::Path) -> Catalog {
+    let mut catalog = Catalog::open_debug(path).expect("failed to open catalog");
+    for sql in fuzz::SCHEMA {
+        let stmt = parse_statements(sql).unwrap().into_element();
+        let pcx = PlanContext::default();
+        let plan = {
+            let session = Session::dummy();
+            let conn_catalog = catalog.for_session(&session);
+            sql::plan::plan(&pcx, &conn_catalog, stmt, &Params::empty())
+                .expect("failed to plan schema")
+                .0
+        };
+        match plan {
+            Plan::CreateTable {
+                name,
+                table,
+                depends_on,
+                ..
+            } => {
+                let id = catalog.allocate_id().unwrap();
+                let oid = catalog.allocate_oid().unwrap();
+                let table = Table {
+                    create_sql: table.create_sql,
+                    plan_cx: pcx,
+                    desc: table.desc,
+                    defaults: table.defaults,
+                    checks: table.checks,
+                    conn_id: None,
+                    depends_on,
+                };
+                let _ = catalog.insert_item(id, oid, name, CatalogItem::Table(table));
+            }
+            _ => panic!("schema statement did not create a table: {}", sql),
+        }
+    }
+    catalog
+}
+
+fn main() {
+    let catalog_file = NamedTempFile::new().unwrap();
+    let catalog = open_catalog(catalog_file.path());
+    let session = Session::dummy();
+    let conn_catalog = catalog.for_session(&session);
+    loop {
+        fuzz!(|data: &[u8]| {
+            let sql = match fuzz::generate_query(&mut Unstructured::new(data)) {
+                Ok(sql) => sql,
+                Err(_) => return,
+            };
+            let stmts = match parse_statements(&sql) {
+                Ok(stmts) => stmts,
+                Err(_) => return,
+            };
+            for stmt in stmts {
+                let pcx = PlanContext::default();
+                if let Ok((Plan::Peek { source, .. }, _)) =
+                    sql::plan::plan(&pcx, &conn_catalog, stmt, &Params::empty())
+                {
+                    let _ = Optimizer::default().optimize(
+                        source,
+                        catalog.indexes(),
+                        catalog.foreign_keys(),
+                    );
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Checks that generated statements survive a round trip through the
+//! parser and the pretty printer unchanged.
+
+use arbitrary::Unstructured;
+use honggfuzz::fuzz;
+use sql_parser::parser::parse_statements;
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let sql = match fuzz::generate_query(&mut Unstructured::new(data)) {
+                Ok(sql) => sql,
+                Err(_) => return,
+            };
+            let stmts = parse_statements(&sql)
+                .unwrap_or_else(|e| panic!("generated query failed to parse: {}: {}", sql, e));
+            for stmt in stmts {
+                let formatted = stmt.to_string();
+                let reparsed = parse_statements(&formatted).unwrap_or_else(|e| {
+                    panic!("formatted statement failed to parse: {}: {}", formatted, e)
+                });
+                assert_eq!(vec![stmt], reparsed, "{}", formatted);
+            }
+        });
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Generation of SQL queries from fuzzer input.
+//!
+//! Fuzzing the parser and planner with arbitrary strings spends nearly all of
+//! its time in the lexer. The generator in this module instead interprets the
+//! fuzzer's input as a sequence of choices in a small grammar of `SELECT`
+//! statements over the tables in [`SCHEMA`], so that most of the queries it
+//! produces parse, and many of them plan.
+
+use arbitrary::{Result, Unstructured};
+
+/// The statements that create the tables that generated queries refer to.
+pub const SCHEMA: &[&str] = &[
+    "CREATE TABLE t1 (a int NOT NULL, b text, c bool)",
+    "CREATE TABLE t2 (d int, e double precision, f timestamp)",
+];
+
+/// The columns of the tables in [`SCHEMA`].
+const TABLES: &[(&str, &[(&str, Type)])] = &[
+    (
+        "t1",
+        &[("a", Type::Int), ("b", Type::Text), ("c", Type::Bool)],
+    ),
+    (
+        "t2",
+        &[("d", Type::Int), ("e", Type::Float), ("f", Type::Timestamp)],
+    ),
+];
+
+const SET_OPS: &[&str] = &["UNION", "UNION ALL", "EXCEPT", "EXCEPT ALL", "INTERSECT"];
+
+/// The maximum nesting depth of generated queries and expressions.
+const MAX_DEPTH: usize = 6;
+
+/// Generates a `SELECT` statement from `u`.
+pub fn generate_query(u: &mut Unstructured) -> Result<String> {
+    let mut gen = Generator {
+        u,
+        depth: 0,
+        next_alias: 0,
+    };
+    let types = gen.types()?;
+    gen.query(&[], &types)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Type {
+    Int,
+    Float,
+    Text,
+    Bool,
+    Timestamp,
+}
+
+const TYPES: &[Type] = &[
+    Type::Int,
+    Type::Float,
+    Type::Text,
+    Type::Bool,
+    Type::Timestamp,
+];
+
+impl Type {
+    fn name(self) -> &'static str {
+        match self {
+            Type::Int => "int",
+            Type::Float => "double precision",
+            Type::Text => "text",
+            Type::Bool => "bool",
+            Type::Timestamp => "timestamp",
+        }
+    }
+}
+
+/// A column that expressions may refer to.
+#[derive(Clone, Debug)]
+struct Column {
+    /// The qualified name of the column, e.g. `r0.a`.
+    name: String,
+    typ: Type,
+}
+
+struct Generator<'a, 'b> {
+    u: &'a mut Unstructured<'b>,
+    depth: usize,
+    next_alias: usize,
+}
+
+impl<'a, 'b> Generator<'a, 'b> {
+    /// Chooses a number in `0..n`.
+    fn choice(&mut self, n: usize) -> Result<usize> {
+        self.u.int_in_range(0..=n - 1)
+    }
+
+    fn flip(&mut self) -> Result<bool> {
+        Ok(self.choice(2)? == 1)
+    }
+
+    /// Reports whether the generator may recurse further.
+    fn may_nest(&self) -> bool {
+        self.depth < MAX_DEPTH
+    }
+
+    fn alias(&mut self) -> String {
+        let alias = format!("r{}", self.next_alias);
+        self.next_alias += 1;
+        alias
+    }
+
+    fn types(&mut self) -> Result<Vec<Type>> {
+        let n = 1 + self.choice(3)?;
+        (0..n).map(|_| self.typ()).collect()
+    }
+
+    fn typ(&mut self) -> Result<Type> {
+        Ok(TYPES[self.choice(TYPES.len())?])
+    }
+
+    /// Generates a query whose columns have types `types`. Expressions in the
+    /// query may refer to the columns in `outer`.
+    fn query(&mut self, outer: &[Column], types: &[Type]) -> Result<String> {
+        self.depth += 1;
+        let query = if self.may_nest() && self.choice(4)? == 0 {
+            let op = SET_OPS[self.choice(SET_OPS.len())?];
+            let left = self.query(outer, types)?;
+            let right = self.query(outer, types)?;
+            format!("({}) {} ({})", left, op, right)
+        } else {
+            self.select(outer, types)?
+        };
+        self.depth -= 1;
+        Ok(query)
+    }
+
+    fn select(&mut self, outer: &[Column], types: &[Type]) -> Result<String> {
+        let mut scope = outer.to_vec();
+        let from = self.from(outer, &mut scope)?;
+        let mut sql = String::from("SELECT ");
+        if self.choice(4)? == 0 {
+            sql.push_str("DISTINCT ");
+        }
+        for (i, typ) in types.iter().enumerate() {
+            if i > 0 {
+                sql.push_str(", ");
+            }
+            let expr = self.expr(&scope, *typ)?;
+            sql.push_str(&format!("{} AS c{}", expr, i));
+        }
+        sql.push_str(" FROM ");
+        sql.push_str(&from);
+        if self.flip()? {
+            let pred = self.expr(&scope, Type::Bool)?;
+            sql.push_str(&format!(" WHERE {}", pred));
+        }
+        if self.choice(4)? == 0 {
+            let n = 1 + self.choice(types.len())?;
+            let keys: Vec<_> = (1..=n).map(|i| i.to_string()).collect();
+            sql.push_str(&format!(" GROUP BY {}", keys.join(", ")));
+            if self.flip()? {
+                let pred = self.expr(&scope, Type::Bool)?;
+                sql.push_str(&format!(" HAVING {}", pred));
+            }
+        }
+        if self.flip()? {
+            let key = 1 + self.choice(types.len())?;
+            let dir = ["", " ASC", " DESC"][self.choice(3)?];
+            sql.push_str(&format!(" ORDER BY {}{}", key, dir));
+        }
+        if self.choice(4)? == 0 {
+            sql.push_str(&format!(" LIMIT {}", self.choice(10)?));
+            if self.flip()? {
+                sql.push_str(&format!(" OFFSET {}", self.choice(10)?));
+            }
+        }
+        Ok(sql)
+    }
+
+    /// Generates a `FROM` clause, adding the columns it introduces to
+    /// `scope`.
+    fn from(&mut self, outer: &[Column], scope: &mut Vec<Column>) -> Result<String> {
+        let mut sql = self.table_factor(outer, scope)?;
+        for _ in 0..self.choice(3)? {
+            let right = self.table_factor(outer, scope)?;
+            match self.choice(5)? {
+                0 => sql = format!("{}, {}", sql, right),
+                1 => sql = format!("{} CROSS JOIN {}", sql, right),
+                n => {
+                    let kind = ["JOIN", "LEFT JOIN", "FULL JOIN"][n - 2];
+                    let on = self.expr(scope, Type::Bool)?;
+                    sql = format!("{} {} {} ON {}", sql, kind, right, on);
+                }
+            }
+        }
+        Ok(sql)
+    }
+
+    fn table_factor(&mut self, outer: &[Column], scope: &mut Vec<Column>) -> Result<String> {
+        let alias = self.alias();
+        if self.may_nest() && self.choice(4)? == 0 {
+            let types = self.types()?;
+            let query = self.query(outer, &types)?;
+            for (i, typ) in types.into_iter().enumerate() {
+                scope.push(Column {
+                    name: format!("{}.c{}", alias, i),
+                    typ,
+                });
+            }
+            Ok(format!("({}) AS {}", query, alias))
+        } else {
+            let (name, columns) = TABLES[self.choice(TABLES.len())?];
+            for (column, typ) in columns.iter() {
+                scope.push(Column {
+                    name: format!("{}.{}", alias, column),
+                    typ: *typ,
+                });
+            }
+            Ok(format!("{} AS {}", name, alias))
+        }
+    }
+
+    /// Generates an expression of type `typ` that may refer to the columns in
+    /// `scope`.
+    fn expr(&mut self, scope: &[Column], typ: Type) -> Result<String> {
+        self.depth += 1;
+        let expr = if !self.may_nest() {
+            self.leaf(scope, typ)?
+        } else {
+            match self.choice(8)? {
+                0 | 1 => self.leaf(scope, typ)?,
+                2 => {
+                    let cond = self.expr(scope, Type::Bool)?;
+                    let then = self.expr(scope, typ)?;
+                    let els = self.expr(scope, typ)?;
+                    format!("CASE WHEN {} THEN {} ELSE {} END", cond, then, els)
+                }
+                3 => {
+                    let left = self.expr(scope, typ)?;
+                    let right = self.expr(scope, typ)?;
+                    format!("coalesce({}, {})", left, right)
+                }
+                4 => {
+                    let from = self.typ()?;
+                    let expr = self.expr(scope, from)?;
+                    format!("CAST({} AS {})", expr, typ.name())
+                }
+                5 => {
+                    let query = self.query(scope, &[typ])?;
+                    format!("({})", query)
+                }
+                _ => self.typed_expr(scope, typ)?,
+            }
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    /// Generates an expression that is specific to `typ`.
+    fn typed_expr(&mut self, scope: &[Column], typ: Type) -> Result<String> {
+        Ok(match typ {
+            Type::Int | Type::Float => match self.choice(4)? {
+                0 => {
+                    let op = ["+", "-", "*", "/", "%"][self.choice(5)?];
+                    let left = self.expr(scope, typ)?;
+                    let right = self.expr(scope, typ)?;
+                    format!("({} {} {})", left, op, right)
+                }
+                1 => format!("-({})", self.expr(scope, typ)?),
+                2 => {
+                    let func = ["sum", "min", "max", "abs"][self.choice(4)?];
+                    format!("{}({})", func, self.expr(scope, typ)?)
+                }
+                _ => {
+                    let arg = match self.choice(3)? {
+                        0 => "*".into(),
+                        1 => {
+                            let typ = self.typ()?;
+                            self.expr(scope, typ)?
+                        }
+                        _ => {
+                            let typ = self.typ()?;
+                            format!("DISTINCT {}", self.expr(scope, typ)?)
+                        }
+                    };
+                    format!("CAST(count({}) AS {})", arg, typ.name())
+                }
+            },
+            Type::Text => match self.choice(3)? {
+                0 => {
+                    let left = self.expr(scope, typ)?;
+                    let right = self.expr(scope, typ)?;
+                    format!("({} || {})", left, right)
+                }
+                1 => {
+                    let func = ["upper", "lower", "btrim", "max"][self.choice(4)?];
+                    format!("{}({})", func, self.expr(scope, typ)?)
+                }
+                _ => {
+                    let string = self.expr(scope, typ)?;
+                    let start = self.expr(scope, Type::Int)?;
+                    format!("substr({}, {})", string, start)
+                }
+            },
+            Type::Bool => match self.choice(6)? {
+                0 => {
+                    let op = ["=", "<>", "<", "<=", ">", ">="][self.choice(6)?];
+                    let operand = self.typ()?;
+                    let left = self.expr(scope, operand)?;
+                    let right = self.expr(scope, operand)?;
+                    format!("({} {} {})", left, op, right)
+                }
+                1 => {
+                    let op = ["AND", "OR"][self.choice(2)?];
+                    let left = self.expr(scope, typ)?;
+                    let right = self.expr(scope, typ)?;
+                    format!("({} {} {})", left, op, right)
+                }
+                2 => format!("(NOT {})", self.expr(scope, typ)?),
+                3 => {
+                    let operand = self.typ()?;
+                    let test = ["IS NULL", "IS NOT NULL"][self.choice(2)?];
+                    format!("({} {})", self.expr(scope, operand)?, test)
+                }
+                4 => {
+                    let types = self.types()?;
+                    format!("EXISTS ({})", self.query(scope, &types)?)
+                }
+                _ => {
+                    let operand = self.typ()?;
+                    let expr = self.expr(scope, operand)?;
+                    let query = self.query(scope, &[operand])?;
+                    format!("({} IN ({}))", expr, query)
+                }
+            },
+            Type::Timestamp => match self.choice(2)? {
+                0 => {
+                    let op = ["+", "-"][self.choice(2)?];
+                    let ts = self.expr(scope, typ)?;
+                    format!("({} {} INTERVAL '1 day')", ts, op)
+                }
+                _ => {
+                    let unit = ["second", "hour", "day", "month"][self.choice(4)?];
+                    format!("date_trunc('{}', {})", unit, self.expr(scope, typ)?)
+                }
+            },
+        })
+    }
+
+    /// Generates a column reference or a literal of type `typ`.
+    fn leaf(&mut self, scope: &[Column], typ: Type) -> Result<String> {
+        let columns: Vec<_> = scope.iter().filter(|c| c.typ == typ).collect();
+        if !columns.is_empty() && self.flip()? {
+            return Ok(columns[self.choice(columns.len())?].name.clone());
+        }
+        if self.choice(8)? == 0 {
+            return Ok(format!("CAST(NULL AS {})", typ.name()));
+        }
+        Ok(match typ {
+            Type::Int => ["0", "1", "-1", "7", "2147483647", "-2147483648"][self.choice(6)?].into(),
+            Type::Float => ["0.0", "1.5", "-2.25", "1e300"][self.choice(4)?].into(),
+            Type::Text => ["''", "'a'", "'hello'", "'%'"][self.choice(4)?].into(),
+            Type::Bool => ["true", "false"][self.choice(2)?].into(),
+            Type::Timestamp => [
+                "TIMESTAMP '1970-01-01 00:00:00'",
+                "TIMESTAMP '2020-02-29 12:34:56.789'",
+            ][self.choice(2)?]
+            .into(),
+        })
+    }
+}
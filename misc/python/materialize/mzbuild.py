@@ -186,6 +186,7 @@ class CargoBuild(CargoPreImage):
     def __init__(self, rd: RepositoryDetails, path: Path, config: Dict[str, Any]):
         super().__init__(rd, path)
         self.bin = config.pop("bin", None)
+        self.features = config.pop("features", [])
         self.strip = config.pop("strip", True)
         self.extract = config.pop("extract", {})
         if self.bin is None:
@@ -193,6 +194,8 @@ class CargoBuild(CargoPreImage):
 
     def build(self) -> None:
         cargo_build = [self.rd.xcargo(), "build", "--bin", self.bin]
+        if self.features:
+            cargo_build.extend(["--features", ",".join(self.features)])
         if self.rd.release_mode:
             cargo_build.append("--release")
         spawn.runv(cargo_build, cwd=self.rd.root)
@@ -17,7 +17,7 @@ use crate::schema::{
 };
 use crate::types::{Scalar, Value};
 use crate::{
-    util::{safe_len, zag_i32, zag_i64, TsUnit},
+    util::{read_exact_into, safe_len, zag_i32, zag_i64, TsUnit},
     TrivialDecoder, ValueDecoder,
 };
 
@@ -97,8 +97,8 @@ fn build_ts_value(value: i64, unit: TsUnit) -> Result<Value, AvroError> {
         TsUnit::Micros => 1_000_000,
     };
     let nanos_per_unit = 1_000_000_000 / units_per_second as u32;
-    let seconds = value / units_per_second;
-    let fraction = (value % units_per_second) as u32;
+    let seconds = value.div_euclid(units_per_second);
+    let fraction = value.rem_euclid(units_per_second) as u32;
     Ok(Value::Timestamp(
         NaiveDateTime::from_timestamp_opt(seconds, fraction * nanos_per_unit).ok_or(
             AvroError::Decode(DecodeError::BadTimestamp {
@@ -427,8 +427,12 @@ impl<'a, R: AvroRead> AvroMapAccess for SimpleMapAccess<'a, R> {
         if self.remaining == 0 {
             // TODO -- we can use len_in_bytes to quickly skip non-demanded arrays
             let (len, _len_in_bytes) = match zag_i64(self.r)? {
-                len if len > 0 => (len as usize, None),
-                neglen if neglen < 0 => (neglen.abs() as usize, Some(decode_len(self.r)?)),
+                len if len > 0 => (safe_len(len as usize)?, None),
+                // The negation wraps for `i64::MIN`, which `safe_len` rejects.
+                neglen if neglen < 0 => (
+                    safe_len(neglen.wrapping_neg() as usize)?,
+                    Some(decode_len(self.r)?),
+                ),
                 0 => {
                     self.done = true;
                     return Ok(None);
@@ -444,8 +448,7 @@ impl<'a, R: AvroRead> AvroMapAccess for SimpleMapAccess<'a, R> {
         // right now so it doesn't really matter.
         let key_len = decode_len(self.r)?;
         let mut key_buf = vec![];
-        key_buf.resize_with(key_len, Default::default);
-        self.r.read_exact(&mut key_buf)?;
+        read_exact_into(self.r, key_len, &mut key_buf)?;
         let key = String::from_utf8(key_buf)
             .map_err(|_e| AvroError::Decode(DecodeError::MapKeyUtf8Error))?;
 
@@ -506,8 +509,12 @@ impl<'a, R: AvroRead> AvroArrayAccess for SimpleArrayAccess<'a, R> {
         if self.remaining == 0 {
             // TODO -- we can use len_in_bytes to quickly skip non-demanded arrays
             let (len, _len_in_bytes) = match zag_i64(self.r)? {
-                len if len > 0 => (len as usize, None),
-                neglen if neglen < 0 => (neglen.abs() as usize, Some(decode_len(self.r)?)),
+                len if len > 0 => (safe_len(len as usize)?, None),
+                // The negation wraps for `i64::MIN`, which `safe_len` rejects.
+                neglen if neglen < 0 => (
+                    safe_len(neglen.wrapping_neg() as usize)?,
+                    Some(decode_len(self.r)?),
+                ),
                 0 => {
                     self.done = true;
                     return Ok(None);
@@ -686,7 +693,8 @@ pub mod public_decoders {
     use crate::error::{DecodeError, Error as AvroError};
     use crate::types::{DecimalValue, Scalar, Value};
     use crate::{
-        AvroArrayAccess, AvroDecode, AvroDeserializer, AvroRead, AvroRecordAccess, ValueOrReader,
+        read_exact_into, AvroArrayAccess, AvroDecode, AvroDeserializer, AvroRead, AvroRecordAccess,
+        ValueOrReader,
     };
     use std::collections::HashMap;
 
@@ -1058,8 +1066,7 @@ pub mod public_decoders {
                 ValueOrReader::Value(buf) => buf.to_vec(),
                 ValueOrReader::Reader { len, r } => {
                     let mut buf = vec![];
-                    buf.resize_with(len, Default::default);
-                    r.read_exact(&mut buf)?;
+                    read_exact_into(r, len, &mut buf)?;
                     buf
                 }
             };
@@ -1077,8 +1084,7 @@ pub mod public_decoders {
                 ValueOrReader::Value(buf) => buf.to_vec(),
                 ValueOrReader::Reader { len, r } => {
                     let mut buf = vec![];
-                    buf.resize_with(len, Default::default);
-                    r.read_exact(&mut buf)?;
+                    read_exact_into(r, len, &mut buf)?;
                     buf
                 }
             };
@@ -1092,8 +1098,7 @@ pub mod public_decoders {
                 ValueOrReader::Value(s) => s.to_string(),
                 ValueOrReader::Reader { len, r } => {
                     let mut buf = vec![];
-                    buf.resize_with(len, Default::default);
-                    r.read_exact(&mut buf)?;
+                    read_exact_into(r, len, &mut buf)?;
                     String::from_utf8(buf)
                         .map_err(|_e| AvroError::Decode(DecodeError::StringUtf8Error))?
                 }
@@ -1108,8 +1113,7 @@ pub mod public_decoders {
                 ValueOrReader::Value(val) => val.clone(),
                 ValueOrReader::Reader { len, r } => {
                     let mut buf = vec![];
-                    buf.resize_with(len, Default::default);
-                    r.read_exact(&mut buf)?;
+                    read_exact_into(r, len, &mut buf)?;
                     serde_json::from_slice(&buf)
                         .map_err(|e| AvroError::Decode(DecodeError::BadJson(e.classify())))?
                 }
@@ -1124,8 +1128,7 @@ pub mod public_decoders {
                 ValueOrReader::Value(val) => val.to_vec(),
                 ValueOrReader::Reader { len, r } => {
                     let mut buf = vec![];
-                    buf.resize_with(len, Default::default);
-                    r.read_exact(&mut buf)?;
+                    read_exact_into(r, len, &mut buf)?;
                     buf
                 }
             };
@@ -1143,8 +1146,7 @@ pub mod public_decoders {
                 ValueOrReader::Value(buf) => buf.to_vec(),
                 ValueOrReader::Reader { len, r } => {
                     let mut buf = vec![];
-                    buf.resize_with(len, Default::default);
-                    r.read_exact(&mut buf)?;
+                    read_exact_into(r, len, &mut buf)?;
                     buf
                 }
             };
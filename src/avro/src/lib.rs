@@ -330,7 +330,7 @@ pub use crate::encode::encode as encode_unchecked;
 pub use crate::reader::{from_avro_datum, Reader};
 pub use crate::schema::{ParseSchemaError, Schema};
 pub use crate::types::SchemaResolutionError;
-pub use crate::util::max_allocation_bytes;
+pub use crate::util::{max_allocation_bytes, read_exact_into};
 pub use crate::writer::{to_avro_datum, write_avro_datum, ValidationError, Writer};
 
 #[cfg(test)]
@@ -3,7 +3,7 @@
 // Use of this software is governed by the Apache License, Version 2.0
 
 use std::i64;
-use std::io::Read;
+use std::io::{self, Read};
 use std::sync::Once;
 
 use serde_json::{Map, Value};
@@ -136,6 +136,25 @@ pub fn safe_len(len: usize) -> Result<usize, AvroError> {
     }
 }
 
+/// Reads exactly `len` bytes from `reader` into `buf`, replacing its contents.
+///
+/// Unlike resizing `buf` to `len` bytes and calling [`Read::read_exact`], this
+/// grows `buf` only as bytes are actually read, so a corrupt length fails at
+/// the end of the input rather than first allocating up to
+/// [`MAX_ALLOCATION_BYTES`].
+pub fn read_exact_into<R: Read>(
+    reader: &mut R,
+    len: usize,
+    buf: &mut Vec<u8>,
+) -> Result<(), AvroError> {
+    buf.clear();
+    reader.take(len as u64).read_to_end(buf)?;
+    if buf.len() < len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -214,4 +233,13 @@ mod tests {
         assert_eq!(42usize, safe_len(42usize).unwrap());
         assert!(safe_len(1024 * 1024 * 1024).is_err());
     }
+
+    #[test]
+    fn test_read_exact_into() {
+        let mut buf = vec![9];
+        read_exact_into(&mut &b"abcdef"[..], 3, &mut buf).unwrap();
+        assert_eq!(buf, b"abc");
+        assert!(read_exact_into(&mut &b"abc"[..], 512 * 1024 * 1024, &mut buf).is_err());
+        assert!(buf.capacity() < 1024);
+    }
 }
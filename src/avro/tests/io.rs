@@ -1020,3 +1020,29 @@ fn test_partially_broken_union() {
         .to_string()
         .contains("Reader field `a` not found in writer"));
 }
+
+#[test]
+fn test_negative_timestamps() {
+    let schema =
+        Schema::from_str(r#"{"type": "long", "logicalType": "timestamp-millis"}"#).unwrap();
+    // -1500 milliseconds, zigzag encoded.
+    let encoded: &[u8] = &[0xb7, 0x17];
+    let datum_read = from_avro_datum(&schema, &mut &encoded[..]).unwrap();
+    assert_eq!(
+        datum_read,
+        Value::Timestamp(NaiveDateTime::from_timestamp(-2, 500_000_000))
+    );
+}
+
+#[test]
+fn test_malformed_lengths() {
+    // An array block count of `i64::MIN`.
+    let schema = Schema::from_str(r#"{"type": "array", "items": "long"}"#).unwrap();
+    let encoded: &[u8] = &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+    assert!(from_avro_datum(&schema, &mut &encoded[..]).is_err());
+
+    // A 256MiB string with no data.
+    let schema = Schema::from_str(r#""string""#).unwrap();
+    let encoded: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x02];
+    assert!(from_avro_datum(&schema, &mut &encoded[..]).is_err());
+}
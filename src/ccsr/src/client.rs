@@ -9,9 +9,10 @@
 
 use std::error::Error;
 use std::fmt;
+use std::str::FromStr;
 
 use reqwest::{Method, Url};
-use serde::de::DeserializeOwned;
+use serde::de::{DeserializeOwned, IgnoredAny};
 use serde::Deserialize;
 use serde_json::json;
 
@@ -95,6 +96,38 @@ impl Client {
         let _res: Vec<i32> = send_request(req).await?;
         Ok(())
     }
+
+    /// Deletes one version of the schemas associated with the specified
+    /// subject.
+    ///
+    /// Like [`Client::delete_subject`], this API is only intended to be used
+    /// in development environments.
+    pub async fn delete_subject_version(
+        &self,
+        subject: &str,
+        version: i32,
+    ) -> Result<(), DeleteError> {
+        let req = self.make_request(
+            Method::DELETE,
+            format!("/subjects/{}/versions/{}", subject, version),
+        );
+        let _res: i32 = send_request(req).await?;
+        Ok(())
+    }
+
+    /// Sets the compatibility level that the schema registry enforces when new
+    /// schemas are published for the specified subject.
+    pub async fn set_subject_compatibility_level(
+        &self,
+        subject: &str,
+        level: CompatibilityLevel,
+    ) -> Result<(), ConfigError> {
+        let req = self
+            .make_request(Method::PUT, format!("/config/{}", subject))
+            .json(&json!({ "compatibility": level.as_str() }));
+        let _res: IgnoredAny = send_request(req).await?;
+        Ok(())
+    }
 }
 
 async fn send_request<T>(req: reqwest::RequestBuilder) -> Result<T, UnhandledError>
@@ -330,6 +363,8 @@ impl fmt::Display for ListError {
 pub enum DeleteError {
     /// The specified subject does not exist.
     SubjectNotFound,
+    /// The specified version of the subject does not exist.
+    VersionNotFound,
     /// The underlying HTTP transport failed.
     Transport(reqwest::Error),
     /// An internal server error occured.
@@ -342,6 +377,7 @@ impl From<UnhandledError> for DeleteError {
             UnhandledError::Transport(err) => DeleteError::Transport(err),
             UnhandledError::Api { code, message } => match code {
                 40401 => DeleteError::SubjectNotFound,
+                40402 => DeleteError::VersionNotFound,
                 _ => DeleteError::Server { code, message },
             },
         }
@@ -351,7 +387,9 @@ impl From<UnhandledError> for DeleteError {
 impl Error for DeleteError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
-            DeleteError::SubjectNotFound | DeleteError::Server { .. } => None,
+            DeleteError::SubjectNotFound
+            | DeleteError::VersionNotFound
+            | DeleteError::Server { .. } => None,
             DeleteError::Transport(err) => Some(err),
         }
     }
@@ -361,6 +399,7 @@ impl fmt::Display for DeleteError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             DeleteError::SubjectNotFound => write!(f, "subject not found"),
+            DeleteError::VersionNotFound => write!(f, "version not found"),
             DeleteError::Transport(err) => write!(f, "transport: {}", err),
             DeleteError::Server { code, message } => {
                 write!(f, "server error {}: {}", code, message)
@@ -369,6 +408,90 @@ impl fmt::Display for DeleteError {
     }
 }
 
+/// The compatibility requirements that a schema registry can enforce between
+/// the schemas published for a subject.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CompatibilityLevel {
+    Backward,
+    BackwardTransitive,
+    Forward,
+    ForwardTransitive,
+    Full,
+    FullTransitive,
+    None,
+}
+
+impl CompatibilityLevel {
+    /// Returns the name of the compatibility level used by the schema
+    /// registry's API.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompatibilityLevel::Backward => "BACKWARD",
+            CompatibilityLevel::BackwardTransitive => "BACKWARD_TRANSITIVE",
+            CompatibilityLevel::Forward => "FORWARD",
+            CompatibilityLevel::ForwardTransitive => "FORWARD_TRANSITIVE",
+            CompatibilityLevel::Full => "FULL",
+            CompatibilityLevel::FullTransitive => "FULL_TRANSITIVE",
+            CompatibilityLevel::None => "NONE",
+        }
+    }
+}
+
+impl FromStr for CompatibilityLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "BACKWARD" => Ok(CompatibilityLevel::Backward),
+            "BACKWARD_TRANSITIVE" => Ok(CompatibilityLevel::BackwardTransitive),
+            "FORWARD" => Ok(CompatibilityLevel::Forward),
+            "FORWARD_TRANSITIVE" => Ok(CompatibilityLevel::ForwardTransitive),
+            "FULL" => Ok(CompatibilityLevel::Full),
+            "FULL_TRANSITIVE" => Ok(CompatibilityLevel::FullTransitive),
+            "NONE" => Ok(CompatibilityLevel::None),
+            _ => Err(format!("unknown compatibility level: {}", s)),
+        }
+    }
+}
+
+/// Errors for configuration operations.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The underlying HTTP transport failed.
+    Transport(reqwest::Error),
+    /// An internal server error occured.
+    Server { code: i32, message: String },
+}
+
+impl From<UnhandledError> for ConfigError {
+    fn from(err: UnhandledError) -> ConfigError {
+        match err {
+            UnhandledError::Transport(err) => ConfigError::Transport(err),
+            UnhandledError::Api { code, message } => ConfigError::Server { code, message },
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Server { .. } => None,
+            ConfigError::Transport(err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Transport(err) => write!(f, "transport: {}", err),
+            ConfigError::Server { code, message } => {
+                write!(f, "server error {}: {}", code, message)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct ErrorResponse {
     error_code: i32,
@@ -16,7 +16,10 @@ use hyper::StatusCode;
 use hyper::{Body, Response};
 use lazy_static::lazy_static;
 
-use ccsr::{Client, DeleteError, GetByIdError, GetBySubjectError, PublishError};
+use ccsr::{
+    Client, CompatibilityLevel, ConfigError, DeleteError, GetByIdError, GetBySubjectError,
+    PublishError,
+};
 
 lazy_static! {
     pub static ref SCHEMA_REGISTRY_URL: reqwest::Url = match env::var("SCHEMA_REGISTRY_URL") {
@@ -99,6 +102,23 @@ async fn test_client() -> Result<(), anyhow::Error> {
         .await?;
     assert_eq!(count_schemas(&client, "ccsr-test-").await?, 2);
 
+    // Deleting the latest version of a subject makes the previous version
+    // the latest.
+    client.delete_subject_version("ccsr-test-schema", 2).await?;
+    {
+        let res = client.get_schema_by_subject("ccsr-test-schema").await?;
+        assert_eq!(schema_v1_id, res.id);
+    }
+
+    // Incompatible schemas can be published once compatibility checking is
+    // disabled for the subject.
+    client
+        .set_subject_compatibility_level("ccsr-test-schema", CompatibilityLevel::None)
+        .await?;
+    client
+        .publish_schema("ccsr-test-schema", schema_v2_incompat)
+        .await?;
+
     Ok(())
 }
 
@@ -129,6 +149,18 @@ async fn test_client_errors() -> Result<(), anyhow::Error> {
         Err(DeleteError::SubjectNotFound) => (),
         res => panic!("expected DeleteError::SubjectNotFound, got {:?}", res),
     }
+    // This subject deliberately lacks the "ccsr-test-" prefix, as
+    // `test_client` concurrently deletes and counts those subjects.
+    client
+        .publish_schema("ccsr-errors-schema", "\"int\"")
+        .await?;
+    match client
+        .delete_subject_version("ccsr-errors-schema", i32::max_value())
+        .await
+    {
+        Err(DeleteError::VersionNotFound) => (),
+        res => panic!("expected DeleteError::VersionNotFound, got {:?}", res),
+    }
 
     Ok(())
 }
@@ -176,6 +208,17 @@ async fn test_server_errors() -> Result<(), anyhow::Error> {
         res => panic!("expected DeleteError::Server, got {:?}", res),
     }
 
+    match client_graceful
+        .set_subject_compatibility_level("foo", CompatibilityLevel::Full)
+        .await
+    {
+        Err(ConfigError::Server {
+            code: 50001,
+            ref message,
+        }) if message == "overloaded; try again later" => (),
+        res => panic!("expected ConfigError::Server, got {:?}", res),
+    }
+
     // If the schema registry crashes so hard that it spits out an exception
     // handler in the response, we should report the HTTP status code and a
     // generic message indicating that no further details were available.
@@ -0,0 +1,142 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Embeds a coordinator in process, without pgwire.
+//!
+//! Two frontends share one [`Client`]: one inserts events into a table while
+//! the other waits for a materialized view over the table to reflect them.
+//! Each frontend runs on its own task and opens its own session.
+//!
+//! Run with `cargo run -p coord --example embed`.
+
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::runtime::Runtime;
+
+use coord::session::{EndTransactionAction, Session};
+use coord::{Client, ExecuteResponse, SessionClient};
+use dataflow_types::PeekResponse;
+use repr::Row;
+use sql_parser::parser::parse_statements;
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let runtime = Arc::new(Runtime::new()?);
+    let data_dir = tempfile::tempdir()?;
+    let (handle, client) = runtime.block_on(
+        coord::Config::builder(data_dir.path())
+            .workers(2)
+            .serve(runtime.clone()),
+    )?;
+    println!("started coordinator for cluster {}", handle.cluster_id());
+
+    runtime.block_on(async {
+        run(
+            &client,
+            &[
+                "CREATE TABLE events (id int, kind text)",
+                "CREATE MATERIALIZED VIEW event_counts AS
+                    SELECT kind, count(*) FROM events GROUP BY kind",
+            ],
+        )
+        .await?;
+
+        let writer = tokio::spawn({
+            let client = client.clone();
+            async move {
+                for id in 0..10 {
+                    let kind = if id % 2 == 0 { "even" } else { "odd" };
+                    let sql = format!("INSERT INTO events VALUES ({}, '{}')", id, kind);
+                    run(&client, &[sql.as_str()]).await?;
+                }
+                Ok::<_, String>(())
+            }
+        });
+
+        let reader = tokio::spawn({
+            let client = client.clone();
+            async move {
+                loop {
+                    let rows = run(&client, &["SELECT * FROM event_counts ORDER BY kind"]).await?;
+                    let total: i64 = rows.iter().map(|row| row.unpack()[1].unwrap_int64()).sum();
+                    if total == 10 {
+                        return Ok::<_, String>(rows);
+                    }
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        });
+
+        writer.await.map_err(|e| e.to_string())??;
+        for row in reader.await.map_err(|e| e.to_string())?? {
+            println!("{}", row);
+        }
+        Ok::<_, String>(())
+    })?;
+
+    // The coordinator does not shut down until all of its clients have
+    // dropped, so the client must be dropped before the handle.
+    drop(client);
+    drop(handle);
+    Ok(())
+}
+
+/// Runs `statements` in a new session, each in its own implicit transaction,
+/// and returns the rows produced by the last statement.
+async fn run(client: &Client, statements: &[&str]) -> Result<Vec<Row>, String> {
+    let conn_client = client.new_conn().map_err(|e| e.to_string())?;
+    let session = Session::new(conn_client.conn_id(), "materialize".into());
+    let (mut session_client, _) = conn_client
+        .startup(session)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut result = Ok(vec![]);
+    for sql in statements {
+        result = execute(&mut session_client, sql).await;
+        if result.is_err() {
+            break;
+        }
+    }
+    // A session client must be terminated rather than dropped.
+    session_client.terminate().await;
+    result
+}
+
+async fn execute(session_client: &mut SessionClient, sql: &str) -> Result<Vec<Row>, String> {
+    const EMPTY_PORTAL: &str = "";
+    let stmt = parse_statements(sql)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .next()
+        .ok_or("empty statement")?;
+    session_client.session().start_transaction_implicit(1);
+    session_client
+        .declare(EMPTY_PORTAL.into(), stmt, vec![], None)
+        .await
+        .map_err(|e| e.to_string())?;
+    let response = session_client.execute(EMPTY_PORTAL.into()).await;
+    session_client.session().remove_portal(EMPTY_PORTAL);
+    let mut rows = vec![];
+    if let ExecuteResponse::SendingRows(mut rx) = response.map_err(|e| e.to_string())? {
+        while let Some(batch) = rx.next().await {
+            match batch {
+                PeekResponse::Rows(batch) => rows.extend(batch),
+                PeekResponse::Error(e) => return Err(e),
+                PeekResponse::Canceled => return Err("canceling statement".into()),
+            }
+        }
+    }
+    session_client
+        .end_transaction(EndTransactionAction::Commit)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rows)
+}
@@ -12,7 +12,7 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 use std::sync::{Arc, Mutex, MutexGuard};
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use anyhow::bail;
 use chrono::{DateTime, TimeZone, Utc};
@@ -20,30 +20,33 @@ use dataflow_types::SinkEnvelope;
 use expr::Id;
 use itertools::Itertools;
 use lazy_static::lazy_static;
-use log::{info, trace};
+use log::{info, trace, warn};
 use ore::collections::CollectionExt;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use build_info::DUMMY_BUILD_INFO;
 use dataflow_types::{SinkConnector, SinkConnectorBuilder, SourceConnector};
-use expr::{ExprHumanizer, GlobalId, MirScalarExpr, OptimizedMirRelationExpr};
+use expr::{
+    ExprHumanizer, ForeignKey, GlobalId, MirRelationExpr, MirScalarExpr, OptimizedMirRelationExpr,
+};
 use repr::{ColumnType, RelationDesc, ScalarType};
 use sql::ast::display::AstDisplay;
-use sql::ast::{Expr, Raw};
+use sql::ast::{Expr, Raw, Statement};
 use sql::catalog::{
     Catalog as SqlCatalog, CatalogError as SqlCatalogError, CatalogItem as SqlCatalogItem,
-    CatalogItemType as SqlCatalogItemType,
+    CatalogItemType as SqlCatalogItemType, Feature,
 };
 use sql::names::{DatabaseSpecifier, FullName, PartialName, SchemaName};
 use sql::plan::HirRelationExpr;
-use sql::plan::{Params, Plan, PlanContext};
+use sql::plan::{Params, Plan, PlanContext, TableCheck};
 use transform::Optimizer;
 
 use crate::catalog::builtin::{
     Builtin, BUILTINS, BUILTIN_ROLES, MZ_CATALOG_SCHEMA, MZ_INTERNAL_SCHEMA, MZ_TEMP_SCHEMA,
     PG_CATALOG_SCHEMA,
 };
+use crate::catalog::dump::{CatalogDump, ObjectDump};
 use crate::catalog::error::ErrorKind;
 use crate::catalog::migrate::CONTENT_MIGRATIONS;
 use crate::session::Session;
@@ -53,13 +56,15 @@ mod error;
 mod migrate;
 
 pub mod builtin;
+pub mod dump;
+pub mod fsck;
 pub mod storage;
 
 pub use crate::catalog::config::Config;
 pub use crate::catalog::error::Error;
 
 const SYSTEM_CONN_ID: u32 = 0;
-const SYSTEM_USER: &str = "mz_system";
+pub(crate) const SYSTEM_USER: &str = "mz_system";
 
 // TODO@jldlaughlin: Better assignment strategy for system type OIDs.
 // https://github.com/MaterializeInc/materialize/pull/4316#discussion_r496238962
@@ -93,9 +98,12 @@ pub struct Catalog {
     by_id: BTreeMap<GlobalId, CatalogEntry>,
     by_oid: HashMap<u32, GlobalId>,
     indexes: HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+    partial_indexes: HashMap<GlobalId, Vec<GlobalId>>,
+    foreign_keys: HashMap<GlobalId, Vec<ForeignKey>>,
     ambient_schemas: BTreeMap<String, Schema>,
     temporary_schemas: HashMap<u32, Schema>,
     roles: HashMap<String, Role>,
+    system_parameters: BTreeMap<String, String>,
     storage: Arc<Mutex<storage::Connection>>,
     oid_counter: u32,
     config: sql::catalog::CatalogConfig,
@@ -108,6 +116,7 @@ pub struct ConnCatalog<'a> {
     database: String,
     search_path: &'a [&'a str],
     user: String,
+    enabled_features: Vec<Feature>,
 }
 
 impl ConnCatalog<'_> {
@@ -141,6 +150,8 @@ pub struct Role {
     pub id: i64,
     #[serde(skip)]
     pub oid: u32,
+    /// The defaults for configuration parameters set via `ALTER ROLE ... SET`.
+    pub parameters: BTreeMap<String, String>,
 }
 
 #[derive(Clone, Debug)]
@@ -161,6 +172,7 @@ pub enum CatalogItem {
     Index(Index),
     Type(Type),
     Func(Func),
+    Task(Task),
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -170,6 +182,8 @@ pub struct Table {
     pub desc: RelationDesc,
     #[serde(skip)]
     pub defaults: Vec<Expr<Raw>>,
+    #[serde(skip)]
+    pub checks: Vec<TableCheck>,
     pub conn_id: Option<u32>,
     pub depends_on: Vec<GlobalId>,
 }
@@ -182,6 +196,9 @@ pub struct Source {
     pub connector: SourceConnector,
     pub bare_desc: RelationDesc,
     pub desc: RelationDesc,
+    /// The foreign keys declared on the source.
+    pub foreign_keys: Vec<ForeignKey>,
+    pub depends_on: Vec<GlobalId>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -209,6 +226,14 @@ pub struct View {
     pub desc: RelationDesc,
     pub conn_id: Option<u32>,
     pub depends_on: Vec<GlobalId>,
+    /// Whether the view is materialized on demand, i.e., whether its index is
+    /// only built while the view is being queried.
+    pub lazy: bool,
+    /// The number of bytes of reduction state per worker that are kept in
+    /// memory, if the state of the view's reductions spills to disk.
+    pub reduce_hot_set_size: Option<usize>,
+    /// The foreign keys declared on the view.
+    pub foreign_keys: Vec<ForeignKey>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -217,8 +242,14 @@ pub struct Index {
     pub plan_cx: PlanContext,
     pub on: GlobalId,
     pub keys: Vec<MirScalarExpr>,
+    /// The predicates that rows of `on` must satisfy to be included in the
+    /// index. Empty unless the index is a partial index.
+    pub predicates: Vec<MirScalarExpr>,
     pub conn_id: Option<u32>,
     pub depends_on: Vec<GlobalId>,
+    /// Whether the index was created on demand to materialize a lazy view,
+    /// and so is dropped once the view goes unqueried.
+    pub lazy: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -229,6 +260,19 @@ pub struct Type {
     pub depends_on: Vec<GlobalId>,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct Task {
+    pub create_sql: String,
+    pub plan_cx: PlanContext,
+    /// The table into which the task inserts.
+    pub target: GlobalId,
+    /// The rows that the task inserts each time it runs.
+    pub query: MirRelationExpr,
+    /// The interval at which the task runs.
+    pub schedule: Duration,
+    pub depends_on: Vec<GlobalId>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub enum TypeInner {
     Array {
@@ -271,6 +315,7 @@ impl CatalogItem {
             CatalogItem::Index(_) => sql::catalog::CatalogItemType::Index,
             CatalogItem::Type(_) => sql::catalog::CatalogItemType::Type,
             CatalogItem::Func(_) => sql::catalog::CatalogItemType::Func,
+            CatalogItem::Task(_) => sql::catalog::CatalogItemType::Task,
         }
     }
 
@@ -282,6 +327,7 @@ impl CatalogItem {
             CatalogItem::Func(_)
             | CatalogItem::Index(_)
             | CatalogItem::Sink(_)
+            | CatalogItem::Task(_)
             | CatalogItem::Type(_) => Err(SqlCatalogError::InvalidDependency {
                 name: name.to_string(),
                 typ: self.typ(),
@@ -303,8 +349,9 @@ impl CatalogItem {
             CatalogItem::Func(_) => &[],
             CatalogItem::Index(idx) => &idx.depends_on,
             CatalogItem::Sink(sink) => &sink.depends_on,
-            CatalogItem::Source(_) => &[],
+            CatalogItem::Source(source) => &source.depends_on,
             CatalogItem::Table(table) => &table.depends_on,
+            CatalogItem::Task(task) => &task.depends_on,
             CatalogItem::Type(typ) => &typ.depends_on,
             CatalogItem::View(view) => &view.depends_on,
         }
@@ -318,6 +365,7 @@ impl CatalogItem {
             | CatalogItem::Index(_)
             | CatalogItem::Source(_)
             | CatalogItem::Table(_)
+            | CatalogItem::Task(_)
             | CatalogItem::Type(_)
             | CatalogItem::View(_) => false,
             CatalogItem::Sink(s) => match s.connector {
@@ -388,6 +436,11 @@ impl CatalogItem {
                 i.create_sql = do_rewrite(i.create_sql)?;
                 Ok(CatalogItem::Index(i))
             }
+            CatalogItem::Task(i) => {
+                let mut i = i.clone();
+                i.create_sql = do_rewrite(i.create_sql)?;
+                Ok(CatalogItem::Task(i))
+            }
             CatalogItem::Func(_) | CatalogItem::Type(_) => {
                 unreachable!("{}s cannot be renamed", self.typ())
             }
@@ -449,6 +502,179 @@ impl Catalog {
     /// Returns the catalog and a list of events that describe the initial state
     /// of the catalog.
     pub fn open(config: &Config) -> Result<(Catalog, Vec<Event>), Error> {
+        Self::open_inner(config, None)
+    }
+
+    /// Checks whether the catalog at `config.path` can be opened by this
+    /// release of materialized, without modifying it.
+    ///
+    /// The check applies any pending migrations to a snapshot of the catalog,
+    /// then plans every item in the snapshot. It returns the errors for the
+    /// items that fail to plan. Problems that would prevent the catalog from
+    /// being opened at all, like a failed migration or a catalog written by a
+    /// newer release, are returned as an error.
+    pub fn check_upgrade(config: &Config) -> Result<Vec<Error>, anyhow::Error> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("catalog");
+        storage::snapshot(config.path, &path)?;
+        let mut failures = vec![];
+        Self::open_inner(
+            &Config {
+                path: &path,
+                ..config.clone()
+            },
+            Some(&mut failures),
+        )?;
+        Ok(failures.into_iter().map(|(_id, e)| e).collect())
+    }
+
+    /// Checks whether `stmts` can be executed in order against the catalog at
+    /// `config.path`, without modifying it or starting any dataflows.
+    ///
+    /// The statements are planned against a snapshot of the catalog, or
+    /// against a new catalog if there is no catalog at `config.path`. Objects
+    /// created by a statement are added to the snapshot, so later statements
+    /// can refer to them. Other statements are planned, but have no effect on
+    /// later statements.
+    ///
+    /// Returns the index and error of each statement that fails. Problems that
+    /// would prevent the catalog from being opened at all are returned as an
+    /// error.
+    pub fn check_statements(
+        config: &Config,
+        stmts: Vec<Statement<Raw>>,
+    ) -> Result<Vec<(usize, anyhow::Error)>, anyhow::Error> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("catalog");
+        if config.path.exists() {
+            storage::snapshot(config.path, &path)?;
+        }
+        let (mut catalog, _) = Self::open(&Config {
+            path: &path,
+            ..config.clone()
+        })?;
+        let session = Session::dummy();
+        let mut failures = vec![];
+        for (i, stmt) in stmts.into_iter().enumerate() {
+            if let Err(e) = catalog.check_statement(&session, stmt) {
+                failures.push((i, e));
+            }
+        }
+        Ok(failures)
+    }
+
+    /// Plans `stmt` on behalf of `session` and, if it is a `CREATE` statement,
+    /// applies it to the catalog.
+    fn check_statement(
+        &mut self,
+        session: &Session,
+        stmt: Statement<Raw>,
+    ) -> Result<(), anyhow::Error> {
+        let pcx = PlanContext::default();
+        let (plan, _) = sql::plan::plan(&pcx, &self.for_session(session), stmt, &Params::empty())?;
+        let mut ops = vec![];
+        let if_not_exists = match plan {
+            Plan::CreateDatabase {
+                name,
+                if_not_exists,
+            } => {
+                let oid = self.allocate_oid()?;
+                ops.push(Op::CreateDatabase { name, oid });
+                if_not_exists
+            }
+            Plan::CreateSchema {
+                database_name,
+                schema_name,
+                if_not_exists,
+            } => {
+                let oid = self.allocate_oid()?;
+                ops.push(Op::CreateSchema {
+                    database_name,
+                    schema_name,
+                    oid,
+                });
+                if_not_exists
+            }
+            Plan::CreateView {
+                ref name,
+                replace,
+                if_not_exists,
+                ..
+            } => {
+                let name = name.clone();
+                if let Some(id) = replace {
+                    ops.extend(self.drop_items_ops(&[id]));
+                }
+                let item = self.item_from_plan(plan, pcx)?;
+                ops.push(Op::CreateItem {
+                    id: self.allocate_id()?,
+                    oid: self.allocate_oid()?,
+                    name,
+                    item,
+                });
+                if_not_exists
+            }
+            Plan::CreateTable {
+                ref name,
+                if_not_exists,
+                ..
+            }
+            | Plan::CreateSource {
+                ref name,
+                if_not_exists,
+                ..
+            }
+            | Plan::CreateSink {
+                ref name,
+                if_not_exists,
+                ..
+            }
+            | Plan::CreateIndex {
+                ref name,
+                if_not_exists,
+                ..
+            }
+            | Plan::CreateTask {
+                ref name,
+                if_not_exists,
+                ..
+            } => {
+                let name = name.clone();
+                let item = self.item_from_plan(plan, pcx)?;
+                ops.push(Op::CreateItem {
+                    id: self.allocate_id()?,
+                    oid: self.allocate_oid()?,
+                    name,
+                    item,
+                });
+                if_not_exists
+            }
+            _ => return Ok(()),
+        };
+        match self.transact(ops) {
+            Ok(_) => Ok(()),
+            Err(e)
+                if if_not_exists
+                    && matches!(
+                        e.kind,
+                        ErrorKind::DatabaseAlreadyExists(_)
+                            | ErrorKind::SchemaAlreadyExists(_)
+                            | ErrorKind::ItemAlreadyExists(_)
+                    ) =>
+            {
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Like [`Catalog::open`], but if `failures` is provided, items that fail
+    /// to load are skipped and their IDs and errors are collected into
+    /// `failures` rather than returned.
+    fn open_inner(
+        config: &Config,
+        mut failures: Option<&mut Vec<(GlobalId, Error)>>,
+    ) -> Result<(Catalog, Vec<Event>), Error> {
         let (storage, experimental_mode, cluster_id) = storage::Connection::open(&config)?;
 
         let mut catalog = Catalog {
@@ -456,9 +682,12 @@ impl Catalog {
             by_id: BTreeMap::new(),
             by_oid: HashMap::new(),
             indexes: HashMap::new(),
+            partial_indexes: HashMap::new(),
+            foreign_keys: HashMap::new(),
             ambient_schemas: BTreeMap::new(),
             temporary_schemas: HashMap::new(),
             roles: HashMap::new(),
+            system_parameters: BTreeMap::new(),
             storage: Arc::new(Mutex::new(storage)),
             oid_counter: FIRST_USER_OID,
             config: sql::catalog::CatalogConfig {
@@ -531,11 +760,23 @@ impl Catalog {
                     name: name.clone(),
                     id,
                     oid,
+                    parameters: BTreeMap::new(),
                 },
             );
             events.push(Event::CreatedRole { name, id, oid });
         }
 
+        catalog.system_parameters = catalog
+            .storage()
+            .load_system_parameters()?
+            .into_iter()
+            .collect();
+        for (role_id, name, value) in catalog.storage().load_role_parameters()? {
+            if let Some(role) = catalog.roles.values_mut().find(|r| r.id == role_id) {
+                role.parameters.insert(name, value);
+            }
+        }
+
         for builtin in BUILTINS.values() {
             let name = FullName {
                 database: DatabaseSpecifier::Ambient,
@@ -563,6 +804,8 @@ impl Catalog {
                             connector: dataflow_types::SourceConnector::Local,
                             bare_desc: log.variant.desc(),
                             desc: log.variant.desc(),
+                            foreign_keys: vec![],
+                            depends_on: vec![],
                         }),
                     ));
                     let oid = catalog.allocate_oid()?;
@@ -583,6 +826,7 @@ impl Catalog {
                                     .into_iter()
                                     .map(MirScalarExpr::Column)
                                     .collect(),
+                                predicates: vec![],
                                 create_sql: super::coord::index_sql(
                                     index_name,
                                     name,
@@ -592,6 +836,7 @@ impl Catalog {
                                 plan_cx: PlanContext::default(),
                                 conn_id: None,
                                 depends_on: vec![log.id],
+                                lazy: false,
                             }),
                         ),
                     );
@@ -616,6 +861,7 @@ impl Catalog {
                             plan_cx: PlanContext::default(),
                             desc: table.desc.clone(),
                             defaults: vec![Expr::null(); table.desc.arity()],
+                            checks: vec![],
                             conn_id: None,
                             depends_on: vec![],
                         }),
@@ -627,7 +873,7 @@ impl Catalog {
                             oid,
                             FullName {
                                 database: DatabaseSpecifier::Ambient,
-                                schema: MZ_CATALOG_SCHEMA.into(),
+                                schema: table.schema.into(),
                                 item: index_name,
                             },
                             CatalogItem::Index(Index {
@@ -636,10 +882,12 @@ impl Catalog {
                                     .iter()
                                     .map(|i| MirScalarExpr::Column(*i))
                                     .collect(),
+                                predicates: vec![],
                                 create_sql: index_sql,
                                 plan_cx: PlanContext::default(),
                                 conn_id: None,
                                 depends_on: vec![table.id],
+                                lazy: false,
                             }),
                         ),
                     );
@@ -680,7 +928,9 @@ impl Catalog {
                                     TypeInner::Array { element_id }
                                 }
                                 postgres_types::Kind::Pseudo => TypeInner::Pseudo,
-                                postgres_types::Kind::Simple => TypeInner::Base,
+                                postgres_types::Kind::Simple | postgres_types::Kind::Range(_) => {
+                                    TypeInner::Base
+                                }
                                 _ => unreachable!(),
                             },
                             depends_on: vec![],
@@ -706,18 +956,26 @@ impl Catalog {
         }
 
         let mut catalog_content_version = catalog.storage().get_catalog_content_version()?;
+        if catalog_content_version > CONTENT_MIGRATIONS.len() {
+            return Err(Error::new(ErrorKind::UnsupportedVersion {
+                component: "content",
+                found: catalog_content_version,
+                supported: CONTENT_MIGRATIONS.len(),
+            }));
+        }
 
         while CONTENT_MIGRATIONS.len() > catalog_content_version {
-            if let Err(e) = CONTENT_MIGRATIONS[catalog_content_version](&mut catalog) {
+            let mut storage = catalog.storage();
+            let tx = storage.transaction()?;
+            if let Err(e) = CONTENT_MIGRATIONS[catalog_content_version](&tx) {
                 return Err(Error::new(ErrorKind::FailedMigration {
                     last_version: catalog_content_version,
                     cause: e.to_string(),
                 }));
             }
             catalog_content_version += 1;
-            catalog
-                .storage()
-                .set_catalog_content_version(catalog_content_version)?;
+            tx.set_catalog_content_version(catalog_content_version)?;
+            tx.commit()?;
         }
 
         let items = catalog.storage().load_items()?;
@@ -732,20 +990,33 @@ impl Catalog {
             }
             let item = match catalog.deserialize_item(def) {
                 Ok(item) => item,
-                Err(e) if LOGGING_ERROR.is_match(&e.to_string()) => {
-                    return Err(Error::new(ErrorKind::UnsatisfiableLoggingDependency {
-                        depender_name: name.to_string(),
-                    }));
-                }
                 Err(e) => {
-                    return Err(Error::new(ErrorKind::Corruption {
-                        detail: format!("failed to deserialize item {} ({}): {}", id, name, e),
-                    }))
+                    let e = if LOGGING_ERROR.is_match(&e.to_string()) {
+                        Error::new(ErrorKind::UnsatisfiableLoggingDependency {
+                            depender_name: name.to_string(),
+                        })
+                    } else {
+                        Error::new(ErrorKind::Corruption {
+                            detail: format!("failed to deserialize item {} ({}): {}", id, name, e),
+                        })
+                    };
+                    match &mut failures {
+                        Some(failures) => {
+                            failures.push((id, e));
+                            continue;
+                        }
+                        None => return Err(e),
+                    }
                 }
             };
             let oid = catalog.allocate_oid()?;
             events.push(catalog.insert_item(id, oid, name, item));
         }
+
+        for (id, name, reason) in catalog.storage().load_quarantined_items()? {
+            warn!("catalog item {} ({}) is quarantined: {}", id, name, reason);
+        }
+
         Ok((catalog, events))
     }
 
@@ -773,6 +1044,11 @@ impl Catalog {
             database: session.vars().database().into(),
             search_path: session.vars().search_path(),
             user: session.user().into(),
+            enabled_features: Feature::ALL
+                .iter()
+                .copied()
+                .filter(|f| session.vars().feature_enabled(*f))
+                .collect(),
         }
     }
 
@@ -783,13 +1059,33 @@ impl Catalog {
             database: "materialize".into(),
             search_path: &[],
             user,
+            enabled_features: vec![],
         }
     }
 
     // Leaving the system's search path empty allows us to catch issues
     // where catalog object names have not been normalized correctly.
+    //
+    // All features are enabled for the system session, so that objects that
+    // were created while a feature flag was enabled can be loaded regardless
+    // of the flag's current value.
     pub fn for_system_session(&self) -> ConnCatalog {
-        self.for_sessionless_user(SYSTEM_USER.into())
+        ConnCatalog {
+            enabled_features: Feature::ALL.to_vec(),
+            ..self.for_sessionless_user(SYSTEM_USER.into())
+        }
+    }
+
+    /// Returns the system defaults for configuration parameters, as set by
+    /// `ALTER SYSTEM SET`.
+    pub fn system_parameters(&self) -> &BTreeMap<String, String> {
+        &self.system_parameters
+    }
+
+    /// Returns the defaults for configuration parameters for the named role,
+    /// as set by `ALTER ROLE ... SET`, or `None` if the role does not exist.
+    pub fn role_parameters(&self, role_name: &str) -> Option<&BTreeMap<String, String>> {
+        self.roles.get(role_name).map(|role| &role.parameters)
     }
 
     fn storage(&self) -> MutexGuard<storage::Connection> {
@@ -941,6 +1237,37 @@ impl Catalog {
             .map(|id| &self.by_id[id])
     }
 
+    /// Returns the number of maintained dataflows, i.e., indexes and sinks,
+    /// that live in the named schema.
+    pub fn dataflow_count(&self, schema: &SchemaName, conn_id: u32) -> usize {
+        match self.get_schema(&schema.database, &schema.schema, conn_id) {
+            None => 0,
+            Some(schema) => schema
+                .items
+                .values()
+                .filter(|id| {
+                    matches!(
+                        self.by_id[id].item(),
+                        CatalogItem::Index(_) | CatalogItem::Sink(_)
+                    )
+                })
+                .count(),
+        }
+    }
+
+    /// Returns the IDs of the indexes that live in the named schema.
+    pub fn index_ids_in_schema<'a>(
+        &'a self,
+        schema: &SchemaName,
+        conn_id: u32,
+    ) -> impl Iterator<Item = GlobalId> + 'a {
+        self.get_schema(&schema.database, &schema.schema, conn_id)
+            .into_iter()
+            .flat_map(|schema| schema.items.values())
+            .filter(move |id| matches!(self.by_id[id].item(), CatalogItem::Index(_)))
+            .copied()
+    }
+
     pub fn try_get_by_id(&self, id: GlobalId) -> Option<&CatalogEntry> {
         self.by_id.get(&id)
     }
@@ -1067,16 +1394,35 @@ impl Catalog {
         }
 
         match entry.item() {
-            CatalogItem::Table(_) | CatalogItem::Source(_) | CatalogItem::View(_) => {
+            CatalogItem::Table(_) => {
                 self.indexes.insert(id, vec![]);
             }
-            CatalogItem::Index(index) => {
+            CatalogItem::Source(Source { foreign_keys, .. })
+            | CatalogItem::View(View { foreign_keys, .. }) => {
+                self.indexes.insert(id, vec![]);
+                if !foreign_keys.is_empty() {
+                    self.foreign_keys.insert(id, foreign_keys.clone());
+                }
+            }
+            CatalogItem::Index(index) if index.predicates.is_empty() => {
                 self.indexes
                     .get_mut(&index.on)
                     .unwrap()
                     .push((id, index.keys.clone()));
             }
-            CatalogItem::Func(_) | CatalogItem::Sink(_) | CatalogItem::Type(_) => (),
+            CatalogItem::Index(index) => {
+                // Partial indexes do not contain every row of the indexed
+                // item, so they are kept apart from `self.indexes` to ensure
+                // that they are never used in its stead.
+                self.partial_indexes
+                    .entry(index.on)
+                    .or_insert_with(Vec::new)
+                    .push(id);
+            }
+            CatalogItem::Func(_)
+            | CatalogItem::Sink(_)
+            | CatalogItem::Task(_)
+            | CatalogItem::Type(_) => (),
         }
 
         let conn_id = entry.item().conn_id().unwrap_or(SYSTEM_CONN_ID);
@@ -1242,6 +1588,16 @@ impl Catalog {
                 name: String,
             },
             DropItem(GlobalId),
+            AlterSystemSet {
+                name: String,
+                value: Option<String>,
+            },
+            AlterSystemResetAll,
+            AlterRoleSet {
+                role_name: String,
+                name: String,
+                value: Option<String>,
+            },
             UpdateItem {
                 id: GlobalId,
                 from_name: Option<FullName>,
@@ -1387,6 +1743,30 @@ impl Catalog {
                     tx.remove_role(&name)?;
                     vec![Action::DropRole { name }]
                 }
+                Op::AlterSystemSet { name, value } => {
+                    tx.set_system_parameter(&name, value.as_deref())?;
+                    vec![Action::AlterSystemSet { name, value }]
+                }
+                Op::AlterSystemResetAll => {
+                    tx.clear_system_parameters()?;
+                    vec![Action::AlterSystemResetAll]
+                }
+                Op::AlterRoleSet {
+                    role_name,
+                    name,
+                    value,
+                } => {
+                    let role_id = match self.roles.get(&role_name) {
+                        Some(role) => role.id,
+                        None => return Err(SqlCatalogError::UnknownRole(role_name).into()),
+                    };
+                    tx.set_role_parameter(role_id, &name, value.as_deref())?;
+                    vec![Action::AlterRoleSet {
+                        role_name,
+                        name,
+                        value,
+                    }]
+                }
                 Op::DropItem(id) => {
                     let entry = self.get_by_id(&id);
                     // Prevent dropping a table's default index unless the table
@@ -1470,6 +1850,19 @@ impl Catalog {
                     });
                     actions
                 }
+                Op::UpdateItem { id, item } => {
+                    let entry = self.by_id.get(&id).unwrap();
+                    if !item.is_temporary() {
+                        let serialized_item = self.serialize_item(&item);
+                        tx.update_item(id, &entry.name.item, &serialized_item)?;
+                    }
+                    vec![Action::UpdateItem {
+                        id,
+                        from_name: None,
+                        to_name: entry.name.clone(),
+                        item,
+                    }]
+                }
             });
         }
         tx.commit()?;
@@ -1529,6 +1922,7 @@ impl Catalog {
                             name: name.clone(),
                             id,
                             oid,
+                            parameters: BTreeMap::new(),
                         },
                     );
                     Event::CreatedRole { name, id, oid }
@@ -1578,6 +1972,35 @@ impl Catalog {
                     None => Event::NoOp,
                 },
 
+                Action::AlterSystemSet { name, value } => {
+                    info!("alter system set {} = {:?}", name, value);
+                    match value {
+                        Some(value) => self.system_parameters.insert(name, value),
+                        None => self.system_parameters.remove(&name),
+                    };
+                    Event::NoOp
+                }
+
+                Action::AlterSystemResetAll => {
+                    info!("alter system reset all");
+                    self.system_parameters.clear();
+                    Event::NoOp
+                }
+
+                Action::AlterRoleSet {
+                    role_name,
+                    name,
+                    value,
+                } => {
+                    info!("alter role {} set {} = {:?}", role_name, name, value);
+                    let role = self.roles.get_mut(&role_name).unwrap();
+                    match value {
+                        Some(value) => role.parameters.insert(name, value),
+                        None => role.parameters.remove(&name),
+                    };
+                    Event::NoOp
+                }
+
                 Action::DropItem(id) => {
                     let metadata = self.by_id.remove(&id).unwrap();
                     if !metadata.item.is_placeholder() {
@@ -1599,15 +2022,23 @@ impl Catalog {
                         .remove(&metadata.name.item)
                         .expect("catalog out of sync");
                     if let CatalogItem::Index(index) = &metadata.item {
-                        let indexes = self
-                            .indexes
-                            .get_mut(&index.on)
-                            .expect("catalog out of sync");
-                        let i = indexes
-                            .iter()
-                            .position(|(idx_id, _keys)| *idx_id == id)
-                            .expect("catalog out of sync");
-                        indexes.remove(i);
+                        if index.predicates.is_empty() {
+                            let indexes = self
+                                .indexes
+                                .get_mut(&index.on)
+                                .expect("catalog out of sync");
+                            let i = indexes
+                                .iter()
+                                .position(|(idx_id, _keys)| *idx_id == id)
+                                .expect("catalog out of sync");
+                            indexes.remove(i);
+                        } else {
+                            let indexes = self
+                                .partial_indexes
+                                .get_mut(&index.on)
+                                .expect("catalog out of sync");
+                            indexes.retain(|idx_id| *idx_id != id);
+                        }
                         let nullable: Vec<bool> = index
                             .keys
                             .iter()
@@ -1622,6 +2053,8 @@ impl Catalog {
                         }
                     } else {
                         self.indexes.remove(&id);
+                        self.partial_indexes.remove(&id);
+                        self.foreign_keys.remove(&id);
                         Event::DroppedItem {
                             schema_id,
                             entry: metadata,
@@ -1659,7 +2092,7 @@ impl Catalog {
                             to_name,
                             item,
                         },
-                        None => Event::NoOp, // If name didn't change, don't update system tables.
+                        None => Event::UpdatedItemDefinition { id },
                     }
                 }
             })
@@ -1671,26 +2104,37 @@ impl Catalog {
             CatalogItem::Table(table) => SerializedCatalogItem::V1 {
                 create_sql: table.create_sql.clone(),
                 eval_env: Some(table.plan_cx.clone().into()),
+                lazy_index: false,
             },
             CatalogItem::Source(source) => SerializedCatalogItem::V1 {
                 create_sql: source.create_sql.clone(),
                 eval_env: Some(source.plan_cx.clone().into()),
+                lazy_index: false,
             },
             CatalogItem::View(view) => SerializedCatalogItem::V1 {
                 create_sql: view.create_sql.clone(),
                 eval_env: Some(view.plan_cx.clone().into()),
+                lazy_index: false,
             },
             CatalogItem::Index(index) => SerializedCatalogItem::V1 {
                 create_sql: index.create_sql.clone(),
                 eval_env: Some(index.plan_cx.clone().into()),
+                lazy_index: index.lazy,
             },
             CatalogItem::Sink(sink) => SerializedCatalogItem::V1 {
                 create_sql: sink.create_sql.clone(),
                 eval_env: Some(sink.plan_cx.clone().into()),
+                lazy_index: false,
             },
             CatalogItem::Type(typ) => SerializedCatalogItem::V1 {
                 create_sql: typ.create_sql.clone(),
                 eval_env: Some(typ.plan_cx.clone().into()),
+                lazy_index: false,
+            },
+            CatalogItem::Task(task) => SerializedCatalogItem::V1 {
+                create_sql: task.create_sql.clone(),
+                eval_env: Some(task.plan_cx.clone().into()),
+                lazy_index: false,
             },
             CatalogItem::Func(_) => unreachable!("cannot serialize functions yet"),
         };
@@ -1701,6 +2145,7 @@ impl Catalog {
         let SerializedCatalogItem::V1 {
             create_sql,
             eval_env,
+            lazy_index,
         } = serde_json::from_slice(&bytes)?;
         let pcx = match eval_env {
             // Old sources and sinks don't have plan contexts, but it's safe to
@@ -1709,7 +2154,11 @@ impl Catalog {
             None => PlanContext::default(),
             Some(eval_env) => eval_env.into(),
         };
-        self.parse_item(create_sql, pcx)
+        let mut item = self.parse_item(create_sql, pcx)?;
+        if let CatalogItem::Index(index) = &mut item {
+            index.lazy = lazy_index;
+        }
+        Ok(item)
     }
 
     fn parse_item(
@@ -1718,7 +2167,13 @@ impl Catalog {
         pcx: PlanContext,
     ) -> Result<CatalogItem, anyhow::Error> {
         let stmt = sql::parse::parse(&create_sql)?.into_element();
-        let plan = sql::plan::plan(&pcx, &self.for_system_session(), stmt, &Params::empty())?;
+        let (plan, _) = sql::plan::plan(&pcx, &self.for_system_session(), stmt, &Params::empty())?;
+        self.item_from_plan(plan, pcx)
+    }
+
+    /// Builds the catalog item described by `plan`, which must be the plan for
+    /// a `CREATE` statement for an item.
+    fn item_from_plan(&self, plan: Plan, pcx: PlanContext) -> Result<CatalogItem, anyhow::Error> {
         Ok(match plan {
             Plan::CreateTable {
                 table, depends_on, ..
@@ -1727,12 +2182,16 @@ impl Catalog {
                 plan_cx: pcx,
                 desc: table.desc,
                 defaults: table.defaults,
+                checks: table.checks,
                 conn_id: None,
                 depends_on,
             }),
-            Plan::CreateSource { source, .. } => {
+            Plan::CreateSource {
+                source, depends_on, ..
+            } => {
                 let mut optimizer = Optimizer::default();
-                let optimized_expr = optimizer.optimize(source.expr, self.indexes())?;
+                let optimized_expr =
+                    optimizer.optimize(source.expr, self.indexes(), self.foreign_keys())?;
                 let transformed_desc =
                     RelationDesc::new(optimized_expr.as_ref().typ(), source.column_names);
                 CatalogItem::Source(Source {
@@ -1742,13 +2201,16 @@ impl Catalog {
                     connector: source.connector,
                     bare_desc: source.bare_desc,
                     desc: transformed_desc,
+                    foreign_keys: source.foreign_keys,
+                    depends_on,
                 })
             }
             Plan::CreateView {
                 view, depends_on, ..
             } => {
-                let mut optimizer = Optimizer::default();
-                let optimized_expr = optimizer.optimize(view.expr, self.indexes())?;
+                let mut optimizer = Optimizer::new(view.prefer_delta_joins);
+                let optimized_expr =
+                    optimizer.optimize(view.expr, self.indexes(), self.foreign_keys())?;
                 let desc = RelationDesc::new(optimized_expr.as_ref().typ(), view.column_names);
                 CatalogItem::View(View {
                     create_sql: view.create_sql,
@@ -1757,6 +2219,9 @@ impl Catalog {
                     desc,
                     conn_id: None,
                     depends_on,
+                    lazy: view.lazy,
+                    reduce_hot_set_size: view.reduce_hot_set_size,
+                    foreign_keys: view.foreign_keys,
                 })
             }
             Plan::CreateIndex {
@@ -1766,8 +2231,10 @@ impl Catalog {
                 plan_cx: pcx,
                 on: index.on,
                 keys: index.keys,
+                predicates: index.predicates,
                 conn_id: None,
                 depends_on,
+                lazy: false,
             }),
             Plan::CreateSink {
                 sink,
@@ -1791,6 +2258,16 @@ impl Catalog {
                 inner: typ.inner.into(),
                 depends_on,
             }),
+            Plan::CreateTask {
+                task, depends_on, ..
+            } => CatalogItem::Task(Task {
+                create_sql: task.create_sql,
+                plan_cx: pcx,
+                target: task.target,
+                query: task.query,
+                schedule: task.schedule,
+                depends_on,
+            }),
             _ => bail!("catalog entry generated inappropriate plan"),
         })
     }
@@ -1801,6 +2278,24 @@ impl Catalog {
         &self.indexes
     }
 
+    /// Returns a mapping from each source and view to the foreign keys declared
+    /// on it. Items without any declared foreign keys are omitted.
+    pub fn foreign_keys(&self) -> &HashMap<GlobalId, Vec<ForeignKey>> {
+        &self.foreign_keys
+    }
+
+    /// Returns the partial indexes on the item with the specified `id`.
+    pub fn partial_indexes(&self, id: GlobalId) -> impl Iterator<Item = (GlobalId, &Index)> {
+        self.partial_indexes
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(move |idx_id| match self.get_by_id(idx_id).item() {
+                CatalogItem::Index(index) => (*idx_id, index),
+                _ => unreachable!("partial index {} is not an index", idx_id),
+            })
+    }
+
     /// Returns the default index for the specified `id`.
     ///
     /// Panics if `id` does not exist, or if `id` is not an object on which
@@ -1873,17 +2368,29 @@ impl Catalog {
             | CatalogItem::Func(_)
             | CatalogItem::Index(_)
             | CatalogItem::Sink(_)
+            | CatalogItem::Task(_)
             | CatalogItem::Type(_) => false,
         }
     }
 
-    /// Serializes the catalog's in-memory state.
+    /// Serializes the databases, schemas, and objects in the catalog as JSON.
     ///
-    /// There are no guarantees about the format of the serialized state, except
-    /// that the serialized state for two identical catalogs will compare
-    /// identically.
+    /// The format is described by [`CatalogDump`] and is versioned by
+    /// [`dump::CATALOG_DUMP_VERSION`]. Apart from object OIDs, which are
+    /// reassigned when the catalog is reopened, the serialized state for two
+    /// identical catalogs will compare identically.
     pub fn dump(&self) -> String {
-        serde_json::to_string(&self.by_name).expect("serialization cannot fail")
+        serde_json::to_string(&CatalogDump::new(self)).expect("serialization cannot fail")
+    }
+
+    /// Describes the object with the specified ID as JSON, in the same format
+    /// as the objects in [`Catalog::dump`].
+    ///
+    /// Returns `None` if the object does not belong to one of the catalog's
+    /// databases.
+    pub fn object_json(&self, id: GlobalId) -> Option<serde_json::Value> {
+        let dump = ObjectDump::new(self.try_get_by_id(id)?)?;
+        Some(serde_json::to_value(dump).expect("serialization cannot fail"))
     }
 
     pub fn config(&self) -> &sql::catalog::CatalogConfig {
@@ -1934,6 +2441,27 @@ pub enum Op {
         id: GlobalId,
         to_name: String,
     },
+    /// Replaces the definition of the identified item in place, keeping its
+    /// name and dependencies.
+    UpdateItem {
+        id: GlobalId,
+        item: CatalogItem,
+    },
+    /// Sets the system default for a configuration parameter, or removes it
+    /// if `value` is `None`.
+    AlterSystemSet {
+        name: String,
+        value: Option<String>,
+    },
+    /// Removes the system defaults for all configuration parameters.
+    AlterSystemResetAll,
+    /// Sets a role's default for a configuration parameter, or removes it if
+    /// `value` is `None`.
+    AlterRoleSet {
+        role_name: String,
+        name: String,
+        value: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -1993,6 +2521,11 @@ pub enum Event {
         to_name: FullName,
         item: CatalogItem,
     },
+    /// The definition of an item changed, but its name did not, e.g. because
+    /// an item that it references was renamed.
+    UpdatedItemDefinition {
+        id: GlobalId,
+    },
     NoOp,
 }
 
@@ -2002,6 +2535,9 @@ enum SerializedCatalogItem {
         create_sql: String,
         // The name "eval_env" is historical.
         eval_env: Option<SerializedPlanContext>,
+        /// Whether the item is an index that materializes a lazy view.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        lazy_index: bool,
     },
 }
 
@@ -2109,10 +2645,15 @@ impl ExprHumanizer for ConnCatalog<'_> {
                     // qualified object name to refer to type.
                     self.get_item_by_oid(&pgrepr_type.oid()).name().to_string()
                 };
-                if let ScalarType::Decimal(p, s) = typ {
-                    format!("{}({},{})", res, p, s)
-                } else {
-                    res
+                match typ {
+                    ScalarType::Decimal(p, s) => format!("{}({},{})", res, p, s),
+                    ScalarType::Char {
+                        length: Some(length),
+                    }
+                    | ScalarType::VarChar {
+                        length: Some(length),
+                    } => format!("{}({})", res, length),
+                    _ => res,
                 }
             }
         }
@@ -2281,6 +2822,10 @@ impl SqlCatalog for ConnCatalog<'_> {
     fn config(&self) -> &sql::catalog::CatalogConfig {
         &self.catalog.config
     }
+
+    fn feature_enabled(&self, feature: Feature) -> bool {
+        self.enabled_features.contains(&feature)
+    }
 }
 
 impl sql::catalog::CatalogDatabase for Database {
@@ -2342,6 +2887,7 @@ impl sql::catalog::CatalogItem for CatalogEntry {
             CatalogItem::View(View { create_sql, .. }) => create_sql,
             CatalogItem::Index(Index { create_sql, .. }) => create_sql,
             CatalogItem::Type(Type { create_sql, .. }) => create_sql,
+            CatalogItem::Task(Task { create_sql, .. }) => create_sql,
             CatalogItem::Func(_) => "TODO",
         }
     }
@@ -2354,6 +2900,7 @@ impl sql::catalog::CatalogItem for CatalogEntry {
             CatalogItem::View(View { plan_cx, .. }) => plan_cx,
             CatalogItem::Index(Index { plan_cx, .. }) => plan_cx,
             CatalogItem::Type(Type { plan_cx, .. }) => plan_cx,
+            CatalogItem::Task(Task { plan_cx, .. }) => plan_cx,
             CatalogItem::Func(Func { plan_cx, .. }) => plan_cx,
         }
     }
@@ -2389,11 +2936,13 @@ impl sql::catalog::CatalogItem for CatalogEntry {
 
 #[cfg(test)]
 mod tests {
+    use rusqlite::params;
     use tempfile::NamedTempFile;
 
+    use build_info::DUMMY_BUILD_INFO;
     use sql::names::{DatabaseSpecifier, FullName, PartialName};
 
-    use crate::catalog::{Catalog, MZ_CATALOG_SCHEMA, PG_CATALOG_SCHEMA};
+    use crate::catalog::{Catalog, Config, MZ_CATALOG_SCHEMA, PG_CATALOG_SCHEMA};
     use crate::session::Session;
 
     /// System sessions have an empty `search_path` so it's necessary to
@@ -2465,4 +3014,124 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_downgrade() -> Result<(), anyhow::Error> {
+        for sql in &[
+            "PRAGMA user_version = 1000",
+            "UPDATE settings SET value = 1000 WHERE name = 'catalog_content_version'",
+        ] {
+            let catalog_file = NamedTempFile::new()?;
+            Catalog::open_debug(catalog_file.path())?;
+            rusqlite::Connection::open(catalog_file.path())?.execute_batch(sql)?;
+            let err = Catalog::open_debug(catalog_file.path()).unwrap_err();
+            assert!(
+                err.to_string().contains("downgrades are not supported"),
+                "unexpected error: {}",
+                err
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_upgrade() -> Result<(), anyhow::Error> {
+        let catalog_file = NamedTempFile::new()?;
+        let config = Config {
+            path: catalog_file.path(),
+            experimental_mode: None,
+            enable_logging: true,
+            cache_directory: None,
+            build_info: &DUMMY_BUILD_INFO,
+        };
+        Catalog::open_debug(catalog_file.path())?;
+        assert!(Catalog::check_upgrade(&config)?.is_empty());
+
+        // Store a view that no longer plans.
+        rusqlite::Connection::open(catalog_file.path())?.execute(
+            "INSERT INTO items (gid, schema_id, name, definition) VALUES (
+                CAST('{\"User\":1}' AS blob),
+                (SELECT id FROM schemas WHERE name = 'public'),
+                'v',
+                CAST(? AS blob)
+            )",
+            params![r#"{"V1":{"create_sql":"CREATE VIEW materialize.public.v AS SELECT * FROM materialize.public.missing","eval_env":null}}"#],
+        )?;
+        let failures = Catalog::check_upgrade(&config)?;
+        assert_eq!(failures.len(), 1);
+        assert!(
+            failures[0].to_string().contains("materialize.public.v"),
+            "unexpected error: {}",
+            failures[0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fsck() -> Result<(), anyhow::Error> {
+        let catalog_file = NamedTempFile::new()?;
+        let config = Config {
+            path: catalog_file.path(),
+            experimental_mode: None,
+            enable_logging: true,
+            cache_directory: None,
+            build_info: &DUMMY_BUILD_INFO,
+        };
+        Catalog::open_debug(catalog_file.path())?;
+        assert!(Catalog::fsck(&config, false)?.is_empty());
+
+        // Store a view that no longer plans, a view that depends on it, an
+        // item whose schema is missing, and a schema whose database is
+        // missing.
+        let conn = rusqlite::Connection::open(catalog_file.path())?;
+        for (id, schema, name, sql) in &[
+            (1, "public", "v", "SELECT * FROM materialize.public.missing"),
+            (2, "public", "w", "SELECT * FROM materialize.public.v"),
+            (3, "orphan", "x", "SELECT 1"),
+        ] {
+            conn.execute(
+                "INSERT INTO items (gid, schema_id, name, definition) VALUES (
+                    CAST(? AS blob),
+                    coalesce((SELECT id FROM schemas WHERE name = ?), 42),
+                    ?,
+                    CAST(? AS blob)
+                )",
+                params![
+                    format!(r#"{{"User":{}}}"#, id),
+                    schema,
+                    name,
+                    format!(
+                        r#"{{"V1":{{"create_sql":"CREATE VIEW materialize.public.{} AS {}","eval_env":null}}}}"#,
+                        name, sql
+                    )
+                ],
+            )?;
+        }
+        conn.execute(
+            "INSERT INTO schemas (database_id, name) VALUES (42, 'orphan_db')",
+            params![],
+        )?;
+
+        // Checking reports every problem without modifying the catalog.
+        let issues = Catalog::fsck(&config, false)?;
+        let issues: Vec<_> = issues.iter().map(|i| i.to_string()).collect();
+        assert_eq!(issues.len(), 4, "unexpected issues: {:?}", issues);
+        assert!(issues[0].contains("orphan_db"), "{}", issues[0]);
+        assert!(issues[1].contains("item x (u3)"), "{}", issues[1]);
+        assert!(issues[2].contains("materialize.public.v"), "{}", issues[2]);
+        assert!(issues[3].contains("materialize.public.w"), "{}", issues[3]);
+        assert_eq!(Catalog::fsck(&config, false)?.len(), 4);
+
+        // Repairing quarantines the broken items, after which the catalog
+        // opens cleanly.
+        assert_eq!(Catalog::fsck(&config, true)?.len(), 4);
+        assert!(Catalog::fsck(&config, false)?.is_empty());
+        Catalog::open_debug(catalog_file.path())?;
+        let quarantined = conn
+            .prepare("SELECT name FROM quarantined_items ORDER BY name")?
+            .query_map(params![], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        assert_eq!(quarantined, vec!["v", "w", "x"]);
+        Ok(())
+    }
 }
@@ -416,6 +416,102 @@ pub const TYPE_INT2_ARRAY: BuiltinType = BuiltinType {
     pgtype: &postgres_types::Type::INT2_ARRAY,
 };
 
+pub const TYPE_CHAR_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1042),
+    pgtype: &postgres_types::Type::CHAR_ARRAY,
+};
+
+pub const TYPE_VARCHAR_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1043),
+    pgtype: &postgres_types::Type::VARCHAR_ARRAY,
+};
+
+pub const TYPE_INT4_RANGE: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1044),
+    pgtype: &postgres_types::Type::INT4_RANGE,
+};
+
+pub const TYPE_INT4_RANGE_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1045),
+    pgtype: &postgres_types::Type::INT4_RANGE_ARRAY,
+};
+
+pub const TYPE_TSTZ_RANGE: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1046),
+    pgtype: &postgres_types::Type::TSTZ_RANGE,
+};
+
+pub const TYPE_TSTZ_RANGE_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1047),
+    pgtype: &postgres_types::Type::TSTZ_RANGE_ARRAY,
+};
+
+pub const TYPE_INET: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1048),
+    pgtype: &postgres_types::Type::INET,
+};
+
+pub const TYPE_INET_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1049),
+    pgtype: &postgres_types::Type::INET_ARRAY,
+};
+
+pub const TYPE_CIDR: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1050),
+    pgtype: &postgres_types::Type::CIDR,
+};
+
+pub const TYPE_CIDR_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1051),
+    pgtype: &postgres_types::Type::CIDR_ARRAY,
+};
+
+pub const TYPE_TS_VECTOR: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1054),
+    pgtype: &postgres_types::Type::TS_VECTOR,
+};
+
+pub const TYPE_TS_VECTOR_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1055),
+    pgtype: &postgres_types::Type::TS_VECTOR_ARRAY,
+};
+
+pub const TYPE_TSQUERY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1056),
+    pgtype: &postgres_types::Type::TSQUERY,
+};
+
+pub const TYPE_TSQUERY_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1057),
+    pgtype: &postgres_types::Type::TSQUERY_ARRAY,
+};
+
+pub const TYPE_BPCHAR: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1058),
+    pgtype: &postgres_types::Type::BPCHAR,
+};
+
+pub const TYPE_BPCHAR_ARRAY: BuiltinType = BuiltinType {
+    schema: PG_CATALOG_SCHEMA,
+    id: GlobalId::System(1059),
+    pgtype: &postgres_types::Type::BPCHAR_ARRAY,
+};
+
 lazy_static! {
     pub static ref TYPE_LIST: BuiltinType = BuiltinType {
         schema: PG_CATALOG_SCHEMA,
@@ -427,6 +523,16 @@ lazy_static! {
         id: GlobalId::System(1999),
         pgtype: &pgrepr::MAP,
     };
+    pub static ref TYPE_GEOMETRY: BuiltinType = BuiltinType {
+        schema: PG_CATALOG_SCHEMA,
+        id: GlobalId::System(1052),
+        pgtype: &pgrepr::GEOMETRY,
+    };
+    pub static ref TYPE_GEOGRAPHY: BuiltinType = BuiltinType {
+        schema: PG_CATALOG_SCHEMA,
+        id: GlobalId::System(1053),
+        pgtype: &pgrepr::GEOGRAPHY,
+    };
 }
 
 pub const MZ_DATAFLOW_OPERATORS: BuiltinLog = BuiltinLog {
@@ -541,6 +647,30 @@ pub const MZ_SOURCE_INFO: BuiltinLog = BuiltinLog {
     index_id: GlobalId::System(3027),
 };
 
+pub const MZ_SOURCE_STATISTICS: BuiltinLog = BuiltinLog {
+    name: "mz_source_statistics",
+    schema: MZ_CATALOG_SCHEMA,
+    variant: LogVariant::Materialized(MaterializedLog::SourceStatistics),
+    id: GlobalId::System(3028),
+    index_id: GlobalId::System(3029),
+};
+
+pub const MZ_KAFKA_SOURCE_STATUS: BuiltinLog = BuiltinLog {
+    name: "mz_kafka_source_status",
+    schema: MZ_CATALOG_SCHEMA,
+    variant: LogVariant::Materialized(MaterializedLog::KafkaSourceStatus),
+    id: GlobalId::System(3030),
+    index_id: GlobalId::System(3031),
+};
+
+pub const MZ_SOURCE_SNAPSHOT_PROGRESS: BuiltinLog = BuiltinLog {
+    name: "mz_source_snapshot_progress",
+    schema: MZ_CATALOG_SCHEMA,
+    variant: LogVariant::Materialized(MaterializedLog::SourceSnapshotProgress),
+    id: GlobalId::System(3032),
+    index_id: GlobalId::System(3033),
+};
+
 lazy_static! {
     pub static ref MZ_VIEW_KEYS: BuiltinTable = BuiltinTable {
         name: "mz_view_keys",
@@ -614,7 +744,9 @@ lazy_static! {
             .with_column("name", ScalarType::String.nullable(false))
             .with_column("position", ScalarType::Int64.nullable(false))
             .with_column("nullable", ScalarType::Bool.nullable(false))
-            .with_column("type", ScalarType::String.nullable(false)),
+            .with_column("type", ScalarType::String.nullable(false))
+            .with_column("type_oid", ScalarType::Oid.nullable(false))
+            .with_column("type_mod", ScalarType::Int32.nullable(false)),
         id: GlobalId::System(4013),
         index_id: GlobalId::System(4014),
     };
@@ -759,10 +891,141 @@ lazy_static! {
             .with_column("schema_id", ScalarType::Int64.nullable(false))
             .with_column("name", ScalarType::String.nullable(false))
             .with_column("arg_ids", ScalarType::Array(Box::new(ScalarType::String)).nullable(false))
-            .with_column("variadic_id", ScalarType::String.nullable(true)),
+            .with_column("variadic_id", ScalarType::String.nullable(true))
+            .with_column("arg_oids", ScalarType::Array(Box::new(ScalarType::Oid)).nullable(false))
+            .with_column("kind", ScalarType::String.nullable(false)),
             id: GlobalId::System(4041),
             index_id: GlobalId::System(4042),
     };
+    pub static ref MZ_INDEX_COMPACTION_WINDOWS: BuiltinTable = BuiltinTable {
+        name: "mz_index_compaction_windows",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("index_id", ScalarType::String.nullable(false))
+            .with_column("window_ms", ScalarType::Int64.nullable(true))
+            .with_column("auto_tuned", ScalarType::Bool.nullable(false))
+            .with_key(vec![0]),
+        id: GlobalId::System(4043),
+        index_id: GlobalId::System(4044),
+    };
+    pub static ref MZ_STATEMENT_STATISTICS: BuiltinTable = BuiltinTable {
+        name: "mz_statement_statistics",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("fingerprint", ScalarType::String.nullable(false))
+            .with_column("executions", ScalarType::Int64.nullable(false))
+            .with_column("errors", ScalarType::Int64.nullable(false))
+            .with_column("rows_returned", ScalarType::Int64.nullable(false))
+            .with_column("total_duration_ns", ScalarType::Int64.nullable(false))
+            .with_key(vec![0]),
+        id: GlobalId::System(4045),
+        index_id: GlobalId::System(4046),
+    };
+    pub static ref MZ_STATEMENT_DURATIONS: BuiltinTable = BuiltinTable {
+        name: "mz_statement_durations",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("fingerprint", ScalarType::String.nullable(false))
+            .with_column("duration_ns", ScalarType::Int64.nullable(false))
+            .with_column("count", ScalarType::Int64.nullable(false))
+            .with_key(vec![0, 1]),
+        id: GlobalId::System(4047),
+        index_id: GlobalId::System(4048),
+    };
+    pub static ref MZ_STALLED_DATAFLOWS: BuiltinTable = BuiltinTable {
+        name: "mz_stalled_dataflows",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("index_id", ScalarType::String.nullable(false))
+            .with_column("upper", ScalarType::Int64.nullable(false))
+            .with_column("stalled_since", ScalarType::Int64.nullable(false))
+            .with_key(vec![0]),
+        id: GlobalId::System(4049),
+        index_id: GlobalId::System(4050),
+    };
+    pub static ref MZ_TASKS: BuiltinTable = BuiltinTable {
+        name: "mz_tasks",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("id", ScalarType::String.nullable(false))
+            .with_column("oid", ScalarType::Oid.nullable(false))
+            .with_column("schema_id", ScalarType::Int64.nullable(false))
+            .with_column("name", ScalarType::String.nullable(false))
+            .with_column("target_id", ScalarType::String.nullable(false))
+            .with_column("schedule_ms", ScalarType::Int64.nullable(false)),
+        id: GlobalId::System(4051),
+        index_id: GlobalId::System(4052),
+    };
+    pub static ref MZ_TASK_STATUS: BuiltinTable = BuiltinTable {
+        name: "mz_task_status",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("task_id", ScalarType::String.nullable(false))
+            .with_column("last_run", ScalarType::Int64.nullable(false))
+            .with_column("succeeded", ScalarType::Bool.nullable(false))
+            .with_column("rows_inserted", ScalarType::Int64.nullable(true))
+            .with_column("error", ScalarType::String.nullable(true))
+            .with_key(vec![0]),
+        id: GlobalId::System(4053),
+        index_id: GlobalId::System(4054),
+    };
+    pub static ref MZ_SOURCE_TIMESTAMPING: BuiltinTable = BuiltinTable {
+        name: "mz_source_timestamping",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("source_id", ScalarType::String.nullable(false))
+            .with_column("consistency", ScalarType::String.nullable(false))
+            .with_column("consistency_topic", ScalarType::String.nullable(true))
+            .with_column("timestamp_frequency_ms", ScalarType::Int64.nullable(false))
+            .with_key(vec![0]),
+        id: GlobalId::System(4055),
+        index_id: GlobalId::System(4056),
+    };
+    pub static ref MZ_OBJECT_JSON: BuiltinTable = BuiltinTable {
+        name: "mz_object_json",
+        schema: MZ_INTERNAL_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("id", ScalarType::String.nullable(false))
+            .with_column("oid", ScalarType::Oid.nullable(false))
+            .with_column("json", ScalarType::Jsonb.nullable(false))
+            .with_key(vec![0]),
+        id: GlobalId::System(4057),
+        index_id: GlobalId::System(4058),
+    };
+    pub static ref MZ_VIEW_FRESHNESS: BuiltinTable = BuiltinTable {
+        name: "mz_view_freshness",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("index_id", ScalarType::String.nullable(false))
+            .with_column("samples", ScalarType::Int64.nullable(false))
+            .with_column("p50_ms", ScalarType::Int64.nullable(true))
+            .with_column("p90_ms", ScalarType::Int64.nullable(true))
+            .with_column("p99_ms", ScalarType::Int64.nullable(true))
+            .with_column("max_ms", ScalarType::Int64.nullable(true))
+            .with_column("pending_ms", ScalarType::Int64.nullable(true))
+            .with_column("slo_breached", ScalarType::Bool.nullable(true))
+            .with_key(vec![0]),
+        id: GlobalId::System(4059),
+        index_id: GlobalId::System(4060),
+    };
+    pub static ref MZ_STATEMENT_HISTORY: BuiltinTable = BuiltinTable {
+        name: "mz_statement_history",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("id", ScalarType::Int64.nullable(false))
+            .with_column("conn_id", ScalarType::Int32.nullable(false))
+            .with_column("user_name", ScalarType::String.nullable(false))
+            .with_column("sql", ScalarType::String.nullable(false))
+            .with_column("tag", ScalarType::String.nullable(true))
+            .with_column("started_at", ScalarType::Int64.nullable(false))
+            .with_column("status", ScalarType::String.nullable(false))
+            .with_column("duration_ns", ScalarType::Int64.nullable(true))
+            .with_column("rows_returned", ScalarType::Int64.nullable(true))
+            .with_column("error", ScalarType::String.nullable(true))
+            .with_key(vec![0]),
+        id: GlobalId::System(4061),
+        index_id: GlobalId::System(4062),
+    };
 }
 
 pub const MZ_RELATIONS: BuiltinView = BuiltinView {
@@ -783,6 +1046,8 @@ pub const MZ_OBJECTS: BuiltinView = BuiltinView {
     SELECT id, oid, schema_id, name, type FROM mz_catalog.mz_relations
 UNION
     SELECT id, oid, schema_id, name, 'sink' FROM mz_catalog.mz_sinks
+UNION
+    SELECT id, oid, schema_id, name, 'task' FROM mz_catalog.mz_tasks
 UNION
     SELECT mz_indexes.id, mz_indexes.oid, schema_id, mz_indexes.name, 'index'
     FROM mz_catalog.mz_indexes
@@ -943,6 +1208,63 @@ GROUP BY
     needs_logs: true,
 };
 
+// Dataflows are named after the index or sink that they export, so this view
+// recovers the catalog object for each dataflow by matching on that name.
+pub const MZ_DATAFLOW_OBJECTS: BuiltinView = BuiltinView {
+    name: "mz_dataflow_objects",
+    schema: MZ_CATALOG_SCHEMA,
+    sql: "CREATE VIEW mz_dataflow_objects AS SELECT
+    mz_dataflow_names.id,
+    mz_dataflow_names.worker,
+    mz_dataflow_names.local_id,
+    mz_catalog_names.global_id,
+    mz_catalog_names.name
+FROM
+    mz_catalog.mz_dataflow_names,
+    mz_catalog.mz_catalog_names
+WHERE
+    mz_dataflow_names.name = 'Dataflow: ' || mz_catalog_names.name",
+    id: GlobalId::System(5025),
+    needs_logs: true,
+};
+
+// The scheduling time of an operator includes the time spent in any operators
+// nested within it, so only the immediate children of each dataflow are
+// counted, to avoid counting the same time more than once.
+pub const MZ_DATAFLOW_CPU_USAGE: BuiltinView = BuiltinView {
+    name: "mz_dataflow_cpu_usage",
+    schema: MZ_CATALOG_SCHEMA,
+    sql: "CREATE VIEW mz_dataflow_cpu_usage AS SELECT
+    mz_dataflow_objects.global_id,
+    mz_dataflow_objects.name,
+    mz_dataflow_objects.worker,
+    pg_catalog.SUM(mz_scheduling_elapsed.elapsed_ns) AS elapsed_ns
+FROM
+    mz_catalog.mz_scheduling_elapsed,
+    mz_catalog.mz_dataflow_operator_addresses,
+    mz_catalog.mz_dataflow_objects,
+    (
+        SELECT id, worker
+        FROM mz_catalog.mz_dataflow_operator_addresses
+        GROUP BY id, worker
+        HAVING pg_catalog.count(*) = 2
+    ) AS dataflow_children
+WHERE
+    mz_scheduling_elapsed.id = dataflow_children.id AND
+    mz_scheduling_elapsed.worker = dataflow_children.worker AND
+    mz_scheduling_elapsed.id = mz_dataflow_operator_addresses.id AND
+    mz_scheduling_elapsed.worker = mz_dataflow_operator_addresses.worker AND
+    mz_dataflow_operator_addresses.slot = 0 AND
+    mz_dataflow_operator_addresses.value = mz_dataflow_objects.local_id AND
+    mz_dataflow_operator_addresses.worker = mz_dataflow_objects.worker
+GROUP BY
+    mz_dataflow_objects.global_id,
+    mz_dataflow_objects.name,
+    mz_dataflow_objects.worker",
+    id: GlobalId::System(5026),
+    needs_logs: true,
+};
+
 pub const MZ_PERF_ARRANGEMENT_RECORDS: BuiltinView = BuiltinView {
     name: "mz_perf_arrangement_records",
     schema: MZ_CATALOG_SCHEMA,
@@ -1022,6 +1344,76 @@ JOIN mz_catalog.mz_catalog_names mcn_source ON mcn_source.global_id = source_inf
     needs_logs: true,
 };
 
+// A source instance reports the offset and timestamp that it most recently
+// bound for each partition it reads.
+pub const MZ_SOURCE_TIMESTAMP_BINDINGS: BuiltinView = BuiltinView {
+    name: "mz_source_timestamp_bindings",
+    schema: MZ_CATALOG_SCHEMA,
+    sql: "CREATE VIEW mz_source_timestamp_bindings AS SELECT
+    info.source_id,
+    sources.name AS source_name,
+    info.dataflow_id,
+    info.partition_id,
+    info.\"offset\",
+    info.timestamp,
+    timestamping.consistency,
+    timestamping.timestamp_frequency_ms
+FROM mz_catalog.mz_source_info info
+JOIN mz_catalog.mz_sources sources ON info.source_id = sources.id
+JOIN mz_catalog.mz_source_timestamping timestamping ON info.source_id = timestamping.source_id",
+    id: GlobalId::System(5030),
+    needs_logs: true,
+};
+
+// The progress of a source is summed across its partitions. If several
+// dataflows ingest the same partition, the progress of the dataflow that is
+// furthest behind is reported.
+pub const MZ_HYDRATION_STATUS: BuiltinView = BuiltinView {
+    name: "mz_hydration_status",
+    schema: MZ_CATALOG_SCHEMA,
+    sql: "CREATE VIEW mz_hydration_status AS SELECT
+    frontiers.global_id,
+    dependencies.source AS source_id,
+    progress.\"offset\",
+    progress.high_watermark,
+    CASE
+        WHEN progress.high_watermark IS NULL THEN NULL
+        WHEN progress.\"offset\" >= progress.high_watermark THEN 1::pg_catalog.float8
+        ELSE progress.\"offset\"::pg_catalog.float8 / progress.high_watermark::pg_catalog.float8
+    END AS fraction_complete,
+    CASE
+        WHEN progress.high_watermark IS NULL THEN NULL
+        WHEN progress.\"offset\" >= progress.high_watermark THEN 0::pg_catalog.int8
+        WHEN progress.messages_per_second > 0
+            THEN (progress.high_watermark - progress.\"offset\") / progress.messages_per_second
+    END AS estimated_seconds_remaining,
+    frontiers.time > 0 AND coalesce(progress.\"offset\" >= progress.high_watermark, true) AS hydrated
+FROM mz_catalog.mz_materialization_frontiers frontiers
+LEFT JOIN (
+    SELECT DISTINCT dataflow, source FROM mz_catalog.mz_materialization_dependencies
+) dependencies ON frontiers.global_id = dependencies.dataflow
+LEFT JOIN (
+    SELECT
+        source_id,
+        pg_catalog.sum(\"offset\")::pg_catalog.int8 AS \"offset\",
+        pg_catalog.sum(high_watermark)::pg_catalog.int8 AS high_watermark,
+        pg_catalog.sum(messages_per_second)::pg_catalog.int8 AS messages_per_second
+    FROM (
+        SELECT
+            source_id,
+            partition_id,
+            pg_catalog.min(\"offset\") AS \"offset\",
+            pg_catalog.max(high_watermark) AS high_watermark,
+            pg_catalog.max(messages_per_second) AS messages_per_second
+        FROM mz_catalog.mz_source_statistics
+        GROUP BY source_id, partition_id
+    ) partitions
+    GROUP BY source_id
+) progress ON dependencies.source = progress.source_id",
+    id: GlobalId::System(5029),
+    needs_logs: true,
+};
+
 pub const PG_NAMESPACE: BuiltinView = BuiltinView {
     name: "pg_namespace",
     schema: PG_CATALOG_SCHEMA,
@@ -1076,7 +1468,21 @@ pub const PG_INDEX: BuiltinView = BuiltinView {
     schema: PG_CATALOG_SCHEMA,
     sql: "CREATE VIEW pg_index AS SELECT
     mz_indexes.oid as indexrelid,
-    mz_objects.oid as indrelid
+    mz_objects.oid as indrelid,
+    (
+        SELECT pg_catalog.count(*)
+        FROM mz_catalog.mz_index_columns
+        WHERE mz_index_columns.index_id = mz_indexes.id
+    )::pg_catalog.int4 AS indnatts,
+    false::pg_catalog.bool AS indisunique,
+    false::pg_catalog.bool AS indisprimary,
+    false::pg_catalog.bool AS indisexclusion,
+    false::pg_catalog.bool AS indisclustered,
+    true::pg_catalog.bool AS indisvalid,
+    true::pg_catalog.bool AS indisready,
+    true::pg_catalog.bool AS indislive,
+    NULL::pg_catalog.text AS indexprs,
+    NULL::pg_catalog.text AS indpred
 FROM mz_catalog.mz_indexes
 JOIN mz_catalog.mz_objects ON mz_indexes.on_id = mz_objects.id",
     id: GlobalId::System(5018),
@@ -1102,14 +1508,13 @@ pub const PG_ATTRIBUTE: BuiltinView = BuiltinView {
     sql: "CREATE VIEW pg_attribute AS SELECT
     mz_objects.oid as attrelid,
     mz_columns.name as attname,
-    mz_types.oid AS atttypid,
+    mz_columns.type_oid AS atttypid,
     position as attnum,
-    -1::pg_catalog.int4 as atttypmod,
+    mz_columns.type_mod as atttypmod,
     NOT nullable as attnotnull,
     FALSE as attisdropped
 FROM mz_catalog.mz_objects
-JOIN mz_catalog.mz_columns ON mz_objects.id = mz_columns.id
-JOIN mz_catalog.mz_types ON mz_columns.type = mz_types.name",
+JOIN mz_catalog.mz_columns ON mz_objects.id = mz_columns.id",
     id: GlobalId::System(5020),
     needs_logs: false,
 };
@@ -1123,7 +1528,19 @@ pub const PG_TYPE: BuiltinView = BuiltinView {
     mz_schemas.oid AS typnamespace,
     typtype,
     0::pg_catalog.oid AS typrelid,
-    NULL::pg_catalog.oid AS typelem,
+    coalesce(
+        (
+            SELECT
+                t.oid
+            FROM
+                mz_catalog.mz_array_types AS a
+                JOIN mz_catalog.mz_types AS t ON a.element_id = t.id
+            WHERE
+                a.type_id = mz_types.id
+        ),
+        0
+    )
+        AS typelem,
     coalesce(
         (
             SELECT
@@ -1159,9 +1576,22 @@ pub const PG_PROC: BuiltinView = BuiltinView {
     name: "pg_proc",
     schema: PG_CATALOG_SCHEMA,
     sql: "CREATE VIEW pg_proc AS SELECT
-    NULL::pg_catalog.oid AS oid,
-    NULL::pg_catalog.text AS proname
-    WHERE false",
+    mz_functions.oid,
+    mz_functions.name AS proname,
+    mz_schemas.oid AS pronamespace,
+    NULL::pg_catalog.oid AS proowner,
+    CASE WHEN mz_functions.kind = 'aggregate' THEN 'a' ELSE 'f' END AS prokind,
+    mz_functions.kind = 'table' AS proretset,
+    coalesce(pg_catalog.array_length(mz_functions.arg_oids, 1), 0)::pg_catalog.int4 AS pronargs,
+    mz_functions.arg_oids AS proargtypes,
+    coalesce(
+        (SELECT mz_types.oid FROM mz_catalog.mz_types WHERE mz_types.id = mz_functions.variadic_id),
+        0
+    )
+        AS provariadic,
+    NULL::pg_catalog.oid AS prorettype
+FROM mz_catalog.mz_functions
+JOIN mz_catalog.mz_schemas ON mz_functions.schema_id = mz_schemas.id",
     id: GlobalId::System(5022),
     needs_logs: false,
 };
@@ -1190,6 +1620,42 @@ pub const PG_ENUM: BuiltinView = BuiltinView {
     needs_logs: false,
 };
 
+pub const PG_ROLES: BuiltinView = BuiltinView {
+    name: "pg_roles",
+    schema: PG_CATALOG_SCHEMA,
+    sql: "CREATE VIEW pg_roles AS SELECT
+    oid,
+    name AS rolname,
+    name = 'mz_system' AS rolsuper,
+    true AS rolinherit,
+    name = 'mz_system' AS rolcreaterole,
+    name = 'mz_system' AS rolcreatedb,
+    true AS rolcanlogin,
+    -1::pg_catalog.int4 AS rolconnlimit,
+    NULL::pg_catalog.timestamptz AS rolvaliduntil
+FROM mz_catalog.mz_roles",
+    id: GlobalId::System(5027),
+    needs_logs: false,
+};
+
+pub const PG_CONSTRAINT: BuiltinView = BuiltinView {
+    name: "pg_constraint",
+    schema: PG_CATALOG_SCHEMA,
+    sql: "CREATE VIEW pg_constraint AS SELECT
+    NULL::pg_catalog.oid AS oid,
+    NULL::pg_catalog.text AS conname,
+    NULL::pg_catalog.oid AS connamespace,
+    NULL::pg_catalog.text AS contype,
+    NULL::pg_catalog.oid AS conrelid,
+    NULL::pg_catalog.oid AS conindid,
+    NULL::pg_catalog.oid AS confrelid,
+    NULL::pg_catalog.int4[] AS conkey,
+    NULL::pg_catalog.int4[] AS confkey
+    WHERE false",
+    id: GlobalId::System(5028),
+    needs_logs: false,
+};
+
 pub const MZ_SYSTEM: BuiltinRole = BuiltinRole {
     name: "mz_system",
     id: -1,
@@ -1204,17 +1670,28 @@ lazy_static! {
             Builtin::Type(&TYPE_ANYNONARRAY),
             Builtin::Type(&TYPE_BOOL),
             Builtin::Type(&TYPE_BOOL_ARRAY),
+            Builtin::Type(&TYPE_BPCHAR),
+            Builtin::Type(&TYPE_BPCHAR_ARRAY),
             Builtin::Type(&TYPE_BYTEA),
             Builtin::Type(&TYPE_BYTEA_ARRAY),
             Builtin::Type(&TYPE_CHAR),
+            Builtin::Type(&TYPE_CHAR_ARRAY),
+            Builtin::Type(&TYPE_CIDR),
+            Builtin::Type(&TYPE_CIDR_ARRAY),
             Builtin::Type(&TYPE_DATE),
             Builtin::Type(&TYPE_DATE_ARRAY),
             Builtin::Type(&TYPE_FLOAT4),
             Builtin::Type(&TYPE_FLOAT4_ARRAY),
             Builtin::Type(&TYPE_FLOAT8),
             Builtin::Type(&TYPE_FLOAT8_ARRAY),
+            Builtin::Type(&TYPE_GEOGRAPHY),
+            Builtin::Type(&TYPE_GEOMETRY),
+            Builtin::Type(&TYPE_INET),
+            Builtin::Type(&TYPE_INET_ARRAY),
             Builtin::Type(&TYPE_INT4),
             Builtin::Type(&TYPE_INT4_ARRAY),
+            Builtin::Type(&TYPE_INT4_RANGE),
+            Builtin::Type(&TYPE_INT4_RANGE_ARRAY),
             Builtin::Type(&TYPE_INT8),
             Builtin::Type(&TYPE_INT8_ARRAY),
             Builtin::Type(&TYPE_INTERVAL),
@@ -1239,9 +1716,16 @@ lazy_static! {
             Builtin::Type(&TYPE_TIMESTAMP_ARRAY),
             Builtin::Type(&TYPE_TIMESTAMPTZ),
             Builtin::Type(&TYPE_TIMESTAMPTZ_ARRAY),
+            Builtin::Type(&TYPE_TSTZ_RANGE),
+            Builtin::Type(&TYPE_TSTZ_RANGE_ARRAY),
+            Builtin::Type(&TYPE_TSQUERY),
+            Builtin::Type(&TYPE_TSQUERY_ARRAY),
+            Builtin::Type(&TYPE_TS_VECTOR),
+            Builtin::Type(&TYPE_TS_VECTOR_ARRAY),
             Builtin::Type(&TYPE_UUID),
             Builtin::Type(&TYPE_UUID_ARRAY),
             Builtin::Type(&TYPE_VARCHAR),
+            Builtin::Type(&TYPE_VARCHAR_ARRAY),
             Builtin::Log(&MZ_DATAFLOW_OPERATORS),
             Builtin::Log(&MZ_DATAFLOW_OPERATORS_ADDRESSES),
             Builtin::Log(&MZ_DATAFLOW_CHANNELS),
@@ -1256,6 +1740,9 @@ lazy_static! {
             Builtin::Log(&MZ_PEEK_ACTIVE),
             Builtin::Log(&MZ_PEEK_DURATIONS),
             Builtin::Log(&MZ_SOURCE_INFO),
+            Builtin::Log(&MZ_SOURCE_STATISTICS),
+            Builtin::Log(&MZ_KAFKA_SOURCE_STATUS),
+            Builtin::Log(&MZ_SOURCE_SNAPSHOT_PROGRESS),
             Builtin::Table(&MZ_VIEW_KEYS),
             Builtin::Table(&MZ_VIEW_FOREIGN_KEYS),
             Builtin::Table(&MZ_KAFKA_SINKS),
@@ -1277,6 +1764,16 @@ lazy_static! {
             Builtin::Table(&MZ_ROLES),
             Builtin::Table(&MZ_PSEUDO_TYPES),
             Builtin::Table(&MZ_FUNCTIONS),
+            Builtin::Table(&MZ_INDEX_COMPACTION_WINDOWS),
+            Builtin::Table(&MZ_STATEMENT_STATISTICS),
+            Builtin::Table(&MZ_STATEMENT_DURATIONS),
+            Builtin::Table(&MZ_STALLED_DATAFLOWS),
+            Builtin::Table(&MZ_TASKS),
+            Builtin::Table(&MZ_TASK_STATUS),
+            Builtin::Table(&MZ_SOURCE_TIMESTAMPING),
+            Builtin::Table(&MZ_OBJECT_JSON),
+            Builtin::Table(&MZ_VIEW_FRESHNESS),
+            Builtin::Table(&MZ_STATEMENT_HISTORY),
             Builtin::View(&MZ_RELATIONS),
             Builtin::View(&MZ_OBJECTS),
             Builtin::View(&MZ_CATALOG_NAMES),
@@ -1286,12 +1783,16 @@ lazy_static! {
             Builtin::View(&MZ_RECORDS_PER_DATAFLOW_OPERATOR),
             Builtin::View(&MZ_RECORDS_PER_DATAFLOW),
             Builtin::View(&MZ_RECORDS_PER_DATAFLOW_GLOBAL),
+            Builtin::View(&MZ_DATAFLOW_OBJECTS),
+            Builtin::View(&MZ_DATAFLOW_CPU_USAGE),
             Builtin::View(&MZ_PERF_ARRANGEMENT_RECORDS),
             Builtin::View(&MZ_PERF_PEEK_DURATIONS_CORE),
             Builtin::View(&MZ_PERF_PEEK_DURATIONS_BUCKET),
             Builtin::View(&MZ_PERF_PEEK_DURATIONS_AGGREGATES),
             Builtin::View(&MZ_MATERIALIZATION_FRONTIERS),
             Builtin::View(&MZ_PERF_DEPENDENCY_FRONTIERS),
+            Builtin::View(&MZ_HYDRATION_STATUS),
+            Builtin::View(&MZ_SOURCE_TIMESTAMP_BINDINGS),
             Builtin::View(&PG_NAMESPACE),
             Builtin::View(&PG_CLASS),
             Builtin::View(&PG_DATABASE),
@@ -1302,6 +1803,8 @@ lazy_static! {
             Builtin::View(&PG_PROC),
             Builtin::View(&PG_RANGE),
             Builtin::View(&PG_ENUM),
+            Builtin::View(&PG_ROLES),
+            Builtin::View(&PG_CONSTRAINT),
         ];
 
         // TODO(sploiselle): assign static global IDs to functions
@@ -0,0 +1,159 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Machine-readable descriptions of the catalog.
+//!
+//! The structures in this module are serialized to JSON by
+//! [`Catalog::dump`] and by the `mz_internal.object_json` function, and are
+//! consumed by external tools, like catalog-diffing and lineage tools. Their
+//! format is therefore versioned by [`CATALOG_DUMP_VERSION`].
+//!
+//! Adding a field is a backwards-compatible change and does not require a
+//! version bump. Removing or renaming a field, or changing the meaning of an
+//! existing field, does.
+
+use serde::Serialize;
+
+use sql::catalog::CatalogItem as SqlCatalogItem;
+use sql::names::DatabaseSpecifier;
+
+use crate::catalog::{Catalog, CatalogEntry};
+
+/// The version of the catalog dump format.
+pub const CATALOG_DUMP_VERSION: u32 = 1;
+
+/// A description of the entire catalog.
+#[derive(Debug, Serialize)]
+pub struct CatalogDump {
+    /// The version of the format, i.e., [`CATALOG_DUMP_VERSION`].
+    pub version: u32,
+    /// The databases in the catalog, ordered by name.
+    pub databases: Vec<DatabaseDump>,
+    /// The schemas in the catalog's databases, ordered by database name and
+    /// then by schema name.
+    pub schemas: Vec<SchemaDump>,
+    /// The objects in the catalog's databases, ordered by ID.
+    pub objects: Vec<ObjectDump>,
+}
+
+/// A description of a database.
+#[derive(Debug, Serialize)]
+pub struct DatabaseDump {
+    pub id: i64,
+    pub name: String,
+}
+
+/// A description of a schema.
+#[derive(Debug, Serialize)]
+pub struct SchemaDump {
+    pub id: i64,
+    /// The name of the database that contains the schema.
+    pub database: String,
+    pub name: String,
+}
+
+/// A description of a single object, like a table, source, view, or index.
+#[derive(Debug, Serialize)]
+pub struct ObjectDump {
+    /// The version of the format, i.e., [`CATALOG_DUMP_VERSION`].
+    pub version: u32,
+    /// The object's ID, e.g. `u1`.
+    pub id: String,
+    /// The object's OID. OIDs are not stable across restarts.
+    pub oid: u32,
+    pub database: String,
+    pub schema: String,
+    pub name: String,
+    /// The type of the object, e.g. `table` or `view`.
+    #[serde(rename = "type")]
+    pub item_type: String,
+    /// The columns of the object, in order. Empty for objects that do not
+    /// produce rows, like indexes and sinks.
+    pub columns: Vec<ColumnDump>,
+    /// The IDs of the objects upon which the object directly depends.
+    pub depends_on: Vec<String>,
+    /// The SQL statement that created the object.
+    pub create_sql: String,
+}
+
+/// A description of a column of an object.
+#[derive(Debug, Serialize)]
+pub struct ColumnDump {
+    pub name: String,
+    /// The PostgreSQL name of the column's type, e.g. `integer`.
+    #[serde(rename = "type")]
+    pub column_type: String,
+    pub nullable: bool,
+}
+
+impl CatalogDump {
+    pub fn new(catalog: &Catalog) -> CatalogDump {
+        let mut schemas = vec![];
+        for database in catalog.by_name.values() {
+            for schema in database.schemas.values() {
+                schemas.push(SchemaDump {
+                    id: schema.id,
+                    database: database.name.clone(),
+                    name: schema.name.schema.clone(),
+                });
+            }
+        }
+        CatalogDump {
+            version: CATALOG_DUMP_VERSION,
+            databases: catalog
+                .by_name
+                .values()
+                .map(|database| DatabaseDump {
+                    id: database.id,
+                    name: database.name.clone(),
+                })
+                .collect(),
+            schemas,
+            objects: catalog.by_id.values().filter_map(ObjectDump::new).collect(),
+        }
+    }
+}
+
+impl ObjectDump {
+    /// Describes the object in `entry`.
+    ///
+    /// Returns `None` if the object does not belong to one of the catalog's
+    /// databases, as is the case for builtin and temporary objects.
+    pub fn new(entry: &CatalogEntry) -> Option<ObjectDump> {
+        let database = match &entry.name.database {
+            DatabaseSpecifier::Name(name) if !entry.item.is_temporary() => name.clone(),
+            _ => return None,
+        };
+        let columns = match entry.desc() {
+            Ok(desc) => desc
+                .iter()
+                .map(|(name, typ)| ColumnDump {
+                    name: name
+                        .map(|n| n.to_string())
+                        .unwrap_or_else(|| "?column?".to_owned()),
+                    column_type: pgrepr::Type::from(&typ.scalar_type).name().to_owned(),
+                    nullable: typ.nullable,
+                })
+                .collect(),
+            Err(_) => vec![],
+        };
+        Some(ObjectDump {
+            version: CATALOG_DUMP_VERSION,
+            id: entry.id.to_string(),
+            oid: entry.oid,
+            database,
+            schema: entry.name.schema.clone(),
+            name: entry.name.item.clone(),
+            item_type: entry.item_type().to_string(),
+            columns,
+            depends_on: entry.uses().iter().map(|id| id.to_string()).collect(),
+            create_sql: entry.create_sql().to_owned(),
+        })
+    }
+}
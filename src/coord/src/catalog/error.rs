@@ -56,6 +56,11 @@ pub(crate) enum ErrorKind {
         last_version: usize,
         cause: String,
     },
+    UnsupportedVersion {
+        component: &'static str,
+        found: usize,
+        supported: usize,
+    },
 }
 
 impl Error {
@@ -120,7 +125,8 @@ impl std::error::Error for Error {
             | ErrorKind::TypeRename(_)
             | ErrorKind::ExperimentalModeRequired
             | ErrorKind::ExperimentalModeUnavailable
-            | ErrorKind::FailedMigration { .. } => None,
+            | ErrorKind::FailedMigration { .. }
+            | ErrorKind::UnsupportedVersion { .. } => None,
             ErrorKind::Sql(e) => Some(e),
             ErrorKind::Storage(e) => Some(e),
         }
@@ -210,6 +216,16 @@ more details, see https://materialize.com/docs/cli#experimental-mode"#
                 "migration from catalog content version {} failed: {}",
                 last_version, cause,
             ),
+            ErrorKind::UnsupportedVersion {
+                component,
+                found,
+                supported,
+            } => write!(
+                f,
+                "catalog {} version {} was written by a newer release of materialized; \
+                 this release supports versions up to {}, and downgrades are not supported",
+                component, found, supported,
+            ),
         }
     }
 }
@@ -0,0 +1,156 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Consistency checks for the on-disk catalog.
+//!
+//! [`Catalog::fsck`] finds the stored objects that would prevent the catalog
+//! from being opened, and can optionally quarantine them so that the server
+//! can start without them. Quarantined items are moved to the
+//! `quarantined_items` table of the catalog, where they can be inspected, and
+//! if need be restored, by hand.
+
+use std::fmt;
+
+use expr::GlobalId;
+
+use crate::catalog::storage;
+use crate::catalog::{Catalog, Config, Error};
+
+/// A problem found by [`Catalog::fsck`].
+#[derive(Debug)]
+pub enum Issue {
+    /// A schema belongs to a database that does not exist.
+    OrphanedSchema {
+        id: i64,
+        name: String,
+        database_id: i64,
+    },
+    /// An item belongs to a schema that does not exist, or to a schema that
+    /// belongs to a database that does not exist.
+    OrphanedItem {
+        id: GlobalId,
+        name: String,
+        schema_id: Option<i64>,
+    },
+    /// An item cannot be planned, e.g., because it depends on an item that
+    /// does not exist, or because its definition is no longer valid.
+    BrokenItem { id: GlobalId, error: Error },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::OrphanedSchema {
+                id,
+                name,
+                database_id,
+            } => write!(
+                f,
+                "schema {} ({}) belongs to missing database {}",
+                name, id, database_id
+            ),
+            Issue::OrphanedItem {
+                id,
+                name,
+                schema_id: Some(schema_id),
+            } => write!(
+                f,
+                "item {} ({}) belongs to missing schema {}",
+                name, id, schema_id
+            ),
+            Issue::OrphanedItem {
+                id,
+                name,
+                schema_id: None,
+            } => write!(f, "item {} ({}) does not belong to a schema", name, id),
+            Issue::BrokenItem { error, .. } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl Catalog {
+    /// Checks the catalog at `config.path` for objects that would prevent it
+    /// from being opened.
+    ///
+    /// The check applies any pending migrations to a snapshot of the catalog,
+    /// then reports schemas whose database is missing, items whose schema is
+    /// missing, and items that fail to plan. Items that depend on a reported
+    /// item fail to plan too, and so are reported as well.
+    ///
+    /// If `repair` is true, the reported items are then quarantined in the
+    /// catalog at `config.path`, and the reported schemas are removed, so that
+    /// the catalog can be opened without them. Otherwise the catalog is not
+    /// modified.
+    ///
+    /// Problems that would prevent the catalog from being opened at all, like
+    /// a failed migration or a catalog written by a newer release, cannot be
+    /// repaired and are returned as an error.
+    pub fn fsck(config: &Config, repair: bool) -> Result<Vec<Issue>, anyhow::Error> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("catalog");
+        storage::snapshot(config.path, &path)?;
+        let snapshot_config = Config {
+            path: &path,
+            ..config.clone()
+        };
+
+        let mut issues = vec![];
+        {
+            let (mut storage, _, _) = storage::Connection::open(&snapshot_config)?;
+            for (id, database_id, name) in storage.load_orphaned_schemas()? {
+                issues.push(Issue::OrphanedSchema {
+                    id,
+                    name,
+                    database_id,
+                });
+            }
+            for (id, schema_id, name) in storage.load_orphaned_items()? {
+                issues.push(Issue::OrphanedItem {
+                    id,
+                    name,
+                    schema_id,
+                });
+            }
+            // Orphaned schemas cause the catalog to refuse to open, so they
+            // must be removed from the snapshot before its items can be
+            // planned.
+            quarantine(&mut storage, &issues)?;
+        }
+
+        let mut failures = vec![];
+        Self::open_inner(&snapshot_config, Some(&mut failures))?;
+        issues.extend(
+            failures
+                .into_iter()
+                .map(|(id, error)| Issue::BrokenItem { id, error }),
+        );
+
+        if repair && !issues.is_empty() {
+            let (mut storage, _, _) = storage::Connection::open(config)?;
+            quarantine(&mut storage, &issues)?;
+        }
+        Ok(issues)
+    }
+}
+
+/// Quarantines the items and removes the schemas named by `issues`, in a
+/// single transaction.
+fn quarantine(storage: &mut storage::Connection, issues: &[Issue]) -> Result<(), Error> {
+    let tx = storage.transaction()?;
+    for issue in issues {
+        match issue {
+            Issue::OrphanedSchema { id, .. } => tx.remove_schema_by_id(*id)?,
+            Issue::OrphanedItem { id, .. } | Issue::BrokenItem { id, .. } => {
+                tx.quarantine_item(*id, &issue.to_string())?
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
@@ -19,10 +19,15 @@ use sql::ast::{
     WithOptionValue,
 };
 
-use crate::catalog::{Catalog, SerializedCatalogItem};
+use crate::catalog::storage::Transaction;
+use crate::catalog::SerializedCatalogItem;
 use crate::catalog::{MZ_CATALOG_SCHEMA, MZ_INTERNAL_SCHEMA, PG_CATALOG_SCHEMA};
 
-pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] = &[
+/// Migrations for the contents of the on-disk catalog.
+///
+/// Each migration runs in the same transaction that records that it has been
+/// applied, so a migration that fails leaves the catalog untouched.
+pub const CONTENT_MIGRATIONS: &[fn(&Transaction) -> Result<(), anyhow::Error>] = &[
     // Rewrites all built-in type references to have `pg_catalog` qualification;
     // this is necessary to support resolving all type names to the catalog.
     //
@@ -33,7 +38,7 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
     // will fail.
     //
     // Introduced for v0.6.1
-    |catalog: &mut Catalog| {
+    |tx: &Transaction| {
         struct TypeNormalizer;
 
         impl<'ast> VisitMut<'ast, Raw> for TypeNormalizer {
@@ -54,14 +59,13 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
             }
         }
 
-        let mut storage = catalog.storage();
-        let items = storage.load_items()?;
-        let tx = storage.transaction()?;
+        let items = tx.load_items()?;
 
         for (id, name, def) in items {
             let SerializedCatalogItem::V1 {
                 create_sql,
                 eval_env,
+                lazy_index,
             } = serde_json::from_slice(&def)?;
 
             let mut stmt = sql::parse::parse(&create_sql)?.into_element();
@@ -82,6 +86,7 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
                 Statement::CreateView(CreateViewStatement {
                     name: _,
                     columns: _,
+                    key_constraints: _,
                     query,
                     temporary: _,
                     materialized: _,
@@ -94,6 +99,7 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
                     on_name: _,
                     key_parts,
                     with_options,
+                    predicate: _,
                     if_not_exists: _,
                 }) => {
                     if let Some(key_parts) = key_parts {
@@ -126,20 +132,20 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
             let serialized_item = SerializedCatalogItem::V1 {
                 create_sql: stmt.to_ast_string_stable(),
                 eval_env,
+                lazy_index,
             };
 
             let serialized_item =
                 serde_json::to_vec(&serialized_item).expect("catalog serialization cannot fail");
             tx.update_item(id, &name.item, &serialized_item)?;
         }
-        tx.commit()?;
         Ok(())
     },
     // This was previously the place where the function name migration occurred;
     // however #5802 showed that the implementation was insufficient.
     //
     // Introduced for v0.7.0
-    |_: &mut Catalog| Ok(()),
+    |_: &Transaction| Ok(()),
     // Rewrites all function references to have `pg_catalog` qualification; this
     // is necessary to support resolving all built-in functions to the catalog.
     // (At the time of writing Materialize did not support user-defined
@@ -149,7 +155,7 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
     // names that could refer to functions.
     //
     // Introduced for v0.7.1
-    |catalog: &mut Catalog| {
+    |tx: &Transaction| {
         fn normalize_function_name(name: &mut UnresolvedObjectName) {
             if name.0.len() == 1 {
                 let func_name = name.to_string();
@@ -185,14 +191,13 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
             }
         }
 
-        let mut storage = catalog.storage();
-        let items = storage.load_items()?;
-        let tx = storage.transaction()?;
+        let items = tx.load_items()?;
 
         for (id, name, def) in items {
             let SerializedCatalogItem::V1 {
                 create_sql,
                 eval_env,
+                lazy_index,
             } = serde_json::from_slice(&def)?;
 
             let mut stmt = sql::parse::parse(&create_sql)?.into_element();
@@ -200,6 +205,7 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
                 Statement::CreateView(CreateViewStatement {
                     name: _,
                     columns: _,
+                    key_constraints: _,
                     query,
                     temporary: _,
                     materialized: _,
@@ -212,6 +218,7 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
                     on_name: _,
                     key_parts,
                     with_options: _,
+                    predicate: _,
                     if_not_exists: _,
                 }) => {
                     if let Some(key_parts) = key_parts {
@@ -249,13 +256,13 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
             let serialized_item = SerializedCatalogItem::V1 {
                 create_sql: stmt.to_ast_string_stable(),
                 eval_env,
+                lazy_index,
             };
 
             let serialized_item =
                 serde_json::to_vec(&serialized_item).expect("catalog serialization cannot fail");
             tx.update_item(id, &name.item, &serialized_item)?;
         }
-        tx.commit()?;
         Ok(())
     },
     // Insert default value for confluent_wire_format
@@ -273,15 +280,14 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
     // default is encoded in the on-disk catalog.
     //
     // Introduced for v0.7.1
-    |catalog: &mut Catalog| {
-        let mut storage = catalog.storage();
-        let items = storage.load_items()?;
-        let tx = storage.transaction()?;
+    |tx: &Transaction| {
+        let items = tx.load_items()?;
 
         for (id, name, def) in items {
             let SerializedCatalogItem::V1 {
                 create_sql,
                 eval_env,
+                lazy_index,
             } = serde_json::from_slice(&def)?;
 
             let mut stmt = sql::parse::parse(&create_sql)?.into_element();
@@ -310,6 +316,7 @@ pub const CONTENT_MIGRATIONS: &[fn(&mut Catalog) -> Result<(), anyhow::Error>] =
             let serialized_item = SerializedCatalogItem::V1 {
                 create_sql: stmt.to_ast_string_stable(),
                 eval_env,
+                lazy_index,
             };
 
             let serialized_item =
@@ -8,10 +8,11 @@
 // by the Apache License, Version 2.0.
 
 use std::convert::TryFrom;
+use std::path::Path;
 
 use rusqlite::params;
 use rusqlite::types::{FromSql, FromSqlError, ToSql, ToSqlOutput, Value, ValueRef};
-use rusqlite::OptionalExtension;
+use rusqlite::{OpenFlags, OptionalExtension};
 use serde::{Deserialize, Serialize};
 
 use expr::GlobalId;
@@ -105,6 +106,38 @@ const MIGRATIONS: &[&str] = &[
     // Introduced in v0.7.0.
     "INSERT INTO schemas (database_id, name) VALUES
         (NULL, 'mz_internal');",
+    // Creates tables to store the configuration parameter defaults set by
+    // `ALTER SYSTEM SET` and `ALTER ROLE ... SET`.
+    //
+    // Introduced in v0.7.1.
+    //
+    // `role_parameters.role_id` deliberately does not reference the `roles`
+    // table, as builtin roles are not stored there.
+    "CREATE TABLE system_parameters (
+        name  text PRIMARY KEY,
+        value text NOT NULL
+    );
+    CREATE TABLE role_parameters (
+        role_id integer NOT NULL,
+        name    text NOT NULL,
+        value   text NOT NULL,
+        PRIMARY KEY (role_id, name)
+    );",
+    // Creates a table to hold the items that `materialized --fsck-repair`
+    // quarantined because they prevented the catalog from loading.
+    //
+    // Introduced in v0.7.1.
+    //
+    // `quarantined_items.schema_id` deliberately does not reference the
+    // `schemas` table, as an item may be quarantined because its schema is
+    // missing.
+    "CREATE TABLE quarantined_items (
+        gid        blob PRIMARY KEY,
+        schema_id  integer,
+        name       text NOT NULL,
+        definition blob NOT NULL,
+        reason     text NOT NULL
+    );",
     // Add new migrations here.
     //
     // Migrations should be preceded with a comment of the following form:
@@ -151,6 +184,13 @@ impl Connection {
         // Run unapplied migrations. The `user_version` field stores the index
         // of the last migration that was run.
         let version: u32 = sqlite.query_row("PRAGMA user_version", params![], |row| row.get(0))?;
+        if usize::cast_from(version) >= MIGRATIONS.len() {
+            return Err(Error::new(ErrorKind::UnsupportedVersion {
+                component: "schema",
+                found: usize::cast_from(version),
+                supported: MIGRATIONS.len() - 1,
+            }));
+        }
         for (i, sql) in MIGRATIONS
             .iter()
             .enumerate()
@@ -283,18 +323,6 @@ impl Connection {
         Ok(usize::cast_from(version))
     }
 
-    pub fn set_catalog_content_version(&mut self, new_version: usize) -> Result<(), Error> {
-        let tx = self.inner.transaction()?;
-        tx.execute(
-            "UPDATE settings SET value = ? WHERE name = 'catalog_content_version'",
-            params![
-                u32::try_from(new_version).expect("fewer than u32::MAX catalog content migrations")
-            ],
-        )?;
-        tx.commit()?;
-        Ok(())
-    }
-
     pub fn load_databases(&self) -> Result<Vec<(i64, String)>, Error> {
         self.inner
             .prepare("SELECT id, name FROM databases")?
@@ -333,31 +361,84 @@ impl Connection {
             .collect()
     }
 
+    pub fn load_system_parameters(&self) -> Result<Vec<(String, String)>, Error> {
+        self.inner
+            .prepare("SELECT name, value FROM system_parameters")?
+            .query_and_then(params![], |row| -> Result<_, Error> {
+                let name: String = row.get(0)?;
+                let value: String = row.get(1)?;
+                Ok((name, value))
+            })?
+            .collect()
+    }
+
+    pub fn load_role_parameters(&self) -> Result<Vec<(i64, String, String)>, Error> {
+        self.inner
+            .prepare("SELECT role_id, name, value FROM role_parameters")?
+            .query_and_then(params![], |row| -> Result<_, Error> {
+                let role_id: i64 = row.get(0)?;
+                let name: String = row.get(1)?;
+                let value: String = row.get(2)?;
+                Ok((role_id, name, value))
+            })?
+            .collect()
+    }
+
     pub fn load_items(&self) -> Result<Vec<(GlobalId, FullName, Vec<u8>)>, Error> {
-        // Order user views by their GlobalId
+        load_items(&self.inner)
+    }
+
+    /// Loads the ID, database ID, and name of each schema that belongs to a
+    /// database that does not exist.
+    pub fn load_orphaned_schemas(&self) -> Result<Vec<(i64, i64, String)>, Error> {
         self.inner
             .prepare(
-                "SELECT items.gid, databases.name, schemas.name, items.name, items.definition
+                "SELECT schemas.id, schemas.database_id, schemas.name
+                FROM schemas
+                LEFT JOIN databases ON schemas.database_id = databases.id
+                WHERE schemas.database_id IS NOT NULL AND databases.id IS NULL",
+            )?
+            .query_and_then(params![], |row| -> Result<_, Error> {
+                let id: i64 = row.get(0)?;
+                let database_id: i64 = row.get(1)?;
+                let name: String = row.get(2)?;
+                Ok((id, database_id, name))
+            })?
+            .collect()
+    }
+
+    /// Loads the ID, schema ID, and name of each item that belongs to a schema
+    /// that does not exist or to a schema that belongs to a database that does
+    /// not exist. These items are silently skipped by
+    /// [`Connection::load_items`].
+    pub fn load_orphaned_items(&self) -> Result<Vec<(GlobalId, Option<i64>, String)>, Error> {
+        self.inner
+            .prepare(
+                "SELECT items.gid, items.schema_id, items.name
                 FROM items
-                JOIN schemas ON items.schema_id = schemas.id
-                JOIN databases ON schemas.database_id = databases.id
-                ORDER BY json_extract(items.gid, '$.User')",
+                LEFT JOIN schemas ON items.schema_id = schemas.id
+                LEFT JOIN databases ON schemas.database_id = databases.id
+                WHERE schemas.id IS NULL
+                    OR (schemas.database_id IS NOT NULL AND databases.id IS NULL)",
             )?
             .query_and_then(params![], |row| -> Result<_, Error> {
                 let id: SqlVal<GlobalId> = row.get(0)?;
-                let database: Option<String> = row.get(1)?;
-                let schema: String = row.get(2)?;
-                let item: String = row.get(3)?;
-                let definition: Vec<u8> = row.get(4)?;
-                Ok((
-                    id.0,
-                    FullName {
-                        database: DatabaseSpecifier::from(database),
-                        schema,
-                        item,
-                    },
-                    definition,
-                ))
+                let schema_id: Option<i64> = row.get(1)?;
+                let name: String = row.get(2)?;
+                Ok((id.0, schema_id, name))
+            })?
+            .collect()
+    }
+
+    /// Loads the ID, name, and quarantine reason of each quarantined item.
+    pub fn load_quarantined_items(&self) -> Result<Vec<(GlobalId, String, String)>, Error> {
+        self.inner
+            .prepare("SELECT gid, name, reason FROM quarantined_items")?
+            .query_and_then(params![], |row| -> Result<_, Error> {
+                let id: SqlVal<GlobalId> = row.get(0)?;
+                let name: String = row.get(1)?;
+                let reason: String = row.get(2)?;
+                Ok((id.0, name, reason))
             })?
             .collect()
     }
@@ -508,6 +589,11 @@ impl Transaction<'_> {
     }
 
     pub fn remove_role(&self, name: &str) -> Result<(), Error> {
+        self.inner
+            .prepare_cached(
+                "DELETE FROM role_parameters WHERE role_id = (SELECT id FROM roles WHERE name = ?)",
+            )?
+            .execute(params![name])?;
         let n = self
             .inner
             .prepare_cached("DELETE FROM roles WHERE name = ?")?
@@ -533,6 +619,34 @@ impl Transaction<'_> {
         }
     }
 
+    /// Moves the item with the specified ID to the `quarantined_items` table,
+    /// recording `reason` alongside it. Quarantined items are not loaded by
+    /// [`Connection::load_items`].
+    pub fn quarantine_item(&self, id: GlobalId, reason: &str) -> Result<(), Error> {
+        self.inner
+            .prepare_cached(
+                "INSERT INTO quarantined_items (gid, schema_id, name, definition, reason)
+                SELECT gid, schema_id, name, definition, ? FROM items WHERE gid = ?",
+            )?
+            .execute(params![reason, SqlVal(id)])?;
+        self.remove_item(id)
+    }
+
+    /// Removes the schema with the specified ID, regardless of the database it
+    /// belongs to.
+    pub fn remove_schema_by_id(&self, id: i64) -> Result<(), Error> {
+        let n = self
+            .inner
+            .prepare_cached("DELETE FROM schemas WHERE id = ?")?
+            .execute(params![id])?;
+        assert!(n <= 1);
+        if n == 1 {
+            Ok(())
+        } else {
+            Err(SqlCatalogError::UnknownSchema(id.to_string()).into())
+        }
+    }
+
     pub fn update_item(&self, id: GlobalId, item_name: &str, item: &[u8]) -> Result<(), Error> {
         let n = self
             .inner
@@ -546,6 +660,70 @@ impl Transaction<'_> {
         }
     }
 
+    /// Sets the system default for the configuration parameter `name` to
+    /// `value`, or removes the system default if `value` is `None`.
+    pub fn set_system_parameter(&self, name: &str, value: Option<&str>) -> Result<(), Error> {
+        match value {
+            Some(value) => self
+                .inner
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO system_parameters (name, value) VALUES (?, ?)",
+                )?
+                .execute(params![name, value])?,
+            None => self
+                .inner
+                .prepare_cached("DELETE FROM system_parameters WHERE name = ?")?
+                .execute(params![name])?,
+        };
+        Ok(())
+    }
+
+    /// Removes all system defaults for configuration parameters.
+    pub fn clear_system_parameters(&self) -> Result<(), Error> {
+        self.inner
+            .prepare_cached("DELETE FROM system_parameters")?
+            .execute(params![])?;
+        Ok(())
+    }
+
+    /// Sets the default for the configuration parameter `name` for the role
+    /// identified by `role_id` to `value`, or removes the role default if
+    /// `value` is `None`.
+    pub fn set_role_parameter(
+        &self,
+        role_id: i64,
+        name: &str,
+        value: Option<&str>,
+    ) -> Result<(), Error> {
+        match value {
+            Some(value) => self
+                .inner
+                .prepare_cached(
+                    "INSERT OR REPLACE INTO role_parameters (role_id, name, value) VALUES (?, ?, ?)",
+                )?
+                .execute(params![role_id, name, value])?,
+            None => self
+                .inner
+                .prepare_cached("DELETE FROM role_parameters WHERE role_id = ? AND name = ?")?
+                .execute(params![role_id, name])?,
+        };
+        Ok(())
+    }
+
+    pub fn load_items(&self) -> Result<Vec<(GlobalId, FullName, Vec<u8>)>, Error> {
+        load_items(&self.inner)
+    }
+
+    pub fn set_catalog_content_version(&self, new_version: usize) -> Result<(), Error> {
+        self.inner.execute(
+            "UPDATE settings SET value = ? WHERE name = 'catalog_content_version'",
+            params![
+                u32::try_from(new_version).expect("fewer than u32::MAX catalog content migrations")
+            ],
+        )?;
+        Ok(())
+    }
+
     pub fn commit(self) -> Result<(), rusqlite::Error> {
         self.inner.commit()
     }
@@ -587,3 +765,39 @@ where
         ))
     }
 }
+
+/// Copies a consistent snapshot of the catalog at `from` to a new catalog at
+/// `to`.
+pub fn snapshot(from: &Path, to: &Path) -> Result<(), Error> {
+    let conn = rusqlite::Connection::open_with_flags(from, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    conn.execute("VACUUM INTO ?", params![to.to_string_lossy().into_owned()])?;
+    Ok(())
+}
+
+fn load_items(conn: &rusqlite::Connection) -> Result<Vec<(GlobalId, FullName, Vec<u8>)>, Error> {
+    // Order user views by their GlobalId
+    conn.prepare(
+        "SELECT items.gid, databases.name, schemas.name, items.name, items.definition
+        FROM items
+        JOIN schemas ON items.schema_id = schemas.id
+        JOIN databases ON schemas.database_id = databases.id
+        ORDER BY json_extract(items.gid, '$.User')",
+    )?
+    .query_and_then(params![], |row| -> Result<_, Error> {
+        let id: SqlVal<GlobalId> = row.get(0)?;
+        let database: Option<String> = row.get(1)?;
+        let schema: String = row.get(2)?;
+        let item: String = row.get(3)?;
+        let definition: Vec<u8> = row.get(4)?;
+        Ok((
+            id.0,
+            FullName {
+                database: DatabaseSpecifier::from(database),
+                schema,
+                item,
+            },
+            definition,
+        ))
+    })?
+    .collect()
+}
@@ -8,15 +8,19 @@
 // by the Apache License, Version 2.0.
 
 use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use tokio::sync::{mpsc, oneshot, watch};
 use uuid::Uuid;
 
+use dataflow_types::TimestampSourceUpdate;
+use expr::GlobalId;
 use ore::thread::JoinOnDropHandle;
 use sql::ast::{Raw, Statement};
 
-use crate::command::{Cancelled, Command, ExecuteResponse, Response, StartupResponse};
+use crate::command::{Cancelled, Command, ExecuteResponse, Readiness, Response, StartupResponse};
+use crate::coord::{AdvanceSourceTimestamp, Message};
 use crate::error::CoordError;
 use crate::id_alloc::IdAllocator;
 use crate::session::{EndTransactionAction, Session};
@@ -27,6 +31,10 @@ use crate::session::{EndTransactionAction, Session};
 /// the coordinator's thread to exit, which will only occur after all
 /// outstanding [`Client`]s for the coordinator have dropped.
 pub struct Handle {
+    // Must be declared before `_thread`, so that it is dropped before the
+    // coordinator's thread is joined. The coordinator does not exit until
+    // all senders for its internal command channel have dropped.
+    pub(crate) internal_cmd_tx: mpsc::UnboundedSender<Message>,
     pub(crate) cluster_id: Uuid,
     pub(crate) _thread: JoinOnDropHandle<()>,
 }
@@ -39,6 +47,35 @@ impl Handle {
     pub fn cluster_id(&self) -> Uuid {
         self.cluster_id
     }
+
+    /// Advances the timestamp bindings for the source with the specified ID,
+    /// exactly as if the update had been produced by the source's
+    /// timestamper.
+    ///
+    /// This is intended for tests that need to control when sources'
+    /// frontiers advance. Sources should use a `consistency` source that never
+    /// produces any updates of its own, or the two will race.
+    pub fn advance_source_timestamp(&self, id: GlobalId, update: TimestampSourceUpdate) {
+        self.internal_cmd_tx
+            .send(Message::AdvanceSourceTimestamp(AdvanceSourceTimestamp {
+                id,
+                update,
+            }))
+            .expect("coordinator unexpectedly gone");
+    }
+
+    /// Ticks the coordinator, running any scheduled tasks that are due,
+    /// flushing statement statistics, and retiring idle dataflows.
+    ///
+    /// This is intended for tests that configure the coordinator with
+    /// `manual_ticks`, so that the coordinator does its periodic work only
+    /// when the test tells it to. The tick is handled before any command
+    /// that a client sends after this method returns.
+    pub fn tick(&self) {
+        self.internal_cmd_tx
+            .send(Message::Tick)
+            .expect("coordinator unexpectedly gone");
+    }
 }
 
 /// A coordinator client.
@@ -46,18 +83,31 @@ impl Handle {
 /// A coordinator client is a simple handle to a communication channel with the
 /// coordinator. It can be cheaply cloned.
 ///
+/// Clients are `Send` and `Sync`, so a single client, or clones of it, can be
+/// shared by any number of frontends running on any number of threads. The
+/// coordinator processes commands from all clients in the order it receives
+/// them.
+///
 /// Clients keep the coordinator alive. The coordinator will not exit until all
 /// outstanding clients have dropped.
 #[derive(Debug, Clone)]
 pub struct Client {
     cmd_tx: mpsc::UnboundedSender<Command>,
+    /// The number of commands that have been sent to the coordinator but that
+    /// it has not yet received, which the coordinator decrements as it
+    /// receives them.
+    queued_cmds: Arc<AtomicUsize>,
     id_alloc: Arc<IdAllocator>,
 }
 
 impl Client {
-    pub(crate) fn new(cmd_tx: mpsc::UnboundedSender<Command>) -> Client {
+    pub(crate) fn new(
+        cmd_tx: mpsc::UnboundedSender<Command>,
+        queued_cmds: Arc<AtomicUsize>,
+    ) -> Client {
         Client {
             cmd_tx,
+            queued_cmds,
             id_alloc: Arc::new(IdAllocator::new(1, 1 << 16)),
         }
     }
@@ -69,15 +119,24 @@ impl Client {
             inner: self.clone(),
         })
     }
+
+    /// Sends `cmd` to the coordinator, counting it among the queued commands
+    /// until the coordinator receives it.
+    fn send_cmd(&self, cmd: Command) -> Result<(), mpsc::error::SendError<Command>> {
+        self.queued_cmds.fetch_add(1, Ordering::SeqCst);
+        self.cmd_tx.send(cmd)
+    }
 }
 
 /// A coordinator client that is bound to a connection.
 ///
 /// The `ConnClient` automatically allocates an ID for the connection when
 /// it is created, and frees that ID for potential reuse when it is dropped.
+/// It therefore cannot be cloned; each connection must allocate its own
+/// `ConnClient` with [`Client::new_conn`].
 ///
 /// See also [`Client`].
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct ConnClient {
     conn_id: u32,
     inner: Client,
@@ -137,8 +196,7 @@ impl ConnClient {
     /// Cancels the query currently running on another connection.
     pub async fn cancel_request(&mut self, conn_id: u32, secret_key: u32) {
         self.inner
-            .cmd_tx
-            .send(Command::CancelRequest {
+            .send_cmd(Command::CancelRequest {
                 conn_id,
                 secret_key,
             })
@@ -151,8 +209,7 @@ impl ConnClient {
     {
         let (tx, rx) = oneshot::channel();
         self.inner
-            .cmd_tx
-            .send(f(tx))
+            .send_cmd(f(tx))
             .expect("coordinator unexpectedly gone");
         rx.await.expect("coordinator unexpectedly canceled request")
     }
@@ -204,17 +261,20 @@ impl SessionClient {
     /// Saves the specified statement as a prepared statement.
     ///
     /// The prepared statement is saved in the connection's [`sql::Session`]
-    /// under the specified name.
+    /// under the specified name. The query tag `tag`, if any, is attached to
+    /// every execution of the prepared statement.
     pub async fn describe(
         &mut self,
         name: String,
         stmt: Option<Statement<Raw>>,
         param_types: Vec<Option<pgrepr::Type>>,
+        tag: Option<String>,
     ) -> Result<(), CoordError> {
         self.send(|tx, session| Command::Describe {
             name,
             stmt,
             param_types,
+            tag,
             session,
             tx,
         })
@@ -222,16 +282,20 @@ impl SessionClient {
     }
 
     /// Binds a statement to a portal.
+    ///
+    /// The query tag `tag`, if any, is attached to the execution of the portal.
     pub async fn declare(
         &mut self,
         name: String,
         stmt: Statement<Raw>,
         param_types: Vec<Option<pgrepr::Type>>,
+        tag: Option<String>,
     ) -> Result<(), CoordError> {
         self.send(|tx, session| Command::Declare {
             name,
             stmt,
             param_types,
+            tag,
             session,
             tx,
         })
@@ -267,6 +331,12 @@ impl SessionClient {
             .await
     }
 
+    /// Reports whether the coordinator is ready to serve queries.
+    pub async fn check_readiness(&mut self) -> Result<Readiness, CoordError> {
+        self.send(|tx, session| Command::CheckReadiness { session, tx })
+            .await
+    }
+
     /// Terminates this client session.
     ///
     /// This method cleans up any coordinator state associated with the session
@@ -276,8 +346,7 @@ impl SessionClient {
         let session = self.session.take().expect("session invariant violated");
         self.inner
             .inner
-            .cmd_tx
-            .send(Command::Terminate { session })
+            .send_cmd(Command::Terminate { session })
             .expect("coordinator unexpectedly gone");
     }
 
@@ -304,3 +373,22 @@ impl Drop for SessionClient {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn test_client_thread_safety() {
+        // Frontends rely on sharing clients across threads and on moving
+        // connection-bound clients into the tasks that serve connections.
+        assert_send::<Client>();
+        assert_sync::<Client>();
+        assert_send::<Handle>();
+        assert_send::<ConnClient>();
+        assert_send::<SessionClient>();
+    }
+}
@@ -8,22 +8,19 @@
 // by the Apache License, Version 2.0.
 
 use std::fmt;
-use std::future::Future;
-use std::pin::Pin;
 use std::sync::Arc;
 
 use derivative::Derivative;
 use tokio::sync::{mpsc, oneshot};
 
-use dataflow_types::PeekResponse;
 use ore::str::StrExt;
 use repr::Row;
 use sql::ast::{FetchDirection, ObjectType, Raw, Statement};
-use sql::plan::ExecuteTimeout;
+use sql::plan::{ExecuteTimeout, PlanNotice};
 use tokio::sync::watch;
 
 use crate::error::CoordError;
-use crate::session::{EndTransactionAction, Session};
+use crate::session::{EndTransactionAction, RowBatchStream, Session};
 
 #[derive(Debug)]
 pub enum Command {
@@ -37,6 +34,7 @@ pub enum Command {
         name: String,
         stmt: Statement<Raw>,
         param_types: Vec<Option<pgrepr::Type>>,
+        tag: Option<String>,
         session: Session,
         tx: oneshot::Sender<Response<()>>,
     },
@@ -45,6 +43,7 @@ pub enum Command {
         name: String,
         stmt: Option<Statement<Raw>>,
         param_types: Vec<Option<pgrepr::Type>>,
+        tag: Option<String>,
         session: Session,
         tx: oneshot::Sender<Response<()>>,
     },
@@ -71,19 +70,39 @@ pub enum Command {
         tx: oneshot::Sender<Response<String>>,
     },
 
+    CheckReadiness {
+        session: Session,
+        tx: oneshot::Sender<Response<Readiness>>,
+    },
+
     Terminate {
         session: Session,
     },
 }
 
+impl Command {
+    /// Returns the ID of the connection that the command concerns.
+    pub fn conn_id(&self) -> u32 {
+        match self {
+            Command::Startup { session, .. }
+            | Command::Declare { session, .. }
+            | Command::Describe { session, .. }
+            | Command::Execute { session, .. }
+            | Command::Commit { session, .. }
+            | Command::DumpCatalog { session, .. }
+            | Command::CheckReadiness { session, .. }
+            | Command::Terminate { session } => session.conn_id(),
+            Command::CancelRequest { conn_id, .. } => *conn_id,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Response<T> {
     pub result: Result<T, CoordError>,
     pub session: Session,
 }
 
-pub type RowsFuture = Pin<Box<dyn Future<Output = PeekResponse> + Send>>;
-
 /// The response to [`Client::startup`](crate::Client::startup).
 #[derive(Debug)]
 pub struct StartupResponse {
@@ -93,6 +112,16 @@ pub struct StartupResponse {
     pub messages: Vec<StartupMessage>,
 }
 
+/// The response to [`SessionClient::check_readiness`](crate::SessionClient::check_readiness).
+#[derive(Debug)]
+pub struct Readiness {
+    /// Whether the coordinator is ready to serve queries.
+    pub ready: bool,
+    /// The names of the sources that have not connected since the coordinator
+    /// started.
+    pub rehydrating_sources: Vec<String>,
+}
+
 /// Messages in a [`StartupResponse`].
 #[derive(Debug)]
 pub enum StartupMessage {
@@ -129,6 +158,56 @@ impl fmt::Display for StartupMessage {
     }
 }
 
+/// Notices that the coordinator reports to the client while executing a
+/// statement.
+///
+/// Notices are queued on the [`Session`] and are expected to be delivered to
+/// the client before the response to the statement.
+#[derive(Clone, Debug)]
+pub enum Notice {
+    /// The planner reported a notice.
+    Plan(PlanNotice),
+    /// A query filtered the named relation, but no index on the relation could
+    /// be used to look up the rows that pass the filter, so all rows in the
+    /// index were scanned.
+    IndexFullScan { relation: String },
+}
+
+impl Notice {
+    /// Reports additional details about the notice, if any are available.
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            Notice::Plan(n) => n.detail(),
+            Notice::IndexFullScan { .. } => None,
+        }
+    }
+
+    /// Reports a hint for the user about how the notice could be addressed.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Notice::Plan(n) => n.hint(),
+            Notice::IndexFullScan { .. } => Some(
+                "Create an index whose key consists of the columns \
+                 that the query constrains to literal values."
+                    .into(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for Notice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Notice::Plan(n) => n.fmt(f),
+            Notice::IndexFullScan { relation } => write!(
+                f,
+                "index unused, full scan of {} performed",
+                relation.quoted()
+            ),
+        }
+    }
+}
+
 /// The response to [`SessionClient::execute`](crate::SessionClient::execute).
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -142,6 +221,8 @@ pub enum ExecuteResponse {
     AlteredObject(ObjectType),
     // The index was altered.
     AlteredIndexLogicalCompaction,
+    /// The system configuration was altered.
+    AlteredSystemConfiguration,
     /// The requested cursor was closed.
     ClosedCursor,
     CopyTo {
@@ -178,6 +259,10 @@ pub enum ExecuteResponse {
     CreatedView {
         existed: bool,
     },
+    /// The requested task was created.
+    CreatedTask {
+        existed: bool,
+    },
     /// The requested type was created.
     CreatedType,
     /// The requested cursor was declared.
@@ -206,6 +291,8 @@ pub enum ExecuteResponse {
     DroppedSink,
     /// The requested type was dropped.
     DroppedType,
+    /// The requested task was dropped.
+    DroppedTask,
     /// The provided query was empty.
     EmptyQuery,
     /// Fetch results from a cursor.
@@ -219,12 +306,16 @@ pub enum ExecuteResponse {
     },
     /// The specified number of rows were inserted into the requested table.
     Inserted(usize),
-    /// Rows will be delivered via the specified future.
-    SendingRows(#[derivative(Debug = "ignore")] RowsFuture),
+    /// Rows will be delivered incrementally via the specified stream.
+    SendingRows(#[derivative(Debug = "ignore")] RowBatchStream),
     /// The specified variable was set to a new value.
     SetVariable {
         name: String,
     },
+    /// The specified variable was reset to its default value.
+    ResetVariable {
+        name: String,
+    },
     /// A new transaction was started.
     StartedTransaction,
     /// Updates to the requested source or view will be streamed to the
@@ -17,34 +17,40 @@
 //! must accumulate to the same value as would an un-compacted trace.
 
 use std::cmp;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
+use std::fmt::Write;
 use std::iter;
 use std::mem;
 use std::os::unix::ffi::OsStringExt;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Context};
 use derivative::Derivative;
 use differential_dataflow::lattice::Lattice;
 use futures::future::{self, FutureExt, TryFutureExt};
 use futures::stream::{self, StreamExt};
+use log::{info, warn};
 use rand::Rng;
 use timely::communication::WorkerGuards;
+use timely::progress::frontier::MutableAntichain;
 use timely::progress::{Antichain, ChangeBatch, Timestamp as _};
 use tokio::runtime::{Handle as TokioHandle, Runtime};
-use tokio::sync::{mpsc, watch};
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::Instrument;
 
 use build_info::BuildInfo;
 use dataflow::{CacheMessage, SequencedCommand, WorkerFeedback, WorkerFeedbackWithMeta};
 use dataflow_types::logging::LoggingConfig as DataflowLoggingConfig;
 use dataflow_types::{
-    AvroOcfSinkConnector, DataflowDesc, IndexDesc, KafkaSinkConnector, PeekResponse, SinkConnector,
-    SourceConnector, TailSinkConnector, TimestampSourceUpdate, Update,
+    AvroOcfSinkConnector, Consistency, DataEncoding, DataflowDesc, ExternalSourceConnector,
+    IndexDesc, KafkaSinkConnector, PeekResponse, SinkConnector, SourceConnector, TailEnvelope,
+    TailSinkConnector, TimestampSourceUpdate, Update,
 };
 use dataflow_types::{SinkAsOf, SinkEnvelope};
 use expr::{
@@ -52,10 +58,12 @@ use expr::{
     OptimizedMirRelationExpr, RowSetFinishing,
 };
 use ore::collections::CollectionExt;
+use ore::now::NowFn;
 use ore::str::StrExt;
 use ore::thread::{JoinHandleExt, JoinOnDropHandle};
 use repr::adt::array::ArrayDimension;
-use repr::{ColumnName, Datum, RelationDesc, RelationType, Row, RowPacker, Timestamp};
+use repr::adt::jsonb::JsonbPacker;
+use repr::{ColumnName, Datum, RelationDesc, RelationType, Row, RowArena, RowPacker, Timestamp};
 use sql::ast::display::AstDisplay;
 use sql::ast::{
     CreateIndexStatement, CreateTableStatement, DropObjectsStatement, ExplainOptions, ExplainStage,
@@ -69,32 +77,65 @@ use sql::plan::{
 };
 use transform::Optimizer;
 
+use self::admission::AdmissionController;
 use self::arrangement_state::{ArrangementFrontiers, Frontiers};
+use self::compaction::CompactionTuner;
+use self::freshness::FreshnessTracker;
+use self::group_commit::{PendingWriteTxn, PendingWrites};
+use self::hydration::HydrationWaiters;
+use self::lazy_views::LazyViews;
+use self::peek::PeekCache;
+use self::shared_dataflows::{SharedDataflowGuard, SharedDataflows};
+use self::statement_history::StatementHistory;
+use self::statement_stats::StatementStatistics;
+use self::task_scheduler::TaskScheduler;
+use self::watchdog::DataflowWatchdog;
+
+pub use self::admission::Overload;
+
 use crate::cache::{CacheConfig, Cacher};
 use crate::catalog::builtin::{
     BUILTINS, MZ_ARRAY_TYPES, MZ_AVRO_OCF_SINKS, MZ_BASE_TYPES, MZ_COLUMNS, MZ_DATABASES,
-    MZ_FUNCTIONS, MZ_INDEXES, MZ_INDEX_COLUMNS, MZ_KAFKA_SINKS, MZ_LIST_TYPES, MZ_MAP_TYPES,
-    MZ_PSEUDO_TYPES, MZ_ROLES, MZ_SCHEMAS, MZ_SINKS, MZ_SOURCES, MZ_TABLES, MZ_TYPES, MZ_VIEWS,
-    MZ_VIEW_FOREIGN_KEYS, MZ_VIEW_KEYS,
+    MZ_FUNCTIONS, MZ_INDEXES, MZ_INDEX_COLUMNS, MZ_INDEX_COMPACTION_WINDOWS, MZ_KAFKA_SINKS,
+    MZ_LIST_TYPES, MZ_MAP_TYPES, MZ_OBJECT_JSON, MZ_PSEUDO_TYPES, MZ_ROLES, MZ_SCHEMAS, MZ_SINKS,
+    MZ_SOURCES, MZ_SOURCE_TIMESTAMPING, MZ_STALLED_DATAFLOWS, MZ_STATEMENT_DURATIONS,
+    MZ_STATEMENT_HISTORY, MZ_STATEMENT_STATISTICS, MZ_TABLES, MZ_TASKS, MZ_TASK_STATUS, MZ_TYPES,
+    MZ_VIEWS, MZ_VIEW_FOREIGN_KEYS, MZ_VIEW_FRESHNESS, MZ_VIEW_KEYS,
 };
 use crate::catalog::{
     self, Catalog, CatalogItem, Func, Index, SinkConnectorState, Type, TypeInner,
 };
 use crate::client::{Client, Handle};
 use crate::command::{
-    Cancelled, Command, ExecuteResponse, Response, StartupMessage, StartupResponse,
+    Cancelled, Command, ExecuteResponse, Notice, Readiness, Response, StartupMessage,
+    StartupResponse,
 };
 use crate::error::CoordError;
 use crate::session::{
-    EndTransactionAction, PreparedStatement, Session, TransactionOps, TransactionStatus, WriteOp,
+    EndTransactionAction, PreparedStatement, Session, TransactionOps, TransactionStatus, Vars,
+    WriteOp,
 };
 use crate::sink_connector;
-use crate::timestamp::{TimestampMessage, Timestamper};
+use crate::timestamp::{self, TimestampMessage, Timestamper};
 use crate::util::ClientTransmitter;
 
+mod admission;
 mod arrangement_state;
+mod compaction;
 mod dataflow_builder;
+mod explain_dataflow;
+mod freshness;
+mod group_commit;
+mod hydration;
+mod index_advice;
+mod lazy_views;
 mod metrics;
+mod peek;
+mod shared_dataflows;
+mod statement_history;
+mod statement_stats;
+mod task_scheduler;
+mod watchdog;
 
 #[derive(Debug)]
 pub enum Message {
@@ -102,7 +143,11 @@ pub enum Message {
     Worker(WorkerFeedbackWithMeta),
     AdvanceSourceTimestamp(AdvanceSourceTimestamp),
     StatementReady(StatementReady),
+    StatementExecuted(StatementExecuted),
     SinkConnectorReady(SinkConnectorReady),
+    SourceConnectorVerified(SourceConnectorVerified),
+    Tick,
+    ScheduledTaskRan(ScheduledTaskRan),
     Shutdown,
 }
 
@@ -120,6 +165,19 @@ pub struct StatementReady {
     pub tx: ClientTransmitter<ExecuteResponse>,
     pub result: Result<sql::ast::Statement<Raw>, CoordError>,
     pub params: Params,
+    pub tag: Option<String>,
+}
+
+/// Reports the completion of a statement, for statement statistics and the
+/// statement history.
+#[derive(Debug)]
+pub struct StatementExecuted {
+    pub fingerprint: String,
+    pub history_id: u64,
+    pub tag: Option<String>,
+    pub duration: Duration,
+    pub rows_returned: u64,
+    pub error: Option<String>,
 }
 
 #[derive(Derivative)]
@@ -133,6 +191,28 @@ pub struct SinkConnectorReady {
     pub result: Result<SinkConnector, CoordError>,
 }
 
+/// Reports whether the source `id` could connect with the connection options
+/// of an ALTER SOURCE statement.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct SourceConnectorVerified {
+    pub session: Session,
+    #[derivative(Debug = "ignore")]
+    pub tx: ClientTransmitter<ExecuteResponse>,
+    pub id: GlobalId,
+    pub source: sql::plan::Source,
+    pub result: Result<(), CoordError>,
+}
+
+/// Reports the completion of a run of a scheduled task.
+#[derive(Debug)]
+pub struct ScheduledTaskRan {
+    pub id: GlobalId,
+    /// The rows that the task's query produced, or the error that it failed
+    /// with.
+    pub result: Result<Vec<Row>, String>,
+}
+
 /// Configures dataflow worker logging.
 #[derive(Clone, Debug)]
 pub struct LoggingConfig {
@@ -140,7 +220,47 @@ pub struct LoggingConfig {
     pub log_logging: bool,
 }
 
+/// Configures resource limits enforced by the coordinator.
+#[derive(Clone, Debug, Default)]
+pub struct QuotaConfig {
+    /// The maximum number of maintained dataflows (indexes and sinks) that may
+    /// live in any one schema.
+    pub max_dataflows_per_schema: Option<usize>,
+    /// The number of bytes that the arrangements of the indexes in any one
+    /// schema may hold before new dataflows in the schema are rejected.
+    pub max_arrangement_bytes_per_schema: Option<usize>,
+    /// The maximum number of queries that sessions belonging to any one role
+    /// may have outstanding at once.
+    pub max_concurrent_peeks_per_role: Option<usize>,
+    /// The maximum number of connections that may be open at once.
+    pub max_connections: Option<usize>,
+    /// The maximum number of connections that sessions belonging to any one
+    /// role may have open at once.
+    pub max_connections_per_role: Option<usize>,
+    /// The memory usage of the process, in bytes, above which queries that
+    /// require a transient dataflow are rejected.
+    pub max_memory: Option<usize>,
+    /// The number of commands waiting for the coordinator above which queries
+    /// that require a transient dataflow are rejected.
+    pub max_queue_depth: Option<usize>,
+}
+
+/// Controls the log filter of the running process.
+///
+/// Log filters use the syntax of the `MZ_LOG` environment variable, e.g.,
+/// `info,dataflow::source=debug`.
+pub trait LogFilterControl: Send + Sync {
+    /// Installs `filter` as the log filter.
+    fn set(&self, filter: &str) -> Result<(), anyhow::Error>;
+    /// Restores the log filter that was configured at startup.
+    fn reset(&self) -> Result<(), anyhow::Error>;
+}
+
 /// Configures a coordinator.
+///
+/// Services that embed a coordinator should prefer to construct a
+/// configuration with [`Config::builder`], which fills in defaults for the
+/// many options that only matter to a standalone `materialized` process.
 pub struct Config<'a> {
     pub workers: usize,
     pub timely_worker: timely::WorkerConfig,
@@ -150,8 +270,154 @@ pub struct Config<'a> {
     pub timestamp_frequency: Duration,
     pub cache: Option<CacheConfig>,
     pub logical_compaction_window: Option<Duration>,
+    pub auto_tune_logical_compaction_window: bool,
     pub experimental_mode: bool,
+    pub quotas: QuotaConfig,
+    pub slow_query_threshold: Option<Duration>,
+    /// The maximum size of the statement history log, in bytes. Zero disables
+    /// the statement history.
+    pub statement_history_size: u64,
+    pub stalled_dataflow_threshold: Option<Duration>,
+    /// How long a transient dataflow shared by the peeks of the same
+    /// expression may go unused before it is torn down, if transient
+    /// dataflows are shared.
+    pub shared_dataflow_idle_timeout: Option<Duration>,
+    /// The number of peeks of the same expression, each within the idle
+    /// timeout of the last, after which its transient dataflow is shared.
+    pub shared_dataflow_min_peeks: usize,
+    pub freshness_slo: Option<Duration>,
+    pub rehydration_budget: Option<Duration>,
+    pub lazy_view_idle_timeout: Duration,
+    pub log_filter: Option<Arc<dyn LogFilterControl>>,
     pub build_info: &'static BuildInfo,
+    /// The clock from which the coordinator chooses timestamps.
+    pub now: NowFn,
+    /// Whether the coordinator ticks only when told to via [`Handle::tick`],
+    /// rather than every second. Ticks run scheduled tasks, flush statement
+    /// statistics, and retire idle dataflows.
+    pub manual_ticks: bool,
+}
+
+impl<'a> Config<'a> {
+    /// Returns a builder for a configuration that stores the catalog in
+    /// `data_directory`.
+    ///
+    /// The built configuration runs a single dataflow worker, disables
+    /// introspection, and otherwise matches the defaults of `materialized`.
+    pub fn builder(data_directory: &'a Path) -> ConfigBuilder<'a> {
+        ConfigBuilder {
+            config: Config {
+                workers: 1,
+                timely_worker: timely::WorkerConfig::default(),
+                symbiosis_url: None,
+                logging: None,
+                data_directory,
+                timestamp_frequency: Duration::from_millis(10),
+                cache: None,
+                logical_compaction_window: Some(Duration::from_millis(1)),
+                auto_tune_logical_compaction_window: false,
+                experimental_mode: false,
+                quotas: QuotaConfig::default(),
+                slow_query_threshold: None,
+                statement_history_size: 64 << 20,
+                stalled_dataflow_threshold: None,
+                shared_dataflow_idle_timeout: None,
+                shared_dataflow_min_peeks: 2,
+                freshness_slo: None,
+                rehydration_budget: None,
+                lazy_view_idle_timeout: Duration::from_secs(300),
+                log_filter: None,
+                build_info: &build_info::DUMMY_BUILD_INFO,
+                now: ore::now::system_time_fn(),
+                manual_ticks: false,
+            },
+        }
+    }
+}
+
+/// A builder for a [`Config`].
+pub struct ConfigBuilder<'a> {
+    config: Config<'a>,
+}
+
+impl<'a> ConfigBuilder<'a> {
+    /// Sets the number of dataflow worker threads.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.config.workers = workers;
+        self
+    }
+
+    /// Enables introspection, i.e., the dataflow worker logging that backs
+    /// the `mz_catalog` log sources.
+    pub fn logging(mut self, logging: LoggingConfig) -> Self {
+        self.config.logging = Some(logging);
+        self
+    }
+
+    /// Sets the frequency at which timestamps are assigned to source data,
+    /// i.e., how often the frontiers of sources advance.
+    pub fn timestamp_frequency(mut self, frequency: Duration) -> Self {
+        self.config.timestamp_frequency = frequency;
+        self
+    }
+
+    /// Sets how far behind the upper frontier of each index its compaction
+    /// frontier trails. Set to `None` to disable logical compaction.
+    pub fn logical_compaction_window(mut self, window: Option<Duration>) -> Self {
+        self.config.logical_compaction_window = window;
+        self
+    }
+
+    /// Sets whether experimental features are enabled.
+    pub fn experimental_mode(mut self, experimental_mode: bool) -> Self {
+        self.config.experimental_mode = experimental_mode;
+        self
+    }
+
+    /// Sets the resource limits to enforce.
+    pub fn quotas(mut self, quotas: QuotaConfig) -> Self {
+        self.config.quotas = quotas;
+        self
+    }
+
+    /// Sets the maximum size of the statement history log, in bytes. Set to
+    /// zero to disable the statement history.
+    pub fn statement_history_size(mut self, size: u64) -> Self {
+        self.config.statement_history_size = size;
+        self
+    }
+
+    /// Sets the build information that the coordinator reports, e.g., in
+    /// `mz_version()`.
+    pub fn build_info(mut self, build_info: &'static BuildInfo) -> Self {
+        self.config.build_info = build_info;
+        self
+    }
+
+    /// Sets the clock from which the coordinator chooses timestamps.
+    pub fn now(mut self, now: NowFn) -> Self {
+        self.config.now = now;
+        self
+    }
+
+    /// Sets whether the coordinator ticks only when told to via
+    /// [`Handle::tick`].
+    pub fn manual_ticks(mut self, manual_ticks: bool) -> Self {
+        self.config.manual_ticks = manual_ticks;
+        self
+    }
+
+    /// Constructs the configuration.
+    pub fn build(self) -> Config<'a> {
+        self.config
+    }
+
+    /// Starts a coordinator with the configuration.
+    ///
+    /// See [`serve`].
+    pub async fn serve(self, runtime: Arc<Runtime>) -> Result<(Handle, Client), CoordError> {
+        serve(self.config, runtime).await
+    }
 }
 
 /// Glues the external world to the Timely workers.
@@ -186,9 +452,72 @@ pub struct Coordinator {
     /// TODO(justin): this is a hack, and does not work right with TAIL.
     need_advance: bool,
     transient_id_counter: u64,
+    /// The ID of the most recently executed statement, used to correlate log
+    /// messages.
+    statement_id_counter: u64,
     /// A map from connection ID to metadata about that connection for all
     // active connections.
     active_conns: HashMap<u32, ConnMeta>,
+    /// Resource limits to enforce.
+    quotas: QuotaConfig,
+    /// The number of outstanding peeks for each role.
+    active_peeks: HashMap<String, Arc<AtomicUsize>>,
+    /// The approximate number of bytes held by each worker's share of each
+    /// index's arrangement, as most recently reported by the workers.
+    arrangement_bytes: HashMap<GlobalId, Vec<usize>>,
+    /// The upper frontier of each source's instances, across all workers.
+    source_uppers: HashMap<GlobalId, MutableAntichain<Timestamp>>,
+    /// The number of commands that clients have sent to the coordinator but
+    /// that it has not yet received.
+    queued_cmds: Arc<AtomicUsize>,
+    /// Rejects expensive peeks while the process is overloaded.
+    admission: AdmissionController,
+    /// The results of recent peeks, for sessions that opt in to sharing them.
+    peek_cache: Arc<Mutex<PeekCache>>,
+    /// Chooses per-index logical compaction windows, if enabled.
+    compaction_tuner: Option<CompactionTuner>,
+    /// The rows most recently reported to `mz_index_compaction_windows`.
+    compaction_window_rows: HashMap<GlobalId, Row>,
+    /// The rows most recently reported to `mz_object_json`.
+    object_json_rows: HashMap<GlobalId, Row>,
+    /// Statistics about executed statements, by fingerprint.
+    statement_stats: StatementStatistics,
+    /// The duration above which executed statements are logged, if any.
+    slow_query_threshold: Option<Duration>,
+    /// The persistent history of executed statements.
+    statement_history: StatementHistory,
+    /// Detects stalled dataflows, if enabled.
+    dataflow_watchdog: Option<DataflowWatchdog>,
+    /// Shares transient dataflows among peeks of the same expression, if
+    /// enabled.
+    shared_dataflows: Option<SharedDataflows>,
+    /// Measures the end-to-end freshness of indexes.
+    freshness: FreshnessTracker,
+    /// Controls the process's log filter, if it can be changed at runtime.
+    log_filter: Option<Arc<dyn LogFilterControl>>,
+    /// The time at which the coordinator started.
+    start_time: Instant,
+    /// How long after startup to report readiness even if some sources have
+    /// not connected, if ever.
+    rehydration_budget: Option<Duration>,
+    /// The user sources that existed at startup and have not yet connected.
+    ///
+    /// Sources whose timestamper tracks offsets, i.e., Kafka sources and
+    /// sources with a consistency topic, connect when the timestamper first
+    /// advances their timestamps. Other sources connect when the first
+    /// timestamp is bound in the dataflow that reads them, and so are only
+    /// tracked if a dataflow installed at startup reads them.
+    rehydrating_sources: HashSet<GlobalId>,
+    /// Statements that are waiting for the indexes they created to hydrate.
+    hydration_waiters: HydrationWaiters,
+    /// Transactions whose table writes are waiting to be committed together.
+    pending_writes: PendingWrites,
+    /// Tracks when scheduled tasks are due to run.
+    task_scheduler: TaskScheduler,
+    /// Tracks when lazy views were last queried.
+    lazy_views: LazyViews,
+    /// The clock from which timestamps are chosen.
+    now: NowFn,
 }
 
 /// Metadata about an active connection.
@@ -205,6 +534,8 @@ struct ConnMeta {
     /// requests are required to authenticate with the secret of the connection
     /// that they are targeting.
     secret_key: u32,
+    /// The role as which the connection authenticated.
+    user: String,
 }
 
 impl Coordinator {
@@ -240,12 +571,7 @@ impl Coordinator {
         // This is a hack. In a perfect world we would represent time as having a "real" dimension
         // and a "coordinator" dimension so that clients always observed linearizability from
         // things the coordinator did without being related to the real dimension.
-        let ts = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .expect("failed to get millis since epoch")
-            .as_millis()
-            .try_into()
-            .expect("current time did not fit into u64");
+        let ts = (self.now)();
 
         if ts < self.read_lower_bound {
             self.read_lower_bound
@@ -282,6 +608,9 @@ impl Coordinator {
                 //using a single dataflow, we have to make sure the rebuild process re-runs
                 //the same multiple-build dataflow.
                 CatalogItem::Source(source) => {
+                    if id.is_user() && timestamper_tracks_offsets(&source.connector) {
+                        self.rehydrating_sources.insert(*id);
+                    }
                     // Inform the timestamper about this source.
                     self.update_timestamper(*id, true).await;
                     self.maybe_begin_caching(*id, &source.connector).await;
@@ -300,8 +629,14 @@ impl Coordinator {
                         self.indexes
                             .insert(*id, Frontiers::new(self.num_workers(), Some(1_000)));
                     } else {
-                        self.ship_dataflow(self.dataflow_builder().build_index_dataflow(*id))
-                            .await?;
+                        let dataflow = self.dataflow_builder().build_index_dataflow(*id);
+                        for source_id in dataflow.source_imports.keys() {
+                            let item = self.catalog.get_by_id(source_id).item();
+                            if source_id.is_user() && matches!(item, CatalogItem::Source(_)) {
+                                self.rehydrating_sources.insert(*source_id);
+                            }
+                        }
+                        self.ship_dataflow(dataflow).await?;
                     }
                 }
                 _ => (), // Handled in next loop.
@@ -391,13 +726,24 @@ impl Coordinator {
         cmd_rx: mpsc::UnboundedReceiver<Command>,
         feedback_rx: mpsc::UnboundedReceiver<WorkerFeedbackWithMeta>,
         _timestamper_thread_handle: JoinOnDropHandle<()>,
+        manual_ticks: bool,
     ) {
+        let queued_cmds = Arc::clone(&self.queued_cmds);
         let cmd_stream = UnboundedReceiverStream::new(cmd_rx)
-            .map(Message::Command)
+            .map(move |cmd| {
+                queued_cmds.fetch_sub(1, Ordering::SeqCst);
+                Message::Command(cmd)
+            })
             .chain(stream::once(future::ready(Message::Shutdown)));
 
         let feedback_stream = UnboundedReceiverStream::new(feedback_rx).map(Message::Worker);
 
+        let task_ticker = if manual_ticks {
+            None
+        } else {
+            Some(task_scheduler::spawn_ticker(self.internal_cmd_tx.clone()))
+        };
+
         let mut messages = ore::future::select_all_biased(vec![
             // Order matters here. We want to drain internal commands
             // (`internal_cmd_rx` and `feedback_stream`) before processing
@@ -407,43 +753,56 @@ impl Coordinator {
             cmd_stream.boxed(),
         ]);
 
-        while let Some(msg) = messages.next().await {
+        loop {
+            // Handle messages that are immediately available before
+            // committing any pending writes, so that writes from concurrent
+            // sessions are committed together at a single timestamp, unless
+            // the pending writes have waited long enough.
+            let msg = match messages.next().now_or_never() {
+                Some(msg) if !self.pending_writes.is_due() => msg,
+                Some(msg) => {
+                    self.group_commit();
+                    msg
+                }
+                None => {
+                    self.group_commit();
+                    self.advance_local_inputs();
+                    messages.next().await
+                }
+            };
+            let msg = match msg {
+                Some(msg) => msg,
+                None => break,
+            };
             match msg {
                 Message::Command(cmd) => self.message_command(cmd).await,
                 Message::Worker(worker) => self.message_worker(worker).await,
                 Message::StatementReady(ready) => self.message_statement_ready(ready).await,
+                Message::StatementExecuted(executed) => {
+                    self.message_statement_executed(executed).await
+                }
                 Message::SinkConnectorReady(ready) => {
                     self.message_sink_connector_ready(ready).await
                 }
+                Message::SourceConnectorVerified(verified) => {
+                    self.message_source_connector_verified(verified).await
+                }
                 Message::AdvanceSourceTimestamp(advance) => {
                     self.message_advance_source_timestamp(advance).await
                 }
+                Message::Tick => self.message_tick().await,
+                Message::ScheduledTaskRan(ran) => self.message_scheduled_task_ran(ran).await,
                 Message::Shutdown => {
+                    self.group_commit();
                     self.message_shutdown().await;
+                    // Stop the ticker, so that it releases its handle on the
+                    // internal command channel.
+                    drop(task_ticker);
                     break;
                 }
             }
 
-            let needed = self.need_advance;
-            let mut next_ts = self.get_ts();
-            self.need_advance = false;
-            if next_ts <= self.read_lower_bound {
-                next_ts = self.read_lower_bound + 1;
-            }
-            // TODO(justin): this is pretty hacky, and works more-or-less because this frequency
-            // lines up with that used in the logging views.
-            if needed
-                || self.logging_granularity.is_some()
-                    && next_ts / self.logging_granularity.unwrap()
-                        > self.closed_up_to / self.logging_granularity.unwrap()
-            {
-                if next_ts > self.closed_up_to {
-                    self.broadcast(SequencedCommand::AdvanceAllLocalInputs {
-                        advance_to: next_ts,
-                    });
-                    self.closed_up_to = next_ts;
-                }
-            }
+            self.advance_local_inputs();
         }
 
         // Cleanly drain any pending messages from the worker before shutting
@@ -452,34 +811,154 @@ impl Coordinator {
         while messages.next().await.is_some() {}
     }
 
+    /// Advances all local inputs, if anyone has observed a timestamp since
+    /// they were last advanced.
+    fn advance_local_inputs(&mut self) {
+        let needed = self.need_advance;
+        let mut next_ts = self.get_ts();
+        self.need_advance = false;
+        if next_ts <= self.read_lower_bound {
+            next_ts = self.read_lower_bound + 1;
+        }
+        // TODO(justin): this is pretty hacky, and works more-or-less because this frequency
+        // lines up with that used in the logging views.
+        if needed
+            || self.logging_granularity.is_some()
+                && next_ts / self.logging_granularity.unwrap()
+                    > self.closed_up_to / self.logging_granularity.unwrap()
+        {
+            if next_ts > self.closed_up_to {
+                self.broadcast(SequencedCommand::AdvanceAllLocalInputs {
+                    advance_to: next_ts,
+                });
+                self.closed_up_to = next_ts;
+            }
+        }
+    }
+
+    /// Commits the writes of all transactions that are waiting to commit at
+    /// a single write timestamp, then responds to their clients.
+    fn group_commit(&mut self) {
+        if self.pending_writes.is_empty() {
+            return;
+        }
+        let timestamp = self.get_write_ts();
+        for PendingWriteTxn {
+            writes,
+            response,
+            tx,
+            session,
+        } in self.pending_writes.take()
+        {
+            // Re-verify that the tables still exist, as they may have been
+            // dropped while the transaction was waiting to commit.
+            let unknown = writes
+                .iter()
+                .find(|WriteOp { id, .. }| self.catalog.try_get_by_id(*id).is_none())
+                .map(|WriteOp { id, .. }| *id);
+            if let Some(id) = unknown {
+                let e = CoordError::SqlCatalog(CatalogError::UnknownItem(id.to_string()));
+                tx.send(Err(e), session);
+                continue;
+            }
+            for WriteOp { id, rows } in writes {
+                let updates = rows
+                    .into_iter()
+                    .map(|(row, diff)| Update {
+                        row,
+                        diff,
+                        timestamp,
+                    })
+                    .collect();
+                self.broadcast(SequencedCommand::Insert { id, updates });
+            }
+            tx.send(Ok(response), session);
+        }
+    }
+
     async fn message_worker(
         &mut self,
-        WorkerFeedbackWithMeta {
-            worker_id: _,
-            message,
-        }: WorkerFeedbackWithMeta,
+        WorkerFeedbackWithMeta { worker_id, message }: WorkerFeedbackWithMeta,
     ) {
         match message {
             WorkerFeedback::FrontierUppers(updates) => {
                 for (name, changes) in updates {
                     self.update_upper(&name, changes);
                 }
+                self.hydration_waiters.notify(&self.indexes);
                 self.maintenance().await;
             }
+            WorkerFeedback::SourceFrontierUppers(updates) => {
+                let num_workers = self.num_workers();
+                for (id, mut changes) in updates {
+                    // Reports for dropped sources may still be in flight.
+                    if self.catalog.try_get_by_id(id).is_none() {
+                        continue;
+                    }
+                    let upper = self.source_uppers.entry(id).or_insert_with(|| {
+                        let mut upper = MutableAntichain::new();
+                        upper.update_iter(Some((0, num_workers as i64)));
+                        upper
+                    });
+                    let changes: Vec<_> = upper.update_iter(changes.drain()).collect();
+                    if !changes.is_empty() {
+                        if let Some(watchdog) = &mut self.dataflow_watchdog {
+                            watchdog.frontier_advanced(id);
+                        }
+                        self.source_connected(id);
+                    }
+                }
+            }
+            WorkerFeedback::SourceEventTimes(updates) => {
+                for (source, times) in updates {
+                    self.freshness
+                        .record_event_times(source, &times, &self.indexes);
+                }
+            }
+            WorkerFeedback::ArrangementBytes(updates) => {
+                let num_workers = self.num_workers();
+                for (id, bytes) in updates {
+                    // Reports for dropped indexes may still be in flight.
+                    if self.indexes.get(&id).is_some() {
+                        self.arrangement_bytes
+                            .entry(id)
+                            .or_insert_with(|| vec![0; num_workers])[worker_id] = bytes;
+                    }
+                }
+            }
         }
     }
 
     async fn message_statement_ready(
         &mut self,
         StatementReady {
-            session,
+            mut session,
             tx,
             result,
             params,
+            tag,
         }: StatementReady,
     ) {
+        let tx = match &result {
+            Ok(stmt) => {
+                let history_id = self.statement_history.start(
+                    session.conn_id(),
+                    session.user(),
+                    statement_history::redact(stmt),
+                    tag.clone(),
+                    (self.now)(),
+                );
+                self.record_statement_statistics(
+                    statement_stats::fingerprint(stmt),
+                    history_id,
+                    tag,
+                    tx,
+                )
+            }
+            Err(_) => tx,
+        };
         match future::ready(result)
-            .and_then(|stmt| self.handle_statement(&session, stmt, &params))
+            .and_then(|stmt| self.handle_statement(&mut session, stmt, &params))
             .await
         {
             Ok((pcx, plan)) => self.sequence_plan(tx, session, pcx, plan).await,
@@ -487,6 +966,83 @@ impl Coordinator {
         }
     }
 
+    async fn message_statement_executed(
+        &mut self,
+        StatementExecuted {
+            fingerprint,
+            history_id,
+            tag,
+            duration,
+            rows_returned,
+            error,
+        }: StatementExecuted,
+    ) {
+        statement_stats::observe_duration(tag.as_deref(), duration, error.is_some());
+        match self.slow_query_threshold {
+            Some(threshold) if duration >= threshold => warn!(
+                "slow statement: duration={:?} tag={} rows_returned={} error={}: {}",
+                duration,
+                tag.as_deref().unwrap_or("none"),
+                rows_returned,
+                error.is_some(),
+                fingerprint
+            ),
+            _ => (),
+        }
+        self.statement_stats
+            .record(fingerprint, duration, rows_returned, error.is_some());
+        self.statement_history
+            .finish(history_id, duration, rows_returned, error);
+    }
+
+    /// Returns a transmitter that forwards the response to `tx`, and that
+    /// reports the execution of the statement with fingerprint `fingerprint`,
+    /// statement history ID `history_id`, and query tag `tag` once the
+    /// response, including any rows it streams, is complete.
+    fn record_statement_statistics(
+        &self,
+        fingerprint: String,
+        history_id: u64,
+        tag: Option<String>,
+        tx: ClientTransmitter<ExecuteResponse>,
+    ) -> ClientTransmitter<ExecuteResponse> {
+        let (inner_tx, inner_rx) = oneshot::channel();
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        let start = Instant::now();
+        tokio::spawn(async move {
+            let Response { result, session } = inner_rx
+                .await
+                .expect("client transmitter dropped without send");
+            let report = move |rows_returned, error| {
+                // The coordinator may have shut down, in which case there is
+                // no one left to record the statistics.
+                let _ = internal_cmd_tx.send(Message::StatementExecuted(StatementExecuted {
+                    fingerprint,
+                    history_id,
+                    tag,
+                    duration: start.elapsed(),
+                    rows_returned,
+                    error,
+                }));
+            };
+            let result = match result {
+                Ok(ExecuteResponse::SendingRows(rows)) => Ok(ExecuteResponse::SendingRows(
+                    statement_stats::count_rows(rows, report),
+                )),
+                Ok(resp) => {
+                    report(0, None);
+                    Ok(resp)
+                }
+                Err(e) => {
+                    report(0, Some(e.to_string()));
+                    Err(e)
+                }
+            };
+            tx.send(result, session);
+        });
+        ClientTransmitter::new(inner_tx)
+    }
+
     async fn message_sink_connector_ready(
         &mut self,
         SinkConnectorReady {
@@ -529,6 +1085,28 @@ impl Coordinator {
         }
     }
 
+    async fn message_source_connector_verified(
+        &mut self,
+        SourceConnectorVerified {
+            session,
+            tx,
+            id,
+            source,
+            result,
+        }: SourceConnectorVerified,
+    ) {
+        let result = match result {
+            // Another session may have dropped the source while we were
+            // connecting to it.
+            Ok(()) if self.catalog.try_get_by_id(id).is_none() => Err(CoordError::SqlCatalog(
+                CatalogError::UnknownItem(id.to_string()),
+            )),
+            Ok(()) => self.alter_source(id, source).await,
+            Err(e) => Err(e),
+        };
+        tx.send(result, session);
+    }
+
     async fn message_shutdown(&mut self) {
         self.ts_tx.send(TimestampMessage::Shutdown).unwrap();
         self.broadcast(SequencedCommand::Shutdown);
@@ -538,16 +1116,168 @@ impl Coordinator {
         &mut self,
         AdvanceSourceTimestamp { id, update }: AdvanceSourceTimestamp,
     ) {
+        self.source_connected(id);
         self.broadcast(SequencedCommand::AdvanceSourceTimestamp { id, update });
     }
 
+    /// Records that the source `id` has connected, either because the
+    /// timestamper advanced its timestamps or because its dataflow bound its
+    /// first timestamp.
+    fn source_connected(&mut self, id: GlobalId) {
+        if self.rehydrating_sources.remove(&id) && self.rehydrating_sources.is_empty() {
+            info!(
+                "all sources connected {:?} after startup",
+                self.start_time.elapsed()
+            );
+        }
+    }
+
+    async fn message_tick(&mut self) {
+        self.run_scheduled_tasks().await;
+        self.drop_idle_lazy_views().await;
+        self.drop_idle_shared_dataflows().await;
+
+        if let Some(updates) = self.statement_stats.flush((self.now)()) {
+            self.update_catalog_view(MZ_STATEMENT_STATISTICS.id, updates.statistics)
+                .await;
+            self.update_catalog_view(MZ_STATEMENT_DURATIONS.id, updates.durations)
+                .await;
+        }
+
+        if let Some(updates) = self.statement_history.flush((self.now)()) {
+            self.update_catalog_view(MZ_STATEMENT_HISTORY.id, updates)
+                .await;
+        }
+    }
+
+    async fn run_scheduled_tasks(&mut self) {
+        for id in self.task_scheduler.due() {
+            let task = match self.catalog.try_get_by_id(id).map(|entry| entry.item()) {
+                Some(CatalogItem::Task(task)) => task.clone(),
+                _ => continue,
+            };
+            let finishing = RowSetFinishing {
+                order_by: vec![],
+                limit: None,
+                offset: 0,
+                project: (0..task.query.arity()).collect(),
+            };
+            let resp = self
+                .sequence_peek(
+                    &mut Session::dummy(),
+                    task.query,
+                    PeekWhen::Immediately,
+                    finishing,
+                    None,
+                )
+                .await;
+            let internal_cmd_tx = self.internal_cmd_tx.clone();
+            tokio::spawn(async move {
+                let result = match resp {
+                    Ok(ExecuteResponse::SendingRows(rows)) => {
+                        task_scheduler::collect_rows(rows).await
+                    }
+                    Ok(_) => unreachable!("peeks always send rows"),
+                    Err(e) => Err(e.to_string()),
+                };
+                // The coordinator may have shut down while the query was
+                // running, in which case the result is of no interest.
+                let _ = internal_cmd_tx
+                    .send(Message::ScheduledTaskRan(ScheduledTaskRan { id, result }));
+            });
+        }
+    }
+
+    async fn message_scheduled_task_ran(
+        &mut self,
+        ScheduledTaskRan { id, result }: ScheduledTaskRan,
+    ) {
+        let target = match self.catalog.try_get_by_id(id).map(|entry| entry.item()) {
+            Some(CatalogItem::Task(task)) => task.target,
+            // The task was dropped while it was running.
+            _ => return,
+        };
+        let result = result.and_then(|rows| {
+            self.check_constraints(target, &rows)
+                .map_err(|e| e.to_string())?;
+            let n = rows.len();
+            let timestamp = self.get_write_ts();
+            let updates = rows
+                .into_iter()
+                .map(|row| Update {
+                    row,
+                    diff: 1,
+                    timestamp,
+                })
+                .collect();
+            self.broadcast(SequencedCommand::Insert {
+                id: target,
+                updates,
+            });
+            Ok(n)
+        });
+        if let Err(e) = &result {
+            warn!("scheduled task {} failed: {}", id, e);
+        }
+        let updates = self.task_scheduler.finished(id, result);
+        self.update_catalog_view(MZ_TASK_STATUS.id, updates).await;
+    }
+
+    /// Drops the indexes of the lazy views that have gone unqueried for longer
+    /// than the idle timeout.
+    async fn drop_idle_lazy_views(&mut self) {
+        for index_id in self.lazy_views.idle_indexes() {
+            let ops = self.catalog.drop_items_ops(&[index_id]);
+            if let Err(e) = self.catalog_transact(ops).await {
+                warn!("unable to drop idle lazy view index {}: {}", index_id, e);
+            }
+        }
+    }
+
+    /// Drops the shared transient dataflows that have gone unused for longer
+    /// than the idle timeout.
+    async fn drop_idle_shared_dataflows(&mut self) {
+        let idle = match &mut self.shared_dataflows {
+            Some(shared_dataflows) => shared_dataflows.idle_indexes(),
+            None => return,
+        };
+        if !idle.is_empty() {
+            self.drop_indexes(idle).await;
+        }
+    }
+
     async fn message_command(&mut self, cmd: Command) {
+        // Attach the connection and, for executed statements, a unique
+        // statement ID to all log messages emitted while handling the command.
+        let span = match &cmd {
+            Command::Execute { .. } => {
+                self.statement_id_counter += 1;
+                tracing::info_span!(
+                    "statement",
+                    conn_id = cmd.conn_id(),
+                    statement_id = self.statement_id_counter
+                )
+            }
+            _ => tracing::info_span!("command", conn_id = cmd.conn_id()),
+        };
+        self.handle_command(cmd).instrument(span).await
+    }
+
+    async fn handle_command(&mut self, cmd: Command) {
         match cmd {
             Command::Startup {
-                session,
+                mut session,
                 cancel_tx,
                 tx,
             } => {
+                if let Err(e) = self.admit_conn(session.user()) {
+                    let _ = tx.send(Response {
+                        result: Err(e),
+                        session,
+                    });
+                    return;
+                }
+
                 if let Err(e) = self.catalog.create_temporary_schema(session.conn_id()) {
                     let _ = tx.send(Response {
                         result: Err(e.into()),
@@ -575,6 +1305,21 @@ impl Coordinator {
                     ));
                 }
 
+                // Install the defaults for configuration parameters. Role
+                // defaults take precedence over system defaults, and both
+                // yield to any values the client set at startup.
+                let defaults = self.catalog.system_parameters().iter().chain(
+                    self.catalog
+                        .role_parameters(session.user())
+                        .into_iter()
+                        .flatten(),
+                );
+                for (name, value) in defaults {
+                    if let Err(e) = session.vars_mut().set_default(name, value) {
+                        warn!("ignoring default for parameter {}: {}", name, e);
+                    }
+                }
+
                 let secret_key = rand::thread_rng().gen();
 
                 self.active_conns.insert(
@@ -582,6 +1327,7 @@ impl Coordinator {
                     ConnMeta {
                         cancel_tx,
                         secret_key,
+                        user: session.user().into(),
                     },
                 );
 
@@ -614,6 +1360,12 @@ impl Coordinator {
                 };
                 let stmt = portal.stmt.clone();
                 let params = portal.parameters.clone();
+                // A tag in the text of the statement takes precedence over the
+                // session's tag.
+                let tag = portal
+                    .tag
+                    .clone()
+                    .or_else(|| session.vars().query_tag().map(String::from));
                 match stmt {
                     Some(stmt) => {
                         // Verify that this statetement type can be executed in the current
@@ -705,6 +1457,12 @@ impl Coordinator {
                                 // Statements below must by run singly (in Started).
                                 Statement::AlterIndexOptions(_)
                                 | Statement::AlterObjectRename(_)
+                                | Statement::AlterSourceOptions(_)
+                                | Statement::AlterSourceSchema(_)
+                                | Statement::AlterRoleReset(_)
+                                | Statement::AlterRoleSet(_)
+                                | Statement::AlterSystemReset(_)
+                                | Statement::AlterSystemSet(_)
                                 | Statement::CreateDatabase(_)
                                 | Statement::CreateIndex(_)
                                 | Statement::CreateRole(_)
@@ -712,11 +1470,13 @@ impl Coordinator {
                                 | Statement::CreateSink(_)
                                 | Statement::CreateSource(_)
                                 | Statement::CreateTable(_)
+                                | Statement::CreateTask(_)
                                 | Statement::CreateType(_)
                                 | Statement::CreateView(_)
                                 | Statement::Delete(_)
                                 | Statement::DropDatabase(_)
                                 | Statement::DropObjects(_)
+                                | Statement::ResetVariable(_)
                                 | Statement::SetVariable(_)
                                 | Statement::Update(_) => {
                                     let _ = tx.send(Response {
@@ -739,6 +1499,7 @@ impl Coordinator {
                                     tx: ClientTransmitter::new(tx),
                                     result,
                                     params,
+                                    tag,
                                 }))
                                 .expect("sending to internal_cmd_tx cannot fail");
                         });
@@ -756,10 +1517,11 @@ impl Coordinator {
                 name,
                 stmt,
                 param_types,
+                tag,
                 mut session,
                 tx,
             } => {
-                let result = self.handle_declare(&mut session, name, stmt, param_types);
+                let result = self.handle_declare(&mut session, name, stmt, param_types, tag);
                 let _ = tx.send(Response { result, session });
             }
 
@@ -767,10 +1529,11 @@ impl Coordinator {
                 name,
                 stmt,
                 param_types,
+                tag,
                 mut session,
                 tx,
             } => {
-                let result = self.handle_describe(&mut session, name, stmt, param_types);
+                let result = self.handle_describe(&mut session, name, stmt, param_types, tag);
                 let _ = tx.send(Response { result, session });
             }
 
@@ -791,17 +1554,36 @@ impl Coordinator {
                 });
             }
 
+            Command::CheckReadiness { session, tx } => {
+                let budget_exhausted = self
+                    .rehydration_budget
+                    .map_or(false, |budget| self.start_time.elapsed() >= budget);
+                let mut rehydrating_sources: Vec<_> = self
+                    .rehydrating_sources
+                    .iter()
+                    .map(|id| self.catalog.get_by_id(id).name().to_string())
+                    .collect();
+                rehydrating_sources.sort();
+                let _ = tx.send(Response {
+                    result: Ok(Readiness {
+                        ready: rehydrating_sources.is_empty() || budget_exhausted,
+                        rehydrating_sources,
+                    }),
+                    session,
+                });
+            }
+
             Command::Terminate { mut session } => {
                 self.handle_terminate(&mut session).await;
             }
 
             Command::Commit {
                 action,
-                mut session,
+                session,
                 tx,
             } => {
-                let result = self.sequence_end_transaction(&mut session, action).await;
-                let _ = tx.send(Response { result, session });
+                self.sequence_end_transaction(ClientTransmitter::new(tx), session, action)
+                    .await;
             }
         }
     }
@@ -811,6 +1593,11 @@ impl Coordinator {
         if let Some(index_state) = self.indexes.get_mut(name) {
             let changes: Vec<_> = index_state.upper.update_iter(changes.drain()).collect();
             if !changes.is_empty() {
+                if let Some(watchdog) = &mut self.dataflow_watchdog {
+                    watchdog.frontier_advanced(*name);
+                }
+                self.freshness
+                    .upper_advanced(*name, index_state.upper.frontier(), (self.now)());
                 // Advance the compaction frontier to trail the new frontier.
                 // If the compaction latency is `None` compaction messages are
                 // not emitted, and the trace should be broadly useable.
@@ -859,11 +1646,32 @@ impl Coordinator {
             let since_updates = mem::take(&mut self.since_updates);
             self.broadcast(SequencedCommand::AllowCompaction(since_updates));
         }
+
+        if let Some(tuner) = &mut self.compaction_tuner {
+            let changed = tuner.tune(&mut self.indexes);
+            for id in changed {
+                self.report_compaction_window_update(id).await;
+            }
+        }
+
+        if let Some(watchdog) = &mut self.dataflow_watchdog {
+            let updates = watchdog.check(&self.indexes);
+            if !updates.is_empty() {
+                self.update_catalog_view(MZ_STALLED_DATAFLOWS.id, updates)
+                    .await;
+            }
+        }
+
+        let updates = self.freshness.report((self.now)());
+        if !updates.is_empty() {
+            self.update_catalog_view(MZ_VIEW_FRESHNESS.id, updates)
+                .await;
+        }
     }
 
     async fn handle_statement(
         &mut self,
-        session: &Session,
+        session: &mut Session,
         stmt: sql::ast::Statement<Raw>,
         params: &sql::plan::Params,
     ) -> Result<(PlanContext, sql::plan::Plan), CoordError> {
@@ -891,13 +1699,19 @@ impl Coordinator {
             }
         }
 
-        match sql::plan::plan(
+        let res = sql::plan::plan(
             &pcx,
             &self.catalog.for_session(session),
             stmt.clone(),
             params,
-        ) {
-            Ok(plan) => Ok((pcx, plan)),
+        );
+        match res {
+            Ok((plan, notices)) => {
+                for notice in notices {
+                    session.add_notice(Notice::Plan(notice));
+                }
+                Ok((pcx, plan))
+            }
             Err(err) => match self.symbiosis {
                 Some(ref mut postgres) if postgres.can_handle(&stmt) => {
                     let plan = postgres
@@ -916,6 +1730,7 @@ impl Coordinator {
         name: String,
         stmt: Statement<Raw>,
         param_types: Vec<Option<pgrepr::Type>>,
+        tag: Option<String>,
     ) -> Result<(), CoordError> {
         // handle_describe cares about symbiosis mode here. Declared cursors are
         // perhaps rare enough we can ignore that worry and just error instead.
@@ -927,7 +1742,7 @@ impl Coordinator {
         )?;
         let params = vec![];
         let result_formats = vec![pgrepr::Format::Text; desc.arity()];
-        session.set_portal(name, desc, Some(stmt), params, result_formats)?;
+        session.set_portal(name, desc, Some(stmt), params, result_formats, tag)?;
         Ok(())
     }
 
@@ -937,6 +1752,7 @@ impl Coordinator {
         name: String,
         stmt: Option<Statement<Raw>>,
         param_types: Vec<Option<pgrepr::Type>>,
+        tag: Option<String>,
     ) -> Result<(), CoordError> {
         let desc = if let Some(stmt) = stmt.clone() {
             match describe(
@@ -958,7 +1774,7 @@ impl Coordinator {
         } else {
             StatementDesc::new(None)
         };
-        session.set_prepared_statement(name, PreparedStatement::new(stmt, desc));
+        session.set_prepared_statement(name, PreparedStatement::new(stmt, desc, tag));
         Ok(())
     }
 
@@ -976,6 +1792,10 @@ impl Coordinator {
             // Tell dataflow to cancel any pending peeks.
             self.broadcast(SequencedCommand::CancelPeek { conn_id });
 
+            // Stop waiting for any indexes that the connection created to
+            // hydrate.
+            self.hydration_waiters.cancel(conn_id);
+
             // Inform the target session (if it asks) about the cancellation.
             let _ = conn_meta.cancel_tx.send(Cancelled::Cancelled);
         }
@@ -1145,6 +1965,8 @@ impl Coordinator {
                         Datum::Int64(i as i64 + 1),
                         Datum::from(column_type.nullable),
                         Datum::String(pgrepr::Type::from(&column_type.scalar_type).name()),
+                        Datum::Int32(pgrepr::Type::from(&column_type.scalar_type).oid() as i32),
+                        Datum::Int32(pgrepr::typmod(&column_type.scalar_type)),
                     ]),
                     diff,
                 )),
@@ -1291,6 +2113,40 @@ impl Coordinator {
         .await
     }
 
+    async fn report_source_timestamping_update(
+        &mut self,
+        global_id: GlobalId,
+        source: &catalog::Source,
+        diff: isize,
+    ) {
+        let (consistency, ts_frequency) = match &source.connector {
+            SourceConnector::External {
+                consistency,
+                ts_frequency,
+                ..
+            } => (consistency, ts_frequency),
+            SourceConnector::Local => return,
+        };
+        let (consistency, topic) = match consistency {
+            Consistency::RealTime => ("real-time", None),
+            Consistency::BringYourOwn(topic) => ("byo", Some(topic.as_str())),
+        };
+        let ts_frequency_ms = i64::try_from(ts_frequency.as_millis()).unwrap_or(i64::MAX);
+        self.update_catalog_view(
+            MZ_SOURCE_TIMESTAMPING.id,
+            iter::once((
+                Row::pack_slice(&[
+                    Datum::String(&global_id.to_string()),
+                    Datum::String(consistency),
+                    Datum::from(topic),
+                    Datum::Int64(ts_frequency_ms),
+                ]),
+                diff,
+            )),
+        )
+        .await
+    }
+
     async fn report_view_update(
         &mut self,
         global_id: GlobalId,
@@ -1386,21 +2242,53 @@ impl Coordinator {
         .await
     }
 
-    async fn report_func_update(
+    async fn report_task_update(
         &mut self,
         id: GlobalId,
+        oid: u32,
         schema_id: i64,
         name: &str,
-        func: &Func,
+        task: &catalog::Task,
         diff: isize,
     ) {
-        for func_impl_details in func.inner.func_impls() {
-            let arg_ids = func_impl_details
-                .arg_oids
-                .iter()
-                .map(|oid| self.catalog.get_by_oid(oid).id().to_string())
-                .collect::<Vec<_>>();
-            let mut packer = RowPacker::new();
+        let schedule_ms = i64::try_from(task.schedule.as_millis()).unwrap_or(i64::MAX);
+        self.update_catalog_view(
+            MZ_TASKS.id,
+            iter::once((
+                Row::pack_slice(&[
+                    Datum::String(&id.to_string()),
+                    Datum::Int32(oid as i32),
+                    Datum::Int64(schema_id),
+                    Datum::String(name),
+                    Datum::String(&task.target.to_string()),
+                    Datum::Int64(schedule_ms),
+                ]),
+                diff,
+            )),
+        )
+        .await
+    }
+
+    async fn report_func_update(
+        &mut self,
+        id: GlobalId,
+        schema_id: i64,
+        name: &str,
+        func: &Func,
+        diff: isize,
+    ) {
+        let kind = match func.inner {
+            sql::func::Func::Scalar(_) => "scalar",
+            sql::func::Func::Aggregate(_) => "aggregate",
+            sql::func::Func::Table(_) => "table",
+        };
+        for func_impl_details in func.inner.func_impls() {
+            let arg_ids = func_impl_details
+                .arg_oids
+                .iter()
+                .map(|oid| self.catalog.get_by_oid(oid).id().to_string())
+                .collect::<Vec<_>>();
+            let mut packer = RowPacker::new();
             packer
                 .push_array(
                     &[ArrayDimension {
@@ -1410,8 +2298,22 @@ impl Coordinator {
                     arg_ids.iter().map(|id| Datum::String(&id)),
                 )
                 .unwrap();
+            packer
+                .push_array(
+                    &[ArrayDimension {
+                        lower_bound: 1,
+                        length: func_impl_details.arg_oids.len(),
+                    }],
+                    func_impl_details
+                        .arg_oids
+                        .iter()
+                        .map(|oid| Datum::Int32(*oid as i32)),
+                )
+                .unwrap();
             let row = packer.finish();
-            let arg_ids = row.unpack_first();
+            let mut arrays = row.iter();
+            let arg_ids = arrays.next().unwrap();
+            let arg_oids = arrays.next().unwrap();
 
             let variadic_id = match func_impl_details.variadic_oid {
                 Some(oid) => Some(self.catalog.get_by_oid(&oid).id().to_string()),
@@ -1428,6 +2330,8 @@ impl Coordinator {
                         Datum::String(name),
                         arg_ids,
                         Datum::from(variadic_id.as_deref()),
+                        arg_oids,
+                        Datum::String(kind),
                     ]),
                     diff,
                 )),
@@ -1487,9 +2391,18 @@ impl Coordinator {
                 source,
                 if_not_exists,
                 materialized,
+                depends_on,
             } => tx.send(
-                self.sequence_create_source(pcx, name, source, if_not_exists, materialized)
-                    .await,
+                self.sequence_create_source(
+                    pcx,
+                    name,
+                    source,
+                    if_not_exists,
+                    materialized,
+                    depends_on,
+                    session.conn_id(),
+                )
+                .await,
                 session,
             ),
 
@@ -1520,20 +2433,21 @@ impl Coordinator {
                 materialize,
                 if_not_exists,
                 depends_on,
-            } => tx.send(
-                self.sequence_create_view(
-                    pcx,
-                    name,
-                    view,
-                    replace,
-                    session.conn_id(),
-                    materialize,
-                    if_not_exists,
-                    depends_on,
-                )
-                .await,
-                session,
-            ),
+            } => {
+                let result = self
+                    .sequence_create_view(
+                        pcx,
+                        name,
+                        view,
+                        replace,
+                        session.conn_id(),
+                        materialize,
+                        if_not_exists,
+                        depends_on,
+                    )
+                    .await;
+                self.send_after_hydration(tx, session, result);
+            }
 
             Plan::CreateIndex {
                 name,
@@ -1541,11 +2455,20 @@ impl Coordinator {
                 options,
                 if_not_exists,
                 depends_on,
-            } => tx.send(
-                self.sequence_create_index(pcx, name, index, options, if_not_exists, depends_on)
-                    .await,
-                session,
-            ),
+            } => {
+                let result = self
+                    .sequence_create_index(
+                        pcx,
+                        name,
+                        index,
+                        options,
+                        if_not_exists,
+                        depends_on,
+                        session.conn_id(),
+                    )
+                    .await;
+                self.send_after_hydration(tx, session, result);
+            }
 
             Plan::CreateType {
                 name,
@@ -1556,6 +2479,17 @@ impl Coordinator {
                 session,
             ),
 
+            Plan::CreateTask {
+                name,
+                task,
+                if_not_exists,
+                depends_on,
+            } => tx.send(
+                self.sequence_create_task(pcx, name, task, if_not_exists, depends_on)
+                    .await,
+                session,
+            ),
+
             Plan::DropDatabase { name } => {
                 tx.send(self.sequence_drop_database(name).await, session)
             }
@@ -1583,6 +2517,33 @@ impl Coordinator {
                 session,
             ),
 
+            Plan::AlterSystemSet { name, value } => {
+                tx.send(self.sequence_alter_system(name, Some(value)).await, session)
+            }
+
+            Plan::AlterSystemReset { name } => {
+                tx.send(self.sequence_alter_system(name, None).await, session)
+            }
+
+            Plan::ResetVariable { name } => tx.send(
+                self.sequence_reset_variable(&mut session, name).await,
+                session,
+            ),
+
+            Plan::AlterRoleSet {
+                role_name,
+                name,
+                value,
+            } => tx.send(
+                self.sequence_alter_role(role_name, name, Some(value)).await,
+                session,
+            ),
+
+            Plan::AlterRoleReset { role_name, name } => tx.send(
+                self.sequence_alter_role(role_name, name, None).await,
+                session,
+            ),
+
             Plan::StartTransaction => {
                 session.start_transaction();
                 tx.send(Ok(ExecuteResponse::StartedTransaction), session)
@@ -1594,10 +2555,7 @@ impl Coordinator {
                     Plan::AbortTransaction => EndTransactionAction::Rollback,
                     _ => unreachable!(),
                 };
-                tx.send(
-                    self.sequence_end_transaction(&mut session, action).await,
-                    session,
-                )
+                self.sequence_end_transaction(tx, session, action).await
             }
 
             Plan::Peek {
@@ -1606,7 +2564,7 @@ impl Coordinator {
                 finishing,
                 copy_to,
             } => tx.send(
-                self.sequence_peek(session.conn_id(), source, when, finishing, copy_to)
+                self.sequence_peek(&mut session, source, when, finishing, copy_to)
                     .await,
                 session,
             ),
@@ -1619,6 +2577,8 @@ impl Coordinator {
                 emit_progress,
                 object_columns,
                 desc,
+                envelope,
+                key_indices,
             } => tx.send(
                 self.sequence_tail(
                     &mut session,
@@ -1629,6 +2589,8 @@ impl Coordinator {
                     emit_progress,
                     object_columns,
                     desc,
+                    envelope,
+                    key_indices,
                 )
                 .await,
                 session,
@@ -1642,6 +2604,7 @@ impl Coordinator {
                 row_set_finishing,
                 stage,
                 options,
+                prefer_delta_joins,
             } => tx.send(
                 self.sequence_explain_plan(
                     &session,
@@ -1650,10 +2613,15 @@ impl Coordinator {
                     row_set_finishing,
                     stage,
                     options,
+                    prefer_delta_joins,
                 ),
                 session,
             ),
 
+            Plan::ExplainDataflow { id } => {
+                tx.send(self.sequence_explain_dataflow(&session, id), session)
+            }
+
             Plan::SendDiffs {
                 id,
                 updates,
@@ -1684,15 +2652,20 @@ impl Coordinator {
                 session,
             ),
 
-            Plan::AlterIndexSetOptions { id, options } => {
-                tx.send(self.sequence_alter_index_set_options(id, options), session)
-            }
+            Plan::AlterIndexSetOptions { id, options } => tx.send(
+                self.sequence_alter_index_set_options(id, options).await,
+                session,
+            ),
 
             Plan::AlterIndexResetOptions { id, options } => tx.send(
-                self.sequence_alter_index_reset_options(id, options),
+                self.sequence_alter_index_reset_options(id, options).await,
                 session,
             ),
 
+            Plan::AlterSource { id, source } => {
+                self.sequence_alter_source(session, tx, id, source).await
+            }
+
             Plan::DiscardTemp => {
                 self.drop_temp_items(session.conn_id()).await;
                 tx.send(Ok(ExecuteResponse::DiscardedTemp), session);
@@ -1804,6 +2777,7 @@ impl Coordinator {
         depends_on: Vec<GlobalId>,
         conn_id: u32,
     ) -> Result<ExecuteResponse, CoordError> {
+        self.check_dataflow_quota(&name, conn_id, 1)?;
         let conn_id = if table.temporary { Some(conn_id) } else { None };
         let table_id = self.catalog.allocate_id()?;
         let mut index_depends_on = depends_on.clone();
@@ -1813,6 +2787,7 @@ impl Coordinator {
             plan_cx: pcx,
             desc: table.desc,
             defaults: table.defaults,
+            checks: table.checks,
             conn_id,
             depends_on,
         };
@@ -1863,10 +2838,17 @@ impl Coordinator {
         source: sql::plan::Source,
         if_not_exists: bool,
         materialized: bool,
+        depends_on: Vec<GlobalId>,
+        conn_id: u32,
     ) -> Result<ExecuteResponse, CoordError> {
-        let optimized_expr = self
-            .optimizer
-            .optimize(source.expr, self.catalog.indexes())?;
+        if materialized {
+            self.check_dataflow_quota(&name, conn_id, 1)?;
+        }
+        let optimized_expr = self.optimizer.optimize(
+            source.expr,
+            self.catalog.indexes(),
+            self.catalog.foreign_keys(),
+        )?;
         let transformed_desc = RelationDesc::new(optimized_expr.0.typ(), source.column_names);
         let source = catalog::Source {
             create_sql: source.create_sql,
@@ -1875,6 +2857,8 @@ impl Coordinator {
             connector: source.connector,
             bare_desc: source.bare_desc,
             desc: transformed_desc,
+            foreign_keys: source.foreign_keys,
+            depends_on,
         };
         let source_id = self.catalog.allocate_id()?;
         let source_oid = self.catalog.allocate_oid()?;
@@ -1935,6 +2919,11 @@ impl Coordinator {
         if_not_exists: bool,
         depends_on: Vec<GlobalId>,
     ) {
+        if let Err(e) = self.check_dataflow_quota(&name, session.conn_id(), 1) {
+            tx.send(Err(e), session);
+            return;
+        }
+
         // First try to allocate an ID and an OID. If either fails, we're done.
         let id = match self.catalog.allocate_id() {
             Ok(id) => id,
@@ -2011,7 +3000,15 @@ impl Coordinator {
         materialize: bool,
         if_not_exists: bool,
         depends_on: Vec<GlobalId>,
-    ) -> Result<ExecuteResponse, CoordError> {
+    ) -> Result<(ExecuteResponse, Option<(GlobalId, Timestamp)>), CoordError> {
+        if view.lazy && !materialize {
+            coord_bail!("only materialized views can be lazy");
+        }
+        // Lazy views are not materialized until they are queried.
+        let materialize = materialize && !view.lazy;
+        if materialize {
+            self.check_dataflow_quota(&name, conn_id, 1)?;
+        }
         let mut ops = vec![];
         if let Some(id) = replace {
             ops.extend(self.catalog.drop_items_ops(&[id]));
@@ -2019,7 +3016,8 @@ impl Coordinator {
         let view_id = self.catalog.allocate_id()?;
         let view_oid = self.catalog.allocate_oid()?;
         // Optimize the expression so that we can form an accurately typed description.
-        let optimized_expr = self.prep_relation_expr(view.expr, ExprPrepStyle::Static)?;
+        let optimized_expr =
+            self.prep_relation_expr(view.expr, ExprPrepStyle::Static, view.prefer_delta_joins)?;
         let desc = RelationDesc::new(optimized_expr.as_ref().typ(), view.column_names);
         let view = catalog::View {
             create_sql: view.create_sql,
@@ -2028,6 +3026,9 @@ impl Coordinator {
             desc,
             conn_id: if view.temporary { Some(conn_id) } else { None },
             depends_on,
+            lazy: view.lazy,
+            reduce_hot_set_size: view.reduce_hot_set_size,
+            foreign_keys: view.foreign_keys,
         };
         ops.push(catalog::Op::CreateItem {
             id: view_id,
@@ -2060,17 +3061,23 @@ impl Coordinator {
         };
         match self.catalog_transact(ops).await {
             Ok(()) => {
-                if let Some(index_id) = index_id {
-                    self.ship_dataflow(self.dataflow_builder().build_index_dataflow(index_id))
-                        .await?;
-                }
-                Ok(ExecuteResponse::CreatedView { existed: false })
+                let hydration = match index_id {
+                    Some(index_id) => {
+                        let dataflow = self.dataflow_builder().build_index_dataflow(index_id);
+                        let target = self.hydration_target(&dataflow);
+                        self.ship_dataflow(dataflow).await?;
+                        Some((index_id, target))
+                    }
+                    None => None,
+                };
+                Ok((ExecuteResponse::CreatedView { existed: false }, hydration))
             }
-            Err(_) if if_not_exists => Ok(ExecuteResponse::CreatedView { existed: true }),
+            Err(_) if if_not_exists => Ok((ExecuteResponse::CreatedView { existed: true }, None)),
             Err(err) => Err(err),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn sequence_create_index(
         &mut self,
         pcx: PlanContext,
@@ -2079,17 +3086,27 @@ impl Coordinator {
         options: Vec<IndexOption>,
         if_not_exists: bool,
         depends_on: Vec<GlobalId>,
-    ) -> Result<ExecuteResponse, CoordError> {
+        session_conn_id: u32,
+    ) -> Result<(ExecuteResponse, Option<(GlobalId, Timestamp)>), CoordError> {
+        self.check_dataflow_quota(&name, session_conn_id, 1)?;
         for key in &mut index.keys {
             Self::prep_scalar_expr(key, ExprPrepStyle::Static)?;
         }
+        for predicate in &mut index.predicates {
+            Self::prep_scalar_expr(predicate, ExprPrepStyle::Static)?;
+        }
+        // An index on a temporary item lives in the same temporary schema as
+        // the item it indexes, and so must share its lifetime.
+        let conn_id = self.catalog.get_by_id(&index.on).item().conn_id();
         let index = catalog::Index {
             create_sql: index.create_sql,
             plan_cx: pcx,
             keys: index.keys,
+            predicates: index.predicates,
             on: index.on,
-            conn_id: None,
+            conn_id,
             depends_on,
+            lazy: false,
         };
         let id = self.catalog.allocate_id()?;
         let oid = self.catalog.allocate_oid()?;
@@ -2101,16 +3118,69 @@ impl Coordinator {
         };
         match self.catalog_transact(vec![op]).await {
             Ok(()) => {
-                self.ship_dataflow(self.dataflow_builder().build_index_dataflow(id))
-                    .await?;
-                self.set_index_options(id, options);
-                Ok(ExecuteResponse::CreatedIndex { existed: false })
+                let mut dataflow = self.dataflow_builder().build_index_dataflow(id);
+                for option in &options {
+                    if let IndexOption::RefreshInterval(interval) = option {
+                        dataflow.refresh_interval = interval.map(duration_to_timestamp_millis);
+                    }
+                }
+                let target = self.hydration_target(&dataflow);
+                self.ship_dataflow(dataflow).await?;
+                self.set_index_options(id, options).await?;
+                Ok((
+                    ExecuteResponse::CreatedIndex { existed: false },
+                    Some((id, target)),
+                ))
             }
-            Err(_) if if_not_exists => Ok(ExecuteResponse::CreatedIndex { existed: true }),
+            Err(_) if if_not_exists => Ok((ExecuteResponse::CreatedIndex { existed: true }, None)),
             Err(err) => Err(err),
         }
     }
 
+    /// Sends the result of a statement that may have created an index to
+    /// the client.
+    ///
+    /// If the session has enabled `wait_for_hydration` and the statement
+    /// created the index `id`, the result is instead sent once the upper
+    /// frontier of the index advances beyond `target`.
+    fn send_after_hydration(
+        &mut self,
+        tx: ClientTransmitter<ExecuteResponse>,
+        session: Session,
+        result: Result<(ExecuteResponse, Option<(GlobalId, Timestamp)>), CoordError>,
+    ) {
+        match result {
+            Ok((response, Some((id, target)))) if session.vars().wait_for_hydration() => {
+                self.hydration_waiters
+                    .wait(id, target, response, tx, session);
+                self.hydration_waiters.notify(&self.indexes);
+            }
+            Ok((response, _)) => tx.send(Ok(response), session),
+            Err(e) => tx.send(Err(e), session),
+        }
+    }
+
+    /// Returns the time beyond which the upper frontier of the index
+    /// maintained by `dataflow` must advance for the index to be hydrated.
+    ///
+    /// The index must catch up to the current upper frontiers of the indexes
+    /// it reads from. Sources are timestamped in real time, so an index that
+    /// reads from sources must also catch up to the current time.
+    fn hydration_target(&self, dataflow: &DataflowDesc) -> Timestamp {
+        let mut target = 0;
+        for id in dataflow.index_imports.keys() {
+            if let Some(upper) = self.indexes.upper_of(id) {
+                if let Some(upper) = upper.iter().next() {
+                    target = cmp::max(target, upper.saturating_sub(1));
+                }
+            }
+        }
+        if !dataflow.source_imports.is_empty() {
+            target = cmp::max(target, (self.now)());
+        }
+        target
+    }
+
     async fn sequence_create_type(
         &mut self,
         pcx: PlanContext,
@@ -2138,6 +3208,37 @@ impl Coordinator {
         }
     }
 
+    async fn sequence_create_task(
+        &mut self,
+        pcx: PlanContext,
+        name: FullName,
+        task: sql::plan::Task,
+        if_not_exists: bool,
+        depends_on: Vec<GlobalId>,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let task = catalog::Task {
+            create_sql: task.create_sql,
+            plan_cx: pcx,
+            target: task.target,
+            query: task.query,
+            schedule: task.schedule,
+            depends_on,
+        };
+        let id = self.catalog.allocate_id()?;
+        let oid = self.catalog.allocate_oid()?;
+        let op = catalog::Op::CreateItem {
+            id,
+            oid,
+            name,
+            item: CatalogItem::Task(task),
+        };
+        match self.catalog_transact(vec![op]).await {
+            Ok(()) => Ok(ExecuteResponse::CreatedTask { existed: false }),
+            Err(_) if if_not_exists => Ok(ExecuteResponse::CreatedTask { existed: true }),
+            Err(err) => Err(err),
+        }
+    }
+
     async fn sequence_drop_database(
         &mut self,
         name: String,
@@ -2191,6 +3292,7 @@ impl Coordinator {
             ObjectType::View => ExecuteResponse::DroppedView,
             ObjectType::Table => ExecuteResponse::DroppedTable,
             ObjectType::Sink => ExecuteResponse::DroppedSink,
+            ObjectType::Task => ExecuteResponse::DroppedTask,
             ObjectType::Index => ExecuteResponse::DroppedIndex,
             ObjectType::Type => ExecuteResponse::DroppedType,
             ObjectType::Role => unreachable!("DROP ROLE not supported"),
@@ -2238,11 +3340,75 @@ impl Coordinator {
         Ok(ExecuteResponse::SetVariable { name })
     }
 
+    async fn sequence_reset_variable(
+        &self,
+        session: &mut Session,
+        name: String,
+    ) -> Result<ExecuteResponse, CoordError> {
+        session.vars_mut().reset(&name)?;
+        Ok(ExecuteResponse::ResetVariable { name })
+    }
+
+    /// Sets the system parameter `name` to `value`, or resets it to its
+    /// startup value if `value` is `None`.
+    ///
+    /// With the exception of `log_filter`, which applies only to the running
+    /// process, system parameters are durably recorded in the catalog as the
+    /// defaults for the configuration parameters of new sessions.
+    async fn sequence_alter_system(
+        &mut self,
+        name: String,
+        value: Option<String>,
+    ) -> Result<ExecuteResponse, CoordError> {
+        if name == "log_filter" {
+            let log_filter = match &self.log_filter {
+                Some(log_filter) => log_filter,
+                None => coord_bail!("log_filter cannot be changed in this process"),
+            };
+            match value {
+                Some(value) => log_filter.set(&value)?,
+                None => log_filter.reset()?,
+            }
+        } else if value.is_none() && name.eq_ignore_ascii_case("all") {
+            self.catalog_transact(vec![catalog::Op::AlterSystemResetAll])
+                .await?;
+        } else {
+            let name = validate_parameter_default(&name, value.as_deref())?;
+            self.catalog_transact(vec![catalog::Op::AlterSystemSet { name, value }])
+                .await?;
+        }
+        Ok(ExecuteResponse::AlteredSystemConfiguration)
+    }
+
+    /// Sets the default for the configuration parameter `name` for the role
+    /// named `role_name` to `value`, or removes the role default if `value`
+    /// is `None`.
+    async fn sequence_alter_role(
+        &mut self,
+        role_name: String,
+        name: String,
+        value: Option<String>,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let name = validate_parameter_default(&name, value.as_deref())?;
+        self.catalog_transact(vec![catalog::Op::AlterRoleSet {
+            role_name,
+            name,
+            value,
+        }])
+        .await?;
+        Ok(ExecuteResponse::AlteredObject(ObjectType::Role))
+    }
+
+    /// Ends the transaction of `session`.
+    ///
+    /// If the transaction is committed and performed writes, the response is
+    /// not sent until the next group commit applies those writes.
     async fn sequence_end_transaction(
         &mut self,
-        session: &mut Session,
+        tx: ClientTransmitter<ExecuteResponse>,
+        mut session: Session,
         action: EndTransactionAction,
-    ) -> Result<ExecuteResponse, CoordError> {
+    ) {
         let was_implicit = matches!(
             session.transaction(),
             TransactionStatus::InTransactionImplicit(_)
@@ -2251,63 +3417,168 @@ impl Coordinator {
         let (drop_sinks, txn) = session.clear_transaction();
         self.drop_sinks(drop_sinks).await;
 
+        let response = ExecuteResponse::TransactionExited {
+            tag: action.tag(),
+            was_implicit,
+        };
+
         if let EndTransactionAction::Commit = action {
             match txn {
                 TransactionStatus::Default | TransactionStatus::Failed => {}
                 TransactionStatus::Started(ops)
                 | TransactionStatus::InTransaction(ops)
                 | TransactionStatus::InTransactionImplicit(ops) => {
-                    if let TransactionOps::Writes(inserts) = ops {
-                        let timestamp = self.get_write_ts();
-                        for WriteOp { id, rows } in inserts {
-                            // Re-verify this id exists.
-                            if self.catalog.try_get_by_id(id).is_none() {
-                                return Err(CoordError::SqlCatalog(CatalogError::UnknownItem(
+                    if let TransactionOps::Writes(writes) = ops {
+                        // Verify the ids exist now, so that the common error
+                        // is reported without waiting for the group commit.
+                        for WriteOp { id, .. } in &writes {
+                            if self.catalog.try_get_by_id(*id).is_none() {
+                                let e = CoordError::SqlCatalog(CatalogError::UnknownItem(
                                     id.to_string(),
-                                )));
+                                ));
+                                tx.send(Err(e), session);
+                                return;
                             }
-
-                            let updates = rows
-                                .into_iter()
-                                .map(|(row, diff)| Update {
-                                    row,
-                                    diff,
-                                    timestamp,
-                                })
-                                .collect();
-
-                            self.broadcast(SequencedCommand::Insert { id, updates });
                         }
+                        self.pending_writes.push(PendingWriteTxn {
+                            writes,
+                            response,
+                            tx,
+                            session,
+                        });
+                        return;
                     }
                 }
             }
         }
 
-        Ok(ExecuteResponse::TransactionExited {
-            tag: action.tag(),
-            was_implicit,
-        })
+        tx.send(Ok(response), session);
     }
 
-    async fn sequence_peek(
+    /// Materializes the lazy views that `source` reads from that are not
+    /// already materialized, and records that they were queried.
+    async fn materialize_lazy_views(
         &mut self,
+        source: &MirRelationExpr,
         conn_id: u32,
+    ) -> Result<(), CoordError> {
+        let mut to_materialize = vec![];
+        let mut seen = HashSet::new();
+        let mut ids = source.global_uses();
+        while let Some(id) = ids.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            let view = match self.catalog.get_by_id(&id).item() {
+                CatalogItem::View(view) => view,
+                _ => continue,
+            };
+            let indexed = !self.catalog.indexes()[&id].is_empty();
+            if view.lazy {
+                self.lazy_views.touch(id);
+                if !indexed {
+                    to_materialize.push(id);
+                }
+            } else if !indexed {
+                // Unmaterialized views are inlined into the dataflow that
+                // reads from them, so the lazy views that they read from are
+                // read from too.
+                ids.extend(view.depends_on.iter().copied());
+            }
+        }
+
+        for id in to_materialize {
+            let entry = self.catalog.get_by_id(&id);
+            let name = entry.name().clone();
+            let view = match entry.item() {
+                CatalogItem::View(view) => view.clone(),
+                _ => unreachable!("lazy view {} is not a view", id),
+            };
+            let mut index_name = name.clone();
+            index_name.item = lazy_views::index_name(&name.item);
+            self.check_dataflow_quota(&index_name, conn_id, 1)?;
+            let mut index = auto_generate_primary_idx(
+                index_name.item.clone(),
+                name,
+                id,
+                &view.desc,
+                view.conn_id,
+                vec![id],
+            );
+            index.lazy = true;
+            let index_id = self.catalog.allocate_id()?;
+            let index_oid = self.catalog.allocate_oid()?;
+            self.catalog_transact(vec![catalog::Op::CreateItem {
+                id: index_id,
+                oid: index_oid,
+                name: index_name,
+                item: CatalogItem::Index(index),
+            }])
+            .await?;
+            let dataflow = self.dataflow_builder().build_index_dataflow(index_id);
+            // Sources assign new timestamps to the data they read, so there
+            // is no time at which an index on a source is known to have
+            // caught up until its upper frontier advances.
+            if dataflow.source_imports.is_empty() {
+                self.lazy_views
+                    .index_hydrating(index_id, self.hydration_target(&dataflow));
+            }
+            self.ship_dataflow(dataflow).await?;
+        }
+        Ok(())
+    }
+
+    async fn sequence_peek(
+        &mut self,
+        session: &mut Session,
         source: MirRelationExpr,
         when: PeekWhen,
         finishing: RowSetFinishing,
         copy_to: Option<CopyFormat>,
     ) -> Result<ExecuteResponse, CoordError> {
-        let timestamp = self.determine_timestamp(&source, when)?;
+        let conn_id = session.conn_id();
+        let peek_guard = self.admit_peek(session.user())?;
+        self.materialize_lazy_views(&source, conn_id).await?;
+
+        // A partial index can serve the peek on its own, but the optimizer does
+        // not consider partial indexes, so the timestamp must account for it
+        // explicitly when the query's inputs are not otherwise indexed.
+        let partial_index = self.applicable_partial_index(&source)?;
+        let uses_ids = source.global_uses();
+        let (index_ids, indexes_complete) = self.catalog.nearest_indexes(&uses_ids);
+        let timestamp = match &partial_index {
+            Some((partial_id, _)) if !indexes_complete => {
+                self.determine_timestamp_for(&uses_ids, &[*partial_id], true, when)?
+            }
+            _ => self.determine_timestamp_for(&uses_ids, &index_ids, indexes_complete, when)?,
+        };
 
         let source = self.prep_relation_expr(
             source,
             ExprPrepStyle::OneShot {
                 logical_time: timestamp,
             },
+            session.vars().prefer_delta_joins(),
         )?;
 
+        // Identical peeks at the same timestamp produce identical results, and
+        // so can share them if the session allows it.
+        let cache_key = if session.vars().enable_peek_result_cache() {
+            Some((source.as_ref().clone(), finishing.clone()))
+        } else {
+            None
+        };
+        let cached_rows = cache_key.as_ref().and_then(|key| {
+            self.peek_cache
+                .lock()
+                .expect("lock poisoned")
+                .get(timestamp, key)
+        });
+
         // If this optimizes to a constant expression, we can immediately return the result.
-        let resp = if let MirRelationExpr::Constant { rows, typ: _ } = source.as_ref() {
+        let resp = if let Some(rows) = cached_rows {
+            send_immediate_rows(rows)
+        } else if let MirRelationExpr::Constant { rows, typ: _ } = source.as_ref() {
             let rows = match rows {
                 Ok(rows) => rows,
                 Err(e) => return Err(e.clone().into()),
@@ -2335,8 +3606,7 @@ impl Coordinator {
             // Choose a timestamp for all workers to use in the peek.
             // We minimize over all participating views, to ensure that the query will not
             // need to block on the arrival of further input data.
-            let (rows_tx, rows_rx) = mpsc::unbounded_channel();
-
+            //
             // Extract any surrounding linear operators to determine if we can simply read
             // out the contents from an existing arrangement.
             let (mut map_filter_project, inner) =
@@ -2357,84 +3627,174 @@ impl Coordinator {
                 // values by predicate constraints in `map_filter_project`. If we find such
                 // an index, we can use it with the literal to perform look-ups at workers,
                 // and in principle avoid even contacting all but one worker (future work).
-                if let Some(indexes) = self.catalog.indexes().get(id) {
-                    // Determine for each index identifier, an optional row literal as key.
-                    // We want to extract the "best" option, where we prefer indexes with
-                    // literals and long keys, then indexes at all, then exit correctly.
-                    fast_path = indexes
-                        .iter()
-                        .map(|(id, exprs)| {
-                            let literal_row = map_filter_project.literal_constraints(exprs);
-                            // Prefer non-trivial literal rows foremost, then long expressions,
-                            // then we don't really care at that point.
-                            (literal_row.is_some(), exprs.len(), literal_row, *id)
-                        })
-                        .max()
-                        .map(|(_some, _len, literal, id)| (id, literal));
+                let mut indexes = self
+                    .catalog
+                    .indexes()
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .map(|(id, exprs)| (*id, exprs, false))
+                    .collect::<Vec<_>>();
+                // A partial index is only a candidate if it is valid at the chosen
+                // timestamp, which may not be the case if the timestamp was driven by
+                // other indexes.
+                if let Some((partial_id, keys)) = &partial_index {
+                    let valid = self
+                        .indexes
+                        .since_of(partial_id)
+                        .map(|since| since.less_equal(&timestamp))
+                        .unwrap_or(false);
+                    if valid {
+                        indexes.push((*partial_id, keys, true));
+                    }
+                }
+                // Determine for each index identifier, an optional row literal as key.
+                // We want to extract the "best" option, where we prefer indexes with
+                // literals, then partial indexes, then long keys, then indexes at all,
+                // then exit correctly.
+                fast_path = indexes
+                    .into_iter()
+                    .map(|(id, exprs, partial)| {
+                        let literal_row = map_filter_project.literal_constraints(exprs);
+                        // Prefer non-trivial literal rows foremost, then partial indexes,
+                        // then long expressions, then we don't really care at that point.
+                        (literal_row.is_some(), partial, exprs.len(), literal_row, id)
+                    })
+                    .max()
+                    .map(|(_some, _partial, _len, literal, id)| (id, literal));
+
+                // Let the user know if the query filters a relation but none
+                // of the relation's indexes could be used to look up the rows
+                // that pass the filter, as the query then scans every row.
+                if let Some((index_id, None)) = &fast_path {
+                    let partial =
+                        matches!(&partial_index, Some((partial_id, _)) if partial_id == index_id);
+                    if !partial && id.is_user() && !map_filter_project.predicates.is_empty() {
+                        let relation = self
+                            .catalog
+                            .for_session(session)
+                            .humanize_id(*id)
+                            .unwrap_or_else(|| id.to_string());
+                        session.add_notice(Notice::IndexFullScan { relation });
+                    }
                 }
             }
 
             // Unpack what we have learned with default values if we found nothing.
-            let (fast_path, index_id, literal_row) = if let Some((id, row)) = fast_path {
+            let (fast_path, mut index_id, literal_row) = if let Some((id, row)) = fast_path {
                 (true, id, row)
             } else {
                 (false, self.allocate_transient_id()?, None)
             };
 
+            let mut shared_guard = None;
+            let mut transient = false;
             if !fast_path {
-                // Slow path. We need to perform some computation, so build
-                // a new transient dataflow that will be dropped after the
-                // peek completes.
-                let typ = source.as_ref().typ();
-                map_filter_project = expr::MapFilterProject::new(typ.arity());
-                let key: Vec<_> = (0..typ.arity()).map(MirScalarExpr::Column).collect();
-                let view_id = self.allocate_transient_id()?;
-                let mut dataflow = DataflowDesc::new(format!("temp-view-{}", view_id));
-                dataflow.set_as_of(Antichain::from_elem(timestamp));
-                self.dataflow_builder()
-                    .import_view_into_dataflow(&view_id, &source, &mut dataflow);
-                dataflow.add_index_to_build(index_id, view_id, typ.clone(), key.clone());
-                dataflow.add_index_export(index_id, view_id, typ, key);
-                self.ship_dataflow(dataflow).await?;
+                match self.share_dataflow(inner, timestamp) {
+                    Some(SharedPeek::Existing(id, guard)) => {
+                        // A shared dataflow already computes everything but
+                        // the outermost filter and projection, which are
+                        // applied as the dataflow's index is read. Reading
+                        // the index requires no new dataflow, and so the peek
+                        // is admitted even while the process is overloaded.
+                        index_id = id;
+                        shared_guard = Some(guard);
+                    }
+                    Some(SharedPeek::Build) => {
+                        // Build a dataflow for everything but the outermost
+                        // filter and projection, and keep it around for
+                        // later peeks of the same expression.
+                        self.admit_dataflow_peek()?;
+                        let inner = OptimizedMirRelationExpr::declare_optimized(inner.clone());
+                        let typ = inner.as_ref().typ();
+                        let key: Vec<_> = (0..typ.arity()).map(MirScalarExpr::Column).collect();
+                        let view_id = self.allocate_transient_id()?;
+                        let mut dataflow = DataflowDesc::new(format!("shared-view-{}", view_id));
+                        dataflow.set_as_of(Antichain::from_elem(timestamp));
+                        self.dataflow_builder().import_view_into_dataflow(
+                            &view_id,
+                            &inner,
+                            &mut dataflow,
+                        );
+                        dataflow.add_index_to_build(index_id, view_id, typ.clone(), key.clone());
+                        dataflow.add_index_export(index_id, view_id, typ, key);
+                        let depends_on = dataflow
+                            .index_imports
+                            .keys()
+                            .chain(dataflow.source_imports.keys())
+                            .copied()
+                            .chain(inner.as_ref().global_uses())
+                            .collect();
+                        self.ship_dataflow(dataflow).await?;
+                        if let Some(shared_dataflows) = &mut self.shared_dataflows {
+                            shared_guard = Some(shared_dataflows.insert(
+                                inner.into_inner(),
+                                index_id,
+                                depends_on,
+                            ));
+                        }
+                    }
+                    None => {
+                        // Slow path. We need to perform some computation, so
+                        // build a new transient dataflow that will be dropped
+                        // after the peek completes.
+                        self.admit_dataflow_peek()?;
+                        let typ = source.as_ref().typ();
+                        map_filter_project = expr::MapFilterProject::new(typ.arity());
+                        let key: Vec<_> = (0..typ.arity()).map(MirScalarExpr::Column).collect();
+                        let view_id = self.allocate_transient_id()?;
+                        let mut dataflow = DataflowDesc::new(format!("temp-view-{}", view_id));
+                        dataflow.set_as_of(Antichain::from_elem(timestamp));
+                        self.dataflow_builder().import_view_into_dataflow(
+                            &view_id,
+                            &source,
+                            &mut dataflow,
+                        );
+                        dataflow.add_index_to_build(index_id, view_id, typ.clone(), key.clone());
+                        dataflow.add_index_export(index_id, view_id, typ, key);
+                        self.ship_dataflow(dataflow).await?;
+                        transient = true;
+                    }
+                }
             }
 
-            self.broadcast(SequencedCommand::Peek {
-                id: index_id,
-                key: literal_row,
-                conn_id,
-                tx: rows_tx,
-                timestamp,
-                finishing: finishing.clone(),
-                map_filter_project,
+            // Each worker sends its results on a channel of its own, so that
+            // the workers' sorted results can be merged as they arrive.
+            let mut rows_rxs = vec![];
+            self.broadcast_with(|| {
+                let (rows_tx, rows_rx) = mpsc::unbounded_channel();
+                rows_rxs.push(UnboundedReceiverStream::new(rows_rx));
+                SequencedCommand::Peek {
+                    id: index_id,
+                    key: literal_row.clone(),
+                    conn_id,
+                    tx: rows_tx,
+                    timestamp,
+                    finishing: finishing.clone(),
+                    map_filter_project: map_filter_project.clone(),
+                }
             });
 
-            if !fast_path {
+            if transient {
                 self.drop_indexes(vec![index_id]).await;
             }
 
-            let rows_rx = UnboundedReceiverStream::new(rows_rx)
-                .fold(PeekResponse::Rows(vec![]), |memo, resp| async {
-                    match (memo, resp) {
-                        (PeekResponse::Rows(mut memo), PeekResponse::Rows(rows)) => {
-                            memo.extend(rows);
-                            PeekResponse::Rows(memo)
-                        }
-                        (PeekResponse::Error(e), _) | (_, PeekResponse::Error(e)) => {
-                            PeekResponse::Error(e)
-                        }
-                        (PeekResponse::Canceled, _) | (_, PeekResponse::Canceled) => {
-                            PeekResponse::Canceled
-                        }
-                    }
-                })
-                .map(move |mut resp| {
-                    if let PeekResponse::Rows(rows) = &mut resp {
-                        finishing.finish(rows)
-                    }
-                    resp
-                });
-
-            ExecuteResponse::SendingRows(Box::pin(rows_rx))
+            // The peek remains admitted, and any shared dataflow that it reads
+            // from remains in use, for as long as its results are being
+            // delivered.
+            let rows = peek::finish_responses(rows_rxs, finishing).map(move |resp| {
+                let _ = (&peek_guard, &shared_guard);
+                resp
+            });
+            match cache_key {
+                Some(key) => ExecuteResponse::SendingRows(peek::cache_results(
+                    Box::new(rows),
+                    Arc::clone(&self.peek_cache),
+                    timestamp,
+                    key,
+                )),
+                None => ExecuteResponse::SendingRows(Box::new(rows)),
+            }
         };
 
         match copy_to {
@@ -2457,6 +3817,8 @@ impl Coordinator {
         emit_progress: bool,
         object_columns: usize,
         desc: RelationDesc,
+        envelope: TailEnvelope,
+        key_indices: Option<Vec<usize>>,
     ) -> Result<ExecuteResponse, CoordError> {
         // Determine the frontier of updates to tail *from*.
         // Updates greater or equal to this frontier will be produced.
@@ -2493,8 +3855,13 @@ impl Coordinator {
                 emit_progress,
                 object_columns,
                 value_desc: desc,
+                envelope,
+                key_indices,
             }),
-            SinkEnvelope::Tail { emit_progress },
+            SinkEnvelope::Tail {
+                emit_progress,
+                envelope,
+            },
             SinkAsOf {
                 frontier,
                 strict: !with_snapshot,
@@ -2513,6 +3880,59 @@ impl Coordinator {
         }
     }
 
+    /// Finds a partial index that can answer a peek of `source` on its own.
+    ///
+    /// A partial index is applicable if `source` is a linear operator over the
+    /// index's underlying item whose predicates include all of the index's
+    /// predicates. Returns the identifier of the index and its keys.
+    fn applicable_partial_index(
+        &mut self,
+        source: &MirRelationExpr,
+    ) -> Result<Option<(GlobalId, Vec<MirScalarExpr>)>, CoordError> {
+        let on = match source.global_uses().as_slice() {
+            [on] => *on,
+            _ => return Ok(None),
+        };
+        let candidates = self
+            .catalog
+            .partial_indexes(on)
+            .filter(|(id, _)| self.indexes.contains_key(*id))
+            .map(|(id, index)| (id, index.keys.clone(), index.predicates.clone()))
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        let source = self.prep_relation_expr(source.clone(), ExprPrepStyle::Explain, false)?;
+        let (mut mfp, inner) = expr::MapFilterProject::extract_from_expression(source.as_ref());
+        mfp.optimize();
+        let typ = match inner {
+            MirRelationExpr::Get {
+                id: Id::Global(id),
+                typ,
+            } if *id == on => typ.clone(),
+            _ => return Ok(None),
+        };
+
+        for (id, keys, predicates) in candidates {
+            // Put the index's predicates into the same canonical form as the
+            // query's, so that they can be compared syntactically.
+            let filter = MirRelationExpr::global_get(on, typ.clone()).filter(predicates);
+            let filter = self.prep_relation_expr(filter, ExprPrepStyle::Explain, false)?;
+            let (mut index_mfp, _) =
+                expr::MapFilterProject::extract_from_expression(filter.as_ref());
+            index_mfp.optimize();
+            let covered = index_mfp
+                .predicates
+                .iter()
+                .all(|(_, p)| mfp.predicates.iter().any(|(_, q)| p == q));
+            if covered {
+                return Ok(Some((id, keys)));
+            }
+        }
+        Ok(None)
+    }
+
     /// A policy for determining the timestamp for a peek.
     ///
     /// The result may be `None` in the case that the `when` policy cannot be satisfied,
@@ -2537,12 +3957,40 @@ impl Coordinator {
         // a larger timestamp and block, perhaps the user should intervene).
         let uses_ids = &source.global_uses();
         let (index_ids, indexes_complete) = self.catalog.nearest_indexes(&uses_ids);
+        self.determine_timestamp_for(uses_ids, &index_ids, indexes_complete, when)
+    }
 
+    /// Like `determine_timestamp`, but for a query that reads the items
+    /// `uses_ids` through the indexes `index_ids`.
+    ///
+    /// `indexes_complete` indicates whether `index_ids` covers all of
+    /// `uses_ids`.
+    fn determine_timestamp_for(
+        &mut self,
+        uses_ids: &[GlobalId],
+        index_ids: &[GlobalId],
+        indexes_complete: bool,
+        when: PeekWhen,
+    ) -> Result<Timestamp, CoordError> {
         // Determine the valid lower bound of times that can produce correct outputs.
         // This bound is determined by the arrangements contributing to the query,
         // and does not depend on the transitive sources.
         let since = self.indexes.least_valid_since(index_ids.iter().cloned());
 
+        // Record how far behind the indexes' uppers explicitly requested
+        // timestamps lie, so that their compaction windows can be tuned to
+        // keep serving such queries.
+        if let (PeekWhen::AtTimestamp(timestamp), Some(tuner)) = (&when, &mut self.compaction_tuner)
+        {
+            for id in index_ids {
+                if let Some(upper) = self.indexes.upper_of(id) {
+                    if let Some(upper) = upper.iter().next() {
+                        tuner.observe(*id, upper.saturating_sub(*timestamp));
+                    }
+                }
+            }
+        }
+
         // First determine the candidate timestamp, which is either the explicitly requested
         // timestamp, or the latest timestamp known to be immediately available.
         let timestamp = match when {
@@ -2588,10 +4036,24 @@ impl Coordinator {
                                         .less_equal(&0)
                                 })
                                 .collect::<Vec<_>>();
-                            coord_bail!(
-                                "At least one input has no complete timestamps yet: {:?}",
-                                unstarted
-                            );
+                            // If the only unstarted inputs are lazy views that
+                            // were just materialized from other indexes, peek
+                            // at the time by which their indexes will have
+                            // caught up with their inputs. The peek waits for
+                            // the indexes to reach that time. Otherwise there
+                            // is no time at which the results are known to be
+                            // complete.
+                            let targets: Option<Vec<_>> = unstarted
+                                .iter()
+                                .map(|id| self.lazy_views.hydration_target(**id))
+                                .collect();
+                            match targets {
+                                Some(targets) => targets.into_iter().max().unwrap_or(0),
+                                None => coord_bail!(
+                                    "At least one input has no complete timestamps yet: {:?}",
+                                    unstarted
+                                ),
+                            }
                         }
                     } else {
                         // A complete trace can be read in its final form with this time.
@@ -2616,22 +4078,24 @@ impl Coordinator {
         if since.less_equal(&timestamp) {
             Ok(timestamp)
         } else {
-            let invalid = index_ids
+            let indexes = index_ids
                 .iter()
-                .filter(|id| {
-                    !self
-                        .indexes
-                        .since_of(id)
-                        .expect("id not found")
-                        .less_equal(&timestamp)
+                .filter_map(|id| {
+                    let since = self.indexes.since_of(id).expect("id not found");
+                    if since.less_equal(&timestamp) {
+                        None
+                    } else {
+                        let name = self.catalog.get_by_id(id).name().to_string();
+                        let since = since
+                            .elements()
+                            .get(0)
+                            .copied()
+                            .unwrap_or_else(Timestamp::max_value);
+                        Some((name, since))
+                    }
                 })
-                .map(|id| (id, self.indexes.since_of(id)))
-                .collect::<Vec<_>>();
-            coord_bail!(
-                "Timestamp ({}) is not valid for all inputs: {:?}",
-                timestamp,
-                invalid
-            );
+                .collect();
+            Err(CoordError::TimestampCompacted { timestamp, indexes })
         }
     }
 
@@ -2661,6 +4125,7 @@ impl Coordinator {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn sequence_explain_plan(
         &mut self,
         session: &Session,
@@ -2669,7 +4134,10 @@ impl Coordinator {
         row_set_finishing: Option<RowSetFinishing>,
         stage: ExplainStage,
         options: ExplainOptions,
+        prefer_delta_joins: Option<bool>,
     ) -> Result<ExecuteResponse, CoordError> {
+        let prefer_delta_joins =
+            prefer_delta_joins.unwrap_or_else(|| session.vars().prefer_delta_joins());
         let explanation_string = match stage {
             ExplainStage::RawPlan => {
                 let catalog = self.catalog.for_session(session);
@@ -2695,7 +4163,11 @@ impl Coordinator {
             }
             ExplainStage::OptimizedPlan => {
                 let optimized_plan = self
-                    .prep_relation_expr(decorrelated_plan, ExprPrepStyle::Explain)?
+                    .prep_relation_expr(
+                        decorrelated_plan,
+                        ExprPrepStyle::Explain,
+                        prefer_delta_joins,
+                    )?
                     .into_inner();
                 let catalog = self.catalog.for_session(session);
                 let mut explanation = expr::explain::Explanation::new(&optimized_plan, &catalog);
@@ -2707,11 +4179,116 @@ impl Coordinator {
                 }
                 explanation.to_string()
             }
+            ExplainStage::Timestamp => self.explain_timestamp(session, &decorrelated_plan)?,
+            ExplainStage::IndexAdvice => {
+                let optimized_plan = self
+                    .prep_relation_expr(
+                        decorrelated_plan,
+                        ExprPrepStyle::Explain,
+                        prefer_delta_joins,
+                    )?
+                    .into_inner();
+                self.explain_index_advice(session, &optimized_plan)
+            }
+            ExplainStage::Dataflow => {
+                unreachable!("EXPLAIN DATAFLOW is planned only for sources and sinks")
+            }
+        };
+        let rows = vec![Row::pack_slice(&[Datum::from(&*explanation_string)])];
+        Ok(send_immediate_rows(rows))
+    }
+
+    fn sequence_explain_dataflow(
+        &self,
+        session: &Session,
+        id: GlobalId,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let catalog = self.catalog.for_session(session);
+        let explanation_string = match self.catalog.get_by_id(&id).item() {
+            CatalogItem::Source(source) => explain_dataflow::explain_source(&catalog, id, source),
+            CatalogItem::Sink(sink) => explain_dataflow::explain_sink(&catalog, id, sink),
+            _ => unreachable!("EXPLAIN DATAFLOW is planned only for sources and sinks"),
         };
         let rows = vec![Row::pack_slice(&[Datum::from(&*explanation_string)])];
         Ok(send_immediate_rows(rows))
     }
 
+    /// Describes the timestamp that a peek of `source` would use, together
+    /// with the `since` and `upper` frontiers of the indexes it would read.
+    ///
+    /// An index is reported as holding back the query if its `upper` is not
+    /// beyond the chosen timestamp's successor: either the query must wait for
+    /// the index to catch up, or the index is the reason a later timestamp was
+    /// not chosen.
+    fn explain_timestamp(
+        &mut self,
+        session: &Session,
+        source: &MirRelationExpr,
+    ) -> Result<String, CoordError> {
+        let timestamp = self.determine_timestamp(source, PeekWhen::Immediately)?;
+        let (index_ids, _indexes_complete) = self.catalog.nearest_indexes(&source.global_uses());
+        let since = self.indexes.least_valid_since(index_ids.iter().copied());
+        let upper = self.indexes.greatest_open_upper(index_ids.iter().copied());
+        let catalog = self.catalog.for_session(session);
+
+        let mut explanation = String::new();
+        writeln!(explanation, "timestamp: {}", timestamp).unwrap();
+        writeln!(explanation, "    since: {:?}", since.elements()).unwrap();
+        writeln!(explanation, "    upper: {:?}", upper.elements()).unwrap();
+        for id in index_ids {
+            let since = self.indexes.since_of(&id).expect("id not found");
+            let upper = self.indexes.upper_of(&id).expect("id not found");
+            let name = catalog.humanize_id(id).unwrap_or_else(|| id.to_string());
+            writeln!(explanation).unwrap();
+            writeln!(explanation, "index {} ({}):", name, id).unwrap();
+            writeln!(explanation, "          since: {:?}", since.elements()).unwrap();
+            writeln!(explanation, "          upper: {:?}", upper.to_vec()).unwrap();
+            writeln!(
+                explanation,
+                "  holding reads: {}",
+                upper.less_equal(&timestamp.saturating_add(1))
+            )
+            .unwrap();
+        }
+        Ok(explanation)
+    }
+
+    /// Describes the indexes that would benefit `optimized_plan` as a list of
+    /// `CREATE INDEX` statements, each annotated with its expected benefit.
+    fn explain_index_advice(&self, session: &Session, optimized_plan: &MirRelationExpr) -> String {
+        let catalog = self.catalog.for_session(session);
+        let mut explanation = String::new();
+        for ((id, columns), benefit) in index_advice::advise(&self.catalog, optimized_plan) {
+            let entry = self.catalog.get_by_id(&id);
+            let desc = match entry.desc() {
+                Ok(desc) => desc,
+                Err(_) => continue,
+            };
+            let names = columns
+                .iter()
+                .map(|c| {
+                    desc.get_name(*c)
+                        .map(|name| Ident::new(name.as_str()).to_string())
+                })
+                .collect::<Option<Vec<_>>>();
+            let name = catalog.humanize_id(id).unwrap_or_else(|| id.to_string());
+            if let Some(names) = names {
+                writeln!(
+                    explanation,
+                    "CREATE INDEX ON {} ({}) -- {}",
+                    name,
+                    names.join(", "),
+                    benefit.describe()
+                )
+                .unwrap();
+            }
+        }
+        if explanation.is_empty() {
+            explanation.push_str("no indexes recommended\n");
+        }
+        explanation
+    }
+
     async fn sequence_send_diffs(
         &mut self,
         session: &mut Session,
@@ -2737,22 +4314,13 @@ impl Coordinator {
         let prep_style = ExprPrepStyle::OneShot {
             logical_time: self.get_write_ts(),
         };
-        match self.prep_relation_expr(values, prep_style)?.into_inner() {
+        match self
+            .prep_relation_expr(values, prep_style, false)?
+            .into_inner()
+        {
             MirRelationExpr::Constant { rows, typ: _ } => {
                 let rows = rows?;
-                let desc = self.catalog.get_by_id(&id).desc()?;
-                for (row, _) in &rows {
-                    for (datum, (name, typ)) in row.unpack().iter().zip(desc.iter()) {
-                        if datum == &Datum::Null && !typ.nullable {
-                            coord_bail!(
-                                "null value in column {} violates not-null constraint",
-                                name.unwrap_or(&ColumnName::from("unnamed column"))
-                                    .as_str()
-                                    .quoted()
-                            )
-                        }
-                    }
-                }
+                self.check_constraints(id, rows.iter().map(|(row, _)| row))?;
                 let affected_rows = rows.len();
                 self.sequence_send_diffs(session, id, rows, affected_rows, MutationKind::Insert)
                     .await
@@ -2764,6 +4332,41 @@ impl Coordinator {
         }
     }
 
+    /// Verifies that `rows` do not violate the not-null or check constraints
+    /// of the table `id`.
+    fn check_constraints<'a, I>(&self, id: GlobalId, rows: I) -> Result<(), CoordError>
+    where
+        I: IntoIterator<Item = &'a Row>,
+    {
+        let entry = self.catalog.get_by_id(&id);
+        let desc = entry.desc()?;
+        let checks = match entry.item() {
+            CatalogItem::Table(table) => &table.checks[..],
+            _ => &[],
+        };
+        let temp_storage = RowArena::new();
+        for row in rows {
+            let datums = row.unpack();
+            for (datum, (name, typ)) in datums.iter().zip(desc.iter()) {
+                if datum == &Datum::Null && !typ.nullable {
+                    return Err(CoordError::NotNullViolation(
+                        name.cloned()
+                            .unwrap_or_else(|| ColumnName::from("unnamed column")),
+                    ));
+                }
+            }
+            for check in checks {
+                if check.expr.eval(&datums, &temp_storage)? == Datum::False {
+                    return Err(CoordError::CheckViolation {
+                        relation: entry.name().item.clone(),
+                        constraint: check.name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn sequence_alter_item_rename(
         &mut self,
         id: GlobalId,
@@ -2777,29 +4380,144 @@ impl Coordinator {
         }
     }
 
-    fn sequence_alter_index_set_options(
+    async fn sequence_alter_source(
+        &mut self,
+        session: Session,
+        tx: ClientTransmitter<ExecuteResponse>,
+        id: GlobalId,
+        source: sql::plan::Source,
+    ) {
+        let kc = match &source.connector {
+            SourceConnector::External {
+                connector: ExternalSourceConnector::Kafka(kc),
+                ..
+            } => kc.clone(),
+            _ => unreachable!("ALTER SOURCE planned for non-Kafka source"),
+        };
+
+        // Connect with the new options before committing to them, so that
+        // bad credentials or an unreachable broker fail the ALTER SOURCE
+        // statement, rather than the reconnection of the running source.
+        // Connecting can take arbitrarily long, so it happens off the main
+        // coordinator thread.
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        tokio::spawn(async move {
+            let result =
+                match tokio::task::spawn_blocking(move || timestamp::verify_kafka_connector(&kc))
+                    .await
+                {
+                    Ok(result) => result.map_err(|e| {
+                        CoordError::Unstructured(e.context("unable to connect to source"))
+                    }),
+                    Err(e) => Err(CoordError::Unstructured(anyhow!(e))),
+                };
+            internal_cmd_tx
+                .send(Message::SourceConnectorVerified(SourceConnectorVerified {
+                    session,
+                    tx,
+                    id,
+                    source,
+                    result,
+                }))
+                .expect("sending to internal_cmd_tx cannot fail");
+        });
+    }
+
+    async fn alter_source(
+        &mut self,
+        id: GlobalId,
+        source: sql::plan::Source,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let mut item = match self.catalog.get_by_id(&id).item() {
+            CatalogItem::Source(source) => source.clone(),
+            _ => unreachable!("ALTER SOURCE planned for non-source"),
+        };
+        let old_connector = mem::replace(&mut item.connector, source.connector);
+        item.create_sql = source.create_sql;
+        let connector = item.connector.clone();
+        let op = catalog::Op::UpdateItem {
+            id,
+            item: CatalogItem::Source(item),
+        };
+        self.catalog_transact(vec![op]).await?;
+
+        let (old_external, old_encoding) = match old_connector {
+            SourceConnector::External {
+                connector,
+                encoding,
+                ..
+            } => (connector, encoding),
+            SourceConnector::Local => unreachable!("ALTER SOURCE planned for local source"),
+        };
+        let (external, encoding) = match &connector {
+            SourceConnector::External {
+                connector,
+                encoding,
+                ..
+            } => (connector.clone(), encoding.clone()),
+            SourceConnector::Local => unreachable!("ALTER SOURCE planned for local source"),
+        };
+
+        if old_external != external {
+            // Restart the timestamper for the source, so that it reads
+            // partition metadata with the new connection options, and then
+            // point the running instances of the source at the new options.
+            // The workers' timestamp bindings are left untouched.
+            self.ts_tx
+                .send(TimestampMessage::Drop(id))
+                .expect("Failed to send DROP Instance notice to timestamper");
+            self.ts_tx
+                .send(TimestampMessage::Add(id, connector))
+                .expect("Failed to send CREATE Instance notice to timestamper");
+            self.broadcast(SequencedCommand::UpdateSourceConnector {
+                id,
+                connector: external,
+            });
+        }
+        if let (DataEncoding::Protobuf(old_encoding), DataEncoding::Protobuf(encoding)) =
+            (old_encoding, encoding)
+        {
+            if old_encoding != encoding {
+                self.broadcast(SequencedCommand::UpdateSourceEncoding { id, encoding });
+            }
+        }
+        Ok(ExecuteResponse::AlteredObject(ObjectType::Source))
+    }
+
+    async fn sequence_alter_index_set_options(
         &mut self,
         id: GlobalId,
         options: Vec<IndexOption>,
     ) -> Result<ExecuteResponse, CoordError> {
-        self.set_index_options(id, options);
+        self.set_index_options(id, options).await?;
         Ok(ExecuteResponse::AlteredObject(ObjectType::Index))
     }
 
-    fn sequence_alter_index_reset_options(
+    async fn sequence_alter_index_reset_options(
         &mut self,
         id: GlobalId,
         options: Vec<IndexOptionName>,
     ) -> Result<ExecuteResponse, CoordError> {
+        let resets_window = options.contains(&IndexOptionName::LogicalCompactionWindow);
         let options = options
             .into_iter()
             .map(|o| match o {
                 IndexOptionName::LogicalCompactionWindow => IndexOption::LogicalCompactionWindow(
                     self.logical_compaction_window_ms.map(Duration::from_millis),
                 ),
+                IndexOptionName::RefreshInterval => IndexOption::RefreshInterval(None),
             })
             .collect();
-        self.set_index_options(id, options);
+        self.set_index_options(id, options).await?;
+        // Resetting the window hands it back to the tuner, if there is one.
+        if let (true, Some(tuner), Some(window_ms)) = (
+            resets_window,
+            &mut self.compaction_tuner,
+            self.logical_compaction_window_ms,
+        ) {
+            tuner.register(id, window_ms);
+            self.report_compaction_window_update(id).await;
+        }
         Ok(ExecuteResponse::AlteredObject(ObjectType::Index))
     }
 
@@ -2843,23 +4561,44 @@ impl Coordinator {
                     if let Ok(desc) = item.desc(&name) {
                         self.report_column_updates(desc, *id, 1).await;
                     }
+                    self.report_object_json_update(*id).await;
                     metrics::item_created(*id, &item);
                     match item {
                         CatalogItem::Index(index) => {
                             self.report_index_update(*id, *oid, &index, &name.item, 1)
-                                .await
+                                .await;
+                            if index.lazy {
+                                self.lazy_views.index_created(index.on, *id);
+                            }
+                            // Indexes created at runtime have not yet been
+                            // shipped, and will use the default window.
+                            let window_ms = self
+                                .indexes
+                                .get(id)
+                                .map(|frontiers| frontiers.compaction_window_ms)
+                                .unwrap_or(self.logical_compaction_window_ms);
+                            if let (Some(tuner), Some(window_ms)) =
+                                (&mut self.compaction_tuner, window_ms)
+                            {
+                                tuner.register(*id, window_ms);
+                            }
+                            self.report_compaction_window_update(*id).await;
                         }
                         CatalogItem::Table(_) => {
                             self.report_table_update(*id, *oid, *schema_id, &name.item, 1)
                                 .await
                         }
-                        CatalogItem::Source(_) => {
+                        CatalogItem::Source(source) => {
                             self.report_source_update(*id, *oid, *schema_id, &name.item, 1)
                                 .await;
+                            self.report_source_timestamping_update(*id, source, 1).await;
                         }
-                        CatalogItem::View(_) => {
+                        CatalogItem::View(view) => {
                             self.report_view_update(*id, *oid, *schema_id, &name.item, 1)
                                 .await;
+                            if view.lazy {
+                                self.lazy_views.register(*id);
+                            }
                         }
                         CatalogItem::Sink(sink) => {
                             if let catalog::Sink {
@@ -2875,6 +4614,11 @@ impl Coordinator {
                             self.report_type_update(*id, *oid, *schema_id, &name.item, ty, 1)
                                 .await;
                         }
+                        CatalogItem::Task(task) => {
+                            self.report_task_update(*id, *oid, *schema_id, &name.item, task, 1)
+                                .await;
+                            self.task_scheduler.schedule(*id, task.schedule);
+                        }
                         CatalogItem::Func(func) => {
                             self.report_func_update(*id, *schema_id, &name.item, func, 1)
                                 .await;
@@ -2889,6 +4633,7 @@ impl Coordinator {
                     to_name,
                     item,
                 } => {
+                    self.report_object_json_update(*id).await;
                     // Remove old name and add new name to relevant mz system tables.
                     match item {
                         CatalogItem::Source(_) => {
@@ -2940,9 +4685,25 @@ impl Coordinator {
                             self.report_type_update(*id, *oid, *schema_id, &to_name.item, &typ, 1)
                                 .await;
                         }
+                        CatalogItem::Task(task) => {
+                            self.report_task_update(
+                                *id,
+                                *oid,
+                                *schema_id,
+                                &from_name.item,
+                                task,
+                                -1,
+                            )
+                            .await;
+                            self.report_task_update(*id, *oid, *schema_id, &to_name.item, task, 1)
+                                .await;
+                        }
                         CatalogItem::Func(_) => unreachable!("functions cannot be updated"),
                     }
                 }
+                catalog::Event::UpdatedItemDefinition { id } => {
+                    self.report_object_json_update(*id).await;
+                }
                 catalog::Event::DroppedDatabase { id, oid, name } => {
                     self.report_database_update(*id, *oid, name, -1).await;
                 }
@@ -2967,6 +4728,15 @@ impl Coordinator {
                 catalog::Event::DroppedIndex { entry, nullable } => match entry.item() {
                     CatalogItem::Index(index) => {
                         indexes_to_drop.push(entry.id());
+                        if let Some(shared_dataflows) = &mut self.shared_dataflows {
+                            indexes_to_drop.extend(shared_dataflows.forget_dependents(entry.id()));
+                        }
+                        self.retract_object_json(entry.id()).await;
+                        self.lazy_views.index_dropped(entry.id());
+                        if let Some(tuner) = &mut self.compaction_tuner {
+                            tuner.forget(entry.id());
+                        }
+                        self.retract_compaction_window(entry.id()).await;
                         self.report_index_update_inner(
                             entry.id(),
                             entry.oid(),
@@ -2981,6 +4751,10 @@ impl Coordinator {
                 },
                 catalog::Event::DroppedItem { schema_id, entry } => {
                     metrics::item_dropped(entry.id(), entry.item());
+                    if let Some(shared_dataflows) = &mut self.shared_dataflows {
+                        indexes_to_drop.extend(shared_dataflows.forget_dependents(entry.id()));
+                    }
+                    self.retract_object_json(entry.id()).await;
                     match entry.item() {
                         CatalogItem::Table(_) => {
                             sources_to_drop.push(entry.id());
@@ -2993,7 +4767,7 @@ impl Coordinator {
                             )
                             .await;
                         }
-                        CatalogItem::Source(_) => {
+                        CatalogItem::Source(source) => {
                             sources_to_drop.push(entry.id());
                             self.report_source_update(
                                 entry.id(),
@@ -3003,6 +4777,8 @@ impl Coordinator {
                                 -1,
                             )
                             .await;
+                            self.report_source_timestamping_update(entry.id(), source, -1)
+                                .await;
                         }
                         CatalogItem::View(_) => {
                             self.report_view_update(
@@ -3013,6 +4789,7 @@ impl Coordinator {
                                 -1,
                             )
                             .await;
+                            self.lazy_views.forget(entry.id());
                         }
                         CatalogItem::Sink(catalog::Sink {
                             connector: SinkConnectorState::Ready(connector),
@@ -3071,6 +4848,21 @@ impl Coordinator {
                             )
                             .await;
                         }
+                        CatalogItem::Task(task) => {
+                            self.report_task_update(
+                                entry.id(),
+                                entry.oid(),
+                                *schema_id,
+                                &entry.name().item,
+                                task,
+                                -1,
+                            )
+                            .await;
+                            if let Some(update) = self.task_scheduler.forget(entry.id()) {
+                                self.update_catalog_view(MZ_TASK_STATUS.id, iter::once(update))
+                                    .await;
+                            }
+                        }
                         CatalogItem::Index(_) => {
                             unreachable!("dropped indexes should be handled by DroppedIndex");
                         }
@@ -3086,6 +4878,15 @@ impl Coordinator {
             }
         }
 
+        if let Some(watchdog) = &mut self.dataflow_watchdog {
+            for id in &sources_to_drop {
+                watchdog.forget(*id);
+            }
+        }
+        for id in &sources_to_drop {
+            self.rehydrating_sources.remove(id);
+            self.source_uppers.remove(id);
+        }
         if !sources_to_drop.is_empty() {
             self.broadcast(SequencedCommand::DropSources(sources_to_drop));
         }
@@ -3107,38 +4908,164 @@ impl Coordinator {
 
     async fn drop_indexes(&mut self, indexes: Vec<GlobalId>) {
         let mut trace_keys = Vec::new();
+        let mut stalled_updates = Vec::new();
+        let mut freshness_updates = Vec::new();
         for id in indexes {
             if self.indexes.remove(&id).is_some() {
                 trace_keys.push(id);
             }
+            self.arrangement_bytes.remove(&id);
+            if let Some(watchdog) = &mut self.dataflow_watchdog {
+                stalled_updates.extend(watchdog.forget(id));
+            }
+            freshness_updates.extend(self.freshness.forget(id));
+        }
+        if !stalled_updates.is_empty() {
+            self.update_catalog_view(MZ_STALLED_DATAFLOWS.id, stalled_updates)
+                .await;
+        }
+        if !freshness_updates.is_empty() {
+            self.update_catalog_view(MZ_VIEW_FRESHNESS.id, freshness_updates)
+                .await;
         }
         if !trace_keys.is_empty() {
             self.broadcast(SequencedCommand::DropIndexes(trace_keys))
         }
+        self.hydration_waiters.notify(&self.indexes);
+    }
+
+    /// Updates the row for the index `id` in `mz_index_compaction_windows` to
+    /// reflect its current logical compaction window.
+    async fn report_compaction_window_update(&mut self, id: GlobalId) {
+        let window_ms = self
+            .indexes
+            .get(&id)
+            .map(|frontiers| frontiers.compaction_window_ms)
+            .unwrap_or(self.logical_compaction_window_ms);
+        let auto_tuned = match &self.compaction_tuner {
+            Some(tuner) => tuner.is_tuned(id),
+            None => false,
+        };
+        let row = Row::pack_slice(&[
+            Datum::String(&id.to_string()),
+            match window_ms {
+                Some(window_ms) => Datum::Int64(window_ms as i64),
+                None => Datum::Null,
+            },
+            Datum::from(auto_tuned),
+        ]);
+        let mut updates = vec![];
+        match self.compaction_window_rows.insert(id, row.clone()) {
+            Some(old_row) if old_row == row => return,
+            Some(old_row) => updates.push((old_row, -1)),
+            None => (),
+        }
+        updates.push((row, 1));
+        self.update_catalog_view(MZ_INDEX_COMPACTION_WINDOWS.id, updates)
+            .await;
+    }
+
+    /// Updates the row for the object `id` in `mz_object_json` to reflect its
+    /// current definition.
+    async fn report_object_json_update(&mut self, id: GlobalId) {
+        let json = match self.catalog.object_json(id) {
+            Some(json) => json,
+            None => return,
+        };
+        let mut packer = RowPacker::new();
+        packer.push(Datum::String(&id.to_string()));
+        packer.push(Datum::Int32(self.catalog.get_by_id(&id).oid() as i32));
+        let row = JsonbPacker::new(packer)
+            .pack_serde_json(json)
+            .expect("object descriptions are valid jsonb")
+            .finish();
+        let mut updates = vec![];
+        match self.object_json_rows.insert(id, row.clone()) {
+            Some(old_row) if old_row == row => return,
+            Some(old_row) => updates.push((old_row, -1)),
+            None => (),
+        }
+        updates.push((row, 1));
+        self.update_catalog_view(MZ_OBJECT_JSON.id, updates).await;
+    }
+
+    /// Removes the row for the dropped object `id` from `mz_object_json`.
+    async fn retract_object_json(&mut self, id: GlobalId) {
+        if let Some(row) = self.object_json_rows.remove(&id) {
+            self.update_catalog_view(MZ_OBJECT_JSON.id, iter::once((row, -1)))
+                .await;
+        }
+    }
+
+    /// Removes the row for the dropped index `id` from
+    /// `mz_index_compaction_windows`.
+    async fn retract_compaction_window(&mut self, id: GlobalId) {
+        if let Some(row) = self.compaction_window_rows.remove(&id) {
+            self.update_catalog_view(MZ_INDEX_COMPACTION_WINDOWS.id, iter::once((row, -1)))
+                .await;
+        }
     }
 
-    fn set_index_options(&mut self, id: GlobalId, options: Vec<IndexOption>) {
+    /// Applies `options` to the index `id`.
+    ///
+    /// The refresh interval of an index is fixed when its dataflow is
+    /// rendered, so changing it renders the index's dataflow anew.
+    async fn set_index_options(
+        &mut self,
+        id: GlobalId,
+        options: Vec<IndexOption>,
+    ) -> Result<(), CoordError> {
         let index = self.indexes.get_mut(&id).expect("index known to exist");
+        let mut rerender = None;
         for o in options {
             match o {
                 IndexOption::LogicalCompactionWindow(window) => {
                     let window = window.map(duration_to_timestamp_millis);
                     index.set_compaction_window_ms(window);
+                    // An explicitly chosen window is never tuned.
+                    if let Some(tuner) = &mut self.compaction_tuner {
+                        tuner.forget(id);
+                    }
+                }
+                IndexOption::RefreshInterval(interval) => {
+                    let interval = interval.map(duration_to_timestamp_millis);
+                    if index.refresh_interval_ms != interval {
+                        rerender = Some(interval);
+                    }
                 }
             }
         }
+        self.report_compaction_window_update(id).await;
+        if let Some(interval) = rerender {
+            let mut dataflow = self.dataflow_builder().build_index_dataflow(id);
+            dataflow.refresh_interval = interval;
+            self.ship_dataflow(dataflow).await?;
+        }
+        Ok(())
     }
 
     /// Prepares a relation expression for execution by preparing all contained
     /// scalar expressions (see `prep_scalar_expr`), then optimizing the
     /// relation expression.
+    ///
+    /// If `prefer_delta_joins` is set, joins are planned as delta queries even
+    /// if that requires new arrangements of their inputs.
     fn prep_relation_expr(
         &mut self,
         mut expr: MirRelationExpr,
         style: ExprPrepStyle,
+        prefer_delta_joins: bool,
     ) -> Result<OptimizedMirRelationExpr, CoordError> {
+        let mut delta_join_optimizer;
+        let optimizer = if prefer_delta_joins {
+            delta_join_optimizer = Optimizer::new(true);
+            &mut delta_join_optimizer
+        } else {
+            &mut self.optimizer
+        };
         if let ExprPrepStyle::Static = style {
-            let mut opt_expr = self.optimizer.optimize(expr, self.catalog.indexes())?;
+            let mut opt_expr =
+                optimizer.optimize(expr, self.catalog.indexes(), self.catalog.foreign_keys())?;
             opt_expr.0.try_visit_mut(&mut |e| {
                 if let expr::MirRelationExpr::Filter {
                     input: _,
@@ -3168,7 +5095,7 @@ impl Coordinator {
             // constant expression that originally contains a global get? Is
             // there anything not containing a global get that cannot be
             // optimized to a constant expression?
-            Ok(self.optimizer.optimize(expr, self.catalog.indexes())?)
+            Ok(optimizer.optimize(expr, self.catalog.indexes(), self.catalog.foreign_keys())?)
         }
     }
 
@@ -3228,13 +5155,36 @@ impl Coordinator {
             );
         }
 
+        let created_at = self.hydration_target(&dataflow);
+        let inputs: Vec<_> = dataflow
+            .index_imports
+            .keys()
+            .chain(dataflow.source_imports.keys())
+            .copied()
+            .collect();
+
         // For each produced arrangement, start tracking the arrangement with
-        // a compaction frontier of at least `since`.
+        // a compaction frontier of at least `since`. An arrangement that is
+        // already tracked is being rendered anew. It keeps its frontiers, and
+        // the new arrangement starts from its compaction frontier.
+        let mut rerendered = vec![];
         for (global_id, _description, _typ) in dataflow.index_exports.iter() {
+            if let Some(frontiers) = self.indexes.get_mut(global_id) {
+                since.join_assign(&frontiers.since);
+                frontiers.advance_since(&since);
+                frontiers.refresh_interval_ms = dataflow.refresh_interval;
+                rerendered.push(*global_id);
+                continue;
+            }
             let mut frontiers =
                 Frontiers::new(self.num_workers(), self.logical_compaction_window_ms);
             frontiers.advance_since(&since);
+            frontiers.refresh_interval_ms = dataflow.refresh_interval;
             self.indexes.insert(*global_id, frontiers);
+            if let Some(watchdog) = &mut self.dataflow_watchdog {
+                watchdog.watch(*global_id, inputs.clone());
+            }
+            self.freshness.watch(*global_id, &inputs, created_at);
         }
 
         for (id, sink) in &dataflow.sink_exports {
@@ -3288,12 +5238,28 @@ impl Coordinator {
 
         // Finalize the dataflow by broadcasting its construction to all workers.
         self.broadcast(SequencedCommand::CreateDataflows(vec![dataflow]));
+
+        // The new arrangements of re-rendered indexes may be compacted as far
+        // as the arrangements they replace.
+        for id in rerendered {
+            let since = self.indexes.since_of(&id).expect("index known to exist");
+            self.since_updates.push((id, since.clone()));
+        }
         Ok(())
     }
 
     fn broadcast(&self, cmd: SequencedCommand) {
+        self.broadcast_with(|| cmd.clone())
+    }
+
+    /// Like [`Coordinator::broadcast`], but sends each worker a command of its
+    /// own, as produced by `f`.
+    fn broadcast_with<F>(&self, mut f: F)
+    where
+        F: FnMut() -> SequencedCommand,
+    {
         for tx in &self.worker_txs {
-            tx.send(cmd.clone())
+            tx.send(f())
                 .expect("worker command receiver should not drop first")
         }
         for handle in self.worker_guards.guards() {
@@ -3347,6 +5313,143 @@ impl Coordinator {
         }
     }
 
+    /// Verifies that installing `new` additional dataflows in the schema that
+    /// contains `name` would not exceed the configured quotas.
+    ///
+    /// The size of a new dataflow's arrangements is not known until it is
+    /// built, so the arrangement quota only rejects new dataflows in schemas
+    /// whose existing arrangements have already exceeded the limit.
+    fn check_dataflow_quota(
+        &self,
+        name: &FullName,
+        conn_id: u32,
+        new: usize,
+    ) -> Result<(), CoordError> {
+        let schema = SchemaName {
+            database: name.database.clone(),
+            schema: name.schema.clone(),
+        };
+        if let Some(limit) = self.quotas.max_dataflows_per_schema {
+            if self.catalog.dataflow_count(&schema, conn_id) + new > limit {
+                return Err(CoordError::DataflowQuotaExceeded {
+                    schema: schema.to_string(),
+                    limit,
+                });
+            }
+        }
+        if let Some(limit) = self.quotas.max_arrangement_bytes_per_schema {
+            let bytes: usize = self
+                .catalog
+                .index_ids_in_schema(&schema, conn_id)
+                .filter_map(|id| self.arrangement_bytes.get(&id))
+                .flatten()
+                .sum();
+            if bytes > limit {
+                return Err(CoordError::ArrangementQuotaExceeded {
+                    schema: schema.to_string(),
+                    bytes,
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether a new connection on behalf of `role` would exceed the
+    /// connection limits.
+    ///
+    /// Connections for the system user are always admitted, so that operators
+    /// retain access to a server that has run out of connections.
+    fn admit_conn(&self, role: &str) -> Result<(), CoordError> {
+        if role == catalog::SYSTEM_USER {
+            return Ok(());
+        }
+        if let Some(limit) = self.quotas.max_connections {
+            if self.active_conns.len() >= limit {
+                return Err(CoordError::ConnectionQuotaExceeded { limit });
+            }
+        }
+        if let Some(limit) = self.quotas.max_connections_per_role {
+            let active = self
+                .active_conns
+                .values()
+                .filter(|meta| meta.user == role)
+                .count();
+            if active >= limit {
+                return Err(CoordError::RoleConnectionQuotaExceeded {
+                    role: role.into(),
+                    limit,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Registers an outstanding peek on behalf of `role`.
+    ///
+    /// The peek is considered outstanding until the returned guard is dropped.
+    /// Returns an error if the role has already reached its limit on
+    /// concurrent peeks.
+    fn admit_peek(&mut self, role: &str) -> Result<PeekGuard, CoordError> {
+        let active = self.active_peeks.entry(role.into()).or_default();
+        if let Some(limit) = self.quotas.max_concurrent_peeks_per_role {
+            if active.load(Ordering::SeqCst) >= limit {
+                return Err(CoordError::PeekQuotaExceeded {
+                    role: role.into(),
+                    limit,
+                });
+            }
+        }
+        active.fetch_add(1, Ordering::SeqCst);
+        Ok(PeekGuard(Arc::clone(active)))
+    }
+
+    /// Checks whether the process has the resources to run a peek that
+    /// requires a transient dataflow.
+    ///
+    /// The queue depth is the number of commands that are waiting for the
+    /// coordinator, which does not include the command for the peek itself.
+    fn admit_dataflow_peek(&mut self) -> Result<(), CoordError> {
+        let queue_depth = self.queued_cmds.load(Ordering::SeqCst);
+        self.admission
+            .admit(queue_depth)
+            .map_err(CoordError::Overloaded)
+    }
+
+    /// Decides whether the slow-path peek of `expr`, beneath its outermost
+    /// filter and projection, at `timestamp` can share a transient dataflow
+    /// with other peeks.
+    fn share_dataflow(
+        &mut self,
+        expr: &MirRelationExpr,
+        timestamp: Timestamp,
+    ) -> Option<SharedPeek> {
+        let shared_dataflows = self.shared_dataflows.as_mut()?;
+        // Arranging an entire relation is the job of an index, which the user
+        // should create instead.
+        if let MirRelationExpr::Get { .. } = expr {
+            return None;
+        }
+        if let Some((index_id, guard)) = shared_dataflows.acquire(expr) {
+            // The shared dataflow may have compacted away the timestamp.
+            let valid = self
+                .indexes
+                .since_of(&index_id)
+                .map(|since| since.less_equal(&timestamp))
+                .unwrap_or(false);
+            return if valid {
+                Some(SharedPeek::Existing(index_id, guard))
+            } else {
+                None
+            };
+        }
+        if shared_dataflows.should_share(expr) {
+            Some(SharedPeek::Build)
+        } else {
+            None
+        }
+    }
+
     fn allocate_transient_id(&mut self) -> Result<GlobalId, CoordError> {
         let id = self.transient_id_counter;
         if id == u64::max_value() {
@@ -3374,8 +5477,21 @@ pub async fn serve(
         timestamp_frequency,
         cache: cache_config,
         logical_compaction_window,
+        auto_tune_logical_compaction_window,
         experimental_mode,
+        quotas,
+        slow_query_threshold,
+        statement_history_size,
+        stalled_dataflow_threshold,
+        shared_dataflow_idle_timeout,
+        shared_dataflow_min_peeks,
+        freshness_slo,
+        rehydration_budget,
+        lazy_view_idle_timeout,
+        log_filter,
         build_info,
+        now,
+        manual_ticks,
     }: Config<'_>,
     // TODO(benesch): Don't pass runtime explicitly when
     // `Handle::current().block_in_place()` lands. See:
@@ -3383,6 +5499,7 @@ pub async fn serve(
     runtime: Arc<Runtime>,
 ) -> Result<(Handle, Client), CoordError> {
     let (cmd_tx, cmd_rx) = mpsc::unbounded_channel();
+    let queued_cmds = Arc::new(AtomicUsize::new(0));
     let (feedback_tx, feedback_rx) = mpsc::unbounded_channel();
     let cache_tx = if let Some(cache_config) = &cache_config {
         let (cache_tx, cache_rx) = mpsc::unbounded_channel();
@@ -3393,6 +5510,7 @@ pub async fn serve(
         None
     };
     let (internal_cmd_tx, internal_cmd_rx) = mpsc::unbounded_channel();
+    let handle_internal_cmd_tx = internal_cmd_tx.clone();
     let symbiosis = if let Some(symbiosis_url) = symbiosis_url {
         Some(symbiosis::Postgres::open_and_erase(symbiosis_url).await?)
     } else {
@@ -3414,6 +5532,7 @@ pub async fn serve(
     let worker_guards = dataflow::serve(dataflow::Config {
         command_receivers: worker_rxs,
         timely_worker,
+        spill_directory: data_directory.join("spill"),
     })
     .map_err(|s| CoordError::Unstructured(anyhow!("{}", s)))?;
 
@@ -3448,7 +5567,41 @@ pub async fn serve(
         last_op_was_read: false,
         need_advance: true,
         transient_id_counter: 1,
+        statement_id_counter: 0,
         active_conns: HashMap::new(),
+        queued_cmds: Arc::clone(&queued_cmds),
+        admission: AdmissionController::new(quotas.max_memory, quotas.max_queue_depth),
+        quotas,
+        active_peeks: HashMap::new(),
+        arrangement_bytes: HashMap::new(),
+        source_uppers: HashMap::new(),
+        peek_cache: Arc::new(Mutex::new(PeekCache::default())),
+        compaction_tuner: if auto_tune_logical_compaction_window
+            && logical_compaction_window.is_some()
+        {
+            Some(CompactionTuner::default())
+        } else {
+            None
+        },
+        compaction_window_rows: HashMap::new(),
+        object_json_rows: HashMap::new(),
+        statement_stats: StatementStatistics::new(now()),
+        slow_query_threshold,
+        statement_history: StatementHistory::open(data_directory, statement_history_size, now()),
+        dataflow_watchdog: stalled_dataflow_threshold.map(DataflowWatchdog::new),
+        shared_dataflows: shared_dataflow_idle_timeout.map(|idle_timeout| {
+            SharedDataflows::new(idle_timeout, shared_dataflow_min_peeks, now.clone())
+        }),
+        freshness: FreshnessTracker::new(freshness_slo),
+        log_filter,
+        start_time: Instant::now(),
+        rehydration_budget,
+        rehydrating_sources: HashSet::new(),
+        hydration_waiters: HydrationWaiters::default(),
+        pending_writes: PendingWrites::default(),
+        task_scheduler: TaskScheduler::new(now.clone()),
+        lazy_views: LazyViews::new(lazy_view_idle_timeout, now.clone()),
+        now,
     };
     coord.broadcast(SequencedCommand::EnableFeedback(feedback_tx));
     if let Some(config) = &logging {
@@ -3472,13 +5625,15 @@ pub async fn serve(
                     cmd_rx,
                     feedback_rx,
                     timestamper_thread_handle,
+                    manual_ticks,
                 ))
             });
             let handle = Handle {
+                internal_cmd_tx: handle_internal_cmd_tx,
                 cluster_id,
                 _thread: thread.join_on_drop(),
             };
-            let client = Client::new(cmd_tx);
+            let client = Client::new(cmd_tx, queued_cmds);
             Ok((handle, client))
         }
         Err(e) => {
@@ -3507,11 +5662,42 @@ enum ExprPrepStyle {
     OneShot { logical_time: u64 },
 }
 
+/// How a slow-path peek can share a transient dataflow with other peeks.
+enum SharedPeek {
+    /// The peek can read from the index of the existing shared dataflow.
+    Existing(GlobalId, SharedDataflowGuard),
+    /// The peek should build a new shared dataflow.
+    Build,
+}
+
+/// Marks a peek as outstanding for as long as it is alive.
+struct PeekGuard(Arc<AtomicUsize>);
+
+impl Drop for PeekGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Validates that `value`, if present, is a valid default for the
+/// configuration parameter `name`, and returns the canonical name of the
+/// parameter.
+fn validate_parameter_default(name: &str, value: Option<&str>) -> Result<String, CoordError> {
+    let mut vars = Vars::default();
+    let name = vars.get(name)?.name();
+    if let Some(value) = value {
+        vars.set_default(name, value)?;
+    }
+    Ok(name.into())
+}
+
 /// Constructs an [`ExecuteResponse`] that that will send some rows to the
 /// client immediately, as opposed to asking the dataflow layer to send along
 /// the rows after some computation.
 fn send_immediate_rows(rows: Vec<Row>) -> ExecuteResponse {
-    ExecuteResponse::SendingRows(Box::pin(async { PeekResponse::Rows(rows) }))
+    ExecuteResponse::SendingRows(Box::new(stream::once(future::ready(PeekResponse::Rows(
+        rows,
+    )))))
 }
 
 fn auto_generate_primary_idx(
@@ -3532,8 +5718,10 @@ fn auto_generate_primary_idx(
             .iter()
             .map(|k| MirScalarExpr::Column(*k))
             .collect(),
+        predicates: vec![],
         conn_id,
         depends_on,
+        lazy: false,
     }
 }
 
@@ -3559,6 +5747,7 @@ pub fn index_sql(
                 .collect(),
         ),
         with_options: vec![],
+        predicate: None,
         if_not_exists: false,
     }
     .to_ast_string_stable()
@@ -3577,6 +5766,21 @@ fn duration_to_timestamp_millis(d: Duration) -> Timestamp {
     }
 }
 
+/// Reports whether the timestamper tracks the offsets of a source with
+/// `connector`, and so reports when the source connects.
+fn timestamper_tracks_offsets(connector: &SourceConnector) -> bool {
+    match connector {
+        SourceConnector::External {
+            consistency: Consistency::BringYourOwn(_),
+            ..
+        } => true,
+        SourceConnector::External { connector, .. } => {
+            matches!(connector, ExternalSourceConnector::Kafka(_))
+        }
+        SourceConnector::Local => false,
+    }
+}
+
 /// Creates a description of the statement `stmt`.
 ///
 /// This function is identical to sql::plan::describe except this is also
@@ -0,0 +1,146 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Admission control for expensive queries.
+//!
+//! A query that cannot be served from an existing index builds a transient
+//! dataflow, whose memory use is unbounded. When the process is already short
+//! on memory, admitting another such query risks the OOM killer taking down
+//! the whole instance. The [`AdmissionController`] instead rejects these
+//! queries with a retryable error while the process's memory usage or the
+//! depth of the coordinator's command queue exceeds its configured limits.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use sysinfo::{ProcessExt, SystemExt};
+
+/// How long a measurement of the process's memory usage remains current.
+const MEMORY_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Decides whether to admit queries that require a transient dataflow.
+pub struct AdmissionController {
+    /// The memory usage, in bytes, above which queries are rejected.
+    max_memory: Option<usize>,
+    /// The number of commands waiting for the coordinator above which queries
+    /// are rejected.
+    max_queue_depth: Option<usize>,
+    system: sysinfo::System,
+    pid: Option<sysinfo::Pid>,
+    /// The most recent measurement of the process's memory usage, in bytes,
+    /// and when it was taken.
+    memory: Option<(usize, Instant)>,
+}
+
+/// The reason a query was not admitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overload {
+    /// The process's memory usage exceeds the limit.
+    Memory { usage: usize, limit: usize },
+    /// The number of commands waiting for the coordinator exceeds the limit.
+    QueueDepth { depth: usize, limit: usize },
+}
+
+impl fmt::Display for Overload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Overload::Memory { usage, limit } => write!(
+                f,
+                "memory usage of {} MiB exceeds the limit of {} MiB",
+                usage >> 20,
+                limit >> 20
+            ),
+            Overload::QueueDepth { depth, limit } => write!(
+                f,
+                "{} commands waiting for the coordinator exceed the limit of {}",
+                depth, limit
+            ),
+        }
+    }
+}
+
+impl AdmissionController {
+    /// Constructs an admission controller that rejects queries while the
+    /// process uses more than `max_memory` bytes of memory, or while more than
+    /// `max_queue_depth` commands are waiting for the coordinator.
+    pub fn new(max_memory: Option<usize>, max_queue_depth: Option<usize>) -> AdmissionController {
+        AdmissionController {
+            max_memory,
+            max_queue_depth,
+            system: sysinfo::System::new(),
+            pid: sysinfo::get_current_pid().ok(),
+            memory: None,
+        }
+    }
+
+    /// Decides whether to admit a query that requires a transient dataflow,
+    /// given that `queue_depth` commands are waiting for the coordinator.
+    pub fn admit(&mut self, queue_depth: usize) -> Result<(), Overload> {
+        if let Some(limit) = self.max_queue_depth {
+            if queue_depth >= limit {
+                return Err(Overload::QueueDepth {
+                    depth: queue_depth,
+                    limit,
+                });
+            }
+        }
+        if let Some(limit) = self.max_memory {
+            if let Some(usage) = self.memory_usage() {
+                if usage >= limit {
+                    return Err(Overload::Memory { usage, limit });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the memory usage of the process, in bytes, if it can be
+    /// determined.
+    fn memory_usage(&mut self) -> Option<usize> {
+        // Measuring the process's memory usage reads from procfs, so a recent
+        // measurement is reused when queries arrive in a burst.
+        if let Some((usage, measured_at)) = self.memory {
+            if measured_at.elapsed() < MEMORY_REFRESH_INTERVAL {
+                return Some(usage);
+            }
+        }
+        let pid = self.pid?;
+        self.system.refresh_process(pid);
+        // sysinfo reports memory usage in KiB.
+        let usage = (self.system.get_process(pid)?.memory() as usize) << 10;
+        self.memory = Some((usage, Instant::now()));
+        Some(usage)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admission() {
+        let mut admission = AdmissionController::new(None, Some(2));
+        assert_eq!(admission.admit(0), Ok(()));
+        assert_eq!(admission.admit(1), Ok(()));
+        assert_eq!(
+            admission.admit(2),
+            Err(Overload::QueueDepth { depth: 2, limit: 2 })
+        );
+
+        // A process always uses more than one byte of memory.
+        let mut admission = AdmissionController::new(Some(1), None);
+        assert!(matches!(
+            admission.admit(100),
+            Err(Overload::Memory { limit: 1, .. })
+        ));
+
+        let mut admission = AdmissionController::new(None, None);
+        assert_eq!(admission.admit(100), Ok(()));
+    }
+}
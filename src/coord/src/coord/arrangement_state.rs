@@ -109,6 +109,11 @@ pub struct Frontiers<T: Timestamp> {
     /// This timestamp drives the advancement of the since frontier as a
     /// function of the upper frontier, trailing it by exactly this much.
     pub compaction_window_ms: Option<T>,
+    /// The interval to whose multiples the times of updates to the index are
+    /// rounded up, if any.
+    ///
+    /// The interval is fixed when the index's dataflow is rendered.
+    pub refresh_interval_ms: Option<T>,
 }
 
 impl<T: Timestamp> Frontiers<T> {
@@ -120,6 +125,7 @@ impl<T: Timestamp> Frontiers<T> {
             upper,
             since: Antichain::from_elem(T::minimum()),
             compaction_window_ms,
+            refresh_interval_ms: None,
         }
     }
 
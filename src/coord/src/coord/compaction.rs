@@ -0,0 +1,237 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Automatic tuning of logical compaction windows.
+//!
+//! A single global logical compaction window is either too small for indexes
+//! that are queried at historical timestamps, or wastes memory on indexes that
+//! are only ever queried at the latest timestamp. The [`CompactionTuner`]
+//! instead chooses a window for each index individually, based on how far
+//! behind the index's upper frontier queries have asked to read it, and falls
+//! back to the configured window when the system is short on memory.
+
+use std::cmp;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use sysinfo::SystemExt;
+
+use expr::GlobalId;
+use repr::Timestamp;
+
+use super::arrangement_state::ArrangementFrontiers;
+
+/// The interval at which compaction windows are adjusted.
+const TUNING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The largest compaction window, in milliseconds, that tuning will choose.
+const MAX_WINDOW_MS: Timestamp = 60 * 60 * 1000;
+
+/// The fraction of system memory in use above which compaction windows are
+/// reset to the configured window.
+const MEMORY_PRESSURE_THRESHOLD: f64 = 0.9;
+
+/// Chooses a logical compaction window for each index.
+pub struct CompactionTuner {
+    /// The indexes whose compaction window is chosen by the tuner.
+    indexes: HashMap<GlobalId, TunedIndex>,
+    last_tuned: Instant,
+    system: sysinfo::System,
+}
+
+/// Tuning state for an index.
+struct TunedIndex {
+    /// The compaction window, in milliseconds, to use if the index is only
+    /// queried at recent timestamps.
+    base_window_ms: Timestamp,
+    /// The greatest lag, in milliseconds, behind the index's upper frontier at
+    /// which a query has requested to read the index, decayed over time.
+    observed_lag_ms: Timestamp,
+}
+
+impl Default for CompactionTuner {
+    fn default() -> CompactionTuner {
+        CompactionTuner {
+            indexes: HashMap::new(),
+            last_tuned: Instant::now(),
+            system: sysinfo::System::new(),
+        }
+    }
+}
+
+impl CompactionTuner {
+    /// Begins tuning the compaction window of the index `id`, which would
+    /// otherwise use a window of `base_window_ms`.
+    pub fn register(&mut self, id: GlobalId, base_window_ms: Timestamp) {
+        self.indexes.insert(
+            id,
+            TunedIndex {
+                base_window_ms,
+                observed_lag_ms: 0,
+            },
+        );
+    }
+
+    /// Stops tuning the compaction window of the index `id`, either because it
+    /// has been dropped or because its window has been set explicitly.
+    pub fn forget(&mut self, id: GlobalId) {
+        self.indexes.remove(&id);
+    }
+
+    /// Reports whether the compaction window of `id` is chosen by the tuner.
+    pub fn is_tuned(&self, id: GlobalId) -> bool {
+        self.indexes.contains_key(&id)
+    }
+
+    /// Records that a query requested to read the index `id` at a timestamp
+    /// `lag_ms` milliseconds behind the index's upper frontier.
+    pub fn observe(&mut self, id: GlobalId, lag_ms: Timestamp) {
+        if let Some(index) = self.indexes.get_mut(&id) {
+            index.observed_lag_ms = cmp::max(index.observed_lag_ms, lag_ms);
+        }
+    }
+
+    /// Adjusts the compaction windows of the tuned indexes, if enough time has
+    /// passed since the last adjustment.
+    ///
+    /// Returns the identifiers of the indexes whose window changed.
+    pub fn tune(&mut self, frontiers: &mut ArrangementFrontiers<Timestamp>) -> Vec<GlobalId> {
+        if self.last_tuned.elapsed() < TUNING_INTERVAL {
+            return vec![];
+        }
+        self.last_tuned = Instant::now();
+        let under_pressure = self.under_memory_pressure();
+        self.adjust(under_pressure, frontiers)
+    }
+
+    /// Chooses a new compaction window for each tuned index in `frontiers`,
+    /// and decays the observed lags.
+    fn adjust(
+        &mut self,
+        under_pressure: bool,
+        frontiers: &mut ArrangementFrontiers<Timestamp>,
+    ) -> Vec<GlobalId> {
+        let mut changed = vec![];
+        for (id, index) in &mut self.indexes {
+            let window = if under_pressure {
+                index.base_window_ms
+            } else {
+                let window = cmp::max(index.base_window_ms, index.observed_lag_ms);
+                cmp::min(window, cmp::max(index.base_window_ms, MAX_WINDOW_MS))
+            };
+            if let Some(frontiers) = frontiers.get_mut(id) {
+                if frontiers.compaction_window_ms != Some(window) {
+                    frontiers.set_compaction_window_ms(Some(window));
+                    changed.push(*id);
+                }
+            }
+            // Halve the observed lag, so that the window shrinks back once
+            // queries stop asking for old timestamps.
+            index.observed_lag_ms /= 2;
+        }
+        changed
+    }
+
+    fn under_memory_pressure(&mut self) -> bool {
+        self.system.refresh_memory();
+        let total = self.system.get_total_memory();
+        let used = self.system.get_used_memory();
+        total > 0 && (used as f64 / total as f64) >= MEMORY_PRESSURE_THRESHOLD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::arrangement_state::Frontiers;
+
+    fn window(frontiers: &ArrangementFrontiers<Timestamp>, id: GlobalId) -> Option<Timestamp> {
+        frontiers.get(&id).unwrap().compaction_window_ms
+    }
+
+    #[test]
+    fn test_window_follows_observed_lag() {
+        let id = GlobalId::User(1);
+        let mut frontiers = ArrangementFrontiers::default();
+        frontiers.insert(id, Frontiers::new(1, Some(1)));
+        let mut tuner = CompactionTuner::default();
+        tuner.register(id, 1);
+
+        // Without any historical reads, the window is the base window.
+        assert!(tuner.adjust(false, &mut frontiers).is_empty());
+        assert_eq!(window(&frontiers, id), Some(1));
+
+        // A historical read widens the window to cover it.
+        tuner.observe(id, 1000);
+        assert_eq!(tuner.adjust(false, &mut frontiers), vec![id]);
+        assert_eq!(window(&frontiers, id), Some(1000));
+
+        // Once reads stop, the window shrinks back by half at each
+        // adjustment, but never below the base window.
+        assert_eq!(tuner.adjust(false, &mut frontiers), vec![id]);
+        assert_eq!(window(&frontiers, id), Some(500));
+        for _ in 0..20 {
+            tuner.adjust(false, &mut frontiers);
+        }
+        assert_eq!(window(&frontiers, id), Some(1));
+    }
+
+    #[test]
+    fn test_window_is_bounded() {
+        let id = GlobalId::User(1);
+        let mut frontiers = ArrangementFrontiers::default();
+        frontiers.insert(id, Frontiers::new(1, Some(1)));
+        let mut tuner = CompactionTuner::default();
+        tuner.register(id, 1);
+
+        tuner.observe(id, 10 * MAX_WINDOW_MS);
+        tuner.adjust(false, &mut frontiers);
+        assert_eq!(window(&frontiers, id), Some(MAX_WINDOW_MS));
+
+        // A base window larger than the maximum is never narrowed.
+        let wide = GlobalId::User(2);
+        frontiers.insert(wide, Frontiers::new(1, Some(2 * MAX_WINDOW_MS)));
+        tuner.register(wide, 2 * MAX_WINDOW_MS);
+        tuner.observe(wide, 10 * MAX_WINDOW_MS);
+        tuner.adjust(false, &mut frontiers);
+        assert_eq!(window(&frontiers, wide), Some(2 * MAX_WINDOW_MS));
+    }
+
+    #[test]
+    fn test_memory_pressure_resets_window() {
+        let id = GlobalId::User(1);
+        let mut frontiers = ArrangementFrontiers::default();
+        frontiers.insert(id, Frontiers::new(1, Some(1)));
+        let mut tuner = CompactionTuner::default();
+        tuner.register(id, 1);
+
+        tuner.observe(id, 1000);
+        tuner.adjust(false, &mut frontiers);
+        assert_eq!(window(&frontiers, id), Some(1000));
+
+        tuner.observe(id, 1000);
+        assert_eq!(tuner.adjust(true, &mut frontiers), vec![id]);
+        assert_eq!(window(&frontiers, id), Some(1));
+    }
+
+    #[test]
+    fn test_untuned_indexes_are_ignored() {
+        let id = GlobalId::User(1);
+        let mut frontiers = ArrangementFrontiers::default();
+        frontiers.insert(id, Frontiers::new(1, Some(5)));
+        let mut tuner = CompactionTuner::default();
+        tuner.register(id, 1);
+        tuner.forget(id);
+        assert!(!tuner.is_tuned(id));
+
+        tuner.observe(id, 1000);
+        assert!(tuner.adjust(false, &mut frontiers).is_empty());
+        assert_eq!(window(&frontiers, id), Some(5));
+    }
+}
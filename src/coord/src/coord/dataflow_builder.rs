@@ -118,6 +118,9 @@ impl<'a> DataflowBuilder<'a> {
                 }
                 CatalogItem::View(view) => {
                     self.import_view_into_dataflow(id, &view.optimized_expr, dataflow);
+                    if let Some(hot_set_size) = view.reduce_hot_set_size {
+                        dataflow.spill_reduce_state(*id, hot_set_size);
+                    }
                 }
                 _ => unreachable!(),
             }
@@ -174,8 +177,21 @@ impl<'a> DataflowBuilder<'a> {
         let on_type = on_entry.desc().unwrap().typ().clone();
         let mut dataflow = DataflowDesc::new(index_entry.name().to_string());
         self.import_into_dataflow(&index.on, &mut dataflow);
-        dataflow.add_index_to_build(id, index.on.clone(), on_type.clone(), index.keys.clone());
-        dataflow.add_index_export(id, index.on, on_type, index.keys.clone());
+        if index.predicates.is_empty() {
+            dataflow.add_index_to_build(id, index.on.clone(), on_type.clone(), index.keys.clone());
+            dataflow.add_index_export(id, index.on, on_type, index.keys.clone());
+        } else {
+            // A partial index arranges only those rows that satisfy its
+            // predicates. The filtered collection is built under the index's
+            // own identifier, so that it is never mistaken for `index.on`.
+            let filtered = OptimizedMirRelationExpr::declare_optimized(
+                MirRelationExpr::global_get(index.on, on_type.clone())
+                    .filter(index.predicates.clone()),
+            );
+            dataflow.add_view_to_build(id, filtered, on_type.clone());
+            dataflow.add_index_to_build(id, id, on_type.clone(), index.keys.clone());
+            dataflow.add_index_export(id, id, on_type, index.keys.clone());
+        }
         dataflow
     }
 
@@ -0,0 +1,331 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Explanations of the dataflows that maintain sources and sinks.
+//!
+//! `EXPLAIN` of a query describes the relational plan that the query's
+//! dataflow computes. The dataflows of sources and sinks instead consist of
+//! connector-specific stages, like decoding, envelope processing, and writing
+//! consistency records, whose costs are invisible in a relational plan. The
+//! explanations here list those stages in the order in which the dataflow
+//! renders them.
+
+use std::fmt::Write;
+
+use dataflow_types::{
+    Compression, Consistency, DataEncoding, DeduplicateKeep, ExternalSourceConnector,
+    SinkConnector, SinkConnectorBuilder, SinkEnvelope, SourceConnector, SourceEnvelope,
+    TailEnvelope, UpsertMergeStrategy,
+};
+use expr::{ExprHumanizer, GlobalId, Id, MirRelationExpr};
+use interchange::avro::DebeziumDeduplicationStrategy;
+use repr::RelationDesc;
+use sql::ast::Ident;
+
+use crate::catalog::{Sink, SinkConnectorState, Source};
+
+/// Describes the stages of the dataflow that ingests the source `id`.
+pub fn explain_source(humanizer: &dyn ExprHumanizer, id: GlobalId, source: &Source) -> String {
+    let mut stages = Stages::new("source", humanizer, id);
+    match &source.connector {
+        SourceConnector::Local => {
+            stages.push("read the updates written by INSERT, UPDATE, and DELETE".into());
+        }
+        SourceConnector::External {
+            connector,
+            encoding,
+            envelope,
+            consistency,
+            ts_frequency,
+            event_time,
+        } => {
+            stages.push(format!("read {}", describe_source_connector(connector)));
+            stages.push(match consistency {
+                Consistency::RealTime => {
+                    format!("timestamp in real time, every {:?}", ts_frequency)
+                }
+                Consistency::BringYourOwn(topic) => {
+                    format!("timestamp from consistency topic {:?}", topic)
+                }
+            });
+            match envelope {
+                SourceEnvelope::Upsert(key_encoding, merge) => {
+                    stages.push(format!(
+                        "decode keys as {} and values as {}",
+                        describe_encoding(key_encoding),
+                        describe_encoding(encoding)
+                    ));
+                    stages.push(format!(
+                        "apply the upsert envelope, {} the value of each key",
+                        match merge {
+                            UpsertMergeStrategy::Replace => "replacing",
+                            UpsertMergeStrategy::JsonMergePatch => "merging JSON patches into",
+                        }
+                    ));
+                }
+                _ => stages.push(format!("decode {}", describe_encoding(encoding))),
+            }
+            match envelope {
+                SourceEnvelope::None | SourceEnvelope::Upsert(..) => (),
+                SourceEnvelope::Debezium(dedup) => stages.push(format!(
+                    "apply the Debezium envelope, {}",
+                    match dedup {
+                        DebeziumDeduplicationStrategy::Ordered => {
+                            String::from("deduplicating by trusting that offsets are ordered")
+                        }
+                        DebeziumDeduplicationStrategy::Full => {
+                            String::from("deduplicating by remembering every record")
+                        }
+                        DebeziumDeduplicationStrategy::FullInRange { start, end, .. } => format!(
+                            "deduplicating by remembering every record between {} and {}",
+                            start, end
+                        ),
+                    }
+                )),
+                SourceEnvelope::CdcV2 => stages.push("apply the CDCv2 envelope".into()),
+                SourceEnvelope::Deduplicate { key_indices, keep } => stages.push(format!(
+                    "deduplicate on {}, keeping the {} record for each key",
+                    column_list(&source.bare_desc, key_indices),
+                    match keep {
+                        DeduplicateKeep::First => "first",
+                        DeduplicateKeep::Last => "last",
+                    }
+                )),
+            }
+            if let Some(event_time) = event_time {
+                stages.push(format!(
+                    "assign event times from {}, with lateness {:?} and clock skew {:?}",
+                    column_list(&source.bare_desc, &[event_time.column]),
+                    event_time.lateness,
+                    event_time.clock_skew
+                ));
+            }
+        }
+    }
+    stages.push(
+        "apply the filters and projections pushed down by each dataflow that reads the source"
+            .into(),
+    );
+    // Most sources read their decoded records as they are.
+    let optimized_expr = source.optimized_expr.as_ref();
+    if !matches!(
+        optimized_expr,
+        MirRelationExpr::Get {
+            id: Id::LocalBareSource,
+            ..
+        }
+    ) {
+        let explanation = expr::explain::Explanation::new(optimized_expr, humanizer);
+        stages.push(format!("transform the decoded records:\n{}", explanation));
+    }
+    stages.push(format!(
+        "produce the columns {}",
+        column_list(&source.desc, &(0..source.desc.arity()).collect::<Vec<_>>())
+    ));
+    stages.finish()
+}
+
+/// Describes the stages of the dataflow that exports the sink `id`.
+pub fn explain_sink(humanizer: &dyn ExprHumanizer, id: GlobalId, sink: &Sink) -> String {
+    let mut stages = Stages::new("sink", humanizer, id);
+    stages.push(format!(
+        "read {} ({}), {} a snapshot of its current contents",
+        humanizer
+            .humanize_id(sink.from)
+            .unwrap_or_else(|| sink.from.to_string()),
+        sink.from,
+        if sink.with_snapshot {
+            "starting with"
+        } else {
+            "without"
+        }
+    ));
+
+    let (key_desc_and_indices, value_desc) = match &sink.connector {
+        SinkConnectorState::Ready(SinkConnector::Kafka(c)) => {
+            (c.key_desc_and_indices.as_ref(), &c.value_desc)
+        }
+        SinkConnectorState::Ready(SinkConnector::Tail(c)) => (None, &c.value_desc),
+        SinkConnectorState::Ready(SinkConnector::AvroOcf(c)) => (None, &c.value_desc),
+        SinkConnectorState::Pending(SinkConnectorBuilder::Kafka(b)) => {
+            (b.key_desc_and_indices.as_ref(), &b.value_desc)
+        }
+        SinkConnectorState::Pending(SinkConnectorBuilder::AvroOcf(b)) => (None, &b.value_desc),
+    };
+    if let Some((_key_desc, key_indices)) = key_desc_and_indices {
+        stages.push(format!("key by {}", column_list(value_desc, key_indices)));
+    }
+    stages.push("route every update to a single worker, to write them in order".into());
+    stages.push(match sink.envelope {
+        SinkEnvelope::Debezium => {
+            "arrange by key and apply the Debezium envelope, pairing the rows before and after \
+             each change at each timestamp"
+                .into()
+        }
+        SinkEnvelope::Upsert => {
+            "arrange by key and apply the upsert envelope, reporting the new value of each \
+             changed key at each timestamp"
+                .into()
+        }
+        SinkEnvelope::Tail { envelope, .. } => format!(
+            "apply the {} envelope",
+            match envelope {
+                TailEnvelope::Diffs => "diffs",
+                TailEnvelope::Upsert => "upsert",
+                TailEnvelope::Debezium => "Debezium",
+            }
+        ),
+    });
+
+    match &sink.connector {
+        SinkConnectorState::Ready(SinkConnector::Kafka(c)) => {
+            if let Some(consistency) = &c.consistency {
+                stages.push(format!(
+                    "append the timestamp to each value, and write the boundaries of each \
+                     timestamp to consistency topic {:?}",
+                    consistency.topic
+                ));
+            }
+            stages.push(format!(
+                "encode as Avro and write to kafka topic {:?} on {}, at most {} records at a time",
+                c.topic, c.addrs, c.fuel
+            ));
+        }
+        SinkConnectorState::Ready(SinkConnector::Tail(_)) => {
+            stages.push("send to the client of the TAIL".into());
+        }
+        SinkConnectorState::Ready(SinkConnector::AvroOcf(c)) => {
+            stages.push(format!("write to avro-ocf file {}", c.path.display()));
+        }
+        SinkConnectorState::Pending(SinkConnectorBuilder::Kafka(b)) => {
+            if b.consistency_value_schema.is_some() {
+                stages.push(
+                    "append the timestamp to each value, and write the boundaries of each \
+                     timestamp to a consistency topic"
+                        .into(),
+                );
+            }
+            stages.push(format!(
+                "encode as Avro and write to a kafka topic with prefix {:?} on {}, once the \
+                 topic is created",
+                b.topic_prefix, b.broker_addrs
+            ));
+        }
+        SinkConnectorState::Pending(SinkConnectorBuilder::AvroOcf(b)) => {
+            stages.push(format!(
+                "write to an avro-ocf file in {}, once the file is created",
+                b.path.display()
+            ));
+        }
+    }
+    stages.finish()
+}
+
+/// Accumulates the numbered stages of an explanation.
+struct Stages {
+    explanation: String,
+    count: usize,
+}
+
+impl Stages {
+    fn new(item_type: &str, humanizer: &dyn ExprHumanizer, id: GlobalId) -> Stages {
+        let name = humanizer.humanize_id(id).unwrap_or_else(|| id.to_string());
+        Stages {
+            explanation: format!("{} {} ({}):\n", item_type, name, id),
+            count: 0,
+        }
+    }
+
+    /// Appends a stage, indenting any lines after the first beneath it.
+    fn push(&mut self, stage: String) {
+        self.count += 1;
+        let mut lines = stage.lines();
+        if let Some(first) = lines.next() {
+            writeln!(self.explanation, "{:>3}. {}", self.count, first).unwrap();
+        }
+        for line in lines {
+            writeln!(self.explanation, "     {}", line).unwrap();
+        }
+    }
+
+    fn finish(self) -> String {
+        self.explanation
+    }
+}
+
+fn describe_source_connector(connector: &ExternalSourceConnector) -> String {
+    match connector {
+        ExternalSourceConnector::Kafka(k) => format!(
+            "kafka topic {:?} from {} on all workers{}",
+            k.topic,
+            k.addrs,
+            if k.enable_caching {
+                ", caching records to disk"
+            } else {
+                ""
+            }
+        ),
+        ExternalSourceConnector::Kinesis(k) => {
+            format!("kinesis stream {:?} on one worker", k.stream_name)
+        }
+        ExternalSourceConnector::File(f) | ExternalSourceConnector::AvroOcf(f) => format!(
+            "{} {} on one worker{}{}",
+            connector.name(),
+            f.path.display(),
+            if f.tail { ", tailing it" } else { "" },
+            describe_compression(&f.compression)
+        ),
+        ExternalSourceConnector::S3(s) => format!(
+            "s3 objects{} on one worker{}",
+            match &s.pattern {
+                Some(pattern) => format!(" matching {:?}", pattern.glob()),
+                None => String::new(),
+            },
+            describe_compression(&s.compression)
+        ),
+        ExternalSourceConnector::Postgres(p) => format!(
+            "postgres table {}.{} from publication {:?} on one worker",
+            Ident::new(p.namespace.as_str()),
+            Ident::new(p.table.as_str()),
+            p.publication
+        ),
+    }
+}
+
+fn describe_compression(compression: &Compression) -> &'static str {
+    match compression {
+        Compression::Gzip => ", decompressing with gzip",
+        Compression::Zstd => ", decompressing with zstd",
+        Compression::Bzip2 => ", decompressing with bzip2",
+        Compression::Auto => ", decompressing according to the file extension",
+        Compression::None => "",
+    }
+}
+
+fn describe_encoding(encoding: &DataEncoding) -> String {
+    match encoding {
+        DataEncoding::Avro(avro) if avro.confluent_wire_format => {
+            "Avro in the Confluent wire format".into()
+        }
+        DataEncoding::Csv(csv) => format!("Csv with {} columns", csv.n_cols),
+        _ => encoding.op_name().into(),
+    }
+}
+
+/// Formats the names of the columns of `desc` at `indices`.
+fn column_list(desc: &RelationDesc, indices: &[usize]) -> String {
+    let names: Vec<_> = indices
+        .iter()
+        .map(|i| match desc.get_name(*i) {
+            Some(name) => Ident::new(name.as_str()).to_string(),
+            None => format!("#{}", i),
+        })
+        .collect();
+    format!("({})", names.join(", "))
+}
@@ -0,0 +1,359 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! End-to-end freshness tracking.
+//!
+//! The freshness of an index is the delay between an event occurring in an
+//! upstream system and the index reflecting that event, i.e., the upper
+//! frontier of the index advancing past the timestamp that the event was
+//! assigned. Sources report the upstream time of each event they ingest, where
+//! the upstream system records one, like the timestamp of a Kafka message.
+//! The [`FreshnessTracker`] matches those times against the upper frontiers
+//! of the indexes that transitively depend on each source, and reports
+//! freshness percentiles over a trailing window in the `mz_view_freshness`
+//! table. If a freshness SLO is configured, whether each index is currently
+//! meeting it is exported as a Prometheus metric, on which alerts can be
+//! configured per view rather than per process.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use log::warn;
+use prometheus::{register_uint_gauge_vec, UIntGaugeVec};
+use timely::progress::frontier::AntichainRef;
+
+use expr::GlobalId;
+use repr::{Datum, Row, Timestamp};
+
+use super::arrangement_state::ArrangementFrontiers;
+
+/// The interval at which `mz_view_freshness` is brought up to date.
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The trailing window over which freshness percentiles are computed.
+const WINDOW_MS: u64 = 60_000;
+
+/// The maximum number of samples retained per index within the window.
+const MAX_SAMPLES: usize = 10_000;
+
+/// The reported percentiles, and their labels in the Prometheus metric.
+const PERCENTILES: &[(f64, &str)] = &[(0.5, "0.5"), (0.9, "0.9"), (0.99, "0.99")];
+
+lazy_static! {
+    static ref FRESHNESS: UIntGaugeVec = register_uint_gauge_vec!(
+        "mz_view_freshness_milliseconds",
+        "Percentiles of the delay between upstream events and their reflection in an index.",
+        &["index_id", "quantile"]
+    )
+    .unwrap();
+    static ref FRESHNESS_SLO_BREACHED: UIntGaugeVec = register_uint_gauge_vec!(
+        "mz_view_freshness_slo_breached",
+        "Whether the freshness of an index currently exceeds the configured SLO.",
+        &["index_id"]
+    )
+    .unwrap();
+}
+
+/// The freshness state of a single index.
+#[derive(Default)]
+struct IndexFreshness {
+    /// The sources that the index transitively depends on.
+    sources: Vec<GlobalId>,
+    /// Events at or before this timestamp were ingested while the index was
+    /// hydrating, and are not considered.
+    created_at: Timestamp,
+    /// The latest upstream event time at each timestamp that the index does
+    /// not yet reflect.
+    pending: BTreeMap<Timestamp, i64>,
+    /// The time at which each event within the window was reflected, and its
+    /// freshness, both in milliseconds.
+    samples: VecDeque<(u64, u64)>,
+    /// The row reported for the index, if any.
+    row: Option<Row>,
+}
+
+/// Measures the end-to-end freshness of indexes.
+pub struct FreshnessTracker {
+    slo: Option<Duration>,
+    indexes: HashMap<GlobalId, IndexFreshness>,
+    last_reported: Instant,
+}
+
+impl FreshnessTracker {
+    /// Constructs a tracker that considers an index in breach of its SLO once
+    /// its freshness exceeds `slo`, if specified.
+    pub fn new(slo: Option<Duration>) -> FreshnessTracker {
+        FreshnessTracker {
+            slo,
+            indexes: HashMap::new(),
+            last_reported: Instant::now(),
+        }
+    }
+
+    /// Begins tracking the index `id`, which reads from the indexes and
+    /// sources identified by `inputs`, and which is hydrated once its upper
+    /// frontier advances beyond `created_at`.
+    pub fn watch(&mut self, id: GlobalId, inputs: &[GlobalId], created_at: Timestamp) {
+        let mut sources = vec![];
+        for input in inputs {
+            match self.indexes.get(input) {
+                Some(index) => sources.extend(index.sources.iter().copied()),
+                None => sources.push(*input),
+            }
+        }
+        sources.sort();
+        sources.dedup();
+        self.indexes.insert(
+            id,
+            IndexFreshness {
+                sources,
+                created_at,
+                ..Default::default()
+            },
+        );
+    }
+
+    /// Records the upstream event times that the source `source` observed at
+    /// each timestamp.
+    ///
+    /// Events at timestamps that an index already reflects, e.g. because a
+    /// new instance of the source is rereading old data, are not attributed
+    /// to that index.
+    pub fn record_event_times(
+        &mut self,
+        source: GlobalId,
+        times: &[(Timestamp, i64)],
+        indexes: &ArrangementFrontiers<Timestamp>,
+    ) {
+        for (id, index) in &mut self.indexes {
+            if !index.sources.contains(&source) {
+                continue;
+            }
+            let upper = match indexes.upper_of(id) {
+                Some(upper) => upper,
+                None => continue,
+            };
+            for (time, upstream_time) in times {
+                if *time <= index.created_at || !upper.less_equal(time) {
+                    continue;
+                }
+                let latest = index.pending.entry(*time).or_insert(*upstream_time);
+                *latest = (*latest).max(*upstream_time);
+            }
+        }
+    }
+
+    /// Records that the upper frontier of the index `id` has advanced to
+    /// `upper` at time `now`, in milliseconds since the epoch.
+    pub fn upper_advanced(&mut self, id: GlobalId, upper: AntichainRef<Timestamp>, now: u64) {
+        let index = match self.indexes.get_mut(&id) {
+            Some(index) => index,
+            None => return,
+        };
+        while let Some((time, upstream_time)) = index
+            .pending
+            .iter()
+            .next()
+            .map(|(time, upstream_time)| (*time, *upstream_time))
+        {
+            if upper.less_equal(&time) {
+                break;
+            }
+            index.pending.remove(&time);
+            let freshness = u64::try_from(upstream_time).map_or(0, |t| now.saturating_sub(t));
+            if index.samples.len() == MAX_SAMPLES {
+                index.samples.pop_front();
+            }
+            index.samples.push_back((now, freshness));
+        }
+    }
+
+    /// Stops tracking the index `id`.
+    ///
+    /// Returns the update that retracts the index's row from
+    /// `mz_view_freshness`, if a row was reported.
+    pub fn forget(&mut self, id: GlobalId) -> Option<(Row, isize)> {
+        let index = self.indexes.remove(&id)?;
+        let label = id.to_string();
+        for (_, quantile) in PERCENTILES {
+            let _ = FRESHNESS.remove_label_values(&[label.as_str(), *quantile]);
+        }
+        let _ = FRESHNESS_SLO_BREACHED.remove_label_values(&[label.as_str()]);
+        index.row.map(|row| (row, -1))
+    }
+
+    /// Recomputes the freshness of each index at time `now`, in milliseconds
+    /// since the epoch, if enough time has passed since the last report.
+    ///
+    /// Returns the updates that bring `mz_view_freshness` up to date.
+    pub fn report(&mut self, now: u64) -> Vec<(Row, isize)> {
+        let mut updates = vec![];
+        if self.last_reported.elapsed() < REPORT_INTERVAL {
+            return updates;
+        }
+        self.last_reported = Instant::now();
+
+        for (id, index) in &mut self.indexes {
+            while let Some((reflected_at, _)) = index.samples.front() {
+                if now.saturating_sub(*reflected_at) <= WINDOW_MS {
+                    break;
+                }
+                index.samples.pop_front();
+            }
+            // The oldest event that the index does not yet reflect bounds its
+            // current freshness from below, even if the index is not advancing
+            // at all.
+            let pending_ms = index
+                .pending
+                .values()
+                .min()
+                .map(|t| u64::try_from(*t).map_or(0, |t| now.saturating_sub(t)));
+
+            let row = if index.samples.is_empty() && pending_ms.is_none() {
+                None
+            } else {
+                let mut freshness: Vec<u64> = index.samples.iter().map(|(_, f)| *f).collect();
+                freshness.sort_unstable();
+                let percentiles: Vec<_> = PERCENTILES
+                    .iter()
+                    .map(|(p, _)| percentile(&freshness, *p))
+                    .collect();
+                let worst = freshness.last().copied();
+
+                let label = id.to_string();
+                for ((_, quantile), value) in PERCENTILES.iter().zip(&percentiles) {
+                    if let Some(value) = value {
+                        FRESHNESS
+                            .with_label_values(&[label.as_str(), *quantile])
+                            .set(*value);
+                    }
+                }
+                let breached = self.slo.map(|slo| {
+                    let slo = u64::try_from(slo.as_millis()).unwrap_or(u64::MAX);
+                    let p99 = percentiles[PERCENTILES.len() - 1];
+                    p99.map_or(false, |p99| p99 > slo) || pending_ms.map_or(false, |p| p > slo)
+                });
+                if let Some(breached) = breached {
+                    let gauge = FRESHNESS_SLO_BREACHED.with_label_values(&[label.as_str()]);
+                    if breached && gauge.get() == 0 {
+                        warn!("index {} is not meeting its freshness SLO", id);
+                    }
+                    gauge.set(u64::from(breached));
+                }
+
+                let ms = |v: Option<u64>| match v {
+                    Some(v) => Datum::Int64(i64::try_from(v).unwrap_or(i64::MAX)),
+                    None => Datum::Null,
+                };
+                Some(Row::pack_slice(&[
+                    Datum::String(&label),
+                    Datum::Int64(index.samples.len() as i64),
+                    ms(percentiles[0]),
+                    ms(percentiles[1]),
+                    ms(percentiles[2]),
+                    ms(worst),
+                    ms(pending_ms),
+                    match breached {
+                        Some(breached) => Datum::from(breached),
+                        None => Datum::Null,
+                    },
+                ]))
+            };
+
+            if row != index.row {
+                if let Some(old) = index.row.take() {
+                    updates.push((old, -1));
+                }
+                if let Some(new) = &row {
+                    updates.push((new.clone(), 1));
+                }
+                index.row = row;
+            }
+        }
+        updates
+    }
+}
+
+/// Returns the `p`th percentile of `sorted`, using the nearest-rank method.
+fn percentile(sorted: &[u64], p: f64) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.max(1).min(sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::arrangement_state::Frontiers;
+
+    #[test]
+    fn test_percentile() {
+        assert_eq!(percentile(&[], 0.5), None);
+        assert_eq!(percentile(&[7], 0.99), Some(7));
+        let sorted: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&sorted, 0.5), Some(50));
+        assert_eq!(percentile(&sorted, 0.9), Some(90));
+        assert_eq!(percentile(&sorted, 0.99), Some(99));
+    }
+
+    #[test]
+    fn test_freshness() {
+        let source = GlobalId::User(1);
+        let view_index = GlobalId::User(2);
+        let downstream_index = GlobalId::User(3);
+        let mut indexes = ArrangementFrontiers::default();
+        indexes.insert(view_index, Frontiers::new(1, None));
+        indexes.insert(downstream_index, Frontiers::new(1, None));
+
+        let mut tracker = FreshnessTracker::new(Some(Duration::from_millis(500)));
+        tracker.watch(view_index, &[source], 10);
+        tracker.watch(downstream_index, &[view_index], 10);
+        assert_eq!(tracker.indexes[&downstream_index].sources, vec![source]);
+
+        // Events ingested during hydration are ignored.
+        tracker.record_event_times(source, &[(5, 900), (20, 1_000), (30, 1_100)], &indexes);
+        assert_eq!(tracker.indexes[&view_index].pending.len(), 2);
+        assert_eq!(tracker.indexes[&downstream_index].pending.len(), 2);
+
+        // Advancing past the first event yields a sample; the second event is
+        // still pending.
+        tracker.upper_advanced(view_index, AntichainRef::new(&[25]), 1_200);
+        tracker.last_reported -= REPORT_INTERVAL;
+        let updates = tracker.report(1_300);
+        assert_eq!(updates.len(), 2);
+        let row = tracker.indexes[&view_index].row.clone().unwrap();
+        let datums = row.unpack();
+        assert_eq!(datums[1], Datum::Int64(1));
+        assert_eq!(datums[2], Datum::Int64(200));
+        assert_eq!(datums[6], Datum::Int64(200));
+        assert_eq!(datums[7], Datum::False);
+
+        // The downstream index has no samples, but its oldest pending event
+        // breaches the SLO.
+        let row = tracker.indexes[&downstream_index].row.clone().unwrap();
+        let datums = row.unpack();
+        assert_eq!(datums[1], Datum::Int64(0));
+        assert_eq!(datums[2], Datum::Null);
+        assert_eq!(datums[6], Datum::Int64(300));
+        tracker.last_reported -= REPORT_INTERVAL;
+        tracker.report(1_600);
+        let row = tracker.indexes[&downstream_index].row.clone().unwrap();
+        assert_eq!(row.unpack()[7], Datum::True);
+
+        // Dropping the index retracts the reported row.
+        assert_eq!(
+            tracker.forget(downstream_index).map(|(_, diff)| diff),
+            Some(-1)
+        );
+    }
+}
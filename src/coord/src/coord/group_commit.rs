@@ -0,0 +1,85 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Table writes that are waiting to be committed together.
+//!
+//! Committing a transaction that wrote to tables does not immediately apply
+//! its writes. Instead the transaction joins the group of transactions that
+//! are waiting to commit, and the coordinator commits the whole group at a
+//! single write timestamp once it has no other messages to process, or once
+//! the group is due: when it grows large, or when its oldest transaction has
+//! waited for a few milliseconds. Under load from many sessions issuing small
+//! inserts, this amortizes the cost of assigning a timestamp and advancing the
+//! local inputs across every transaction in the group, rather than paying it
+//! once per transaction, while a steady stream of other messages cannot delay
+//! any commit indefinitely.
+
+use std::mem;
+use std::time::{Duration, Instant};
+
+use crate::command::ExecuteResponse;
+use crate::session::{Session, WriteOp};
+use crate::util::ClientTransmitter;
+
+/// The maximum number of transactions to commit together.
+pub const MAX_GROUP_SIZE: usize = 1024;
+
+/// The longest that a transaction waits to be committed while the
+/// coordinator has other messages to process.
+pub const MAX_GROUP_DELAY: Duration = Duration::from_millis(5);
+
+/// A transaction that is waiting for its writes to be committed.
+pub struct PendingWriteTxn {
+    /// The writes that the transaction performed.
+    pub writes: Vec<WriteOp>,
+    /// The response to send once the writes are committed.
+    pub response: ExecuteResponse,
+    pub tx: ClientTransmitter<ExecuteResponse>,
+    pub session: Session,
+}
+
+/// Tracks transactions that are waiting for their writes to be committed.
+#[derive(Default)]
+pub struct PendingWrites {
+    txns: Vec<PendingWriteTxn>,
+    /// When the oldest transaction in the group was added.
+    oldest: Option<Instant>,
+}
+
+impl PendingWrites {
+    /// Adds a transaction to the group that will next be committed.
+    pub fn push(&mut self, txn: PendingWriteTxn) {
+        if self.txns.is_empty() {
+            self.oldest = Some(Instant::now());
+        }
+        self.txns.push(txn);
+    }
+
+    /// Reports whether any transactions are waiting to be committed.
+    pub fn is_empty(&self) -> bool {
+        self.txns.is_empty()
+    }
+
+    /// Reports whether the group should be committed without waiting for the
+    /// coordinator to become idle, because it has grown large or because its
+    /// oldest transaction has waited long enough.
+    pub fn is_due(&self) -> bool {
+        self.txns.len() >= MAX_GROUP_SIZE
+            || self
+                .oldest
+                .map_or(false, |oldest| oldest.elapsed() >= MAX_GROUP_DELAY)
+    }
+
+    /// Removes and returns the transactions that are waiting to be
+    /// committed, in the order in which they were added.
+    pub fn take(&mut self) -> Vec<PendingWriteTxn> {
+        self.oldest = None;
+        mem::take(&mut self.txns)
+    }
+}
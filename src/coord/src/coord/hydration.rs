@@ -0,0 +1,102 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Statements that wait for the indexes they create to hydrate.
+//!
+//! A new index is hydrated once its dataflow has caught up with its inputs,
+//! i.e., once the upper frontier of the index has advanced past the time at
+//! which the index was created. Until then, queries that read from the index
+//! must wait for it to catch up. Sessions that enable the
+//! `wait_for_hydration` configuration parameter instead have the statement
+//! that creates the index wait, so that the index is ready to serve queries
+//! as soon as the statement completes.
+
+use std::mem;
+
+use expr::GlobalId;
+use repr::Timestamp;
+
+use super::arrangement_state::ArrangementFrontiers;
+use crate::command::ExecuteResponse;
+use crate::session::Session;
+use crate::util::ClientTransmitter;
+
+/// A statement that is waiting for an index to hydrate.
+struct Waiter {
+    /// The index that the statement created.
+    id: GlobalId,
+    /// The index is hydrated once its upper frontier is beyond this time.
+    target: Timestamp,
+    /// The response to send once the index is hydrated.
+    response: ExecuteResponse,
+    tx: ClientTransmitter<ExecuteResponse>,
+    session: Session,
+}
+
+/// Tracks statements that are waiting for indexes to hydrate.
+#[derive(Default)]
+pub struct HydrationWaiters {
+    waiters: Vec<Waiter>,
+}
+
+impl HydrationWaiters {
+    /// Arranges to send `response` to `tx` once the upper frontier of the
+    /// index `id` has advanced beyond `target`.
+    pub fn wait(
+        &mut self,
+        id: GlobalId,
+        target: Timestamp,
+        response: ExecuteResponse,
+        tx: ClientTransmitter<ExecuteResponse>,
+        session: Session,
+    ) {
+        self.waiters.push(Waiter {
+            id,
+            target,
+            response,
+            tx,
+            session,
+        });
+    }
+
+    /// Responds to the statements whose indexes are now hydrated.
+    ///
+    /// Statements whose indexes have since been dropped are responded to as
+    /// well, as there is no longer anything to wait for.
+    pub fn notify(&mut self, indexes: &ArrangementFrontiers<Timestamp>) {
+        if self.waiters.is_empty() {
+            return;
+        }
+        let (hydrated, waiting): (Vec<_>, Vec<_>) = mem::take(&mut self.waiters)
+            .into_iter()
+            .partition(|waiter| match indexes.upper_of(&waiter.id) {
+                Some(upper) => !upper.less_equal(&waiter.target),
+                None => true,
+            });
+        self.waiters = waiting;
+        for waiter in hydrated {
+            waiter.tx.send(Ok(waiter.response), waiter.session);
+        }
+    }
+
+    /// Responds to the statements issued by the connection `conn_id` without
+    /// waiting any longer for their indexes to hydrate.
+    ///
+    /// The indexes already exist, so the statements are reported as
+    /// successful.
+    pub fn cancel(&mut self, conn_id: u32) {
+        let (cancelled, waiting): (Vec<_>, Vec<_>) = mem::take(&mut self.waiters)
+            .into_iter()
+            .partition(|waiter| waiter.session.conn_id() == conn_id);
+        self.waiters = waiting;
+        for waiter in cancelled {
+            waiter.tx.send(Ok(waiter.response), waiter.session);
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Recommendations of indexes that would benefit a query.
+//!
+//! The advice is derived from an optimized plan by looking for two patterns
+//! that an index on a catalog item would make cheaper: arrangements that the
+//! query would build privately over the item, and peeks that filter the item
+//! by literal values but must scan it in full because no index has a matching
+//! key.
+
+use std::collections::BTreeMap;
+
+use expr::{GlobalId, Id, MapFilterProject, MirRelationExpr, MirScalarExpr};
+
+use crate::catalog::Catalog;
+
+/// The reason an index was recommended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IndexBenefit {
+    /// The index would replace an arrangement built by the query itself.
+    SharedArrangement,
+    /// The index would allow the query to look up rows by key rather than
+    /// scanning the entire collection.
+    PointLookup,
+}
+
+impl IndexBenefit {
+    /// A short description of the benefit, suitable for display to users.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            IndexBenefit::SharedArrangement => {
+                "reuses the arrangement this query would otherwise build"
+            }
+            IndexBenefit::PointLookup => "enables point lookups instead of a full scan",
+        }
+    }
+}
+
+/// Determines the indexes that would benefit `expr`, which must be optimized.
+///
+/// Returns a map from catalog item and key columns to the benefit of an index
+/// on those columns. Only keys made up entirely of column references are
+/// recommended, and never keys for which an index already exists.
+pub fn advise(
+    catalog: &Catalog,
+    expr: &MirRelationExpr,
+) -> BTreeMap<(GlobalId, Vec<usize>), IndexBenefit> {
+    let mut advice = BTreeMap::new();
+
+    // Peeks read out of an existing index without a dataflow only if the
+    // query is a linear operator over a catalog item. When that operator
+    // constrains columns to literal values, an index keyed by those columns
+    // turns the peek into a point lookup.
+    let (mut mfp, inner) = MapFilterProject::extract_from_expression(expr);
+    mfp.optimize();
+    if let MirRelationExpr::Get {
+        id: Id::Global(id), ..
+    } = inner
+    {
+        let columns = (0..mfp.input_arity)
+            .filter(|c| mfp.literal_constraint(&MirScalarExpr::Column(*c)).is_some())
+            .collect::<Vec<_>>();
+        let served = catalog
+            .indexes()
+            .get(id)
+            .map(|indexes| {
+                indexes
+                    .iter()
+                    .any(|(_, keys)| mfp.literal_constraints(keys).is_some())
+            })
+            .unwrap_or(false);
+        if !columns.is_empty() && !served {
+            advice.insert((*id, columns), IndexBenefit::PointLookup);
+        }
+    }
+
+    expr.visit(&mut |e| {
+        if let MirRelationExpr::ArrangeBy { input, keys } = e {
+            if let MirRelationExpr::Get {
+                id: Id::Global(id), ..
+            } = &**input
+            {
+                let existing = catalog.indexes().get(id);
+                for key in keys {
+                    let exists = existing
+                        .map(|indexes| indexes.iter().any(|(_, k)| k == key))
+                        .unwrap_or(false);
+                    if exists {
+                        continue;
+                    }
+                    let columns = key
+                        .iter()
+                        .map(|k| match k {
+                            MirScalarExpr::Column(c) => Some(*c),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<_>>>();
+                    if let Some(columns) = columns {
+                        advice
+                            .entry((*id, columns))
+                            .or_insert(IndexBenefit::SharedArrangement);
+                    }
+                }
+            }
+        }
+    });
+
+    advice
+}
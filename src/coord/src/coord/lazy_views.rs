@@ -0,0 +1,183 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Materialized views that are materialized on demand.
+//!
+//! A view created with `CREATE MATERIALIZED VIEW ... WITH (lazy = true)` does
+//! not build its index when it is created. Instead, the first query that
+//! reads from the view creates the view's default index, named
+//! `<view>_lazy_idx`. If the view reads only from other indexes, the query
+//! waits for the new index to hydrate. If the view reads from sources, the
+//! sources must first assign timestamps to their data, so, like any query
+//! against a new index on a source, the query fails until the index has
+//! complete timestamps, and should be retried. Subsequent queries are
+//! served from the index until the view goes unqueried for the configured
+//! idle timeout, at which point the index, and with it the dataflow, is
+//! dropped. The next query materializes the view anew.
+//!
+//! The index is an ordinary catalog item while it exists, marked by its
+//! `lazy` flag. An index that survives a restart is rebuilt at boot, like
+//! any other index, and is dropped once the idle timeout elapses.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use expr::GlobalId;
+use ore::now::NowFn;
+use repr::Timestamp;
+
+/// Returns the name of the index that materializes the lazy view named
+/// `view_name`.
+pub fn index_name(view_name: &str) -> String {
+    format!("{}_lazy_idx", view_name)
+}
+
+struct LazyView {
+    /// The index that materializes the view, if the view is materialized.
+    index: Option<GlobalId>,
+    /// When the view was last queried, in milliseconds since the epoch.
+    last_used: u64,
+}
+
+/// Tracks the lazy views and when they were last queried.
+pub struct LazyViews {
+    idle_timeout: Duration,
+    now: NowFn,
+    views: HashMap<GlobalId, LazyView>,
+    /// The time by which each index created since startup will have caught
+    /// up with its inputs, for indexes that do not read from sources.
+    hydration_targets: HashMap<GlobalId, Timestamp>,
+}
+
+impl LazyViews {
+    /// Constructs a tracker that reports views as idle once they have gone
+    /// unqueried for `idle_timeout`, as measured by `now`.
+    pub fn new(idle_timeout: Duration, now: NowFn) -> LazyViews {
+        LazyViews {
+            idle_timeout,
+            now,
+            views: HashMap::new(),
+            hydration_targets: HashMap::new(),
+        }
+    }
+
+    /// Begins tracking the lazy view `id`.
+    pub fn register(&mut self, id: GlobalId) {
+        self.views.insert(
+            id,
+            LazyView {
+                index: None,
+                last_used: (self.now)(),
+            },
+        );
+    }
+
+    /// Stops tracking the view `id`.
+    pub fn forget(&mut self, id: GlobalId) {
+        self.views.remove(&id);
+    }
+
+    /// Reports whether `id` is a lazy view.
+    pub fn is_lazy(&self, id: GlobalId) -> bool {
+        self.views.contains_key(&id)
+    }
+
+    /// Records that the lazy view `view` is materialized by the index
+    /// `index`.
+    pub fn index_created(&mut self, view: GlobalId, index: GlobalId) {
+        if let Some(view) = self.views.get_mut(&view) {
+            view.index = Some(index);
+            view.last_used = (self.now)();
+        }
+    }
+
+    /// Records that the dataflow for the index `index` was just built, and
+    /// will have caught up with its inputs once its upper frontier advances
+    /// beyond `target`.
+    pub fn index_hydrating(&mut self, index: GlobalId, target: Timestamp) {
+        self.hydration_targets.insert(index, target);
+    }
+
+    /// Returns the time by which the lazy view index `index` will have
+    /// caught up with its inputs, if the index was built since startup.
+    pub fn hydration_target(&self, index: GlobalId) -> Option<Timestamp> {
+        self.hydration_targets.get(&index).copied()
+    }
+
+    /// Records that the index `index` was dropped.
+    pub fn index_dropped(&mut self, index: GlobalId) {
+        for view in self.views.values_mut() {
+            if view.index == Some(index) {
+                view.index = None;
+            }
+        }
+        self.hydration_targets.remove(&index);
+    }
+
+    /// Records that the lazy view `id` was queried.
+    pub fn touch(&mut self, id: GlobalId) {
+        if let Some(view) = self.views.get_mut(&id) {
+            view.last_used = (self.now)();
+        }
+    }
+
+    /// Returns the indexes of the materialized lazy views that have gone
+    /// unqueried for longer than the idle timeout.
+    pub fn idle_indexes(&self) -> Vec<GlobalId> {
+        let now = (self.now)();
+        let mut indexes: Vec<_> = self
+            .views
+            .values()
+            .filter(|view| {
+                Duration::from_millis(now.saturating_sub(view.last_used)) >= self.idle_timeout
+            })
+            .filter_map(|view| view.index)
+            .collect();
+        indexes.sort();
+        indexes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ore::now::ManualClock;
+
+    use super::*;
+
+    #[test]
+    fn test_idle_indexes() {
+        let (view, index) = (GlobalId::User(1), GlobalId::User(2));
+        let clock = ManualClock::new(0);
+        let mut lazy_views = LazyViews::new(Duration::from_secs(60), clock.now_fn());
+        lazy_views.register(view);
+        assert!(lazy_views.is_lazy(view));
+
+        // Unmaterialized views have no index to drop.
+        clock.set(120_000);
+        assert!(lazy_views.idle_indexes().is_empty());
+
+        // Materializing a view counts as a use.
+        lazy_views.index_created(view, index);
+        lazy_views.index_hydrating(index, 42);
+        assert_eq!(lazy_views.hydration_target(index), Some(42));
+        assert!(lazy_views.idle_indexes().is_empty());
+
+        clock.set(179_999);
+        assert!(lazy_views.idle_indexes().is_empty());
+        clock.set(180_000);
+        assert_eq!(lazy_views.idle_indexes(), vec![index]);
+        lazy_views.touch(view);
+        assert!(lazy_views.idle_indexes().is_empty());
+
+        lazy_views.index_dropped(index);
+        assert_eq!(lazy_views.hydration_target(index), None);
+        lazy_views.forget(view);
+        assert!(!lazy_views.is_lazy(view));
+    }
+}
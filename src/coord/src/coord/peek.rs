@@ -0,0 +1,682 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Incremental delivery of peek results.
+//!
+//! Each worker responds to a peek with the rows it holds, in batches. The
+//! functions in this module apply the query's finishing to those batches as
+//! they arrive and as the client consumes the rows, so that the coordinator
+//! need not hold a second copy of the entire result, and so that the workers
+//! can stop sending rows once the query's limit is reached.
+//!
+//! The [`PeekCache`] additionally allows identical peeks at the same timestamp
+//! to share their results.
+
+use std::cmp::{self, Ordering};
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::vec;
+
+use futures::ready;
+use futures::stream::{self, SelectAll, Stream, StreamExt};
+
+use dataflow_types::PeekResponse;
+use expr::{ColumnOrder, MirRelationExpr, RowSetFinishing};
+use repr::{Row, RowPacker, Timestamp};
+
+use crate::session::RowBatchStream;
+
+/// The maximum number of rows in each batch produced by an ordered merge.
+const MERGE_BATCH_SIZE: usize = 1024;
+
+/// The number of bytes after which a batch produced by an ordered merge is
+/// cut short, so that results with very large rows are still delivered
+/// incrementally.
+const MERGE_BATCH_BYTES: usize = 8 << 20;
+
+/// The maximum number of rows in a result that will be cached.
+///
+/// Larger results are not worth the memory that caching them would consume.
+const MAX_CACHED_ROWS: usize = 10_000;
+
+/// The maximum number of bytes in a result that will be cached.
+const MAX_CACHED_BYTES: usize = 64 << 20;
+
+/// Identifies a peek by its optimized plan and its finishing.
+pub type PeekCacheKey = (MirRelationExpr, RowSetFinishing);
+
+/// A cache of the results of peeks at the latest timestamp at which any peek
+/// has been performed.
+///
+/// The results of a peek are fully determined by its plan, finishing, and
+/// timestamp, so peeks that agree in all three can share results. Only
+/// results at the latest timestamp are retained, as peeks that are not
+/// explicitly timestamped are unlikely to ever choose an older timestamp.
+#[derive(Debug, Default)]
+pub struct PeekCache {
+    timestamp: Timestamp,
+    results: HashMap<PeekCacheKey, Vec<Row>>,
+}
+
+impl PeekCache {
+    /// Returns the cached results of the peek identified by `key` at
+    /// `timestamp`, if any.
+    pub fn get(&mut self, timestamp: Timestamp, key: &PeekCacheKey) -> Option<Vec<Row>> {
+        self.advance_to(timestamp);
+        if timestamp == self.timestamp {
+            self.results.get(key).cloned()
+        } else {
+            None
+        }
+    }
+
+    /// Records the results of the peek identified by `key` at `timestamp`.
+    pub fn insert(&mut self, timestamp: Timestamp, key: PeekCacheKey, rows: Vec<Row>) {
+        self.advance_to(timestamp);
+        if timestamp == self.timestamp {
+            self.results.insert(key, rows);
+        }
+    }
+
+    /// Discards all cached results if `timestamp` is newer than the
+    /// timestamp of the cached results.
+    fn advance_to(&mut self, timestamp: Timestamp) {
+        if timestamp > self.timestamp {
+            self.timestamp = timestamp;
+            self.results.clear();
+        }
+    }
+}
+
+/// Wraps `rows`, the finished results of the peek identified by `key` at
+/// `timestamp`, so that they are recorded in `cache` once they have been
+/// delivered in full.
+///
+/// Results that end in an error or cancellation, that are not consumed in
+/// full, or that are too large are not recorded.
+pub fn cache_results(
+    rows: RowBatchStream,
+    cache: Arc<Mutex<PeekCache>>,
+    timestamp: Timestamp,
+    key: PeekCacheKey,
+) -> RowBatchStream {
+    Box::new(CachingStream {
+        inner: rows,
+        buffer: Some(vec![]),
+        buffer_bytes: 0,
+        cache,
+        timestamp,
+        key: Some(key),
+    })
+}
+
+struct CachingStream {
+    inner: RowBatchStream,
+    /// The rows delivered so far, or `None` if the results are not to be
+    /// cached.
+    buffer: Option<Vec<Row>>,
+    /// The total size of the rows in `buffer`.
+    buffer_bytes: usize,
+    cache: Arc<Mutex<PeekCache>>,
+    timestamp: Timestamp,
+    key: Option<PeekCacheKey>,
+}
+
+impl Stream for CachingStream {
+    type Item = PeekResponse;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<PeekResponse>> {
+        let this = self.get_mut();
+        let resp = this.inner.poll_next_unpin(cx);
+        match &resp {
+            Poll::Pending => (),
+            Poll::Ready(Some(PeekResponse::Rows(rows))) => {
+                let len = this.buffer.as_ref().map(|b| b.len()).unwrap_or(0);
+                this.buffer_bytes += rows.iter().map(|row| row.byte_len()).sum::<usize>();
+                if len + rows.len() > MAX_CACHED_ROWS || this.buffer_bytes > MAX_CACHED_BYTES {
+                    this.buffer = None;
+                } else if let Some(buffer) = &mut this.buffer {
+                    buffer.extend(rows.iter().cloned());
+                }
+            }
+            Poll::Ready(Some(PeekResponse::Error(_)))
+            | Poll::Ready(Some(PeekResponse::Canceled)) => {
+                this.buffer = None;
+            }
+            Poll::Ready(None) => {
+                if let (Some(buffer), Some(key)) = (this.buffer.take(), this.key.take()) {
+                    this.cache
+                        .lock()
+                        .expect("lock poisoned")
+                        .insert(this.timestamp, key, buffer);
+                }
+            }
+        }
+        resp
+    }
+}
+
+/// Applies `finishing` to the peek `responses` of each worker, producing the
+/// finished rows incrementally.
+///
+/// If `finishing` does not specify an ordering, each batch of rows is finished
+/// and produced as soon as any worker sends it. Otherwise, each worker's rows
+/// must already be sorted according to the ordering, and they are merged as
+/// they arrive, which requires a pending batch from every worker that has yet
+/// to send all of its rows. In either case the stream ends as soon as the
+/// finishing's limit is reached, and an error or cancellation reported by any
+/// worker is produced in place of any further rows.
+///
+/// Once the stream ends, it drops `responses`, so that the workers can stop
+/// sending rows that are no longer needed.
+pub fn finish_responses<S>(responses: Vec<S>, finishing: RowSetFinishing) -> RowBatchStream
+where
+    S: Stream<Item = PeekResponse> + Send + Unpin + 'static,
+{
+    if finishing.order_by.is_empty() {
+        Box::new(UnorderedFinish {
+            limiter: Limiter::new(&finishing),
+            responses: Some(stream::select_all(responses)),
+        })
+    } else {
+        Box::new(OrderedMerge::new(responses, finishing))
+    }
+}
+
+/// Finishes the responses of all workers in the order in which they arrive.
+struct UnorderedFinish<S> {
+    limiter: Limiter,
+    /// The responses of all workers, or `None` once the results are finished.
+    responses: Option<SelectAll<S>>,
+}
+
+impl<S> Stream for UnorderedFinish<S>
+where
+    S: Stream<Item = PeekResponse> + Unpin,
+{
+    type Item = PeekResponse;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<PeekResponse>> {
+        let this = self.get_mut();
+        while let Some(responses) = &mut this.responses {
+            match ready!(responses.poll_next_unpin(cx)) {
+                Some(PeekResponse::Rows(rows)) => {
+                    let rows = this.limiter.apply(rows);
+                    if this.limiter.exhausted() {
+                        this.responses = None;
+                    }
+                    if !rows.is_empty() {
+                        return Poll::Ready(Some(PeekResponse::Rows(rows)));
+                    }
+                }
+                Some(resp) => {
+                    this.responses = None;
+                    return Poll::Ready(Some(resp));
+                }
+                None => this.responses = None,
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+/// Applies the offset, limit, and projection of a [`RowSetFinishing`] to a
+/// sequence of batches of rows.
+struct Limiter {
+    /// The number of rows that remain to be skipped.
+    offset: usize,
+    /// The number of rows that remain to be produced, if limited.
+    limit: Option<usize>,
+    project: Vec<usize>,
+    row_packer: RowPacker,
+}
+
+impl Limiter {
+    fn new(finishing: &RowSetFinishing) -> Limiter {
+        Limiter {
+            offset: finishing.offset,
+            limit: finishing.limit,
+            project: finishing.project.clone(),
+            row_packer: RowPacker::new(),
+        }
+    }
+
+    /// Returns the rows of the next batch that belong in the output.
+    fn apply(&mut self, mut rows: Vec<Row>) -> Vec<Row> {
+        let skip = cmp::min(self.offset, rows.len());
+        rows.drain(..skip);
+        self.offset -= skip;
+        if let Some(limit) = &mut self.limit {
+            rows.truncate(*limit);
+            *limit -= rows.len();
+        }
+        // Rows that the projection would leave unchanged are passed through
+        // as is, rather than copied.
+        let identity = rows
+            .first()
+            .map(|row| self.project.iter().copied().eq(0..row.iter().count()))
+            .unwrap_or(true);
+        if identity {
+            return rows;
+        }
+        for row in &mut rows {
+            let datums = row.unpack();
+            let new_row = self
+                .row_packer
+                .pack(self.project.iter().map(|i| &datums[*i]));
+            *row = new_row;
+        }
+        rows
+    }
+
+    /// Reports whether the limit has been reached.
+    fn exhausted(&self) -> bool {
+        self.limit == Some(0)
+    }
+}
+
+/// Merges the responses of all workers, each of which is sorted by a common
+/// ordering, into batches of finished rows.
+struct OrderedMerge<S> {
+    order_by: Vec<ColumnOrder>,
+    workers: Vec<WorkerRows<S>>,
+    limiter: Limiter,
+}
+
+/// The rows of one worker that are yet to be merged.
+struct WorkerRows<S> {
+    /// The worker's remaining responses, or `None` once it has sent them all.
+    responses: Option<S>,
+    /// The unmerged rows of the worker's most recent batch.
+    rows: Peekable<vec::IntoIter<Row>>,
+}
+
+impl<S> OrderedMerge<S>
+where
+    S: Stream<Item = PeekResponse> + Unpin,
+{
+    fn new(responses: Vec<S>, finishing: RowSetFinishing) -> OrderedMerge<S> {
+        OrderedMerge {
+            limiter: Limiter::new(&finishing),
+            order_by: finishing.order_by,
+            workers: responses
+                .into_iter()
+                .map(|responses| WorkerRows {
+                    responses: Some(responses),
+                    rows: Vec::new().into_iter().peekable(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Ensures that each worker that has yet to send all of its rows has a
+    /// pending batch to merge.
+    ///
+    /// Returns the first error or cancellation that any worker reports.
+    fn poll_workers(&mut self, cx: &mut Context) -> Poll<Option<PeekResponse>> {
+        let mut pending = false;
+        for worker in &mut self.workers {
+            while worker.rows.peek().is_none() {
+                let responses = match &mut worker.responses {
+                    Some(responses) => responses,
+                    None => break,
+                };
+                match responses.poll_next_unpin(cx) {
+                    Poll::Ready(Some(PeekResponse::Rows(rows))) => {
+                        worker.rows = rows.into_iter().peekable();
+                    }
+                    Poll::Ready(Some(resp)) => return Poll::Ready(Some(resp)),
+                    Poll::Ready(None) => worker.responses = None,
+                    Poll::Pending => {
+                        pending = true;
+                        break;
+                    }
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(None)
+        }
+    }
+
+    /// Removes and returns the least row at the head of any worker's batch,
+    /// unless that batch is exhausted and the worker has further batches to
+    /// send.
+    fn next_row(&mut self) -> Option<Row> {
+        let order_by = &self.order_by;
+        let mut least: Option<(usize, &Row)> = None;
+        for (i, worker) in self.workers.iter_mut().enumerate() {
+            if let Some(row) = worker.rows.peek() {
+                let less = match least {
+                    None => true,
+                    Some((_, least_row)) => {
+                        let ordering = expr::compare_columns(
+                            order_by,
+                            &row.unpack(),
+                            &least_row.unpack(),
+                            || row.cmp(least_row),
+                        );
+                        ordering == Ordering::Less
+                    }
+                };
+                if less {
+                    least = Some((i, row));
+                }
+            }
+        }
+        let i = least.map(|(i, _)| i)?;
+        self.workers[i].rows.next()
+    }
+
+    /// Reports whether some worker's batch is exhausted, but the worker has
+    /// further batches to send, without which the merge cannot proceed.
+    fn starved(&self) -> bool {
+        self.workers
+            .iter()
+            .any(|worker| worker.rows.len() == 0 && worker.responses.is_some())
+    }
+}
+
+impl<S> Stream for OrderedMerge<S>
+where
+    S: Stream<Item = PeekResponse> + Unpin,
+{
+    type Item = PeekResponse;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<PeekResponse>> {
+        let this = self.get_mut();
+        while !this.limiter.exhausted() {
+            if let Some(resp) = ready!(this.poll_workers(cx)) {
+                this.workers.clear();
+                return Poll::Ready(Some(resp));
+            }
+            let mut rows = Vec::new();
+            let mut bytes = 0;
+            while rows.len() < MERGE_BATCH_SIZE && bytes < MERGE_BATCH_BYTES && !this.starved() {
+                match this.next_row() {
+                    Some(row) => {
+                        bytes += row.byte_len();
+                        rows.push(row);
+                    }
+                    None => break,
+                }
+            }
+            if rows.is_empty() {
+                break;
+            }
+            let rows = this.limiter.apply(rows);
+            if !rows.is_empty() {
+                return Poll::Ready(Some(PeekResponse::Rows(rows)));
+            }
+        }
+        this.workers.clear();
+        Poll::Ready(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use futures::future::FutureExt;
+    use futures::stream::BoxStream;
+    use repr::Datum;
+
+    use super::*;
+
+    fn rows(values: &[i64]) -> Vec<Row> {
+        values
+            .iter()
+            .map(|v| Row::pack_slice(&[Datum::Int64(*v), Datum::Int64(-v)]))
+            .collect()
+    }
+
+    /// Returns a worker that sends `responses`, and then either hangs up, if
+    /// `done` is set, or never sends anything again.
+    fn worker(responses: Vec<PeekResponse>, done: bool) -> BoxStream<'static, PeekResponse> {
+        if done {
+            stream::iter(responses).boxed()
+        } else {
+            stream::iter(responses).chain(stream::pending()).boxed()
+        }
+    }
+
+    /// Finishes the responses of `workers`, which must not require waiting on
+    /// any worker that never sends anything again.
+    fn finish(
+        workers: Vec<BoxStream<'static, PeekResponse>>,
+        finishing: RowSetFinishing,
+    ) -> Vec<PeekResponse> {
+        finish_responses(workers, finishing)
+            .collect()
+            .now_or_never()
+            .expect("finished responses waited on a stalled worker")
+    }
+
+    #[test]
+    fn test_unordered_limit() {
+        let finishing = RowSetFinishing {
+            order_by: vec![],
+            limit: Some(3),
+            offset: 1,
+            project: vec![1],
+        };
+        // The limit is reached without waiting for the workers to hang up.
+        let out = finish(
+            vec![worker(
+                vec![
+                    PeekResponse::Rows(rows(&[1, 2])),
+                    PeekResponse::Rows(rows(&[3, 4])),
+                    PeekResponse::Rows(rows(&[5, 6])),
+                ],
+                false,
+            )],
+            finishing,
+        );
+        let expected = vec![
+            PeekResponse::Rows(vec![Row::pack_slice(&[Datum::Int64(-2)])]),
+            PeekResponse::Rows(vec![
+                Row::pack_slice(&[Datum::Int64(-3)]),
+                Row::pack_slice(&[Datum::Int64(-4)]),
+            ]),
+        ];
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_unordered_error() {
+        // An error from any worker ends the results, without waiting for the
+        // other workers.
+        let finishing = RowSetFinishing {
+            order_by: vec![],
+            limit: None,
+            offset: 0,
+            project: vec![0],
+        };
+        let out = finish(
+            vec![
+                worker(vec![PeekResponse::Rows(rows(&[1, 2]))], false),
+                worker(vec![PeekResponse::Error("division by zero".into())], true),
+            ],
+            finishing.clone(),
+        );
+        assert_eq!(
+            out.last(),
+            Some(&PeekResponse::Error("division by zero".into()))
+        );
+
+        let out = finish(
+            vec![
+                worker(vec![PeekResponse::Canceled], true),
+                worker(vec![], false),
+            ],
+            finishing,
+        );
+        assert_eq!(out, vec![PeekResponse::Canceled]);
+    }
+
+    #[test]
+    fn test_ordered_merge() {
+        let finishing = RowSetFinishing {
+            order_by: vec![ColumnOrder {
+                column: 0,
+                desc: true,
+            }],
+            limit: Some(4),
+            offset: 1,
+            project: vec![0, 1],
+        };
+        let out = finish(
+            vec![
+                worker(
+                    vec![
+                        PeekResponse::Rows(rows(&[9, 5])),
+                        PeekResponse::Rows(rows(&[1])),
+                    ],
+                    true,
+                ),
+                worker(vec![PeekResponse::Rows(rows(&[8, 7, 2]))], true),
+                worker(vec![], true),
+            ],
+            finishing.clone(),
+        );
+        assert_eq!(
+            out,
+            vec![
+                PeekResponse::Rows(rows(&[8, 7, 5])),
+                PeekResponse::Rows(rows(&[2])),
+            ]
+        );
+
+        // The limit is reached without waiting for the workers' remaining
+        // batches.
+        let finishing = RowSetFinishing {
+            limit: Some(2),
+            offset: 0,
+            ..finishing
+        };
+        let out = finish(
+            vec![
+                worker(vec![PeekResponse::Rows(rows(&[9, 5]))], false),
+                worker(vec![PeekResponse::Rows(rows(&[8, 7, 2]))], false),
+            ],
+            finishing,
+        );
+        assert_eq!(out, vec![PeekResponse::Rows(rows(&[9, 8]))]);
+    }
+
+    #[test]
+    fn test_ordered_error() {
+        let finishing = RowSetFinishing {
+            order_by: vec![ColumnOrder {
+                column: 0,
+                desc: false,
+            }],
+            limit: None,
+            offset: 0,
+            project: vec![0, 1],
+        };
+        let out = finish(
+            vec![
+                worker(vec![PeekResponse::Rows(rows(&[1, 2]))], false),
+                worker(vec![PeekResponse::Error("division by zero".into())], true),
+            ],
+            finishing,
+        );
+        assert_eq!(out, vec![PeekResponse::Error("division by zero".into())]);
+    }
+
+    #[test]
+    fn test_peek_cache() {
+        let finishing = RowSetFinishing {
+            order_by: vec![],
+            limit: None,
+            offset: 0,
+            project: vec![0, 1],
+        };
+        let key = (
+            MirRelationExpr::constant(vec![], repr::RelationType::empty()),
+            finishing,
+        );
+        let cache = Arc::new(Mutex::new(PeekCache::default()));
+
+        let cached = |responses: Vec<PeekResponse>, timestamp| {
+            let rows = Box::new(stream::iter(responses));
+            let rows = cache_results(rows, Arc::clone(&cache), timestamp, key.clone());
+            block_on(rows.collect::<Vec<_>>())
+        };
+
+        // Errors are not cached.
+        cached(vec![PeekResponse::Error("oops".into())], 5);
+        assert_eq!(cache.lock().unwrap().get(5, &key), None);
+
+        cached(
+            vec![
+                PeekResponse::Rows(rows(&[1])),
+                PeekResponse::Rows(rows(&[2])),
+            ],
+            5,
+        );
+        assert_eq!(cache.lock().unwrap().get(5, &key), Some(rows(&[1, 2])));
+
+        // Results at older timestamps are neither returned nor cached.
+        assert_eq!(cache.lock().unwrap().get(4, &key), None);
+        cached(vec![PeekResponse::Rows(rows(&[3]))], 4);
+        assert_eq!(cache.lock().unwrap().get(5, &key), Some(rows(&[1, 2])));
+
+        // Newer timestamps invalidate the cache.
+        assert_eq!(cache.lock().unwrap().get(6, &key), None);
+        assert_eq!(cache.lock().unwrap().get(5, &key), None);
+
+        // Results with too many bytes are not cached.
+        let big = "a".repeat(MAX_CACHED_BYTES / 2 + 1);
+        let big_rows = vec![Row::pack_slice(&[Datum::String(&big)])];
+        cached(
+            vec![
+                PeekResponse::Rows(big_rows.clone()),
+                PeekResponse::Rows(big_rows),
+            ],
+            6,
+        );
+        assert_eq!(cache.lock().unwrap().get(6, &key), None);
+    }
+
+    #[test]
+    fn test_ordered_merge_large_rows() {
+        let finishing = RowSetFinishing {
+            order_by: vec![ColumnOrder {
+                column: 0,
+                desc: false,
+            }],
+            limit: None,
+            offset: 0,
+            project: vec![0, 1],
+        };
+        let big = "a".repeat(MERGE_BATCH_BYTES / 2);
+        let row = |i| Row::pack_slice(&[Datum::Int64(i), Datum::String(&big)]);
+        let out = finish(
+            vec![
+                worker(vec![PeekResponse::Rows(vec![row(1), row(3)])], true),
+                worker(vec![PeekResponse::Rows(vec![row(2)])], true),
+            ],
+            finishing,
+        );
+        assert_eq!(
+            out,
+            vec![
+                PeekResponse::Rows(vec![row(1), row(2)]),
+                PeekResponse::Rows(vec![row(3)]),
+            ]
+        );
+    }
+}
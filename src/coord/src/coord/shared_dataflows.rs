@@ -0,0 +1,236 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Transient dataflows shared by the peeks of the same expression.
+//!
+//! A peek that cannot be served from an existing index builds a transient
+//! dataflow, which is torn down as soon as the peek is answered. Dashboards
+//! tend to issue many such queries that differ only in their outermost
+//! filter, projection, or ordering, and so rebuild the same dataflow over and
+//! over.
+//!
+//! Once an expression beneath a peek's outermost filter and projection has
+//! been peeked a configured number of times, each within the idle timeout of
+//! the last, the coordinator instead builds a dataflow for that expression alone,
+//! applies the filter and projection when reading from the dataflow's index,
+//! and keeps the dataflow running for later peeks of the same expression. The
+//! dataflow is torn down once no peek is reading from it and none has for
+//! the idle timeout, or once any of its inputs is dropped.
+//!
+//! Requiring a second peek keeps one-off queries from leaving dataflows
+//! behind. Dashboards that re-issue the same heavy query every few seconds can
+//! instead require only one, so that every peek after the first reuses the
+//! dataflow.
+//!
+//! Expressions that differ only in literals, like the times substituted for
+//! `mz_logical_timestamp()`, do not match, and so are not shared.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use expr::{GlobalId, MirRelationExpr};
+use ore::now::NowFn;
+
+struct SharedDataflow {
+    /// The index that the dataflow exports.
+    index_id: GlobalId,
+    /// The objects that the dataflow reads from.
+    depends_on: Vec<GlobalId>,
+    /// The number of outstanding peeks that read from the dataflow.
+    active: Arc<AtomicUsize>,
+    /// When the dataflow was last peeked, in milliseconds since the epoch.
+    last_used: u64,
+}
+
+/// Marks a peek of a shared dataflow as outstanding for as long as it is
+/// alive.
+pub struct SharedDataflowGuard(Arc<AtomicUsize>);
+
+impl Drop for SharedDataflowGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Tracks the shared dataflows and the recently peeked expressions.
+pub struct SharedDataflows {
+    idle_timeout: Duration,
+    now: NowFn,
+    /// The number of peeks of an expression after which it is shared.
+    min_peeks: usize,
+    dataflows: HashMap<MirRelationExpr, SharedDataflow>,
+    /// The hashes of the expressions that were recently peeked without a
+    /// shared dataflow, how many times they were peeked, and when they were
+    /// last peeked.
+    recent: HashMap<u64, (usize, u64)>,
+}
+
+impl SharedDataflows {
+    /// Constructs a tracker that shares the dataflow for an expression once it
+    /// has been peeked `min_peeks` times, and tears down shared dataflows once
+    /// they have been idle for `idle_timeout`, as measured by `now`.
+    pub fn new(idle_timeout: Duration, min_peeks: usize, now: NowFn) -> SharedDataflows {
+        SharedDataflows {
+            idle_timeout,
+            now,
+            min_peeks,
+            dataflows: HashMap::new(),
+            recent: HashMap::new(),
+        }
+    }
+
+    /// Looks up the shared dataflow for `expr`, if any, returning the index
+    /// that it exports and a guard that marks a peek of the index as
+    /// outstanding.
+    pub fn acquire(&mut self, expr: &MirRelationExpr) -> Option<(GlobalId, SharedDataflowGuard)> {
+        let dataflow = self.dataflows.get_mut(expr)?;
+        dataflow.last_used = (self.now)();
+        dataflow.active.fetch_add(1, Ordering::SeqCst);
+        Some((
+            dataflow.index_id,
+            SharedDataflowGuard(Arc::clone(&dataflow.active)),
+        ))
+    }
+
+    /// Records a peek of `expr`, which has no shared dataflow, and reports
+    /// whether a shared dataflow should be built for it, i.e., whether
+    /// `expr` has now been peeked the minimum number of times, each within the
+    /// idle timeout of the last.
+    pub fn should_share(&mut self, expr: &MirRelationExpr) -> bool {
+        let mut hasher = DefaultHasher::new();
+        expr.hash(&mut hasher);
+        let hash = hasher.finish();
+        let now = (self.now)();
+        let (peeks, peeked_at) = self.recent.entry(hash).or_insert((0, now));
+        if Duration::from_millis(now.saturating_sub(*peeked_at)) >= self.idle_timeout {
+            *peeks = 0;
+        }
+        *peeks += 1;
+        *peeked_at = now;
+        if *peeks >= self.min_peeks {
+            self.recent.remove(&hash);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Records that `index_id` is the index of a new shared dataflow for
+    /// `expr`, which reads from the objects in `depends_on`.
+    ///
+    /// Returns a guard that marks the peek that built the dataflow as
+    /// outstanding.
+    pub fn insert(
+        &mut self,
+        expr: MirRelationExpr,
+        index_id: GlobalId,
+        depends_on: Vec<GlobalId>,
+    ) -> SharedDataflowGuard {
+        let active = Arc::new(AtomicUsize::new(1));
+        self.dataflows.insert(
+            expr,
+            SharedDataflow {
+                index_id,
+                depends_on,
+                active: Arc::clone(&active),
+                last_used: (self.now)(),
+            },
+        );
+        SharedDataflowGuard(active)
+    }
+
+    /// Stops tracking the shared dataflows that read from `id`, which is
+    /// being dropped, returning the indexes that they export.
+    pub fn forget_dependents(&mut self, id: GlobalId) -> Vec<GlobalId> {
+        let mut indexes = vec![];
+        self.dataflows.retain(|_expr, dataflow| {
+            let dependent = dataflow.depends_on.contains(&id);
+            if dependent {
+                indexes.push(dataflow.index_id);
+            }
+            !dependent
+        });
+        indexes.sort();
+        indexes
+    }
+
+    /// Stops tracking the shared dataflows that have been idle for longer
+    /// than the idle timeout, returning the indexes that they export.
+    pub fn idle_indexes(&mut self) -> Vec<GlobalId> {
+        let idle_timeout = self.idle_timeout;
+        let now = (self.now)();
+        let elapsed = |since: u64| Duration::from_millis(now.saturating_sub(since));
+        let mut indexes = vec![];
+        self.dataflows.retain(|_expr, dataflow| {
+            let idle = dataflow.active.load(Ordering::SeqCst) == 0
+                && elapsed(dataflow.last_used) >= idle_timeout;
+            if idle {
+                indexes.push(dataflow.index_id);
+            }
+            !idle
+        });
+        self.recent
+            .retain(|_hash, (_peeks, peeked_at)| elapsed(*peeked_at) < idle_timeout);
+        indexes.sort();
+        indexes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ore::now::ManualClock;
+    use repr::RelationType;
+
+    use super::*;
+
+    #[test]
+    fn test_shared_dataflows() {
+        let expr = MirRelationExpr::constant(vec![], RelationType::empty()).distinct();
+        let (input, index) = (GlobalId::User(1), GlobalId::Transient(1));
+        let clock = ManualClock::new(0);
+        let mut shared = SharedDataflows::new(Duration::from_secs(60), 2, clock.now_fn());
+
+        // Expressions are shared once they are peeked a second time.
+        assert!(shared.acquire(&expr).is_none());
+        assert!(!shared.should_share(&expr));
+        assert!(shared.should_share(&expr));
+        let guard = shared.insert(expr.clone(), index, vec![input]);
+
+        // Dataflows with outstanding peeks are never idle.
+        clock.set(120_000);
+        assert!(shared.idle_indexes().is_empty());
+        drop(guard);
+        assert_eq!(shared.idle_indexes(), vec![index]);
+        assert!(shared.acquire(&expr).is_none());
+
+        // Dataflows are torn down along with their inputs.
+        shared.insert(expr.clone(), index, vec![input]);
+        let (id, _guard) = shared.acquire(&expr).unwrap();
+        assert_eq!(id, index);
+        assert!(shared.forget_dependents(GlobalId::User(2)).is_empty());
+        assert_eq!(shared.forget_dependents(input), vec![index]);
+        assert!(shared.acquire(&expr).is_none());
+
+        // Expressions can be shared as soon as they are peeked, or only after
+        // repeated peeks within the idle timeout.
+        let mut shared = SharedDataflows::new(Duration::from_secs(60), 1, clock.now_fn());
+        assert!(shared.should_share(&expr));
+        let mut shared = SharedDataflows::new(Duration::from_secs(60), 3, clock.now_fn());
+        assert!(!shared.should_share(&expr));
+        assert!(!shared.should_share(&expr));
+        clock.set(240_000);
+        assert!(!shared.should_share(&expr));
+        assert!(!shared.should_share(&expr));
+        assert!(shared.should_share(&expr));
+    }
+}
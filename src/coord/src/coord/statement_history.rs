@@ -0,0 +1,547 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A history of executed statements that persists across restarts.
+//!
+//! Each statement is recorded when it starts executing and again when it
+//! finishes. The records are appended to a log in the data directory as they
+//! occur, so that if the process crashes, the log shows which statements were
+//! running at the time. The log is replayed at startup, and statements that
+//! never finished are reported as interrupted. The history is reported in the
+//! `mz_statement_history` table.
+//!
+//! The log is bounded in size. It is split into two files, the current
+//! generation and the previous one, each of which holds at most half of the
+//! size limit. When the current file fills up, it replaces the previous file,
+//! and the statements that started in the replaced file are forgotten.
+//!
+//! The SQL of each statement is recorded with its literals and connection
+//! strings redacted, as these may contain passwords and other sensitive data.
+//! The log is written by a dedicated thread, so that slow disks do not stall
+//! the coordinator.
+
+use std::cmp;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use repr::{Datum, Row};
+use sql::ast::display::AstDisplay;
+use sql::ast::visit_mut::{self, VisitMut};
+use sql::ast::{Connector, Raw, Statement, Value};
+
+/// The name of the file that holds the current generation of the log.
+const FILE_NAME: &str = "statement_history";
+
+/// The name of the file that holds the previous generation of the log.
+const PREVIOUS_FILE_NAME: &str = "statement_history.previous";
+
+/// The maximum length of the SQL recorded for a statement, in bytes. Longer
+/// statements are truncated.
+const MAX_SQL_LEN: usize = 8 << 10;
+
+/// The interval at which changes to the history are reported.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The text that replaces redacted values.
+const REDACTED: &str = "<REDACTED>";
+
+/// Renders `stmt` as SQL with every literal value, including the values of
+/// `WITH` options, and every connection string replaced with a placeholder.
+pub fn redact(stmt: &Statement<Raw>) -> String {
+    struct Redactor;
+
+    impl<'ast> VisitMut<'ast, Raw> for Redactor {
+        fn visit_value_mut(&mut self, value: &'ast mut Value) {
+            *value = Value::String(REDACTED.into());
+        }
+
+        fn visit_connector_mut(&mut self, connector: &'ast mut Connector<Raw>) {
+            if let Connector::Postgres { conn, .. } = connector {
+                *conn = REDACTED.into();
+            }
+            visit_mut::visit_connector_mut(self, connector);
+        }
+    }
+
+    let mut stmt = stmt.clone();
+    Redactor.visit_statement_mut(&mut stmt);
+    stmt.to_ast_string()
+}
+
+/// A request to the thread that writes the log.
+enum WriterCommand {
+    /// Appends a line to the current log file.
+    Write(Vec<u8>),
+    /// Replaces the previous generation of the log with the current one.
+    Rotate,
+}
+
+/// Writes the log file `file` in directory `dir` as commanded by `rx`, until
+/// the sender hangs up.
+///
+/// If the log cannot be written, a warning is logged and later commands are
+/// ignored.
+fn write_log(dir: PathBuf, file: File, rx: Receiver<WriterCommand>) {
+    let mut file = Some(file);
+    for command in rx {
+        match command {
+            WriterCommand::Write(line) => {
+                if let Some(f) = &mut file {
+                    if let Err(e) = f.write_all(&line) {
+                        warn!("not persisting statement history: writing log: {}", e);
+                        file = None;
+                    }
+                }
+            }
+            WriterCommand::Rotate => {
+                if file.is_some() {
+                    let path = dir.join(FILE_NAME);
+                    let result = fs::rename(&path, dir.join(PREVIOUS_FILE_NAME))
+                        .and_then(|()| OpenOptions::new().create(true).append(true).open(&path));
+                    match result {
+                        Ok(f) => file = Some(f),
+                        Err(e) => {
+                            warn!("not persisting statement history: rotating log: {}", e);
+                            file = None;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An entry in the log.
+#[derive(Debug, Serialize, Deserialize)]
+enum Record {
+    Started {
+        id: u64,
+        conn_id: u32,
+        user: String,
+        sql: String,
+        tag: Option<String>,
+        started_at: u64,
+    },
+    Finished {
+        id: u64,
+        duration_ns: u64,
+        rows_returned: u64,
+        error: Option<String>,
+    },
+}
+
+/// A statement in the history.
+#[derive(Debug)]
+struct Entry {
+    conn_id: u32,
+    user: String,
+    sql: String,
+    tag: Option<String>,
+    /// When the statement started, in milliseconds since the Unix epoch.
+    started_at: u64,
+    /// The generation of the log that recorded the start of the statement.
+    generation: u64,
+    status: Status,
+}
+
+#[derive(Debug)]
+enum Status {
+    Running,
+    /// The process that was executing the statement exited before the
+    /// statement finished.
+    Interrupted,
+    Finished {
+        duration_ns: u64,
+        rows_returned: u64,
+        error: Option<String>,
+    },
+}
+
+/// A size-bounded, persistent history of executed statements.
+pub struct StatementHistory {
+    /// The maximum combined size of the log files, in bytes. A size of zero
+    /// disables the history.
+    max_size: u64,
+    /// Sends commands to the thread that writes the log, or `None` if the log
+    /// cannot be written.
+    writer_tx: Option<Sender<WriterCommand>>,
+    /// The thread that writes the log.
+    writer: Option<JoinHandle<()>>,
+    /// The size of the current log file, in bytes.
+    file_size: u64,
+    /// The generation of the current log file.
+    generation: u64,
+    next_id: u64,
+    entries: BTreeMap<u64, Entry>,
+    /// The rows last reported for each statement.
+    reported: HashMap<u64, Row>,
+    /// The statements whose rows have changed since they were last reported.
+    dirty: HashSet<u64>,
+    /// The time of the last flush, in milliseconds since the Unix epoch,
+    /// according to the coordinator's clock.
+    last_flushed: u64,
+}
+
+impl StatementHistory {
+    /// Opens the history whose log is stored in `dir`, and whose log files may
+    /// together occupy at most `max_size` bytes. If `max_size` is zero, the
+    /// history is disabled, and statements are neither recorded nor reported.
+    ///
+    /// The replayed history is first reported one flush interval after `now`.
+    pub fn open(dir: &Path, max_size: u64, now: u64) -> StatementHistory {
+        let mut history = StatementHistory {
+            max_size,
+            writer_tx: None,
+            writer: None,
+            file_size: 0,
+            generation: 0,
+            next_id: 0,
+            entries: BTreeMap::new(),
+            reported: HashMap::new(),
+            dirty: HashSet::new(),
+            last_flushed: now,
+        };
+        if max_size == 0 {
+            return history;
+        }
+
+        for name in &[PREVIOUS_FILE_NAME, FILE_NAME] {
+            history.generation += 1;
+            let path = dir.join(name);
+            match history.replay(&path) {
+                Ok(()) => (),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => warn!("reading statement history {}: {}", path.display(), e),
+            }
+        }
+        for entry in history.entries.values_mut() {
+            if let Status::Running = entry.status {
+                entry.status = Status::Interrupted;
+            }
+        }
+
+        let path = dir.join(FILE_NAME);
+        match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => {
+                history.file_size = file.metadata().map(|m| m.len()).unwrap_or(0);
+                let (tx, rx) = mpsc::channel();
+                let dir = dir.to_owned();
+                history.writer_tx = Some(tx);
+                history.writer = Some(thread::spawn(move || write_log(dir, file, rx)));
+            }
+            Err(e) => warn!(
+                "not persisting statement history: opening {}: {}",
+                path.display(),
+                e
+            ),
+        }
+        history
+    }
+
+    /// Applies the records in the log file at `path`.
+    fn replay(&mut self, path: &Path) -> Result<(), io::Error> {
+        for line in BufReader::new(File::open(path)?).lines() {
+            // The last record is incomplete if the process crashed while
+            // writing it.
+            if let Ok(record) = serde_json::from_str(&line?) {
+                self.apply(record);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, record: Record) {
+        match record {
+            Record::Started {
+                id,
+                conn_id,
+                user,
+                sql,
+                tag,
+                started_at,
+            } => {
+                self.next_id = cmp::max(self.next_id, id + 1);
+                self.entries.insert(
+                    id,
+                    Entry {
+                        conn_id,
+                        user,
+                        sql,
+                        tag,
+                        started_at,
+                        generation: self.generation,
+                        status: Status::Running,
+                    },
+                );
+                self.dirty.insert(id);
+            }
+            Record::Finished {
+                id,
+                duration_ns,
+                rows_returned,
+                error,
+            } => {
+                if let Some(entry) = self.entries.get_mut(&id) {
+                    entry.status = Status::Finished {
+                        duration_ns,
+                        rows_returned,
+                        error,
+                    };
+                    self.dirty.insert(id);
+                }
+            }
+        }
+    }
+
+    /// Records that connection `conn_id`, authenticated as `user`, started
+    /// executing `sql` with query tag `tag` at `now`. Returns the ID of the
+    /// statement, to be passed to [`StatementHistory::finish`].
+    ///
+    /// The caller is responsible for redacting `sql`; see [`redact`].
+    pub fn start(
+        &mut self,
+        conn_id: u32,
+        user: &str,
+        mut sql: String,
+        tag: Option<String>,
+        now: u64,
+    ) -> u64 {
+        let id = self.next_id;
+        if self.max_size == 0 {
+            return id;
+        }
+        if sql.len() > MAX_SQL_LEN {
+            let mut len = MAX_SQL_LEN;
+            while !sql.is_char_boundary(len) {
+                len -= 1;
+            }
+            sql.truncate(len);
+        }
+        self.record(Record::Started {
+            id,
+            conn_id,
+            user: user.into(),
+            sql,
+            tag,
+            started_at: now,
+        });
+        id
+    }
+
+    /// Records that the statement with ID `id` finished after `duration`,
+    /// having returned `rows_returned` rows and, if it failed, `error`.
+    pub fn finish(
+        &mut self,
+        id: u64,
+        duration: Duration,
+        rows_returned: u64,
+        error: Option<String>,
+    ) {
+        // The statement may have been forgotten while it was running.
+        if !self.entries.contains_key(&id) {
+            return;
+        }
+        self.record(Record::Finished {
+            id,
+            duration_ns: duration.as_nanos() as u64,
+            rows_returned,
+            error,
+        });
+    }
+
+    /// Sends `record` to the log writer and applies it.
+    ///
+    /// The log is rotated first if the record would not fit in the current
+    /// file. Sizes are tracked even if the log cannot be written, so that the
+    /// in-memory history remains bounded too.
+    fn record(&mut self, record: Record) {
+        let mut line = serde_json::to_vec(&record).expect("records serialize");
+        line.push(b'\n');
+        let len = line.len() as u64;
+        if self.file_size > 0 && self.file_size + len > self.max_size / 2 {
+            self.rotate();
+        }
+        self.send(WriterCommand::Write(line));
+        self.file_size += len;
+        self.apply(record);
+    }
+
+    fn send(&mut self, command: WriterCommand) {
+        if let Some(tx) = &self.writer_tx {
+            // The writer only exits once the sender is dropped.
+            tx.send(command).expect("statement history writer exited");
+        }
+    }
+
+    /// Replaces the previous generation of the log with the current one, and
+    /// forgets the statements that started in the previous generation.
+    fn rotate(&mut self) {
+        self.send(WriterCommand::Rotate);
+        self.file_size = 0;
+        self.generation += 1;
+
+        let generation = self.generation;
+        let forgotten: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.generation + 1 < generation)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in forgotten {
+            self.entries.remove(&id);
+            self.dirty.insert(id);
+        }
+    }
+
+    /// Returns the updates that bring `mz_statement_history` up to date, if
+    /// enough time has passed between the last flush and `now`.
+    pub fn flush(&mut self, now: u64) -> Option<Vec<(Row, isize)>> {
+        let elapsed = Duration::from_millis(now.saturating_sub(self.last_flushed));
+        if self.dirty.is_empty() || elapsed < FLUSH_INTERVAL {
+            return None;
+        }
+        self.last_flushed = now;
+
+        let mut updates = vec![];
+        for id in self.dirty.drain() {
+            if let Some(row) = self.reported.remove(&id) {
+                updates.push((row, -1));
+            }
+            if let Some(entry) = self.entries.get(&id) {
+                let (status, duration_ns, rows_returned, error) = match &entry.status {
+                    Status::Running => ("running", None, None, None),
+                    Status::Interrupted => ("interrupted", None, None, None),
+                    Status::Finished {
+                        duration_ns,
+                        rows_returned,
+                        error,
+                    } => (
+                        if error.is_some() { "error" } else { "success" },
+                        Some(*duration_ns as i64),
+                        Some(*rows_returned as i64),
+                        error.as_deref(),
+                    ),
+                };
+                let row = Row::pack_slice(&[
+                    Datum::Int64(id as i64),
+                    Datum::Int32(entry.conn_id as i32),
+                    Datum::String(&entry.user),
+                    Datum::String(&entry.sql),
+                    Datum::from(entry.tag.as_deref()),
+                    Datum::Int64(entry.started_at as i64),
+                    Datum::String(status),
+                    Datum::from(duration_ns),
+                    Datum::from(rows_returned),
+                    Datum::from(error),
+                ]);
+                updates.push((row.clone(), 1));
+                self.reported.insert(id, row);
+            }
+        }
+        Some(updates)
+    }
+}
+
+impl Drop for StatementHistory {
+    /// Waits for the log writer to write every record.
+    fn drop(&mut self) {
+        self.writer_tx.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(history: &StatementHistory, id: u64) -> Option<&'static str> {
+        history.entries.get(&id).map(|entry| match &entry.status {
+            Status::Running => "running",
+            Status::Interrupted => "interrupted",
+            Status::Finished { error: None, .. } => "success",
+            Status::Finished { error: Some(_), .. } => "error",
+        })
+    }
+
+    #[test]
+    fn test_statement_history() -> Result<(), io::Error> {
+        let dir = tempfile::tempdir()?;
+
+        let mut history = StatementHistory::open(dir.path(), 1 << 20, 0);
+        let a = history.start(1, "materialize", "SELECT 1".into(), None, 0);
+        let b = history.start(2, "materialize", "SELECT 2".into(), Some("app".into()), 0);
+        history.finish(a, Duration::from_millis(1), 1, None);
+        assert!(history.flush(999).is_none());
+        assert_eq!(history.flush(1000).unwrap().len(), 2);
+        drop(history);
+
+        // After a restart, the statement that was running is interrupted, and
+        // new statements receive new IDs.
+        let mut history = StatementHistory::open(dir.path(), 1 << 20, 0);
+        assert_eq!(status(&history, a), Some("success"));
+        assert_eq!(status(&history, b), Some("interrupted"));
+        let c = history.start(1, "materialize", "SELECT 3".into(), None, 0);
+        assert!(c > b);
+        history.finish(c, Duration::from_millis(1), 0, Some("boom".into()));
+        assert_eq!(status(&history, c), Some("error"));
+        assert_eq!(history.flush(1000).unwrap().len(), 3);
+
+        // Statements are forgotten once the log outgrows its size limit.
+        let mut history = StatementHistory::open(dir.path(), 1 << 10, 0);
+        for _ in 0..100 {
+            let id = history.start(1, "materialize", "SELECT 4".into(), None, 0);
+            history.finish(id, Duration::from_millis(1), 1, None);
+        }
+        assert_eq!(status(&history, a), None);
+        assert!(history.entries.len() < 100);
+        drop(history);
+        let size = fs::metadata(dir.path().join(FILE_NAME))?.len()
+            + fs::metadata(dir.path().join(PREVIOUS_FILE_NAME))?.len();
+        assert!(size <= 1 << 10, "log occupies {} bytes", size);
+
+        // A disabled history records nothing.
+        let mut history = StatementHistory::open(dir.path(), 0, 0);
+        let id = history.start(1, "materialize", "SELECT 5".into(), None, 0);
+        history.finish(id, Duration::from_millis(1), 1, None);
+        assert!(history.flush(1000).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_redact() {
+        let redact = |sql| redact(&sql_parser::parser::parse_statements(sql).unwrap()[0]);
+        assert_eq!(
+            redact("SELECT a FROM t WHERE b = 'secret' AND c = 42"),
+            "SELECT a FROM t WHERE b = '<REDACTED>' AND c = '<REDACTED>'"
+        );
+        assert_eq!(
+            redact(
+                "CREATE SOURCE s FROM KAFKA BROKER 'k:9092' TOPIC 't' \
+                 WITH (sasl_password = 'hunter2') FORMAT BYTES"
+            ),
+            "CREATE SOURCE s FROM KAFKA BROKER 'k:9092' TOPIC 't' \
+             WITH (sasl_password = '<REDACTED>') FORMAT BYTES"
+        );
+        let sql = redact(
+            "CREATE SOURCE s FROM POSTGRES HOST 'host=pg password=hunter2' \
+             PUBLICATION 'p' NAMESPACE 'public' TABLE 't' (a int)",
+        );
+        assert!(!sql.contains("hunter2"), "{}", sql);
+    }
+}
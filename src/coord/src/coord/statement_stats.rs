@@ -0,0 +1,401 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Aggregate statistics about executed statements.
+//!
+//! Statements are grouped by a normalized fingerprint, in which every literal
+//! value is replaced with a parameter placeholder, so that, e.g., `SELECT * FROM
+//! t WHERE a = 1` and `SELECT * FROM t WHERE a = 2` contribute to the same
+//! statistics. The statistics are reported in the `mz_statement_statistics` and
+//! `mz_statement_durations` tables.
+//!
+//! Statement durations are additionally exported as a Prometheus metric that
+//! is labeled with each statement's query tag, so that load can be attributed
+//! to the applications that issued it. Only the first [`MAX_QUERY_TAGS`]
+//! distinct tags receive their own label value, so that clients cannot create
+//! an unbounded number of time series.
+
+use std::cmp;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::stream::{Stream, StreamExt};
+use lazy_static::lazy_static;
+use prometheus::{register_histogram_vec, HistogramVec};
+
+use dataflow_types::PeekResponse;
+use repr::{Datum, Row};
+use sql::ast::display::AstDisplay;
+use sql::ast::visit::{self, Visit};
+use sql::ast::visit_mut::{self, VisitMut};
+use sql::ast::{Expr, Raw, Statement};
+
+use crate::session::RowBatchStream;
+
+/// The maximum number of fingerprints for which statistics are retained.
+const MAX_FINGERPRINTS: usize = 5_000;
+
+/// The interval at which changed statistics are reported.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The maximum number of distinct query tags that label the
+/// `mz_statement_durations` metric.
+const MAX_QUERY_TAGS: usize = 100;
+
+/// The label of the statements whose query tag was first seen after
+/// [`MAX_QUERY_TAGS`] others.
+///
+/// Valid query tags cannot contain spaces, so this cannot collide with a tag.
+const OVERFLOW_QUERY_TAG: &str = "(other)";
+
+lazy_static! {
+    static ref STATEMENT_DURATIONS: HistogramVec = register_histogram_vec!(
+        "mz_statement_durations",
+        "how long executed statements took, by query tag",
+        &["query_tag", "status"],
+        ore::stats::HISTOGRAM_BUCKETS.to_vec()
+    )
+    .unwrap();
+    /// The query tags that label the `mz_statement_durations` metric.
+    static ref QUERY_TAGS: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// Records the execution of a statement with query tag `tag` that took
+/// `duration` in the `mz_statement_durations` metric.
+///
+/// Statements without a query tag are recorded with an empty tag.
+pub fn observe_duration(tag: Option<&str>, duration: Duration, error: bool) {
+    let status = if error { "error" } else { "success" };
+    let tag = query_tag_label(&mut QUERY_TAGS.lock().expect("lock poisoned"), tag);
+    STATEMENT_DURATIONS
+        .with_label_values(&[tag, status])
+        .observe(duration.as_secs_f64());
+}
+
+/// Chooses the label for query tag `tag`, admitting it to `tags` if there is
+/// room.
+fn query_tag_label<'a>(tags: &mut HashSet<String>, tag: Option<&'a str>) -> &'a str {
+    match tag {
+        None => "",
+        Some(tag) if tags.contains(tag) => tag,
+        Some(tag) if tags.len() < MAX_QUERY_TAGS => {
+            tags.insert(tag.into());
+            tag
+        }
+        Some(_) => OVERFLOW_QUERY_TAG,
+    }
+}
+
+/// Computes the normalized fingerprint of `stmt`.
+pub fn fingerprint(stmt: &Statement<Raw>) -> String {
+    struct MaxParameter(usize);
+
+    impl<'ast> Visit<'ast, Raw> for MaxParameter {
+        fn visit_expr(&mut self, expr: &'ast Expr<Raw>) {
+            if let Expr::Parameter(n) = expr {
+                self.0 = cmp::max(self.0, *n);
+            }
+            visit::visit_expr(self, expr);
+        }
+    }
+
+    struct LiteralReplacer(usize);
+
+    impl<'ast> VisitMut<'ast, Raw> for LiteralReplacer {
+        fn visit_expr_mut(&mut self, expr: &'ast mut Expr<Raw>) {
+            if let Expr::Value(_) = expr {
+                self.0 += 1;
+                *expr = Expr::Parameter(self.0);
+            } else {
+                visit_mut::visit_expr_mut(self, expr);
+            }
+        }
+    }
+
+    // Number the new placeholders after any parameters that the statement
+    // already contains, so that the two are not confused.
+    let mut max_parameter = MaxParameter(0);
+    max_parameter.visit_statement(stmt);
+
+    let mut stmt = stmt.clone();
+    LiteralReplacer(max_parameter.0).visit_statement_mut(&mut stmt);
+    stmt.to_ast_string()
+}
+
+/// Statistics about the executions of statements with one fingerprint.
+#[derive(Debug, Default)]
+struct FingerprintStats {
+    executions: u64,
+    errors: u64,
+    rows_returned: u64,
+    total_duration: Duration,
+    /// Maps the upper bound of each duration bucket, in nanoseconds, to the
+    /// number of executions that fell into that bucket.
+    durations: BTreeMap<u64, u64>,
+}
+
+/// The rows last reported for a fingerprint.
+struct ReportedRows {
+    statistics: Row,
+    durations: Vec<Row>,
+}
+
+/// Updates to the statement statistics tables.
+#[derive(Debug, Default)]
+pub struct StatisticsUpdates {
+    /// Updates to `mz_statement_statistics`.
+    pub statistics: Vec<(Row, isize)>,
+    /// Updates to `mz_statement_durations`.
+    pub durations: Vec<(Row, isize)>,
+}
+
+/// Aggregates statistics about executed statements by fingerprint.
+pub struct StatementStatistics {
+    fingerprints: HashMap<String, FingerprintStats>,
+    reported: HashMap<String, ReportedRows>,
+    /// The fingerprints whose statistics have changed since they were last
+    /// reported.
+    dirty: HashSet<String>,
+    /// The time of the last flush, in milliseconds since the Unix epoch,
+    /// according to the coordinator's clock.
+    last_flushed: u64,
+}
+
+impl StatementStatistics {
+    /// Constructs statement statistics that are first flushed one flush
+    /// interval after `now`.
+    pub fn new(now: u64) -> StatementStatistics {
+        StatementStatistics {
+            fingerprints: HashMap::new(),
+            reported: HashMap::new(),
+            dirty: HashSet::new(),
+            last_flushed: now,
+        }
+    }
+
+    /// Records an execution of a statement with fingerprint `fingerprint`
+    /// that took `duration`, returned `rows_returned` rows, and either
+    /// succeeded or failed according to `error`.
+    pub fn record(
+        &mut self,
+        fingerprint: String,
+        duration: Duration,
+        rows_returned: u64,
+        error: bool,
+    ) {
+        if !self.fingerprints.contains_key(&fingerprint)
+            && self.fingerprints.len() >= MAX_FINGERPRINTS
+        {
+            // Make room by evicting the least frequently executed fingerprint.
+            let evicted = self
+                .fingerprints
+                .iter()
+                .min_by_key(|(_, stats)| stats.executions)
+                .map(|(fingerprint, _)| fingerprint.clone());
+            if let Some(evicted) = evicted {
+                self.fingerprints.remove(&evicted);
+                self.dirty.insert(evicted);
+            }
+        }
+
+        let stats = self.fingerprints.entry(fingerprint.clone()).or_default();
+        stats.executions += 1;
+        if error {
+            stats.errors += 1;
+        }
+        stats.rows_returned += rows_returned;
+        stats.total_duration += duration;
+        let duration_ns = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        *stats
+            .durations
+            .entry(duration_ns.next_power_of_two())
+            .or_default() += 1;
+        self.dirty.insert(fingerprint);
+    }
+
+    /// Returns the updates that bring the statement statistics tables up to
+    /// date, if enough time has passed between the last flush and `now`.
+    pub fn flush(&mut self, now: u64) -> Option<StatisticsUpdates> {
+        let elapsed = Duration::from_millis(now.saturating_sub(self.last_flushed));
+        if self.dirty.is_empty() || elapsed < FLUSH_INTERVAL {
+            return None;
+        }
+        self.last_flushed = now;
+
+        let mut updates = StatisticsUpdates::default();
+        for fingerprint in self.dirty.drain() {
+            if let Some(reported) = self.reported.remove(&fingerprint) {
+                updates.statistics.push((reported.statistics, -1));
+                updates
+                    .durations
+                    .extend(reported.durations.into_iter().map(|row| (row, -1)));
+            }
+            if let Some(stats) = self.fingerprints.get(&fingerprint) {
+                let reported = ReportedRows {
+                    statistics: Row::pack_slice(&[
+                        Datum::String(&fingerprint),
+                        Datum::Int64(stats.executions as i64),
+                        Datum::Int64(stats.errors as i64),
+                        Datum::Int64(stats.rows_returned as i64),
+                        Datum::Int64(stats.total_duration.as_nanos() as i64),
+                    ]),
+                    durations: stats
+                        .durations
+                        .iter()
+                        .map(|(duration_ns, count)| {
+                            Row::pack_slice(&[
+                                Datum::String(&fingerprint),
+                                Datum::Int64(*duration_ns as i64),
+                                Datum::Int64(*count as i64),
+                            ])
+                        })
+                        .collect(),
+                };
+                updates.statistics.push((reported.statistics.clone(), 1));
+                updates
+                    .durations
+                    .extend(reported.durations.iter().map(|row| (row.clone(), 1)));
+                self.reported.insert(fingerprint, reported);
+            }
+        }
+        Some(updates)
+    }
+}
+
+/// Wraps `rows` in a stream that counts the rows it yields, and calls
+/// `report` with the number of rows and the error that occurred, if any, when
+/// the stream is dropped.
+pub fn count_rows<F>(rows: RowBatchStream, report: F) -> RowBatchStream
+where
+    F: FnOnce(u64, Option<String>) + Send + Unpin + 'static,
+{
+    Box::new(CountingStream {
+        inner: rows,
+        rows: 0,
+        error: None,
+        report: Some(report),
+    })
+}
+
+struct CountingStream<F>
+where
+    F: FnOnce(u64, Option<String>),
+{
+    inner: RowBatchStream,
+    rows: u64,
+    error: Option<String>,
+    report: Option<F>,
+}
+
+impl<F> Stream for CountingStream<F>
+where
+    F: FnOnce(u64, Option<String>) + Unpin,
+{
+    type Item = PeekResponse;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let next = this.inner.poll_next_unpin(cx);
+        match &next {
+            Poll::Ready(Some(PeekResponse::Rows(rows))) => this.rows += rows.len() as u64,
+            Poll::Ready(Some(PeekResponse::Error(e))) => this.error = Some(e.clone()),
+            _ => (),
+        }
+        next
+    }
+}
+
+impl<F> Drop for CountingStream<F>
+where
+    F: FnOnce(u64, Option<String>),
+{
+    fn drop(&mut self) {
+        if let Some(report) = self.report.take() {
+            report(self.rows, self.error.take());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint_sql(sql: &str) -> String {
+        let stmt = sql::parse::parse(sql).unwrap().into_iter().next().unwrap();
+        fingerprint(&stmt)
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            fingerprint_sql("SELECT a FROM t WHERE b = 1 AND c = 'foo'"),
+            "SELECT a FROM t WHERE b = $1 AND c = $2"
+        );
+        assert_eq!(
+            fingerprint_sql("SELECT a FROM t WHERE b = 2 AND c = 'bar'"),
+            fingerprint_sql("SELECT a FROM t WHERE b = 1 AND c = 'foo'")
+        );
+        assert_eq!(
+            fingerprint_sql("SELECT a FROM t WHERE b = $1 LIMIT 10"),
+            "SELECT a FROM t WHERE b = $1 LIMIT $2"
+        );
+    }
+
+    #[test]
+    fn test_query_tag_label() {
+        let mut tags = HashSet::new();
+        assert_eq!(query_tag_label(&mut tags, None), "");
+        for i in 0..MAX_QUERY_TAGS {
+            let tag = format!("tag{}", i);
+            assert_eq!(query_tag_label(&mut tags, Some(&tag)), tag);
+        }
+        // Once the limit is reached, new tags share one label, but tags that
+        // were already admitted keep theirs.
+        assert_eq!(query_tag_label(&mut tags, Some("new")), OVERFLOW_QUERY_TAG);
+        assert_eq!(query_tag_label(&mut tags, Some("tag0")), "tag0");
+        assert_eq!(query_tag_label(&mut tags, None), "");
+        assert_eq!(tags.len(), MAX_QUERY_TAGS);
+    }
+
+    #[test]
+    fn test_flush() {
+        let mut stats = StatementStatistics::new(0);
+        stats.record("q".into(), Duration::from_nanos(100), 3, false);
+        stats.record("q".into(), Duration::from_nanos(200), 0, true);
+
+        // Nothing is reported until the flush interval elapses.
+        assert!(stats.flush(999).is_none());
+        let updates = stats.flush(1000).unwrap();
+        assert_eq!(
+            updates.statistics,
+            vec![(
+                Row::pack_slice(&[
+                    Datum::String("q"),
+                    Datum::Int64(2),
+                    Datum::Int64(1),
+                    Datum::Int64(3),
+                    Datum::Int64(300),
+                ]),
+                1
+            )]
+        );
+        assert_eq!(updates.durations.len(), 2);
+
+        // A later execution retracts the previously reported rows.
+        stats.record("q".into(), Duration::from_nanos(100), 1, false);
+        let updates = stats.flush(2000).unwrap();
+        assert_eq!(updates.statistics.len(), 2);
+        assert_eq!(updates.statistics[0].1, -1);
+        assert_eq!(updates.durations.len(), 4);
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Scheduled tasks.
+//!
+//! A task, created with `CREATE TASK`, inserts the results of a query into a
+//! table at a fixed interval, e.g. to periodically snapshot the contents of a
+//! view. The coordinator wakes up every [`TICK_INTERVAL`] to run the tasks
+//! that are due. Each run reads the query's results at the latest timestamp
+//! that is available without waiting, and then inserts them into the table
+//! at a new write timestamp. The [`TaskScheduler`] tracks when each task is
+//! next due and reports the outcome of each task's most recent run in the
+//! `mz_task_status` table.
+//!
+//! Schedules are not persisted: after a restart, each task next runs one
+//! interval after the coordinator boots.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use futures::future;
+use futures::stream::StreamExt;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time;
+
+use dataflow_types::PeekResponse;
+use expr::GlobalId;
+use ore::now::NowFn;
+use repr::{Datum, Row};
+
+use super::Message;
+use crate::session::RowBatchStream;
+
+/// The interval at which the coordinator checks for tasks that are due.
+pub const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+struct ScheduledTask {
+    schedule: Duration,
+    /// When the task is next due, in milliseconds since the epoch.
+    next_run: u64,
+    /// Whether a run of the task is in progress. A task that is still running
+    /// when it next becomes due skips that run.
+    running: bool,
+    /// The row reported for the task's most recent run, if it has run.
+    status: Option<Row>,
+}
+
+/// Tracks when scheduled tasks are due to run.
+pub struct TaskScheduler {
+    now: NowFn,
+    tasks: HashMap<GlobalId, ScheduledTask>,
+}
+
+impl TaskScheduler {
+    /// Constructs a scheduler that determines when tasks are due by `now`.
+    pub fn new(now: NowFn) -> TaskScheduler {
+        TaskScheduler {
+            now,
+            tasks: HashMap::new(),
+        }
+    }
+
+    /// Begins running the task `id` every `schedule`, starting `schedule` from
+    /// now.
+    pub fn schedule(&mut self, id: GlobalId, schedule: Duration) {
+        self.tasks.insert(
+            id,
+            ScheduledTask {
+                schedule,
+                next_run: (self.now)() + duration_millis(schedule),
+                running: false,
+                status: None,
+            },
+        );
+    }
+
+    /// Stops running the task `id`.
+    ///
+    /// Returns the update that retracts the task's row from `mz_task_status`,
+    /// if the task has run.
+    pub fn forget(&mut self, id: GlobalId) -> Option<(Row, isize)> {
+        let row = self.tasks.remove(&id)?.status?;
+        Some((row, -1))
+    }
+
+    /// Returns the tasks that are due to run, and marks them as running.
+    pub fn due(&mut self) -> Vec<GlobalId> {
+        let now = (self.now)();
+        let mut due = vec![];
+        for (id, task) in &mut self.tasks {
+            if task.next_run > now {
+                continue;
+            }
+            task.next_run += duration_millis(task.schedule);
+            if task.next_run <= now {
+                // Runs that were missed entirely are skipped rather than
+                // caught up on.
+                task.next_run = now + duration_millis(task.schedule);
+            }
+            if !task.running {
+                task.running = true;
+                due.push(*id);
+            }
+        }
+        due.sort();
+        due
+    }
+
+    /// Records the outcome of a run of the task `id`: either the number of
+    /// rows that the run inserted, or the error that the run failed with.
+    ///
+    /// Returns the updates that bring `mz_task_status` up to date.
+    pub fn finished(&mut self, id: GlobalId, result: Result<usize, String>) -> Vec<(Row, isize)> {
+        let task = match self.tasks.get_mut(&id) {
+            Some(task) => task,
+            // The task was dropped while it was running.
+            None => return vec![],
+        };
+        task.running = false;
+
+        let mut updates = vec![];
+        if let Some(row) = task.status.take() {
+            updates.push((row, -1));
+        }
+        let last_run_ms = i64::try_from((self.now)()).unwrap_or(i64::MAX);
+        let (rows_inserted, error) = match &result {
+            Ok(n) => (
+                Datum::Int64(i64::try_from(*n).unwrap_or(i64::MAX)),
+                Datum::Null,
+            ),
+            Err(e) => (Datum::Null, Datum::String(e)),
+        };
+        let row = Row::pack_slice(&[
+            Datum::String(&id.to_string()),
+            Datum::Int64(last_run_ms),
+            Datum::from(result.is_ok()),
+            rows_inserted,
+            error,
+        ]);
+        updates.push((row.clone(), 1));
+        task.status = Some(row);
+        updates
+    }
+}
+
+fn duration_millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Sends [`Message::Tick`] on `internal_cmd_tx` every
+/// [`TICK_INTERVAL`].
+///
+/// The ticks stop, and the ticker drops its handle on `internal_cmd_tx`, once
+/// the returned sender is dropped.
+pub fn spawn_ticker(internal_cmd_tx: mpsc::UnboundedSender<Message>) -> oneshot::Sender<()> {
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let ticks = async move {
+            let mut interval = time::interval(TICK_INTERVAL);
+            loop {
+                interval.tick().await;
+                if internal_cmd_tx.send(Message::Tick).is_err() {
+                    break;
+                }
+            }
+        };
+        future::select(Box::pin(ticks), shutdown_rx).await;
+    });
+    shutdown_tx
+}
+
+/// Collects the rows produced by a task's query.
+pub async fn collect_rows(mut rows: RowBatchStream) -> Result<Vec<Row>, String> {
+    let mut out = vec![];
+    while let Some(resp) = rows.next().await {
+        match resp {
+            PeekResponse::Rows(rows) => out.extend(rows),
+            PeekResponse::Error(e) => return Err(e),
+            PeekResponse::Canceled => return Err("query canceled".into()),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use ore::now::ManualClock;
+
+    use super::*;
+
+    #[test]
+    fn test_schedule() {
+        let task = GlobalId::User(1);
+        let clock = ManualClock::new(0);
+        let mut scheduler = TaskScheduler::new(clock.now_fn());
+        scheduler.schedule(task, Duration::from_secs(60));
+
+        // The task is not due until its first interval has elapsed.
+        clock.set(59_999);
+        assert!(scheduler.due().is_empty());
+        clock.set(60_000);
+        assert_eq!(scheduler.due(), vec![task]);
+
+        // A task that is still running skips the runs that become due.
+        clock.set(120_000);
+        assert!(scheduler.due().is_empty());
+
+        // Each run replaces the reported status of the previous run.
+        let updates = scheduler.finished(task, Ok(3));
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].1, 1);
+        let updates = scheduler.finished(task, Err("boom".into()));
+        assert_eq!(
+            updates.iter().map(|(_, diff)| *diff).collect::<Vec<_>>(),
+            vec![-1, 1]
+        );
+
+        // Dropping the task retracts its status.
+        assert_eq!(scheduler.forget(task).map(|(_, diff)| diff), Some(-1));
+        assert!(scheduler.finished(task, Ok(0)).is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Detection of stalled dataflows.
+//!
+//! A dataflow is stalled if the upper frontier of the index that it maintains
+//! has not advanced for longer than a configurable threshold, even though the
+//! frontier of at least one of its inputs has advanced in the meantime. The
+//! inputs of a dataflow are the indexes it imports and the sources it reads;
+//! the frontier of a source is the frontier of its decoded output, as reported
+//! by the workers, rather than the timestamps bound for it, so that a source
+//! whose ingestion has stopped does not count as advancing. Left
+//! undetected, a stall surfaces only when users notice that query results have
+//! gone stale. The [`DataflowWatchdog`] reports stalled dataflows in the
+//! `mz_stalled_dataflows` table, logs a warning when a dataflow stalls, and
+//! exports the number of stalled dataflows as a Prometheus metric, on which
+//! alerts can be configured.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::time::{Duration, Instant, SystemTime};
+
+use lazy_static::lazy_static;
+use log::{info, warn};
+use prometheus::{register_uint_gauge, UIntGauge};
+
+use expr::GlobalId;
+use repr::{Datum, Row, Timestamp};
+
+use super::arrangement_state::ArrangementFrontiers;
+
+/// The interval at which dataflows are checked for stalls.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+lazy_static! {
+    static ref STALLED_DATAFLOWS: UIntGauge = register_uint_gauge!(
+        "mz_stalled_dataflows",
+        "The number of dataflows whose frontier has stopped advancing."
+    )
+    .unwrap();
+}
+
+/// Detects dataflows whose frontiers have stopped advancing.
+pub struct DataflowWatchdog {
+    threshold: Duration,
+    /// The last time at which the frontier of each index or source advanced.
+    last_advanced: HashMap<GlobalId, Instant>,
+    /// The inputs of each watched dataflow, keyed by the ID of the index that
+    /// the dataflow maintains.
+    dataflows: HashMap<GlobalId, Vec<GlobalId>>,
+    /// The rows reported for the dataflows that are currently stalled.
+    stalled: HashMap<GlobalId, Row>,
+    last_checked: Instant,
+}
+
+impl DataflowWatchdog {
+    /// Constructs a watchdog that considers a dataflow stalled once its
+    /// frontier has not advanced for `threshold`.
+    pub fn new(threshold: Duration) -> DataflowWatchdog {
+        DataflowWatchdog {
+            threshold,
+            last_advanced: HashMap::new(),
+            dataflows: HashMap::new(),
+            stalled: HashMap::new(),
+            last_checked: Instant::now(),
+        }
+    }
+
+    /// Begins watching the dataflow that maintains the index `id`, which reads
+    /// from the indexes and sources identified by `inputs`.
+    pub fn watch(&mut self, id: GlobalId, inputs: Vec<GlobalId>) {
+        self.dataflows.insert(id, inputs);
+        self.last_advanced.insert(id, Instant::now());
+    }
+
+    /// Records that the frontier of the index or source `id` has advanced.
+    pub fn frontier_advanced(&mut self, id: GlobalId) {
+        self.last_advanced.insert(id, Instant::now());
+    }
+
+    /// Stops tracking the index or source `id`.
+    ///
+    /// Returns the update that retracts the dataflow's row from
+    /// `mz_stalled_dataflows`, if the dataflow was stalled.
+    pub fn forget(&mut self, id: GlobalId) -> Option<(Row, isize)> {
+        self.dataflows.remove(&id);
+        self.last_advanced.remove(&id);
+        let row = self.stalled.remove(&id)?;
+        STALLED_DATAFLOWS.set(self.stalled.len() as u64);
+        Some((row, -1))
+    }
+
+    /// Checks the watched dataflows for stalls, if enough time has passed
+    /// since the last check.
+    ///
+    /// Returns the updates that bring `mz_stalled_dataflows` up to date.
+    pub fn check(&mut self, indexes: &ArrangementFrontiers<Timestamp>) -> Vec<(Row, isize)> {
+        let mut updates = vec![];
+        if self.last_checked.elapsed() < CHECK_INTERVAL {
+            return updates;
+        }
+        self.last_checked = Instant::now();
+
+        for (id, inputs) in &self.dataflows {
+            let last_advanced = self.last_advanced[id];
+            let upper = match indexes
+                .upper_of(id)
+                .and_then(|upper| upper.iter().next().copied())
+            {
+                Some(upper) => upper,
+                // Dataflows that have finished cannot stall.
+                None => continue,
+            };
+            let stalled = last_advanced.elapsed() >= self.threshold
+                && inputs.iter().any(|input| {
+                    self.last_advanced
+                        .get(input)
+                        .map_or(false, |input_advanced| *input_advanced > last_advanced)
+                });
+            match (stalled, self.stalled.contains_key(id)) {
+                (true, false) => {
+                    warn!(
+                        "dataflow maintaining index {} has not advanced past {} for {:?}, \
+                         although its inputs have",
+                        id,
+                        upper,
+                        last_advanced.elapsed()
+                    );
+                    let stalled_since = SystemTime::now() - last_advanced.elapsed();
+                    let stalled_since_ms = stalled_since
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .map(|d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX))
+                        .unwrap_or(0);
+                    let row = Row::pack_slice(&[
+                        Datum::String(&id.to_string()),
+                        Datum::Int64(upper as i64),
+                        Datum::Int64(stalled_since_ms),
+                    ]);
+                    updates.push((row.clone(), 1));
+                    self.stalled.insert(*id, row);
+                }
+                (false, true) => {
+                    info!("dataflow maintaining index {} is no longer stalled", id);
+                    let row = self.stalled.remove(id).expect("known to exist");
+                    updates.push((row, -1));
+                }
+                _ => (),
+            }
+        }
+        STALLED_DATAFLOWS.set(self.stalled.len() as u64);
+        updates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coord::arrangement_state::Frontiers;
+
+    #[test]
+    fn test_stall() {
+        let input = GlobalId::User(1);
+        let index = GlobalId::User(2);
+        let mut indexes = ArrangementFrontiers::default();
+        indexes.insert(index, Frontiers::new(1, None));
+
+        let mut watchdog = DataflowWatchdog::new(Duration::from_secs(0));
+        watchdog.watch(index, vec![input]);
+        *watchdog.last_advanced.get_mut(&index).unwrap() -= Duration::from_millis(1);
+
+        // The dataflow is not stalled while its inputs are idle too.
+        watchdog.last_checked -= CHECK_INTERVAL;
+        assert!(watchdog.check(&indexes).is_empty());
+
+        // The dataflow is stalled once its input advances without it.
+        watchdog.frontier_advanced(input);
+        watchdog.last_checked -= CHECK_INTERVAL;
+        let updates = watchdog.check(&indexes);
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].1, 1);
+
+        // Dropping the index retracts the reported row.
+        assert_eq!(watchdog.forget(index).map(|(_, diff)| diff), Some(-1));
+        watchdog.last_checked -= CHECK_INTERVAL;
+        assert!(watchdog.check(&indexes).is_empty());
+    }
+}
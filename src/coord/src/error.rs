@@ -12,18 +12,36 @@ use std::fmt;
 
 use expr::EvalError;
 use ore::str::StrExt;
+use repr::{ColumnName, Timestamp};
 use transform::TransformError;
 
 use crate::catalog;
+use crate::coord::Overload;
 use crate::session::Var;
 
 /// Errors that can occur in the coordinator.
 #[derive(Debug)]
 pub enum CoordError {
+    /// The arrangements of the named schema's indexes have exceeded their
+    /// limit on memory.
+    ArrangementQuotaExceeded {
+        schema: String,
+        bytes: usize,
+        limit: usize,
+    },
     /// An error occurred in a catalog operation.
     Catalog(catalog::Error),
+    /// A row violates the named check constraint of the named relation.
+    CheckViolation {
+        relation: String,
+        constraint: String,
+    },
     /// The specified session parameter is constrained to its current value.
     ConstrainedParameter(&'static (dyn Var + Send + Sync)),
+    /// The server has reached its limit on open connections.
+    ConnectionQuotaExceeded { limit: usize },
+    /// The named schema has reached its limit on maintained dataflows.
+    DataflowQuotaExceeded { schema: String, limit: usize },
     /// The cursor already exists.
     DuplicateCursor(String),
     /// An error while evaluating an expression.
@@ -32,16 +50,32 @@ pub enum CoordError {
     IdExhaustionError,
     /// The value for the specified parameter does not have the right type.
     InvalidParameterType(&'static (dyn Var + Send + Sync)),
+    /// A row contains a null value in the named non-nullable column.
+    NotNullViolation(ColumnName),
     /// The named operation cannot be run in a transaction.
     OperationProhibitsTransaction(String),
     /// The named operation requires an active transaction.
     OperationRequiresTransaction(String),
+    /// The server is too heavily loaded to admit the query.
+    Overloaded(Overload),
+    /// The named role has reached its limit on concurrent peeks.
+    PeekQuotaExceeded { role: String, limit: usize },
     /// The transaction is in read-only mode.
     ReadOnlyTransaction,
     /// The specified session parameter is read-only.
     ReadOnlyParameter(&'static (dyn Var + Send + Sync)),
+    /// The named role has reached its limit on open connections.
+    RoleConnectionQuotaExceeded { role: String, limit: usize },
     /// An error occurred in a SQL catalog operation.
     SqlCatalog(sql::catalog::CatalogError),
+    /// The requested timestamp precedes the compaction frontier of the named
+    /// indexes, which retain no history that far back.
+    TimestampCompacted {
+        timestamp: Timestamp,
+        /// The name of each index that cannot serve the timestamp, and the
+        /// earliest timestamp that it can serve.
+        indexes: Vec<(String, Timestamp)>,
+    },
     /// An error occurred in the optimizer.
     Transform(TransformError),
     /// The named cursor does not exist.
@@ -73,6 +107,41 @@ impl CoordError {
         match self {
             CoordError::Catalog(c) => c.hint(),
             CoordError::Eval(e) => e.hint(),
+            CoordError::ArrangementQuotaExceeded { .. } => Some(
+                "Drop an unused index or materialized view in the schema, \
+                 or raise the --max-arrangement-bytes-per-schema limit."
+                    .into(),
+            ),
+            CoordError::ConnectionQuotaExceeded { .. } => {
+                Some("Close an idle connection, or raise the --max-connections limit.".into())
+            }
+            CoordError::DataflowQuotaExceeded { .. } => Some(
+                "Drop an unused index, materialized view, or sink in the schema, \
+                 or raise the --max-dataflows-per-schema limit."
+                    .into(),
+            ),
+            CoordError::Overloaded(_) => Some(
+                "Retry the query later, or create an index so that the query does not \
+                 require a temporary dataflow."
+                    .into(),
+            ),
+            CoordError::PeekQuotaExceeded { .. } => {
+                Some("Retry the query once an outstanding query has completed.".into())
+            }
+            CoordError::RoleConnectionQuotaExceeded { .. } => Some(
+                "Close an idle connection for the role, \
+                 or raise the --max-connections-per-role limit."
+                    .into(),
+            ),
+            CoordError::TimestampCompacted { indexes, .. } => {
+                let earliest = indexes.iter().map(|(_, since)| *since).max();
+                Some(format!(
+                    "Query at timestamp {} or later, or retain more history by raising \
+                     the logical compaction window of the listed indexes with \
+                     ALTER INDEX ... SET (logical_compaction_window = ...).",
+                    earliest.unwrap_or(0)
+                ))
+            }
             CoordError::UnknownLoginRole(_) => {
                 // TODO(benesch): this will be a bad hint when people are used
                 // to creating roles in Materialize, since they might drop the
@@ -91,13 +160,42 @@ impl CoordError {
 impl fmt::Display for CoordError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            CoordError::ArrangementQuotaExceeded {
+                schema,
+                bytes,
+                limit,
+            } => write!(
+                f,
+                "the indexes in schema {} hold {} bytes, exceeding its limit of {}",
+                schema.quoted(),
+                bytes,
+                limit
+            ),
             CoordError::Catalog(e) => e.fmt(f),
+            CoordError::CheckViolation {
+                relation,
+                constraint,
+            } => write!(
+                f,
+                "new row for relation {} violates check constraint {}",
+                relation.quoted(),
+                constraint.quoted()
+            ),
             CoordError::ConstrainedParameter(p) => write!(
                 f,
                 "parameter {} can only be set to {}",
                 p.name().quoted(),
                 p.value().quoted()
             ),
+            CoordError::ConnectionQuotaExceeded { limit } => {
+                write!(f, "sorry, too many clients already (limit {})", limit)
+            }
+            CoordError::DataflowQuotaExceeded { schema, limit } => write!(
+                f,
+                "schema {} has reached its limit of {} dataflows",
+                schema.quoted(),
+                limit
+            ),
             CoordError::DuplicateCursor(name) => {
                 write!(f, "cursor {} already exists", name.quoted())
             }
@@ -109,17 +207,52 @@ impl fmt::Display for CoordError {
                 p.name().quoted(),
                 p.type_name().quoted()
             ),
+            CoordError::NotNullViolation(name) => write!(
+                f,
+                "null value in column {} violates not-null constraint",
+                name.as_str().quoted()
+            ),
             CoordError::OperationProhibitsTransaction(op) => {
                 write!(f, "{} cannot be run inside a transaction block", op)
             }
             CoordError::OperationRequiresTransaction(op) => {
                 write!(f, "{} can only be used in transaction blocks", op)
             }
+            CoordError::Overloaded(overload) => {
+                write!(f, "server is overloaded: {}", overload)
+            }
+            CoordError::PeekQuotaExceeded { role, limit } => write!(
+                f,
+                "role {} has reached its limit of {} concurrent queries",
+                role.quoted(),
+                limit
+            ),
             CoordError::ReadOnlyTransaction => f.write_str("transaction in read-only mode"),
             CoordError::ReadOnlyParameter(p) => {
                 write!(f, "parameter {} cannot be changed", p.name().quoted())
             }
+            CoordError::RoleConnectionQuotaExceeded { role, limit } => write!(
+                f,
+                "too many connections for role {} (limit {})",
+                role.quoted(),
+                limit
+            ),
             CoordError::SqlCatalog(e) => e.fmt(f),
+            CoordError::TimestampCompacted { timestamp, indexes } => {
+                write!(f, "Timestamp ({}) is not valid for all inputs: ", timestamp)?;
+                for (i, (name, since)) in indexes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(
+                        f,
+                        "index {} has been compacted to timestamp {}",
+                        name.quoted(),
+                        since
+                    )?;
+                }
+                Ok(())
+            }
             CoordError::Transform(e) => e.fmt(f),
             CoordError::UnknownCursor(name) => {
                 write!(f, "cursor {} does not exist", name.quoted())
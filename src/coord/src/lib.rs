@@ -21,7 +21,21 @@
 //!   * Assigning timestamps to incoming source data.
 //!
 //! The main interface to the coordinator is [`Client`]. To start a coordinator,
-//! use the [`serve`] function.
+//! use the [`serve`] function, or [`ConfigBuilder::serve`].
+//!
+//! # Embedding
+//!
+//! The coordinator does not depend on any particular frontend. `materialized`
+//! serves pgwire, HTTP, and other protocols from the same [`Client`], and other
+//! Rust services can embed a coordinator in process and issue SQL through a
+//! [`Client`] directly. See `examples/embed.rs` for a complete example.
+//!
+//! [`Client`] is `Send` and `Sync` and cheap to clone, so any number of
+//! frontends on any number of threads can share one coordinator. Each
+//! connection must allocate its own [`ConnClient`] with [`Client::new_conn`],
+//! which can in turn be upgraded to a [`SessionClient`]. Connection and session
+//! clients are `Send`, so they can be moved to whichever task serves the
+//! connection, but they cannot be cloned.
 
 // TODO(benesch): delete this once we use structured errors everywhere.
 macro_rules! coord_bail {
@@ -45,6 +59,8 @@ pub mod session;
 
 pub use crate::cache::CacheConfig;
 pub use crate::client::{Client, ConnClient, Handle, SessionClient};
-pub use crate::command::{Cancelled, ExecuteResponse, StartupMessage};
-pub use crate::coord::{serve, Config, LoggingConfig};
+pub use crate::command::{Cancelled, ExecuteResponse, Notice, Readiness, StartupMessage};
+pub use crate::coord::{
+    serve, Config, ConfigBuilder, LogFilterControl, LoggingConfig, Overload, QuotaConfig,
+};
 pub use crate::error::CoordError;
@@ -17,15 +17,19 @@ use std::mem;
 use derivative::Derivative;
 use futures::Stream;
 
+use dataflow_types::PeekResponse;
 use expr::GlobalId;
 use repr::{Datum, Row, ScalarType};
 use sql::ast::{Raw, Statement};
 use sql::plan::{Params, StatementDesc};
 
+use crate::command::Notice;
 use crate::error::CoordError;
 
 mod vars;
 
+pub mod query_tag;
+
 pub use self::vars::{Var, Vars};
 
 const DUMMY_CONNECTION_ID: u32 = 0;
@@ -40,6 +44,7 @@ pub struct Session {
     user: String,
     vars: Vars,
     drop_sinks: Vec<GlobalId>,
+    notices: Vec<Notice>,
 }
 
 impl Session {
@@ -66,6 +71,7 @@ impl Session {
             user,
             vars: Vars::default(),
             drop_sinks: vec![],
+            notices: vec![],
         }
     }
 
@@ -162,6 +168,17 @@ impl Session {
         self.drop_sinks.push(name);
     }
 
+    /// Queues a notice to be delivered to the client.
+    pub fn add_notice(&mut self, notice: Notice) {
+        self.notices.push(notice);
+    }
+
+    /// Removes and returns the notices that have been queued for delivery to
+    /// the client.
+    pub fn drain_notices(&mut self) -> Vec<Notice> {
+        mem::take(&mut self.notices)
+    }
+
     /// Registers the prepared statement under `name`.
     pub fn set_prepared_statement(&mut self, name: String, statement: PreparedStatement) {
         self.prepared_statements.insert(name, statement);
@@ -187,7 +204,7 @@ impl Session {
     /// provided.
     ///
     // The `results_formats` parameter sets the desired format of the results,
-    /// and is stored on the portal.
+    /// and is stored on the portal, as is the query tag `tag`.
     pub fn set_portal(
         &mut self,
         portal_name: String,
@@ -195,6 +212,7 @@ impl Session {
         stmt: Option<Statement<Raw>>,
         params: Vec<(Datum, ScalarType)>,
         result_formats: Vec<pgrepr::Format>,
+        tag: Option<String>,
     ) -> Result<(), CoordError> {
         // The empty portal can be silently replaced.
         if !portal_name.is_empty() && self.portals.contains_key(&portal_name) {
@@ -210,6 +228,7 @@ impl Session {
                     types: params.into_iter().map(|(_d, t)| t).collect(),
                 },
                 result_formats: result_formats.into_iter().map(Into::into).collect(),
+                tag,
                 state: PortalState::NotStarted,
             },
         );
@@ -267,12 +286,17 @@ impl Session {
 pub struct PreparedStatement {
     sql: Option<Statement<Raw>>,
     desc: StatementDesc,
+    tag: Option<String>,
 }
 
 impl PreparedStatement {
     /// Constructs a new prepared statement.
-    pub fn new(sql: Option<Statement<Raw>>, desc: StatementDesc) -> PreparedStatement {
-        PreparedStatement { sql, desc }
+    pub fn new(
+        sql: Option<Statement<Raw>>,
+        desc: StatementDesc,
+        tag: Option<String>,
+    ) -> PreparedStatement {
+        PreparedStatement { sql, desc, tag }
     }
 
     /// Returns the raw SQL string associated with this prepared statement,
@@ -285,6 +309,12 @@ impl PreparedStatement {
     pub fn desc(&self) -> &StatementDesc {
         &self.desc
     }
+
+    /// Returns the query tag that was specified in the text of the prepared
+    /// statement, if any.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
 }
 
 /// A portal represents the execution state of a running or runnable query.
@@ -299,6 +329,8 @@ pub struct Portal {
     pub parameters: Params,
     /// The desired output format for each column in the result set.
     pub result_formats: Vec<pgrepr::Format>,
+    /// The query tag that was specified in the text of the statement, if any.
+    pub tag: Option<String>,
     /// The execution state of the portal.
     #[derivative(Debug = "ignore")]
     pub state: PortalState,
@@ -318,7 +350,9 @@ pub enum PortalState {
 }
 
 /// A stream of batched rows.
-pub type RowBatchStream = Box<dyn Stream<Item = Vec<Row>> + Send + Unpin>;
+///
+/// The stream ends after yielding an error or a cancellation.
+pub type RowBatchStream = Box<dyn Stream<Item = PeekResponse> + Send + Unpin>;
 
 /// The transaction status of a session.
 ///
@@ -0,0 +1,89 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Query tags.
+//!
+//! A query tag is a short label that a client attaches to its statements, so
+//! that operators can attribute load to the application that issued them. A
+//! statement's tag is taken from a `/* mz_tag: NAME */` comment in its SQL
+//! text, if present, and otherwise from the `query_tag` session variable.
+
+/// The maximum length of a query tag, in bytes.
+///
+/// Query tags are used as Prometheus label values, so their length and
+/// character set are restricted.
+pub const MAX_QUERY_TAG_LEN: usize = 64;
+
+const COMMENT_PREFIX: &str = "mz_tag:";
+
+/// Reports whether `tag` is a valid query tag.
+///
+/// Valid query tags are between 1 and [`MAX_QUERY_TAG_LEN`] bytes long and
+/// consist only of ASCII alphanumerics, underscores, hyphens, and periods.
+pub fn is_valid(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.len() <= MAX_QUERY_TAG_LEN
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+/// Extracts the query tag from the first `/* mz_tag: NAME */` comment in
+/// `sql`.
+///
+/// Returns `None` if there is no such comment or if the tag in the comment is
+/// not valid.
+pub fn extract(sql: &str) -> Option<String> {
+    let mut rest = sql;
+    while let Some(start) = rest.find("/*") {
+        rest = &rest[start + 2..];
+        let end = rest.find("*/")?;
+        let comment = rest[..end].trim();
+        rest = &rest[end + 2..];
+        if let Some(tag) = comment.strip_prefix(COMMENT_PREFIX) {
+            let tag = tag.trim();
+            return if is_valid(tag) {
+                Some(tag.into())
+            } else {
+                None
+            };
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(
+            extract("/* mz_tag: dashboard_x */ SELECT 1"),
+            Some("dashboard_x".into())
+        );
+        assert_eq!(
+            extract("SELECT 1 /* unrelated */ /*mz_tag:etl-2*/"),
+            Some("etl-2".into())
+        );
+        assert_eq!(extract("SELECT 1 -- mz_tag: dashboard_x"), None);
+        assert_eq!(extract("/* mz_tag: has spaces */ SELECT 1"), None);
+        assert_eq!(extract("/* mz_tag: unterminated SELECT 1"), None);
+        assert_eq!(extract("SELECT 1"), None);
+    }
+
+    #[test]
+    fn test_is_valid() {
+        assert!(is_valid("dashboard_x"));
+        assert!(is_valid("svc.reporting-v2"));
+        assert!(!is_valid(""));
+        assert!(!is_valid("a\"b"));
+        assert!(!is_valid(&"x".repeat(MAX_QUERY_TAG_LEN + 1)));
+    }
+}
@@ -9,8 +9,13 @@
 
 use std::borrow::Borrow;
 use std::fmt;
+use std::time::Duration;
+
+use ore::str::StrExt;
+use sql::catalog::Feature;
 
 use crate::error::CoordError;
+use crate::session::query_tag;
 
 const APPLICATION_NAME: ServerVar<str> = ServerVar {
     name: unicase::Ascii::new("application_name"),
@@ -37,18 +42,71 @@ const DATE_STYLE: ServerVar<str> = ServerVar {
     description: "Sets the display format for date and time values (PostgreSQL).",
 };
 
+const ENABLE_ARRAY_CONSTRUCTOR: ServerVar<bool> = ServerVar {
+    name: unicase::Ascii::new("enable_array_constructor"),
+    value: &false,
+    description: "Enables the experimental ARRAY constructor (Materialize).",
+};
+
+const ENABLE_MULTIDIMENSIONAL_LISTS: ServerVar<bool> = ServerVar {
+    name: unicase::Ascii::new("enable_multidimensional_lists"),
+    value: &false,
+    description:
+        "Enables experimental functions and operators on nested lists, like list_ndims (Materialize).",
+};
+
+const ENABLE_PEEK_RESULT_CACHE: ServerVar<bool> = ServerVar {
+    name: unicase::Ascii::new("enable_peek_result_cache"),
+    value: &false,
+    description: "Reuses the results of identical queries at the same timestamp (Materialize).",
+};
+
+const ENABLE_REPEAT_FUNCTION: ServerVar<bool> = ServerVar {
+    name: unicase::Ascii::new("enable_repeat_function"),
+    value: &false,
+    description: "Enables the experimental repeat table function (Materialize).",
+};
+
 const EXTRA_FLOAT_DIGITS: ServerVar<i32> = ServerVar {
     name: unicase::Ascii::new("extra_float_digits"),
     value: &3,
     description: "Adjusts the number of digits displayed for floating-point values (PostgreSQL).",
 };
 
+const IDLE_IN_TRANSACTION_SESSION_TIMEOUT: ServerVar<Duration> = ServerVar {
+    name: unicase::Ascii::new("idle_in_transaction_session_timeout"),
+    value: &Duration::from_secs(0),
+    description:
+        "Sets the maximum allowed idle time between queries, when in a transaction (PostgreSQL).",
+};
+
+const IDLE_SESSION_TIMEOUT: ServerVar<Duration> = ServerVar {
+    name: unicase::Ascii::new("idle_session_timeout"),
+    value: &Duration::from_secs(0),
+    description:
+        "Sets the maximum allowed idle time between queries, when not in a transaction (PostgreSQL).",
+};
+
 const INTEGER_DATETIMES: ServerVar<bool> = ServerVar {
     name: unicase::Ascii::new("integer_datetimes"),
     value: &true,
     description: "Reports whether the server uses 64-bit-integer dates and times (PostgreSQL).",
 };
 
+const PREFER_DELTA_JOINS: ServerVar<bool> = ServerVar {
+    name: unicase::Ascii::new("prefer_delta_joins"),
+    value: &false,
+    description:
+        "Plans the joins of queries as delta queries, even if that requires new arrangements (Materialize).",
+};
+
+const QUERY_TAG: ServerVar<str> = ServerVar {
+    name: unicase::Ascii::new("query_tag"),
+    value: "",
+    description:
+        "Sets the tag that is attached to statement metrics and the slow query log (Materialize).",
+};
+
 const SEARCH_PATH: ServerVar<[&str]> = ServerVar {
     name: unicase::Ascii::new("search_path"),
     value: &["mz_catalog", "pg_catalog", "public", "mz_temp"],
@@ -98,6 +156,13 @@ const TRANSACTION_ISOLATION: ServerVar<str> = ServerVar {
     description: "Sets the current transaction's isolation level (PostgreSQL).",
 };
 
+const WAIT_FOR_HYDRATION: ServerVar<bool> = ServerVar {
+    name: unicase::Ascii::new("wait_for_hydration"),
+    value: &false,
+    description:
+        "Delays the completion of statements that create indexes until the indexes are hydrated (Materialize).",
+};
+
 /// Session variables.
 ///
 /// Materialize roughly follows the PostgreSQL configuration model, which works
@@ -110,11 +175,13 @@ const TRANSACTION_ISOLATION: ServerVar<str> = ServerVar {
 /// precedence over command line arguments, which in turn take precedence over
 /// settings in the on-disk configuration.
 ///
-/// The Materialize configuration hierarchy at the moment is much simpler.
-/// Global defaults are hardcoded into the binary, and a select few parameters
-/// can be overridden per session. The infrastructure has been designed with an
-/// eye towards supporting additional layers to the hierarchy, however, should
-/// the need arise.
+/// The Materialize configuration hierarchy is simpler. Global defaults are
+/// hardcoded into the binary. Parameters that can be overridden per session
+/// can also have their defaults overridden durably, either for the whole
+/// system via `ALTER SYSTEM SET` or for a particular role via `ALTER ROLE ...
+/// SET`. Role defaults take precedence over system defaults. Both kinds of
+/// defaults are installed into a session's `Vars` via [`Vars::set_default`]
+/// when the session starts, and are what `RESET` restores.
 ///
 /// The configuration parameters that exist are driven by compatibility with
 /// PostgreSQL drivers that expect them, not because they are particularly
@@ -125,8 +192,16 @@ pub struct Vars {
     client_encoding: ServerVar<str>,
     database: SessionVar<str>,
     date_style: ServerVar<str>,
+    enable_array_constructor: SessionVar<bool>,
+    enable_multidimensional_lists: SessionVar<bool>,
+    enable_peek_result_cache: SessionVar<bool>,
+    enable_repeat_function: SessionVar<bool>,
     extra_float_digits: SessionVar<i32>,
+    idle_in_transaction_session_timeout: SessionVar<Duration>,
+    idle_session_timeout: SessionVar<Duration>,
     integer_datetimes: ServerVar<bool>,
+    prefer_delta_joins: SessionVar<bool>,
+    query_tag: SessionVar<str>,
     search_path: ServerVar<[&'static str]>,
     server_version: ServerVar<str>,
     server_version_num: ServerVar<i32>,
@@ -134,6 +209,7 @@ pub struct Vars {
     standard_conforming_strings: ServerVar<bool>,
     timezone: ServerVar<str>,
     transaction_isolation: ServerVar<str>,
+    wait_for_hydration: SessionVar<bool>,
 }
 
 impl Default for Vars {
@@ -143,8 +219,18 @@ impl Default for Vars {
             client_encoding: CLIENT_ENCODING,
             database: SessionVar::new(&DATABASE),
             date_style: DATE_STYLE,
+            enable_array_constructor: SessionVar::new(&ENABLE_ARRAY_CONSTRUCTOR),
+            enable_multidimensional_lists: SessionVar::new(&ENABLE_MULTIDIMENSIONAL_LISTS),
+            enable_peek_result_cache: SessionVar::new(&ENABLE_PEEK_RESULT_CACHE),
+            enable_repeat_function: SessionVar::new(&ENABLE_REPEAT_FUNCTION),
             extra_float_digits: SessionVar::new(&EXTRA_FLOAT_DIGITS),
+            idle_in_transaction_session_timeout: SessionVar::new(
+                &IDLE_IN_TRANSACTION_SESSION_TIMEOUT,
+            ),
+            idle_session_timeout: SessionVar::new(&IDLE_SESSION_TIMEOUT),
             integer_datetimes: INTEGER_DATETIMES,
+            prefer_delta_joins: SessionVar::new(&PREFER_DELTA_JOINS),
+            query_tag: SessionVar::new(&QUERY_TAG),
             search_path: SEARCH_PATH,
             server_version: SERVER_VERSION,
             server_version_num: SERVER_VERSION_NUM,
@@ -152,6 +238,7 @@ impl Default for Vars {
             standard_conforming_strings: STANDARD_CONFORMING_STRINGS,
             timezone: TIMEZONE,
             transaction_isolation: TRANSACTION_ISOLATION,
+            wait_for_hydration: SessionVar::new(&WAIT_FOR_HYDRATION),
         }
     }
 }
@@ -165,8 +252,16 @@ impl Vars {
             &self.client_encoding,
             &self.database,
             &self.date_style,
+            &self.enable_array_constructor,
+            &self.enable_multidimensional_lists,
+            &self.enable_peek_result_cache,
+            &self.enable_repeat_function,
             &self.extra_float_digits,
+            &self.idle_in_transaction_session_timeout,
+            &self.idle_session_timeout,
             &self.integer_datetimes,
+            &self.prefer_delta_joins,
+            &self.query_tag,
             &self.search_path,
             &self.server_version,
             &self.server_version_num,
@@ -174,6 +269,7 @@ impl Vars {
             &self.standard_conforming_strings,
             &self.timezone,
             &self.transaction_isolation,
+            &self.wait_for_hydration,
         ]
         .into_iter()
     }
@@ -212,10 +308,26 @@ impl Vars {
             Ok(&self.database)
         } else if name == DATE_STYLE.name {
             Ok(&self.date_style)
+        } else if name == ENABLE_ARRAY_CONSTRUCTOR.name {
+            Ok(&self.enable_array_constructor)
+        } else if name == ENABLE_MULTIDIMENSIONAL_LISTS.name {
+            Ok(&self.enable_multidimensional_lists)
+        } else if name == ENABLE_PEEK_RESULT_CACHE.name {
+            Ok(&self.enable_peek_result_cache)
+        } else if name == ENABLE_REPEAT_FUNCTION.name {
+            Ok(&self.enable_repeat_function)
         } else if name == EXTRA_FLOAT_DIGITS.name {
             Ok(&self.extra_float_digits)
+        } else if name == IDLE_IN_TRANSACTION_SESSION_TIMEOUT.name {
+            Ok(&self.idle_in_transaction_session_timeout)
+        } else if name == IDLE_SESSION_TIMEOUT.name {
+            Ok(&self.idle_session_timeout)
         } else if name == INTEGER_DATETIMES.name {
             Ok(&self.integer_datetimes)
+        } else if name == PREFER_DELTA_JOINS.name {
+            Ok(&self.prefer_delta_joins)
+        } else if name == QUERY_TAG.name {
+            Ok(&self.query_tag)
         } else if name == SEARCH_PATH.name {
             Ok(&self.search_path)
         } else if name == SERVER_VERSION.name {
@@ -230,6 +342,8 @@ impl Vars {
             Ok(&self.timezone)
         } else if name == TRANSACTION_ISOLATION.name {
             Ok(&self.transaction_isolation)
+        } else if name == WAIT_FOR_HYDRATION.name {
+            Ok(&self.wait_for_hydration)
         } else {
             Err(CoordError::UnknownParameter(name.into()))
         }
@@ -257,10 +371,32 @@ impl Vars {
                 }
             }
             Ok(())
+        } else if name == ENABLE_ARRAY_CONSTRUCTOR.name {
+            self.enable_array_constructor.set(value)
+        } else if name == ENABLE_MULTIDIMENSIONAL_LISTS.name {
+            self.enable_multidimensional_lists.set(value)
+        } else if name == ENABLE_PEEK_RESULT_CACHE.name {
+            self.enable_peek_result_cache.set(value)
+        } else if name == ENABLE_REPEAT_FUNCTION.name {
+            self.enable_repeat_function.set(value)
         } else if name == EXTRA_FLOAT_DIGITS.name {
             self.extra_float_digits.set(value)
+        } else if name == IDLE_IN_TRANSACTION_SESSION_TIMEOUT.name {
+            self.idle_in_transaction_session_timeout.set(value)
+        } else if name == IDLE_SESSION_TIMEOUT.name {
+            self.idle_session_timeout.set(value)
         } else if name == INTEGER_DATETIMES.name {
             Err(CoordError::ReadOnlyParameter(&INTEGER_DATETIMES))
+        } else if name == PREFER_DELTA_JOINS.name {
+            self.prefer_delta_joins.set(value)
+        } else if name == QUERY_TAG.name {
+            if !value.is_empty() && !query_tag::is_valid(value) {
+                coord_bail!(
+                    "invalid value for parameter \"query_tag\": {}",
+                    value.quoted()
+                );
+            }
+            self.query_tag.set(value)
         } else if name == SEARCH_PATH.name {
             Err(CoordError::ReadOnlyParameter(&SEARCH_PATH))
         } else if name == SERVER_VERSION.name {
@@ -279,11 +415,74 @@ impl Vars {
             }
         } else if name == TRANSACTION_ISOLATION.name {
             Err(CoordError::ReadOnlyParameter(&TRANSACTION_ISOLATION))
+        } else if name == WAIT_FOR_HYDRATION.name {
+            self.wait_for_hydration.set(value)
         } else {
             Err(CoordError::UnknownParameter(name.into()))
         }
     }
 
+    /// Sets the default value of the configuration parameter named `name` to
+    /// the value represented by `value`.
+    ///
+    /// The default value is the value that the parameter takes if it is not
+    /// set in this session, or after it is reset. Values are validated exactly
+    /// as they are by [`Vars::set`]. Setting the default of a parameter that
+    /// cannot be changed per session is a no-op, provided `value` would be
+    /// accepted by `set`.
+    pub fn set_default(&mut self, name: &str, value: &str) -> Result<(), CoordError> {
+        Vars::default().set(name, value)?;
+        match self.session_var_mut(name) {
+            Some(var) => var.set_default(value),
+            None => Ok(()),
+        }
+    }
+
+    /// Restores the configuration parameter named `name` to its default value.
+    ///
+    /// As a special case, if `name` is `all`, all configuration parameters are
+    /// restored to their default values.
+    pub fn reset(&mut self, name: &str) -> Result<(), CoordError> {
+        if name == unicase::Ascii::new("all") {
+            for var in self.session_vars_mut() {
+                var.reset();
+            }
+            return Ok(());
+        }
+        self.get(name)?;
+        if let Some(var) = self.session_var_mut(name) {
+            var.reset();
+        }
+        Ok(())
+    }
+
+    /// Returns the configuration parameters that can be changed per session.
+    fn session_vars_mut(&mut self) -> Vec<&mut dyn SessionVarMut> {
+        vec![
+            &mut self.application_name as &mut dyn SessionVarMut,
+            &mut self.database,
+            &mut self.enable_array_constructor,
+            &mut self.enable_multidimensional_lists,
+            &mut self.enable_peek_result_cache,
+            &mut self.enable_repeat_function,
+            &mut self.extra_float_digits,
+            &mut self.idle_in_transaction_session_timeout,
+            &mut self.idle_session_timeout,
+            &mut self.prefer_delta_joins,
+            &mut self.query_tag,
+            &mut self.sql_safe_updates,
+            &mut self.wait_for_hydration,
+        ]
+    }
+
+    /// Returns the configuration parameter named `name`, if it exists and
+    /// can be changed per session.
+    fn session_var_mut(&mut self, name: &str) -> Option<&mut dyn SessionVarMut> {
+        self.session_vars_mut()
+            .into_iter()
+            .find(|var| name == unicase::Ascii::new(var.name()))
+    }
+
     /// Returns the value of the `application_name` configuration parameter.
     pub fn application_name(&self) -> &str {
         self.application_name.value()
@@ -299,6 +498,23 @@ impl Vars {
         self.date_style.value
     }
 
+    /// Returns the value of the `enable_peek_result_cache` configuration
+    /// parameter.
+    pub fn enable_peek_result_cache(&self) -> bool {
+        *self.enable_peek_result_cache.value()
+    }
+
+    /// Reports whether the feature flag for `feature` is enabled in this
+    /// session.
+    pub fn feature_enabled(&self, feature: Feature) -> bool {
+        let var = match feature {
+            Feature::ArrayConstructor => &self.enable_array_constructor,
+            Feature::MultidimensionalLists => &self.enable_multidimensional_lists,
+            Feature::RepeatFunction => &self.enable_repeat_function,
+        };
+        *var.value()
+    }
+
     /// Returns the value of the `database` configuration parameter.
     pub fn database(&self) -> &str {
         self.database.value()
@@ -309,11 +525,37 @@ impl Vars {
         *self.extra_float_digits.value()
     }
 
+    /// Returns the value of the `idle_in_transaction_session_timeout`
+    /// configuration parameter, or `None` if the timeout is disabled.
+    pub fn idle_in_transaction_session_timeout(&self) -> Option<Duration> {
+        nonzero_duration(*self.idle_in_transaction_session_timeout.value())
+    }
+
+    /// Returns the value of the `idle_session_timeout` configuration
+    /// parameter, or `None` if the timeout is disabled.
+    pub fn idle_session_timeout(&self) -> Option<Duration> {
+        nonzero_duration(*self.idle_session_timeout.value())
+    }
+
     /// Returns the value of the `integer_datetimes` configuration parameter.
     pub fn integer_datetimes(&self) -> bool {
         *self.integer_datetimes.value
     }
 
+    /// Returns the value of the `prefer_delta_joins` configuration parameter.
+    pub fn prefer_delta_joins(&self) -> bool {
+        *self.prefer_delta_joins.value()
+    }
+
+    /// Returns the value of the `query_tag` configuration parameter, or `None`
+    /// if it is empty.
+    pub fn query_tag(&self) -> Option<&str> {
+        match self.query_tag.value() {
+            "" => None,
+            tag => Some(tag),
+        }
+    }
+
     /// Returns the value of the `search_path` configuration parameter.
     pub fn search_path(&self) -> &'static [&'static str] {
         self.search_path.value
@@ -350,6 +592,19 @@ impl Vars {
     pub fn transaction_isolation(&self) -> &'static str {
         self.transaction_isolation.value
     }
+
+    /// Returns the value of the `wait_for_hydration` configuration parameter.
+    pub fn wait_for_hydration(&self) -> bool {
+        *self.wait_for_hydration.value()
+    }
+}
+
+fn nonzero_duration(d: Duration) -> Option<Duration> {
+    if d == Duration::from_secs(0) {
+        None
+    } else {
+        Some(d)
+    }
 }
 
 /// A `Var` represents a configuration parameter of an arbitrary type.
@@ -403,12 +658,16 @@ where
 
 /// A `SessionVar` is the session value for a configuration parameter. If unset,
 /// the server default is used instead.
+///
+/// The default value of a `SessionVar` is that of its parent [`ServerVar`],
+/// unless it has been overridden with a system or role default.
 #[derive(Debug)]
 pub struct SessionVar<V>
 where
     V: Value + fmt::Debug + ?Sized + 'static,
 {
     value: Option<V::Owned>,
+    default_value: Option<V::Owned>,
     parent: &'static ServerVar<V>,
 }
 
@@ -419,6 +678,7 @@ where
     pub fn new(parent: &'static ServerVar<V>) -> SessionVar<V> {
         SessionVar {
             value: None,
+            default_value: None,
             parent,
         }
     }
@@ -433,9 +693,24 @@ where
         }
     }
 
+    pub fn set_default(&mut self, s: &str) -> Result<(), CoordError> {
+        match V::parse(s) {
+            Ok(v) => {
+                self.default_value = Some(v);
+                Ok(())
+            }
+            Err(()) => Err(CoordError::InvalidParameterType(self.parent)),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.value = None;
+    }
+
     pub fn value(&self) -> &V {
         self.value
             .as_ref()
+            .or_else(|| self.default_value.as_ref())
             .map(|v| v.borrow())
             .unwrap_or(self.parent.value)
     }
@@ -463,6 +738,26 @@ where
     }
 }
 
+/// A `Var` whose session value can be changed.
+trait SessionVarMut: Var {
+    fn set_default(&mut self, s: &str) -> Result<(), CoordError>;
+    fn reset(&mut self);
+}
+
+impl<V> SessionVarMut for SessionVar<V>
+where
+    V: Value + ToOwned + fmt::Debug + ?Sized + 'static,
+    V::Owned: fmt::Debug,
+{
+    fn set_default(&mut self, s: &str) -> Result<(), CoordError> {
+        SessionVar::set_default(self, s)
+    }
+
+    fn reset(&mut self) {
+        SessionVar::reset(self)
+    }
+}
+
 /// A value that can be stored in a session variable.
 pub trait Value: ToOwned + Send + Sync {
     /// The name of the value type.
@@ -504,6 +799,49 @@ impl Value for i32 {
     }
 }
 
+/// Durations are specified as an integer number of milliseconds, optionally
+/// followed by a unit, as in `500`, `30s`, or `5min`. Like in PostgreSQL, the
+/// supported units are `ms`, `s`, `min`, `h`, and `d`.
+impl Value for Duration {
+    const TYPE_NAME: &'static str = "duration";
+
+    fn parse(s: &str) -> Result<Duration, ()> {
+        let s = s.trim();
+        let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        let (n, unit) = s.split_at(split);
+        let n: u64 = n.parse().map_err(|_| ())?;
+        let ms_per_unit = match unit.trim() {
+            "" | "ms" => 1,
+            "s" => 1_000,
+            "min" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            _ => return Err(()),
+        };
+        n.checked_mul(ms_per_unit)
+            .map(Duration::from_millis)
+            .ok_or(())
+    }
+
+    fn format(&self) -> String {
+        let ms = self.as_millis();
+        if ms == 0 {
+            return "0".into();
+        }
+        for (unit, ms_per_unit) in &[
+            ("d", 86_400_000),
+            ("h", 3_600_000),
+            ("min", 60_000),
+            ("s", 1_000),
+        ] {
+            if ms % ms_per_unit == 0 {
+                return format!("{}{}", ms / ms_per_unit, unit);
+            }
+        }
+        format!("{}ms", ms)
+    }
+}
+
 impl Value for str {
     const TYPE_NAME: &'static str = "string";
 
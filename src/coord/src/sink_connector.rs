@@ -12,7 +12,6 @@ use std::time::Duration;
 
 use anyhow::{anyhow, Context};
 use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, ResourceSpecifier, TopicReplication};
-use rdkafka::client::DefaultClientContext;
 use rdkafka::config::ClientConfig;
 
 use dataflow_types::{
@@ -20,6 +19,8 @@ use dataflow_types::{
     KafkaSinkConnectorBuilder, KafkaSinkConsistencyConnector, SinkConnector, SinkConnectorBuilder,
 };
 use expr::GlobalId;
+use kafka_util::credentials;
+use kafka_util::oauth::MzClientContext;
 use ore::collections::CollectionExt;
 
 use crate::error::CoordError;
@@ -35,7 +36,7 @@ pub async fn build(
 }
 
 async fn register_kafka_topic(
-    client: &AdminClient<DefaultClientContext>,
+    client: &AdminClient<MzClientContext>,
     topic: &str,
     mut partition_count: i32,
     mut replication_factor: i32,
@@ -178,11 +179,9 @@ async fn build_kafka(
     // Create Kafka topic with single partition.
     let mut config = ClientConfig::new();
     config.set("bootstrap.servers", &builder.broker_addrs.to_string());
-    for (k, v) in builder.config_options.iter() {
-        config.set(k, v);
-    }
+    credentials::set_config_options(&mut config, &builder.config_options)?;
     let client = config
-        .create::<AdminClient<_>>()
+        .create_with_context(MzClientContext)
         .expect("creating admin client failed");
     let ccsr = builder.ccsr_config.build();
 
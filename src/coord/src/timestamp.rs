@@ -8,7 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use std::cmp;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
 use std::io::BufRead;
 use std::ops::Deref;
@@ -36,6 +36,7 @@ use rusoto_kinesis::KinesisClient;
 use tokio::sync::mpsc;
 
 use aws_util::{client, kinesis};
+use dataflow::faults;
 use dataflow::source::read_file_task;
 use dataflow::source::FileReadStyle;
 use dataflow_types::{
@@ -44,6 +45,8 @@ use dataflow_types::{
     SourceEnvelope, TimestampSourceUpdate,
 };
 use expr::{GlobalId, PartitionId};
+use kafka_util::credentials::{self, CredentialFiles};
+use kafka_util::oauth::MzClientContext;
 use ore::collections::CollectionExt;
 
 use crate::coord;
@@ -263,11 +266,11 @@ use std::fmt::Display;
 
 /// Data consumer for Kafka source with BYO consistency
 struct ByoKafkaConnector {
-    consumer: BaseConsumer,
+    consumer: BaseConsumer<MzClientContext>,
 }
 
 impl ByoKafkaConnector {
-    fn new(consumer: BaseConsumer) -> ByoKafkaConnector {
+    fn new(consumer: BaseConsumer<MzClientContext>) -> ByoKafkaConnector {
         ByoKafkaConnector { consumer }
     }
 }
@@ -406,7 +409,7 @@ fn byo_extract_update_from_bytes(
 }
 
 /// Polls a message from a Kafka Source
-fn kafka_get_next_message(consumer: &mut BaseConsumer) -> Option<Vec<u8>> {
+fn kafka_get_next_message(consumer: &mut BaseConsumer<MzClientContext>) -> Option<Vec<u8>> {
     if let Some(result) = consumer.poll(Duration::from_millis(60)) {
         match result {
             Ok(message) => match message.payload() {
@@ -426,12 +429,26 @@ fn kafka_get_next_message(consumer: &mut BaseConsumer) -> Option<Vec<u8>> {
     }
 }
 
+/// Verifies that a consumer configured with the connection options of `kc`
+/// can reach the broker and read the metadata of the source's topic.
+pub fn verify_kafka_connector(kc: &KafkaSourceConnector) -> Result<(), anyhow::Error> {
+    let mut config = ClientConfig::new();
+    config.set("bootstrap.servers", &kc.addrs.to_string());
+    credentials::set_config_options(&mut config, &kc.config_options)?;
+    let consumer: BaseConsumer<MzClientContext> = config.create_with_context(MzClientContext)?;
+    get_kafka_partitions(&consumer, &kc.topic, Duration::from_secs(5))?;
+    Ok(())
+}
+
 /// Return the list of partition ids associated with a specific topic
 fn get_kafka_partitions(
-    consumer: &BaseConsumer,
+    consumer: &BaseConsumer<MzClientContext>,
     topic: &str,
     timeout: Duration,
 ) -> Result<Vec<i32>, anyhow::Error> {
+    if let Some(delay) = faults::metadata_refresh_delay(topic) {
+        thread::sleep(delay);
+    }
     let meta = consumer.fetch_metadata(Some(&topic), timeout)?;
     if meta.topics().len() == 0 {
         bail!("topic {} does not exist", topic);
@@ -779,6 +796,7 @@ impl Timestamper {
                         envelope,
                         consistency,
                         ts_frequency: _,
+                        event_time: _,
                     } = sc
                     {
                         (connector, encoding, envelope, consistency)
@@ -1166,11 +1184,13 @@ impl Timestamper {
             config.set("debug", "all");
         }
 
-        for (k, v) in &kc.config_options {
-            config.set(k, v);
+        let credential_files = CredentialFiles::new(&kc.config_options);
+        if let Err(e) = credentials::set_config_options(&mut config, &kc.config_options) {
+            error!("Failed to configure Kafka Consumer {}", e);
+            return None;
         }
 
-        let consumer = match config.create::<BaseConsumer>() {
+        let consumer = match config.create_with_context(MzClientContext) {
             Ok(consumer) => consumer,
             Err(e) => {
                 error!("Failed to create Kafka Consumer {}", e);
@@ -1201,7 +1221,17 @@ impl Timestamper {
 
         thread::spawn({
             let connector = connector.clone();
-            move || rt_kafka_metadata_fetch_loop(connector, consumer, metadata_refresh_frequency)
+            let config_options = kc.config_options;
+            move || {
+                rt_kafka_metadata_fetch_loop(
+                    connector,
+                    config,
+                    config_options,
+                    consumer,
+                    credential_files,
+                    metadata_refresh_frequency,
+                )
+            }
         });
 
         Some(connector)
@@ -1368,11 +1398,12 @@ impl Timestamper {
             ),
         );
 
-        for (k, v) in &kc.config_options {
-            config.set(k, v);
+        if let Err(e) = credentials::set_config_options(&mut config, &kc.config_options) {
+            error!("Could not configure a Kafka consumer. Error: {}", e);
+            return None;
         }
 
-        match config.create() {
+        match config.create_with_context(MzClientContext) {
             Ok(consumer) => {
                 let consumer = ByoKafkaConnector::new(consumer);
                 consumer.consumer.subscribe(&[&timestamp_topic]).unwrap();
@@ -1418,7 +1449,14 @@ impl Timestamper {
     }
 }
 
-fn rt_kafka_metadata_fetch_loop(c: RtKafkaConnector, consumer: BaseConsumer, wait: Duration) {
+fn rt_kafka_metadata_fetch_loop(
+    c: RtKafkaConnector,
+    mut config: ClientConfig,
+    config_options: BTreeMap<String, String>,
+    mut consumer: BaseConsumer<MzClientContext>,
+    mut credential_files: CredentialFiles,
+    wait: Duration,
+) {
     debug!(
         "Starting realtime Kafka thread for {} (source {})",
         &c.topic, &c.id
@@ -1427,6 +1465,26 @@ fn rt_kafka_metadata_fetch_loop(c: RtKafkaConnector, consumer: BaseConsumer, wai
     let mut current_partition_count = 0;
 
     while !c.coordination_state.stop.load(Ordering::SeqCst) {
+        // librdkafka only reads credential files on startup, so pick up
+        // rotated credentials by recreating the consumer. If that fails, the
+        // change is retried at the next check.
+        if credential_files.changed() {
+            info!(
+                "Recreating realtime Kafka consumer for {} (source {}) after credential change",
+                c.topic, c.id
+            );
+            let new_credential_files = CredentialFiles::new(&config_options);
+            match credentials::set_config_options(&mut config, &config_options)
+                .and_then(|()| Ok(config.create_with_context(MzClientContext)?))
+            {
+                Ok(new_consumer) => {
+                    consumer = new_consumer;
+                    credential_files = new_credential_files;
+                }
+                Err(e) => error!("Failed to recreate Kafka Consumer {}", e),
+            }
+        }
+
         match get_kafka_partitions(&consumer, &c.topic, Duration::from_secs(30)) {
             Ok(partitions) => {
                 // There cannot be more than i32 partitions
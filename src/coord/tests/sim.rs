@@ -0,0 +1,296 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A deterministic simulation harness for the coordinator.
+//!
+//! Each file in `tests/testdata-sim` is run against a fresh coordinator whose
+//! clock is under the control of the test, via the following directives:
+//!
+//!   * `set-clock time=N` sets the coordinator's clock to `N` milliseconds.
+//!   * `tick` ticks the coordinator, which runs the scheduled tasks that are
+//!     due, flushes statement statistics, and drops idle dataflows, all as of
+//!     the time that the clock reads. The coordinator never ticks on its own.
+//!   * `sql session=NAME` runs the input as a simple query on the named
+//!     session, which is created on first use, and prints the rows it
+//!     returns, sorted, or the response of its last statement.
+//!   * `write-file name=NAME` writes the input to the named file in the data
+//!     directory. `$DATA_DIR` in SQL refers to that directory.
+//!   * `advance-source name=NAME timestamp=T offset=O` binds the offsets of
+//!     the named file source up to `O` to timestamp `T`.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+
+use coord::session::{EndTransactionAction, Session, TransactionStatus};
+use coord::{Client, ExecuteResponse, Handle, SessionClient};
+use dataflow_types::{MzOffset, PeekResponse, TimestampSourceUpdate};
+use expr::{GlobalId, PartitionId};
+use ore::now::ManualClock;
+use repr::Datum;
+use sql::ast::{Raw, Statement};
+use sql_parser::parser::parse_statements;
+
+/// The timestamp at which the coordinator's clock starts.
+const START_TIME: u64 = 1;
+
+struct Simulation {
+    runtime: Arc<Runtime>,
+    clock: ManualClock,
+    data_dir: TempDir,
+    sessions: BTreeMap<String, SessionClient>,
+    client: Option<Client>,
+    handle: Option<Handle>,
+}
+
+impl Simulation {
+    fn start() -> Result<Simulation, Box<dyn Error>> {
+        let runtime = Arc::new(Runtime::new()?);
+        let clock = ManualClock::new(START_TIME);
+        let data_dir = tempfile::tempdir()?;
+        let (handle, client) = runtime.block_on(coord::serve(
+            coord::Config {
+                workers: 1,
+                timely_worker: timely::WorkerConfig::default(),
+                symbiosis_url: None,
+                logging: None,
+                data_directory: data_dir.path(),
+                timestamp_frequency: Duration::from_millis(10),
+                cache: None,
+                logical_compaction_window: None,
+                auto_tune_logical_compaction_window: false,
+                experimental_mode: true,
+                quotas: coord::QuotaConfig::default(),
+                slow_query_threshold: None,
+                statement_history_size: 0,
+                stalled_dataflow_threshold: None,
+                shared_dataflow_idle_timeout: None,
+                shared_dataflow_min_peeks: 2,
+                freshness_slo: None,
+                rehydration_budget: None,
+                lazy_view_idle_timeout: Duration::from_secs(3600),
+                log_filter: None,
+                build_info: &build_info::DUMMY_BUILD_INFO,
+                now: clock.now_fn(),
+                manual_ticks: true,
+            },
+            runtime.clone(),
+        ))?;
+        Ok(Simulation {
+            runtime,
+            clock,
+            data_dir,
+            sessions: BTreeMap::new(),
+            client: Some(client),
+            handle: Some(handle),
+        })
+    }
+
+    /// Runs `sql` on the session named `name` as if it were sent in a
+    /// pgwire simple query message.
+    fn run_sql(&mut self, name: &str, sql: &str) -> Result<String, Box<dyn Error>> {
+        let sql = sql.replace("$DATA_DIR", &self.data_dir.path().display().to_string());
+        let stmts = parse_statements(&sql)?;
+        if !self.sessions.contains_key(name) {
+            let conn_client = self.client.as_ref().unwrap().new_conn()?;
+            let conn_id = conn_client.conn_id();
+            let session = Session::new(conn_id, "materialize".into());
+            let (session_client, _) = self.runtime.block_on(conn_client.startup(session))?;
+            self.sessions.insert(name.into(), session_client);
+        }
+        let session_client = self.sessions.get_mut(name).unwrap();
+        Ok(self.runtime.block_on(simple_query(session_client, stmts)))
+    }
+
+    /// Binds the offsets of the file source named `name` up to `offset` to
+    /// `timestamp`.
+    fn advance_source(
+        &mut self,
+        name: &str,
+        timestamp: u64,
+        offset: i64,
+    ) -> Result<(), Box<dyn Error>> {
+        let out = self.run_sql(
+            "sim",
+            &format!("SELECT id FROM mz_sources WHERE name = '{}'", name),
+        )?;
+        let id = GlobalId::from_str(out.trim())?;
+        self.handle.as_ref().unwrap().advance_source_timestamp(
+            id,
+            TimestampSourceUpdate::BringYourOwn(
+                1,
+                PartitionId::File,
+                timestamp,
+                MzOffset { offset },
+            ),
+        );
+        Ok(())
+    }
+}
+
+impl Drop for Simulation {
+    fn drop(&mut self) {
+        for (_, session_client) in std::mem::take(&mut self.sessions) {
+            self.runtime.block_on(session_client.terminate());
+        }
+        // The coordinator does not shut down until all of its clients have
+        // dropped, so the client must be dropped before the handle.
+        drop(self.client.take());
+        drop(self.handle.take());
+    }
+}
+
+async fn simple_query(session_client: &mut SessionClient, stmts: Vec<Statement<Raw>>) -> String {
+    let num_stmts = stmts.len();
+    let mut out = String::new();
+    for stmt in stmts {
+        if let TransactionStatus::Failed = session_client.session().transaction() {
+            if !matches!(stmt, Statement::Commit(_) | Statement::Rollback(_)) {
+                out = "error: current transaction is aborted, commands ignored until end of transaction block\n".into();
+                break;
+            }
+        }
+        session_client
+            .session()
+            .start_transaction_implicit(num_stmts);
+        match one_query(session_client, stmt).await {
+            Ok(res) => out = res,
+            Err(e) => {
+                let session = session_client.session();
+                match session.transaction() {
+                    TransactionStatus::InTransaction(_) => session.fail_transaction(),
+                    _ => {
+                        session.clear_transaction();
+                    }
+                }
+                out = format!("error: {}\n", e);
+                break;
+            }
+        }
+    }
+    let implicit = matches!(
+        session_client.session().transaction(),
+        TransactionStatus::Started(_) | TransactionStatus::InTransactionImplicit(_)
+    );
+    if implicit {
+        // Writes in implicit transactions are only committed here, so their
+        // errors must be reported.
+        if let Err(e) = session_client
+            .end_transaction(EndTransactionAction::Commit)
+            .await
+        {
+            out = format!("error: {}\n", e);
+        }
+    }
+    out
+}
+
+async fn one_query(
+    session_client: &mut SessionClient,
+    stmt: Statement<Raw>,
+) -> Result<String, String> {
+    const EMPTY_PORTAL: &str = "";
+    session_client
+        .declare(EMPTY_PORTAL.into(), stmt, vec![], None)
+        .await
+        .map_err(|e| e.to_string())?;
+    let desc = session_client
+        .session()
+        .get_portal(EMPTY_PORTAL)
+        .map(|portal| portal.desc.relation_desc.clone())
+        .expect("unnamed portal should be present");
+    let response = session_client.execute(EMPTY_PORTAL.into()).await;
+    session_client.session().remove_portal(EMPTY_PORTAL);
+    match response.map_err(|e| e.to_string())? {
+        ExecuteResponse::SendingRows(mut rx) => {
+            let desc = desc.expect("missing row description for SendingRows");
+            let mut lines = vec![];
+            while let Some(batch) = rx.next().await {
+                match batch {
+                    PeekResponse::Rows(rows) => {
+                        for row in rows {
+                            let line: Vec<_> = row
+                                .iter()
+                                .zip(desc.typ().column_types.iter())
+                                .map(|(datum, typ)| {
+                                    let mut buf = String::new();
+                                    match datum {
+                                        Datum::Null => buf.push_str("NULL"),
+                                        _ => {
+                                            pgrepr::Value::from_datum(datum, &typ.scalar_type)
+                                                .expect("datum is not null")
+                                                .encode_text(&mut buf);
+                                        }
+                                    }
+                                    buf
+                                })
+                                .collect();
+                            lines.push(line.join(" "));
+                        }
+                    }
+                    PeekResponse::Error(e) => return Err(e),
+                    PeekResponse::Canceled => return Err("canceling statement".into()),
+                }
+            }
+            lines.sort();
+            Ok(lines.into_iter().map(|line| line + "\n").collect())
+        }
+        response => Ok(format!("{:?}\n", response)),
+    }
+}
+
+#[test]
+fn datadriven() {
+    datadriven::walk("tests/testdata-sim", |f| {
+        let mut sim = Simulation::start().unwrap();
+        f.run(|test_case| -> String {
+            let arg = |name: &str| -> String {
+                match test_case.args.get(name).map(|v| v.as_slice()) {
+                    Some([value]) => value.clone(),
+                    _ => panic!(
+                        "directive {} requires argument {}",
+                        test_case.directive, name
+                    ),
+                }
+            };
+            match test_case.directive.as_str() {
+                "set-clock" => {
+                    sim.clock.set(arg("time").parse().unwrap());
+                    "ok\n".into()
+                }
+                "tick" => {
+                    sim.handle.as_ref().unwrap().tick();
+                    "ok\n".into()
+                }
+                "sql" => sim.run_sql(&arg("session"), &test_case.input).unwrap(),
+                "write-file" => {
+                    fs::write(sim.data_dir.path().join(arg("name")), &test_case.input).unwrap();
+                    "ok\n".into()
+                }
+                "advance-source" => {
+                    sim.advance_source(
+                        &arg("name"),
+                        arg("timestamp").parse().unwrap(),
+                        arg("offset").parse().unwrap(),
+                    )
+                    .unwrap();
+                    "ok\n".into()
+                }
+                dir => panic!("unhandled directive {}", dir),
+            }
+        })
+    });
+}
@@ -69,6 +69,7 @@ fn datadriven() {
                                 Vec::<Option<String>>::new(),
                             ),
                             defaults: vec![Expr::null(); 0],
+                            checks: vec![],
                             conn_id: None,
                             depends_on: vec![],
                         }),
@@ -87,6 +88,7 @@ fn datadriven() {
                         pcx: &PlanContext::default(),
                         ids: HashSet::new(),
                         param_types: Rc::new(RefCell::new(BTreeMap::new())),
+                        notices: Rc::new(RefCell::new(vec![])),
                     };
                     let mut qcx = QueryContext::root(&scx, QueryLifetime::OneShot);
                     let q = parsed[0].clone();
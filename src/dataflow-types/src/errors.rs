@@ -16,12 +16,14 @@ use serde::{Deserialize, Serialize};
 #[derive(Ord, PartialOrd, Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
 pub enum SourceError {
     FileIO(String),
+    Decode(String),
 }
 
 impl Display for SourceError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             SourceError::FileIO(e) => write!(f, "File IO: {}", e),
+            SourceError::Decode(e) => write!(f, "Decode: {}", e),
         }
     }
 }
@@ -30,6 +32,8 @@ impl Display for SourceError {
 pub enum DataflowError {
     EvalError(EvalError),
     SourceError(SourceError),
+    /// An operator failed to spill its state to disk or to read it back.
+    SpillError(String),
 }
 
 impl Display for DataflowError {
@@ -37,6 +41,7 @@ impl Display for DataflowError {
         match self {
             DataflowError::EvalError(e) => write!(f, "Evaluation error: {}", e),
             DataflowError::SourceError(e) => write!(f, "Source error: {}", e),
+            DataflowError::SpillError(e) => write!(f, "Spill error: {}", e),
         }
     }
 }
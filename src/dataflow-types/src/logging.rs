@@ -51,9 +51,12 @@ pub enum MaterializedLog {
     DataflowCurrent,
     DataflowDependency,
     FrontierCurrent,
+    KafkaSourceStatus,
     PeekCurrent,
     PeekDuration,
     SourceInfo,
+    SourceSnapshotProgress,
+    SourceStatistics,
 }
 
 impl LogVariant {
@@ -143,6 +146,58 @@ impl LogVariant {
                 .with_column("timestamp", ScalarType::Int64.nullable(false))
                 .with_key(vec![0, 1, 2, 3]),
 
+            LogVariant::Materialized(MaterializedLog::SourceStatistics) => RelationDesc::empty()
+                .with_column("source_name", ScalarType::String.nullable(false))
+                .with_column("source_id", ScalarType::String.nullable(false))
+                .with_column("dataflow_id", ScalarType::Int64.nullable(false))
+                .with_column("partition_id", ScalarType::String.nullable(false))
+                .with_column("messages_ingested", ScalarType::Int64.nullable(false))
+                .with_column("bytes_ingested", ScalarType::Int64.nullable(false))
+                .with_column("messages_per_second", ScalarType::Int64.nullable(false))
+                .with_column("bytes_per_second", ScalarType::Int64.nullable(false))
+                .with_column("offset", ScalarType::Int64.nullable(false))
+                .with_column("high_watermark", ScalarType::Int64.nullable(true))
+                .with_column("lag", ScalarType::Int64.nullable(true))
+                .with_key(vec![0, 1, 2, 3]),
+
+            LogVariant::Materialized(MaterializedLog::SourceSnapshotProgress) => {
+                RelationDesc::empty()
+                    .with_column("source_name", ScalarType::String.nullable(false))
+                    .with_column("source_id", ScalarType::String.nullable(false))
+                    .with_column("dataflow_id", ScalarType::Int64.nullable(false))
+                    .with_column("worker", ScalarType::Int64.nullable(false))
+                    .with_column("complete", ScalarType::Bool.nullable(false))
+                    .with_column("records_processed", ScalarType::Int64.nullable(false))
+                    .with_column("records_total", ScalarType::Int64.nullable(true))
+                    .with_column("bytes_processed", ScalarType::Int64.nullable(false))
+                    .with_column("bytes_total", ScalarType::Int64.nullable(true))
+                    .with_column("progress", ScalarType::Float64.nullable(true))
+                    .with_column("started_at", ScalarType::TimestampTz.nullable(false))
+                    .with_column("last_progress_at", ScalarType::TimestampTz.nullable(true))
+                    .with_column(
+                        "estimated_completion",
+                        ScalarType::TimestampTz.nullable(true),
+                    )
+                    .with_key(vec![0, 1, 2, 3])
+            }
+
+            LogVariant::Materialized(MaterializedLog::KafkaSourceStatus) => RelationDesc::empty()
+                .with_column("source_name", ScalarType::String.nullable(false))
+                .with_column("source_id", ScalarType::String.nullable(false))
+                .with_column("dataflow_id", ScalarType::Int64.nullable(false))
+                .with_column("worker", ScalarType::Int64.nullable(false))
+                .with_column("status", ScalarType::String.nullable(false))
+                .with_column("brokers_up", ScalarType::Int64.nullable(false))
+                .with_column("brokers", ScalarType::Int64.nullable(false))
+                .with_column("partitions", ScalarType::Int64.nullable(false))
+                .with_column(
+                    "last_metadata_refresh",
+                    ScalarType::TimestampTz.nullable(true),
+                )
+                .with_column("last_error", ScalarType::String.nullable(true))
+                .with_column("last_error_time", ScalarType::TimestampTz.nullable(true))
+                .with_key(vec![0, 1, 2, 3]),
+
             LogVariant::Materialized(MaterializedLog::DataflowDependency) => RelationDesc::empty()
                 .with_column("dataflow", ScalarType::String.nullable(false))
                 .with_column("source", ScalarType::String.nullable(false))
@@ -200,8 +255,11 @@ impl LogVariant {
             LogVariant::Materialized(MaterializedLog::DataflowCurrent) => vec![],
             LogVariant::Materialized(MaterializedLog::DataflowDependency) => vec![],
             LogVariant::Materialized(MaterializedLog::FrontierCurrent) => vec![],
+            LogVariant::Materialized(MaterializedLog::KafkaSourceStatus) => vec![],
             LogVariant::Materialized(MaterializedLog::PeekCurrent) => vec![],
             LogVariant::Materialized(MaterializedLog::SourceInfo) => vec![],
+            LogVariant::Materialized(MaterializedLog::SourceSnapshotProgress) => vec![],
+            LogVariant::Materialized(MaterializedLog::SourceStatistics) => vec![],
             LogVariant::Materialized(MaterializedLog::PeekDuration) => vec![],
         }
     }
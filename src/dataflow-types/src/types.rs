@@ -18,7 +18,9 @@ use std::fmt;
 use std::path::PathBuf;
 use std::time::Duration;
 
-use anyhow::Context;
+use ::encoding::label::encoding_from_whatwg_label;
+use ::encoding::DecoderTrap;
+use anyhow::{bail, Context};
 use globset::Glob;
 use log::warn;
 use regex::Regex;
@@ -71,6 +73,9 @@ pub struct BuildDesc {
     /// If building a view, the types of columns of the built view
     /// None if building an index
     pub typ: Option<RelationType>,
+    /// If set, the reductions in the view keep at most this many bytes of
+    /// state per worker in memory, and spill the rest of their state to disk.
+    pub reduce_hot_set_size: Option<usize>,
 }
 
 /// A description of a dataflow to construct and results to surface.
@@ -95,6 +100,12 @@ pub struct DataflowDesc {
     /// the upper bound of `since` frontiers contributing to the dataflow.
     /// It is an error for this to be set to a frontier not beyond that default.
     pub as_of: Option<Antichain<Timestamp>>,
+    /// If set, the times of the updates to the exported indexes are rounded
+    /// up to a multiple of this many milliseconds.
+    ///
+    /// The interval cannot change while the dataflow runs. Changing the refresh
+    /// interval of an index renders its dataflow anew.
+    pub refresh_interval: Option<Timestamp>,
     /// Human readable name
     pub debug_name: String,
 }
@@ -157,9 +168,20 @@ impl DataflowDesc {
             id,
             relation_expr: expr,
             typ: Some(typ),
+            reduce_hot_set_size: None,
         });
     }
 
+    /// Spills the state of the reductions in the view `id` to disk, keeping at
+    /// most `hot_set_size` bytes of state per worker in memory.
+    pub fn spill_reduce_state(&mut self, id: GlobalId, hot_set_size: usize) {
+        for object in &mut self.objects_to_build {
+            if object.id == id && object.typ.is_some() {
+                object.reduce_hot_set_size = Some(hot_set_size);
+            }
+        }
+    }
+
     pub fn add_index_to_build(
         &mut self,
         id: GlobalId,
@@ -176,6 +198,7 @@ impl DataflowDesc {
                 },
             ),
             typ: None,
+            reduce_hot_set_size: None,
         });
     }
 
@@ -215,6 +238,15 @@ impl DataflowDesc {
         ));
     }
 
+    /// Returns the identifiers of the indexes and sinks exported by the
+    /// dataflow.
+    pub fn export_ids(&self) -> impl Iterator<Item = GlobalId> + '_ {
+        self.index_exports
+            .iter()
+            .map(|(id, _, _)| *id)
+            .chain(self.sink_exports.iter().map(|(id, _)| *id))
+    }
+
     /// Returns true iff the id is already imported.
     pub fn is_imported(&self, id: &GlobalId) -> bool {
         self.objects_to_build.iter().any(|bd| &bd.id == id)
@@ -309,8 +341,8 @@ pub enum DataEncoding {
     Csv(CsvEncoding),
     Regex(RegexEncoding),
     Postgres(RelationDesc),
-    Bytes,
-    Text,
+    Bytes(BytesEncoding),
+    Text(TextEncoding),
 }
 
 impl DataEncoding {
@@ -319,7 +351,7 @@ impl DataEncoding {
     pub fn desc(&self, envelope: &SourceEnvelope) -> Result<RelationDesc, anyhow::Error> {
         // Add columns for the key, if using the upsert envelope.
         let key_desc = match envelope {
-            SourceEnvelope::Upsert(key_encoding) => {
+            SourceEnvelope::Upsert(key_encoding, _) => {
                 let key_desc = key_encoding.desc(&SourceEnvelope::None)?;
 
                 // It doesn't make sense for the key to have keys.
@@ -343,7 +375,9 @@ impl DataEncoding {
 
         // Add columns for the data, based on the encoding format.
         Ok(match self {
-            DataEncoding::Bytes => key_desc.with_column("data", ScalarType::Bytes.nullable(false)),
+            DataEncoding::Bytes(_) => {
+                key_desc.with_column("data", ScalarType::Bytes.nullable(false))
+            }
             DataEncoding::AvroOcf(AvroOcfEncoding { reader_schema }) => {
                 let desc =
                     avro::validate_value_schema(&*reader_schema, envelope.get_avro_envelope_type())
@@ -403,7 +437,7 @@ impl DataEncoding {
                 let d = decode_descriptors(descriptors)?;
                 validate_descriptors(message_name, &d)?
             }
-            DataEncoding::Regex(RegexEncoding { regex }) => regex
+            DataEncoding::Regex(RegexEncoding { regex, casts, .. }) => regex
                 .capture_names()
                 .enumerate()
                 // The first capture is the entire matched string. This will
@@ -411,12 +445,13 @@ impl DataEncoding {
                 // just surround their entire regex in an explicit capture
                 // group.
                 .skip(1)
-                .fold(key_desc, |desc, (i, name)| {
+                .zip(casts)
+                .fold(key_desc, |desc, ((i, name), cast)| {
                     let name = match name {
                         None => format!("column{}", i),
                         Some(name) => name.to_owned(),
                     };
-                    let ty = ScalarType::String.nullable(true);
+                    let ty = cast.typ(&RegexEncoding::capture_type()).nullable(true);
                     desc.with_column(name, ty)
                 }),
             DataEncoding::Csv(CsvEncoding { n_cols, .. }) => {
@@ -424,23 +459,47 @@ impl DataEncoding {
                     desc.with_column(format!("column{}", i), ScalarType::String.nullable(false))
                 })
             }
-            DataEncoding::Text => key_desc.with_column("text", ScalarType::String.nullable(false)),
+            DataEncoding::Text(_) => {
+                key_desc.with_column("text", ScalarType::String.nullable(false))
+            }
             DataEncoding::Postgres(desc) => desc.clone(),
         })
     }
 
     pub fn op_name(&self) -> &str {
         match self {
-            DataEncoding::Bytes => "Bytes",
+            DataEncoding::Bytes(_) => "Bytes",
             DataEncoding::AvroOcf { .. } => "AvroOcf",
             DataEncoding::Avro(_) => "Avro",
             DataEncoding::Protobuf(_) => "Protobuf",
             DataEncoding::Regex { .. } => "Regex",
             DataEncoding::Csv(_) => "Csv",
-            DataEncoding::Text => "Text",
+            DataEncoding::Text(_) => "Text",
             DataEncoding::Postgres(_) => "Postgres",
         }
     }
+
+    /// Returns the character set conversion that must be applied to the data
+    /// before it is decoded, if any.
+    pub fn transcoding(&self) -> Option<&Transcoding> {
+        match self {
+            DataEncoding::Text(TextEncoding { transcoding })
+            | DataEncoding::Csv(CsvEncoding { transcoding, .. })
+            | DataEncoding::Regex(RegexEncoding { transcoding, .. }) => transcoding.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Removes and returns the character set conversion that must be applied
+    /// to the data before it is decoded, if any.
+    pub fn take_transcoding(&mut self) -> Option<Transcoding> {
+        match self {
+            DataEncoding::Text(TextEncoding { transcoding })
+            | DataEncoding::Csv(CsvEncoding { transcoding, .. })
+            | DataEncoding::Regex(RegexEncoding { transcoding, .. }) => transcoding.take(),
+            _ => None,
+        }
+    }
 }
 
 /// Encoding in Avro format.
@@ -464,18 +523,158 @@ pub struct ProtobufEncoding {
     pub message_name: String,
 }
 
+/// Encoding as raw bytes.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BytesEncoding {
+    pub framing: Framing,
+}
+
+/// How a stream of bytes is split into records.
+///
+/// Framing only applies to sources that read a continuous stream of bytes,
+/// like file sources. Sources that receive discrete messages, like Kafka
+/// sources, treat each message as a record.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Framing {
+    /// Records are separated by the specified non-empty delimiter.
+    Delimited(Vec<u8>),
+    /// Records are separated by the specified non-empty delimiter, which is
+    /// only recognized at offsets that are a multiple of the specified width in
+    /// bytes. This splits text in character sets whose code units are wider
+    /// than a byte, like UTF-16, where the bytes of the delimiter can also
+    /// appear straddling two other code units.
+    AlignedDelimited { delimiter: Vec<u8>, width: usize },
+    /// Each record is preceded by its length, encoded as a big-endian unsigned
+    /// integer of the specified width in bytes, which is 1, 2, 4, or 8.
+    LengthPrefixed(usize),
+    /// Every record has the specified non-zero width in bytes.
+    FixedWidth(usize),
+}
+
+impl Default for Framing {
+    fn default() -> Self {
+        Framing::Delimited(b"\n".to_vec())
+    }
+}
+
+/// Encoding as UTF-8 text.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TextEncoding {
+    pub transcoding: Option<Transcoding>,
+}
+
+/// A conversion of text in some character set to UTF-8.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Transcoding {
+    /// The WHATWG label of the character set, e.g. `latin1` or `utf-16be`.
+    pub charset: String,
+    /// How to handle bytes that are not valid in the character set.
+    pub invalid_bytes: InvalidBytes,
+}
+
+/// How to handle bytes that are not valid in a character set.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum InvalidBytes {
+    /// Report the record that contains the bytes as an error.
+    Strict,
+    /// Replace the bytes with U+FFFD REPLACEMENT CHARACTER.
+    Replace,
+    /// Drop the bytes.
+    Ignore,
+}
+
+impl Transcoding {
+    /// Constructs a conversion from the character set named `charset`.
+    ///
+    /// Like `convert_from`, this accepts PostgreSQL-style names like `LATIN1`
+    /// and `UTF16` in addition to WHATWG labels.
+    pub fn new(charset: &str, invalid_bytes: InvalidBytes) -> Result<Transcoding, anyhow::Error> {
+        let label = charset.to_lowercase().replace("_", "-");
+        let candidates = [
+            Some(label.clone()),
+            label.strip_prefix("utf").map(|s| format!("utf-{}", s)),
+            label.strip_prefix("win").map(|s| format!("windows-{}", s)),
+        ];
+        match candidates
+            .iter()
+            .flatten()
+            .find(|label| encoding_from_whatwg_label(label).is_some())
+        {
+            Some(label) => Ok(Transcoding {
+                charset: label.clone(),
+                invalid_bytes,
+            }),
+            None => bail!("unknown encoding {}", charset),
+        }
+    }
+
+    /// Returns the framing that splits text in the character set into lines.
+    pub fn line_framing(&self) -> Framing {
+        let encoding = encoding_from_whatwg_label(&self.charset)
+            .expect("charset is validated when the transcoding is constructed");
+        match encoding.name() {
+            "utf-16le" => Framing::AlignedDelimited {
+                delimiter: b"\n\0".to_vec(),
+                width: 2,
+            },
+            "utf-16be" => Framing::AlignedDelimited {
+                delimiter: b"\0\n".to_vec(),
+                width: 2,
+            },
+            // Every other supported character set encodes a newline as the
+            // byte `\n`, which is not part of the encoding of any other
+            // character.
+            _ => Framing::default(),
+        }
+    }
+
+    /// Returns a function that converts text in the character set to UTF-8,
+    /// or describes why it cannot be converted.
+    pub fn decoder(&self) -> impl Fn(&[u8]) -> Result<String, String> + Send {
+        let encoding = encoding_from_whatwg_label(&self.charset)
+            .expect("charset is validated when the transcoding is constructed");
+        let invalid_bytes = self.invalid_bytes;
+        move |bytes| {
+            let trap = match invalid_bytes {
+                InvalidBytes::Strict => DecoderTrap::Strict,
+                InvalidBytes::Replace => DecoderTrap::Replace,
+                InvalidBytes::Ignore => DecoderTrap::Ignore,
+            };
+            encoding
+                .decode(bytes, trap)
+                .map_err(|cause| format!("invalid {} text: {}", encoding.name(), cause))
+        }
+    }
+}
+
 /// Encoding in CSV format, with `n_cols` columns per row, with an optional header.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CsvEncoding {
     pub header_row: bool,
     pub n_cols: usize,
     pub delimiter: u8,
+    pub transcoding: Option<Transcoding>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RegexEncoding {
     #[serde(with = "serde_regex")]
     pub regex: Regex,
+    /// For each capture group, in order, an expression that converts the
+    /// captured text, which it reads from column 0, to the group's column.
+    pub casts: Vec<MirScalarExpr>,
+    /// Whether lines that do not match and captures that cannot be converted
+    /// are reported as errors, rather than skipped and replaced with `NULL`
+    /// respectively.
+    pub strict: bool,
+    pub transcoding: Option<Transcoding>,
+}
+
+impl RegexEncoding {
+    /// The type of the input to the expressions in `casts`.
+    pub fn capture_type() -> RelationType {
+        RelationType::new(vec![ScalarType::String.nullable(true)])
+    }
 }
 
 /// A source of updates for a relational collection.
@@ -517,7 +716,23 @@ pub struct SinkDesc {
 pub enum SinkEnvelope {
     Debezium,
     Upsert,
-    Tail { emit_progress: bool },
+    Tail {
+        emit_progress: bool,
+        envelope: TailEnvelope,
+    },
+}
+
+/// How a `TAIL` reports the changes to the tailed relation.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TailEnvelope {
+    /// Each changed row is reported along with its change in multiplicity.
+    Diffs,
+    /// The new state of each changed key is reported, either as the row now
+    /// associated with the key or as a deletion.
+    Upsert,
+    /// Each change is reported as a pair of the row before and the row after
+    /// the change.
+    Debezium,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -530,16 +745,42 @@ pub struct SinkAsOf {
 pub enum SourceEnvelope {
     None,
     Debezium(DebeziumDeduplicationStrategy),
-    Upsert(DataEncoding),
+    Upsert(DataEncoding, UpsertMergeStrategy),
     CdcV2,
+    /// An append-only stream in which records that share the values in
+    /// `key_indices` are duplicates of one another.
+    Deduplicate {
+        key_indices: Vec<usize>,
+        keep: DeduplicateKeep,
+    },
+}
+
+/// How a new value for a key is combined with the previous value under
+/// [`SourceEnvelope::Upsert`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum UpsertMergeStrategy {
+    /// The new value replaces the previous value outright.
+    Replace,
+    /// The new value is a JSON merge patch (RFC 7396) that is applied to the
+    /// previous value.
+    JsonMergePatch,
+}
+
+/// Which record to retain for a key under [`SourceEnvelope::Deduplicate`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DeduplicateKeep {
+    /// Retain the first record seen for each key and ignore the rest.
+    First,
+    /// Retain only the most recent record seen for each key.
+    Last,
 }
 
 impl SourceEnvelope {
     pub fn get_avro_envelope_type(&self) -> avro::EnvelopeType {
         match self {
-            SourceEnvelope::None => avro::EnvelopeType::None,
+            SourceEnvelope::None | SourceEnvelope::Deduplicate { .. } => avro::EnvelopeType::None,
             SourceEnvelope::Debezium { .. } => avro::EnvelopeType::Debezium,
-            SourceEnvelope::Upsert(_) => avro::EnvelopeType::Upsert,
+            SourceEnvelope::Upsert(..) => avro::EnvelopeType::Upsert,
             SourceEnvelope::CdcV2 => avro::EnvelopeType::CdcV2,
         }
     }
@@ -548,9 +789,37 @@ impl SourceEnvelope {
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Compression {
     Gzip,
+    Zstd,
+    Bzip2,
+    /// Detect the compression of each file or object from its extension.
+    Auto,
     None,
 }
 
+impl Compression {
+    /// Resolves the compression to use for the file or object named `name`.
+    ///
+    /// Only [`Compression::Auto`] depends on `name`; it is resolved using the
+    /// extension of `name`, falling back to no compression if the extension is
+    /// not recognized. The result is never [`Compression::Auto`].
+    pub fn resolve(&self, name: &str) -> Compression {
+        match self {
+            Compression::Auto => {
+                if name.ends_with(".gz") {
+                    Compression::Gzip
+                } else if name.ends_with(".zst") || name.ends_with(".zstd") {
+                    Compression::Zstd
+                } else if name.ends_with(".bz2") {
+                    Compression::Bzip2
+                } else {
+                    Compression::None
+                }
+            }
+            other => other.clone(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SourceConnector {
     External {
@@ -559,10 +828,34 @@ pub enum SourceConnector {
         envelope: SourceEnvelope,
         consistency: Consistency,
         ts_frequency: Duration,
+        /// If set, records are timestamped by a column of the record rather
+        /// than by the time at which they were ingested.
+        event_time: Option<EventTime>,
     },
     Local,
 }
 
+/// Assigns timestamps to the records of a source from one of their columns.
+///
+/// Each record is timestamped with its event time, as read from `column`,
+/// unless that time is already covered by the source's watermark, in which
+/// case the record is late and is timestamped just after the watermark. The
+/// watermark trails the latest event time seen by `lateness`, and is advanced
+/// even when no records arrive, on the assumption that no record will be
+/// ingested more than `lateness` after its event time. Event times more than
+/// `clock_skew` ahead of the ingestion time are clamped, so that a single
+/// record from a misconfigured clock cannot advance the watermark arbitrarily
+/// far into the future.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct EventTime {
+    /// The index of the column that holds the event time.
+    pub column: usize,
+    /// How far the watermark trails the latest event time.
+    pub lateness: Duration,
+    /// How far an event time may be ahead of the ingestion time.
+    pub clock_skew: Duration,
+}
+
 pub fn cached_files(e: &ExternalSourceConnector) -> Vec<PathBuf> {
     match e {
         ExternalSourceConnector::Kafka(KafkaSourceConnector { cached_files, .. }) => {
@@ -727,6 +1020,7 @@ pub struct S3SourceConnector {
     pub key_sources: Vec<S3KeySource>,
     pub pattern: Option<Glob>,
     pub aws_info: aws::ConnectInfo,
+    pub compression: Compression,
 }
 
 /// A Source of Object Key names, the argument of the `OBJECTS FROM` clause
@@ -790,7 +1084,7 @@ impl SinkConnector {
                 .key_desc_and_indices
                 .as_ref()
                 .map(|(_desc, indices)| indices.as_slice()),
-            SinkConnector::Tail(_) => None,
+            SinkConnector::Tail(t) => t.key_indices.as_deref(),
             SinkConnector::AvroOcf(_) => None,
         }
     }
@@ -811,6 +1105,10 @@ pub struct TailSinkConnector {
     pub emit_progress: bool,
     pub object_columns: usize,
     pub value_desc: RelationDesc,
+    pub envelope: TailEnvelope,
+    /// The columns of `value_desc` by which changes are grouped, if the
+    /// envelope requires it.
+    pub key_indices: Option<Vec<usize>>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
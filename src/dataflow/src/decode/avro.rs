@@ -35,18 +35,23 @@ impl AvroDecoderState {
         dedup_strat: Option<DebeziumDeduplicationStrategy>,
         dbz_key_indices: Option<Vec<usize>>,
         confluent_wire_format: bool,
+        demanded: Option<Vec<bool>>,
     ) -> Result<Self, anyhow::Error> {
+        let mut decoder = Decoder::new(
+            reader_schema,
+            schema_registry_config,
+            envelope,
+            debug_name,
+            worker_index,
+            dedup_strat,
+            dbz_key_indices,
+            confluent_wire_format,
+        )?;
+        if let Some(demanded) = demanded {
+            decoder.set_demanded_columns(demanded);
+        }
         Ok(AvroDecoderState {
-            decoder: Decoder::new(
-                reader_schema,
-                schema_registry_config,
-                envelope,
-                debug_name,
-                worker_index,
-                dedup_strat,
-                dbz_key_indices,
-                confluent_wire_format,
-            )?,
+            decoder,
             events_success: 0,
             events_error: 0,
             reject_non_inserts,
@@ -37,24 +37,17 @@ where
     // Delimiters must be single-byte utf8 to safely treat all matched fields as valid utf8.
     assert!(delimiter.is_ascii());
 
-    let operators = operators.take();
-    let demanded = (0..n_cols)
-        .map(move |c| {
-            operators
-                .as_ref()
-                .map(|o| o.projection.contains(&c))
-                .unwrap_or(true)
-        })
-        .collect::<Vec<_>>();
+    let demanded =
+        super::demanded_columns(&operators.take(), n_cols).unwrap_or_else(|| vec![true; n_cols]);
 
-    let stream =
-    stream.unary(
+    let stream = stream.unary(
         SourceOutput::<Vec<u8>, Vec<u8>>::position_value_contract(),
         "CsvDecode",
         |_, _| {
             // Temporary storage, and a re-useable CSV reader.
             let mut buffer = vec![0u8];
-            let mut bounds = vec![0usize];
+            let mut bounds = vec![0usize; n_cols + 1];
+            let mut skipped = vec![0u8; 1024];
             let mut csv_reader = csv_core::ReaderBuilder::new().delimiter(delimiter).build();
             let mut row_packer = repr::RowPacker::new();
             move |input, output| {
@@ -67,92 +60,112 @@ where
                     // but the CsvReader *itself* searches for line breaks.
                     // This is mainly an aesthetic/performance-golfing
                     // issue as I doubt it will ever be a bottleneck.
-                    for SourceOutput { key: _, value: line, position: line_no , upstream_time_millis: _ } in &*lines {
-                        // We only want to process utf8 strings, as this ensures that all fields
-                        // will be utf8 as well, allowing some unsafe shenanigans.
-                        if std::str::from_utf8(line.as_slice()).is_err() {
-                            events_error += 1;
-                            error!("CSV error: input text is not utf8");
-                        } else {
-                            // Reset the reader to read a new series of records.
-                            csv_reader.reset();
-                            if let Some(line_no) = line_no {
-                                csv_reader.set_line(*line_no as u64);
-                                if header_row && *line_no == 1 {
-                                    continue;
-                                }
+                    for SourceOutput {
+                        key: _,
+                        value: line,
+                        position: line_no,
+                        upstream_time_millis: _,
+                    } in &*lines
+                    {
+                        // Reset the reader to read a new series of records.
+                        csv_reader.reset();
+                        if let Some(line_no) = line_no {
+                            csv_reader.set_line(*line_no as u64);
+                            if header_row && *line_no == 1 {
+                                continue;
                             }
+                        }
 
-                            let mut input = line.as_slice();
-                            let mut buffer_valid = 0;
-                            let mut bounds_valid = 0;
-                            let mut done = false;
-
-                            while !done {
+                        let mut input = line.as_slice();
+                        let mut buffer_valid = 0;
+                        let mut fields = 0;
+                        let mut done = false;
 
-                                // Note that we protect the first element of `bounds`, a zero, so that ranges are easier to extract below.
-                                let (result, in_read, out_wrote, ends_wrote) = csv_reader
-                                    .read_record(input, &mut buffer[buffer_valid..], &mut bounds[1+bounds_valid..]);
+                        while !done {
+                            // Demanded fields are copied into `buffer`, and their ends recorded in `bounds`.
+                            // Undemanded fields are read into `skipped`, which is overwritten as needed.
+                            // Note that we protect the first element of `bounds`, a zero, so that ranges are easier to extract below.
+                            let demand = fields < n_cols && demanded[fields];
+                            let (result, in_read, out_wrote) = if demand {
+                                csv_reader.read_field(input, &mut buffer[buffer_valid..])
+                            } else {
+                                csv_reader.read_field(input, &mut skipped)
+                            };
 
-                                // Advance buffers, as requested by return values.
-                                input = &input[in_read..];
+                            // Advance buffers, as requested by return values.
+                            input = &input[in_read..];
+                            if demand {
                                 buffer_valid += out_wrote;
-                                bounds_valid += ends_wrote;
+                            }
 
-                                match result {
-                                    csv_core::ReadRecordResult::InputEmpty => {
-                                        // We will go around the loop again with an empty input buffer and flush any final record.
-                                    }
-                                    csv_core::ReadRecordResult::OutputFull => {
+                            match result {
+                                csv_core::ReadFieldResult::InputEmpty => {
+                                    // We will go around the loop again with an empty input buffer and flush any final record.
+                                }
+                                csv_core::ReadFieldResult::OutputFull => {
+                                    if demand {
                                         let length = buffer.len();
                                         buffer.extend(std::iter::repeat(0).take(length));
                                     }
-                                    csv_core::ReadRecordResult::OutputEndsFull => {
-                                        let length = bounds.len();
-                                        bounds.extend(std::iter::repeat(0).take(length));
+                                }
+                                csv_core::ReadFieldResult::Field { record_end } => {
+                                    if fields < n_cols {
+                                        bounds[fields + 1] = buffer_valid;
+                                    }
+                                    fields += 1;
+                                    if !record_end {
+                                        continue;
                                     }
-                                    csv_core::ReadRecordResult::Record => {
-                                        if bounds_valid != n_cols {
-                                            events_error += 1;
-                                            error!(
-                                                "CSV error: expected {} columns, got {}. Ignoring row.",
-                                                n_cols, bounds_valid,
-                                            );
-                                        } else {
-                                            events_success += 1;
-                                            session.give((
-                                                row_packer.pack(
-                                                    (0..n_cols)
-                                                        .map(|i| {
-                                                            // Unsafety rationalized as 1. the input text is determined to be
-                                                            // valid utf8, and 2. the delimiter is ascii, which should make each
-                                                            // delimited region also utf8.
+                                    if fields != n_cols {
+                                        events_error += 1;
+                                        error!(
+                                            "CSV error: expected {} columns, got {}. Ignoring row.",
+                                            n_cols, fields,
+                                        );
+                                    } else if (0..n_cols).any(|i| {
+                                        demanded[i]
+                                            && std::str::from_utf8(
+                                                &buffer[bounds[i]..bounds[i + 1]],
+                                            )
+                                            .is_err()
+                                    }) {
+                                        // We only want to process utf8 strings. Fields that are not
+                                        // demanded are never inspected.
+                                        events_error += 1;
+                                        error!("CSV error: input text is not utf8");
+                                    } else {
+                                        events_success += 1;
+                                        session.give((
+                                            row_packer.pack(
+                                                (0..n_cols)
+                                                    .map(|i| {
+                                                        if demanded[i] {
+                                                            // Unsafety rationalized as the demanded fields are
+                                                            // determined to be valid utf8 above.
                                                             Datum::String(unsafe {
-                                                                if demanded[i] {
-                                                                    std::str::from_utf8_unchecked(
-                                                                        &buffer
-                                                                            [bounds[i]..bounds[i + 1]],
-                                                                    )
-                                                                } else {
-                                                                    ""
-                                                                }
+                                                                std::str::from_utf8_unchecked(
+                                                                    &buffer
+                                                                        [bounds[i]..bounds[i + 1]],
+                                                                )
                                                             })
-                                                        })
-                                                        .chain(iter::once(
-                                                            line_no.map(Datum::Int64).into(),
-                                                        )),
-                                                ),
-                                                *cap.time(),
-                                                1,
-                                            ));
-                                            // Reset valid data to extract the next record, should one exist.
-                                            buffer_valid = 0;
-                                            bounds_valid = 0;
-                                        }
-                                    }
-                                    csv_core::ReadRecordResult::End => {
-                                        done = true;
+                                                        } else {
+                                                            Datum::Dummy
+                                                        }
+                                                    })
+                                                    .chain(iter::once(
+                                                        line_no.map(Datum::Int64).into(),
+                                                    )),
+                                            ),
+                                            *cap.time(),
+                                            1,
+                                        ));
                                     }
+                                    // Reset valid data to extract the next record, should one exist.
+                                    buffer_valid = 0;
+                                    fields = 0;
+                                }
+                                csv_core::ReadFieldResult::End => {
+                                    done = true;
                                 }
                             }
                         }
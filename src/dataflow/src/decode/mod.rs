@@ -30,7 +30,8 @@ use timely::{
 
 use ::mz_avro::{types::Value, Schema};
 use dataflow_types::LinearOperator;
-use dataflow_types::{DataEncoding, RegexEncoding, SourceEnvelope};
+use dataflow_types::{DataEncoding, SourceEnvelope};
+use expr::GlobalId;
 use interchange::avro::{extract_row, ConfluentAvroResolver, DebeziumDecodeState, DiffPair};
 use log::error;
 use repr::Datum;
@@ -38,13 +39,16 @@ use repr::{Diff, Row, RowPacker, Timestamp};
 
 use self::csv::csv;
 use self::regex::regex as regex_fn;
+use self::transcode::transcode;
 use crate::operator::StreamExt;
+use crate::server::SourceEncodingUpdates;
 use crate::source::SourceOutput;
 
 mod avro;
 mod csv;
 mod protobuf;
 mod regex;
+mod transcode;
 
 pub fn decode_avro_values<G>(
     stream: &Stream<G, SourceOutput<Vec<u8>, Value>>,
@@ -87,7 +91,7 @@ where
         )| {
             let top_node = schema.top_node();
             let diffs = match envelope {
-                SourceEnvelope::None => {
+                SourceEnvelope::None | SourceEnvelope::Deduplicate { .. } => {
                     extract_row(value, index.map(Datum::from), top_node).map(|r| DiffPair {
                         before: None,
                         after: r,
@@ -102,7 +106,7 @@ where
                         ))
                     }
                 }
-                SourceEnvelope::Upsert(_) => unreachable!("Upsert is not supported for AvroOCF"),
+                SourceEnvelope::Upsert(..) => unreachable!("Upsert is not supported for AvroOCF"),
                 SourceEnvelope::CdcV2 => unreachable!("CDC envelope is not supported for AvroOCF"),
             }
             .unwrap_or_else(|e| {
@@ -150,6 +154,9 @@ pub trait DecoderState {
     /// Register number of success and failures with decoding,
     /// and reset count of pending events if necessary
     fn log_error_count(&mut self);
+    /// Picks up any changes to the encoding made since the decoder was
+    /// created, before the next batch of messages is decoded
+    fn refresh(&mut self) {}
 }
 
 fn pack_with_line_no(datum: Datum, line_no: Option<i64>) -> Row {
@@ -242,11 +249,12 @@ pub(crate) fn get_decoder(
                 None,
                 None,
                 val_enc.confluent_wire_format,
+                None,
             )
             .expect(avro_err),
         ),
-        DataEncoding::Bytes => Box::new(OffsetDecoderState::from(bytes_to_datum)),
-        DataEncoding::Text => Box::new(OffsetDecoderState::from(text_to_datum)),
+        DataEncoding::Bytes(_) => Box::new(OffsetDecoderState::from(bytes_to_datum)),
+        DataEncoding::Text(_) => Box::new(OffsetDecoderState::from(text_to_datum)),
         _ => unreachable!("Unsupported encoding combination"),
     }
 }
@@ -267,6 +275,7 @@ where
 {
     let stream = stream.unary(contract, &op_name, move |_, _| {
         move |input, output| {
+            value_decoder_state.refresh();
             input.for_each(|cap, data| {
                 let mut session = output.session(&cap);
                 for SourceOutput {
@@ -362,13 +371,35 @@ fn decode_cdcv2<G: Scope<Timestamp = Timestamp>>(
     ((stream.as_collection(), None), Some(token))
 }
 
+/// Determines which of the first `arity` columns are read by `operators`,
+/// either because they are projected or because a predicate refers to them.
+///
+/// Returns `None` if there are no operators, in which case every column is
+/// demanded.
+fn demanded_columns(operators: &Option<LinearOperator>, arity: usize) -> Option<Vec<bool>> {
+    operators.as_ref().map(|operators| {
+        let mut demanded = vec![false; arity];
+        for col in operators
+            .projection
+            .iter()
+            .copied()
+            .chain(operators.predicates.iter().flat_map(|p| p.support()))
+        {
+            if col < arity {
+                demanded[col] = true;
+            }
+        }
+        demanded
+    })
+}
+
 /// Decode a stream of values from a stream of bytes.
 /// Returns the corresponding stream of Row/Timestamp/Diff tuples,
 /// and, optionally, a token that can be dropped to stop the decoding operator
 /// (if it isn't automatically stopped by the upstream operator stopping)
 pub fn decode_values<G>(
     stream: &Stream<G, SourceOutput<Vec<u8>, Vec<u8>>>,
-    encoding: DataEncoding,
+    mut encoding: DataEncoding,
     debug_name: &str,
     envelope: &SourceEnvelope,
     // Information about optional transformations that can be eagerly done.
@@ -377,6 +408,8 @@ pub fn decode_values<G>(
     operators: &mut Option<LinearOperator>,
     fast_forwarded: bool,
     desc: RelationDesc,
+    source_id: GlobalId,
+    encoding_updates: SourceEncodingUpdates,
 ) -> (
     (
         Collection<G, Row, Diff>,
@@ -387,10 +420,39 @@ pub fn decode_values<G>(
 where
     G: Scope<Timestamp = Timestamp>,
 {
+    // Text that is not UTF-8 is converted before it reaches the decoder, which
+    // then proceeds as if the source had always been UTF-8.
+    if let Some(transcoding) = encoding.take_transcoding() {
+        let (stream, transcode_errs) = transcode(stream, &transcoding, debug_name);
+        let ((oks, errs), token) = decode_values(
+            &stream,
+            encoding,
+            debug_name,
+            envelope,
+            operators,
+            fast_forwarded,
+            desc,
+            source_id,
+            encoding_updates,
+        );
+        let errs = match errs {
+            Some(errs) => errs.concat(&transcode_errs),
+            None => transcode_errs,
+        };
+        return ((oks, Some(errs)), token);
+    }
+
     let op_name = format!("{}Decode", encoding.op_name());
     let worker_index = stream.scope().index();
+    // Deduplication is applied to the decoded rows by the caller, so decode
+    // these sources exactly as if they had no envelope.
+    let no_envelope = SourceEnvelope::None;
+    let envelope = match envelope {
+        SourceEnvelope::Deduplicate { .. } => &no_envelope,
+        envelope => envelope,
+    };
     match (encoding, envelope) {
-        (_, SourceEnvelope::Upsert(_)) => {
+        (_, SourceEnvelope::Upsert(..)) => {
             unreachable!("Internal error: Upsert is not supported yet on non-Kafka sources.")
         }
         (DataEncoding::Csv(enc), SourceEnvelope::None) => (
@@ -435,6 +497,7 @@ where
                         Some(dedup_strat),
                         dbz_key_indices,
                         enc.confluent_wire_format,
+                        None,
                     )
                     .expect("Failed to create Avro decoder"),
                     &op_name,
@@ -446,6 +509,8 @@ where
         (DataEncoding::Avro(enc), envelope) => (
             decode_values_inner(
                 stream,
+                // The decoder leaves `operators` in place, as the predicates
+                // must still be applied downstream.
                 avro::AvroDecoderState::new(
                     &enc.value_schema,
                     enc.schema_registry_config,
@@ -456,6 +521,7 @@ where
                     None,
                     None,
                     enc.confluent_wire_format,
+                    demanded_columns(operators, desc.arity()),
                 )
                 .expect("Failed to create Avro decoder"),
                 &op_name,
@@ -469,19 +535,26 @@ where
         (_, SourceEnvelope::Debezium(_)) => unreachable!(
             "Internal error: A non-Avro Debezium-envelope source should not have been created."
         ),
-        (DataEncoding::Regex(RegexEncoding { regex }), SourceEnvelope::None) => {
-            ((regex_fn(stream, regex, debug_name), None), None)
+        (DataEncoding::Regex(enc), SourceEnvelope::None) => {
+            (regex_fn(stream, enc, debug_name), None)
         }
         (DataEncoding::Protobuf(enc), SourceEnvelope::None) => (
             decode_values_inner(
                 stream,
-                protobuf::ProtobufDecoderState::new(&enc.descriptors, &enc.message_name),
+                // The decoder leaves `operators` in place, as the predicates
+                // must still be applied downstream.
+                protobuf::ProtobufDecoderState::new(
+                    enc,
+                    demanded_columns(operators, desc.arity()),
+                    source_id,
+                    encoding_updates,
+                ),
                 &op_name,
                 SourceOutput::<Vec<u8>, Vec<u8>>::position_value_contract(),
             ),
             None,
         ),
-        (DataEncoding::Bytes, SourceEnvelope::None) => (
+        (DataEncoding::Bytes(_), SourceEnvelope::None) => (
             decode_values_inner(
                 stream,
                 OffsetDecoderState::from(bytes_to_datum),
@@ -490,7 +563,7 @@ where
             ),
             None,
         ),
-        (DataEncoding::Text, SourceEnvelope::None) => (
+        (DataEncoding::Text(_), SourceEnvelope::None) => (
             decode_values_inner(
                 stream,
                 OffsetDecoderState::from(text_to_datum),
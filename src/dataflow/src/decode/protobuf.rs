@@ -7,32 +7,56 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use log::error;
+use log::{error, info};
 
+use dataflow_types::ProtobufEncoding;
+use expr::GlobalId;
 use interchange::protobuf::{self, Decoder};
 use repr::{Diff, Row, Timestamp};
 
 use super::{DecoderState, PushSession};
 use crate::metrics::EVENTS_COUNTER;
+use crate::server::SourceEncodingUpdates;
 
 pub struct ProtobufDecoderState {
     decoder: Decoder,
+    encoding: ProtobufEncoding,
+    demanded: Option<Vec<bool>>,
+    source_id: GlobalId,
+    encoding_updates: SourceEncodingUpdates,
     events_success: i64,
     events_error: i64,
 }
 
 impl ProtobufDecoderState {
-    pub fn new(descriptors: &[u8], message_name: &str) -> Self {
-        let descriptors = protobuf::decode_descriptors(descriptors)
-            .expect("descriptors provided to protobuf source are pre-validated");
+    pub fn new(
+        encoding: ProtobufEncoding,
+        demanded: Option<Vec<bool>>,
+        source_id: GlobalId,
+        encoding_updates: SourceEncodingUpdates,
+    ) -> Self {
         ProtobufDecoderState {
-            decoder: Decoder::new(descriptors, message_name),
+            decoder: build_decoder(&encoding, demanded.clone()),
+            encoding,
+            demanded,
+            source_id,
+            encoding_updates,
             events_success: 0,
             events_error: 0,
         }
     }
 }
 
+fn build_decoder(encoding: &ProtobufEncoding, demanded: Option<Vec<bool>>) -> Decoder {
+    let descriptors = protobuf::decode_descriptors(&encoding.descriptors)
+        .expect("descriptors provided to protobuf source are pre-validated");
+    let mut decoder = Decoder::new(descriptors, &encoding.message_name);
+    if let Some(demanded) = demanded {
+        decoder.set_demanded_columns(demanded);
+    }
+    decoder
+}
+
 impl DecoderState for ProtobufDecoderState {
     fn decode_key(&mut self, bytes: &[u8]) -> Result<Row, String> {
         // Note that we're passing `None` as the offset for the key -
@@ -113,4 +137,20 @@ impl DecoderState for ProtobufDecoderState {
             self.events_error = 0;
         }
     }
+
+    /// Switch to the source's new descriptors, if they have been altered.
+    /// The new descriptors are known to describe the same columns as the old
+    /// ones, so the demanded columns still apply.
+    fn refresh(&mut self) {
+        if let Some(encoding) = self.encoding_updates.borrow().get(&self.source_id) {
+            if *encoding != self.encoding {
+                info!(
+                    "source {} switching to new protobuf descriptors",
+                    self.source_id
+                );
+                self.decoder = build_decoder(encoding, self.demanded.clone());
+                self.encoding = encoding.clone();
+            }
+        }
+    }
 }
@@ -7,79 +7,134 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::iter;
 use std::str;
 
 use differential_dataflow::{AsCollection, Collection};
 use log::warn;
-use regex::Regex;
-use timely::dataflow::operators::Operator;
 use timely::dataflow::{Scope, Stream};
 
-use repr::{Datum, Diff, Row, Timestamp};
+use dataflow_types::{DataflowError, RegexEncoding, SourceError};
+use repr::{Datum, Diff, Row, RowArena, Timestamp};
 
+use crate::operator::StreamExt;
 use crate::source::SourceOutput;
 
 pub fn regex<G>(
     stream: &Stream<G, SourceOutput<Vec<u8>, Vec<u8>>>,
-    regex: Regex,
+    encoding: RegexEncoding,
     name: &str,
-) -> Collection<G, Row, Diff>
+) -> (
+    Collection<G, Row, Diff>,
+    Option<Collection<G, DataflowError, Diff>>,
+)
 where
     G: Scope<Timestamp = Timestamp>,
 {
+    let RegexEncoding {
+        regex,
+        casts,
+        strict,
+        ..
+    } = encoding;
+    let column_names: Vec<_> = regex
+        .capture_names()
+        .enumerate()
+        .skip(1)
+        .map(|(i, name)| match name {
+            None => format!("column{}", i),
+            Some(name) => name.to_owned(),
+        })
+        .collect();
     let name = String::from(name);
     let pact = SourceOutput::<Vec<u8>, Vec<u8>>::position_value_contract();
     let mut row_packer = repr::RowPacker::new();
-    stream
-        .unary(pact, "RegexDecode", |_cap, _op_info| {
-            move |input, output| {
-                input.for_each(|cap, lines| {
-                    let mut session = output.session(&cap);
-                    for SourceOutput {
-                        key: _,
-                        value: line,
-                        position: line_no,
-                        upstream_time_millis: _,
-                    } in &*lines
-                    {
-                        let line = match str::from_utf8(&line) {
-                            Ok(line) => line,
-                            Err(_) => {
-                                let line_no = match line_no {
-                                    Some(line_no) => line_no.to_string(),
-                                    None => "unknown".into(),
-                                };
-                                let line_prefix = String::from_utf8_lossy(&line)
-                                    .chars()
-                                    .take(64)
-                                    .collect::<String>();
-                                warn!(
-                                    "dropping line with invalid UTF-8 \
-                                (source: {}, line number: {}, line prefix: {:?})",
-                                    name, line_no, line_prefix,
-                                );
-                                continue;
-                            }
-                        };
+    let (oks, errs) = stream.unary_fallible(pact, "RegexDecode", |_cap, _op_info| {
+        Box::new(move |input, ok_output, err_output| {
+            input.for_each(|cap, lines| {
+                let mut ok_session = ok_output.session(&cap);
+                let mut err_session = err_output.session(&cap);
+                for SourceOutput {
+                    key: _,
+                    value: line,
+                    position: line_no,
+                    upstream_time_millis: _,
+                } in &*lines
+                {
+                    let describe_line_no = || match line_no {
+                        Some(line_no) => line_no.to_string(),
+                        None => "unknown".into(),
+                    };
+                    let mut report = |msg: String| {
+                        let err = SourceError::Decode(format!(
+                            "source {}, line number {}: {}",
+                            name,
+                            describe_line_no(),
+                            msg
+                        ));
+                        err_session.give((DataflowError::SourceError(err), *cap.time(), 1));
+                    };
 
-                        let captures = match regex.captures(line) {
-                            Some(captures) => captures,
-                            None => continue,
-                        };
+                    let line = match str::from_utf8(&line) {
+                        Ok(line) => line,
+                        Err(_) if strict => {
+                            report("invalid UTF-8".into());
+                            continue;
+                        }
+                        Err(_) => {
+                            let line_prefix = String::from_utf8_lossy(&line)
+                                .chars()
+                                .take(64)
+                                .collect::<String>();
+                            warn!(
+                                "dropping line with invalid UTF-8 \
+                                (source: {}, line number: {}, line prefix: {:?})",
+                                name,
+                                describe_line_no(),
+                                line_prefix,
+                            );
+                            continue;
+                        }
+                    };
 
-                        // Skip the 0th capture, which is the entire match, so that
-                        // we only output the actual capture groups.
-                        let datums = captures
-                            .iter()
-                            .skip(1)
-                            .map(|c| Datum::from(c.map(|c| c.as_str())))
-                            .chain(iter::once(Datum::from(*line_no)));
+                    let captures = match regex.captures(line) {
+                        Some(captures) => captures,
+                        None if strict => {
+                            report("line does not match regex".into());
+                            continue;
+                        }
+                        None => continue,
+                    };
 
-                        session.give((row_packer.pack(datums), *cap.time(), 1));
+                    // Skip the 0th capture, which is the entire match, so that
+                    // we only output the actual capture groups. Captures that
+                    // cannot be converted to their column's type are errors
+                    // in strict mode and `NULL` otherwise.
+                    let arena = RowArena::new();
+                    let mut datums = Vec::with_capacity(casts.len() + 1);
+                    let mut failed = false;
+                    for ((capture, cast), column_name) in
+                        captures.iter().skip(1).zip(&casts).zip(&column_names)
+                    {
+                        let capture = Datum::from(capture.map(|c| c.as_str()));
+                        match cast.eval(&[capture], &arena) {
+                            Ok(datum) => datums.push(datum),
+                            Err(e) if strict => {
+                                report(format!("column {}: {}", column_name, e));
+                                failed = true;
+                                break;
+                            }
+                            Err(_) => datums.push(Datum::Null),
+                        }
                     }
-                });
-            }
+                    if failed {
+                        continue;
+                    }
+                    datums.push(Datum::from(*line_no));
+
+                    ok_session.give((row_packer.pack(datums), *cap.time(), 1));
+                }
+            });
         })
-        .as_collection()
+    });
+    (oks.as_collection(), Some(errs.as_collection()))
 }
@@ -0,0 +1,67 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use differential_dataflow::{AsCollection, Collection};
+use timely::dataflow::{Scope, Stream};
+
+use dataflow_types::{DataflowError, SourceError, Transcoding};
+use repr::{Diff, Timestamp};
+
+use crate::operator::StreamExt;
+use crate::source::SourceOutput;
+
+/// Converts the values in `stream` to UTF-8 according to `transcoding`.
+///
+/// Values that cannot be converted are reported as errors, which only occurs
+/// if `transcoding` does not replace or ignore invalid bytes.
+pub fn transcode<G>(
+    stream: &Stream<G, SourceOutput<Vec<u8>, Vec<u8>>>,
+    transcoding: &Transcoding,
+    name: &str,
+) -> (
+    Stream<G, SourceOutput<Vec<u8>, Vec<u8>>>,
+    Collection<G, DataflowError, Diff>,
+)
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let decode = transcoding.decoder();
+    let name = String::from(name);
+    let pact = SourceOutput::<Vec<u8>, Vec<u8>>::position_value_contract();
+    let (oks, errs) = stream.unary_fallible(pact, "Transcode", |_cap, _op_info| {
+        Box::new(move |input, ok_output, err_output| {
+            input.for_each(|cap, outputs| {
+                let mut ok_session = ok_output.session(&cap);
+                let mut err_session = err_output.session(&cap);
+                for output in &*outputs {
+                    match decode(&output.value) {
+                        Ok(value) => ok_session.give(SourceOutput {
+                            key: output.key.clone(),
+                            value: value.into_bytes(),
+                            position: output.position,
+                            upstream_time_millis: output.upstream_time_millis,
+                        }),
+                        Err(msg) => {
+                            let line_no = match output.position {
+                                Some(line_no) => line_no.to_string(),
+                                None => "unknown".into(),
+                            };
+                            let err = SourceError::Decode(format!(
+                                "source {}, line number {}: {}",
+                                name, line_no, msg
+                            ));
+                            err_session.give((DataflowError::SourceError(err), *cap.time(), 1));
+                        }
+                    }
+                }
+            });
+        })
+    });
+    (oks, errs.as_collection())
+}
@@ -0,0 +1,173 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Fault injection for Kafka sources and sinks.
+//!
+//! Faults are recorded in a process-wide registry, typically via the
+//! `/internal/faults` HTTP endpoint, and are observed by the sources and sinks
+//! that read from or write to the affected topic the next time they run.
+//!
+//! Injecting faults requires the `fault-injection` feature. Without it, the
+//! registry is always empty, and the checks performed by sources and sinks
+//! reduce to a test of a constant.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+/// A fault to inject.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Fault {
+    /// Drops the Kafka connections of the sources and sinks that use a topic.
+    /// The sources and sinks then reconnect.
+    DropConnection {
+        /// The affected topic.
+        topic: String,
+    },
+    /// Delays every refresh of a topic's metadata.
+    DelayMetadataRefresh {
+        /// The affected topic.
+        topic: String,
+        /// The delay, in milliseconds.
+        delay_ms: u64,
+    },
+    /// Corrupts the payloads of the next messages read from or written to a
+    /// topic.
+    CorruptMessages {
+        /// The affected topic.
+        topic: String,
+        /// The number of messages to corrupt.
+        count: u64,
+    },
+    /// Stops sources from reading a partition of a topic. Sinks stop writing
+    /// to a topic while any of its partitions is stalled.
+    StallPartition {
+        /// The affected topic.
+        topic: String,
+        /// The affected partition.
+        partition: i32,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    /// The number of connection drops injected for each topic.
+    connection_drops: HashMap<String, u64>,
+    metadata_delays: HashMap<String, Duration>,
+    /// The number of messages that remain to be corrupted for each topic.
+    corruptions: HashMap<String, u64>,
+    stalls: HashSet<(String, i32)>,
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+/// Injects `fault`.
+#[cfg(feature = "fault-injection")]
+pub fn inject(fault: Fault) {
+    log::warn!("injecting fault: {:?}", fault);
+    let mut registry = REGISTRY.lock().expect("lock poisoned");
+    match fault {
+        Fault::DropConnection { topic } => {
+            *registry.connection_drops.entry(topic).or_default() += 1;
+        }
+        Fault::DelayMetadataRefresh { topic, delay_ms } => {
+            registry
+                .metadata_delays
+                .insert(topic, Duration::from_millis(delay_ms));
+        }
+        Fault::CorruptMessages { topic, count } => {
+            *registry.corruptions.entry(topic).or_default() += count;
+        }
+        Fault::StallPartition { topic, partition } => {
+            registry.stalls.insert((topic, partition));
+        }
+    }
+}
+
+/// Clears all injected faults.
+///
+/// Connections that were dropped stay dropped; clearing faults does not cause
+/// any further reconnections.
+#[cfg(feature = "fault-injection")]
+pub fn clear() {
+    log::warn!("clearing injected faults");
+    let mut registry = REGISTRY.lock().expect("lock poisoned");
+    registry.metadata_delays.clear();
+    registry.corruptions.clear();
+    registry.stalls.clear();
+}
+
+/// Returns the number of connection drops injected for `topic`.
+///
+/// Sources and sinks should remember the value returned when they connect,
+/// and reconnect whenever it changes.
+pub fn connection_drops(topic: &str) -> u64 {
+    if !cfg!(feature = "fault-injection") {
+        return 0;
+    }
+    let registry = REGISTRY.lock().expect("lock poisoned");
+    registry.connection_drops.get(topic).copied().unwrap_or(0)
+}
+
+/// Returns the delay to apply before refreshing the metadata of `topic`, if
+/// any.
+pub fn metadata_refresh_delay(topic: &str) -> Option<Duration> {
+    if !cfg!(feature = "fault-injection") {
+        return None;
+    }
+    let registry = REGISTRY.lock().expect("lock poisoned");
+    registry.metadata_delays.get(topic).copied()
+}
+
+/// Reports whether the next message read from or written to `topic` should be
+/// corrupted, counting it against the injected corruptions if so.
+pub fn take_corruption(topic: &str) -> bool {
+    if !cfg!(feature = "fault-injection") {
+        return false;
+    }
+    let mut registry = REGISTRY.lock().expect("lock poisoned");
+    match registry.corruptions.get_mut(topic) {
+        Some(count) if *count > 0 => {
+            *count -= 1;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Corrupts `payload` in place, by inverting every bit.
+pub fn corrupt(payload: &mut [u8]) {
+    for b in payload {
+        *b = !*b;
+    }
+}
+
+/// Reports whether `partition` of `topic` is stalled.
+pub fn partition_stalled(topic: &str, partition: i32) -> bool {
+    if !cfg!(feature = "fault-injection") {
+        return false;
+    }
+    let registry = REGISTRY.lock().expect("lock poisoned");
+    registry.stalls.contains(&(topic.to_owned(), partition))
+}
+
+/// Reports whether any partition of `topic` is stalled.
+pub fn topic_stalled(topic: &str) -> bool {
+    if !cfg!(feature = "fault-injection") {
+        return false;
+    }
+    let registry = REGISTRY.lock().expect("lock poisoned");
+    registry.stalls.iter().any(|(t, _)| t == topic)
+}
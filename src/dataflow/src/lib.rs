@@ -19,6 +19,7 @@ mod render;
 mod server;
 mod sink;
 
+pub mod faults;
 pub mod logging;
 pub mod source;
 
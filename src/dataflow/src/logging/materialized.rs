@@ -11,6 +11,7 @@
 
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use differential_dataflow::{difference::DiffPair, operators::count::CountTotal};
 use log::error;
 use timely::communication::Allocate;
@@ -53,10 +54,146 @@ pub enum MaterializedEvent {
         /// Difference between the previous timestamp and current highest timestamp we've seen
         timestamp: i64,
     },
+    /// Tracks ingestion statistics for a source partition.
+    SourceStatistics {
+        /// Name of the source
+        source_name: String,
+        /// Source identifier
+        source_id: SourceInstanceId,
+        /// Partition identifier
+        partition_id: String,
+        /// The statistics
+        statistics: SourceStatistics,
+        /// Whether the statistics are inserted (1) or retracted (-1)
+        diff: i64,
+    },
+    /// Tracks the health of a Kafka source's connection on one worker.
+    KafkaSourceStatus {
+        /// Name of the source
+        source_name: String,
+        /// Source identifier
+        source_id: SourceInstanceId,
+        /// The status
+        status: KafkaSourceStatus,
+        /// Whether the status is inserted (1) or retracted (-1)
+        diff: i64,
+    },
+    /// Tracks the progress of a source's initial read on one worker.
+    SourceSnapshotProgress {
+        /// Name of the source
+        source_name: String,
+        /// Source identifier
+        source_id: SourceInstanceId,
+        /// The progress
+        progress: SnapshotProgress,
+        /// Whether the progress is inserted (1) or retracted (-1)
+        diff: i64,
+    },
     /// Available frontier information for views.
     Frontier(GlobalId, Timestamp, i64),
 }
 
+/// Ingestion statistics for a source partition.
+#[derive(Debug, Clone, Copy, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct SourceStatistics {
+    /// Total number of messages ingested
+    pub messages_ingested: i64,
+    /// Total number of bytes ingested
+    pub bytes_ingested: i64,
+    /// Messages ingested per second, over the last reporting interval
+    pub messages_per_second: i64,
+    /// Bytes ingested per second, over the last reporting interval
+    pub bytes_per_second: i64,
+    /// The highest offset ingested
+    pub offset: i64,
+    /// The offset after the latest message in the upstream partition, if known
+    pub high_watermark: Option<i64>,
+}
+
+/// The health of a Kafka source's connection, as reported by librdkafka.
+#[derive(Debug, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct KafkaSourceStatus {
+    /// Whether librdkafka has reported statistics for the connection yet
+    pub reported: bool,
+    /// Number of brokers to which the consumer has an open connection
+    pub brokers_up: i64,
+    /// Number of brokers known to the consumer
+    pub brokers: i64,
+    /// Number of partitions of the topic discovered by the source
+    pub partitions: i64,
+    /// When the topic's metadata was last refreshed, if known
+    pub last_metadata_refresh: Option<DateTime<Utc>>,
+    /// The most recent error reported by librdkafka, if any
+    pub last_error: Option<String>,
+    /// When the most recent error was reported
+    pub last_error_time: Option<DateTime<Utc>>,
+}
+
+impl KafkaSourceStatus {
+    /// Summarizes the status as `connecting`, `connected`, or `disconnected`.
+    pub fn summary(&self) -> &'static str {
+        if !self.reported {
+            "connecting"
+        } else if self.brokers_up > 0 {
+            "connected"
+        } else {
+            "disconnected"
+        }
+    }
+}
+
+/// The progress of a source's initial read of the data that existed upstream
+/// when the source was created, e.g., the objects found by an S3 bucket scan.
+#[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash)]
+pub struct SnapshotProgress {
+    /// Whether the initial read has completed
+    pub complete: bool,
+    /// Number of records read so far
+    pub records_processed: i64,
+    /// Estimated number of records in the initial read, if known
+    pub records_total: Option<i64>,
+    /// Number of bytes read so far
+    pub bytes_processed: i64,
+    /// Estimated number of bytes in the initial read, if known
+    pub bytes_total: Option<i64>,
+    /// How far behind the upstream system the source was when it first
+    /// reported its lag, in milliseconds, if known
+    pub initial_lag_ms: Option<i64>,
+    /// How far behind the upstream system the source is, in milliseconds, if
+    /// known
+    pub lag_ms: Option<i64>,
+    /// When the initial read started
+    pub started_at: DateTime<Utc>,
+    /// When the source last made progress, if it has made any
+    pub last_progress_at: Option<DateTime<Utc>>,
+    /// When the initial read is expected to complete, if it can be estimated
+    pub estimated_completion: Option<DateTime<Utc>>,
+}
+
+impl SnapshotProgress {
+    /// Estimates the fraction of the initial read that has completed, between
+    /// zero and one.
+    ///
+    /// The estimate is based on bytes if their total is known, then on
+    /// records, and finally on how far the source has caught up to the
+    /// upstream system.
+    pub fn fraction(&self) -> Option<f64> {
+        if self.complete {
+            return Some(1.0);
+        }
+        let fraction = match (self.bytes_total, self.records_total, self.initial_lag_ms) {
+            (Some(total), _, _) if total > 0 => self.bytes_processed as f64 / total as f64,
+            (_, Some(total), _) if total > 0 => self.records_processed as f64 / total as f64,
+            (_, _, Some(initial)) if initial > 0 => {
+                let lag = self.lag_ms.unwrap_or(initial);
+                1.0 - lag as f64 / initial as f64
+            }
+            _ => return None,
+        };
+        Some(fraction.max(0.0).min(1.0))
+    }
+}
+
 /// A logged peek event.
 #[derive(
     Debug, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, serde::Serialize, serde::Deserialize,
@@ -105,6 +242,9 @@ pub fn construct<A: Allocate>(
         let (mut dependency_out, dependency) = demux.new_output();
         let (mut peek_out, peek) = demux.new_output();
         let (mut source_info_out, source_info) = demux.new_output();
+        let (mut source_statistics_out, source_statistics) = demux.new_output();
+        let (mut kafka_source_status_out, kafka_source_status) = demux.new_output();
+        let (mut snapshot_progress_out, snapshot_progress) = demux.new_output();
         let (mut frontier_out, frontier) = demux.new_output();
 
         let mut demux_buffer = Vec::new();
@@ -116,6 +256,9 @@ pub fn construct<A: Allocate>(
                 let mut dependency = dependency_out.activate();
                 let mut peek = peek_out.activate();
                 let mut source_info = source_info_out.activate();
+                let mut source_statistics = source_statistics_out.activate();
+                let mut kafka_source_status = kafka_source_status_out.activate();
+                let mut snapshot_progress = snapshot_progress_out.activate();
                 let mut frontier = frontier_out.activate();
 
                 input.for_each(|time, data| {
@@ -125,6 +268,9 @@ pub fn construct<A: Allocate>(
                     let mut dependency_session = dependency.session(&time);
                     let mut peek_session = peek.session(&time);
                     let mut source_info_session = source_info.session(&time);
+                    let mut source_statistics_session = source_statistics.session(&time);
+                    let mut kafka_source_status_session = kafka_source_status.session(&time);
+                    let mut snapshot_progress_session = snapshot_progress.session(&time);
                     let mut frontier_session = frontier.session(&time);
 
                     for (time, worker, datum) in demux_buffer.drain(..) {
@@ -192,6 +338,84 @@ pub fn construct<A: Allocate>(
                                     DiffPair::new(offset, timestamp),
                                 ));
                             }
+                            MaterializedEvent::SourceStatistics {
+                                source_name,
+                                source_id,
+                                partition_id,
+                                statistics,
+                                diff,
+                            } => {
+                                let lag = statistics.high_watermark.map(|high_watermark| {
+                                    std::cmp::max(high_watermark - statistics.offset, 0)
+                                });
+                                source_statistics_session.give((
+                                    row_packer.pack(&[
+                                        Datum::String(&source_name),
+                                        Datum::String(&source_id.source_id.to_string()),
+                                        Datum::Int64(source_id.dataflow_id as i64),
+                                        Datum::String(&partition_id),
+                                        Datum::Int64(statistics.messages_ingested),
+                                        Datum::Int64(statistics.bytes_ingested),
+                                        Datum::Int64(statistics.messages_per_second),
+                                        Datum::Int64(statistics.bytes_per_second),
+                                        Datum::Int64(statistics.offset),
+                                        Datum::from(statistics.high_watermark),
+                                        Datum::from(lag),
+                                    ]),
+                                    time_ms,
+                                    diff as isize,
+                                ));
+                            }
+                            MaterializedEvent::KafkaSourceStatus {
+                                source_name,
+                                source_id,
+                                status,
+                                diff,
+                            } => {
+                                kafka_source_status_session.give((
+                                    row_packer.pack(&[
+                                        Datum::String(&source_name),
+                                        Datum::String(&source_id.source_id.to_string()),
+                                        Datum::Int64(source_id.dataflow_id as i64),
+                                        Datum::Int64(worker as i64),
+                                        Datum::String(status.summary()),
+                                        Datum::Int64(status.brokers_up),
+                                        Datum::Int64(status.brokers),
+                                        Datum::Int64(status.partitions),
+                                        Datum::from(status.last_metadata_refresh),
+                                        Datum::from(status.last_error.as_deref()),
+                                        Datum::from(status.last_error_time),
+                                    ]),
+                                    time_ms,
+                                    diff as isize,
+                                ));
+                            }
+                            MaterializedEvent::SourceSnapshotProgress {
+                                source_name,
+                                source_id,
+                                progress,
+                                diff,
+                            } => {
+                                snapshot_progress_session.give((
+                                    row_packer.pack(&[
+                                        Datum::String(&source_name),
+                                        Datum::String(&source_id.source_id.to_string()),
+                                        Datum::Int64(source_id.dataflow_id as i64),
+                                        Datum::Int64(worker as i64),
+                                        Datum::from(progress.complete),
+                                        Datum::Int64(progress.records_processed),
+                                        Datum::from(progress.records_total),
+                                        Datum::Int64(progress.bytes_processed),
+                                        Datum::from(progress.bytes_total),
+                                        Datum::from(progress.fraction()),
+                                        Datum::from(progress.started_at),
+                                        Datum::from(progress.last_progress_at),
+                                        Datum::from(progress.estimated_completion),
+                                    ]),
+                                    time_ms,
+                                    diff as isize,
+                                ));
+                            }
                             MaterializedEvent::Frontier(name, logical, delta) => {
                                 frontier_session.give((
                                     row_packer.pack(&[
@@ -282,6 +506,12 @@ pub fn construct<A: Allocate>(
             }
         });
 
+        let source_statistics_current = source_statistics.as_collection();
+
+        let kafka_source_status_current = kafka_source_status.as_collection();
+
+        let snapshot_progress_current = snapshot_progress.as_collection();
+
         let frontier_current = frontier.as_collection();
 
         // Duration statistics derive from the non-rounded event times.
@@ -373,6 +603,18 @@ pub fn construct<A: Allocate>(
                 LogVariant::Materialized(MaterializedLog::SourceInfo),
                 source_info_current,
             ),
+            (
+                LogVariant::Materialized(MaterializedLog::SourceStatistics),
+                source_statistics_current,
+            ),
+            (
+                LogVariant::Materialized(MaterializedLog::KafkaSourceStatus),
+                kafka_source_status_current,
+            ),
+            (
+                LogVariant::Materialized(MaterializedLog::SourceSnapshotProgress),
+                snapshot_progress_current,
+            ),
         ];
 
         use differential_dataflow::operators::arrange::arrangement::ArrangeByKey;
@@ -45,7 +45,8 @@ where
                         let datums = row.unpack();
                         let temp_storage = RowArena::new();
                         let key_row =
-                            Row::try_pack(keys2.iter().map(|k| k.eval(&datums, &temp_storage)))?;
+                            Row::try_pack(keys2.iter().map(|k| k.eval(&datums, &temp_storage)))?
+                                .normalize_zeros();
                         Ok::<_, DataflowError>((key_row, row))
                     });
                     let err_collection = err_built.concat(&err_collection);
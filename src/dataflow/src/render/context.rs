@@ -12,6 +12,7 @@
 
 use std::any::Any;
 use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
 use std::rc::Rc;
 
 use differential_dataflow::lattice::Lattice;
@@ -89,6 +90,11 @@ where
     pub additional_tokens: HashMap<GlobalId, Vec<Rc<dyn Any>>>,
     /// Tokens for CDCv2 capture sources that have been built in this context.
     pub cdc_tokens: HashMap<GlobalId, Rc<dyn Any>>,
+    /// If set, reductions keep at most this many bytes of state per worker in
+    /// memory, and spill the rest of their state to disk.
+    pub reduce_hot_set_size: Option<usize>,
+    /// The directory in which operators spill state to disk.
+    pub spill_directory: PathBuf,
     /// Dataflow local collections.
     pub collections: HashMap<P, (Collection<S, V, Diff>, Collection<S, DataflowError, Diff>)>,
     /// Dataflow local arrangements.
@@ -115,7 +121,11 @@ where
     S::Timestamp: Lattice + Refines<T>,
 {
     /// Creates a new empty Context.
-    pub fn for_dataflow(dataflow: &DataflowDesc, dataflow_id: usize) -> Self {
+    pub fn for_dataflow(
+        dataflow: &DataflowDesc,
+        dataflow_id: usize,
+        spill_directory: PathBuf,
+    ) -> Self {
         let as_of_frontier = dataflow
             .as_of
             .clone()
@@ -131,6 +141,8 @@ where
             local: HashMap::new(),
             trace: HashMap::new(),
             cdc_tokens: HashMap::new(),
+            reduce_hot_set_size: None,
+            spill_directory,
         }
     }
 
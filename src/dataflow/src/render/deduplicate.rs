@@ -0,0 +1,132 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use differential_dataflow::hashable::Hashable;
+use differential_dataflow::{AsCollection, Collection};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::Scope;
+
+use dataflow_types::DeduplicateKeep;
+use repr::{Diff, Row, Timestamp};
+
+/// Removes duplicate records from the append-only output of a source.
+///
+/// Two records are duplicates if they agree on the columns in `key_indices`.
+/// With [`DeduplicateKeep::First`], the first record for each key is emitted
+/// and every later one is discarded, so only the keys need to be remembered.
+/// With [`DeduplicateKeep::Last`], each new record for a key retracts the
+/// record it replaces.
+///
+/// Records for the same key that arrive at the same time are ordered by their
+/// contents, so that every worker and every restart makes the same choice.
+pub fn deduplicate<G>(
+    collection: &Collection<G, Row, Diff>,
+    key_indices: Vec<usize>,
+    keep: DeduplicateKeep,
+) -> Collection<G, Row, Diff>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let exchange_key_indices = key_indices.clone();
+    collection
+        .inner
+        .unary_frontier(
+            Exchange::new(move |(row, _time, _diff): &(Row, Timestamp, Diff)| {
+                let datums = row.unpack();
+                Row::pack(exchange_key_indices.iter().map(|i| datums[*i])).hashed()
+            }),
+            "Deduplicate",
+            |_cap, _info| {
+                // This is a map of (time) -> (capability, ((key) -> (record))).
+                // It is a BTreeMap so that records are emitted in time order,
+                // which is what determines the first and last record for a key.
+                let mut pending = BTreeMap::<_, (_, HashMap<Row, Row>)>::new();
+                // The keys that have already been emitted, for `KEEP FIRST`.
+                let mut seen_keys = HashSet::new();
+                // The record currently emitted for each key, for `KEEP LAST`.
+                let mut current_values = HashMap::new();
+
+                let mut vector = Vec::new();
+
+                move |input, output| {
+                    input.for_each(|cap, data| {
+                        data.swap(&mut vector);
+                        for (row, time, diff) in vector.drain(..) {
+                            // The source is append-only, so there is nothing to
+                            // deduplicate against for retractions.
+                            if diff <= 0 {
+                                continue;
+                            }
+                            let key = {
+                                let datums = row.unpack();
+                                Row::pack(key_indices.iter().map(|i| datums[*i]))
+                            };
+                            let records = &mut pending
+                                .entry(time)
+                                .or_insert_with(|| (cap.delayed(&time), HashMap::new()))
+                                .1;
+                            match records.entry(key) {
+                                Entry::Vacant(entry) => {
+                                    entry.insert(row);
+                                }
+                                Entry::Occupied(mut entry) => {
+                                    let replace = match keep {
+                                        DeduplicateKeep::First => row < *entry.get(),
+                                        DeduplicateKeep::Last => row > *entry.get(),
+                                    };
+                                    if replace {
+                                        entry.insert(row);
+                                    }
+                                }
+                            }
+                        }
+                    });
+
+                    let mut removed_times = Vec::new();
+                    for (time, (cap, records)) in pending.iter_mut() {
+                        if input.frontier.less_equal(time) {
+                            // Because this is a BTreeMap, all remaining times
+                            // are also incomplete.
+                            break;
+                        }
+                        removed_times.push(*time);
+                        let mut session = output.session(cap);
+                        for (key, row) in records.drain() {
+                            match keep {
+                                DeduplicateKeep::First => {
+                                    if seen_keys.insert(key) {
+                                        session.give((row, *time, 1));
+                                    }
+                                }
+                                DeduplicateKeep::Last => {
+                                    match current_values.insert(key, row.clone()) {
+                                        Some(old_row) if old_row == row => (),
+                                        Some(old_row) => {
+                                            session.give((old_row, *time, -1));
+                                            session.give((row, *time, 1));
+                                        }
+                                        None => session.give((row, *time, 1)),
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    // Discard entries and capabilities for complete times.
+                    for time in removed_times {
+                        pending.remove(&time);
+                    }
+                }
+            },
+        )
+        .as_collection()
+}
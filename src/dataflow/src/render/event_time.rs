@@ -0,0 +1,99 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::convert::TryFrom;
+
+use differential_dataflow::{AsCollection, Collection};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::Scope;
+
+use dataflow_types::EventTime;
+use repr::{Datum, Diff, Row, Timestamp};
+
+/// Retimestamps the records of a source by their event time.
+///
+/// The input is timestamped by the time at which each record was ingested, in
+/// milliseconds since the Unix epoch. Each record is moved to its event time,
+/// clamped to at most `clock_skew` after its ingestion time, unless the
+/// watermark has already passed that time, in which case the record is late
+/// and is moved to just after the watermark instead. Records whose event time
+/// is null are always late.
+///
+/// The watermark trails both the latest event time seen and the input
+/// frontier by `lateness`, so the output frontier advances with the input
+/// frontier even when no records arrive.
+pub fn assign_event_times<G>(
+    collection: &Collection<G, Row, Diff>,
+    event_time: &EventTime,
+) -> Collection<G, Row, Diff>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let column = event_time.column;
+    let lateness = Timestamp::try_from(event_time.lateness.as_millis()).unwrap_or(Timestamp::MAX);
+    let clock_skew =
+        Timestamp::try_from(event_time.clock_skew.as_millis()).unwrap_or(Timestamp::MAX);
+    collection
+        .inner
+        .unary_frontier(Pipeline, "AssignEventTimes", |cap, _info| {
+            // Every time at or before the watermark is complete, so the
+            // capability is held just after it.
+            let mut watermark = 0;
+            let mut cap = Some(cap);
+            let mut vector = Vec::new();
+
+            move |input, output| {
+                input.for_each(|_time, data| {
+                    data.swap(&mut vector);
+                    let cap = cap.as_ref().expect("input frontier is not empty");
+                    let mut session = output.session(cap);
+                    let mut max_event_time = 0;
+                    for (row, time, diff) in vector.drain(..) {
+                        let event_time = match row.iter().nth(column).unwrap() {
+                            Datum::Int32(ms) => Timestamp::try_from(ms).unwrap_or(0),
+                            Datum::Int64(ms) => Timestamp::try_from(ms).unwrap_or(0),
+                            Datum::Timestamp(ts) => {
+                                Timestamp::try_from(ts.timestamp_millis()).unwrap_or(0)
+                            }
+                            Datum::TimestampTz(ts) => {
+                                Timestamp::try_from(ts.timestamp_millis()).unwrap_or(0)
+                            }
+                            _ => 0,
+                        };
+                        let event_time = event_time.min(time.saturating_add(clock_skew));
+                        max_event_time = max_event_time.max(event_time);
+                        session.give((row, event_time.max(watermark + 1), diff));
+                    }
+                    watermark = watermark.max(max_event_time.saturating_sub(lateness));
+                });
+
+                match input.frontier().frontier().iter().min() {
+                    Some(frontier) => {
+                        // No future record can be ingested before the input
+                        // frontier, and records are assumed to be ingested
+                        // within `lateness` of their event time, allowing
+                        // for up to `clock_skew` between the two clocks.
+                        let complete = frontier
+                            .saturating_sub(1)
+                            .saturating_sub(lateness)
+                            .saturating_sub(clock_skew);
+                        watermark = watermark.max(complete);
+                        if let Some(cap) = &mut cap {
+                            if *cap.time() <= watermark {
+                                cap.downgrade(&(watermark + 1));
+                            }
+                        }
+                    }
+                    None => cap = None,
+                }
+            }
+        })
+        .as_collection()
+}
@@ -285,6 +285,24 @@ impl FilterPlan {
     pub fn non_temporal(&self) -> bool {
         self.lower_bounds.is_empty() && self.upper_bounds.is_empty()
     }
+
+    /// Returns, for each input column up to the last one that any predicate
+    /// references, whether a predicate references it.
+    pub fn demand(&self) -> Vec<bool> {
+        let mut demand = Vec::new();
+        let exprs = self
+            .normal
+            .iter()
+            .chain(self.lower_bounds.iter())
+            .chain(self.upper_bounds.iter());
+        for column in exprs.flat_map(|expr| expr.support()) {
+            if column >= demand.len() {
+                demand.resize(column + 1, false);
+            }
+            demand[column] = true;
+        }
+        demand
+    }
 }
 
 impl<G, T> Context<G, MirRelationExpr, Row, T>
@@ -318,8 +336,10 @@ where
 
             let (oks, errs) = ok_collection.inner.flat_map_fallible({
                 let mut datums = DatumVec::new();
+                // Decode only the columns that the predicates reference.
+                let demand = filter_plan.demand();
                 move |(data, time, diff)| {
-                    let mut datums_local = datums.borrow_with(&data);
+                    let mut datums_local = datums.borrow_with_demanded(&data, &demand);
                     let times_diffs = filter_plan.evaluate(&mut datums_local, time, diff);
                     // Drop to release borrow on `data` and allow it to move into the closure.
                     drop(datums_local);
@@ -569,7 +569,7 @@ where
                     .iter()
                     .map(|e| e.eval(&datums_local, &temp_storage)),
             )?;
-            let row_key = row_packer.finish_and_reuse();
+            let row_key = row_packer.finish_and_reuse().normalize_zeros();
             // Explicit drop to release borrow on `row` so that it can be returned.
             drop(datums_local);
             Ok((row, row_key))
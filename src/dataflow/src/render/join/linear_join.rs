@@ -184,7 +184,8 @@ where
                                     prev_keys
                                         .iter()
                                         .map(|e| e.eval(&datums_local, &temp_storage)),
-                                )?;
+                                )?
+                                .normalize_zeros();
                                 // Explicit drop here to allow `row` to be returned.
                                 drop(datums_local);
                                 // TODO(mcsherry): We could remove any columns used only for `key`.
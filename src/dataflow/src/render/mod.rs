@@ -103,11 +103,13 @@
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
 use std::iter;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::rc::Weak;
 
 use differential_dataflow::AsCollection;
 use timely::communication::Allocate;
+use timely::dataflow::operators::probe::Handle as ProbeHandle;
 use timely::dataflow::operators::to_stream::ToStream;
 use timely::dataflow::scopes::Child;
 use timely::dataflow::Scope;
@@ -123,15 +125,21 @@ use repr::{RelationType, Row, RowArena, Timestamp};
 use crate::arrangement::manager::{TraceBundle, TraceManager};
 use crate::operator::CollectionExt;
 use crate::render::context::{ArrangementFlavor, Context};
-use crate::server::{CacheMessage, LocalInput, TimestampDataUpdates};
+use crate::server::{
+    CacheMessage, LocalInput, SourceConnectorUpdates, SourceEncodingUpdates, SourceEventTimes,
+    TimestampDataUpdates,
+};
 use crate::source::SourceToken;
 
 mod arrange_by;
 mod context;
+mod deduplicate;
+mod event_time;
 pub(crate) mod filter;
 mod flat_map;
 mod join;
 mod reduce;
+mod reduce_spill;
 mod sinks;
 mod sources;
 mod threshold;
@@ -146,13 +154,24 @@ pub struct RenderState {
     pub local_inputs: HashMap<GlobalId, LocalInput>,
     /// Handles to external sources, keyed by ID.
     pub ts_source_mapping: HashMap<GlobalId, Vec<Weak<Option<SourceToken>>>>,
+    /// Probes of the output frontier of each instance of each external source,
+    /// keyed by source ID, alongside the token that keeps the instance alive.
+    pub source_probes: HashMap<GlobalId, Vec<(Weak<Option<SourceToken>>, ProbeHandle<Timestamp>)>>,
     /// Timestamp data updates for each source.
     pub ts_histories: TimestampDataUpdates,
+    /// Upstream event times observed by sources since the last report.
+    pub event_times: SourceEventTimes,
+    /// Altered connection options for each source.
+    pub connector_updates: SourceConnectorUpdates,
+    /// Altered Protobuf descriptors for each source.
+    pub encoding_updates: SourceEncodingUpdates,
     /// Tokens that should be dropped when a dataflow is dropped to clean up
     /// associated state.
     pub dataflow_tokens: HashMap<GlobalId, Box<dyn Any>>,
     /// Sender to give data to be cached.
     pub caching_tx: Option<mpsc::UnboundedSender<CacheMessage>>,
+    /// The directory in which operators spill state to disk.
+    pub spill_directory: PathBuf,
 }
 
 /// Build a dataflow from a description.
@@ -171,7 +190,11 @@ pub fn build_dataflow<A: Allocate>(
         // so that other similar uses (e.g. with iterative scopes) do not require weird
         // alternate type signatures.
         scope.clone().region(|region| {
-            let mut context = Context::for_dataflow(&dataflow, scope.addr().into_element());
+            let mut context = Context::for_dataflow(
+                &dataflow,
+                scope.addr().into_element(),
+                render_state.spill_directory.clone(),
+            );
 
             assert!(
                 !dataflow
@@ -198,9 +221,22 @@ pub fn build_dataflow<A: Allocate>(
                 context.import_index(render_state, scope, region, *idx_id, idx);
             }
 
+            // The objects that the exported indexes arrange are refreshed at
+            // the dataflow's refresh interval, if it has one.
+            let refreshed: HashSet<_> = dataflow
+                .index_exports
+                .iter()
+                .map(|(_idx_id, idx, _typ)| idx.on_id)
+                .collect();
+
             // Build declared objects.
             for object in &dataflow.objects_to_build {
-                context.build_object(region, object);
+                let refresh_interval = if refreshed.contains(&object.id) {
+                    dataflow.refresh_interval
+                } else {
+                    None
+                };
+                context.build_object(region, object, refresh_interval);
             }
 
             // Export declared indexes.
@@ -262,13 +298,34 @@ where
         }
     }
 
-    fn build_object(&mut self, scope: &mut Child<'g, G, G::Timestamp>, object: &BuildDesc) {
+    /// Builds `object`.
+    ///
+    /// If `refresh_interval` is provided, the times of the updates to the
+    /// object are rounded up to a multiple of the interval, so that anything
+    /// arranged from the object changes at most once per interval. The
+    /// interval is fixed for the lifetime of the dataflow, so that every
+    /// worker rounds every update in the same way.
+    fn build_object(
+        &mut self,
+        scope: &mut Child<'g, G, G::Timestamp>,
+        object: &BuildDesc,
+        refresh_interval: Option<Timestamp>,
+    ) {
+        self.reduce_hot_set_size = object.reduce_hot_set_size;
         self.ensure_rendered(object.relation_expr.as_ref(), scope, scope.index());
         if let Some(typ) = &object.typ {
-            self.clone_from_to(
-                &object.relation_expr.as_ref(),
-                &MirRelationExpr::global_get(object.id, typ.clone()),
-            );
+            let get_expr = MirRelationExpr::global_get(object.id, typ.clone());
+            match refresh_interval {
+                None => self.clone_from_to(&object.relation_expr.as_ref(), &get_expr),
+                Some(interval) => {
+                    let (oks, errs) = self
+                        .collection(&object.relation_expr.as_ref())
+                        .expect("object rendered");
+                    let oks = oks.delay(move |time| round_up(*time, interval));
+                    let errs = errs.delay(move |time| round_up(*time, interval));
+                    self.collections.insert(get_expr, (oks, errs));
+                }
+            }
         } else {
             self.render_arrangeby(&object.relation_expr.as_ref(), Some(&object.id.to_string()));
             // Under the premise that this is always an arrange_by aroung a global get,
@@ -681,6 +738,15 @@ where
     }
 }
 
+/// Rounds `time` up to the next multiple of `interval`.
+fn round_up(time: Timestamp, interval: Timestamp) -> Timestamp {
+    if interval > 0 && time % interval != 0 {
+        time.saturating_add(interval - time % interval)
+    } else {
+        time
+    }
+}
+
 /// A re-useable vector of `Datum` with varying lifetimes.
 ///
 /// This type is meant to allow us to recycle an underlying allocation with
@@ -719,6 +785,32 @@ pub mod datum_vec {
             borrow.extend(row.iter());
             borrow
         }
+        /// Borrow an instance with a specific lifetime, and pre-populate with
+        /// the datums of a `Row` at the positions for which `demand` is true.
+        ///
+        /// The datums at other positions are skipped without being decoded, and
+        /// are replaced by `Datum::Dummy`. The result is truncated after the last
+        /// demanded position.
+        pub fn borrow_with_demanded<'a>(
+            &'a mut self,
+            row: &'a Row,
+            demand: &[bool],
+        ) -> DatumVecBorrow<'a> {
+            let mut borrow = self.borrow();
+            let mut iter = row.iter();
+            let mut skipped = 0;
+            for demanded in demand {
+                if *demanded {
+                    let datum = iter.nth(skipped).expect("row shorter than demand");
+                    borrow.extend(std::iter::repeat(Datum::Dummy).take(skipped));
+                    borrow.push(datum);
+                    skipped = 0;
+                } else {
+                    skipped += 1;
+                }
+            }
+            borrow
+        }
     }
 
     /// A borrowed allocation of `Datum` with a specific lifetime.
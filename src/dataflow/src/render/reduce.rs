@@ -61,6 +61,7 @@
 //! return the output arrangement directly and avoid the extra collation arrangement.
 
 use std::collections::BTreeMap;
+use std::fmt;
 
 use differential_dataflow::collection::AsCollection;
 use differential_dataflow::difference::DiffVector;
@@ -74,15 +75,17 @@ use differential_dataflow::trace::implementations::ord::OrdValSpine;
 use differential_dataflow::Collection;
 use serde::{Deserialize, Serialize};
 use timely::dataflow::Scope;
+use timely::order::TotalOrder;
 use timely::progress::{timestamp::Refines, Timestamp};
 
 use dataflow_types::DataflowError;
 use expr::{AggregateExpr, AggregateFunc, MirRelationExpr};
-use repr::{Datum, DatumList, Row, RowArena, RowPacker};
+use repr::{Datum, DatumList, Diff, Row, RowArena, RowOffsets, RowPacker};
 
 use super::context::Context;
 use crate::render::context::Arrangement;
 use crate::render::datum_vec::DatumVec;
+use crate::render::reduce_spill::build_spilling_reduce;
 
 /// This enum represents the three potential types of aggregations.
 #[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
@@ -514,7 +517,12 @@ where
 {
     /// Renders a `MirRelationExpr::Reduce` using various non-obvious techniques to
     /// minimize worst-case incremental update times and memory footprint.
-    pub fn render_reduce(&mut self, relation_expr: &MirRelationExpr) {
+    ///
+    /// Reductions can only spill to disk in scopes with totally ordered times.
+    pub fn render_reduce(&mut self, relation_expr: &MirRelationExpr)
+    where
+        G::Timestamp: TotalOrder,
+    {
         if let MirRelationExpr::Reduce {
             input,
             group_key,
@@ -595,7 +603,9 @@ where
                             &mut row_packer,
                         ) {
                             Err(e) => return Some(Err(DataflowError::from(e))),
-                            Ok(key) => key.expect("Row expected as no predicate was used"),
+                            Ok(key) => key
+                                .expect("Row expected as no predicate was used")
+                                .normalize_zeros(),
                         };
                         // Evaluate the value expressions.
                         // The prior evaluation may have left additional columns we should delete.
@@ -633,10 +643,29 @@ where
             let ok_input = ok.as_collection();
             err_input = err.as_collection().concat(&err_input);
 
-            // First, let's plan out what we are going to do with this reduce
-            let plan =
-                ReducePlan::create_from(aggregates.clone(), *monotonic, *expected_group_size);
-            let arrangement = plan.render(ok_input);
+            let arrangement = match self.reduce_hot_set_size {
+                // The view asked for its reduction state to spill to disk,
+                // which a single operator does for all aggregations at once.
+                Some(hot_set_size) => {
+                    let (arrangement, errs) = build_spilling_reduce(
+                        ok_input,
+                        aggregates.clone(),
+                        hot_set_size,
+                        &self.spill_directory,
+                    );
+                    err_input = err_input.concat(&errs);
+                    arrangement
+                }
+                None => {
+                    // First, let's plan out what we are going to do with this reduce
+                    let plan = ReducePlan::create_from(
+                        aggregates.clone(),
+                        *monotonic,
+                        *expected_group_size,
+                    );
+                    plan.render(ok_input)
+                }
+            };
             let index = (0..group_key.len()).collect::<Vec<_>>();
             self.set_local_columns(
                 relation_expr,
@@ -831,23 +860,15 @@ where
 
     // If `distinct` is set, we restrict ourselves to the distinct `(key, val)`.
     if distinct {
-        partial = partial.distinct();
+        partial = partial
+            .map(|(key, val)| (key, val.normalize_zeros()))
+            .distinct();
     }
 
     partial.reduce_abelian::<_, OrdValSpine<_, _, _, _>>("ReduceInaccumulable", {
         let mut row_packer = RowPacker::new();
         move |key, source, target| {
-            // Negative counts would be surprising, but until we are 100% certain we wont
-            // see them, we should report when we do. We may want to bake even more info
-            // in here in the future.
-            if source.iter().any(|(_val, cnt)| cnt < &0) {
-                // XXX: This reports user data, which we perhaps should not do!
-                for (val, cnt) in source.iter() {
-                    if cnt < &0 {
-                        log::error!("[customer-data] Negative accumulation in ReduceInaccumulable: {:?} with count {:?}", val, cnt);
-                    }
-                }
-            } else {
+            if !report_negative_accumulations("ReduceInaccumulable", source) {
                 // We respect the multiplicity here (unlike in hierarchical aggregation)
                 // because we don't know that the aggregation method is not sensitive
                 // to the number of records.
@@ -864,6 +885,33 @@ where
     })
 }
 
+/// Logs the values in `source` whose counts are negative, on behalf of the
+/// operator `name`, and returns whether there were any.
+///
+/// Negative counts would be surprising, but until we are 100% certain we wont
+/// see them, we should report when we do. We may want to bake even more info
+/// in here in the future.
+pub(crate) fn report_negative_accumulations<V>(name: &str, source: &[(V, Diff)]) -> bool
+where
+    V: fmt::Debug,
+{
+    if !source.iter().any(|(_val, cnt)| *cnt < 0) {
+        return false;
+    }
+    // XXX: This reports user data, which we perhaps should not do!
+    for (val, cnt) in source {
+        if *cnt < 0 {
+            log::error!(
+                "[customer-data] Negative accumulation in {}: {:?} with count {:?}",
+                name,
+                val,
+                cnt
+            );
+        }
+    }
+    true
+}
+
 /// Build the dataflow to compute and arrange multiple hierarchical aggregations
 /// on non-monotonic inputs.
 ///
@@ -916,23 +964,15 @@ where
     partial.reduce_abelian::<_, OrdValSpine<_, _, _, _>>("ReduceMinsMaxes", {
         let mut row_packer = RowPacker::new();
         move |key, source, target| {
-            // Negative counts would be surprising, but until we are 100% certain we wont
-            // see them, we should report when we do. We may want to bake even more info
-            // in here in the future.
-            if source.iter().any(|(_val, cnt)| cnt < &0) {
-                // XXX: This reports user data, which we perhaps should not do!
-                for (val, cnt) in source.iter() {
-                    if cnt < &0 {
-                        log::error!("[customer-data] Negative accumulation in ReduceMinsMaxes: {:?} with count {:?}", val, cnt);
-                    }
-                }
-            } else {
+            if !report_negative_accumulations("ReduceMinsMaxes", source) {
                 // Pack the value with the key as the result.
                 if prepend_key {
                     row_packer.extend(key.iter());
                 }
                 for (aggr_index, func) in aggr_funcs.iter().enumerate() {
-                    let iter = source.iter().map(|(values, _cnt)| values[aggr_index].iter().next().unwrap());
+                    let iter = source
+                        .iter()
+                        .map(|(values, _cnt)| values[aggr_index].iter().next().unwrap());
                     row_packer.push(func.eval(iter, &RowArena::new()));
                 }
                 target.push((row_packer.finish_and_reuse(), 1));
@@ -1148,20 +1188,13 @@ where
         .map(|(d, t, r)| (d, t, r as i128))
         .as_collection()
         .explode({
+            let mut offsets = RowOffsets::new();
             move |(key, row)| {
                 let mut diffs = vec![0i128; diffs_len];
-                // Try to unpack only the datums we need. Unfortunately, since we
-                // can't random access into a Row, we have to iterate through one by one.
-                // TODO: Even though we don't have random access, we could still avoid unpacking
-                // everything that we don't care about, and it might be worth it to extend the
-                // Row API to do that.
-                let mut row_iter = row.iter().enumerate();
+                // Index the row so that only the datums we need are decoded.
+                let indexed = offsets.index(&row);
                 for (accumulable_index, datum_index, aggr) in simple_aggrs.iter() {
-                    let mut datum = row_iter.next().unwrap();
-                    while datum_index != &datum.0 {
-                        datum = row_iter.next().unwrap();
-                    }
-                    let datum = datum.1;
+                    let datum = indexed.get(*datum_index);
                     let (agg1, agg2) = datum_aggr_values(datum, &aggr.func);
                     diffs[3 * accumulable_index] = 1i128;
                     diffs[3 * accumulable_index + 1] = agg1;
@@ -1179,7 +1212,7 @@ where
             .map(move |(key, row)| {
                 let value = row.iter().nth(datum_index).unwrap();
                 packer.push(value);
-                (key, packer.finish_and_reuse())
+                (key, packer.finish_and_reuse().normalize_zeros())
             })
             .distinct()
             .inner
@@ -1345,7 +1378,9 @@ fn reduction_type(func: &AggregateFunc) -> ReductionType {
         | AggregateFunc::MinDate
         | AggregateFunc::MinTimestamp
         | AggregateFunc::MinTimestampTz => ReductionType::Hierarchical,
-        AggregateFunc::JsonbAgg => ReductionType::Basic,
+        AggregateFunc::JsonbAgg | AggregateFunc::RangeAgg | AggregateFunc::ArrayAgg { .. } => {
+            ReductionType::Basic
+        }
     }
 }
 
@@ -1458,7 +1493,9 @@ pub mod monoids {
             | AggregateFunc::Any
             | AggregateFunc::All
             | AggregateFunc::Dummy
-            | AggregateFunc::JsonbAgg => None,
+            | AggregateFunc::JsonbAgg
+            | AggregateFunc::RangeAgg
+            | AggregateFunc::ArrayAgg { .. } => None,
         }
     }
 }
@@ -0,0 +1,480 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Reductions whose state spills to disk.
+//!
+//! The reductions in [`super::reduce`] keep the state of every group in
+//! memory, which is a frequent cause of OOMs for high-cardinality `GROUP BY`s.
+//! The operator here instead keeps the state of only the most recently used
+//! groups in memory, up to a configured number of bytes, and stores the state
+//! of the remaining groups in files on disk, from which it is read back
+//! whenever the group changes.
+//!
+//! The operator trades speed for memory: it computes every aggregation from
+//! the group's full multiset of values, and so performs none of the
+//! accumulable or hierarchical optimizations of the in-memory reductions.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::iter;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use differential_dataflow::consolidation::consolidate;
+use differential_dataflow::hashable::Hashable;
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::operators::arrange::arrangement::Arrange;
+use differential_dataflow::trace::implementations::ord::OrdValSpine;
+use differential_dataflow::{AsCollection, Collection};
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::operators::ok_err::OkErr;
+use timely::dataflow::operators::Capability;
+use timely::dataflow::Scope;
+use timely::order::TotalOrder;
+use uuid::Uuid;
+
+use dataflow_types::DataflowError;
+use expr::AggregateExpr;
+use repr::{Datum, Diff, Row, RowArena, RowPacker};
+
+use crate::render::context::Arrangement;
+use crate::render::reduce::report_negative_accumulations;
+
+/// The number of files across which the spilled groups of one operator are
+/// spread.
+const SPILL_BUCKETS: u64 = 256;
+
+/// The size below which a bucket's file is never compacted.
+const MIN_COMPACTION_BYTES: usize = 1 << 16;
+
+/// The values in a group, with their multiplicities.
+type Group = Vec<(Row, Diff)>;
+
+/// Build the dataflow to compute and arrange all of the aggregations in
+/// `aggregates`, keeping the state of at most `hot_set_size` bytes of groups
+/// per worker in memory, and spilling the rest to `spill_directory`.
+///
+/// The arrangement presents the key followed by the aggregates as its values,
+/// like the arrangements of the in-memory reductions. If the operator fails to
+/// write or read its spilled state, it reports the failure in the returned
+/// error collection and stops maintaining the arrangement, whose contents are
+/// no longer correct.
+///
+/// Updates are applied once their time is complete, in timestamp order, which
+/// is why the times must be totally ordered.
+pub fn build_spilling_reduce<G>(
+    collection: Collection<G, (Row, Row)>,
+    aggregates: Vec<AggregateExpr>,
+    hot_set_size: usize,
+    spill_directory: &Path,
+) -> (Arrangement<G, Row>, Collection<G, DataflowError>)
+where
+    G: Scope,
+    G::Timestamp: Lattice + TotalOrder,
+{
+    let dir = spill_directory.join(format!("reduce-{}", Uuid::new_v4()));
+    let (oks, errs) = collection
+        .inner
+        .unary_frontier(
+            Exchange::new(
+                |((key, _val), _time, _diff): &((Row, Row), G::Timestamp, Diff)| key.hashed(),
+            ),
+            "ReduceSpilling",
+            move |_cap, _info| {
+                let mut pending =
+                    BTreeMap::<G::Timestamp, (Capability<G::Timestamp>, Vec<_>)>::new();
+                let mut state = Some(SpillingState::new(hot_set_size, dir));
+                let mut vector = Vec::new();
+
+                move |input, output| {
+                    input.for_each(|cap, data| {
+                        data.swap(&mut vector);
+                        // Once the state is lost, there is nothing to apply
+                        // the updates to.
+                        if state.is_none() {
+                            vector.clear();
+                            return;
+                        }
+                        for (update, time, diff) in vector.drain(..) {
+                            pending
+                                .entry(time.clone())
+                                .or_insert_with(|| (cap.delayed(&time), Vec::new()))
+                                .1
+                                .push((update, diff));
+                        }
+                    });
+
+                    // Apply the updates at each complete time, in order.
+                    while let Some(time) = pending.keys().next().cloned() {
+                        if input.frontier().less_equal(&time) {
+                            break;
+                        }
+                        let (cap, updates) = pending.remove(&time).expect("time is pending");
+                        let mut session = output.session(&cap);
+                        let result = match &mut state {
+                            Some(state) => state.apply(updates, &aggregates),
+                            None => continue,
+                        };
+                        match result {
+                            Ok(changes) => {
+                                for (update, diff) in changes {
+                                    session.give((Ok(update), time.clone(), diff));
+                                }
+                            }
+                            Err(e) => {
+                                // Dropping the state releases its memory and
+                                // removes its files.
+                                state = None;
+                                pending.clear();
+                                session.give((Err(DataflowError::SpillError(e)), time, 1));
+                            }
+                        }
+                    }
+                }
+            },
+        )
+        .ok_err(|(x, t, d)| match x {
+            Ok(x) => Ok((x, t, d)),
+            Err(x) => Err((x, t, d)),
+        });
+    let arrangement = oks
+        .as_collection()
+        .arrange_named::<OrdValSpine<_, _, _, _>>("ArrangeReduceSpilling");
+    (arrangement, errs.as_collection())
+}
+
+/// The state of the groups on one worker.
+struct SpillingState {
+    /// The groups whose state is in memory.
+    hot: HashMap<Row, HotGroup>,
+    /// The approximate number of bytes that the groups in `hot` occupy.
+    hot_bytes: usize,
+    hot_set_size: usize,
+    /// Counts group accesses, to find the least recently used groups.
+    clock: u64,
+    /// The spilled groups of the bucket read most recently in the current
+    /// call to `apply`, which have not yet been brought into memory.
+    loaded: Option<(u64, HashMap<Row, Group>)>,
+    store: SpillStore,
+    row_packer: RowPacker,
+}
+
+/// A group whose state is in memory.
+struct HotGroup {
+    values: Group,
+    last_used: u64,
+    /// Whether the group has changed since it was read from the store.
+    dirty: bool,
+}
+
+impl SpillingState {
+    fn new(hot_set_size: usize, dir: PathBuf) -> SpillingState {
+        SpillingState {
+            hot: HashMap::new(),
+            hot_bytes: 0,
+            hot_set_size,
+            clock: 0,
+            loaded: None,
+            store: SpillStore::new(dir),
+            row_packer: RowPacker::new(),
+        }
+    }
+
+    /// Applies `updates` to the groups, returning the resulting changes to the
+    /// output.
+    ///
+    /// The groups are visited bucket by bucket, so that each bucket of the
+    /// store is read at most once, and groups are evicted as soon as the hot
+    /// set outgrows its size.
+    fn apply(
+        &mut self,
+        mut updates: Vec<((Row, Row), Diff)>,
+        aggregates: &[AggregateExpr],
+    ) -> Result<Vec<((Row, Row), Diff)>, String> {
+        updates.sort();
+        // The sort is stable, and so leaves the updates to each group
+        // together.
+        updates.sort_by_cached_key(|((key, _val), _diff)| bucket(key));
+
+        let mut changes = vec![];
+        let mut updates = updates.into_iter().peekable();
+        while let Some(((key, val), diff)) = updates.next() {
+            self.clock += 1;
+            let mut group = self.take_group(&key)?;
+            let old = aggregate(&key, &group.values, aggregates, &mut self.row_packer);
+            group.values.push((val, diff));
+            while updates.peek().map_or(false, |((k, _), _)| *k == key) {
+                let ((_, val), diff) = updates.next().expect("peeked");
+                group.values.push((val, diff));
+            }
+            consolidate(&mut group.values);
+            group.last_used = self.clock;
+            group.dirty = true;
+            let new = aggregate(&key, &group.values, aggregates, &mut self.row_packer);
+            self.hot_bytes += group_size(&key, &group.values);
+            self.hot.insert(key.clone(), group);
+            if old != new {
+                if let Some(old) = old {
+                    changes.push(((key.clone(), old), -1));
+                }
+                if let Some(new) = new {
+                    changes.push(((key, new), 1));
+                }
+            }
+
+            if self.hot_bytes > self.hot_set_size {
+                self.evict()?;
+            }
+        }
+        self.loaded = None;
+        Ok(changes)
+    }
+
+    /// Removes the group `key` from the hot set, reading it from the store if
+    /// it is not in memory.
+    fn take_group(&mut self, key: &Row) -> Result<HotGroup, String> {
+        if let Some(group) = self.hot.remove(key) {
+            self.hot_bytes -= group_size(key, &group.values);
+            return Ok(group);
+        }
+        let bucket = bucket(key);
+        if self.loaded.as_ref().map(|(b, _)| *b) != Some(bucket) {
+            self.loaded = Some((bucket, self.store.read(bucket)?));
+        }
+        let (_, spilled) = self.loaded.as_mut().expect("bucket is loaded");
+        Ok(HotGroup {
+            values: spilled.remove(key).unwrap_or_default(),
+            last_used: 0,
+            dirty: false,
+        })
+    }
+
+    /// Spills the least recently used groups to the store, until the hot set
+    /// occupies at most three quarters of its size.
+    fn evict(&mut self) -> Result<(), String> {
+        let target = self.hot_set_size - self.hot_set_size / 4;
+        let mut by_age: Vec<_> = self
+            .hot
+            .iter()
+            .map(|(key, group)| (group.last_used, key.clone()))
+            .collect();
+        by_age.sort();
+
+        let mut evicted = BTreeMap::<u64, Vec<(Row, Group)>>::new();
+        for (_, key) in by_age {
+            if self.hot_bytes <= target {
+                break;
+            }
+            let group = self.hot.remove(&key).expect("group is hot");
+            self.hot_bytes -= group_size(&key, &group.values);
+            // Groups that are unchanged since they were read are already
+            // stored as they are.
+            if group.dirty {
+                evicted
+                    .entry(bucket(&key))
+                    .or_default()
+                    .push((key, group.values));
+            }
+        }
+        for (bucket, groups) in evicted {
+            self.store.append(bucket, &groups)?;
+            // Keep the loaded bucket in step with the store, as its groups may
+            // yet be brought into memory.
+            if let Some((loaded_bucket, spilled)) = &mut self.loaded {
+                if *loaded_bucket == bucket {
+                    for (key, values) in groups {
+                        spilled.insert(key, values);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Estimates the number of bytes that the group `key` occupies in memory.
+fn group_size(key: &Row, values: &[(Row, Diff)]) -> usize {
+    let values: usize = values
+        .iter()
+        .map(|(val, _diff)| mem::size_of::<(Row, Diff)>() + val.byte_len())
+        .sum();
+    mem::size_of::<(Row, HotGroup)>() + key.byte_len() + values
+}
+
+/// Computes the output row for the group `key`, or `None` if the group is
+/// empty.
+fn aggregate(
+    key: &Row,
+    values: &[(Row, Diff)],
+    aggregates: &[AggregateExpr],
+    row_packer: &mut RowPacker,
+) -> Option<Row> {
+    if values.is_empty() {
+        return None;
+    }
+    if report_negative_accumulations("ReduceSpilling", values) {
+        return None;
+    }
+    row_packer.extend(key.iter());
+    for (index, aggr) in aggregates.iter().enumerate() {
+        let temp_storage = RowArena::new();
+        let datums = values.iter().flat_map(|(val, diff)| {
+            iter::repeat(val.iter().nth(index).unwrap()).take(*diff as usize)
+        });
+        let datum = if aggr.distinct {
+            let mut datums: Vec<Datum> = datums.collect();
+            datums.sort();
+            datums.dedup();
+            aggr.func.eval(datums, &temp_storage)
+        } else {
+            aggr.func.eval(datums, &temp_storage)
+        };
+        row_packer.push(datum);
+    }
+    Some(row_packer.finish_and_reuse())
+}
+
+/// Determines the bucket of the store in which the group `key` is spilled.
+fn bucket(key: &Row) -> u64 {
+    // The low bits of the hash determine the worker that owns the group, so
+    // use the high bits to spread the worker's groups across buckets.
+    (key.hashed() >> 32) % SPILL_BUCKETS
+}
+
+/// The spilled groups of one operator on one worker.
+///
+/// Each bucket of groups is stored in its own file, as a log of the states
+/// that its groups were spilled in. Evicting groups appends their new states
+/// to the log, and reading the bucket replays the log, in which the last state
+/// of each group wins and an empty state removes the group. Reading a bucket
+/// whose log has grown to more than twice the size of its groups rewrites the
+/// log with only their last states.
+///
+/// The files are removed when the operator shuts down.
+struct SpillStore {
+    dir: PathBuf,
+}
+
+impl SpillStore {
+    fn new(dir: PathBuf) -> SpillStore {
+        SpillStore { dir }
+    }
+
+    fn path(&self, bucket: u64) -> PathBuf {
+        self.dir.join(bucket.to_string())
+    }
+
+    /// Reads the groups spilled to `bucket`.
+    fn read(&self, bucket: u64) -> Result<HashMap<Row, Group>, String> {
+        let path = self.path(bucket);
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(e) => {
+                return Err(format!(
+                    "reading spilled reduce state {}: {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+
+        // The last record of each group, by the group's key.
+        let mut records = HashMap::new();
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let record = decode_record(&bytes[offset..]).ok_or_else(|| {
+                format!(
+                    "decoding spilled reduce state {}: truncated record at offset {}",
+                    path.display(),
+                    offset
+                )
+            })?;
+            let (key, values): (Row, Group) = bincode::deserialize(record)
+                .map_err(|e| format!("decoding spilled reduce state {}: {}", path.display(), e))?;
+            let start = offset;
+            offset += RECORD_HEADER_LEN + record.len();
+            records.insert(key, (values, start..offset));
+        }
+        records.retain(|_key, (values, _range)| !values.is_empty());
+
+        let live_bytes: usize = records.values().map(|(_values, range)| range.len()).sum();
+        if bytes.len() > MIN_COMPACTION_BYTES && bytes.len() > 2 * live_bytes {
+            let mut compacted = Vec::with_capacity(live_bytes);
+            for (_values, range) in records.values() {
+                compacted.extend(&bytes[range.clone()]);
+            }
+            self.rewrite(&path, &compacted).map_err(|e| {
+                format!("compacting spilled reduce state {}: {}", path.display(), e)
+            })?;
+        }
+
+        Ok(records
+            .into_iter()
+            .map(|(key, (values, _range))| (key, values))
+            .collect())
+    }
+
+    /// Appends the states of `groups` to the log of `bucket`.
+    fn append(&self, bucket: u64, groups: &[(Row, Group)]) -> Result<(), String> {
+        let path = self.path(bucket);
+        let mut bytes = vec![];
+        for group in groups {
+            let record = bincode::serialize(group).expect("serializing rows cannot fail");
+            bytes.extend(&(record.len() as u64).to_le_bytes());
+            bytes.extend(record);
+        }
+        fs::create_dir_all(&self.dir)
+            .and_then(|()| {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)?
+                    .write_all(&bytes)
+            })
+            .map_err(|e| format!("writing spilled reduce state {}: {}", path.display(), e))
+    }
+
+    /// Atomically replaces the contents of the file at `path` with `bytes`.
+    fn rewrite(&self, path: &Path, bytes: &[u8]) -> Result<(), io::Error> {
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, bytes)?;
+        fs::rename(&tmp_path, path)
+    }
+}
+
+/// The length of the header that precedes each record in a bucket's log.
+const RECORD_HEADER_LEN: usize = mem::size_of::<u64>();
+
+/// Returns the record at the start of `bytes`, or `None` if it is truncated.
+fn decode_record(bytes: &[u8]) -> Option<&[u8]> {
+    if bytes.len() < RECORD_HEADER_LEN {
+        return None;
+    }
+    let mut len = [0; RECORD_HEADER_LEN];
+    len.copy_from_slice(&bytes[..RECORD_HEADER_LEN]);
+    let len = u64::from_le_bytes(len) as usize;
+    bytes[RECORD_HEADER_LEN..].get(..len)
+}
+
+impl Drop for SpillStore {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            if e.kind() != ErrorKind::NotFound {
+                log::warn!(
+                    "unable to remove spilled reduce state {}: {}",
+                    self.dir.display(),
+                    e
+                );
+            }
+        }
+    }
+}
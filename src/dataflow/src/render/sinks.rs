@@ -116,7 +116,72 @@ where
                 });
                 collection
             }
-            SinkEnvelope::Tail { emit_progress } => keyed
+            SinkEnvelope::Tail {
+                emit_progress,
+                envelope: TailEnvelope::Upsert,
+            } => {
+                let key_indices = sink
+                    .connector
+                    .get_key_indices()
+                    .expect("tail with upsert envelope must have a key")
+                    .to_vec();
+                let combined = combine_at_timestamp(keyed.arrange_by_key().stream);
+                combined
+                    .inner
+                    .flat_map(move |((_k, diff_pairs), time, _diff)| {
+                        let mut rp = RowPacker::new();
+                        let key_indices = key_indices.clone();
+                        diff_pairs.into_iter().map(move |diff_pair| {
+                            rp.push(Datum::Decimal(Significand::new(i128::from(time))));
+                            if emit_progress {
+                                rp.push(Datum::False);
+                            }
+                            match (diff_pair.before, diff_pair.after) {
+                                (_, Some(after)) => {
+                                    rp.push(Datum::String("upsert"));
+                                    rp.extend_by_row(&after);
+                                }
+                                (Some(before), None) => {
+                                    // A deletion reports only the key.
+                                    rp.push(Datum::String("delete"));
+                                    rp.extend(before.iter().enumerate().map(|(i, datum)| {
+                                        if key_indices.contains(&i) {
+                                            datum
+                                        } else {
+                                            Datum::Null
+                                        }
+                                    }));
+                                }
+                                (None, None) => unreachable!("diff pairs are never empty"),
+                            }
+                            ((None, Some(rp.finish_and_reuse())), time, 1)
+                        })
+                    })
+                    .as_collection()
+            }
+            SinkEnvelope::Tail {
+                emit_progress,
+                envelope: TailEnvelope::Debezium,
+            } => {
+                let combined = combine_at_timestamp(keyed.arrange_by_key().stream);
+                combined
+                    .inner
+                    .flat_map(move |((_k, diff_pairs), time, _diff)| {
+                        let mut rp = RowPacker::new();
+                        diff_pairs.into_iter().map(move |diff_pair| {
+                            rp.push(Datum::Decimal(Significand::new(i128::from(time))));
+                            if emit_progress {
+                                rp.push(Datum::False);
+                            }
+                            ((None, Some(dbz_format(&mut rp, diff_pair))), time, 1)
+                        })
+                    })
+                    .as_collection()
+            }
+            SinkEnvelope::Tail {
+                emit_progress,
+                envelope: TailEnvelope::Diffs,
+            } => keyed
                 .consolidate()
                 .inner
                 .map({
@@ -14,6 +14,7 @@ use std::rc::Rc;
 use differential_dataflow::hashable::Hashable;
 use differential_dataflow::lattice::Lattice;
 use differential_dataflow::{collection, AsCollection, Collection};
+use timely::dataflow::operators::probe::{Handle as ProbeHandle, Probe};
 use timely::dataflow::operators::unordered_input::UnorderedInput;
 use timely::dataflow::operators::Map;
 use timely::dataflow::scopes::Child;
@@ -74,6 +75,7 @@ where
                 envelope,
                 consistency,
                 ts_frequency,
+                event_time,
             } => {
                 // TODO(benesch): this match arm is hard to follow. Refactor.
 
@@ -119,6 +121,8 @@ where
                     // Distribute read responsibility among workers.
                     active: active_read_worker,
                     timestamp_histories: render_state.ts_histories.clone(),
+                    event_times: render_state.event_times.clone(),
+                    connector_updates: render_state.connector_updates.clone(),
                     consistency,
                     timestamp_frequency: ts_frequency,
                     worker_id: scope.index(),
@@ -199,7 +203,9 @@ where
                             .as_collection(),
                     );
 
-                    let (stream, errors) = if let SourceEnvelope::Upsert(key_encoding) = &envelope {
+                    let (stream, errors) = if let SourceEnvelope::Upsert(key_encoding, merge) =
+                        &envelope
+                    {
                         let value_decoder = get_decoder(encoding, &self.debug_name, scope.index());
                         let key_decoder =
                             get_decoder(key_encoding.clone(), &self.debug_name, scope.index());
@@ -208,8 +214,20 @@ where
                             self.as_of_frontier.clone(),
                             key_decoder,
                             value_decoder,
+                            *merge,
                         )
                     } else {
+                        // Deduplication and event-time assignment must observe
+                        // complete, unfiltered records, so the decoder may not
+                        // apply any operators.
+                        let mut no_operators = None;
+                        let operators = if matches!(envelope, SourceEnvelope::Deduplicate { .. })
+                            || event_time.is_some()
+                        {
+                            &mut no_operators
+                        } else {
+                            &mut src.operators
+                        };
                         // TODO(brennan) -- this should just be a MirRelationExpr::FlatMap using regexp_extract, csv_extract,
                         // a hypothetical future avro_extract, protobuf_extract, etc.
                         let ((stream, errors), extra_token) = decode_values(
@@ -217,9 +235,11 @@ where
                             encoding,
                             &self.debug_name,
                             &envelope,
-                            &mut src.operators,
+                            operators,
                             fast_forwarded,
                             src.desc,
+                            src_id,
+                            render_state.encoding_updates.clone(),
                         );
                         if let Some(tok) = extra_token {
                             self.additional_tokens
@@ -237,6 +257,15 @@ where
                     (stream, capability)
                 };
 
+                if let SourceEnvelope::Deduplicate { key_indices, keep } = &envelope {
+                    collection =
+                        super::deduplicate::deduplicate(&collection, key_indices.clone(), *keep);
+                }
+
+                if let Some(event_time) = &event_time {
+                    collection = super::event_time::assign_event_times(&collection, event_time);
+                }
+
                 // Implement source filtering and projection.
                 // At the moment this is strictly optional, but we perform it anyhow
                 // to demonstrate the intended use.
@@ -292,7 +321,7 @@ where
 
                 // Apply `as_of` to each timestamp.
                 match envelope {
-                    SourceEnvelope::Upsert(_) => {}
+                    SourceEnvelope::Upsert(..) => {}
                     _ => {
                         let as_of_frontier1 = self.as_of_frontier.clone();
                         collection = collection
@@ -312,6 +341,11 @@ where
                     }
                 }
 
+                // Observe the frontier of the source, which the coordinator
+                // compares against the frontiers of the dataflows that read it.
+                let mut probe = ProbeHandle::new();
+                collection = collection.inner.probe_with(&mut probe).as_collection();
+
                 let get = MirRelationExpr::Get {
                     id: Id::BareSource(src_id),
                     typ: src.bare_desc.typ().clone(),
@@ -347,6 +381,11 @@ where
                     .entry(uid.source_id)
                     .or_insert_with(Vec::new)
                     .push(Rc::downgrade(&token));
+                render_state
+                    .source_probes
+                    .entry(uid.source_id)
+                    .or_insert_with(Vec::new)
+                    .push((Rc::downgrade(&token), probe));
             }
         }
     }
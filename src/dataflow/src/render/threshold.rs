@@ -40,6 +40,9 @@ where
                     .map({
                         let mut row_packer = repr::RowPacker::new();
                         move |row| {
+                            // The rows themselves are compared, so normalize
+                            // them rather than just the keys.
+                            let row = row.normalize_zeros();
                             let datums = row.unpack();
                             let key_row = row_packer.pack(keys2.iter().map(|i| datums[*i]));
                             (key_row, row)
@@ -31,6 +31,7 @@ where
             limit,
             offset,
             monotonic,
+            expected_group_size,
         } = relation_expr
         {
             let arity = input.arity();
@@ -42,12 +43,28 @@ where
                 let delay = std::time::Duration::from_nanos(10_000_000_000);
                 let retractions = Variable::new(&mut ok_input.scope(), delay.as_millis() as u64);
                 let thinned = ok_input.concat(&retractions.negate());
-                let result = build_topk(thinned, group_key, order_key, *offset, *limit, arity);
+                let result = build_topk(
+                    thinned,
+                    group_key,
+                    order_key,
+                    *offset,
+                    *limit,
+                    *expected_group_size,
+                    arity,
+                );
                 retractions.set(&ok_input.concat(&result.negate()));
                 self.collections
                     .insert(relation_expr.clone(), (result, err_input));
             } else {
-                let result = build_topk(ok_input, group_key, order_key, *offset, *limit, arity);
+                let result = build_topk(
+                    ok_input,
+                    group_key,
+                    order_key,
+                    *offset,
+                    *limit,
+                    *expected_group_size,
+                    arity,
+                );
                 self.collections
                     .insert(relation_expr.clone(), (result, err_input));
             }
@@ -59,6 +76,7 @@ where
                 order_key: &[expr::ColumnOrder],
                 offset: usize,
                 limit: Option<usize>,
+                expected_group_size: Option<usize>,
                 arity: usize,
             ) -> Collection<G, Row, Diff>
             where
@@ -71,28 +89,21 @@ where
                     move |row| {
                         let row_hash = row.hashed();
                         let datums = row.unpack();
-                        let group_row = row_packer.pack(group_clone.iter().map(|i| datums[*i]));
+                        let group_row = row_packer
+                            .pack(group_clone.iter().map(|i| datums[*i]))
+                            .normalize_zeros();
                         ((group_row, row_hash), row)
                     }
                 });
-                // This sequence of numbers defines the shifts that happen to the 64 bit hash
-                // of the record, and has the properties that 1. there are not too many of them,
-                // and 2. each has a modest difference to the next.
-                //
-                // These two properties mean that there should be no reductions on groups that
-                // are substantially larger than `offset + limit` (the largest factor should be
-                // bounded by two raised to the difference between subsequent numbers);
                 if let Some(limit) = limit {
-                    for log_modulus in
-                        [60, 56, 52, 48, 44, 40, 36, 32, 28, 24, 20, 16, 12, 8, 4u64].iter()
-                    {
+                    for modulus in topk_buckets(offset + limit, expected_group_size) {
                         // here we do not apply `offset`, but instead restrict ourself with a limit
                         // that includes the offset. We cannot apply `offset` until we perform the
                         // final, complete reduction.
                         collection = build_topk_stage(
                             collection,
                             order_key,
-                            1u64 << log_modulus,
+                            modulus,
                             0,
                             Some(offset + limit),
                             arity,
@@ -107,6 +118,35 @@ where
                     .map(|((_key, _hash), row)| row)
             }
 
+            /// Returns the moduli, in decreasing order, by which the stages of a TopK that
+            /// retains `limit` records per group (including any offset) bucket the hashes
+            /// of the records in each group.
+            ///
+            /// Successive moduli differ by a factor of sixteen, so that no stage reduces
+            /// buckets substantially larger than `16 * limit` records. A stage only helps
+            /// if its buckets hold more than `limit` records, though, and otherwise just
+            /// maintains another arrangement of its input. With a hint about the size of
+            /// the groups we can omit those stages; without one, we plan for groups of
+            /// any size.
+            fn topk_buckets(limit: usize, expected_group_size: Option<usize>) -> Vec<u64> {
+                let expected_group_size = match expected_group_size {
+                    Some(expected_group_size) => expected_group_size as u64,
+                    None => return (1..16).rev().map(|i| 1u64 << (4 * i)).collect(),
+                };
+                let limit = std::cmp::max(limit, 1) as u64;
+                let mut buckets = vec![];
+                let mut current = 16u64;
+                while current.saturating_mul(limit) < expected_group_size {
+                    buckets.push(current);
+                    current = match current.checked_mul(16) {
+                        Some(next) => next,
+                        None => break,
+                    };
+                }
+                buckets.reverse();
+                buckets
+            }
+
             // To provide a robust incremental orderby-limit experience, we want to avoid grouping
             // *all* records (or even large groups) and then applying the ordering and limit. Instead,
             // a more robust approach forms groups of bounded size (here, 16) and applies the offset
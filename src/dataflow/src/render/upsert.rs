@@ -19,6 +19,9 @@ use timely::dataflow::{Scope, Stream};
 use timely::progress::Antichain;
 
 use log::error;
+use serde_json::Value as JsonValue;
+
+use dataflow_types::UpsertMergeStrategy;
 use repr::{Diff, Row, Timestamp};
 
 use crate::decode::DecoderState;
@@ -35,6 +38,7 @@ pub fn decode_stream<G>(
     as_of_frontier: Antichain<Timestamp>,
     mut key_decoder_state: Box<dyn DecoderState>,
     mut value_decoder_state: Box<dyn DecoderState>,
+    merge: UpsertMergeStrategy,
 ) -> (
     Collection<G, Row, Diff>,
     Option<Collection<G, dataflow_types::DataflowError, Diff>>,
@@ -67,12 +71,17 @@ where
             // this is a map of (time) -> (capability, ((key) -> (value with max
             // offset))) This is a BTreeMap because we want to ensure that if we
             // receive (key1, value1, time 5) and (key1, value2, time 7) that we
-            // send (key1, value1, time 5) before (key1, value2, time 7)
-            let mut to_send = BTreeMap::<_, (_, HashMap<_, SourceData>)>::new();
+            // send (key1, value1, time 5) before (key1, value2, time 7).
+            // When merging, every value for a key at a time is kept, so that
+            // all of them can be applied in offset order.
+            let mut to_send = BTreeMap::<_, (_, HashMap<_, Vec<SourceData>>)>::new();
             // this is a map of (decoded key) -> (decoded_value). We store the
             // latest value for a given key that way we know what to retract if
             // a new value with the same key comes along
             let mut current_values = HashMap::new();
+            // this is a map of (key) -> (merged JSON document), used only when
+            // values are merge patches against the previous value for the key
+            let mut merged_documents = HashMap::<Vec<u8>, JsonValue>::new();
 
             let mut vector = Vec::new();
             let mut row_packer = repr::RowPacker::new();
@@ -96,12 +105,12 @@ where
                         }
 
                         if let Some(new_offset) = new_position {
-                            let entry = to_send
+                            let entries = to_send
                                 .entry(time)
                                 .or_insert_with(|| (cap.delayed(&time), HashMap::new()))
                                 .1
                                 .entry(key)
-                                .or_insert_with(Default::default);
+                                .or_insert_with(Vec::new);
 
                             let new_entry = SourceData {
                                 value: new_value,
@@ -109,18 +118,15 @@ where
                                 upstream_time_millis: new_upstream_time_millis,
                             };
 
-                            if let Some(offset) = entry.position {
-                                // If the time is equal, toss out the row with the
-                                // lower offset
-                                if offset < new_offset {
-                                    *entry = new_entry;
+                            match (merge, entries.last_mut()) {
+                                (UpsertMergeStrategy::Replace, Some(entry)) => {
+                                    // If the time is equal, toss out the row with
+                                    // the lower offset
+                                    if entry.position < Some(new_offset) {
+                                        *entry = new_entry;
+                                    }
                                 }
-                            } else {
-                                // If there was not a previous entry, we'll have
-                                // inserted a blank default value, and the
-                                // offset would be none.
-                                // Just insert new entry into the hashmap.
-                                *entry = new_entry;
+                                _ => entries.push(new_entry),
                             }
                         } else {
                             // This case should be unreachable because kafka
@@ -135,7 +141,14 @@ where
                     if !input.frontier.less_equal(time) {
                         let mut session = output.session(cap);
                         removed_times.push(time.clone());
-                        for (key, data) in map.drain() {
+                        for (key, mut entries) in map.drain() {
+                            let data = match merge {
+                                UpsertMergeStrategy::Replace => entries.pop().unwrap(),
+                                UpsertMergeStrategy::JsonMergePatch => {
+                                    entries.sort_by_key(|entry| entry.position);
+                                    merge_patches(&mut merged_documents, &key, entries)
+                                }
+                            };
                             // decode key and value
                             match key_decoder_state.decode_key(&key) {
                                 Ok(decoded_key) => {
@@ -203,3 +216,66 @@ where
 
     (ok_stream.as_collection(), None)
 }
+
+/// Applies `patches`, in order, to the document currently stored for `key`.
+///
+/// Returns the record to decode in place of the patches: its value is the
+/// serialized merged document, or empty if the key has been deleted. Patches
+/// that are not valid JSON are logged and skipped.
+fn merge_patches(
+    documents: &mut HashMap<Vec<u8>, JsonValue>,
+    key: &[u8],
+    patches: Vec<SourceData>,
+) -> SourceData {
+    let mut document = documents.remove(key);
+    let mut position = None;
+    let mut upstream_time_millis = None;
+    for patch in patches {
+        position = patch.position;
+        upstream_time_millis = patch.upstream_time_millis;
+        if patch.value.is_empty() {
+            // An empty value is a tombstone, as for a plain upsert.
+            document = None;
+            continue;
+        }
+        match serde_json::from_slice(&patch.value) {
+            Ok(patch) => {
+                json_merge_patch(document.get_or_insert(JsonValue::Null), patch);
+            }
+            Err(e) => error!("Failed to decode JSON merge patch: {}", e),
+        }
+    }
+    let value = match document {
+        Some(document) => {
+            let value = serde_json::to_vec(&document).expect("JSON values serialize");
+            documents.insert(key.to_vec(), document);
+            value
+        }
+        None => Vec::new(),
+    };
+    SourceData {
+        value,
+        position,
+        upstream_time_millis,
+    }
+}
+
+/// Applies a JSON merge patch, as specified by RFC 7396, to `target`.
+fn json_merge_patch(target: &mut JsonValue, patch: JsonValue) {
+    match patch {
+        JsonValue::Object(patch) => {
+            if !target.is_object() {
+                *target = JsonValue::Object(Default::default());
+            }
+            let target = target.as_object_mut().unwrap();
+            for (name, value) in patch {
+                if value.is_null() {
+                    target.remove(&name);
+                } else {
+                    json_merge_patch(target.entry(name).or_insert(JsonValue::Null), value);
+                }
+            }
+        }
+        patch => *target = patch,
+    }
+}
@@ -10,15 +10,20 @@
 //! An interactive dataflow server.
 
 use std::cell::RefCell;
-use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::ErrorKind;
+use std::mem;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Mutex;
 use std::time::{Instant, UNIX_EPOCH};
 
 use differential_dataflow::operators::arrange::arrangement::Arrange;
 use differential_dataflow::trace::cursor::Cursor;
-use differential_dataflow::trace::TraceReader;
+use differential_dataflow::trace::implementations::ord::OrdValBatch;
+use differential_dataflow::trace::{BatchReader, TraceReader};
 use differential_dataflow::Collection;
 use serde::{Deserialize, Serialize};
 use timely::communication::initialize::WorkerGuards;
@@ -36,7 +41,7 @@ use uuid::Uuid;
 use dataflow_types::logging::LoggingConfig;
 use dataflow_types::{
     Consistency, DataflowDesc, DataflowError, ExternalSourceConnector, MzOffset, PeekResponse,
-    SourceConnector, TimestampSourceUpdate, Update,
+    ProtobufEncoding, SourceConnector, TimestampSourceUpdate, Update,
 };
 use expr::{GlobalId, MapFilterProject, PartitionId, RowSetFinishing};
 use repr::{Diff, Row, RowArena, Timestamp};
@@ -51,6 +56,10 @@ use crate::source::cache::WorkerCacheData;
 
 mod metrics;
 
+/// The maximum number of rows in each batch of a peek's results that a worker
+/// sends to the coordinator.
+const PEEK_BATCH_SIZE: usize = 1024;
+
 /// Explicit instructions for timely dataflow workers.
 #[derive(Clone, Debug)]
 pub enum SequencedCommand {
@@ -77,6 +86,9 @@ pub enum SequencedCommand {
         /// Used in responses and cancelation requests.
         conn_id: u32,
         /// A communication link for sending a response.
+        ///
+        /// Each worker is sent its own link, on which it sends its results in
+        /// batches, and which it drops once it has sent them all.
         tx: mpsc::UnboundedSender<PeekResponse>,
         /// The logical timestamp at which the arrangement is queried.
         timestamp: Timestamp,
@@ -122,6 +134,21 @@ pub enum SequencedCommand {
         /// The ID id of the formerly timestamped source.
         id: GlobalId,
     },
+    /// Reconnect all instances of a source with new connection options.
+    UpdateSourceConnector {
+        /// The ID of the altered source.
+        id: GlobalId,
+        /// The connector with the new connection options.
+        connector: ExternalSourceConnector,
+    },
+    /// Decode subsequent messages for all instances of a Protobuf source with
+    /// new descriptors.
+    UpdateSourceEncoding {
+        /// The ID of the altered source.
+        id: GlobalId,
+        /// The encoding with the new descriptors.
+        encoding: ProtobufEncoding,
+    },
     /// Advance all local inputs to the given timestamp.
     AdvanceAllLocalInputs {
         /// The timestamp to advance to.
@@ -164,6 +191,16 @@ pub enum CacheMessage {
 pub enum WorkerFeedback {
     /// A list of identifiers of traces, with prior and new upper frontiers.
     FrontierUppers(Vec<(GlobalId, ChangeBatch<Timestamp>)>),
+    /// A list of identifiers of external sources, with prior and new upper
+    /// frontiers. The frontier of a source is the least frontier of the
+    /// instances of the source that the worker has rendered.
+    SourceFrontierUppers(Vec<(GlobalId, ChangeBatch<Timestamp>)>),
+    /// For each source, the latest upstream event time, in milliseconds since
+    /// the epoch, of the messages that were assigned each timestamp.
+    SourceEventTimes(Vec<(GlobalId, Vec<(Timestamp, i64)>)>),
+    /// For each index whose contents changed, the approximate number of bytes
+    /// that the worker's share of its arrangement holds.
+    ArrangementBytes(Vec<(GlobalId, usize)>),
 }
 
 /// Configures a dataflow server.
@@ -175,6 +212,10 @@ pub struct Config {
     pub command_receivers: Vec<crossbeam_channel::Receiver<SequencedCommand>>,
     /// The Timely worker configuration.
     pub timely_worker: timely::WorkerConfig,
+    /// The directory in which operators spill state to disk.
+    ///
+    /// Anything in the directory is removed when the workers start.
+    pub spill_directory: PathBuf,
 }
 
 /// Initiates a timely dataflow computation, processing materialized commands.
@@ -182,6 +223,20 @@ pub fn serve(config: Config) -> Result<WorkerGuards<()>, String> {
     let workers = config.command_receivers.len();
     assert!(workers > 0);
 
+    // Remove any state spilled by a previous process, which no dataflow will
+    // read again.
+    match fs::remove_dir_all(&config.spill_directory) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            return Err(format!(
+                "removing spill directory {}: {}",
+                config.spill_directory.display(),
+                e
+            ))
+        }
+        _ => (),
+    }
+    let spill_directory = config.spill_directory;
+
     // Construct endpoints for each thread that will receive the coordinator's
     // sequenced command stream.
     //
@@ -209,15 +264,22 @@ pub fn serve(config: Config) -> Result<WorkerGuards<()>, String> {
                     traces: TraceManager::new(worker_idx),
                     local_inputs: HashMap::new(),
                     ts_source_mapping: HashMap::new(),
+                    source_probes: HashMap::new(),
                     ts_histories: Default::default(),
+                    event_times: Default::default(),
+                    connector_updates: Default::default(),
+                    encoding_updates: Default::default(),
                     dataflow_tokens: HashMap::new(),
                     caching_tx: None,
+                    spill_directory: spill_directory.clone(),
                 },
                 materialized_logger: None,
                 command_rx,
                 pending_peeks: Vec::new(),
                 feedback_tx: None,
                 reported_frontiers: HashMap::new(),
+                reported_source_frontiers: HashMap::new(),
+                arrangement_batch_bytes: HashMap::new(),
                 metrics: Metrics::for_worker_id(worker_idx),
             }
             .run()
@@ -240,6 +302,14 @@ pub enum TimestampDataUpdate {
 }
 /// Map of source ID to timestamp data updates (RT or BYO).
 pub type TimestampDataUpdates = Rc<RefCell<HashMap<GlobalId, TimestampDataUpdate>>>;
+/// Map of source ID to the latest upstream event time observed at each
+/// timestamp, awaiting report to the coordinator.
+pub type SourceEventTimes = Rc<RefCell<HashMap<GlobalId, BTreeMap<Timestamp, i64>>>>;
+/// Map of source ID to the most recently altered connector for that source.
+pub type SourceConnectorUpdates = Rc<RefCell<HashMap<GlobalId, ExternalSourceConnector>>>;
+/// Map of source ID to the most recently altered Protobuf encoding for that
+/// source.
+pub type SourceEncodingUpdates = Rc<RefCell<HashMap<GlobalId, ProtobufEncoding>>>;
 
 /// State maintained for each worker thread.
 ///
@@ -263,6 +333,13 @@ where
     feedback_tx: Option<mpsc::UnboundedSender<WorkerFeedbackWithMeta>>,
     /// Tracks the frontier information that has been sent over `feedback_tx`.
     reported_frontiers: HashMap<GlobalId, Antichain<Timestamp>>,
+    /// Tracks the source frontier information that has been sent over
+    /// `feedback_tx`.
+    reported_source_frontiers: HashMap<GlobalId, Antichain<Timestamp>>,
+    /// The size in bytes of each batch in each index's arrangement, by the
+    /// lower and upper frontiers of the batch. Batches are immutable, so each
+    /// is measured only once.
+    arrangement_batch_bytes: HashMap<GlobalId, HashMap<(Vec<Timestamp>, Vec<Timestamp>), usize>>,
     /// Metrics bundle.
     metrics: Metrics,
 }
@@ -431,6 +508,8 @@ where
 
             // Report frontier information back the coordinator.
             self.report_frontiers();
+            self.report_source_frontiers();
+            self.report_event_times();
 
             // Handle any received commands.
             let cmds: Vec<_> = self.command_rx.try_iter().collect();
@@ -454,6 +533,7 @@ where
         if let Some(feedback_tx) = &mut self.feedback_tx {
             let mut upper = Antichain::new();
             let mut progress = Vec::new();
+            let mut sizes = Vec::new();
             for (id, traces) in self.render_state.traces.traces.iter_mut() {
                 // Read the upper frontier and compare to what we've reported.
                 traces.oks_mut().read_upper(&mut upper);
@@ -461,7 +541,9 @@ where
                     .reported_frontiers
                     .get_mut(&id)
                     .expect("Frontier missing!");
-                if lower != &upper {
+                // A re-rendered index must not report a frontier behind the
+                // one reported by the arrangement it replaced.
+                if lower != &upper && <_ as PartialOrder>::less_equal(lower, &upper) {
                     let mut changes = ChangeBatch::new();
                     for time in lower.elements().iter() {
                         changes.update(time.clone(), -1);
@@ -475,6 +557,28 @@ where
                         progress.push((*id, changes));
                     }
                     lower.clone_from(&upper);
+
+                    // The arrangement only receives new batches when its
+                    // upper advances, so this is the time to remeasure it.
+                    // Only the batches that were not measured before are
+                    // visited record by record.
+                    let measured = self.arrangement_batch_bytes.entry(*id).or_default();
+                    let mut batches = HashMap::new();
+                    let mut bytes = 0;
+                    traces.oks_mut().map_batches(|batch| {
+                        let desc = batch.description();
+                        let bounds = (
+                            desc.lower().elements().to_vec(),
+                            desc.upper().elements().to_vec(),
+                        );
+                        let size = measured
+                            .remove(&bounds)
+                            .unwrap_or_else(|| batch_bytes(batch));
+                        bytes += size;
+                        batches.insert(bounds, size);
+                    });
+                    *measured = batches;
+                    sizes.push((*id, bytes));
                 }
             }
             if let Some(logger) = self.materialized_logger.as_mut() {
@@ -492,6 +596,80 @@ where
                     })
                     .expect("feedback receriver should not drop first");
             }
+            if !sizes.is_empty() {
+                feedback_tx
+                    .send(WorkerFeedbackWithMeta {
+                        worker_id: self.timely_worker.index(),
+                        message: WorkerFeedback::ArrangementBytes(sizes),
+                    })
+                    .expect("feedback receiver should not drop first");
+            }
+        }
+    }
+
+    /// Send the frontiers of external sources to the coordinator.
+    fn report_source_frontiers(&mut self) {
+        if let Some(feedback_tx) = &mut self.feedback_tx {
+            let mut progress = Vec::new();
+            for (id, probes) in self.render_state.source_probes.iter_mut() {
+                // Instances of the source whose dataflows have been dropped
+                // no longer hold back its frontier.
+                probes.retain(|(token, _probe)| token.upgrade().is_some());
+                let mut upper = Antichain::new();
+                for (_token, probe) in probes.iter() {
+                    probe.with_frontier(|frontier| {
+                        for time in frontier.iter() {
+                            upper.insert(*time);
+                        }
+                    });
+                }
+                let lower = self
+                    .reported_source_frontiers
+                    .entry(*id)
+                    .or_insert_with(|| Antichain::from_elem(0));
+                if lower != &upper {
+                    let mut changes = ChangeBatch::new();
+                    for time in lower.elements().iter() {
+                        changes.update(*time, -1);
+                    }
+                    for time in upper.elements().iter() {
+                        changes.update(*time, 1);
+                    }
+                    changes.compact();
+                    if !changes.is_empty() {
+                        progress.push((*id, changes));
+                    }
+                    lower.clone_from(&upper);
+                }
+            }
+            if !progress.is_empty() {
+                feedback_tx
+                    .send(WorkerFeedbackWithMeta {
+                        worker_id: self.timely_worker.index(),
+                        message: WorkerFeedback::SourceFrontierUppers(progress),
+                    })
+                    .expect("feedback receiver should not drop first");
+            }
+        }
+    }
+
+    /// Send the upstream event times observed by sources to the coordinator.
+    fn report_event_times(&mut self) {
+        let event_times = mem::take(&mut *self.render_state.event_times.borrow_mut());
+        if event_times.is_empty() {
+            return;
+        }
+        if let Some(feedback_tx) = &mut self.feedback_tx {
+            let event_times = event_times
+                .into_iter()
+                .map(|(id, times)| (id, times.into_iter().collect()))
+                .collect();
+            feedback_tx
+                .send(WorkerFeedbackWithMeta {
+                    worker_id: self.timely_worker.index(),
+                    message: WorkerFeedback::SourceEventTimes(event_times),
+                })
+                .expect("feedback receiver should not drop first");
         }
     }
 
@@ -499,7 +677,23 @@ where
         match cmd {
             SequencedCommand::CreateDataflows(dataflows) => {
                 for dataflow in dataflows.into_iter() {
+                    // Attach the ID of the object that the dataflow maintains
+                    // to the log messages emitted while building it.
+                    let span = match dataflow.export_ids().next() {
+                        Some(id) => tracing::info_span!("dataflow", object_id = %id),
+                        None => tracing::info_span!("dataflow"),
+                    };
+                    let _guard = span.enter();
+
                     for (idx_id, idx, _) in dataflow.index_exports.iter() {
+                        // A dataflow that exports an existing index renders
+                        // the index anew. The new arrangement replaces the old
+                        // one, and its frontier is reported only once it
+                        // catches up to the frontier already reported.
+                        if self.render_state.traces.del_trace(idx_id) {
+                            self.arrangement_batch_bytes.remove(idx_id);
+                            continue;
+                        }
                         self.reported_frontiers
                             .insert(*idx_id, Antichain::from_elem(0));
                         if let Some(logger) = self.materialized_logger.as_mut() {
@@ -521,6 +715,8 @@ where
             SequencedCommand::DropSources(names) => {
                 for name in names {
                     self.render_state.local_inputs.remove(&name);
+                    self.render_state.source_probes.remove(&name);
+                    self.reported_source_frontiers.remove(&name);
                 }
             }
             SequencedCommand::DropSinks(ids) => {
@@ -531,6 +727,7 @@ where
             SequencedCommand::DropIndexes(ids) => {
                 for id in ids {
                     self.render_state.traces.del_trace(&id);
+                    self.arrangement_batch_bytes.remove(&id);
                     let frontier = self
                         .reported_frontiers
                         .remove(&id)
@@ -601,9 +798,9 @@ where
                 let logger = &mut self.materialized_logger;
                 self.pending_peeks.retain(|peek| {
                     if peek.conn_id == conn_id {
-                        peek.tx
-                            .send(PeekResponse::Canceled)
-                            .expect("peek receiver should not drop first");
+                        // The receiver may have hung up already if the
+                        // results of other workers satisfied the peek.
+                        let _ = peek.tx.send(PeekResponse::Canceled);
 
                         if let Some(logger) = logger {
                             logger.log(MaterializedEvent::Peek(peek.as_log_event(), false));
@@ -782,7 +979,35 @@ where
                     }
                 }
             }
+            SequencedCommand::UpdateSourceConnector { id, connector } => {
+                self.render_state
+                    .connector_updates
+                    .borrow_mut()
+                    .insert(id, connector);
+
+                // Wake up all instances of the source so they reconnect
+                // promptly, rather than on their next message.
+                if let Some(sources) = self.render_state.ts_source_mapping.get(&id) {
+                    for source in sources {
+                        if let Some(source) = source.upgrade() {
+                            if let Some(token) = &*source {
+                                token.activate();
+                            }
+                        }
+                    }
+                }
+            }
+            SequencedCommand::UpdateSourceEncoding { id, encoding } => {
+                // Decoders pick up the new encoding before they decode their
+                // next batch of messages, so there is no need to wake them.
+                self.render_state
+                    .encoding_updates
+                    .borrow_mut()
+                    .insert(id, encoding);
+            }
             SequencedCommand::DropSourceTimestamping { id } => {
+                self.render_state.connector_updates.borrow_mut().remove(&id);
+                self.render_state.encoding_updates.borrow_mut().remove(&id);
                 let mut timestamps = self.render_state.ts_histories.borrow_mut();
                 let prev = timestamps.remove(&id);
 
@@ -874,13 +1099,35 @@ impl PendingPeek {
         if upper.less_equal(&self.timestamp) {
             return false;
         }
-        let response = match self.collect_finished_data() {
-            Ok(rows) => PeekResponse::Rows(rows),
-            Err(text) => PeekResponse::Error(text),
-        };
-        self.tx
-            .send(response)
-            .expect("peek receiver should not drop first");
+        match self.collect_finished_data() {
+            Ok(mut rows) => {
+                // Sort the results, so that the coordinator can merge the
+                // responses of all workers rather than sorting them anew.
+                if !self.finishing.order_by.is_empty() {
+                    rows.sort_by(|left, right| {
+                        expr::compare_columns(
+                            &self.finishing.order_by,
+                            &left.unpack(),
+                            &right.unpack(),
+                            || left.cmp(right),
+                        )
+                    });
+                }
+                let mut rows = rows.into_iter().peekable();
+                while rows.peek().is_some() {
+                    let batch = rows.by_ref().take(PEEK_BATCH_SIZE).collect();
+                    // The receiver hangs up once the results of other workers
+                    // satisfy the peek's limit, or once any worker reports an
+                    // error, after which there is no use in sending more.
+                    if self.tx.send(PeekResponse::Rows(batch)).is_err() {
+                        break;
+                    }
+                }
+            }
+            Err(text) => {
+                let _ = self.tx.send(PeekResponse::Error(text));
+            }
+        }
         true
     }
 
@@ -921,6 +1168,9 @@ impl PendingPeek {
         // are collected, so we don't need to have exactly this many results,
         // just at least those results that would have been returned.
         let max_results = self.finishing.limit.map(|l| l + self.finishing.offset);
+        if max_results == Some(0) {
+            return Ok(results);
+        }
 
         if let Some(literal) = &self.key {
             cursor.seek_key(&storage, literal);
@@ -961,8 +1211,15 @@ impl PendingPeek {
                     // When we have a LIMIT we can restrict the number of copies we make.
                     // This protects us when we have many copies of the same records, as
                     // the DD representation uses a binary count and may not exhaust our
-                    // memory in situtations where this copying might.
-                    if let Some(limit) = max_results {
+                    // memory in situtations where this copying might. Without an
+                    // ordering, any records will do, so we need only as many copies as
+                    // it takes to reach `max_results`.
+                    if let Some(max_results) = max_results {
+                        let limit = if self.finishing.order_by.is_empty() {
+                            max_results - results.len()
+                        } else {
+                            max_results
+                        };
                         let limit = std::convert::TryInto::<isize>::try_into(limit);
                         if let Ok(limit) = limit {
                             copies = std::cmp::min(copies, limit);
@@ -972,35 +1229,37 @@ impl PendingPeek {
                         results.push(result.clone());
                     }
 
-                    // If we hold many more than `max_results` records, we can thin down
-                    // `results` using `self.finishing.ordering`.
                     if let Some(max_results) = max_results {
-                        // We use a threshold twice what we intend, to amortize the work
-                        // across all of the insertions. We could tighten this, but it
-                        // works for the moment.
-                        if results.len() >= 2 * max_results {
-                            if self.finishing.order_by.is_empty() {
-                                results.truncate(max_results);
+                        if self.finishing.order_by.is_empty() {
+                            // Without an ordering, the first `max_results` records are
+                            // as good as any others, so we can stop scanning the
+                            // arrangement as soon as we have found them.
+                            if results.len() >= max_results {
                                 return Ok(results);
-                            } else {
-                                // We can sort `results` and then truncate to `max_results`.
-                                // This has an effect similar to a priority queue, without
-                                // its interactive dequeueing properties.
-                                // TODO: Had we left these as `Vec<Datum>` we would avoid
-                                // the unpacking; we should consider doing that, although
-                                // it will require a re-pivot of the code to branch on this
-                                // inner test (as we prefer not to maintain `Vec<Datum>`
-                                // in the other case).
-                                results.sort_by(|left, right| {
-                                    expr::compare_columns(
-                                        &self.finishing.order_by,
-                                        &left.unpack(),
-                                        &right.unpack(),
-                                        || left.cmp(right),
-                                    )
-                                });
-                                results.truncate(max_results);
                             }
+                        } else if results.len() >= 2 * max_results {
+                            // If we hold many more than `max_results` records, we can
+                            // thin down `results` using `self.finishing.ordering`. We
+                            // use a threshold twice what we intend, to amortize the
+                            // work across all of the insertions.
+                            //
+                            // We sort `results` and then truncate to `max_results`.
+                            // This has an effect similar to a priority queue, without
+                            // its interactive dequeueing properties.
+                            // TODO: Had we left these as `Vec<Datum>` we would avoid
+                            // the unpacking; we should consider doing that, although
+                            // it will require a re-pivot of the code to branch on this
+                            // inner test (as we prefer not to maintain `Vec<Datum>`
+                            // in the other case).
+                            results.sort_by(|left, right| {
+                                expr::compare_columns(
+                                    &self.finishing.order_by,
+                                    &left.unpack(),
+                                    &right.unpack(),
+                                    || left.cmp(right),
+                                )
+                            });
+                            results.truncate(max_results);
                         }
                     }
                 }
@@ -1017,3 +1276,23 @@ impl PendingPeek {
         Ok(results)
     }
 }
+
+/// Estimates the number of bytes of memory that `batch` holds.
+///
+/// Each row is charged for its encoded data in addition to its own size,
+/// which overestimates the size of rows that are stored inline.
+fn batch_bytes(batch: &OrdValBatch<Row, Row, Timestamp, Diff>) -> usize {
+    fn rows_bytes(rows: &[Row]) -> usize {
+        rows.iter()
+            .map(|row| mem::size_of::<Row>() + row.data().len())
+            .sum()
+    }
+    let keys = &batch.layer;
+    let vals = &keys.vals;
+    let updates = &vals.vals.vals;
+    rows_bytes(&keys.keys)
+        + keys.offs.len() * mem::size_of::<usize>()
+        + rows_bytes(&vals.keys)
+        + vals.offs.len() * mem::size_of::<usize>()
+        + updates.len() * mem::size_of::<(Timestamp, Diff)>()
+}
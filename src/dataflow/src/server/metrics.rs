@@ -111,6 +111,10 @@ struct CommandsProcessedMetrics {
     advance_source_timestamp: IntCounter,
     drop_source_timestamping_int: i32,
     drop_source_timestamping: IntCounter,
+    update_source_connector_int: i32,
+    update_source_connector: IntCounter,
+    update_source_encoding_int: i32,
+    update_source_encoding: IntCounter,
     enable_feedback_int: i32,
     enable_feedback: IntCounter,
     enable_logging_int: i32,
@@ -158,6 +162,12 @@ impl CommandsProcessedMetrics {
             drop_source_timestamping_int: 0,
             drop_source_timestamping: COMMANDS_PROCESSED_RAW
                 .with_label_values(&[worker, "drop_source_timestamping"]),
+            update_source_connector_int: 0,
+            update_source_connector: COMMANDS_PROCESSED_RAW
+                .with_label_values(&[worker, "update_source_connector"]),
+            update_source_encoding_int: 0,
+            update_source_encoding: COMMANDS_PROCESSED_RAW
+                .with_label_values(&[worker, "update_source_encoding"]),
             enable_feedback_int: 0,
             enable_feedback: COMMANDS_PROCESSED_RAW.with_label_values(&[worker, "enable_feedback"]),
             enable_logging_int: 0,
@@ -189,6 +199,8 @@ impl CommandsProcessedMetrics {
             SequencedCommand::DropSourceTimestamping { .. } => {
                 self.drop_source_timestamping_int += 1
             }
+            SequencedCommand::UpdateSourceConnector { .. } => self.update_source_connector_int += 1,
+            SequencedCommand::UpdateSourceEncoding { .. } => self.update_source_encoding_int += 1,
             SequencedCommand::EnableFeedback(..) => self.enable_feedback_int += 1,
             SequencedCommand::EnableCaching(..) => self.enable_caching_int += 1,
             SequencedCommand::EnableLogging(_) => self.enable_logging_int += 1,
@@ -249,6 +261,16 @@ impl CommandsProcessedMetrics {
                 .inc_by(self.drop_source_timestamping_int as i64);
             self.drop_source_timestamping_int = 0;
         }
+        if self.update_source_connector_int > 0 {
+            self.update_source_connector
+                .inc_by(self.update_source_connector_int as i64);
+            self.update_source_connector_int = 0;
+        }
+        if self.update_source_encoding_int > 0 {
+            self.update_source_encoding
+                .inc_by(self.update_source_encoding_int as i64);
+            self.update_source_encoding_int = 0;
+        }
         if self.enable_feedback_int > 0 {
             self.enable_feedback.inc_by(self.enable_feedback_int as i64);
             self.enable_feedback_int = 0;
@@ -9,18 +9,19 @@
 
 use std::any::Any;
 use std::collections::{HashMap, VecDeque};
+use std::error::Error;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use differential_dataflow::Collection;
 use lazy_static::lazy_static;
-use log::error;
+use log::{error, info, warn};
 use prometheus::{
     register_int_counter_vec, register_uint_gauge_vec, IntCounter, IntCounterVec, UIntGauge,
     UIntGaugeVec,
 };
-use rdkafka::client::ClientContext;
+use rdkafka::client::{ClientContext, OAuthToken};
 use rdkafka::config::ClientConfig;
 use rdkafka::error::{KafkaError, RDKafkaErrorCode};
 use rdkafka::message::Message;
@@ -34,8 +35,12 @@ use timely::dataflow::Scope;
 use dataflow_types::{KafkaSinkConnector, SinkAsOf};
 use expr::GlobalId;
 use interchange::avro::{self, Encoder};
+use kafka_util::credentials::{self, CredentialFiles};
+use kafka_util::oauth;
 use repr::{Diff, RelationDesc, Row, Timestamp};
 
+use crate::faults;
+
 /// Per-Kafka sink metrics.
 #[derive(Clone)]
 pub struct SinkMetrics {
@@ -107,7 +112,14 @@ impl SinkProducerContext {
     }
 }
 
-impl ClientContext for SinkProducerContext {}
+impl ClientContext for SinkProducerContext {
+    const ENABLE_REFRESH_OAUTH_TOKEN: bool = true;
+
+    fn generate_oauth_token(&self, config: Option<&str>) -> Result<OAuthToken, Box<dyn Error>> {
+        oauth::generate_token(config)
+    }
+}
+
 impl ProducerContext for SinkProducerContext {
     type DeliveryOpaque = ();
 
@@ -270,9 +282,9 @@ where
     // if it makes a big difference
     config.set("queue.buffering.max.ms", &format!("{}", 10));
 
-    for (k, v) in connector.config_options.iter() {
-        config.set(k, v);
-    }
+    let mut credential_files = CredentialFiles::new(&connector.config_options);
+    credentials::set_config_options(&mut config, &connector.config_options)
+        .expect("configuring kafka producer for kafka sinks failed");
 
     // TODO(eli): replace with https://github.com/fede1024/rust-rdkafka/pull/333
     let transactional = connector.config_options.contains_key("transactional.id");
@@ -281,7 +293,7 @@ where
     let shutdown_flag = Arc::new(AtomicBool::new(false));
     let mut builder = OperatorBuilder::new(name.clone(), stream.scope());
 
-    let s = {
+    let mut s = {
         let metrics = SinkMetrics::new(
             &connector.topic,
             &id.to_string(),
@@ -316,6 +328,8 @@ where
     let mut ready_rows: VecDeque<(Timestamp, Vec<EncodedRow>)> = VecDeque::new();
     let mut state = SendState::Init;
     let mut vector = Vec::new();
+    let mut connection_drops = faults::connection_drops(&connector.topic);
+    let mut credentials_changed = false;
 
     let mut sink_logic = move |input: &mut FrontieredInputHandle<
         _,
@@ -380,6 +394,53 @@ where
             ready_rows.push_back((ts, rows));
         });
 
+        // Replace the producer if a connection drop was injected, or if its
+        // credentials were rotated on disk, since it was created. This only
+        // happens between transactions, and after flushing the messages in
+        // flight, so that no messages are lost. If the new producer cannot be
+        // created, the old one is kept, and a credential change is retried at
+        // the next check.
+        credentials_changed |= credential_files.changed();
+        let drops = faults::connection_drops(&connector.topic);
+        if (drops != connection_drops || credentials_changed)
+            && matches!(state, SendState::Init | SendState::BeginTxn)
+        {
+            if credentials_changed {
+                info!("recreating producer for {} after credential change", s.name);
+            } else {
+                info!(
+                    "recreating producer for {} after injected connection drop",
+                    s.name
+                );
+            }
+            connection_drops = drops;
+            credentials_changed = false;
+            if let Err(e) = s.producer.flush(Duration::from_secs(5)) {
+                warn!("failed to flush producer for {}: {}", s.name, e);
+            }
+            let new_credential_files = CredentialFiles::new(&connector.config_options);
+            let producer = credentials::set_config_options(&mut config, &connector.config_options)
+                .and_then(|()| {
+                    Ok(config.create_with_context::<_, ThreadedProducer<_>>(
+                        SinkProducerContext::new(s.metrics.clone(), s.shutdown_flag.clone()),
+                    )?)
+                });
+            match producer {
+                Ok(producer) => {
+                    s.producer = producer;
+                    credential_files = new_credential_files;
+                    state = SendState::Init;
+                }
+                Err(e) => error!("failed to recreate producer for {}: {}", s.name, e),
+            }
+        }
+
+        // Hold off on sending while an injected fault stalls the topic.
+        if faults::topic_stalled(&connector.topic) {
+            s.activator.activate_after(Duration::from_millis(100));
+            return true;
+        }
+
         // Send a bounded number of records to Kafka from the ready queue.
         // This loop has explicitly been designed so that each iteration sends
         // at most one record to Kafka
@@ -436,9 +497,19 @@ where
                         mut total_sent,
                     } => {
                         let encoded_row = &rows[row_index];
+                        let mut value = encoded_row.value.as_ref();
+                        let corrupted;
+                        if faults::take_corruption(&connector.topic) {
+                            corrupted = value.map(|value| {
+                                let mut value = value.clone();
+                                faults::corrupt(&mut value);
+                                value
+                            });
+                            value = corrupted.as_ref();
+                        }
                         let record = BaseRecord::to(&connector.topic);
-                        let record = if encoded_row.value.is_some() {
-                            record.payload(encoded_row.value.as_ref().unwrap())
+                        let record = if let Some(value) = value {
+                            record.payload(value)
                         } else {
                             record
                         };
@@ -16,7 +16,7 @@ use timely::dataflow::channels::pact::Pipeline;
 use timely::dataflow::operators::Operator;
 use timely::dataflow::{Scope, Stream};
 
-use dataflow_types::{SinkAsOf, TailSinkConnector};
+use dataflow_types::{SinkAsOf, TailEnvelope, TailSinkConnector};
 use expr::GlobalId;
 use repr::adt::decimal::Significand;
 use repr::{Datum, Diff, Row, RowPacker, Timestamp};
@@ -79,8 +79,14 @@ pub fn tail<G>(
                     // time) is that the batch's upper may be larger than the row time.
                     packer.push(Datum::Decimal(Significand::new(i128::from(upper))));
                     packer.push(Datum::True);
-                    // Fill in the diff column and all table columns with NULL.
-                    for _ in 0..(connector.object_columns + 1) {
+                    // Fill in all columns that follow the header with NULL:
+                    // either the diff or state column and the table columns,
+                    // or the before and after columns.
+                    let data_columns = match connector.envelope {
+                        TailEnvelope::Diffs | TailEnvelope::Upsert => connector.object_columns + 1,
+                        TailEnvelope::Debezium => 2,
+                    };
+                    for _ in 0..data_columns {
                         packer.push(Datum::Null);
                     }
                     results.push(packer.finish_and_reuse());
@@ -7,12 +7,13 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::io::{self, BufRead, Read};
+use std::io::{self, BufRead, BufReader, Read};
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::thread;
 
 use anyhow::{Context, Error};
+use bzip2::read::MultiBzDecoder;
 use flate2::read::MultiGzDecoder;
 #[cfg(target_os = "linux")]
 use inotify::{Inotify, WatchMask};
@@ -20,7 +21,8 @@ use log::error;
 use timely::scheduling::{Activator, SyncActivator};
 
 use dataflow_types::{
-    AvroOcfEncoding, Compression, DataEncoding, ExternalSourceConnector, MzOffset,
+    AvroOcfEncoding, BytesEncoding, Compression, DataEncoding, ExternalSourceConnector, Framing,
+    MzOffset,
 };
 use expr::{PartitionId, SourceInstanceId};
 use mz_avro::types::Value;
@@ -142,12 +144,19 @@ impl SourceConstructor<Vec<u8>> for FileSourceInfo<Vec<u8>> {
         consumer_activator: SyncActivator,
         connector: ExternalSourceConnector,
         consistency_info: &mut ConsistencyInfo,
-        _: DataEncoding,
+        encoding: DataEncoding,
     ) -> Result<FileSourceInfo<Vec<u8>>, anyhow::Error> {
         let receiver = match connector {
             ExternalSourceConnector::File(fc) if active => {
                 log::debug!("creating FileSourceInfo worker_id={}", worker_id);
-                let ctor = |fi| Ok(std::io::BufReader::new(fi).split(b'\n'));
+                let framing = match encoding {
+                    DataEncoding::Bytes(BytesEncoding { framing }) => framing,
+                    encoding => match encoding.transcoding() {
+                        Some(transcoding) => transcoding.line_framing(),
+                        None => Framing::default(),
+                    },
+                };
+                let ctor = |fi| Ok(FramedReader::new(fi, framing));
                 let (tx, rx) = std::sync::mpsc::sync_channel(10000);
                 let tail = if fc.tail {
                     FileReadStyle::TailFollowFd
@@ -377,9 +386,20 @@ pub fn read_file_task<Ctor, I, Out, Err>(
         }
     };
 
-    let file: Box<dyn AvroRead + Send> = match compression {
+    let file: Box<dyn AvroRead + Send> = match compression.resolve(&path.to_string_lossy()) {
         Compression::Gzip => Box::new(MultiGzDecoder::new(file)),
-        Compression::None => Box::new(file),
+        Compression::Zstd => match zstd::stream::read::Decoder::new(file) {
+            Ok(decoder) => Box::new(Decompressed(decoder)),
+            Err(err) => {
+                let _ = tx.send(Err(Error::new(err).context(format!(
+                    "file source: unable to decompress file at path {}",
+                    path.to_string_lossy(),
+                ))));
+                return;
+            }
+        },
+        Compression::Bzip2 => Box::new(Decompressed(MultiBzDecoder::new(file))),
+        Compression::None | Compression::Auto => Box::new(file),
     };
 
     let iter = iter_ctor(file);
@@ -407,6 +427,18 @@ pub enum FileReadStyle {
     // TODO: TailFollowName,
 }
 
+/// Wraps a decompressing reader, which can only skip forward by reading and
+/// discarding the decompressed bytes.
+struct Decompressed<R>(R);
+
+impl<R: Read> Skip for Decompressed<R> {}
+
+impl<R: Read> Read for Decompressed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
 /// Wraps a file, producing a stream that is tailed forever.
 ///
 /// This involves silently swallowing EOFs,
@@ -445,6 +477,104 @@ impl<Ev> Read for ForeverTailedFile<Ev> {
     }
 }
 
+/// Splits a stream of bytes into records according to a [`Framing`].
+struct FramedReader<R> {
+    inner: BufReader<R>,
+    framing: Framing,
+}
+
+impl<R: Read> FramedReader<R> {
+    fn new(inner: R, framing: Framing) -> FramedReader<R> {
+        FramedReader {
+            inner: BufReader::new(inner),
+            framing,
+        }
+    }
+
+    /// Reads the next record, or returns `None` if the stream ended cleanly
+    /// between records.
+    fn read_record(&mut self) -> Result<Option<Vec<u8>>, io::Error> {
+        match &self.framing {
+            Framing::Delimited(delimiter) => read_delimited(&mut self.inner, delimiter, 1),
+            Framing::AlignedDelimited { delimiter, width } => {
+                read_delimited(&mut self.inner, delimiter, *width)
+            }
+            Framing::LengthPrefixed(width) => {
+                let prefix = match read_exact_or_eof(&mut self.inner, *width as u64)? {
+                    None => return Ok(None),
+                    Some(prefix) => prefix,
+                };
+                let len = prefix.iter().fold(0, |len, b| (len << 8) | u64::from(*b));
+                match read_exact_or_eof(&mut self.inner, len)? {
+                    None => Err(truncated_record(len, 0)),
+                    record => Ok(record),
+                }
+            }
+            Framing::FixedWidth(width) => read_exact_or_eof(&mut self.inner, *width as u64),
+        }
+    }
+}
+
+impl<R: Read> Iterator for FramedReader<R> {
+    type Item = Result<Vec<u8>, io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Reads up to and including the next occurrence of `delimiter` that starts at
+/// a multiple of `width` bytes into the record, and returns the bytes before
+/// it. Like [`BufRead::split`], the final record need not be followed by a
+/// delimiter.
+///
+/// The length of `delimiter` must be a multiple of `width`.
+fn read_delimited<R: BufRead>(
+    reader: &mut R,
+    delimiter: &[u8],
+    width: usize,
+) -> Result<Option<Vec<u8>>, io::Error> {
+    let last = *delimiter.last().expect("delimiter is non-empty");
+    let mut record = vec![];
+    loop {
+        if reader.read_until(last, &mut record)? == 0 {
+            return Ok(if record.is_empty() {
+                None
+            } else {
+                Some(record)
+            });
+        }
+        if record.len() % width == 0 && record.ends_with(delimiter) {
+            record.truncate(record.len() - delimiter.len());
+            return Ok(Some(record));
+        }
+    }
+}
+
+/// Reads exactly `len` bytes, or returns `None` if the reader is already at
+/// EOF. Hitting EOF partway through the record is an error.
+fn read_exact_or_eof<R: Read>(reader: &mut R, len: u64) -> Result<Option<Vec<u8>>, io::Error> {
+    // Grow the buffer as data arrives rather than trusting `len`, which may
+    // come from a corrupt length prefix.
+    let mut record = vec![];
+    reader.by_ref().take(len).read_to_end(&mut record)?;
+    match record.len() as u64 {
+        0 if len > 0 => Ok(None),
+        n if n == len => Ok(Some(record)),
+        n => Err(truncated_record(len, n)),
+    }
+}
+
+fn truncated_record(expected: u64, actual: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!(
+            "truncated record: expected {} bytes, but got {}",
+            expected, actual
+        ),
+    )
+}
+
 /// Sends a sequence of records and activates a timely operator for each.
 fn send_records<I, Out, Err>(
     iter: I,
@@ -473,3 +603,56 @@ fn send_records<I, Out, Err>(
     }
     log::trace!("sent {} records to reader", records);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(input: &[u8], framing: Framing) -> Vec<Result<Vec<u8>, String>> {
+        FramedReader::new(input, framing)
+            .map(|r| r.map_err(|e| e.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_framed_reader() {
+        assert_eq!(
+            frame(b"a\r\nb\rc\r\n\r\nd", Framing::Delimited(b"\r\n".to_vec())),
+            vec![
+                Ok(b"a".to_vec()),
+                Ok(b"b\rc".to_vec()),
+                Ok(vec![]),
+                Ok(b"d".to_vec())
+            ],
+        );
+        assert_eq!(frame(b"", Framing::default()), vec![]);
+        // U+0A41 U+0100 LF b in UTF-16LE, where the bytes of the LF also
+        // appear across the boundary of the first two code units.
+        assert_eq!(
+            frame(
+                b"\x41\x0a\x00\x01\x0a\x00\x62\x00",
+                Framing::AlignedDelimited {
+                    delimiter: b"\x0a\x00".to_vec(),
+                    width: 2,
+                }
+            ),
+            vec![Ok(b"\x41\x0a\x00\x01".to_vec()), Ok(b"\x62\x00".to_vec())],
+        );
+        assert_eq!(
+            frame(b"\x00\x02ab\x00\x00\x00\x03c", Framing::LengthPrefixed(2)),
+            vec![
+                Ok(b"ab".to_vec()),
+                Ok(vec![]),
+                Err("truncated record: expected 3 bytes, but got 1".into())
+            ],
+        );
+        assert_eq!(
+            frame(b"abcdefg", Framing::FixedWidth(3)),
+            vec![
+                Ok(b"abc".to_vec()),
+                Ok(b"def".to_vec()),
+                Err("truncated record: expected 3 bytes, but got 1".into())
+            ],
+        );
+    }
+}
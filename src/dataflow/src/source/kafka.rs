@@ -8,14 +8,17 @@
 // by the Apache License, Version 2.0.
 
 use std::cmp;
-use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::convert::TryInto;
+use std::error::Error;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
+use chrono::{DateTime, TimeZone, Utc};
 use differential_dataflow::hashable::Hashable;
+use rdkafka::client::OAuthToken;
 use rdkafka::consumer::base_consumer::PartitionQueue;
 use rdkafka::consumer::{BaseConsumer, Consumer, ConsumerContext};
 use rdkafka::error::KafkaError;
@@ -28,17 +31,24 @@ use dataflow_types::{
     DataEncoding, ExternalSourceConnector, KafkaOffset, KafkaSourceConnector, MzOffset,
 };
 use expr::{GlobalId, PartitionId, SourceInstanceId};
-use kafka_util::KafkaAddrs;
+use kafka_util::credentials::{self, CredentialFiles};
+use kafka_util::{oauth, KafkaAddrs};
 use log::{debug, error, info, log_enabled, warn};
 use repr::{CachedRecord, CachedRecordIter, Timestamp};
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
+use crate::faults;
+use crate::logging::materialized::{KafkaSourceStatus, Logger, MaterializedEvent};
+use crate::server::CacheMessage;
 use crate::source::cache::{RecordFileMetadata, WorkerCacheData};
 use crate::source::{
     ConsistencyInfo, NextMessage, PartitionMetrics, SourceConstructor, SourceInfo, SourceMessage,
 };
-use crate::{logging::materialized::Logger, server::CacheMessage};
+
+/// The interval at which the health of each worker's consumer is reported to
+/// the `mz_kafka_source_status` system table.
+const STATUS_REPORTING_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Contains all information necessary to ingest data from Kafka
 pub struct KafkaSourceInfo {
@@ -65,6 +75,36 @@ pub struct KafkaSourceInfo {
     cached_files: Vec<PathBuf>,
     /// Timely worker logger for source events
     logger: Option<Logger>,
+    /// Activator handed to every consumer created for this source
+    consumer_activator: SyncActivator,
+    /// The connector with which the consumer was created
+    connector: KafkaSourceConnector,
+    /// The number of injected connection drops that the consumer has observed
+    connection_drops: u64,
+    /// The credential files that the consumer was created with
+    credential_files: CredentialFiles,
+    /// The consumer status last reported to the system table, if any
+    reported_status: Option<KafkaSourceStatus>,
+    /// The time at which the consumer status was last reported
+    last_status_report: Instant,
+    /// The progress of each partition through the messages that existed when
+    /// the source was created
+    snapshot_partitions: HashMap<i32, SnapshotPartition>,
+}
+
+/// The progress of a partition through the messages that existed when the
+/// source was created.
+///
+/// Progress is measured in offsets rather than messages, as compacted topics
+/// may have gaps in their offsets.
+struct SnapshotPartition {
+    /// The offset from which the source started reading the partition
+    start_offset: i64,
+    /// The highest offset read
+    offset: i64,
+    /// The offset after the latest message in the partition when its high
+    /// watermark was first reported
+    high_watermark: i64,
 }
 
 impl SourceConstructor<Vec<u8>> for KafkaSourceInfo {
@@ -237,6 +277,36 @@ impl SourceInfo<Vec<u8>> for KafkaSourceInfo {
         consistency_info: &mut ConsistencyInfo,
         activator: &Activator,
     ) -> Result<NextMessage<Vec<u8>>, anyhow::Error> {
+        // Reconnect if a connection drop was injected since the consumer last
+        // connected.
+        let connection_drops = faults::connection_drops(&self.topic_name);
+        if connection_drops != self.connection_drops {
+            self.connection_drops = connection_drops;
+            let connector = ExternalSourceConnector::Kafka(self.connector.clone());
+            if let Err(e) = self.reconnect(&connector) {
+                error!(
+                    "failed to reconnect source: {} topic: {} after injected connection drop: {}",
+                    self.source_name, self.topic_name, e
+                );
+            }
+        }
+
+        // Reconnect if credentials were rotated on disk since the consumer
+        // last connected, as librdkafka only reads them on startup.
+        if self.credential_files.changed() {
+            info!(
+                "credentials changed for source: {} topic: {}",
+                self.source_name, self.topic_name
+            );
+            let connector = ExternalSourceConnector::Kafka(self.connector.clone());
+            if let Err(e) = self.reconnect(&connector) {
+                error!(
+                    "failed to reconnect source: {} topic: {} after credential change: {}",
+                    self.source_name, self.topic_name, e
+                );
+            }
+        }
+
         // Poll the consumer once. Since we split the consumer's partitions out into separate queues and poll those individually,
         // we expect this poll to always return None - but it's necessary to drive logic that consumes from rdkafka's internal
         // event queue, such as statistics callbacks.
@@ -253,11 +323,33 @@ impl SourceInfo<Vec<u8>> for KafkaSourceInfo {
             }
         }
 
+        // Record any high watermarks reported by the statistics callback.
+        let high_watermarks = self.consumer.context().take_high_watermarks();
+        for (pid, high_watermark) in high_watermarks {
+            if let Some(partition_metrics) = consistency_info
+                .partition_metrics
+                .get_mut(&PartitionId::Kafka(pid))
+            {
+                partition_metrics.record_high_watermark(high_watermark);
+                self.start_snapshot_partition(consistency_info, pid, high_watermark);
+            }
+        }
+        self.maybe_report_status();
+
         let mut next_message = NextMessage::Pending;
         let consumer_count = self.get_partition_consumers_count();
         let mut attempts = 0;
         while attempts < consumer_count {
             let mut partition_queue = self.partition_consumers.pop_front().unwrap();
+            if faults::partition_stalled(&self.topic_name, partition_queue.pid()) {
+                // Leave the partition's messages in its queue, and check back
+                // later, as no new message will activate us once the stall is
+                // lifted.
+                activator.activate_after(Duration::from_millis(100));
+                self.partition_consumers.push_back(partition_queue);
+                attempts += 1;
+                continue;
+            }
             let message = match partition_queue.get_next_message() {
                 Err(e) => {
                     let pid = partition_queue.pid();
@@ -326,6 +418,7 @@ impl SourceInfo<Vec<u8>> for KafkaSourceInfo {
                     // to read from this consumer again (even if no new data arrives)
                     activator.activate();
                 } else {
+                    self.record_snapshot_progress(consistency_info, &message);
                     next_message = NextMessage::Ready(message);
                 }
             }
@@ -410,6 +503,47 @@ impl SourceInfo<Vec<u8>> for KafkaSourceInfo {
                 .expect("caching receiver should never drop first");
         }
     }
+
+    /// Replaces the Kafka consumer with one built from the new connection
+    /// options, resuming each assigned partition at the consumer's current
+    /// position.
+    fn reconnect(&mut self, connector: &ExternalSourceConnector) -> Result<(), anyhow::Error> {
+        let kc = match connector {
+            ExternalSourceConnector::Kafka(kc) => kc,
+            _ => unreachable!(),
+        };
+        info!(
+            "Reconnecting Kafka consumer for {} (source {}) on worker {}",
+            self.topic_name, self.id, self.worker_id
+        );
+
+        let positions = self.consumer.position()?.to_topic_map();
+        let credential_files = CredentialFiles::new(&kc.config_options);
+        let consumer = Arc::new(create_consumer(
+            &self.source_name,
+            kc,
+            self.consumer_activator.clone(),
+        )?);
+        let mut partition_list = TopicPartitionList::new();
+        for pc in &self.partition_consumers {
+            let offset = match positions.get(&(self.topic_name.clone(), pc.pid)) {
+                Some(Offset::Offset(o)) => Offset::Offset(*o),
+                _ => Offset::Beginning,
+            };
+            partition_list.add_partition_offset(&self.topic_name, pc.pid, offset)?;
+        }
+        consumer.assign(&partition_list)?;
+
+        // Partition queues are tied to the consumer that created them, so
+        // they must be split off from the new consumer.
+        for pc in &mut self.partition_consumers {
+            pc.partition_queue = split_partition_queue(&consumer, &self.topic_name, pc.pid);
+        }
+        self.consumer = consumer;
+        self.connector = kc.clone();
+        self.credential_files = credential_files;
+        Ok(())
+    }
 }
 
 impl KafkaSourceInfo {
@@ -423,28 +557,15 @@ impl KafkaSourceInfo {
         consumer_activator: SyncActivator,
         kc: KafkaSourceConnector,
     ) -> KafkaSourceInfo {
-        let KafkaSourceConnector {
-            addrs,
-            topic,
-            config_options,
-            group_id_prefix,
-            cluster_id,
-            ..
-        } = kc;
         let worker_id = worker_id.try_into().unwrap();
         let worker_count = worker_count.try_into().unwrap();
-        let kafka_config = create_kafka_config(
-            &source_name,
-            &addrs,
-            group_id_prefix,
-            cluster_id,
-            &config_options,
-        );
-        let consumer: BaseConsumer<GlueConsumerContext> = kafka_config
-            .create_with_context(GlueConsumerContext(consumer_activator))
+        let credential_files = CredentialFiles::new(&kc.config_options);
+        let consumer = create_consumer(&source_name, &kc, consumer_activator.clone())
             .expect("Failed to create Kafka Consumer");
+        let connection_drops = faults::connection_drops(&kc.topic);
         let cached_files = kc
             .cached_files
+            .clone()
             .map(|files| {
                 let mut filtered = files
                     .iter()
@@ -487,7 +608,7 @@ impl KafkaSourceInfo {
 
         KafkaSourceInfo {
             buffered_metadata: HashSet::new(),
-            topic_name: topic,
+            topic_name: kc.topic.clone(),
             source_name,
             id: source_id,
             partition_consumers: VecDeque::new(),
@@ -497,6 +618,127 @@ impl KafkaSourceInfo {
             worker_count,
             cached_files,
             logger,
+            consumer_activator,
+            credential_files,
+            connector: kc,
+            connection_drops,
+            reported_status: None,
+            last_status_report: Instant::now(),
+            snapshot_partitions: HashMap::new(),
+        }
+    }
+
+    /// Reports the health of the consumer to the system table, if it has
+    /// changed and the reporting interval has elapsed.
+    fn maybe_report_status(&mut self) {
+        if self.last_status_report.elapsed() < STATUS_REPORTING_INTERVAL {
+            return;
+        }
+        self.last_status_report = Instant::now();
+        let mut status = self.consumer.context().status();
+        status.partitions = i64::from(self.known_partitions);
+        if self.reported_status.as_ref() == Some(&status) {
+            return;
+        }
+        let logger = match self.logger.as_mut() {
+            Some(logger) => logger,
+            None => return,
+        };
+        if let Some(prev) = self.reported_status.take() {
+            logger.log(MaterializedEvent::KafkaSourceStatus {
+                source_name: self.source_name.clone(),
+                source_id: self.id,
+                status: prev,
+                diff: -1,
+            });
+        }
+        logger.log(MaterializedEvent::KafkaSourceStatus {
+            source_name: self.source_name.clone(),
+            source_id: self.id,
+            status: status.clone(),
+            diff: 1,
+        });
+        self.reported_status = Some(status);
+    }
+
+    /// Starts tracking the progress of partition `pid` through the messages
+    /// that existed when the source was created, once its high watermark is
+    /// first known.
+    fn start_snapshot_partition(
+        &mut self,
+        consistency_info: &mut ConsistencyInfo,
+        pid: i32,
+        high_watermark: i64,
+    ) {
+        if consistency_info.snapshot.is_complete() || self.snapshot_partitions.contains_key(&pid) {
+            return;
+        }
+        let partition = PartitionId::Kafka(pid);
+        let start_offset = consistency_info
+            .get_partition_start_offset(&partition)
+            .offset;
+        let offset = match consistency_info.partition_metadata.get(&partition) {
+            Some(metadata) => cmp::min(metadata.offset.offset, high_watermark),
+            None => start_offset,
+        };
+        consistency_info
+            .snapshot
+            .record_processed(cmp::max(offset - start_offset, 0), 0);
+        self.snapshot_partitions.insert(
+            pid,
+            SnapshotPartition {
+                start_offset,
+                offset,
+                high_watermark,
+            },
+        );
+        let records_total = self
+            .snapshot_partitions
+            .values()
+            .map(|p| cmp::max(p.high_watermark - p.start_offset, 0))
+            .sum();
+        consistency_info.snapshot.set_records_total(records_total);
+        self.maybe_complete_snapshot(consistency_info);
+    }
+
+    /// Records the progress made through the messages that existed when the
+    /// source was created by reading `message`.
+    fn record_snapshot_progress(
+        &mut self,
+        consistency_info: &mut ConsistencyInfo,
+        message: &SourceMessage<Vec<u8>>,
+    ) {
+        let pid = match message.partition {
+            PartitionId::Kafka(pid) => pid,
+            _ => unreachable!(),
+        };
+        let partition = match self.snapshot_partitions.get_mut(&pid) {
+            Some(partition) if partition.offset < partition.high_watermark => partition,
+            _ => return,
+        };
+        // Mz offsets are one greater than Kafka offsets, so the message at
+        // the high watermark is the first one beyond the snapshot.
+        let offset = cmp::min(message.offset.offset, partition.high_watermark);
+        let bytes = message.key.as_ref().map_or(0, |k| k.len())
+            + message.payload.as_ref().map_or(0, |p| p.len());
+        consistency_info
+            .snapshot
+            .record_processed(offset - partition.offset, bytes as i64);
+        partition.offset = offset;
+        self.maybe_complete_snapshot(consistency_info);
+    }
+
+    /// Marks the snapshot as complete once every partition assigned to this
+    /// worker has read up to its initial high watermark.
+    fn maybe_complete_snapshot(&self, consistency_info: &mut ConsistencyInfo) {
+        if !self.partition_consumers.is_empty()
+            && self.snapshot_partitions.len() >= self.partition_consumers.len()
+            && self
+                .snapshot_partitions
+                .values()
+                .all(|p| p.offset >= p.high_watermark)
+        {
+            consistency_info.snapshot.complete();
         }
     }
 
@@ -549,16 +791,10 @@ impl KafkaSourceInfo {
         // Since librdkafka v1.6.0, we need to recreate all partition queues
         // after every call to `self.consumer.assign`.
         for pc in &mut self.partition_consumers {
-            pc.partition_queue = self
-                .consumer
-                .split_partition_queue(&self.topic_name, pc.pid)
-                .expect("partition known to be valid");
+            pc.partition_queue = split_partition_queue(&self.consumer, &self.topic_name, pc.pid);
         }
 
-        let partition_queue = self
-            .consumer
-            .split_partition_queue(&self.topic_name, partition_id)
-            .expect("partition known to be valid");
+        let partition_queue = split_partition_queue(&self.consumer, &self.topic_name, partition_id);
         self.partition_consumers
             .push_front(PartitionConsumer::new(partition_id, partition_queue));
         assert_eq!(
@@ -610,6 +846,32 @@ impl KafkaSourceInfo {
     }
 }
 
+/// Creates a Kafka consumer for the source named `name`.
+fn create_consumer(
+    name: &str,
+    kc: &KafkaSourceConnector,
+    activator: SyncActivator,
+) -> Result<BaseConsumer<GlueConsumerContext>, anyhow::Error> {
+    // Statistics are always collected, so that upstream high watermarks
+    // can be reported, but they are only logged if explicitly requested.
+    let log_statistics = kc.config_options.contains_key("statistics.interval.ms");
+    let consumer = create_kafka_config(
+        name,
+        &kc.addrs,
+        kc.group_id_prefix.clone(),
+        kc.cluster_id,
+        &kc.config_options,
+    )?
+    .create_with_context(GlueConsumerContext {
+        activator,
+        topic: kc.topic.clone(),
+        log_statistics,
+        high_watermarks: Mutex::new(HashMap::new()),
+        status: Mutex::new(KafkaSourceStatus::default()),
+    })?;
+    Ok(consumer)
+}
+
 /// Creates a Kafka config.
 fn create_kafka_config(
     name: &str,
@@ -617,7 +879,7 @@ fn create_kafka_config(
     group_id_prefix: Option<String>,
     cluster_id: Uuid,
     config_options: &BTreeMap<String, String>,
-) -> ClientConfig {
+) -> Result<ClientConfig, anyhow::Error> {
     let mut kafka_config = ClientConfig::new();
 
     // Broker configuration.
@@ -643,6 +905,11 @@ fn create_kafka_config(
 
     kafka_config.set("fetch.message.max.bytes", "134217728");
 
+    // How often to emit statistics. The statistics include the high watermark
+    // of each partition, which is used to compute the consumer lag reported in
+    // `mz_source_statistics`. Users may override this interval.
+    kafka_config.set("statistics.interval.ms", "1000"); // 1 second
+
     // Consumer group ID. librdkafka requires this, and we use offset commiting
     // to provide a way for users to monitor ingest progress (though we do not
     // rely on the committed offsets for any functionality)
@@ -678,11 +945,22 @@ fn create_kafka_config(
     // Set additional configuration operations from the user. While these look
     // arbitrary, other layers of the system tightly control which configuration
     // options are allowable.
-    for (k, v) in config_options {
-        kafka_config.set(k, v);
-    }
+    credentials::set_config_options(&mut kafka_config, config_options)?;
 
-    kafka_config
+    Ok(kafka_config)
+}
+
+impl Drop for KafkaSourceInfo {
+    fn drop(&mut self) {
+        if let (Some(logger), Some(status)) = (self.logger.as_mut(), self.reported_status.take()) {
+            logger.log(MaterializedEvent::KafkaSourceStatus {
+                source_name: self.source_name.clone(),
+                source_id: self.id,
+                status,
+                diff: -1,
+            });
+        }
+    }
 }
 
 impl<'a> From<&BorrowedMessage<'a>> for SourceMessage<Vec<u8>> {
@@ -731,7 +1009,12 @@ impl PartitionConsumer {
         } else {
             match self.partition_queue.poll(Duration::from_millis(0)) {
                 Some(Ok(msg)) => {
-                    let result = SourceMessage::from(&msg);
+                    let mut result = SourceMessage::from(&msg);
+                    if faults::take_corruption(msg.topic()) {
+                        if let Some(payload) = &mut result.payload {
+                            faults::corrupt(payload);
+                        }
+                    }
                     assert_eq!(result.partition, PartitionId::Kafka(self.pid));
                     Ok(Some(result))
                 }
@@ -748,29 +1031,111 @@ impl PartitionConsumer {
 }
 
 /// An implementation of [`ConsumerContext`] that unparks the wrapped thread
-/// when the message queue switches from nonempty to empty.
-struct GlueConsumerContext(SyncActivator);
+/// when the message queue switches from nonempty to empty, and that records
+/// the high watermark of each partition and the health of the connection from
+/// the client statistics and errors.
+struct GlueConsumerContext {
+    activator: SyncActivator,
+    /// The topic that the consumer reads from.
+    topic: String,
+    /// Whether to log the client statistics.
+    log_statistics: bool,
+    /// The high watermarks reported since they were last taken, by partition.
+    high_watermarks: Mutex<HashMap<i32, i64>>,
+    /// The health of the connection. The partition count is filled in by the
+    /// source, which tracks partition discovery.
+    status: Mutex<KafkaSourceStatus>,
+}
 
 impl ClientContext for GlueConsumerContext {
+    const ENABLE_REFRESH_OAUTH_TOKEN: bool = true;
+
+    fn generate_oauth_token(&self, config: Option<&str>) -> Result<OAuthToken, Box<dyn Error>> {
+        oauth::generate_token(config)
+    }
+
     fn stats(&self, statistics: Statistics) {
-        info!("Client stats: {:#?}", statistics);
+        let mut high_watermarks = self.high_watermarks.lock().expect("lock poisoned");
+        for topic in statistics.topics.values() {
+            for (pid, partition) in &topic.partitions {
+                // librdkafka reports a partition with ID -1 for messages that
+                // have not yet been assigned a partition.
+                if *pid >= 0 && partition.hi_offset >= 0 {
+                    high_watermarks.insert(*pid, partition.hi_offset);
+                }
+            }
+        }
+        drop(high_watermarks);
+
+        let mut status = self.status.lock().expect("lock poisoned");
+        status.reported = true;
+        // Internal brokers are librdkafka's own bookkeeping, not connections.
+        let brokers = statistics
+            .brokers
+            .values()
+            .filter(|broker| broker.source != "internal");
+        status.brokers = 0;
+        status.brokers_up = 0;
+        for broker in brokers {
+            status.brokers += 1;
+            if broker.state == "UP" {
+                status.brokers_up += 1;
+            }
+        }
+        if let Some(topic) = statistics.topics.get(&self.topic) {
+            let refreshed_ms = statistics.time * 1000 - topic.metadata_age;
+            status.last_metadata_refresh = Some(Utc.timestamp_millis(refreshed_ms));
+        }
+        drop(status);
+
+        if self.log_statistics {
+            info!("Client stats: {:#?}", statistics);
+        }
+    }
+
+    fn error(&self, error: KafkaError, reason: &str) {
+        error!("librdkafka: {}: {}", error, reason);
+        let mut status = self.status.lock().expect("lock poisoned");
+        status.last_error = Some(format!("{}: {}", error, reason));
+        status.last_error_time = Some(DateTime::from(SystemTime::now()));
     }
 }
 
 impl GlueConsumerContext {
     fn activate(&self) {
-        self.0
+        self.activator
             .activate()
             .expect("timely operator hung up while Kafka source active");
     }
-}
 
-impl ConsumerContext for GlueConsumerContext {
-    fn message_queue_nonempty_callback(&self) {
-        self.activate();
+    /// Takes the high watermarks reported since the last call.
+    fn take_high_watermarks(&self) -> HashMap<i32, i64> {
+        std::mem::take(&mut *self.high_watermarks.lock().expect("lock poisoned"))
+    }
+
+    /// Returns the health of the connection as of the last callback.
+    fn status(&self) -> KafkaSourceStatus {
+        self.status.lock().expect("lock poisoned").clone()
     }
 }
 
+impl ConsumerContext for GlueConsumerContext {}
+
+/// Splits the queue for `partition` off of `consumer`, arranging for the
+/// source operator to be activated whenever the queue becomes nonempty.
+fn split_partition_queue(
+    consumer: &Arc<BaseConsumer<GlueConsumerContext>>,
+    topic: &str,
+    partition: i32,
+) -> PartitionQueue<GlueConsumerContext> {
+    let mut partition_queue = consumer
+        .split_partition_queue(topic, partition)
+        .expect("partition known to be valid");
+    let context = Arc::clone(consumer.context());
+    partition_queue.set_nonempty_callback(move || context.activate());
+    partition_queue
+}
+
 // We want to distribute partitions across workers evenly, such that
 // - different partitions for the same source are uniformly distributed across workers
 // - the same partition id across different sources are uniformly distributed across workers
@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::TryInto;
 use std::time::Duration;
 use std::time::Instant;
@@ -72,6 +72,9 @@ pub struct KinesisSourceInfo {
     buffered_messages: VecDeque<SourceMessage<Vec<u8>>>,
     /// Count of processed message
     processed_message_count: i64,
+    /// How far behind the tip of each shard the source was when the shard was
+    /// last read, in milliseconds
+    shard_lag_ms: HashMap<String, i64>,
 }
 
 impl SourceConstructor<Vec<u8>> for KinesisSourceInfo {
@@ -112,6 +115,7 @@ impl SourceConstructor<Vec<u8>> for KinesisSourceInfo {
                 shard_set,
                 stream_name,
                 processed_message_count: 0,
+                shard_lag_ms: HashMap::new(),
             }),
             Err(e) => Err(anyhow!("{}", e)),
         }
@@ -119,6 +123,31 @@ impl SourceConstructor<Vec<u8>> for KinesisSourceInfo {
 }
 
 impl KinesisSourceInfo {
+    /// Records the progress made through the records that existed when the
+    /// source was created upon reading `output`.
+    ///
+    /// Kinesis does not report how many records a shard contains, so progress
+    /// is measured by how far behind the tip of its furthest behind shard the
+    /// source is.
+    fn record_snapshot_progress(
+        &self,
+        consistency_info: &mut ConsistencyInfo,
+        output: &GetRecordsOutput,
+    ) {
+        let bytes: usize = output.records.iter().map(|r| r.data.len()).sum();
+        let tracker = &mut consistency_info.snapshot;
+        tracker.record_processed(output.records.len() as i64, bytes as i64);
+        if self.shard_lag_ms.len() < self.shard_set.len() {
+            // Some shards have not been read yet.
+            return;
+        }
+        let lag_ms = self.shard_lag_ms.values().copied().max().unwrap_or(0);
+        tracker.record_lag(lag_ms);
+        if lag_ms == 0 {
+            tracker.complete();
+        }
+    }
+
     async fn update_shard_information(
         &mut self,
         consistency_info: &mut ConsistencyInfo,
@@ -237,6 +266,7 @@ impl SourceInfo<Vec<u8>> for KinesisSourceInfo {
                                 let shard_metrics: IntGauge = MILLIS_BEHIND_LATEST
                                     .with_label_values(&[&self.stream_name, &shard_id]);
                                 shard_metrics.set(millis);
+                                self.shard_lag_ms.insert(shard_id.clone(), millis);
                             }
                             output
                         }
@@ -279,6 +309,7 @@ impl SourceInfo<Vec<u8>> for KinesisSourceInfo {
                         }
                     };
 
+                    self.record_snapshot_progress(consistency_info, &get_records_output);
                     for record in get_records_output.records {
                         let data = record.data.as_ref().to_vec();
                         self.processed_message_count += 1;
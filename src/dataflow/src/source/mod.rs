@@ -9,9 +9,11 @@
 
 //! Types related to the creation of dataflow sources.
 
+use chrono::{DateTime, Utc};
 use mz_avro::types::Value;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt::{self, Debug};
@@ -39,9 +41,11 @@ use timely::Data;
 use tokio::sync::mpsc;
 
 use super::source::util::source;
-use crate::logging::materialized::{Logger, MaterializedEvent};
+use crate::logging::materialized::{Logger, MaterializedEvent, SnapshotProgress, SourceStatistics};
 use crate::operator::StreamExt;
-use crate::server::{TimestampDataUpdate, TimestampDataUpdates};
+use crate::server::{
+    SourceConnectorUpdates, SourceEventTimes, TimestampDataUpdate, TimestampDataUpdates,
+};
 use crate::CacheMessage;
 
 mod file;
@@ -75,6 +79,10 @@ pub struct SourceConfig<'a, G> {
     // Timestamping fields.
     /// Data-timestamping updates: information about (timestamp, source offset)
     pub timestamp_histories: TimestampDataUpdates,
+    /// Upstream event times of the messages the source has emitted, by timestamp
+    pub event_times: SourceEventTimes,
+    /// Connection options that have been altered since the source was created
+    pub connector_updates: SourceConnectorUpdates,
     /// A source can use Real-Time consistency timestamping or BYO consistency information.
     pub consistency: Consistency,
     /// Source Type
@@ -346,6 +354,16 @@ pub(crate) trait SourceInfo<Out> {
         debug!("unimplemented: this source does not support reading cached files");
         None
     }
+
+    // connection management
+
+    /// Applies new connection options to the source without losing its
+    /// position in the upstream system.
+    fn reconnect(&mut self, _connector: &ExternalSourceConnector) -> Result<(), anyhow::Error> {
+        // Default implementation is to do nothing
+        debug!("unimplemented: this source does not support reconnecting");
+        Ok(())
+    }
 }
 
 pub(crate) enum NextMessage<Out> {
@@ -415,6 +433,8 @@ pub struct ConsistencyInfo {
     source_metrics: SourceMetrics,
     /// Per-partition Prometheus metrics.
     partition_metrics: HashMap<PartitionId, PartitionMetrics>,
+    /// Progress of the source's initial read.
+    pub snapshot: SnapshotTracker,
 }
 
 impl ConsistencyInfo {
@@ -425,6 +445,7 @@ impl ConsistencyInfo {
         consistency: Consistency,
         timestamp_frequency: Duration,
         connector: &ExternalSourceConnector,
+        logger: Option<Logger>,
     ) -> ConsistencyInfo {
         let start_offsets = match connector {
             ExternalSourceConnector::Kafka(kc) => {
@@ -455,6 +476,7 @@ impl ConsistencyInfo {
             // we have never downgraded, so make sure the initial value is outside of our frequency
             time_since_downgrade: Instant::now() - timestamp_frequency - Duration::from_secs(1),
             partition_metrics: Default::default(),
+            snapshot: SnapshotTracker::new(&source_name, source_id, logger),
         }
     }
 
@@ -721,6 +743,10 @@ impl SourceMetrics {
     }
 }
 
+/// The interval at which partition ingestion statistics are reported to the
+/// `mz_source_statistics` system table.
+const STATISTICS_REPORTING_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Partition-specific metrics, recorded to both Prometheus and a system table
 pub struct PartitionMetrics {
     /// Highest offset that has been received by the source and timestamped
@@ -731,12 +757,24 @@ pub struct PartitionMetrics {
     closed_ts: DeleteOnDropGauge<'static, AtomicU64>,
     /// Total number of messages that have been received by the source and timestamped
     messages_ingested: DeleteOnDropCounter<'static, AtomicI64>,
+    /// Total number of bytes that have been received by the source and timestamped
+    bytes_ingested: DeleteOnDropCounter<'static, AtomicI64>,
+    /// The offset after the latest message in the upstream partition
+    high_watermark: DeleteOnDropGauge<'static, AtomicI64>,
+    /// Number of messages in the upstream partition that have not yet been ingested
+    consumer_lag: DeleteOnDropGauge<'static, AtomicI64>,
     logger: Option<Logger>,
     source_name: String,
     source_id: SourceInstanceId,
     partition_id: String,
     last_offset: i64,
     last_timestamp: i64,
+    /// The ingestion statistics accumulated since the partition was created
+    statistics: SourceStatistics,
+    /// The ingestion statistics last reported to the system table, if any
+    reported_statistics: Option<SourceStatistics>,
+    /// The time at which ingestion statistics were last reported
+    last_reported: Instant,
 }
 
 impl PartitionMetrics {
@@ -755,6 +793,76 @@ impl PartitionMetrics {
         self.last_timestamp = timestamp;
     }
 
+    /// Record the ingestion of a message of `bytes` bytes at `offset`
+    pub fn record_message(&mut self, offset: i64, bytes: i64) {
+        self.offset_ingested.set(offset);
+        self.messages_ingested.inc();
+        self.bytes_ingested.inc_by(bytes);
+        self.statistics.messages_ingested += 1;
+        self.statistics.bytes_ingested += bytes;
+        self.statistics.offset = offset;
+        self.update_consumer_lag();
+    }
+
+    /// Record the offset after the latest message in the upstream partition
+    pub fn record_high_watermark(&mut self, high_watermark: i64) {
+        self.high_watermark.set(high_watermark);
+        self.statistics.high_watermark = Some(high_watermark);
+        self.update_consumer_lag();
+    }
+
+    fn update_consumer_lag(&mut self) {
+        if let Some(high_watermark) = self.statistics.high_watermark {
+            self.consumer_lag
+                .set(cmp::max(high_watermark - self.statistics.offset, 0));
+        }
+    }
+
+    /// Report the ingestion statistics to the system table, if they have
+    /// changed and the reporting interval has elapsed
+    pub fn maybe_report_statistics(&mut self) {
+        let elapsed = self.last_reported.elapsed();
+        if elapsed < STATISTICS_REPORTING_INTERVAL {
+            return;
+        }
+        let logger = match self.logger.as_mut() {
+            Some(logger) => logger,
+            None => return,
+        };
+
+        let mut statistics = self.statistics;
+        let (prev_messages, prev_bytes) = match &self.reported_statistics {
+            Some(prev) => (prev.messages_ingested, prev.bytes_ingested),
+            None => (0, 0),
+        };
+        let elapsed_ms = cmp::max(elapsed.as_millis() as i64, 1);
+        statistics.messages_per_second =
+            (statistics.messages_ingested - prev_messages) * 1000 / elapsed_ms;
+        statistics.bytes_per_second = (statistics.bytes_ingested - prev_bytes) * 1000 / elapsed_ms;
+        self.last_reported = Instant::now();
+
+        if self.reported_statistics == Some(statistics) {
+            return;
+        }
+        if let Some(prev) = self.reported_statistics.take() {
+            logger.log(MaterializedEvent::SourceStatistics {
+                source_name: self.source_name.clone(),
+                source_id: self.source_id,
+                partition_id: self.partition_id.clone(),
+                statistics: prev,
+                diff: -1,
+            });
+        }
+        logger.log(MaterializedEvent::SourceStatistics {
+            source_name: self.source_name.clone(),
+            source_id: self.source_id,
+            partition_id: self.partition_id.clone(),
+            statistics,
+            diff: 1,
+        });
+        self.reported_statistics = Some(statistics);
+    }
+
     /// Initialises partition metrics for a given (source_id, partition_id)
     pub fn new(
         source_name: &str,
@@ -788,6 +896,25 @@ impl PartitionMetrics {
                 &["topic", "source_id", "partition_id"]
             )
             .unwrap();
+            static ref BYTES_INGESTED: IntCounterVec = register_int_counter_vec!(
+                "mz_partition_bytes_ingested",
+                "The number of bytes ingested per partition.",
+                &["topic", "source_id", "partition_id"]
+            )
+            .unwrap();
+            static ref HIGH_WATERMARK: IntGaugeVec = register_int_gauge_vec!(
+                "mz_partition_high_watermark",
+                "The offset after the latest message in the upstream partition, as last reported \
+                by the upstream system.",
+                &["topic", "source_id", "partition_id"]
+            )
+            .unwrap();
+            static ref CONSUMER_LAG: IntGaugeVec = register_int_gauge_vec!(
+                "mz_partition_consumer_lag",
+                "The number of messages in the upstream partition that have not yet been ingested.",
+                &["topic", "source_id", "partition_id"]
+            )
+            .unwrap();
         }
         let labels = &[source_name, &source_id.to_string(), partition_id];
         PartitionMetrics {
@@ -811,12 +938,30 @@ impl PartitionMetrics {
                 &MESSAGES_INGESTED,
                 |e, v| log::debug!("unable to delete metric {}: {}", v.fq_name(), e),
             ),
+            bytes_ingested: DeleteOnDropCounter::new_with_error_handler(
+                BYTES_INGESTED.with_label_values(labels),
+                &BYTES_INGESTED,
+                |e, v| log::debug!("unable to delete metric {}: {}", v.fq_name(), e),
+            ),
+            high_watermark: DeleteOnDropGauge::new_with_error_handler(
+                HIGH_WATERMARK.with_label_values(labels),
+                &HIGH_WATERMARK,
+                |e, v| log::debug!("unable to delete metric {}: {}", v.fq_name(), e),
+            ),
+            consumer_lag: DeleteOnDropGauge::new_with_error_handler(
+                CONSUMER_LAG.with_label_values(labels),
+                &CONSUMER_LAG,
+                |e, v| log::debug!("unable to delete metric {}: {}", v.fq_name(), e),
+            ),
             logger,
             source_name: source_name.to_string(),
             source_id,
             partition_id: partition_id.to_string(),
             last_offset: 0,
             last_timestamp: 0,
+            statistics: SourceStatistics::default(),
+            reported_statistics: None,
+            last_reported: Instant::now(),
         }
     }
 }
@@ -832,6 +977,192 @@ impl Drop for PartitionMetrics {
                 offset: -self.last_offset,
                 timestamp: -self.last_timestamp,
             });
+            if let Some(statistics) = self.reported_statistics.take() {
+                logger.log(MaterializedEvent::SourceStatistics {
+                    source_name: self.source_name.clone(),
+                    source_id: self.source_id,
+                    partition_id: self.partition_id.clone(),
+                    statistics,
+                    diff: -1,
+                });
+            }
+        }
+    }
+}
+
+/// The interval at which the progress of a source's initial read is reported to
+/// the `mz_source_snapshot_progress` system table.
+const SNAPSHOT_PROGRESS_REPORTING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Tracks the progress of a source's initial read of the data that existed
+/// upstream when the source was created, recorded to a system table.
+///
+/// Sources that can estimate the size of their initial read report their
+/// progress via the methods on this type. Nothing is recorded for sources that
+/// never do so. Updates are ignored once the initial read is complete.
+pub struct SnapshotTracker {
+    logger: Option<Logger>,
+    source_name: String,
+    source_id: SourceInstanceId,
+    /// When the source was created
+    started: Instant,
+    started_at: DateTime<Utc>,
+    /// The progress accumulated so far, if the source has reported any
+    progress: Option<SnapshotProgress>,
+    /// The progress last reported to the system table, if any
+    reported: Option<SnapshotProgress>,
+    /// The time at which progress was last reported
+    last_reported: Instant,
+}
+
+impl SnapshotTracker {
+    /// Starts tracking the initial read of a source
+    pub fn new(source_name: &str, source_id: SourceInstanceId, logger: Option<Logger>) -> Self {
+        SnapshotTracker {
+            logger,
+            source_name: source_name.to_string(),
+            source_id,
+            started: Instant::now(),
+            started_at: Utc::now(),
+            progress: None,
+            reported: None,
+            last_reported: Instant::now(),
+        }
+    }
+
+    /// Returns the progress to update, or `None` if the initial read is
+    /// already complete
+    fn progress_mut(&mut self) -> Option<&mut SnapshotProgress> {
+        let started_at = self.started_at;
+        let progress = self.progress.get_or_insert_with(|| SnapshotProgress {
+            complete: false,
+            records_processed: 0,
+            records_total: None,
+            bytes_processed: 0,
+            bytes_total: None,
+            initial_lag_ms: None,
+            lag_ms: None,
+            started_at,
+            last_progress_at: None,
+            estimated_completion: None,
+        });
+        if progress.complete {
+            None
+        } else {
+            Some(progress)
+        }
+    }
+
+    /// Returns whether the source has reported that its initial read is
+    /// complete
+    pub fn is_complete(&self) -> bool {
+        self.progress.as_ref().map_or(false, |p| p.complete)
+    }
+
+    /// Record the reading of `records` records, totaling `bytes` bytes, as
+    /// part of the initial read
+    pub fn record_processed(&mut self, records: i64, bytes: i64) {
+        if let Some(progress) = self.progress_mut() {
+            progress.records_processed += records;
+            progress.bytes_processed += bytes;
+            if records > 0 || bytes > 0 {
+                progress.last_progress_at = Some(Utc::now());
+            }
+        }
+    }
+
+    /// Record the estimated number of records in the initial read
+    pub fn set_records_total(&mut self, total: i64) {
+        if let Some(progress) = self.progress_mut() {
+            progress.records_total = Some(total);
+        }
+    }
+
+    /// Record the estimated number of bytes in the initial read
+    pub fn set_bytes_total(&mut self, total: i64) {
+        if let Some(progress) = self.progress_mut() {
+            progress.bytes_total = Some(total);
+        }
+    }
+
+    /// Record how far behind the upstream system the source is, for sources
+    /// that cannot estimate the size of their initial read
+    pub fn record_lag(&mut self, lag_ms: i64) {
+        if let Some(progress) = self.progress_mut() {
+            if progress.lag_ms.map_or(false, |prev| lag_ms < prev) {
+                progress.last_progress_at = Some(Utc::now());
+            }
+            progress.initial_lag_ms.get_or_insert(lag_ms);
+            progress.lag_ms = Some(lag_ms);
+        }
+    }
+
+    /// Record that the initial read is complete
+    pub fn complete(&mut self) {
+        if let Some(progress) = self.progress_mut() {
+            progress.complete = true;
+            progress.lag_ms = progress.lag_ms.map(|_| 0);
+        }
+    }
+
+    /// Report the progress to the system table, if it has changed and the
+    /// reporting interval has elapsed
+    pub fn maybe_report(&mut self) {
+        if self.last_reported.elapsed() < SNAPSHOT_PROGRESS_REPORTING_INTERVAL {
+            return;
+        }
+        self.last_reported = Instant::now();
+        let logger = match self.logger.as_mut() {
+            Some(logger) => logger,
+            None => return,
+        };
+        let mut progress = match &self.progress {
+            Some(progress) => progress.clone(),
+            None => return,
+        };
+
+        // Extrapolate the time at which the initial read will complete from
+        // the rate of progress so far, to the nearest second.
+        progress.estimated_completion = match progress.fraction() {
+            Some(fraction) if !progress.complete && fraction > 0.0 => {
+                let elapsed_secs = self.started.elapsed().as_secs_f64();
+                let expected_secs = (elapsed_secs / fraction) as i64;
+                Some(progress.started_at + chrono::Duration::seconds(expected_secs))
+            }
+            _ => None,
+        };
+
+        if self.reported.as_ref() == Some(&progress) {
+            return;
+        }
+        if let Some(prev) = self.reported.take() {
+            logger.log(MaterializedEvent::SourceSnapshotProgress {
+                source_name: self.source_name.clone(),
+                source_id: self.source_id,
+                progress: prev,
+                diff: -1,
+            });
+        }
+        logger.log(MaterializedEvent::SourceSnapshotProgress {
+            source_name: self.source_name.clone(),
+            source_id: self.source_id,
+            progress: progress.clone(),
+            diff: 1,
+        });
+        self.reported = Some(progress);
+    }
+}
+
+impl Drop for SnapshotTracker {
+    fn drop(&mut self) {
+        // retract our progress from logging
+        if let (Some(logger), Some(progress)) = (self.logger.as_mut(), self.reported.take()) {
+            logger.log(MaterializedEvent::SourceSnapshotProgress {
+                source_name: self.source_name.clone(),
+                source_id: self.source_id,
+                progress,
+                diff: -1,
+            });
         }
     }
 }
@@ -865,6 +1196,8 @@ where
         id,
         scope,
         timestamp_histories,
+        event_times,
+        connector_updates,
         worker_id,
         worker_count,
         consistency,
@@ -887,6 +1220,7 @@ where
             consistency,
             timestamp_frequency,
             &source_connector,
+            logger.clone(),
         );
 
         // Create source information (this function is specific to a specific
@@ -906,6 +1240,7 @@ where
 
         let mut read_cached_files = false;
         let mut predecessor = None;
+        let mut source_connector = source_connector;
 
         move |cap, output| {
             // First check that the source was successfully created
@@ -921,6 +1256,24 @@ where
                 return SourceStatus::Done;
             }
 
+            // Pick up any connection options altered since the last activation.
+            // The coordinator verifies that the new options connect before it
+            // accepts them, so a failure here is transient. The source keeps
+            // reading with its old connection, and does not retry until the
+            // options change again.
+            if let Some(connector) = connector_updates.borrow().get(&id.source_id) {
+                if *connector != source_connector {
+                    if let Err(e) = source_info.reconnect(connector) {
+                        error!(
+                            "Failed to reconnect source {}, continuing with its previous \
+                             connection options: {}",
+                            name, e
+                        );
+                    }
+                    source_connector = connector.clone();
+                }
+            }
+
             // Downgrade capability (if possible)
             consistency_info.downgrade_capability(&id, cap, source_info, &timestamp_histories);
 
@@ -1023,8 +1376,9 @@ where
                                     .get_mut(&partition)
                                     .unwrap()
                                     .offset = offset;
-                                bytes_read += key.len() as i64;
-                                bytes_read += out.len().unwrap_or(0) as i64;
+                                let message_bytes =
+                                    key.len() as i64 + out.len().unwrap_or(0) as i64;
+                                bytes_read += message_bytes;
                                 let ts_cap = cap.delayed(&ts);
 
                                 output.session(&ts_cap).give(Ok(SourceOutput::new(
@@ -1033,6 +1387,15 @@ where
                                     Some(offset.offset),
                                     message.upstream_time_millis,
                                 )));
+                                if let Some(upstream_time) = message.upstream_time_millis {
+                                    let mut event_times = event_times.borrow_mut();
+                                    let latest = event_times
+                                        .entry(id.source_id)
+                                        .or_default()
+                                        .entry(ts)
+                                        .or_insert(upstream_time);
+                                    *latest = (*latest).max(upstream_time);
+                                }
 
                                 // Update ingestion metrics
                                 // Entry is guaranteed to exist as it gets created when we initialise the partition
@@ -1040,8 +1403,7 @@ where
                                     .partition_metrics
                                     .get_mut(&partition)
                                     .unwrap();
-                                partition_metrics.offset_ingested.set(offset.offset);
-                                partition_metrics.messages_ingested.inc();
+                                partition_metrics.record_message(offset.offset, message_bytes);
 
                                 metric_updates.insert(partition, (offset, ts));
 
@@ -1083,6 +1445,10 @@ where
                     .unwrap();
                 partition_metrics.record_offset(offset.offset, ts as i64);
             }
+            for partition_metrics in consistency_info.partition_metrics.values_mut() {
+                partition_metrics.maybe_report_statistics();
+            }
+            consistency_info.snapshot.maybe_report();
 
             // Downgrade capability (if possible) before exiting
             consistency_info.downgrade_capability(&id, cap, source_info, &timestamp_histories);
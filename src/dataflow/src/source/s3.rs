@@ -10,12 +10,18 @@
 //! Functionality for creating S3 sources
 
 use std::collections::{HashMap, HashSet};
-use std::convert::{From, TryInto};
+use std::convert::From;
 use std::default::Default;
+use std::io;
 use std::ops::AddAssign;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering};
 use std::sync::mpsc::{Receiver, SyncSender, TryRecvError};
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use anyhow::{anyhow, Error};
+use async_compression::tokio::bufread::{BzDecoder, GzipDecoder, ZstdDecoder};
 use globset::GlobMatcher;
 use metrics::BucketMetrics;
 use notifications::Event;
@@ -25,12 +31,12 @@ use rusoto_sqs::{
     DeleteMessageRequest, GetQueueUrlRequest, ReceiveMessageRequest, Sqs,
 };
 use timely::scheduling::{Activator, SyncActivator};
-use tokio::io::AsyncReadExt;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, BufReader, ReadBuf};
 use tokio::sync::mpsc as tokio_mpsc;
 use tokio::time::{self, Duration};
 
 use aws_util::aws;
-use dataflow_types::{DataEncoding, ExternalSourceConnector, MzOffset, S3KeySource};
+use dataflow_types::{Compression, DataEncoding, ExternalSourceConnector, MzOffset, S3KeySource};
 use expr::{PartitionId, SourceInstanceId};
 
 use crate::logging::materialized::Logger;
@@ -45,6 +51,11 @@ mod metrics;
 mod notifications;
 
 type Out = Vec<u8>;
+
+/// The number of records to send from an object between activations of the
+/// reader.
+const ACTIVATION_INTERVAL: u64 = 1024;
+
 struct InternalMessage {
     record: Out,
 }
@@ -65,6 +76,43 @@ pub struct S3SourceInfo {
     buffer: Option<SourceMessage<Out>>,
     /// Total number of records that this source has read
     offset: S3Offset,
+    /// Progress of the bucket scans, if the source scans any buckets
+    snapshot: Option<Arc<SnapshotCounters>>,
+    /// The number of bytes of scanned objects last recorded as processed
+    snapshot_bytes_recorded: i64,
+}
+
+/// Counters shared between the tasks that read an S3 source, which track the
+/// progress of downloading the objects found by bucket scans.
+#[derive(Debug, Default)]
+struct SnapshotCounters {
+    /// Number of bucket scans that have not yet finished
+    scans_pending: AtomicUsize,
+    /// Number of objects found by bucket scans
+    objects_total: AtomicI64,
+    /// Total size of the objects found by bucket scans
+    bytes_total: AtomicI64,
+    /// Number of objects found by bucket scans that have been downloaded
+    objects_done: AtomicI64,
+    /// Total size of the objects found by bucket scans that have been
+    /// downloaded
+    bytes_done: AtomicI64,
+}
+
+impl SnapshotCounters {
+    /// Records that a scanned object of `size` bytes has been downloaded
+    fn object_done(&self, size: i64) {
+        self.objects_done.fetch_add(1, Ordering::Relaxed);
+        self.bytes_done.fetch_add(size, Ordering::Relaxed);
+    }
+
+    /// Returns whether all scans have finished and all objects they found
+    /// have been downloaded
+    fn is_complete(&self) -> bool {
+        self.scans_pending.load(Ordering::Acquire) == 0
+            && self.objects_done.load(Ordering::Relaxed)
+                >= self.objects_total.load(Ordering::Relaxed)
+    }
 }
 
 /// Number of records This source has downloaded
@@ -106,6 +154,19 @@ impl SourceConstructor<Vec<u8>> for S3SourceInfo {
             }
         };
 
+        let scans = s3_conn
+            .key_sources
+            .iter()
+            .filter(|ks| matches!(ks, S3KeySource::Scan { .. }))
+            .count();
+        let snapshot = if active && scans > 0 {
+            let counters = SnapshotCounters::default();
+            counters.scans_pending.store(scans, Ordering::Release);
+            Some(Arc::new(counters))
+        } else {
+            None
+        };
+
         // a single arbitrary worker is responsible for scanning the bucket
         let receiver = if active {
             let (dataflow_tx, dataflow_rx) = std::sync::mpsc::sync_channel(10_000);
@@ -118,6 +179,8 @@ impl SourceConstructor<Vec<u8>> for S3SourceInfo {
                 dataflow_tx,
                 aws_info.clone(),
                 consumer_activator,
+                s3_conn.compression,
+                snapshot.clone(),
             ));
             for key_source in s3_conn.key_sources {
                 match key_source {
@@ -129,6 +192,7 @@ impl SourceConstructor<Vec<u8>> for S3SourceInfo {
                             glob.clone(),
                             aws_info.clone(),
                             keys_tx.clone(),
+                            snapshot.clone().expect("bucket scans are tracked"),
                         ));
                     }
                     S3KeySource::SqsNotifications { queue } => {
@@ -162,6 +226,8 @@ impl SourceConstructor<Vec<u8>> for S3SourceInfo {
             receiver_stream: receiver,
             buffer: None,
             offset: S3Offset(0),
+            snapshot,
+            snapshot_bytes_recorded: 0,
         })
     }
 }
@@ -169,6 +235,8 @@ impl SourceConstructor<Vec<u8>> for S3SourceInfo {
 struct KeyInfo {
     bucket: String,
     key: String,
+    /// The size of the object, if it was found by a bucket scan
+    scanned_size: Option<i64>,
 }
 
 async fn download_objects_task(
@@ -177,6 +245,8 @@ async fn download_objects_task(
     tx: SyncSender<anyhow::Result<InternalMessage>>,
     aws_info: aws::ConnectInfo,
     activator: SyncActivator,
+    compression: Compression,
+    snapshot: Option<Arc<SnapshotCounters>>,
 ) {
     let client = match aws_util::client::s3(aws_info).await {
         Ok(client) => client,
@@ -198,10 +268,17 @@ async fn download_objects_task(
     while let Some(msg) = rx.recv().await {
         match msg {
             Ok(msg) => {
+                let snapshot_object = match (&snapshot, msg.scanned_size) {
+                    (Some(snapshot), Some(size)) => Some((snapshot, size)),
+                    _ => None,
+                };
                 if let Some(bi) = seen_buckets.get_mut(&msg.bucket) {
                     let is_new = bi.keys.insert(msg.key.clone());
                     if !is_new {
                         bi.metrics.objects_duplicate.inc();
+                        if let Some((snapshot, size)) = snapshot_object {
+                            snapshot.object_done(size);
+                        }
                         continue;
                     }
                 } else {
@@ -214,8 +291,18 @@ async fn download_objects_task(
                     seen_buckets.insert(msg.bucket.clone(), bi);
                 };
 
-                let update =
-                    download_object(&tx, &activator, &client, msg.bucket.clone(), msg.key).await;
+                let update = download_object(
+                    &tx,
+                    &activator,
+                    &client,
+                    msg.bucket.clone(),
+                    msg.key,
+                    &compression,
+                )
+                .await;
+                if let Some((snapshot, size)) = snapshot_object {
+                    snapshot.object_done(size);
+                }
 
                 if let Some(update) = update {
                     seen_buckets
@@ -245,6 +332,7 @@ async fn scan_bucket_task(
     glob: Option<GlobMatcher>,
     aws_info: aws::ConnectInfo,
     tx: tokio_mpsc::Sender<anyhow::Result<KeyInfo>>,
+    snapshot: Arc<SnapshotCounters>,
 ) {
     let client = match aws_util::client::s3(aws_info).await {
         Ok(client) => client,
@@ -254,6 +342,7 @@ async fn scan_bucket_task(
                 .unwrap_or_else(|e| {
                     log::debug!("unable to send error on stream creating s3 client: {}", e)
                 });
+            snapshot.scans_pending.fetch_sub(1, Ordering::Release);
             return;
         }
     };
@@ -280,16 +369,19 @@ async fn scan_bucket_task(
                 allowed_errors = 10;
 
                 if let Some(c) = response.contents {
-                    let keys = c
+                    let objects = c
                         .into_iter()
-                        .filter_map(|obj| obj.key)
-                        .filter(|k| glob.map(|g| g.is_match(k)).unwrap_or(true));
+                        .filter_map(|obj| obj.key.map(|key| (key, obj.size.unwrap_or(0))))
+                        .filter(|(k, _)| glob.map(|g| g.is_match(k)).unwrap_or(true));
 
-                    for key in keys {
+                    for (key, size) in objects {
+                        snapshot.objects_total.fetch_add(1, Ordering::Relaxed);
+                        snapshot.bytes_total.fetch_add(size, Ordering::Relaxed);
                         let res = tx
                             .send(Ok(KeyInfo {
                                 bucket: bucket.clone(),
                                 key,
+                                scanned_size: Some(size),
                             }))
                             .await;
 
@@ -326,6 +418,7 @@ async fn scan_bucket_task(
             }
         }
     }
+    snapshot.scans_pending.fetch_sub(1, Ordering::Release);
     log::debug!(
         "exiting bucket scan task source_id={} bucket={}",
         source_id,
@@ -502,6 +595,7 @@ async fn process_message(
                             let ki = Ok(KeyInfo {
                                 bucket: record.s3.bucket.name,
                                 key,
+                                scanned_size: None,
                             });
                             if tx.send(ki).await.is_err() {
                                 log::info!("sqs reader is closed, marking message as visible");
@@ -563,11 +657,12 @@ async fn download_object(
     client: &S3Client,
     bucket: String,
     key: String,
+    compression: &Compression,
 ) -> Option<DownloadMetricUpdate> {
     let obj = match client
         .get_object(GetObjectRequest {
             bucket: bucket.clone(),
-            key,
+            key: key.clone(),
             ..Default::default()
         })
         .await
@@ -581,38 +676,67 @@ async fn download_object(
     };
 
     if let Some(body) = obj.body {
-        let mut reader = body.into_async_read();
-        // unwrap is safe because content length is not allowed to be negative
-        let mut buf = Vec::with_capacity(obj.content_length.unwrap_or(1024).try_into().unwrap());
+        // Objects are streamed through the decompressor a line at a time, so
+        // that large objects need not fit in memory. Like the file source, we
+        // accept concatenations of compressed streams.
+        let bytes = Arc::new(AtomicU64::new(0));
+        let reader = BufReader::new(CountingReader {
+            inner: Box::pin(body.into_async_read()),
+            bytes: Arc::clone(&bytes),
+        });
+        let mut reader: Pin<Box<dyn AsyncBufRead + Send>> = match compression.resolve(&key) {
+            Compression::Gzip => {
+                let mut decoder = GzipDecoder::new(reader);
+                decoder.multiple_members(true);
+                Box::pin(BufReader::new(decoder))
+            }
+            Compression::Zstd => {
+                let mut decoder = ZstdDecoder::new(reader);
+                decoder.multiple_members(true);
+                Box::pin(BufReader::new(decoder))
+            }
+            Compression::Bzip2 => {
+                let mut decoder = BzDecoder::new(reader);
+                decoder.multiple_members(true);
+                Box::pin(BufReader::new(decoder))
+            }
+            Compression::None | Compression::Auto => Box::pin(reader),
+        };
         let mut messages = 0;
 
         let mut sent = Sent::Success;
-        match reader.read_to_end(&mut buf).await {
-            Ok(_) => {
-                let activate = !buf.is_empty();
-                for line in buf.split(|b| *b == b'\n').map(|s| s.to_vec()) {
+        loop {
+            match read_line(&mut reader).await {
+                Ok((line, last)) => {
                     if tx.send(Ok(InternalMessage { record: line })).is_err() {
                         sent = Sent::SenderClosed;
                         break;
-                    } else {
-                        messages += 1;
+                    }
+                    messages += 1;
+                    if last {
+                        break;
+                    }
+                    if messages % ACTIVATION_INTERVAL == 0 {
+                        activator.activate().expect("s3 reader activation failed");
                     }
                 }
-                log::trace!("sent {} messages to reader", messages);
-                if activate {
-                    activator.activate().expect("s3 reader activation failed");
-                }
-            }
-            Err(e) => {
-                if let Err(e) = tx.send(Err(anyhow!("Unable to read object: {}", e))) {
-                    log::debug!("unable to send error on stream: {}", e);
-                    sent = Sent::SenderClosed;
+                Err(e) => {
+                    if let Err(e) = tx.send(Err(anyhow!("Unable to read object: {}", e))) {
+                        log::debug!("unable to send error on stream: {}", e);
+                        sent = Sent::SenderClosed;
+                    }
+                    break;
                 }
             }
         }
+        log::trace!("sent {} messages to reader", messages);
+        let bytes = bytes.load(Ordering::Relaxed);
+        if bytes > 0 {
+            activator.activate().expect("s3 reader activation failed");
+        }
 
         Some(DownloadMetricUpdate {
-            bytes: buf.len() as u64,
+            bytes,
             messages,
             sent,
         })
@@ -622,16 +746,88 @@ async fn download_object(
     }
 }
 
+/// Reads the next line of an object, without its newline, and reports whether
+/// it is the object's last line.
+///
+/// Every newline ends a line, so an object that ends with a newline, like an
+/// empty object, ends with an empty line.
+async fn read_line<R>(reader: &mut R) -> Result<(Vec<u8>, bool), io::Error>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut line = vec![];
+    reader.read_until(b'\n', &mut line).await?;
+    let last = line.last() != Some(&b'\n');
+    if !last {
+        line.pop();
+    }
+    Ok((line, last))
+}
+
+/// Counts the bytes read from an object as they arrive, before they are
+/// decompressed.
+struct CountingReader<R> {
+    inner: R,
+    bytes: Arc<AtomicU64>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CountingReader<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        let read = buf.filled().len() - filled;
+        self.bytes.fetch_add(read as u64, Ordering::Relaxed);
+        poll
+    }
+}
+
+impl S3SourceInfo {
+    /// Records the progress made through the objects found by bucket scans
+    /// upon receiving `message` from the download task.
+    fn record_snapshot_progress(
+        &mut self,
+        consistency_info: &mut ConsistencyInfo,
+        message: &Result<anyhow::Result<InternalMessage>, TryRecvError>,
+    ) {
+        let snapshot = match &self.snapshot {
+            Some(snapshot) => snapshot,
+            None => return,
+        };
+        let records = match message {
+            Ok(Ok(_)) => 1,
+            _ => 0,
+        };
+        let bytes_done = snapshot.bytes_done.load(Ordering::Relaxed);
+        // The number of records in each object is not known until it is
+        // downloaded, so progress is measured in bytes.
+        let tracker = &mut consistency_info.snapshot;
+        tracker.set_bytes_total(snapshot.bytes_total.load(Ordering::Relaxed));
+        tracker.record_processed(records, bytes_done - self.snapshot_bytes_recorded);
+        self.snapshot_bytes_recorded = bytes_done;
+        // Only consider the scans complete once all records from the objects
+        // they found have been received.
+        if message.is_err() && snapshot.is_complete() {
+            tracker.complete();
+        }
+    }
+}
+
 impl SourceInfo<Vec<u8>> for S3SourceInfo {
     fn get_next_message(
         &mut self,
-        _consistency_info: &mut ConsistencyInfo,
+        consistency_info: &mut ConsistencyInfo,
         _activator: &Activator,
     ) -> Result<NextMessage<Out>, anyhow::Error> {
         if let Some(message) = self.buffer.take() {
             return Ok(NextMessage::Ready(message));
         }
-        match self.receiver_stream.try_recv() {
+        let message = self.receiver_stream.try_recv();
+        self.record_snapshot_progress(consistency_info, &message);
+        match message {
             Ok(Ok(InternalMessage { record })) => {
                 self.offset += 1;
                 Ok(NextMessage::Ready(SourceMessage {
@@ -782,6 +978,29 @@ fn find_prefix(glob: &str) -> String {
 mod test {
     use super::*;
 
+    #[test]
+    fn split_lines() {
+        fn lines(mut object: &[u8]) -> Vec<String> {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+            let mut lines = vec![];
+            loop {
+                let (line, last) = runtime.block_on(read_line(&mut object)).unwrap();
+                lines.push(String::from_utf8(line).unwrap());
+                if last {
+                    break;
+                }
+            }
+            lines
+        }
+
+        assert_eq!(lines(b"a\nb"), vec!["a", "b"]);
+        assert_eq!(lines(b"a\nb\n"), vec!["a", "b", ""]);
+        assert_eq!(lines(b"a\n\nb"), vec!["a", "", "b"]);
+        assert_eq!(lines(b""), vec![""]);
+    }
+
     #[test]
     fn glob_prefix() {
         assert_eq!(&find_prefix("foo/**"), "foo/");
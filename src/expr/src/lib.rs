@@ -30,8 +30,8 @@ pub use relation::func::{AggregateFunc, TableFunc};
 pub use relation::func::{AnalyzedRegex, CaptureGroupDesc};
 pub use relation::join_input_mapper::JoinInputMapper;
 pub use relation::{
-    compare_columns, AggregateExpr, ColumnOrder, IdGen, JoinImplementation, MirRelationExpr,
-    RowSetFinishing,
+    compare_columns, AggregateExpr, ColumnOrder, ForeignKey, IdGen, JoinImplementation,
+    MirRelationExpr, RowSetFinishing,
 };
 pub use scalar::func::{BinaryFunc, NullaryFunc, UnaryFunc, VariadicFunc};
 pub use scalar::{like_pattern, EvalError, MirScalarExpr};
@@ -248,7 +248,7 @@ impl MapFilterProject {
                 return None;
             }
         }
-        Some(row_packer.finish_and_reuse())
+        Some(row_packer.finish_and_reuse().normalize_zeros())
     }
 
     /// Extracts any MapFilterProject at the root of the expression.
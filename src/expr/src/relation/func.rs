@@ -9,6 +9,7 @@
 
 #![allow(missing_docs)]
 
+use std::cmp::Ordering;
 use std::fmt;
 use std::fs;
 use std::iter;
@@ -20,13 +21,16 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use ore::cast::CastFrom;
+use repr::adt::array::ArrayDimension;
 use repr::adt::decimal::{Significand, MAX_DECIMAL_PRECISION};
+use repr::adt::range;
 use repr::adt::regex::Regex as ReprRegex;
 use repr::{
     CachedRecordIter, ColumnType, Datum, Diff, RelationType, Row, RowArena, RowPacker, ScalarType,
 };
 
 use crate::id::GlobalId;
+use crate::relation::{compare_columns, ColumnOrder};
 use crate::scalar::func::jsonb_stringify;
 
 // TODO(jamii) be careful about overflow in sum/avg
@@ -385,6 +389,62 @@ where
     Datum::List(datum.unwrap_list())
 }
 
+fn array_agg<'a, I>(datums: I, temp_storage: &'a RowArena, order_by: &[ColumnOrder]) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let mut rows: Vec<Vec<Datum>> = datums
+        .into_iter()
+        .filter(|d| !d.is_null())
+        .map(|d| d.unwrap_list().iter().collect())
+        .collect();
+    if rows.is_empty() {
+        return Datum::Null;
+    }
+    rows.sort_by(|left, right| compare_columns(order_by, left, right, || Ordering::Equal));
+    let dims = [ArrayDimension {
+        lower_bound: 1,
+        length: rows.len(),
+    }];
+    temp_storage.make_datum(|packer| {
+        packer
+            .push_array(&dims, rows.iter().map(|row| row[0]))
+            .expect("array_agg produces a one-dimensional array")
+    })
+}
+
+fn range_agg<'a, I>(datums: I, temp_storage: &'a RowArena) -> Datum<'a>
+where
+    I: IntoIterator<Item = Datum<'a>>,
+{
+    let mut datums = datums.into_iter().filter(|d| !d.is_null()).peekable();
+    if datums.peek().is_none() {
+        return Datum::Null;
+    }
+    let ranges: Vec<_> = range::merge(datums.map(|d| d.unwrap_range()))
+        .into_iter()
+        .map(|bounds| {
+            temp_storage
+                .try_make_datum(|packer| packer.push_range(Some(bounds)))
+                .expect("merged ranges have valid bounds")
+        })
+        .collect();
+    // Empty arrays have zero dimensions, not one dimension of zero length.
+    let dims = if ranges.is_empty() {
+        vec![]
+    } else {
+        vec![ArrayDimension {
+            lower_bound: 1,
+            length: ranges.len(),
+        }]
+    };
+    temp_storage.make_datum(|packer| {
+        packer
+            .push_array(&dims, ranges)
+            .expect("ranges form a valid array")
+    })
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
 pub enum AggregateFunc {
     MaxInt32,
@@ -421,6 +481,21 @@ pub enum AggregateFunc {
     /// layer, this function filters out `Datum::Null`, for consistency with
     /// the other aggregate functions.
     JsonbAgg,
+    /// Accumulates ranges into an array of the smallest set of disjoint,
+    /// non-adjacent ranges that contain the same values, in ascending order.
+    ///
+    /// PostgreSQL returns a multirange from `range_agg`; Materialize does not
+    /// support multiranges, and returns their ranges as an array instead.
+    RangeAgg,
+    /// Accumulates values into a one-dimensional array.
+    ///
+    /// Each input is a record whose first field is the value to accumulate
+    /// and whose remaining fields are the keys described by `order_by`. The
+    /// record wrapping lets the aggregation include null values, which the
+    /// other aggregate functions filter out.
+    ArrayAgg {
+        order_by: Vec<ColumnOrder>,
+    },
     /// Accumulates any number of `Datum::Dummy`s into `Datum::Dummy`.
     ///
     /// Useful for removing an expensive aggregation while maintaining the shape
@@ -463,6 +538,8 @@ impl AggregateFunc {
             AggregateFunc::Any => any(datums),
             AggregateFunc::All => all(datums),
             AggregateFunc::JsonbAgg => jsonb_agg(datums, temp_storage),
+            AggregateFunc::RangeAgg => range_agg(datums, temp_storage),
+            AggregateFunc::ArrayAgg { order_by } => array_agg(datums, temp_storage, order_by),
             AggregateFunc::Dummy => Datum::Dummy,
         }
     }
@@ -501,6 +578,13 @@ impl AggregateFunc {
             AggregateFunc::Any => ScalarType::Bool,
             AggregateFunc::All => ScalarType::Bool,
             AggregateFunc::JsonbAgg => ScalarType::Jsonb,
+            AggregateFunc::RangeAgg => ScalarType::Array(Box::new(input_type.scalar_type)),
+            AggregateFunc::ArrayAgg { .. } => match input_type.scalar_type {
+                ScalarType::Record { fields, .. } => {
+                    ScalarType::Array(Box::new(fields[0].1.scalar_type.clone()))
+                }
+                _ => unreachable!("array_agg input is always a record"),
+            },
             AggregateFunc::SumInt32 => ScalarType::Int64,
             AggregateFunc::SumInt64 => ScalarType::Decimal(MAX_DECIMAL_PRECISION, 0),
             _ => input_type.scalar_type,
@@ -642,6 +726,8 @@ impl fmt::Display for AggregateFunc {
             AggregateFunc::Any => f.write_str("any"),
             AggregateFunc::All => f.write_str("all"),
             AggregateFunc::JsonbAgg => f.write_str("jsonb_agg"),
+            AggregateFunc::RangeAgg => f.write_str("range_agg"),
+            AggregateFunc::ArrayAgg { .. } => f.write_str("array_agg"),
             AggregateFunc::Dummy => f.write_str("dummy"),
         }
     }
@@ -174,6 +174,8 @@ pub enum MirRelationExpr {
         offset: usize,
         /// True iff the input is known to monotonically increase (only addition of records).
         monotonic: bool,
+        /// User hint: expected number of values per group key. Used to optimize physical rendering.
+        expected_group_size: Option<usize>,
     },
     /// Return a dataflow where the row counts are negated
     ///
@@ -653,6 +655,7 @@ impl MirRelationExpr {
         order_key: Vec<ColumnOrder>,
         limit: Option<usize>,
         offset: usize,
+        expected_group_size: Option<usize>,
     ) -> Self {
         MirRelationExpr::TopK {
             input: Box::new(self),
@@ -661,6 +664,7 @@ impl MirRelationExpr {
             limit,
             offset,
             monotonic: false,
+            expected_group_size,
         }
     }
 
@@ -1031,6 +1035,7 @@ impl MirRelationExpr {
                 limit: _,
                 offset: _,
                 monotonic: _,
+                expected_group_size: _,
             }
             | MirRelationExpr::Negate { input: _ }
             | MirRelationExpr::Threshold { input: _ }
@@ -1252,6 +1257,24 @@ impl fmt::Display for AggregateExpr {
     }
 }
 
+/// A foreign key relationship from the columns of one collection to a key of
+/// another collection.
+///
+/// The relationship is declared rather than enforced. It promises that every
+/// record of the referencing collection whose `columns` are all non-null has a
+/// matching record in the `parent` collection, where the values of `columns`
+/// equal the values of `parent_columns`, and that `parent_columns` form a key
+/// of `parent`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
+pub struct ForeignKey {
+    /// The columns of the referencing collection.
+    pub columns: Vec<usize>,
+    /// The referenced collection.
+    pub parent: GlobalId,
+    /// The columns of `parent` that `columns` refer to, in corresponding order.
+    pub parent_columns: Vec<usize>,
+}
+
 /// Describe a join implementation in dataflow.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Hash)]
 pub enum JoinImplementation {
@@ -1285,7 +1308,7 @@ pub enum JoinImplementation {
 /// keywords), whereas much of the rest of SQL is defined in terms of unordered
 /// multisets. But as it turns out, the same idea can be used to optimize
 /// trivial peeks.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct RowSetFinishing {
     /// Order rows by the given columns.
     pub order_by: Vec<ColumnOrder>,
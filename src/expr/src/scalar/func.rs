@@ -9,6 +9,7 @@
 
 use std::borrow::Cow;
 use std::cmp::{self, Ordering};
+use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::iter;
@@ -27,6 +28,7 @@ use regex::RegexBuilder;
 use serde::{Deserialize, Serialize};
 use sha1::Sha1;
 use sha2::{Sha224, Sha256, Sha384, Sha512};
+use uuid::Uuid;
 
 use ore::collections::CollectionExt;
 use ore::fmt::FormatBuffer;
@@ -34,11 +36,15 @@ use ore::result::ResultExt;
 use ore::str::StrExt;
 use pgrepr::Type;
 use repr::adt::array::ArrayDimension;
+use repr::adt::char::{format_str_pad, format_str_trim, format_varchar};
 use repr::adt::datetime::{DateTimeUnits, Timezone};
 use repr::adt::decimal::MAX_DECIMAL_PRECISION;
+use repr::adt::geo::{self, Coord, Shape};
 use repr::adt::interval::Interval;
 use repr::adt::jsonb::JsonbRef;
+use repr::adt::range::RangeBound;
 use repr::adt::regex::Regex;
+use repr::adt::tsearch::{self, TextSearchConfig};
 use repr::{strconv, ColumnName, ColumnType, Datum, RowArena, RowPacker, ScalarType};
 
 use crate::scalar::func::format::DateTimeFormat;
@@ -420,6 +426,25 @@ fn cast_string_to_list<'a>(
     Ok(temp_storage.make_datum(|packer| packer.push_list(parsed_datums)))
 }
 
+fn cast_string_to_array<'a>(
+    a: Datum<'a>,
+    cast_expr: &'a MirScalarExpr,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let (dims, datums) = strconv::parse_array(
+        a.unwrap_str(),
+        || Datum::Null,
+        |elem_text| {
+            let elem_text = match elem_text {
+                Cow::Owned(s) => temp_storage.push_string(s),
+                Cow::Borrowed(s) => s,
+            };
+            cast_expr.eval(&[Datum::String(elem_text)], temp_storage)
+        },
+    )?;
+    Ok(temp_storage.try_make_datum(|packer| packer.push_array(&dims, datums))?)
+}
+
 fn cast_string_to_map<'a>(
     a: Datum<'a>,
     map_typ: &ScalarType,
@@ -450,6 +475,21 @@ fn cast_string_to_map<'a>(
     }))
 }
 
+fn cast_string_to_range<'a>(
+    a: Datum<'a>,
+    cast_expr: &'a MirScalarExpr,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let bounds = strconv::parse_range(a.unwrap_str(), |elem_text| -> Result<Datum, EvalError> {
+        let elem_text = match elem_text {
+            Cow::Owned(s) => temp_storage.push_string(s),
+            Cow::Borrowed(s) => s,
+        };
+        cast_expr.eval(&[Datum::String(elem_text)], temp_storage)
+    })?;
+    Ok(temp_storage.try_make_datum(|packer| packer.push_range(bounds))?)
+}
+
 fn cast_string_to_time<'a>(a: Datum<'a>) -> Result<Datum<'a>, EvalError> {
     strconv::parse_time(a.unwrap_str())
         .map(Datum::Time)
@@ -480,6 +520,93 @@ fn cast_string_to_uuid<'a>(a: Datum<'a>) -> Result<Datum<'a>, EvalError> {
         .err_into()
 }
 
+fn cast_string_to_inet<'a>(a: Datum<'a>) -> Result<Datum<'a>, EvalError> {
+    strconv::parse_inet(a.unwrap_str())
+        .map(Datum::Inet)
+        .err_into()
+}
+
+fn cast_string_to_cidr<'a>(a: Datum<'a>) -> Result<Datum<'a>, EvalError> {
+    strconv::parse_cidr(a.unwrap_str())
+        .map(Datum::Inet)
+        .err_into()
+}
+
+fn cast_string_to_geometry<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let (srid, shape) = strconv::parse_geometry(a.unwrap_str())?;
+    Ok(temp_storage.make_datum(|packer| packer.push_geometry(srid, &shape)))
+}
+
+fn cast_string_to_geography<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let (srid, shape) = strconv::parse_geography(a.unwrap_str())?;
+    Ok(temp_storage.make_datum(|packer| packer.push_geometry(srid, &shape)))
+}
+
+fn cast_string_to_tsvector<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let doc = strconv::parse_tsvector(a.unwrap_str())?;
+    Ok(Datum::String(temp_storage.push_string(doc.to_string())))
+}
+
+fn cast_string_to_tsquery<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let query = strconv::parse_tsquery(a.unwrap_str())?;
+    Ok(Datum::String(temp_storage.push_string(query.to_string())))
+}
+
+fn cast_string_to_char<'a>(
+    a: Datum<'a>,
+    length: Option<usize>,
+    fail_on_len: bool,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let s = format_str_pad(a.unwrap_str(), length, fail_on_len).map_err(|_| {
+        EvalError::StringValueTooLong {
+            target_type: "character".into(),
+            length: length.unwrap_or(0),
+        }
+    })?;
+    Ok(Datum::String(temp_storage.push_string(s)))
+}
+
+fn cast_char_to_string<'a>(a: Datum<'a>) -> Datum<'a> {
+    Datum::String(format_str_trim(a.unwrap_str()))
+}
+
+fn cast_string_to_varchar<'a>(
+    a: Datum<'a>,
+    length: Option<usize>,
+    fail_on_len: bool,
+) -> Result<Datum<'a>, EvalError> {
+    let s = format_varchar(a.unwrap_str(), length, fail_on_len).map_err(|_| {
+        EvalError::StringValueTooLong {
+            target_type: "character varying".into(),
+            length: length.unwrap_or(0),
+        }
+    })?;
+    Ok(Datum::String(s))
+}
+
+fn cast_bytes_to_uuid<'a>(a: Datum<'a>) -> Result<Datum<'a>, EvalError> {
+    let bytes = a.unwrap_bytes();
+    Uuid::from_slice(bytes).map(Datum::Uuid).map_err(|_| {
+        EvalError::InvalidParameterValue(format!(
+            "invalid length for type uuid: expected 16 bytes, got {}",
+            bytes.len()
+        ))
+    })
+}
+
 fn cast_date_to_timestamp<'a>(a: Datum<'a>) -> Datum<'a> {
     Datum::Timestamp(a.unwrap_date().and_hms(0, 0, 0))
 }
@@ -644,6 +771,39 @@ fn cast_uuid_to_string<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a
     Datum::String(temp_storage.push_string(buf))
 }
 
+fn cast_uuid_to_bytes<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    Datum::Bytes(temp_storage.push_bytes(a.unwrap_uuid().as_bytes().to_vec()))
+}
+
+fn cast_inet_to_string<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    // Unlike the output format of `inet`, but like PostgreSQL's `text(inet)`,
+    // the text produced by the cast always includes the subnet mask.
+    let mut buf = String::new();
+    strconv::format_cidr(&mut buf, a.unwrap_inet());
+    Datum::String(temp_storage.push_string(buf))
+}
+
+fn cast_inet_to_cidr<'a>(a: Datum<'a>) -> Datum<'a> {
+    Datum::Inet(a.unwrap_inet().network())
+}
+
+fn cast_geometry_to_string<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    let geometry = a.unwrap_geometry();
+    let mut buf = String::new();
+    strconv::format_geometry(&mut buf, geometry.srid(), &geometry.shape());
+    Datum::String(temp_storage.push_string(buf))
+}
+
+fn cast_geometry_to_geography<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let geometry = a.unwrap_geometry();
+    let shape = geometry.shape();
+    let srid = geo::check_geography(geometry.srid(), &shape)?;
+    Ok(temp_storage.make_datum(|packer| packer.push_geometry(srid, &shape)))
+}
+
 /// Casts between two list types by casting each element of `a` ("list1") using
 /// `cast_expr` and collecting the results into a new list ("list2").
 fn cast_list1_to_list2<'a>(
@@ -1484,6 +1644,195 @@ fn map_get_values<'a>(a: Datum<'a>, b: Datum<'a>, temp_storage: &'a RowArena) ->
     })
 }
 
+fn map_keys<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    let keys: Vec<Datum> = a
+        .unwrap_map()
+        .iter()
+        .map(|(k, _v)| Datum::String(k))
+        .collect();
+    array_create_scalar(&keys, temp_storage).unwrap()
+}
+
+fn map_values<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    let values: Vec<Datum> = a.unwrap_map().iter().map(|(_k, v)| v).collect();
+    array_create_scalar(&values, temp_storage).unwrap()
+}
+
+/// Builds a map from alternating keys and values. Entries with `NULL` keys are
+/// dropped, and later entries take precedence over earlier entries with the
+/// same key.
+fn map_build<'a>(datums: &[Datum<'a>], temp_storage: &'a RowArena) -> Datum<'a> {
+    let mut map = BTreeMap::new();
+    for kv in datums.chunks(2) {
+        if !kv[0].is_null() {
+            map.insert(kv[0].unwrap_str(), kv[1]);
+        }
+    }
+    temp_storage.make_datum(|packer| packer.push_dict(map))
+}
+
+/// Like [`map_build`], but reads the entries from a list of two-field records.
+/// `NULL` records are skipped.
+fn map_build_from_record_list<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    let mut map = BTreeMap::new();
+    for record in a.unwrap_list().iter() {
+        if record.is_null() {
+            continue;
+        }
+        let mut fields = record.unwrap_list().iter();
+        let (k, v) = (fields.next().unwrap(), fields.next().unwrap());
+        if !k.is_null() {
+            map.insert(k.unwrap_str(), v);
+        }
+    }
+    temp_storage.make_datum(|packer| packer.push_dict(map))
+}
+
+fn cast_map_to_jsonb<'a>(
+    a: Datum<'a>,
+    cast_expr: &'a MirScalarExpr,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let mut pairs = vec![];
+    for (k, v) in a.unwrap_map().iter() {
+        // `cast_expr` converts the map's value, bound to the first column, to
+        // jsonb.
+        pairs.push((k, cast_expr.eval(&[v], temp_storage)?));
+    }
+    Ok(temp_storage.make_datum(|packer| packer.push_dict(pairs)))
+}
+
+/// Casts a jsonb object to a map. Like the other casts from jsonb, inputs that
+/// are not objects produce `NULL` rather than an error.
+fn cast_jsonb_to_map<'a>(
+    a: Datum<'a>,
+    map_typ: &ScalarType,
+    cast_expr: &'a MirScalarExpr,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let map = match a {
+        Datum::Map(map) => map,
+        _ => return Ok(Datum::Null),
+    };
+    let stringify = map_typ.unwrap_map_value_type() == &ScalarType::String;
+    let mut pairs = vec![];
+    for (k, v) in map.iter() {
+        let v = match v {
+            Datum::JsonNull => Datum::Null,
+            // Text values are unquoted, as with the `->>` operator.
+            _ if stringify => jsonb_stringify(v, temp_storage),
+            _ => cast_expr.eval(&[v], temp_storage)?,
+        };
+        pairs.push((k, v));
+    }
+    Ok(temp_storage.make_datum(|packer| packer.push_dict(pairs)))
+}
+
+fn range_contains_elem<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    Datum::from(a.unwrap_range().contains_elem(b))
+}
+
+fn range_contains_range<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    Datum::from(a.unwrap_range().contains_range(&b.unwrap_range()))
+}
+
+fn range_overlaps<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    Datum::from(a.unwrap_range().overlaps(&b.unwrap_range()))
+}
+
+fn inet_contains<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    Datum::from(a.unwrap_inet().contains_strict(&b.unwrap_inet()))
+}
+
+fn inet_contains_or_equals<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    Datum::from(a.unwrap_inet().contains(&b.unwrap_inet()))
+}
+
+fn inet_contained_by<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    Datum::from(b.unwrap_inet().contains_strict(&a.unwrap_inet()))
+}
+
+fn inet_contained_by_or_equals<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    Datum::from(b.unwrap_inet().contains(&a.unwrap_inet()))
+}
+
+fn geometry_set_srid<'a>(a: Datum<'a>, b: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    let shape = a.unwrap_geometry().shape();
+    temp_storage.make_datum(|packer| packer.push_geometry(b.unwrap_int32(), &shape))
+}
+
+fn make_point<'a>(
+    a: Datum<'a>,
+    b: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let shape = Shape::Point(Coord {
+        x: a.unwrap_float64(),
+        y: b.unwrap_float64(),
+    });
+    shape.validate()?;
+    Ok(temp_storage.make_datum(|packer| packer.push_geometry(0, &shape)))
+}
+
+/// Returns the shapes of two geometries, provided that they share an SRID.
+fn geometry_pair(a: Datum, b: Datum) -> Result<(Shape, Shape), EvalError> {
+    let (a, b) = (a.unwrap_geometry(), b.unwrap_geometry());
+    if a.srid() != b.srid() {
+        return Err(geo::InvalidGeometryError::MixedSrid(a.srid(), b.srid()).into());
+    }
+    Ok((a.shape(), b.shape()))
+}
+
+fn geometry_contains<'a>(a: Datum<'a>, b: Datum<'a>) -> Result<Datum<'a>, EvalError> {
+    let (a, b) = geometry_pair(a, b)?;
+    Ok(Datum::from(a.contains(&b)))
+}
+
+fn geometry_distance<'a>(a: Datum<'a>, b: Datum<'a>) -> Result<Datum<'a>, EvalError> {
+    let (a, b) = geometry_pair(a, b)?;
+    Ok(Datum::from(a.distance(&b)))
+}
+
+fn geography_distance<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    let (a, b) = (a.unwrap_geometry().shape(), b.unwrap_geometry().shape());
+    Datum::from(a.spherical_distance(&b))
+}
+
+fn text_search_config(name: Datum) -> Result<TextSearchConfig, EvalError> {
+    let name = name.unwrap_str();
+    TextSearchConfig::from_name(name)
+        .ok_or_else(|| EvalError::InvalidTextSearchConfig(name.to_owned()))
+}
+
+fn to_tsvector<'a>(
+    config: Datum<'a>,
+    doc: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let doc = text_search_config(config)?.to_tsvector(doc.unwrap_str());
+    Ok(Datum::String(temp_storage.push_string(doc.to_string())))
+}
+
+fn to_tsquery<'a>(
+    config: Datum<'a>,
+    query: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let query = query.unwrap_str();
+    let query = text_search_config(config)?
+        .to_tsquery(query)
+        .map_err(|_| EvalError::InvalidTsQuery(query.to_owned()))?;
+    Ok(Datum::String(temp_storage.push_string(query.to_string())))
+}
+
+fn ts_match<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    // Both datums are in canonical form, so they always parse.
+    let doc = tsearch::parse_tsvector(a.unwrap_str()).expect("tsvector datum is canonical");
+    let query = tsearch::parse_tsquery(b.unwrap_str(), |text| vec![text.to_owned()])
+        .expect("tsquery datum is canonical");
+    Datum::from(query.matches(&doc))
+}
+
 // TODO(jamii) nested loops are possibly not the fastest way to do this
 fn jsonb_contains_jsonb<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
     // https://www.postgresql.org/docs/current/datatype-json.html#JSON-CONTAINMENT
@@ -2253,6 +2602,21 @@ pub enum BinaryFunc {
     MapContainsAllKeys,
     MapContainsAnyKeys,
     MapContainsMap,
+    RangeContainsElem,
+    RangeContainsRange,
+    RangeOverlaps,
+    InetContains,
+    InetContainsOrEquals,
+    InetContainedBy,
+    InetContainedByOrEquals,
+    GeometrySetSrid,
+    MakePoint,
+    GeometryContains,
+    GeometryDistance,
+    GeographyDistance,
+    ToTsVector,
+    ToTsQuery,
+    TsMatch,
     ConvertFrom,
     Trim,
     TrimLeading,
@@ -2261,7 +2625,9 @@ pub enum BinaryFunc {
     ListIndex,
     ListLengthMax { max_dim: usize },
     ArrayContains,
-    ArrayIndex,
+    ArrayContainsArray,
+    ArrayOverlaps,
+    ArrayRemove,
     ArrayLength,
     ArrayLower,
     ArrayUpper,
@@ -2404,6 +2770,21 @@ impl BinaryFunc {
             BinaryFunc::MapContainsAllKeys => Ok(eager!(map_contains_all_keys)),
             BinaryFunc::MapContainsAnyKeys => Ok(eager!(map_contains_any_keys)),
             BinaryFunc::MapContainsMap => Ok(eager!(map_contains_map)),
+            BinaryFunc::RangeContainsElem => Ok(eager!(range_contains_elem)),
+            BinaryFunc::RangeContainsRange => Ok(eager!(range_contains_range)),
+            BinaryFunc::RangeOverlaps => Ok(eager!(range_overlaps)),
+            BinaryFunc::InetContains => Ok(eager!(inet_contains)),
+            BinaryFunc::InetContainsOrEquals => Ok(eager!(inet_contains_or_equals)),
+            BinaryFunc::InetContainedBy => Ok(eager!(inet_contained_by)),
+            BinaryFunc::InetContainedByOrEquals => Ok(eager!(inet_contained_by_or_equals)),
+            BinaryFunc::GeometrySetSrid => Ok(eager!(geometry_set_srid, temp_storage)),
+            BinaryFunc::MakePoint => eager!(make_point, temp_storage),
+            BinaryFunc::GeometryContains => eager!(geometry_contains),
+            BinaryFunc::GeometryDistance => eager!(geometry_distance),
+            BinaryFunc::GeographyDistance => Ok(eager!(geography_distance)),
+            BinaryFunc::ToTsVector => eager!(to_tsvector, temp_storage),
+            BinaryFunc::ToTsQuery => eager!(to_tsquery, temp_storage),
+            BinaryFunc::TsMatch => Ok(eager!(ts_match)),
             BinaryFunc::RoundDecimal(scale) => Ok(eager!(round_decimal_binary, *scale)),
             BinaryFunc::ConvertFrom => eager!(convert_from),
             BinaryFunc::Encode => eager!(encode, temp_storage),
@@ -2416,7 +2797,9 @@ impl BinaryFunc {
             BinaryFunc::ListLengthMax { max_dim } => eager!(list_length_max, *max_dim),
             BinaryFunc::ArrayLength => Ok(eager!(array_length)),
             BinaryFunc::ArrayContains => Ok(eager!(array_contains)),
-            BinaryFunc::ArrayIndex => Ok(eager!(array_index)),
+            BinaryFunc::ArrayContainsArray => Ok(eager!(array_contains_array)),
+            BinaryFunc::ArrayOverlaps => Ok(eager!(array_overlaps)),
+            BinaryFunc::ArrayRemove => eager!(array_remove, temp_storage),
             BinaryFunc::ArrayLower => Ok(eager!(array_lower)),
             BinaryFunc::ArrayUpper => Ok(eager!(array_upper)),
             BinaryFunc::ListListConcat => Ok(eager!(list_list_concat, temp_storage)),
@@ -2448,9 +2831,8 @@ impl BinaryFunc {
                 | ModDecimal
         );
         match self {
-            And | Or | Eq | NotEq | Lt | Lte | Gt | Gte | ArrayContains => {
-                ScalarType::Bool.nullable(in_nullable)
-            }
+            And | Or | Eq | NotEq | Lt | Lte | Gt | Gte | ArrayContains | ArrayContainsArray
+            | ArrayOverlaps => ScalarType::Bool.nullable(in_nullable),
 
             IsLikePatternMatch { .. } | IsRegexpMatch { .. } => {
                 // The output can be null if the pattern is invalid.
@@ -2563,8 +2945,27 @@ impl BinaryFunc {
             | JsonbDeleteInt64
             | JsonbDeleteString => ScalarType::Jsonb.nullable(true),
 
-            JsonbContainsString | JsonbContainsJsonb | MapContainsKey | MapContainsAllKeys
-            | MapContainsAnyKeys | MapContainsMap => ScalarType::Bool.nullable(in_nullable),
+            JsonbContainsString
+            | JsonbContainsJsonb
+            | MapContainsKey
+            | MapContainsAllKeys
+            | MapContainsAnyKeys
+            | MapContainsMap
+            | RangeContainsElem
+            | RangeContainsRange
+            | RangeOverlaps
+            | InetContains
+            | InetContainsOrEquals
+            | InetContainedBy
+            | InetContainedByOrEquals
+            | GeometryContains
+            | TsMatch => ScalarType::Bool.nullable(in_nullable),
+
+            GeometrySetSrid | MakePoint => ScalarType::Geometry.nullable(in_nullable),
+            GeometryDistance | GeographyDistance => ScalarType::Float64.nullable(in_nullable),
+
+            ToTsVector => ScalarType::TsVector.nullable(in_nullable),
+            ToTsQuery => ScalarType::TsQuery.nullable(in_nullable),
 
             MapGetValue => input1_type
                 .scalar_type
@@ -2583,11 +2984,7 @@ impl BinaryFunc {
                 .clone()
                 .nullable(true),
 
-            ArrayIndex => input1_type
-                .scalar_type
-                .unwrap_array_element_type()
-                .clone()
-                .nullable(true),
+            ArrayRemove => input1_type.scalar_type.nullable(input1_type.nullable),
 
             ListLengthMax { .. } | ArrayLength | ArrayLower | ArrayUpper => {
                 ScalarType::Int64.nullable(true)
@@ -2612,6 +3009,7 @@ impl BinaryFunc {
                 | BinaryFunc::ListListConcat
                 | BinaryFunc::ListElementConcat
                 | BinaryFunc::ElementListConcat
+                | BinaryFunc::ArrayRemove
         )
     }
 
@@ -2742,11 +3140,20 @@ impl BinaryFunc {
             | MapContainsAllKeys
             | MapContainsAnyKeys
             | MapContainsMap
+            | RangeContainsElem
+            | RangeContainsRange
+            | RangeOverlaps
+            | InetContains
+            | InetContainsOrEquals
+            | InetContainedBy
+            | InetContainedByOrEquals
+            | TsMatch
             | TextConcat
             | ListIndex
             | IsRegexpMatch { .. }
             | ArrayContains
-            | ArrayIndex
+            | ArrayContainsArray
+            | ArrayOverlaps
             | ArrayLength
             | ArrayLower
             | ArrayUpper
@@ -2776,6 +3183,7 @@ impl BinaryFunc {
             | TrimTrailing
             | EncodedBytesCharLength
             | ListLengthMax { .. }
+            | ArrayRemove
             | DigestString
             | DigestBytes
             | MzRenderTypemod
@@ -2783,7 +3191,14 @@ impl BinaryFunc {
             | Decode
             | LogDecimal(_)
             | Power
-            | PowerDecimal(_) => false,
+            | PowerDecimal(_)
+            | GeometrySetSrid
+            | MakePoint
+            | GeometryContains
+            | GeometryDistance
+            | GeographyDistance
+            | ToTsVector
+            | ToTsQuery => false,
         }
     }
 }
@@ -2873,7 +3288,23 @@ impl fmt::Display for BinaryFunc {
             BinaryFunc::JsonbGetString { .. } => f.write_str("->"),
             BinaryFunc::JsonbContainsString | BinaryFunc::MapContainsKey => f.write_str("?"),
             BinaryFunc::JsonbConcat => f.write_str("||"),
-            BinaryFunc::JsonbContainsJsonb | BinaryFunc::MapContainsMap => f.write_str("@>"),
+            BinaryFunc::JsonbContainsJsonb
+            | BinaryFunc::MapContainsMap
+            | BinaryFunc::RangeContainsElem
+            | BinaryFunc::RangeContainsRange => f.write_str("@>"),
+            BinaryFunc::RangeOverlaps => f.write_str("&&"),
+            BinaryFunc::InetContains => f.write_str(">>"),
+            BinaryFunc::InetContainsOrEquals => f.write_str(">>="),
+            BinaryFunc::InetContainedBy => f.write_str("<<"),
+            BinaryFunc::InetContainedByOrEquals => f.write_str("<<="),
+            BinaryFunc::GeometrySetSrid => f.write_str("st_setsrid"),
+            BinaryFunc::MakePoint => f.write_str("st_makepoint"),
+            BinaryFunc::GeometryContains => f.write_str("st_contains"),
+            BinaryFunc::GeometryDistance => f.write_str("st_distance"),
+            BinaryFunc::GeographyDistance => f.write_str("st_distance_geog"),
+            BinaryFunc::ToTsVector => f.write_str("to_tsvector"),
+            BinaryFunc::ToTsQuery => f.write_str("to_tsquery"),
+            BinaryFunc::TsMatch => f.write_str("@@"),
             BinaryFunc::JsonbDeleteInt64 => f.write_str("-"),
             BinaryFunc::JsonbDeleteString => f.write_str("-"),
             BinaryFunc::MapGetValue | BinaryFunc::MapGetValues => f.write_str("->"),
@@ -2888,7 +3319,9 @@ impl fmt::Display for BinaryFunc {
             BinaryFunc::ListIndex => f.write_str("list_index"),
             BinaryFunc::ListLengthMax { .. } => f.write_str("list_length_max"),
             BinaryFunc::ArrayContains => f.write_str("array_contains"),
-            BinaryFunc::ArrayIndex => f.write_str("array_index"),
+            BinaryFunc::ArrayContainsArray => f.write_str("@>"),
+            BinaryFunc::ArrayOverlaps => f.write_str("&&"),
+            BinaryFunc::ArrayRemove => f.write_str("array_remove"),
             BinaryFunc::ArrayLength => f.write_str("array_length"),
             BinaryFunc::ArrayLower => f.write_str("array_lower"),
             BinaryFunc::ArrayUpper => f.write_str("array_upper"),
@@ -2972,6 +3405,13 @@ pub enum UnaryFunc {
         // elements' type
         cast_expr: Box<MirScalarExpr>,
     },
+    CastStringToArray {
+        // Target array's type
+        return_ty: ScalarType,
+        // The expression to cast the discovered array elements to the array's
+        // elements' type
+        cast_expr: Box<MirScalarExpr>,
+    },
     CastStringToMap {
         // Target map's value type
         return_ty: ScalarType,
@@ -2979,12 +3419,22 @@ pub enum UnaryFunc {
         // values' type
         cast_expr: Box<MirScalarExpr>,
     },
+    CastStringToRange {
+        // Target range's type
+        return_ty: ScalarType,
+        // The expression used to cast the discovered bounds to the range's
+        // element type
+        cast_expr: Box<MirScalarExpr>,
+    },
     CastStringToTime,
     CastStringToTimestamp,
     CastStringToTimestampTz,
     CastStringToInterval,
     CastStringToDecimal(u8),
     CastStringToUuid,
+    CastStringToInet,
+    CastStringToCidr,
+    CastBytesToUuid,
     CastDateToTimestamp,
     CastDateToTimestampTz,
     CastDateToString,
@@ -3005,6 +3455,29 @@ pub enum UnaryFunc {
     CastJsonbToFloat64,
     CastJsonbToBool,
     CastUuidToString,
+    CastUuidToBytes,
+    CastInetToString,
+    CastInetToCidr,
+    CastCidrToInet,
+    CastStringToGeometry,
+    CastStringToGeography,
+    CastGeometryToString,
+    CastGeometryToGeography,
+    CastGeographyToGeometry,
+    CastStringToTsVector,
+    CastStringToTsQuery,
+    CastTsVectorToString,
+    CastTsQueryToString,
+    CastStringToChar {
+        length: Option<usize>,
+        fail_on_len: bool,
+    },
+    CastCharToString,
+    CastStringToVarChar {
+        length: Option<usize>,
+        fail_on_len: bool,
+    },
+    CastVarCharToString,
     CastRecordToString {
         ty: ScalarType,
     },
@@ -3023,6 +3496,19 @@ pub enum UnaryFunc {
     CastMapToString {
         ty: ScalarType,
     },
+    CastMapToJsonb {
+        // The expression to cast the map's values to jsonb
+        cast_expr: Box<MirScalarExpr>,
+    },
+    CastJsonbToMap {
+        // Target map's type
+        return_ty: ScalarType,
+        // The expression to cast the object's values to the map's values' type
+        cast_expr: Box<MirScalarExpr>,
+    },
+    CastRangeToString {
+        ty: ScalarType,
+    },
     CastInPlace {
         return_ty: ScalarType,
     },
@@ -3061,6 +3547,27 @@ pub enum UnaryFunc {
     TrimTrailingWhitespace,
     RecordGet(usize),
     ListLength,
+    MapKeys,
+    MapValues,
+    MapBuildFromRecordList {
+        value_type: ScalarType,
+    },
+    RangeLower,
+    RangeUpper,
+    RangeIsEmpty,
+    RangeLowerInc,
+    RangeUpperInc,
+    RangeLowerInf,
+    RangeUpperInf,
+    InetHost,
+    InetMasklen,
+    GeometryFromText,
+    GeometryFromWkb,
+    GeometryFromGeoJson,
+    GeometryAsText,
+    GeometryAsBinary,
+    GeometryAsGeoJson,
+    GeometrySrid,
     Upper,
     Lower,
     Cos,
@@ -3145,15 +3652,24 @@ impl UnaryFunc {
                 cast_expr,
                 return_ty,
             } => cast_string_to_list(a, return_ty, &*cast_expr, temp_storage),
+            UnaryFunc::CastStringToArray { cast_expr, .. } => {
+                cast_string_to_array(a, &*cast_expr, temp_storage)
+            }
             UnaryFunc::CastStringToMap {
                 cast_expr,
                 return_ty,
             } => cast_string_to_map(a, return_ty, &*cast_expr, temp_storage),
+            UnaryFunc::CastStringToRange { cast_expr, .. } => {
+                cast_string_to_range(a, &*cast_expr, temp_storage)
+            }
             UnaryFunc::CastStringToTime => cast_string_to_time(a),
             UnaryFunc::CastStringToTimestamp => cast_string_to_timestamp(a),
             UnaryFunc::CastStringToTimestampTz => cast_string_to_timestamptz(a),
             UnaryFunc::CastStringToInterval => cast_string_to_interval(a),
             UnaryFunc::CastStringToUuid => cast_string_to_uuid(a),
+            UnaryFunc::CastStringToInet => cast_string_to_inet(a),
+            UnaryFunc::CastStringToCidr => cast_string_to_cidr(a),
+            UnaryFunc::CastBytesToUuid => cast_bytes_to_uuid(a),
             UnaryFunc::CastDateToTimestamp => Ok(cast_date_to_timestamp(a)),
             UnaryFunc::CastDateToTimestampTz => Ok(cast_date_to_timestamptz(a)),
             UnaryFunc::CastDateToString => Ok(cast_date_to_string(a, temp_storage)),
@@ -3177,15 +3693,46 @@ impl UnaryFunc {
             UnaryFunc::CastJsonbToFloat64 => Ok(cast_jsonb_to_float64(a)),
             UnaryFunc::CastJsonbToBool => Ok(cast_jsonb_to_bool(a)),
             UnaryFunc::CastUuidToString => Ok(cast_uuid_to_string(a, temp_storage)),
+            UnaryFunc::CastUuidToBytes => Ok(cast_uuid_to_bytes(a, temp_storage)),
+            UnaryFunc::CastInetToString => Ok(cast_inet_to_string(a, temp_storage)),
+            UnaryFunc::CastInetToCidr => Ok(cast_inet_to_cidr(a)),
+            UnaryFunc::CastCidrToInet => Ok(a),
+            UnaryFunc::CastStringToGeometry => cast_string_to_geometry(a, temp_storage),
+            UnaryFunc::CastStringToGeography => cast_string_to_geography(a, temp_storage),
+            UnaryFunc::CastGeometryToString => Ok(cast_geometry_to_string(a, temp_storage)),
+            UnaryFunc::CastGeometryToGeography => cast_geometry_to_geography(a, temp_storage),
+            UnaryFunc::CastGeographyToGeometry => Ok(a),
+            UnaryFunc::CastStringToTsVector => cast_string_to_tsvector(a, temp_storage),
+            UnaryFunc::CastStringToTsQuery => cast_string_to_tsquery(a, temp_storage),
+            UnaryFunc::CastTsVectorToString => Ok(a),
+            UnaryFunc::CastTsQueryToString => Ok(a),
+            UnaryFunc::CastStringToChar {
+                length,
+                fail_on_len,
+            } => cast_string_to_char(a, *length, *fail_on_len, temp_storage),
+            UnaryFunc::CastCharToString => Ok(cast_char_to_string(a)),
+            UnaryFunc::CastStringToVarChar {
+                length,
+                fail_on_len,
+            } => cast_string_to_varchar(a, *length, *fail_on_len),
+            UnaryFunc::CastVarCharToString => Ok(a),
             UnaryFunc::CastRecordToString { ty }
             | UnaryFunc::CastArrayToString { ty }
             | UnaryFunc::CastListToString { ty }
-            | UnaryFunc::CastMapToString { ty } => {
+            | UnaryFunc::CastMapToString { ty }
+            | UnaryFunc::CastRangeToString { ty } => {
                 Ok(cast_collection_to_string(a, ty, temp_storage))
             }
             UnaryFunc::CastList1ToList2 { cast_expr, .. } => {
                 cast_list1_to_list2(a, &*cast_expr, temp_storage)
             }
+            UnaryFunc::CastMapToJsonb { cast_expr } => {
+                cast_map_to_jsonb(a, &*cast_expr, temp_storage)
+            }
+            UnaryFunc::CastJsonbToMap {
+                cast_expr,
+                return_ty,
+            } => cast_jsonb_to_map(a, return_ty, &*cast_expr, temp_storage),
             UnaryFunc::CastInPlace { .. } => Ok(a),
             UnaryFunc::CeilFloat32 => Ok(ceil_float32(a)),
             UnaryFunc::CeilFloat64 => Ok(ceil_float64(a)),
@@ -3235,6 +3782,27 @@ impl UnaryFunc {
             UnaryFunc::TrimTrailingWhitespace => Ok(trim_trailing_whitespace(a)),
             UnaryFunc::RecordGet(i) => Ok(record_get(a, *i)),
             UnaryFunc::ListLength => Ok(list_length(a)),
+            UnaryFunc::MapKeys => Ok(map_keys(a, temp_storage)),
+            UnaryFunc::MapValues => Ok(map_values(a, temp_storage)),
+            UnaryFunc::MapBuildFromRecordList { .. } => {
+                Ok(map_build_from_record_list(a, temp_storage))
+            }
+            UnaryFunc::RangeLower => Ok(range_lower(a)),
+            UnaryFunc::RangeUpper => Ok(range_upper(a)),
+            UnaryFunc::RangeIsEmpty => Ok(range_is_empty(a)),
+            UnaryFunc::RangeLowerInc => Ok(range_lower_inc(a)),
+            UnaryFunc::RangeUpperInc => Ok(range_upper_inc(a)),
+            UnaryFunc::RangeLowerInf => Ok(range_lower_inf(a)),
+            UnaryFunc::RangeUpperInf => Ok(range_upper_inf(a)),
+            UnaryFunc::InetHost => Ok(inet_host(a, temp_storage)),
+            UnaryFunc::InetMasklen => Ok(inet_masklen(a)),
+            UnaryFunc::GeometryFromText => geometry_from_text(a, temp_storage),
+            UnaryFunc::GeometryFromWkb => geometry_from_wkb(a, temp_storage),
+            UnaryFunc::GeometryFromGeoJson => geometry_from_geojson(a, temp_storage),
+            UnaryFunc::GeometryAsText => Ok(geometry_as_text(a, temp_storage)),
+            UnaryFunc::GeometryAsBinary => Ok(geometry_as_binary(a, temp_storage)),
+            UnaryFunc::GeometryAsGeoJson => Ok(geometry_as_geojson(a, temp_storage)),
+            UnaryFunc::GeometrySrid => Ok(geometry_srid(a)),
             UnaryFunc::Upper => Ok(upper(a, temp_storage)),
             UnaryFunc::Lower => Ok(lower(a, temp_storage)),
             UnaryFunc::Cos => cos(a),
@@ -3279,6 +3847,23 @@ impl UnaryFunc {
             CastStringToTimestampTz => ScalarType::TimestampTz.nullable(true),
             CastStringToInterval | CastTimeToInterval => ScalarType::Interval.nullable(true),
             CastStringToUuid => ScalarType::Uuid.nullable(true),
+            CastStringToInet | CastCidrToInet => ScalarType::Inet.nullable(in_nullable),
+            CastStringToCidr | CastInetToCidr => ScalarType::Cidr.nullable(in_nullable),
+            CastStringToGeometry | CastGeographyToGeometry => {
+                ScalarType::Geometry.nullable(in_nullable)
+            }
+            CastStringToGeography | CastGeometryToGeography => {
+                ScalarType::Geography.nullable(in_nullable)
+            }
+            CastStringToTsVector => ScalarType::TsVector.nullable(in_nullable),
+            CastStringToTsQuery => ScalarType::TsQuery.nullable(in_nullable),
+            CastStringToChar { length, .. } => {
+                ScalarType::Char { length: *length }.nullable(in_nullable)
+            }
+            CastStringToVarChar { length, .. } => {
+                ScalarType::VarChar { length: *length }.nullable(in_nullable)
+            }
+            CastBytesToUuid => ScalarType::Uuid.nullable(in_nullable),
 
             CastBoolToInt32 => ScalarType::Int32.nullable(in_nullable),
 
@@ -3299,6 +3884,7 @@ impl UnaryFunc {
             | CastArrayToString { .. }
             | CastListToString { .. }
             | CastMapToString { .. }
+            | CastRangeToString { .. }
             | TrimWhitespace
             | TrimLeadingWhitespace
             | TrimTrailingWhitespace
@@ -3353,10 +3939,20 @@ impl UnaryFunc {
             CastJsonbToBool => ScalarType::Bool.nullable(true),
 
             CastUuidToString => ScalarType::String.nullable(true),
+            CastUuidToBytes => ScalarType::Bytes.nullable(in_nullable),
+            CastInetToString => ScalarType::String.nullable(in_nullable),
+            CastGeometryToString => ScalarType::String.nullable(in_nullable),
+            CastTsVectorToString | CastTsQueryToString => ScalarType::String.nullable(in_nullable),
+            CastCharToString | CastVarCharToString => ScalarType::String.nullable(in_nullable),
+
+            CastMapToJsonb { .. } => ScalarType::Jsonb.nullable(in_nullable),
+            CastJsonbToMap { return_ty, .. } => (return_ty.clone()).nullable(true),
 
             CastList1ToList2 { return_ty, .. }
             | CastStringToList { return_ty, .. }
+            | CastStringToArray { return_ty, .. }
             | CastStringToMap { return_ty, .. }
+            | CastStringToRange { return_ty, .. }
             | CastInPlace { return_ty } => (return_ty.clone()).nullable(false),
 
             CeilFloat32 | FloorFloat32 | RoundFloat32 => ScalarType::Float32.nullable(in_nullable),
@@ -3396,6 +3992,35 @@ impl UnaryFunc {
             },
 
             ListLength => ScalarType::Int64.nullable(true),
+            MapKeys => ScalarType::Array(Box::new(ScalarType::String)).nullable(in_nullable),
+            MapValues => ScalarType::Array(Box::new(
+                input_type.scalar_type.unwrap_map_value_type().clone(),
+            ))
+            .nullable(in_nullable),
+            MapBuildFromRecordList { value_type } => ScalarType::Map {
+                value_type: Box::new(value_type.clone()),
+                custom_oid: None,
+            }
+            .nullable(in_nullable),
+
+            RangeLower | RangeUpper => input_type
+                .scalar_type
+                .unwrap_range_element_type()
+                .clone()
+                .nullable(true),
+            RangeIsEmpty | RangeLowerInc | RangeUpperInc | RangeLowerInf | RangeUpperInf => {
+                ScalarType::Bool.nullable(in_nullable)
+            }
+
+            InetHost => ScalarType::String.nullable(in_nullable),
+            InetMasklen => ScalarType::Int32.nullable(in_nullable),
+
+            GeometryFromText | GeometryFromWkb | GeometryFromGeoJson => {
+                ScalarType::Geometry.nullable(in_nullable)
+            }
+            GeometryAsText | GeometryAsGeoJson => ScalarType::String.nullable(in_nullable),
+            GeometryAsBinary => ScalarType::Bytes.nullable(in_nullable),
+            GeometrySrid => ScalarType::Int32.nullable(in_nullable),
 
             RegexpMatch(_) => ScalarType::Array(Box::new(ScalarType::String)).nullable(true),
 
@@ -3502,12 +4127,17 @@ impl fmt::Display for UnaryFunc {
             UnaryFunc::CastStringToDecimal(_) => f.write_str("strtodec"),
             UnaryFunc::CastStringToDate => f.write_str("strtodate"),
             UnaryFunc::CastStringToList { .. } => f.write_str("strtolist"),
+            UnaryFunc::CastStringToArray { .. } => f.write_str("strtoarray"),
             UnaryFunc::CastStringToMap { .. } => f.write_str("strtomap"),
+            UnaryFunc::CastStringToRange { .. } => f.write_str("strtorange"),
             UnaryFunc::CastStringToTime => f.write_str("strtotime"),
             UnaryFunc::CastStringToTimestamp => f.write_str("strtots"),
             UnaryFunc::CastStringToTimestampTz => f.write_str("strtotstz"),
             UnaryFunc::CastStringToInterval => f.write_str("strtoiv"),
             UnaryFunc::CastStringToUuid => f.write_str("strtouuid"),
+            UnaryFunc::CastStringToInet => f.write_str("strtoinet"),
+            UnaryFunc::CastStringToCidr => f.write_str("strtocidr"),
+            UnaryFunc::CastBytesToUuid => f.write_str("bytestouuid"),
             UnaryFunc::CastDateToTimestamp => f.write_str("datetots"),
             UnaryFunc::CastDateToTimestampTz => f.write_str("datetotstz"),
             UnaryFunc::CastDateToString => f.write_str("datetostr"),
@@ -3528,11 +4158,31 @@ impl fmt::Display for UnaryFunc {
             UnaryFunc::CastJsonbToFloat64 => f.write_str("jsonbtof64"),
             UnaryFunc::CastJsonbToBool => f.write_str("jsonbtobool"),
             UnaryFunc::CastUuidToString => f.write_str("uuidtostr"),
+            UnaryFunc::CastUuidToBytes => f.write_str("uuidtobytes"),
+            UnaryFunc::CastInetToString => f.write_str("inettostr"),
+            UnaryFunc::CastInetToCidr => f.write_str("inettocidr"),
+            UnaryFunc::CastCidrToInet => f.write_str("cidrtoinet"),
+            UnaryFunc::CastStringToGeometry => f.write_str("strtogeom"),
+            UnaryFunc::CastStringToGeography => f.write_str("strtogeog"),
+            UnaryFunc::CastGeometryToString => f.write_str("geomtostr"),
+            UnaryFunc::CastGeometryToGeography => f.write_str("geomtogeog"),
+            UnaryFunc::CastGeographyToGeometry => f.write_str("geogtogeom"),
+            UnaryFunc::CastStringToTsVector => f.write_str("strtotsvector"),
+            UnaryFunc::CastStringToTsQuery => f.write_str("strtotsquery"),
+            UnaryFunc::CastTsVectorToString => f.write_str("tsvectortostr"),
+            UnaryFunc::CastTsQueryToString => f.write_str("tsquerytostr"),
+            UnaryFunc::CastStringToChar { .. } => f.write_str("strtochar"),
+            UnaryFunc::CastCharToString => f.write_str("chartostr"),
+            UnaryFunc::CastStringToVarChar { .. } => f.write_str("strtovarchar"),
+            UnaryFunc::CastVarCharToString => f.write_str("varchartostr"),
             UnaryFunc::CastRecordToString { .. } => f.write_str("recordtostr"),
             UnaryFunc::CastArrayToString { .. } => f.write_str("arraytostr"),
             UnaryFunc::CastListToString { .. } => f.write_str("listtostr"),
             UnaryFunc::CastList1ToList2 { .. } => f.write_str("list1tolist2"),
             UnaryFunc::CastMapToString { .. } => f.write_str("maptostr"),
+            UnaryFunc::CastMapToJsonb { .. } => f.write_str("maptojsonb"),
+            UnaryFunc::CastJsonbToMap { .. } => f.write_str("jsonbtomap"),
+            UnaryFunc::CastRangeToString { .. } => f.write_str("rangetostr"),
             UnaryFunc::CastInPlace { .. } => f.write_str("castinplace"),
             UnaryFunc::CeilFloat32 => f.write_str("ceilf32"),
             UnaryFunc::CeilFloat64 => f.write_str("ceilf64"),
@@ -3572,6 +4222,25 @@ impl fmt::Display for UnaryFunc {
             UnaryFunc::TrimTrailingWhitespace => f.write_str("rtrim"),
             UnaryFunc::RecordGet(_) => f.write_str("record_get"),
             UnaryFunc::ListLength => f.write_str("list_length"),
+            UnaryFunc::MapKeys => f.write_str("map_keys"),
+            UnaryFunc::MapValues => f.write_str("map_values"),
+            UnaryFunc::MapBuildFromRecordList { .. } => f.write_str("map_build"),
+            UnaryFunc::RangeLower => f.write_str("lower"),
+            UnaryFunc::RangeUpper => f.write_str("upper"),
+            UnaryFunc::RangeIsEmpty => f.write_str("isempty"),
+            UnaryFunc::RangeLowerInc => f.write_str("lower_inc"),
+            UnaryFunc::RangeUpperInc => f.write_str("upper_inc"),
+            UnaryFunc::RangeLowerInf => f.write_str("lower_inf"),
+            UnaryFunc::RangeUpperInf => f.write_str("upper_inf"),
+            UnaryFunc::InetHost => f.write_str("host"),
+            UnaryFunc::InetMasklen => f.write_str("masklen"),
+            UnaryFunc::GeometryFromText => f.write_str("st_geomfromtext"),
+            UnaryFunc::GeometryFromWkb => f.write_str("st_geomfromwkb"),
+            UnaryFunc::GeometryFromGeoJson => f.write_str("st_geomfromgeojson"),
+            UnaryFunc::GeometryAsText => f.write_str("st_astext"),
+            UnaryFunc::GeometryAsBinary => f.write_str("st_asbinary"),
+            UnaryFunc::GeometryAsGeoJson => f.write_str("st_asgeojson"),
+            UnaryFunc::GeometrySrid => f.write_str("st_srid"),
             UnaryFunc::Upper => f.write_str("upper"),
             UnaryFunc::Lower => f.write_str("lower"),
             UnaryFunc::Cos => f.write_str("cos"),
@@ -4011,6 +4680,40 @@ fn list_create<'a>(datums: &[Datum<'a>], temp_storage: &'a RowArena) -> Datum<'a
     temp_storage.make_datum(|packer| packer.push_list(datums))
 }
 
+// Null bounds describe unbounded ends, so unlike most functions, range
+// construction does not propagate nulls in its first two arguments.
+fn range_create<'a>(
+    datums: &[Datum<'a>],
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let flags = match datums.get(2) {
+        None => "[)",
+        Some(Datum::Null) => {
+            return Err(EvalError::InvalidParameterValue(
+                "range constructor flags argument must not be null".into(),
+            ))
+        }
+        Some(flags) => flags.unwrap_str(),
+    };
+    let (lower_inclusive, upper_inclusive) = match flags {
+        "[)" => (true, false),
+        "[]" => (true, true),
+        "()" => (false, false),
+        "(]" => (false, true),
+        _ => {
+            return Err(EvalError::InvalidParameterValue(
+                "invalid range bound flags".into(),
+            ))
+        }
+    };
+    Ok(temp_storage.try_make_datum(|packer| {
+        packer.push_range(Some((
+            RangeBound::new(datums[0], lower_inclusive),
+            RangeBound::new(datums[1], upper_inclusive),
+        )))
+    })?)
+}
+
 fn cast_collection_to_string<'a>(
     a: Datum,
     ty: &ScalarType,
@@ -4039,9 +4742,17 @@ where
         TimestampTz => strconv::format_timestamptz(buf, d.unwrap_timestamptz()),
         Interval => strconv::format_interval(buf, d.unwrap_interval()),
         Bytes => strconv::format_bytes(buf, d.unwrap_bytes()),
-        String => strconv::format_string(buf, d.unwrap_str()),
+        String | Char { .. } | VarChar { .. } | TsVector | TsQuery => {
+            strconv::format_string(buf, d.unwrap_str())
+        }
         Jsonb => strconv::format_jsonb(buf, JsonbRef::from_datum(d)),
         Uuid => strconv::format_uuid(buf, d.unwrap_uuid()),
+        Inet => strconv::format_inet(buf, d.unwrap_inet()),
+        Cidr => strconv::format_cidr(buf, d.unwrap_inet()),
+        Geometry | Geography => {
+            let geometry = d.unwrap_geometry();
+            strconv::format_geometry(buf, geometry.srid(), &geometry.shape())
+        }
         Record { fields, .. } => {
             let mut fields = fields.iter();
             strconv::format_record(buf, &d.unwrap_list(), |buf, d| {
@@ -4079,6 +4790,9 @@ where
                 stringify_datum(buf.nonnull_buffer(), d, value_type)
             }
         }),
+        Range(elem_type) => strconv::format_range(buf, d.unwrap_range().bounds(), |buf, d| {
+            stringify_datum(buf, d, elem_type)
+        }),
     }
 }
 
@@ -4163,6 +4877,108 @@ fn list_length(a: Datum) -> Datum {
     Datum::Int64(a.unwrap_list().iter().count() as i64)
 }
 
+// The functions below report on the bounds of a range, and treat the empty
+// range as having no bounds at all, as PostgreSQL does.
+
+fn range_lower(a: Datum) -> Datum {
+    match a.unwrap_range().lower() {
+        Some(lower) => lower.datum(),
+        None => Datum::Null,
+    }
+}
+
+fn range_upper(a: Datum) -> Datum {
+    match a.unwrap_range().upper() {
+        Some(upper) => upper.datum(),
+        None => Datum::Null,
+    }
+}
+
+fn range_is_empty(a: Datum) -> Datum {
+    Datum::from(a.unwrap_range().is_empty())
+}
+
+fn range_lower_inc(a: Datum) -> Datum {
+    Datum::from(
+        a.unwrap_range()
+            .lower()
+            .map_or(false, |lower| lower.inclusive),
+    )
+}
+
+fn range_upper_inc(a: Datum) -> Datum {
+    Datum::from(
+        a.unwrap_range()
+            .upper()
+            .map_or(false, |upper| upper.inclusive),
+    )
+}
+
+fn range_lower_inf(a: Datum) -> Datum {
+    Datum::from(
+        a.unwrap_range()
+            .lower()
+            .map_or(false, |lower| lower.value.is_none()),
+    )
+}
+
+fn range_upper_inf(a: Datum) -> Datum {
+    Datum::from(
+        a.unwrap_range()
+            .upper()
+            .map_or(false, |upper| upper.value.is_none()),
+    )
+}
+
+fn inet_host<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    Datum::String(temp_storage.push_string(a.unwrap_inet().addr().to_string()))
+}
+
+fn inet_masklen<'a>(a: Datum<'a>) -> Datum<'a> {
+    Datum::Int32(i32::from(a.unwrap_inet().prefix_len()))
+}
+
+fn geometry_from_text<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let (srid, shape) = geo::parse_ewkt(a.unwrap_str())?;
+    Ok(temp_storage.make_datum(|packer| packer.push_geometry(srid, &shape)))
+}
+
+fn geometry_from_wkb<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Result<Datum<'a>, EvalError> {
+    let (srid, shape) = geo::decode_ewkb(a.unwrap_bytes())?;
+    Ok(temp_storage.make_datum(|packer| packer.push_geometry(srid, &shape)))
+}
+
+fn geometry_from_geojson<'a>(
+    a: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    let (srid, shape) = geo::parse_geojson(a.unwrap_str())?;
+    Ok(temp_storage.make_datum(|packer| packer.push_geometry(srid, &shape)))
+}
+
+fn geometry_as_text<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    Datum::String(temp_storage.push_string(a.unwrap_geometry().shape().to_string()))
+}
+
+fn geometry_as_binary<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    let mut buf = vec![];
+    geo::encode_wkb(&mut buf, &a.unwrap_geometry().shape());
+    Datum::Bytes(temp_storage.push_bytes(buf))
+}
+
+fn geometry_as_geojson<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
+    let geometry = a.unwrap_geometry();
+    let geojson = geo::format_geojson(geometry.srid(), &geometry.shape());
+    Datum::String(temp_storage.push_string(geojson))
+}
+
+fn geometry_srid<'a>(a: Datum<'a>) -> Datum<'a> {
+    Datum::Int32(a.unwrap_geometry().srid())
+}
+
 fn upper<'a>(a: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
     Datum::String(temp_storage.push_string(a.unwrap_str().to_owned().to_uppercase()))
 }
@@ -4261,16 +5077,25 @@ fn array_length<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
     }
 }
 
-fn array_index<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
-    let i = b.unwrap_int64();
-    if i < 1 {
+fn array_index<'a>(datums: &[Datum<'a>]) -> Datum<'a> {
+    let array = datums[0].unwrap_array();
+    let dims = array.dims();
+    // Like PostgreSQL, an array must be indexed with exactly as many
+    // subscripts as it has dimensions.
+    if dims.len() != datums.len() - 1 {
         return Datum::Null;
     }
-    a.unwrap_array()
-        .elements()
-        .iter()
-        .nth(i as usize - 1)
-        .unwrap_or(Datum::Null)
+    let mut offset = 0;
+    for (dim, i) in dims.into_iter().zip(&datums[1..]) {
+        let i = match usize::try_from(i.unwrap_int64()) {
+            Ok(i) if i >= dim.lower_bound && i - dim.lower_bound < dim.length => {
+                i - dim.lower_bound
+            }
+            _ => return Datum::Null,
+        };
+        offset = offset * dim.length + i;
+    }
+    array.elements().iter().nth(offset).unwrap_or(Datum::Null)
 }
 
 fn array_lower<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
@@ -4333,6 +5158,91 @@ fn array_contains<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
     Datum::from(array.elements().iter().any(|e| e == a))
 }
 
+fn array_contains_array<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    let a = a.unwrap_array().elements();
+    let b = b.unwrap_array().elements();
+    // Like PostgreSQL, null elements never match one another.
+    Datum::from(
+        b.iter()
+            .all(|b_elem| !b_elem.is_null() && a.iter().any(|a_elem| a_elem == b_elem)),
+    )
+}
+
+fn array_overlaps<'a>(a: Datum<'a>, b: Datum<'a>) -> Datum<'a> {
+    let a = a.unwrap_array().elements();
+    let b = b.unwrap_array().elements();
+    Datum::from(
+        a.iter()
+            .any(|a_elem| !a_elem.is_null() && b.iter().any(|b_elem| a_elem == b_elem)),
+    )
+}
+
+fn array_position<'a>(datums: &[Datum<'a>]) -> Result<Datum<'a>, EvalError> {
+    if datums[0].is_null() {
+        return Ok(Datum::Null);
+    }
+    let array = datums[0].unwrap_array();
+    let dim = match array.dims().into_iter().collect::<Vec<_>>()[..] {
+        [] => return Ok(Datum::Null),
+        [dim] => dim,
+        _ => return Err(EvalError::MultidimensionalArraySearch),
+    };
+    let start = match datums.get(2) {
+        None => dim.lower_bound,
+        Some(Datum::Null) => {
+            return Err(EvalError::InvalidParameterValue(
+                "initial position must not be null".into(),
+            ))
+        }
+        Some(d) => match usize::try_from(d.unwrap_int32()) {
+            Ok(start) => start.max(dim.lower_bound),
+            Err(_) => dim.lower_bound,
+        },
+    };
+    // Unlike most comparisons, array_position matches nulls.
+    let position = array
+        .elements()
+        .iter()
+        .enumerate()
+        .skip(start - dim.lower_bound)
+        .find(|(_, elem)| *elem == datums[1])
+        .map(|(i, _)| i + dim.lower_bound);
+    match position {
+        None => Ok(Datum::Null),
+        Some(i) => match i32::try_from(i) {
+            Ok(i) => Ok(Datum::Int32(i)),
+            Err(_) => Err(EvalError::Int32OutOfRange),
+        },
+    }
+}
+
+fn array_remove<'a>(
+    a: Datum<'a>,
+    b: Datum<'a>,
+    temp_storage: &'a RowArena,
+) -> Result<Datum<'a>, EvalError> {
+    if a.is_null() {
+        return Ok(a);
+    }
+    let array = a.unwrap_array();
+    if array.dims().len() > 1 {
+        return Err(EvalError::MultidimensionalArrayRemoval);
+    }
+    // Unlike most comparisons, array_remove matches nulls.
+    let elems: Vec<_> = array.elements().iter().filter(|elem| *elem != b).collect();
+    // Empty arrays have zero dimensions, not one dimension of zero length.
+    let dims = if elems.is_empty() {
+        vec![]
+    } else {
+        vec![ArrayDimension {
+            lower_bound: 1,
+            length: elems.len(),
+        }]
+    };
+    let datum = temp_storage.try_make_datum(|packer| packer.push_array(&dims, elems))?;
+    Ok(datum)
+}
+
 fn list_list_concat<'a>(a: Datum<'a>, b: Datum<'a>, temp_storage: &'a RowArena) -> Datum<'a> {
     if a.is_null() {
         return b;
@@ -4416,19 +5326,23 @@ fn mz_render_typemod<'a>(
     let mut typmod = typmod.unwrap_int32();
     let typmod_base = 65_536;
 
-    let inner = if matches!(Type::from_oid(oid as u32), Some(Type::Numeric)) && typmod >= 0 {
-        typmod -= 4;
-        if typmod < 0 {
-            temp_storage.push_string(format!("({},{})", 65_535, typmod_base + typmod))
-        } else {
-            temp_storage.push_string(format!(
-                "({},{})",
-                typmod / typmod_base,
-                typmod % typmod_base
-            ))
+    let inner = match Type::from_oid(oid as u32) {
+        Some(Type::Numeric) if typmod >= 0 => {
+            typmod -= 4;
+            if typmod < 0 {
+                temp_storage.push_string(format!("({},{})", 65_535, typmod_base + typmod))
+            } else {
+                temp_storage.push_string(format!(
+                    "({},{})",
+                    typmod / typmod_base,
+                    typmod % typmod_base
+                ))
+            }
         }
-    } else {
-        ""
+        Some(Type::BpChar) | Some(Type::VarChar) if typmod >= 4 => {
+            temp_storage.push_string(format!("({})", typmod - 4))
+        }
+        _ => "",
     };
 
     Datum::String(inner)
@@ -4451,6 +5365,8 @@ pub enum VariadicFunc {
     ArrayToString {
         elem_type: ScalarType,
     },
+    ArrayIndex,
+    ArrayPosition,
     ListCreate {
         // We need to know the element type to type empty lists.
         elem_type: ScalarType,
@@ -4458,6 +5374,13 @@ pub enum VariadicFunc {
     RecordCreate {
         field_names: Vec<ColumnName>,
     },
+    RangeCreate {
+        elem_type: ScalarType,
+    },
+    MapBuild {
+        // We need to know the value type to type empty maps.
+        value_type: ScalarType,
+    },
     ListSlice,
     SplitPart,
     RegexpMatch,
@@ -4500,9 +5423,13 @@ impl VariadicFunc {
             VariadicFunc::ArrayToString { elem_type } => {
                 eager!(array_to_string, elem_type, temp_storage)
             }
+            VariadicFunc::ArrayIndex => Ok(eager!(array_index)),
+            VariadicFunc::ArrayPosition => eager!(array_position),
             VariadicFunc::ListCreate { .. } | VariadicFunc::RecordCreate { .. } => {
                 Ok(eager!(list_create, temp_storage))
             }
+            VariadicFunc::RangeCreate { .. } => eager!(range_create, temp_storage),
+            VariadicFunc::MapBuild { .. } => Ok(eager!(map_build, temp_storage)),
             VariadicFunc::ListSlice => Ok(eager!(list_slice, temp_storage)),
             VariadicFunc::SplitPart => eager!(split_part),
             VariadicFunc::RegexpMatch => eager!(regexp_match_dynamic, temp_storage),
@@ -4542,6 +5469,12 @@ impl VariadicFunc {
                 }
             }
             ArrayToString { .. } => ScalarType::String.nullable(true),
+            ArrayIndex => input_types[0]
+                .scalar_type
+                .unwrap_array_element_type()
+                .clone()
+                .nullable(true),
+            ArrayPosition => ScalarType::Int32.nullable(true),
             ListCreate { elem_type } => {
                 debug_assert!(
                     input_types.iter().all(|t| t.scalar_type == *elem_type),
@@ -4564,6 +5497,14 @@ impl VariadicFunc {
                 custom_name: None,
             }
             .nullable(true),
+            RangeCreate { elem_type } => {
+                ScalarType::Range(Box::new(elem_type.clone())).nullable(false)
+            }
+            MapBuild { value_type } => ScalarType::Map {
+                value_type: Box::new(value_type.clone()),
+                custom_oid: None,
+            }
+            .nullable(false),
             SplitPart => ScalarType::String.nullable(true),
             RegexpMatch => ScalarType::Array(Box::new(ScalarType::String)).nullable(true),
             HmacString | HmacBytes => ScalarType::Bytes.nullable(true),
@@ -4578,8 +5519,11 @@ impl VariadicFunc {
             | VariadicFunc::JsonbBuildObject
             | VariadicFunc::ListCreate { .. }
             | VariadicFunc::RecordCreate { .. }
+            | VariadicFunc::RangeCreate { .. }
+            | VariadicFunc::MapBuild { .. }
             | VariadicFunc::ArrayCreate { .. }
-            | VariadicFunc::ArrayToString { .. })
+            | VariadicFunc::ArrayToString { .. }
+            | VariadicFunc::ArrayPosition)
     }
 }
 
@@ -4596,8 +5540,12 @@ impl fmt::Display for VariadicFunc {
             VariadicFunc::JsonbBuildObject => f.write_str("jsonb_build_object"),
             VariadicFunc::ArrayCreate { .. } => f.write_str("array_create"),
             VariadicFunc::ArrayToString { .. } => f.write_str("array_to_string"),
+            VariadicFunc::ArrayIndex => f.write_str("array_index"),
+            VariadicFunc::ArrayPosition => f.write_str("array_position"),
             VariadicFunc::ListCreate { .. } => f.write_str("list_create"),
             VariadicFunc::RecordCreate { .. } => f.write_str("record_create"),
+            VariadicFunc::RangeCreate { .. } => f.write_str("range_create"),
+            VariadicFunc::MapBuild { .. } => f.write_str("map_build"),
             VariadicFunc::ListSlice => f.write_str("list_slice"),
             VariadicFunc::SplitPart => f.write_str("split_string"),
             VariadicFunc::RegexpMatch => f.write_str("regexp_match"),
@@ -16,6 +16,8 @@ use serde::{Deserialize, Serialize};
 use ore::collections::CollectionExt;
 use repr::adt::array::InvalidArrayError;
 use repr::adt::datetime::DateTimeUnits;
+use repr::adt::geo::InvalidGeometryError;
+use repr::adt::range::InvalidRangeError;
 use repr::adt::regex::Regex;
 use repr::strconv::{ParseError, ParseHexError};
 use repr::{ColumnType, Datum, RelationType, Row, RowArena, ScalarType};
@@ -713,8 +715,18 @@ pub enum EvalError {
         val: i64,
     },
     InvalidArray(InvalidArrayError),
+    MultidimensionalArrayRemoval,
+    MultidimensionalArraySearch,
+    InvalidRange(InvalidRangeError),
+    InvalidGeometry(InvalidGeometryError),
     InvalidEncodingName(String),
     InvalidHashAlgorithm(String),
+    InvalidTextSearchConfig(String),
+    InvalidTsQuery(String),
+    StringValueTooLong {
+        target_type: String,
+        length: usize,
+    },
     InvalidByteSequence {
         byte_sequence: String,
         encoding_name: String,
@@ -765,8 +777,24 @@ impl fmt::Display for EvalError {
                 val, max_dim
             ),
             EvalError::InvalidArray(e) => e.fmt(f),
+            EvalError::MultidimensionalArrayRemoval => {
+                f.write_str("removing elements from multidimensional arrays is not supported")
+            }
+            EvalError::MultidimensionalArraySearch => {
+                f.write_str("searching for elements in multidimensional arrays is not supported")
+            }
+            EvalError::InvalidRange(e) => e.fmt(f),
+            EvalError::InvalidGeometry(e) => e.fmt(f),
             EvalError::InvalidEncodingName(name) => write!(f, "invalid encoding name '{}'", name),
             EvalError::InvalidHashAlgorithm(alg) => write!(f, "invalid hash algorithm '{}'", alg),
+            EvalError::InvalidTextSearchConfig(name) => {
+                write!(f, "text search configuration \"{}\" does not exist", name)
+            }
+            EvalError::InvalidTsQuery(query) => write!(f, "syntax error in tsquery: \"{}\"", query),
+            EvalError::StringValueTooLong {
+                target_type,
+                length,
+            } => write!(f, "value too long for type {}({})", target_type, length),
             EvalError::InvalidByteSequence {
                 byte_sequence,
                 encoding_name,
@@ -837,6 +865,18 @@ impl From<InvalidArrayError> for EvalError {
     }
 }
 
+impl From<InvalidRangeError> for EvalError {
+    fn from(e: InvalidRangeError) -> EvalError {
+        EvalError::InvalidRange(e)
+    }
+}
+
+impl From<InvalidGeometryError> for EvalError {
+    fn from(e: InvalidGeometryError) -> EvalError {
+        EvalError::InvalidGeometry(e)
+    }
+}
+
 impl From<regex::Error> for EvalError {
     fn from(e: regex::Error) -> EvalError {
         EvalError::InvalidRegex(e.to_string())
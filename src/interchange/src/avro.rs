@@ -37,7 +37,7 @@ use mz_avro::schema::{
 use mz_avro::{
     define_unexpected,
     error::{DecodeError, Error as AvroError},
-    give_value,
+    read_exact_into,
     types::{DecimalValue, Scalar, Value},
     AvroArrayAccess, AvroDecode, AvroDeserializer, AvroMapAccess, AvroRead, AvroRecordAccess,
     GeneralDeserializer, StatefulAvroDecodable, TrivialDecoder, ValueDecoder, ValueOrReader,
@@ -45,6 +45,7 @@ use mz_avro::{
 use ore::str::StrExt;
 use repr::adt::decimal::{Significand, MAX_DECIMAL_PRECISION};
 use repr::adt::jsonb::{JsonbPacker, JsonbRef};
+use repr::strconv;
 use repr::{ColumnName, ColumnType, Datum, RelationDesc, Row, RowPacker, ScalarType};
 
 use ordered_float::OrderedFloat;
@@ -184,8 +185,7 @@ impl<'a> AvroDecode for AvroStringDecoder<'a> {
                 val.as_bytes().read_exact(self.buf)?;
             }
             ValueOrReader::Reader { len, r } => {
-                self.buf.resize_with(len, Default::default);
-                r.read_exact(self.buf)?;
+                read_exact_into(r, len, self.buf)?;
             }
         }
         Ok(())
@@ -236,6 +236,13 @@ impl AvroDecode for AvroDbzSnapshotDecoder {
         let s = match r {
             ValueOrReader::Value(val) => val.as_bytes(),
             ValueOrReader::Reader { len, r } => {
+                if len > s.inline_size() {
+                    return Err(DecodeError::Custom(format!(
+                        "`snapshot` had unexpected length {}",
+                        len
+                    ))
+                    .into());
+                }
                 s.resize_with(len, Default::default);
                 r.read_exact(&mut s)?;
                 &s
@@ -637,46 +644,7 @@ impl<'a> AvroDecode for AvroFlatDecoder<'a> {
         a: &mut A,
     ) -> Result<Self::Out, AvroError> {
         let mut str_buf = std::mem::take(self.buf);
-        let mut pack_record = |rp: &mut RowPacker| -> Result<(), AvroError> {
-            let mut expected = 0;
-            let mut stash = vec![];
-            // The idea here is that if the deserializer gives us fields in the order we're expecting,
-            // we can decode them directly into the row.
-            // If not, we need to decode them into a Value (the old, slow decoding path) and stash them,
-            // so that we can put everything in the right order at the end.
-            //
-            // TODO(btv) - this is pretty bad, as a misordering at the top of the schema graph will
-            // cause the _entire_ chunk under it to be decoded in the slow way!
-            // Maybe instead, we should decode to separate sub-RowPackers and then add an API
-            // to RowPacker that just copies in the bytes from another one.
-            while let Some((_name, idx, f)) = a.next_field()? {
-                let dec = AvroFlatDecoder {
-                    packer: rp,
-                    buf: &mut str_buf,
-                    is_top: false,
-                };
-                if idx == expected {
-                    expected += 1;
-                    f.decode_field(dec)?;
-                } else {
-                    let next = ValueDecoder;
-                    let val = f.decode_field(next)?;
-                    stash.push((idx, val));
-                }
-            }
-            stash.sort_by_key(|(idx, _val)| *idx);
-            for (idx, val) in stash {
-                assert!(idx == expected);
-                expected += 1;
-                let dec = AvroFlatDecoder {
-                    packer: rp,
-                    buf: &mut str_buf,
-                    is_top: false,
-                };
-                give_value(dec, &val)?;
-            }
-            Ok(())
-        };
+        let pack_record = |rp: &mut RowPacker| pack_record_fields(a, rp, &mut str_buf, None);
         if self.is_top {
             pack_record(self.packer)?;
         } else {
@@ -757,8 +725,7 @@ impl<'a> AvroDecode for AvroFlatDecoder<'a> {
         let buf = match r {
             ValueOrReader::Value(val) => val,
             ValueOrReader::Reader { len, r } => {
-                self.buf.resize_with(len, Default::default);
-                r.read_exact(self.buf)?;
+                read_exact_into(r, len, self.buf)?;
                 &self.buf
             }
         };
@@ -776,8 +743,7 @@ impl<'a> AvroDecode for AvroFlatDecoder<'a> {
         let buf = match r {
             ValueOrReader::Value(val) => val,
             ValueOrReader::Reader { len, r } => {
-                self.buf.resize_with(len, Default::default);
-                r.read_exact(self.buf)?;
+                read_exact_into(r, len, self.buf)?;
                 &self.buf
             }
         };
@@ -796,8 +762,7 @@ impl<'a> AvroDecode for AvroFlatDecoder<'a> {
                 // we should special case to just look at the bytes
                 // directly when r is &[u8].
                 // It probably doesn't make a huge difference though.
-                self.buf.resize_with(len, Default::default);
-                r.read_exact(self.buf)?;
+                read_exact_into(r, len, self.buf)?;
                 std::str::from_utf8(&self.buf).map_err(|_| DecodeError::StringUtf8Error)?
             }
         };
@@ -816,8 +781,7 @@ impl<'a> AvroDecode for AvroFlatDecoder<'a> {
                     .map_err(|e| DecodeError::Custom(e.to_string()))?;
             }
             ValueOrReader::Reader { len, r } => {
-                self.buf.resize_with(len, Default::default);
-                r.read_exact(self.buf)?;
+                read_exact_into(r, len, self.buf)?;
                 *self.packer = JsonbPacker::new(std::mem::take(self.packer))
                     .pack_slice(&self.buf)
                     .map_err(|e| DecodeError::Custom(e.to_string()))?;
@@ -833,8 +797,7 @@ impl<'a> AvroDecode for AvroFlatDecoder<'a> {
         let buf = match r {
             ValueOrReader::Value(val) => val,
             ValueOrReader::Reader { len, r } => {
-                self.buf.resize_with(len, Default::default);
-                r.read_exact(self.buf)?;
+                read_exact_into(r, len, self.buf)?;
                 &self.buf
             }
         };
@@ -873,31 +836,122 @@ impl<'a> AvroDecode for AvroFlatDecoder<'a> {
     }
     #[inline]
     fn map<A: AvroMapAccess>(self, a: &mut A) -> Result<Self::Out, AvroError> {
-        // Map (key, value) pairs need to be unique and ordered.
+        // Map (key, value) pairs need to be unique and ordered, so the values
+        // are decoded into rows of their own and packed once all entries have
+        // been read.
         let mut map = BTreeMap::new();
         while let Some((name, f)) = a.next_entry()? {
-            map.insert(name, f.decode_field(ValueDecoder)?);
-        }
-        self.packer
-            .push_dict_with(|packer| -> Result<(), AvroError> {
-                for (key, val) in map {
-                    packer.push(Datum::String(key.as_str()));
-                    give_value(
-                        AvroFlatDecoder {
-                            packer,
-                            buf: &mut vec![],
-                            is_top: false,
-                        },
-                        &val,
-                    )?;
-                }
-                Ok(())
+            let mut packer = RowPacker::new();
+            f.decode_field(AvroFlatDecoder {
+                packer: &mut packer,
+                buf: self.buf,
+                is_top: false,
             })?;
+            map.insert(name, packer.finish());
+        }
+        self.packer.push_dict_with(|packer| {
+            for (key, val) in &map {
+                packer.push(Datum::String(key.as_str()));
+                packer.extend_by_row(val);
+            }
+        });
 
         Ok(())
     }
 }
 
+/// Packs the fields of the record accessed by `a` into `packer`, in the order
+/// in which they appear in the reader schema.
+///
+/// Fields that the deserializer produces in that order are decoded directly
+/// into `packer`. Any other field is decoded into a row of its own, which is
+/// copied into place once the fields that precede it have been packed.
+///
+/// If `demanded` is provided, it describes the top-level fields of the record,
+/// and fields that are not demanded are skipped on the wire, with a
+/// `Datum::Dummy` packed in place of each of their columns.
+fn pack_record_fields<R: AvroRead, A: AvroRecordAccess<R>>(
+    a: &mut A,
+    packer: &mut RowPacker,
+    buf: &mut Vec<u8>,
+    demanded: Option<&[DemandedField]>,
+) -> Result<(), AvroError> {
+    let mut expected = 0;
+    let mut stash = vec![];
+    while let Some((_name, idx, f)) = a.next_field()? {
+        let skip_width = demanded
+            .and_then(|demanded| demanded.get(idx))
+            .filter(|field| !field.demanded)
+            .map(|field| field.width);
+        if idx == expected {
+            expected += 1;
+            match skip_width {
+                None => f.decode_field(AvroFlatDecoder {
+                    packer,
+                    buf,
+                    is_top: false,
+                })?,
+                Some(width) => {
+                    f.decode_field(TrivialDecoder)?;
+                    packer.extend(iter::repeat(Datum::Dummy).take(width));
+                }
+            }
+        } else {
+            let mut field_packer = RowPacker::new();
+            match skip_width {
+                None => f.decode_field(AvroFlatDecoder {
+                    packer: &mut field_packer,
+                    buf,
+                    is_top: false,
+                })?,
+                Some(width) => {
+                    f.decode_field(TrivialDecoder)?;
+                    field_packer.extend(iter::repeat(Datum::Dummy).take(width));
+                }
+            }
+            stash.push((idx, field_packer.finish()));
+        }
+    }
+    stash.sort_by_key(|(idx, _row)| *idx);
+    for (idx, row) in stash {
+        assert!(idx == expected);
+        expected += 1;
+        packer.extend_by_row(&row);
+    }
+    Ok(())
+}
+
+/// Describes how a top-level field of a record schema is decoded when only
+/// some columns are demanded.
+#[derive(Debug, Clone, Copy)]
+struct DemandedField {
+    /// The number of columns that the field decodes into.
+    width: usize,
+    /// Whether any of the field's columns are demanded.
+    demanded: bool,
+}
+
+/// Decodes the top-level record of a row, skipping the fields described as
+/// not demanded by `demanded`.
+struct ProjectedRowDecoder<'a> {
+    packer: &'a mut RowPacker,
+    buf: &'a mut Vec<u8>,
+    demanded: &'a [DemandedField],
+}
+
+impl<'a> AvroDecode for ProjectedRowDecoder<'a> {
+    type Out = ();
+    fn record<R: AvroRead, A: AvroRecordAccess<R>>(
+        self,
+        a: &mut A,
+    ) -> Result<Self::Out, AvroError> {
+        pack_record_fields(a, self.packer, self.buf, Some(self.demanded))
+    }
+    define_unexpected! {
+        union_branch, array, map, enum_variant, scalar, decimal, bytes, string, json, uuid, fixed
+    }
+}
+
 /// Converts an Apache Avro schema into a list of column names and types.
 pub fn validate_value_schema(
     schema: &str,
@@ -2161,6 +2215,8 @@ pub struct Decoder {
     buf1: Vec<u8>,
     buf2: Vec<u8>,
     packer: RowPacker,
+    field_widths: Option<Vec<usize>>,
+    demanded: Option<Vec<DemandedField>>,
 }
 
 impl fmt::Debug for Decoder {
@@ -2199,6 +2255,20 @@ impl Decoder {
             debezium_dedup.map(|strat| DebeziumDeduplicationState::new(strat, key_indices));
         let csr_avro =
             ConfluentAvroResolver::new(reader_schema, schema_registry, confluent_wire_format)?;
+        let field_widths = match parse_schema(reader_schema)?.top_node().inner {
+            SchemaPiece::Record { fields, .. } => Some(
+                fields
+                    .iter()
+                    .map(|f| match &f.schema {
+                        SchemaPieceOrNamed::Piece(SchemaPiece::Union(us)) => {
+                            us.variants().iter().filter(|v| !is_null(v)).count()
+                        }
+                        _ => 1,
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        };
 
         Ok(Decoder {
             csr_avro,
@@ -2209,9 +2279,40 @@ impl Decoder {
             buf1: vec![],
             buf2: vec![],
             packer: Default::default(),
+            field_widths,
+            demanded: None,
         })
     }
 
+    /// Restricts decoding to the columns for which `demanded` is `true`.
+    ///
+    /// Top-level fields none of whose columns are demanded are skipped on the
+    /// wire, without being decoded, and `Datum::Dummy` is packed in place of
+    /// each of their columns. Has no effect for the Debezium envelope, whose
+    /// deduplication reads the key columns of every row, or if the reader
+    /// schema is not a record.
+    pub fn set_demanded_columns(&mut self, demanded: Vec<bool>) {
+        if self.envelope == EnvelopeType::Debezium {
+            return;
+        }
+        if let Some(field_widths) = &self.field_widths {
+            let mut col = 0;
+            let fields = field_widths
+                .iter()
+                .map(|width| {
+                    let field = DemandedField {
+                        width: *width,
+                        demanded: (col..col + width)
+                            .any(|i| demanded.get(i).copied().unwrap_or(true)),
+                    };
+                    col += width;
+                    field
+                })
+                .collect();
+            self.demanded = Some(fields);
+        }
+    }
+
     /// Decodes Avro-encoded `bytes` into a `DiffPair`.
     pub async fn decode(
         &mut self,
@@ -2268,15 +2369,27 @@ impl Decoder {
                 }
             }
         } else {
-            let dec = AvroFlatDecoder {
-                packer: &mut self.packer,
-                buf: &mut self.buf1,
-                is_top: true,
-            };
             let dsr = GeneralDeserializer {
                 schema: resolved_schema.top_node(),
             };
-            dsr.deserialize(&mut bytes, dec)?;
+            match &self.demanded {
+                None => dsr.deserialize(
+                    &mut bytes,
+                    AvroFlatDecoder {
+                        packer: &mut self.packer,
+                        buf: &mut self.buf1,
+                        is_top: true,
+                    },
+                )?,
+                Some(demanded) => dsr.deserialize(
+                    &mut bytes,
+                    ProjectedRowDecoder {
+                        packer: &mut self.packer,
+                        buf: &mut self.buf1,
+                        demanded,
+                    },
+                )?,
+            }
             DiffPair {
                 before: None,
                 after: Some(self.packer.finish_and_reuse()),
@@ -2576,12 +2689,44 @@ impl<'a> mz_avro::types::ToAvro for TypedDatum<'a> {
                     buf
                 }),
                 ScalarType::Bytes => Value::Bytes(Vec::from(datum.unwrap_bytes())),
-                ScalarType::String => Value::String(datum.unwrap_str().to_owned()),
+                ScalarType::String
+                | ScalarType::Char { .. }
+                | ScalarType::VarChar { .. }
+                | ScalarType::TsVector
+                | ScalarType::TsQuery => Value::String(datum.unwrap_str().to_owned()),
                 ScalarType::Jsonb => Value::Json(JsonbRef::from_datum(datum).to_serde_json()),
                 ScalarType::Uuid => Value::Uuid(datum.unwrap_uuid()),
+                ScalarType::Inet => {
+                    let mut buf = String::new();
+                    strconv::format_inet(&mut buf, datum.unwrap_inet());
+                    Value::String(buf)
+                }
+                ScalarType::Cidr => {
+                    let mut buf = String::new();
+                    strconv::format_cidr(&mut buf, datum.unwrap_inet());
+                    Value::String(buf)
+                }
+                ScalarType::Geometry | ScalarType::Geography => {
+                    let geometry = datum.unwrap_geometry();
+                    let mut buf = String::new();
+                    strconv::format_geometry(&mut buf, geometry.srid(), &geometry.shape());
+                    Value::String(buf)
+                }
                 ScalarType::Array(_t) => unimplemented!("array types"),
                 ScalarType::List { .. } => unimplemented!("list types"),
                 ScalarType::Map { .. } => unimplemented!("map types"),
+                ScalarType::Range(elem_type) => {
+                    let mut buf = String::new();
+                    let bounds = datum.unwrap_range().bounds();
+                    strconv::format_range(&mut buf, bounds, |buf, elem| match &**elem_type {
+                        ScalarType::Int32 => strconv::format_int32(buf, elem.unwrap_int32()),
+                        ScalarType::TimestampTz => {
+                            strconv::format_timestamptz(buf, elem.unwrap_timestamptz())
+                        }
+                        _ => unreachable!("range over {:?}", elem_type),
+                    });
+                    Value::String(buf)
+                }
                 ScalarType::Record { fields, .. } => {
                     let list = datum.unwrap_list();
                     let fields = fields
@@ -2704,7 +2849,9 @@ fn build_row_schema_fields<F: FnMut() -> String>(
                 "logicalType": "duration"
             }),
             ScalarType::Bytes => json!("bytes"),
-            ScalarType::String => json!("string"),
+            ScalarType::String | ScalarType::Char { .. } | ScalarType::VarChar { .. } => {
+                json!("string")
+            }
             ScalarType::Jsonb => json!({
                 "type": "string",
                 "connect.name": "io.debezium.data.Json",
@@ -2713,9 +2860,13 @@ fn build_row_schema_fields<F: FnMut() -> String>(
                 "type": "string",
                 "logicalType": "uuid",
             }),
+            ScalarType::Inet | ScalarType::Cidr => json!("string"),
+            ScalarType::Geometry | ScalarType::Geography => json!("string"),
+            ScalarType::TsVector | ScalarType::TsQuery => json!("string"),
             ScalarType::Array(_t) => unimplemented!("array types"),
             ScalarType::List { .. } => unimplemented!("list types"),
             ScalarType::Map { .. } => unimplemented!("map types"),
+            ScalarType::Range(_) => json!("string"),
             ScalarType::Record {
                 fields,
                 custom_name,
@@ -3185,4 +3336,159 @@ mod tests {
 
         Ok(())
     }
+
+    const WIDE_SCHEMA: &str = r#"{
+        "type": "record",
+        "name": "row",
+        "fields": [
+            {"name": "a", "type": "long"},
+            {"name": "b", "type": ["null", "string"]},
+            {"name": "c", "type": ["null", "long", "string"]},
+            {"name": "d", "type": {"type": "map", "values": "long"}},
+            {"name": "e", "type": "string"}
+        ]
+    }"#;
+
+    fn wide_record() -> Value {
+        Value::Record(vec![
+            ("a".into(), Value::Long(1)),
+            (
+                "b".into(),
+                Value::Union {
+                    index: 1,
+                    inner: Box::new(Value::String("b".into())),
+                    n_variants: 2,
+                    null_variant: Some(0),
+                },
+            ),
+            (
+                "c".into(),
+                Value::Union {
+                    index: 2,
+                    inner: Box::new(Value::String("c".into())),
+                    n_variants: 3,
+                    null_variant: Some(0),
+                },
+            ),
+            (
+                "d".into(),
+                Value::Map(
+                    vec![("y".into(), Value::Long(2)), ("x".into(), Value::Long(1))]
+                        .into_iter()
+                        .collect(),
+                ),
+            ),
+            ("e".into(), Value::String("e".into())),
+        ])
+    }
+
+    fn new_decoder(schema: &str) -> anyhow::Result<Decoder> {
+        Decoder::new(
+            schema,
+            None,
+            EnvelopeType::None,
+            "avro_test".into(),
+            0,
+            None,
+            None,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_decode_demanded_columns() -> anyhow::Result<()> {
+        let schema = parse_schema(WIDE_SCHEMA)?;
+        let bytes = mz_avro::to_avro_datum(&schema, wide_record())?;
+
+        let mut decoder = new_decoder(WIDE_SCHEMA)?;
+        let row = futures::executor::block_on(decoder.decode(&bytes, None, None))?
+            .after
+            .unwrap();
+        let mut packer = RowPacker::new();
+        packer.extend(&[
+            Datum::Int64(1),
+            Datum::String("b"),
+            Datum::Null,
+            Datum::String("c"),
+        ]);
+        packer.push_dict_with(|packer| {
+            packer.extend(&[
+                Datum::String("x"),
+                Datum::Int64(1),
+                Datum::String("y"),
+                Datum::Int64(2),
+            ])
+        });
+        packer.push(Datum::String("e"));
+        assert_eq!(row, packer.finish());
+
+        // The columns of `c` are only partly demanded, so both are decoded.
+        decoder.set_demanded_columns(vec![false, true, false, true, false, true]);
+        let row = futures::executor::block_on(decoder.decode(&bytes, None, None))?
+            .after
+            .unwrap();
+        assert_eq!(
+            row.unpack(),
+            vec![
+                Datum::Dummy,
+                Datum::String("b"),
+                Datum::Null,
+                Datum::String("c"),
+                Datum::Dummy,
+                Datum::String("e"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_reordered_fields() -> anyhow::Result<()> {
+        // The writer's fields are in the reverse order of the reader's.
+        let writer_schema = parse_schema(
+            r#"{
+                "type": "record",
+                "name": "row",
+                "fields": [
+                    {"name": "e", "type": "string"},
+                    {"name": "d", "type": {"type": "map", "values": "long"}},
+                    {"name": "c", "type": ["null", "long", "string"]},
+                    {"name": "b", "type": ["null", "string"]},
+                    {"name": "a", "type": "long"}
+                ]
+            }"#,
+        )?;
+        let record = match wide_record() {
+            Value::Record(mut fields) => {
+                fields.reverse();
+                Value::Record(fields)
+            }
+            _ => unreachable!(),
+        };
+        let bytes = mz_avro::to_avro_datum(&writer_schema, record)?;
+        let resolved = resolve_schemas(&writer_schema, &parse_schema(WIDE_SCHEMA)?)?;
+
+        let mut packer = RowPacker::new();
+        let mut buf = vec![];
+        GeneralDeserializer {
+            schema: resolved.top_node(),
+        }
+        .deserialize(
+            &mut &bytes[..],
+            AvroFlatDecoder {
+                packer: &mut packer,
+                buf: &mut buf,
+                is_top: true,
+            },
+        )?;
+        let datums = packer.finish();
+        let datums = datums.unpack();
+        assert_eq!(datums.len(), 6);
+        assert_eq!(datums[0], Datum::Int64(1));
+        assert_eq!(datums[1], Datum::String("b"));
+        assert_eq!(datums[3], Datum::String("c"));
+        assert_eq!(datums[5], Datum::String("e"));
+
+        Ok(())
+    }
 }
@@ -14,6 +14,7 @@ use std::collections::HashSet;
 use anyhow::{anyhow, bail, Context, Result};
 use num_traits::ToPrimitive;
 use ordered_float::OrderedFloat;
+use protobuf::wire_format::WireType;
 use serde::de::Deserialize;
 use serde_protobuf::de::Deserializer;
 use serde_protobuf::descriptor::{
@@ -172,6 +173,11 @@ pub struct Decoder {
     descriptors: Descriptors,
     message_name: String,
     packer: RowPacker,
+    demanded: Option<Vec<bool>>,
+    /// The numbers of the fields whose columns are demanded, if any are not.
+    demanded_fields: Option<HashSet<i32>>,
+    /// Scratch space for the demanded fields of the message being decoded.
+    filtered: Vec<u8>,
 }
 
 impl Decoder {
@@ -185,10 +191,41 @@ impl Decoder {
             descriptors,
             message_name: proto_message_name(message_name),
             packer: RowPacker::new(),
+            demanded: None,
+            demanded_fields: None,
+            filtered: Vec::new(),
         }
     }
 
+    /// Restricts decoding to the columns for which `demanded` is `true`.
+    ///
+    /// Fields whose columns are not demanded are skipped over on the wire
+    /// without being parsed, and `Datum::Dummy` is packed in their place.
+    pub fn set_demanded_columns(&mut self, demanded: Vec<bool>) {
+        self.demanded_fields = match self.descriptors.message_by_name(&self.message_name) {
+            Some(message) if demanded.iter().any(|d| !d) => Some(
+                message
+                    .fields()
+                    .iter()
+                    .zip(&demanded)
+                    .filter(|(_, demanded)| **demanded)
+                    .map(|(f, _)| f.number())
+                    .collect(),
+            ),
+            _ => None,
+        };
+        self.demanded = Some(demanded);
+    }
+
     pub fn decode(&mut self, bytes: &[u8], position: Option<i64>) -> Result<Option<Row>> {
+        let bytes = match &self.demanded_fields {
+            Some(fields) => {
+                filter_fields(bytes, fields, &mut self.filtered)
+                    .context("Skipping undemanded fields")?;
+                &self.filtered[..]
+            }
+            None => bytes,
+        };
         let input_stream = protobuf::CodedInputStream::from_bytes(bytes);
         let mut deserializer =
             Deserializer::for_named_message(&self.descriptors, &self.message_name, input_stream)
@@ -207,6 +244,7 @@ impl Decoder {
                     msg_name
                 )
             })?,
+            self.demanded.as_deref(),
             &mut packer,
         )?;
         if let Some(pos) = position {
@@ -216,10 +254,46 @@ impl Decoder {
     }
 }
 
+/// Copies the fields of the serialized message in `bytes` whose numbers are in
+/// `fields` to `out`, skipping over the other fields without parsing them.
+fn filter_fields(bytes: &[u8], fields: &HashSet<i32>, out: &mut Vec<u8>) -> Result<()> {
+    out.clear();
+    let mut input = protobuf::CodedInputStream::from_bytes(bytes);
+    while !input.eof()? {
+        let start = input.pos() as usize;
+        let (number, wire_type) = input.read_tag_unpack()?;
+        match wire_type {
+            WireType::WireTypeVarint => {
+                input.read_raw_varint64()?;
+            }
+            WireType::WireTypeFixed64 => {
+                input.read_raw_little_endian64()?;
+            }
+            WireType::WireTypeFixed32 => {
+                input.read_raw_little_endian32()?;
+            }
+            WireType::WireTypeLengthDelimited => {
+                let len = input.read_raw_varint32()?;
+                input.skip_raw_bytes(len)?;
+            }
+            wire_type => {
+                input.read_unknown(wire_type)?;
+            }
+        }
+        // A field may appear more than once, e.g. if it is repeated, so every
+        // occurrence of a demanded field is kept.
+        if fields.contains(&(number as i32)) {
+            out.extend_from_slice(&bytes[start..input.pos() as usize]);
+        }
+    }
+    Ok(())
+}
+
 fn extract_row_into(
     deserialized_message: SerdeValue,
     descriptors: &Descriptors,
     message_descriptors: &MessageDescriptor,
+    demanded: Option<&[bool]>,
     packer: &mut RowPacker,
 ) -> Result<()> {
     let deserialized_message = match deserialized_message {
@@ -228,7 +302,11 @@ fn extract_row_into(
     };
 
     // TODO: This is actually unpacking a row, it should always return json
-    for f in message_descriptors.fields().iter() {
+    for (i, f) in message_descriptors.fields().iter().enumerate() {
+        if demanded.and_then(|d| d.get(i)) == Some(&false) {
+            packer.push(Datum::Dummy);
+            continue;
+        }
         let key = SerdeValue::String(f.name().to_string());
         let value = deserialized_message.get(&key);
 
@@ -276,7 +354,7 @@ fn default_datum_from_field<'a>(
         FieldType::Int64 | FieldType::SInt64 | FieldType::SFixed64 => Ok(Datum::Int64(0)),
         FieldType::Enum(e) => Ok(Datum::String(
             e.value_by_number(0)
-                .expect("Error while deserializing protobuf: expected enum to have zero variant")
+                .ok_or_else(|| anyhow!("Enum {} has no zero variant", e.name()))?
                 .name(),
         )),
         FieldType::Float => Ok(Datum::Float32(OrderedFloat::from(0.0))),
@@ -342,7 +420,7 @@ fn default_datum_from_field_nested<'a>(
         | FieldType::Double => Ok(Datum::Float64(OrderedFloat::from(0.0))),
         FieldType::Enum(e) => Ok(Datum::String(
             e.value_by_number(0)
-                .expect("Error while deserializing protobuf: expected enum to have zero variant")
+                .ok_or_else(|| anyhow!("Enum {} has no zero variant", e.name()))?
                 .name(),
         )),
         FieldType::String => Ok(Datum::String("")),
@@ -451,14 +529,11 @@ fn json_nested_from_serde_value(
                                 _ => bail!("Nested message is the wrong type"),
                             };
 
-                            json_nested_from_serde_value(
-                                &v,
-                                packer,
-                                nested_message_descriptor
-                                    .field_by_name(s)
-                                    .expect("Expected this to work"),
-                                descriptors,
-                            )?;
+                            let field = match nested_message_descriptor.field_by_name(s) {
+                                Some(field) => field,
+                                None => bail!("Nested message has no field {}", s),
+                            };
+                            json_nested_from_serde_value(&v, packer, field, descriptors)?;
                         }
                         _ => bail!("Unrecognized value while trying to parse a nested message"),
                     }
@@ -697,6 +772,75 @@ mod tests {
         assert_eq!(datums, expected);
     }
 
+    #[test]
+    fn test_decode_demanded_columns() {
+        let mut test_record = TestRecord::new();
+
+        test_record.set_int_field(1);
+        test_record.set_string_field("one".to_string());
+        test_record.set_int64_field(10000);
+        test_record.set_color_field(Color::BLUE);
+        test_record.set_uint_field(5);
+        test_record.set_uint64_field(55);
+        test_record.set_float_field(5.456);
+        test_record.set_double_field(99.99);
+
+        let bytes = test_record
+            .write_to_bytes()
+            .expect("test failed to serialize to bytes");
+
+        let mut decoder = get_decoder(".TestRecord");
+        decoder.set_demanded_columns(vec![false, true, false, true, false, false, false, true]);
+        let row = decoder
+            .decode(&bytes, Some(7))
+            .expect("deserialize protobuf into a row")
+            .unwrap();
+        let datums = row.iter().collect::<Vec<_>>();
+
+        let expected = vec![
+            Datum::Dummy,
+            Datum::String("one"),
+            Datum::Dummy,
+            Datum::String("BLUE"),
+            Datum::Dummy,
+            Datum::Dummy,
+            Datum::Dummy,
+            Datum::Float64(OrderedFloat::from(99.99)),
+            Datum::Int64(7),
+        ];
+
+        assert_eq!(datums, expected);
+    }
+
+    #[test]
+    fn test_decode_demanded_repeated_columns() {
+        let mut test_record = TestRepeatedRecord::new();
+        test_record.set_int_field(vec![1, 2, 3]);
+        test_record.set_string_field(RepeatedField::from_vec(vec![
+            "one".to_string(),
+            "two".to_string(),
+        ]));
+        let bytes = test_record
+            .write_to_bytes()
+            .expect("test failed to serialize to bytes");
+
+        let mut decoder = get_decoder(".TestRepeatedRecord");
+        decoder.set_demanded_columns(vec![false, false, true]);
+        let row = decoder
+            .decode(&bytes, None)
+            .expect("deserialize protobuf into a row")
+            .unwrap();
+        let datums = row.iter().collect::<Vec<_>>();
+
+        assert_eq!(&datums[..2], &[Datum::Dummy, Datum::Dummy]);
+        if let Datum::List(d) = datums[2] {
+            let datumlist = d.iter().collect::<Vec<Datum>>();
+            assert_eq!(datumlist, vec![Datum::String("one"), Datum::String("two")]);
+        } else {
+            panic!("Expected the third field to be a list of datums!");
+        }
+    }
+
     #[test]
     fn test_repeated() {
         let mut test_record = TestRepeatedRecord::new();
@@ -674,6 +674,6 @@ async fn main() -> anyhow::Result<()> {
             }
         }
     }
-    producer.flush(Timeout::Never);
+    producer.flush(Timeout::Never)?;
     Ok(())
 }
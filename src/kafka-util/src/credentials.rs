@@ -0,0 +1,209 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Detection of rotated credential files.
+//!
+//! librdkafka reads certificates, keys, and keytabs from disk only when a
+//! client is created, so a long-lived client keeps using the old credentials
+//! after they are rotated on disk. [`CredentialFiles`] notices when any of the
+//! files named in a client's configuration change, so that the owner of the
+//! client can rebuild it.
+//!
+//! SASL passwords can be rotated the same way. librdkafka only accepts the
+//! password itself, so the `sasl.password.file` option names a file that
+//! holds the password instead, and [`set_config_options`] reads that file
+//! whenever a client is configured.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::Context;
+use rdkafka::ClientConfig;
+
+/// The configuration option whose value names a file containing the SASL
+/// password.
+///
+/// This is not a librdkafka option. [`set_config_options`] replaces it with
+/// the `sasl.password` option.
+pub const SASL_PASSWORD_FILE_OPTION: &str = "sasl.password.file";
+
+/// The configuration options whose values name files that librdkafka reads
+/// credentials from.
+const CREDENTIAL_FILE_OPTIONS: &[&str] = &[
+    SASL_PASSWORD_FILE_OPTION,
+    "sasl.kerberos.keytab",
+    "ssl.ca.location",
+    "ssl.certificate.location",
+    "ssl.key.location",
+];
+
+/// The minimum interval between checks for changed credential files.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Tracks the credential files named in a Kafka client's configuration.
+#[derive(Debug)]
+pub struct CredentialFiles {
+    /// The paths of the files, and their fingerprints as of the last check.
+    files: Vec<(String, Option<Fingerprint>)>,
+    /// When the files were last checked.
+    last_check: Instant,
+}
+
+/// Identifies a version of a file without reading its contents.
+#[derive(Debug, PartialEq, Eq)]
+struct Fingerprint {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl Fingerprint {
+    /// Fingerprints the file at `path`, or returns `None` if the file cannot be
+    /// inspected, e.g. because it is in the middle of being replaced.
+    fn of(path: &str) -> Option<Fingerprint> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Fingerprint {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+impl CredentialFiles {
+    /// Starts tracking the credential files named in `config_options`.
+    ///
+    /// To avoid missing a rotation, call this before creating the client
+    /// that reads the files, not after.
+    pub fn new(config_options: &BTreeMap<String, String>) -> CredentialFiles {
+        let files = CREDENTIAL_FILE_OPTIONS
+            .iter()
+            .filter_map(|option| config_options.get(*option))
+            .map(|path| (path.clone(), Fingerprint::of(path)))
+            .collect();
+        CredentialFiles {
+            files,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Reports whether any of the tracked files have changed since they were
+    /// first tracked.
+    ///
+    /// A change continues to be reported until the owner of the client
+    /// replaces this `CredentialFiles` with one that tracks the files as of
+    /// the rebuilt client, so a failed rebuild is retried at the next check.
+    ///
+    /// The files are inspected at most once every ten seconds, so this method
+    /// is cheap enough to call on every activation of a source or sink. A file
+    /// that cannot be inspected is not considered changed until it reappears,
+    /// so that clients are not rebuilt while credentials are half-written.
+    pub fn changed(&mut self) -> bool {
+        if self.files.is_empty() || self.last_check.elapsed() < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = Instant::now();
+        self.files
+            .iter()
+            .any(|(path, fingerprint)| match Fingerprint::of(path) {
+                Some(new) => fingerprint.as_ref() != Some(&new),
+                None => false,
+            })
+    }
+}
+
+/// Sets each of `config_options` on `config`.
+///
+/// The `sasl.password.file` option is replaced by the `sasl.password` option,
+/// whose value is read from the named file, less any trailing newline.
+///
+/// # Errors
+///
+/// - The file named by `sasl.password.file` cannot be read.
+pub fn set_config_options(
+    config: &mut ClientConfig,
+    config_options: &BTreeMap<String, String>,
+) -> Result<(), anyhow::Error> {
+    for (k, v) in config_options {
+        if k == SASL_PASSWORD_FILE_OPTION {
+            let password = fs::read_to_string(v)
+                .with_context(|| format!("reading SASL password from {}", v))?;
+            config.set(
+                "sasl.password",
+                password.trim_end_matches(&['\r', '\n'][..]),
+            );
+        } else {
+            config.set(k, v);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn test_credential_files_changed() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("ca.pem");
+        fs::write(&path, "old")?;
+
+        let mut config_options = BTreeMap::new();
+        config_options.insert("ssl.ca.location".into(), path.display().to_string());
+        config_options.insert("client.id".into(), "materialize".into());
+        let mut files = CredentialFiles::new(&config_options);
+        assert_eq!(files.files.len(), 1);
+
+        // Nothing has changed, and the check interval has not elapsed.
+        assert!(!files.changed());
+        files.last_check -= CHECK_INTERVAL;
+        assert!(!files.changed());
+
+        // A rotated file is reported until the files are tracked anew.
+        fs::write(&path, "rotated")?;
+        assert!(!files.changed());
+        files.last_check -= CHECK_INTERVAL;
+        assert!(files.changed());
+        files.last_check -= CHECK_INTERVAL;
+        assert!(files.changed());
+        let mut files = CredentialFiles::new(&config_options);
+        files.last_check -= CHECK_INTERVAL;
+        assert!(!files.changed());
+
+        // A missing file is not a change.
+        fs::remove_file(&path)?;
+        files.last_check -= CHECK_INTERVAL;
+        assert!(!files.changed());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_config_options() -> Result<(), Box<dyn Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("password");
+        fs::write(&path, "hunter2\n")?;
+
+        let mut config_options = BTreeMap::new();
+        config_options.insert(SASL_PASSWORD_FILE_OPTION.into(), path.display().to_string());
+        config_options.insert("sasl.username".into(), "materialize".into());
+        let mut config = ClientConfig::new();
+        set_config_options(&mut config, &config_options)?;
+        assert_eq!(config.get("sasl.password"), Some("hunter2"));
+        assert_eq!(config.get("sasl.username"), Some("materialize"));
+        assert_eq!(config.get(SASL_PASSWORD_FILE_OPTION), None);
+
+        fs::remove_file(&path)?;
+        assert!(set_config_options(&mut config, &config_options).is_err());
+
+        Ok(())
+    }
+}
@@ -14,5 +14,7 @@
 mod addr;
 
 pub mod admin;
+pub mod credentials;
+pub mod oauth;
 
 pub use addr::{KafkaAddrs, KafkaAddrsParseError};
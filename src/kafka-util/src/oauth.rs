@@ -0,0 +1,223 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! SASL/OAUTHBEARER token providers.
+//!
+//! librdkafka delegates the acquisition of OAUTHBEARER tokens to the
+//! application via a token refresh callback, which receives the value of the
+//! `sasl.oauthbearer.config` option verbatim. We interpret that option as a
+//! whitespace-separated list of `key=value` pairs. The `provider` key selects
+//! a [`TokenProvider`], and the remaining keys configure it.
+//!
+//! Threading the configuration through `sasl.oauthbearer.config` means that
+//! any client built from a source or sink's configuration options can generate
+//! tokens without further plumbing, so long as it is created with a context
+//! that calls [`generate_token`], like [`MzClientContext`].
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context};
+use rdkafka::client::{ClientContext, OAuthToken};
+use rdkafka::consumer::ConsumerContext;
+use rusoto_core::Region;
+use rusoto_credential::{AwsCredentials, ChainProvider, ProvideAwsCredentials};
+use rusoto_signature::SignedRequest;
+
+/// The lifetime of tokens generated by [`MskIamTokenProvider`].
+///
+/// This is the maximum lifetime that MSK accepts.
+const MSK_IAM_TOKEN_LIFETIME: Duration = Duration::from_secs(900);
+
+/// Generates OAUTHBEARER tokens.
+pub trait TokenProvider {
+    /// Generates a new token.
+    ///
+    /// librdkafka calls this again before the previously generated token
+    /// expires, so implementations need not cache tokens.
+    fn token(&self) -> Result<OAuthToken, anyhow::Error>;
+}
+
+/// Constructs the [`TokenProvider`] described by the value of a
+/// `sasl.oauthbearer.config` option.
+///
+/// # Errors
+///
+/// - The option is not a list of `key=value` pairs.
+/// - The option does not name a known provider.
+/// - The option contains keys that the provider does not understand, or is
+///   missing keys the provider requires.
+pub fn provider_from_config(config: &str) -> Result<Box<dyn TokenProvider>, anyhow::Error> {
+    let mut options = parse_config(config)?;
+    let name = options
+        .remove("provider")
+        .ok_or_else(|| anyhow!("missing provider"))?;
+    let provider: Box<dyn TokenProvider> = match name.as_str() {
+        "aws_msk_iam" => Box::new(MskIamTokenProvider::from_options(&mut options)?),
+        _ => bail!("unknown provider: {}", name),
+    };
+    if let Some(key) = options.keys().next() {
+        bail!("unexpected key {} for provider {}", key, name);
+    }
+    Ok(provider)
+}
+
+fn parse_config(config: &str) -> Result<BTreeMap<String, String>, anyhow::Error> {
+    let mut options = BTreeMap::new();
+    for pair in config.split_whitespace() {
+        let mut parts = pair.splitn(2, '=');
+        let key = parts.next().expect("splitn always yields one item");
+        let value = match parts.next() {
+            Some(value) => value,
+            None => bail!("expected key=value, but got {}", pair),
+        };
+        if options.insert(key.to_owned(), value.to_owned()).is_some() {
+            bail!("duplicate key {}", key);
+        }
+    }
+    Ok(options)
+}
+
+/// Generates a token using the provider described by `config`, the value of
+/// the `sasl.oauthbearer.config` option that librdkafka passes to
+/// [`ClientContext::generate_oauth_token`].
+pub fn generate_token(config: Option<&str>) -> Result<OAuthToken, Box<dyn Error>> {
+    let config = config.ok_or("sasl.oauthbearer.config must be set to use OAUTHBEARER")?;
+    let provider = provider_from_config(config)?;
+    Ok(provider.token()?)
+}
+
+/// Generates tokens for Amazon MSK's IAM access control scheme.
+///
+/// The token is a `kafka-cluster:Connect` request that is presigned with AWS
+/// Signature Version 4, encoded as unpadded URL-safe base64. Credentials are
+/// resolved using the standard AWS credential chain, i.e., from the
+/// environment, the shared credentials file, or the instance metadata service.
+#[derive(Debug)]
+pub struct MskIamTokenProvider {
+    region: Region,
+}
+
+impl MskIamTokenProvider {
+    /// Constructs a provider that signs requests for the specified region.
+    pub fn new(region: Region) -> MskIamTokenProvider {
+        MskIamTokenProvider { region }
+    }
+
+    fn from_options(
+        options: &mut BTreeMap<String, String>,
+    ) -> Result<MskIamTokenProvider, anyhow::Error> {
+        let region = match options.remove("region") {
+            Some(region) => region
+                .parse()
+                .with_context(|| format!("invalid region {}", region))?,
+            None => bail!("provider aws_msk_iam requires a region"),
+        };
+        Ok(MskIamTokenProvider::new(region))
+    }
+
+    /// Signs a token with the provided credentials.
+    pub fn sign(&self, credentials: &AwsCredentials) -> String {
+        let mut request = SignedRequest::new("GET", "kafka-cluster", &self.region, "/");
+        request.set_hostname(Some(format!("kafka.{}.amazonaws.com", self.region.name())));
+        request.add_param("Action", "kafka-cluster:Connect");
+        let url = request.generate_presigned_url(credentials, &MSK_IAM_TOKEN_LIFETIME, false);
+        base64::encode_config(url, base64::URL_SAFE_NO_PAD)
+    }
+}
+
+impl TokenProvider for MskIamTokenProvider {
+    fn token(&self) -> Result<OAuthToken, anyhow::Error> {
+        // Token refreshes happen on a librdkafka background thread, so we need
+        // a runtime of our own to drive the credential chain.
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let credentials = runtime
+            .block_on(ChainProvider::new().credentials())
+            .context("loading AWS credentials")?;
+        let expiration = SystemTime::now() + MSK_IAM_TOKEN_LIFETIME;
+        Ok(OAuthToken {
+            token: self.sign(&credentials),
+            principal_name: credentials.aws_access_key_id().to_owned(),
+            lifetime_ms: expiration.duration_since(UNIX_EPOCH)?.as_millis() as i64,
+        })
+    }
+}
+
+/// A client context that generates OAUTHBEARER tokens with [`generate_token`].
+///
+/// Clients that do not otherwise need a custom context should use this context
+/// rather than the default, so that they support OAUTHBEARER authentication.
+#[derive(Clone, Debug, Default)]
+pub struct MzClientContext;
+
+impl ClientContext for MzClientContext {
+    const ENABLE_REFRESH_OAUTH_TOKEN: bool = true;
+
+    fn generate_oauth_token(&self, config: Option<&str>) -> Result<OAuthToken, Box<dyn Error>> {
+        generate_token(config)
+    }
+}
+
+impl ConsumerContext for MzClientContext {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_from_config() {
+        for (config, expected) in &[
+            ("", "missing provider"),
+            ("provider", "expected key=value, but got provider"),
+            (
+                "provider=aws_msk_iam provider=aws_msk_iam",
+                "duplicate key provider",
+            ),
+            ("provider=bogus", "unknown provider: bogus"),
+            (
+                "provider=aws_msk_iam",
+                "provider aws_msk_iam requires a region",
+            ),
+            (
+                "provider=aws_msk_iam region=us-east-1 principal=foo",
+                "unexpected key principal for provider aws_msk_iam",
+            ),
+        ] {
+            match provider_from_config(config) {
+                Ok(_) => panic!("config {:?} unexpectedly succeeded", config),
+                Err(e) => assert_eq!(e.to_string(), *expected, "config {:?}", config),
+            }
+        }
+
+        assert!(provider_from_config("provider=aws_msk_iam  region=us-east-1").is_ok());
+    }
+
+    #[test]
+    fn test_msk_iam_sign() -> Result<(), Box<dyn Error>> {
+        let provider = MskIamTokenProvider::new(Region::UsEast1);
+        let credentials = AwsCredentials::new("AKID", "secret", Some("session".into()), None);
+        let token = provider.sign(&credentials);
+        assert!(!token.contains('='));
+        let url = String::from_utf8(base64::decode_config(&token, base64::URL_SAFE_NO_PAD)?)?;
+        assert!(url.starts_with("https://kafka.us-east-1.amazonaws.com/?"));
+        for param in &[
+            "Action=kafka-cluster%3AConnect",
+            "X-Amz-Algorithm=AWS4-HMAC-SHA256",
+            "X-Amz-Expires=900",
+            "X-Amz-Security-Token=session",
+            "X-Amz-Signature=",
+        ] {
+            assert!(url.contains(param), "{} missing from {}", param, url);
+        }
+        Ok(())
+    }
+}
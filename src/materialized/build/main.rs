@@ -14,5 +14,7 @@ mod npm;
 fn main() -> Result<(), anyhow::Error> {
     println!("cargo:rustc-env=TARGET_TRIPLE={}", env::var("TARGET")?);
 
+    tonic_build::compile_protos("src/control/control.proto")?;
+
     npm::ensure()
 }
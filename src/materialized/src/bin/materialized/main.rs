@@ -26,7 +26,7 @@ use std::io;
 use std::net::SocketAddr;
 use std::panic;
 use std::panic::PanicInfo;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -77,6 +77,40 @@ struct Args {
     /// [DANGEROUS] Enable experimental features.
     #[structopt(long)]
     experimental: bool,
+    /// Check whether the catalog in the data directory can be upgraded to this
+    /// version, then exit.
+    ///
+    /// The check applies any pending catalog migrations to a copy of the
+    /// catalog and plans every stored source, view, sink, and index, reporting
+    /// any that fail. The catalog itself is not modified.
+    #[structopt(long)]
+    check_upgrade: bool,
+    /// Check the catalog in the data directory for objects that would prevent
+    /// the server from starting, then exit.
+    ///
+    /// The check reports schemas and items whose database or schema is
+    /// missing, and stored sources, views, sinks, and indexes that fail to
+    /// plan, including those that depend on a missing object. The catalog
+    /// itself is not modified unless --fsck-repair is specified.
+    #[structopt(long)]
+    fsck: bool,
+    /// With --fsck, quarantine the objects that the check reports, so that
+    /// the server can start without them.
+    #[structopt(long, requires = "fsck")]
+    fsck_repair: bool,
+    /// Check the configuration and the init SQL, then exit.
+    ///
+    /// The command-line options are validated as they are at startup. If
+    /// --init-sql is specified, its statements are then planned against a copy
+    /// of the catalog in the data directory, without starting any dataflows.
+    /// Statements that fail are printed to stdout as JSON objects, one per
+    /// line, and the process exits with a nonzero status.
+    #[structopt(long)]
+    check: bool,
+    /// With --check, connect to the external systems, like Kafka brokers and
+    /// schema registries, that the sources in the init SQL use.
+    #[structopt(long, requires = "check")]
+    check_connectors: bool,
 
     // === Timely worker configuration. ===
     /// Number of dataflow worker threads.
@@ -101,6 +135,14 @@ struct Args {
     /// Set to "off" to disable logical compaction.
     #[structopt(long, env = "MZ_LOGICAL_COMPACTION_WINDOW", parse(try_from_str = parse_optional_duration), value_name = "DURATION", default_value = "1ms")]
     logical_compaction_window: OptionalDuration,
+    /// Automatically widen the logical compaction window of indexes that are
+    /// queried at historical timestamps.
+    ///
+    /// The window of each index never shrinks below the value of
+    /// --logical-compaction-window, and reverts to it when the system is low
+    /// on memory.
+    #[structopt(long)]
+    auto_tune_logical_compaction_window: bool,
     /// [DEPRECATED] Frequency with which to advance timestamps.
     #[structopt(long, env = "MZ_TIMESTAMP_FREQUENCY", hidden = true, parse(try_from_str = parse_duration::parse), value_name = "DURATION", default_value = "10ms")]
     timestamp_frequency: Duration,
@@ -113,6 +155,78 @@ struct Args {
         default_value = "1000000"
     )]
     cache_max_pending_records: usize,
+    /// How long the frontier of a dataflow must fail to advance, while its
+    /// inputs advance, before the dataflow is reported as stalled.
+    ///
+    /// Stalled dataflows are reported in the mz_stalled_dataflows table and in
+    /// the log. Set to "off" to disable stall detection.
+    #[structopt(long, env = "MZ_STALLED_DATAFLOW_THRESHOLD", parse(try_from_str = parse_optional_duration), value_name = "DURATION", default_value = "5min")]
+    stalled_dataflow_threshold: OptionalDuration,
+    /// How long a temporary dataflow shared by queries with the same
+    /// expression may go unused before it is torn down.
+    ///
+    /// Set to "off" to build a new temporary dataflow for every query that
+    /// cannot be served from an index.
+    #[structopt(long, env = "MZ_SHARED_DATAFLOW_IDLE_TIMEOUT", parse(try_from_str = parse_optional_duration), value_name = "DURATION", default_value = "off")]
+    shared_dataflow_idle_timeout: OptionalDuration,
+    /// How many times queries with the same expression must be issued, each
+    /// within the shared dataflow idle timeout of the last, before they share
+    /// a temporary dataflow.
+    ///
+    /// Set to 1 to keep the dataflow of every such query warm for later
+    /// queries.
+    #[structopt(
+        long,
+        env = "MZ_SHARED_DATAFLOW_MIN_PEEKS",
+        value_name = "N",
+        default_value = "2"
+    )]
+    shared_dataflow_min_peeks: usize,
+    /// How stale the results of a view may be, relative to the upstream events
+    /// they reflect, before the view is reported as breaching its freshness
+    /// SLO.
+    ///
+    /// Breaches are reported in the mz_view_freshness table and in the
+    /// mz_view_freshness_slo_breached metric. Freshness percentiles are
+    /// reported regardless. Set to "off" to disable SLO reporting.
+    #[structopt(long, env = "MZ_FRESHNESS_SLO", parse(try_from_str = parse_optional_duration), value_name = "DURATION", default_value = "off")]
+    freshness_slo: OptionalDuration,
+    /// How long after startup to report readiness at /api/readyz even if some
+    /// sources have not yet connected.
+    ///
+    /// Set to "off" to wait for all sources indefinitely.
+    #[structopt(long, env = "MZ_REHYDRATION_BUDGET", parse(try_from_str = parse_optional_duration), value_name = "DURATION", default_value = "5min")]
+    rehydration_budget: OptionalDuration,
+    /// How long a materialized view created with the `lazy` option may go
+    /// unqueried before its dataflow is torn down.
+    #[structopt(long, env = "MZ_LAZY_VIEW_IDLE_TIMEOUT", parse(try_from_str = parse_duration::parse), value_name = "DURATION", default_value = "5min")]
+    lazy_view_idle_timeout: Duration,
+    /// Maximum number of indexes and sinks that may exist in any one schema.
+    #[structopt(long, env = "MZ_MAX_DATAFLOWS_PER_SCHEMA", value_name = "N")]
+    max_dataflows_per_schema: Option<usize>,
+    /// Number of bytes that the arrangements of the indexes in any one schema
+    /// may hold before new indexes and sinks in the schema are rejected.
+    #[structopt(long, env = "MZ_MAX_ARRANGEMENT_BYTES_PER_SCHEMA", value_name = "N")]
+    max_arrangement_bytes_per_schema: Option<usize>,
+    /// Maximum number of queries that the sessions of any one role may run
+    /// concurrently.
+    #[structopt(long, env = "MZ_MAX_CONCURRENT_PEEKS_PER_ROLE", value_name = "N")]
+    max_concurrent_peeks_per_role: Option<usize>,
+    /// Maximum number of client connections that may be open at once.
+    #[structopt(long, env = "MZ_MAX_CONNECTIONS", value_name = "N")]
+    max_connections: Option<usize>,
+    /// Maximum number of client connections that any one role may have open
+    /// at once.
+    #[structopt(long, env = "MZ_MAX_CONNECTIONS_PER_ROLE", value_name = "N")]
+    max_connections_per_role: Option<usize>,
+    /// Memory usage, in megabytes, above which queries that require a
+    /// temporary dataflow are rejected.
+    #[structopt(long, env = "MZ_MAX_MEMORY", value_name = "MB")]
+    max_memory: Option<usize>,
+    /// Number of commands waiting for the coordinator above which queries that
+    /// require a temporary dataflow are rejected.
+    #[structopt(long, env = "MZ_MAX_QUEUE_DEPTH", value_name = "N")]
+    max_queue_depth: Option<usize>,
     /// [ADVANCED] Timely progress tracking mode.
     #[structopt(long, env = "MZ_TIMELY_PROGRESS_MODE", value_name = "MODE", possible_values = &["eager", "demand"], default_value = "demand")]
     timely_progress_mode: timely::worker::ProgressMode,
@@ -124,6 +238,55 @@ struct Args {
     /// Where materialized will emit log messages.
     #[structopt(long, env = "MZ_LOG_FILE", value_name = "PATH")]
     log_file: Option<String>,
+    /// How to format log messages.
+    ///
+    /// If set to "text", log messages are formatted for humans. If set to
+    /// "json", each log message is a JSON object on its own line, including
+    /// fields like the connection ID, statement ID, and object ID that the
+    /// message concerns, for consumption by log aggregation systems.
+    ///
+    /// Warnings and errors that are copied to stderr when logging to a file are
+    /// always formatted as text.
+    #[structopt(
+        long,
+        env = "MZ_LOG_FORMAT",
+        possible_values = &["text", "json"],
+        default_value = "text",
+        value_name = "FORMAT"
+    )]
+    log_format: String,
+    /// Read the log filter from the specified file.
+    ///
+    /// The file must contain a filter in the syntax of the MZ_LOG environment
+    /// variable, which it overrides. The file is reread whenever materialized
+    /// receives SIGHUP. If this option is not specified, SIGHUP instead
+    /// restores the filter that was configured at startup.
+    ///
+    /// The log filter can also be changed at runtime via `ALTER SYSTEM SET
+    /// log_filter`.
+    #[structopt(long, env = "MZ_LOG_FILTER_FILE", value_name = "PATH")]
+    log_filter_file: Option<PathBuf>,
+    /// Log a warning for every statement that takes longer than the specified
+    /// duration to execute.
+    ///
+    /// Set to "off" to disable the slow query log.
+    #[structopt(long, env = "MZ_SLOW_QUERY_THRESHOLD", parse(try_from_str = parse_optional_duration), value_name = "DURATION", default_value = "off")]
+    slow_query_threshold: OptionalDuration,
+    /// The maximum size of the statement history, in megabytes.
+    ///
+    /// Every executed statement is recorded in a log in the data directory,
+    /// along with its duration and any error, and reported in the
+    /// mz_statement_history table. The log survives restarts, so that after a
+    /// crash it shows which statements were running. Once the log reaches
+    /// this size, the oldest statements are discarded. Set to 0 to disable the
+    /// statement history.
+    #[structopt(
+        long,
+        env = "MZ_STATEMENT_HISTORY_SIZE",
+        value_name = "MB",
+        default_value = "64"
+    )]
+    statement_history_size: u64,
 
     // == Connection options.
     /// The address on which to listen for connections.
@@ -134,6 +297,20 @@ struct Args {
         default_value = "0.0.0.0:6875"
     )]
     listen_addr: SocketAddr,
+    /// The address on which to serve Arrow Flight requests.
+    ///
+    /// Clients can use Arrow Flight to fetch large query results in columnar
+    /// form. The Arrow Flight endpoint is disabled unless this option is
+    /// specified. It uses the same TLS configuration as the main listener.
+    #[structopt(long, env = "MZ_FLIGHT_LISTEN_ADDR", value_name = "HOST:PORT")]
+    flight_listen_addr: Option<SocketAddr>,
+    /// The address on which to serve the gRPC control API.
+    ///
+    /// The control API lets infrastructure tooling manage materialized with
+    /// typed requests. It is disabled unless this option is specified. Unless
+    /// --tls-mode=verify-full is specified, it must be a loopback address.
+    #[structopt(long, env = "MZ_CONTROL_LISTEN_ADDR", value_name = "HOST:PORT")]
+    control_listen_addr: Option<SocketAddr>,
     /// How stringently to demand TLS authentication and encryption.
     ///
     /// If set to "disable", then materialized rejects HTTP and PostgreSQL
@@ -202,6 +379,14 @@ struct Args {
         default_value = "mzdata"
     )]
     data_directory: PathBuf,
+    /// SQL statements to run at startup.
+    ///
+    /// PATH may be a file of SQL statements or a directory of `.sql` files,
+    /// which run in order of their names. The statements run on every
+    /// startup, after the catalog is loaded and before any connections are
+    /// served. CREATE statements skip objects that already exist.
+    #[structopt(long, env = "MZ_INIT_SQL", value_name = "PATH")]
+    init_sql: Option<PathBuf>,
     /// Enable symbioisis with a PostgreSQL server.
     #[structopt(long, env = "MZ_SYMBIOSIS", hidden = true)]
     symbiosis: Option<String>,
@@ -293,6 +478,14 @@ fn run(args: Args) -> Result<(), anyhow::Error> {
         );
     }
 
+    if args.check_upgrade {
+        return check_upgrade(&args.data_directory, logging.is_some());
+    }
+
+    if args.fsck {
+        return fsck(&args.data_directory, logging.is_some(), args.fsck_repair);
+    }
+
     // Configure connections.
     let tls = if args.tls_mode == "disable" {
         if args.tls_ca.is_some() {
@@ -326,6 +519,21 @@ fn run(args: Args) -> Result<(), anyhow::Error> {
         Some(materialized::TlsConfig { mode, cert, key })
     };
 
+    if args.check {
+        if let Some(path) = &args.log_filter_file {
+            let filter = crate::tracing::read_log_filter_file(path)?;
+            crate::tracing::parse_log_filter(&filter)
+                .with_context(|| format!("parsing log filter file: {}", path.display()))?;
+        }
+        return check(
+            &args.data_directory,
+            args.init_sql.as_deref(),
+            args.experimental,
+            logging.is_some(),
+            args.check_connectors,
+        );
+    }
+
     // Configure storage.
     let data_directory = args.data_directory;
     fs::create_dir_all(&data_directory)
@@ -367,18 +575,24 @@ fn run(args: Args) -> Result<(), anyhow::Error> {
     };
 
     // Configure tracing.
-    {
-        use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+    let log_filter = {
+        use tracing_subscriber::filter::LevelFilter;
         use tracing_subscriber::fmt;
         use tracing_subscriber::layer::SubscriberExt;
         use tracing_subscriber::util::SubscriberInitExt;
 
-        use crate::tracing::FilterLayer;
+        use crate::tracing::{format_layer, FilterLayer, ReloadableLogFilter};
 
-        let env_filter = EnvFilter::try_from_env("MZ_LOG")
-            .or_else(|_| EnvFilter::try_new("info")) // default log level
-            .unwrap()
-            .add_directive("panic=error".parse().unwrap()); // prevent suppressing logs about panics
+        let default_filter = match &args.log_filter_file {
+            Some(path) => crate::tracing::read_log_filter_file(path)?,
+            None => match env::var("MZ_LOG") {
+                Ok(filter) if crate::tracing::parse_log_filter(&filter).is_ok() => filter,
+                _ => "info".into(), // default log level
+            },
+        };
+        let (env_filter, log_filter) = ReloadableLogFilter::new(default_filter)?;
+
+        let json = args.log_format == "json";
 
         match args.log_file.as_deref() {
             Some("stderr") => {
@@ -386,7 +600,7 @@ fn run(args: Args) -> Result<(), anyhow::Error> {
                 // with the user-specified `env_filter`.
                 tracing_subscriber::registry()
                     .with(env_filter)
-                    .with(fmt::layer().with_writer(io::stderr))
+                    .with(format_layer(json, true, io::stderr))
                     .init()
             }
             log_file => {
@@ -396,27 +610,24 @@ fn run(args: Args) -> Result<(), anyhow::Error> {
                     Some(_) => LevelFilter::OFF,
                     None => LevelFilter::WARN,
                 };
+                let path = match log_file {
+                    Some(log_file) => PathBuf::from(log_file),
+                    None => data_directory.join("materialized.log"),
+                };
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("creating log file directory: {}", parent.display())
+                    })?;
+                }
+                let file = fs::OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(&path)
+                    .with_context(|| format!("creating log file: {}", path.display()))?;
+                let writer = move || file.try_clone().expect("failed to clone log file");
                 tracing_subscriber::registry()
                     .with(env_filter)
-                    .with({
-                        let path = match log_file {
-                            Some(log_file) => PathBuf::from(log_file),
-                            None => data_directory.join("materialized.log"),
-                        };
-                        if let Some(parent) = path.parent() {
-                            fs::create_dir_all(parent).with_context(|| {
-                                format!("creating log file directory: {}", parent.display())
-                            })?;
-                        }
-                        let file = fs::OpenOptions::new()
-                            .append(true)
-                            .create(true)
-                            .open(&path)
-                            .with_context(|| format!("creating log file: {}", path.display()))?;
-                        fmt::layer().with_ansi(false).with_writer(move || {
-                            file.try_clone().expect("failed to clone log file")
-                        })
-                    })
+                    .with(format_layer(json, false, writer))
                     .with(FilterLayer::new(
                         fmt::layer().with_writer(io::stderr),
                         stderr_level,
@@ -424,7 +635,9 @@ fn run(args: Args) -> Result<(), anyhow::Error> {
                     .init()
             }
         }
-    }
+
+        Arc::new(log_filter)
+    };
 
     // Configure prometheus process metrics.
     mz_process_collector::register_default_process_collector()?;
@@ -493,17 +706,44 @@ swap: {swap_total}KB total, {swap_used}KB used",
             .build()?,
     );
 
+    runtime.spawn(crate::tracing::reload_log_filter_on_sighup(
+        log_filter.clone(),
+        args.log_filter_file.clone(),
+    ));
+
     let server = runtime.block_on(materialized::serve(
         materialized::Config {
             workers: args.workers.0,
             timely_worker,
             logging,
             logical_compaction_window: args.logical_compaction_window,
+            auto_tune_logical_compaction_window: args.auto_tune_logical_compaction_window,
             timestamp_frequency: args.timestamp_frequency,
+            quotas: coord::QuotaConfig {
+                max_dataflows_per_schema: args.max_dataflows_per_schema,
+                max_arrangement_bytes_per_schema: args.max_arrangement_bytes_per_schema,
+                max_concurrent_peeks_per_role: args.max_concurrent_peeks_per_role,
+                max_connections: args.max_connections,
+                max_connections_per_role: args.max_connections_per_role,
+                max_memory: args.max_memory.map(|mb| mb << 20),
+                max_queue_depth: args.max_queue_depth,
+            },
+            slow_query_threshold: args.slow_query_threshold,
+            statement_history_size: args.statement_history_size << 20,
+            stalled_dataflow_threshold: args.stalled_dataflow_threshold,
+            shared_dataflow_idle_timeout: args.shared_dataflow_idle_timeout,
+            shared_dataflow_min_peeks: args.shared_dataflow_min_peeks,
+            freshness_slo: args.freshness_slo,
+            rehydration_budget: args.rehydration_budget,
+            lazy_view_idle_timeout: args.lazy_view_idle_timeout,
+            log_filter: Some(log_filter),
             cache,
             listen_addr: args.listen_addr,
             tls,
+            flight_listen_addr: args.flight_listen_addr,
+            control_listen_addr: args.control_listen_addr,
             data_directory,
+            init_sql: args.init_sql,
             symbiosis_url: args.symbiosis,
             experimental_mode: args.experimental,
             telemetry_url,
@@ -578,10 +818,116 @@ For more details, see https://materialize.com/docs/cli#experimental-mode
     }
 }
 
+fn check(
+    data_directory: &Path,
+    init_sql: Option<&Path>,
+    experimental_mode: bool,
+    enable_logging: bool,
+    check_connectors: bool,
+) -> Result<(), anyhow::Error> {
+    let init_sql = match init_sql {
+        None => {
+            println!("configuration is valid");
+            return Ok(());
+        }
+        Some(init_sql) => init_sql,
+    };
+    let path = data_directory.join("catalog");
+    let runtime = tokio::runtime::Runtime::new()?;
+    let failures = runtime.block_on(materialized::init_sql::check(
+        &coord::catalog::Config {
+            path: &path,
+            experimental_mode: Some(experimental_mode),
+            enable_logging,
+            cache_directory: None,
+            build_info: &materialized::BUILD_INFO,
+        },
+        init_sql,
+        check_connectors,
+    ))?;
+    if !failures.is_empty() {
+        for failure in &failures {
+            println!("{}", serde_json::to_string(failure)?);
+        }
+        bail!("{} init SQL statements failed to check", failures.len());
+    }
+    println!("configuration and init SQL are valid");
+    Ok(())
+}
+
 lazy_static! {
     static ref PANIC_MUTEX: Mutex<()> = Mutex::new(());
 }
 
+fn check_upgrade(data_directory: &Path, enable_logging: bool) -> Result<(), anyhow::Error> {
+    let path = data_directory.join("catalog");
+    if !path.exists() {
+        bail!("no catalog found at {}", path.display());
+    }
+    let failures = coord::catalog::Catalog::check_upgrade(&coord::catalog::Config {
+        path: &path,
+        experimental_mode: None,
+        enable_logging,
+        cache_directory: None,
+        build_info: &materialized::BUILD_INFO,
+    })
+    .with_context(|| format!("checking catalog at {}", path.display()))?;
+    if !failures.is_empty() {
+        for failure in &failures {
+            eprintln!("materialized: {}", failure);
+        }
+        bail!(
+            "catalog at {} cannot be upgraded to materialized {}: {} items failed to plan",
+            path.display(),
+            materialized::BUILD_INFO.human_version(),
+            failures.len(),
+        );
+    }
+    println!(
+        "catalog at {} can be upgraded to materialized {}",
+        path.display(),
+        materialized::BUILD_INFO.human_version(),
+    );
+    Ok(())
+}
+
+fn fsck(data_directory: &Path, enable_logging: bool, repair: bool) -> Result<(), anyhow::Error> {
+    let path = data_directory.join("catalog");
+    if !path.exists() {
+        bail!("no catalog found at {}", path.display());
+    }
+    let issues = coord::catalog::Catalog::fsck(
+        &coord::catalog::Config {
+            path: &path,
+            experimental_mode: None,
+            enable_logging,
+            cache_directory: None,
+            build_info: &materialized::BUILD_INFO,
+        },
+        repair,
+    )
+    .with_context(|| format!("checking catalog at {}", path.display()))?;
+    for issue in &issues {
+        eprintln!("materialized: {}", issue);
+    }
+    if issues.is_empty() {
+        println!("catalog at {} is consistent", path.display());
+    } else if repair {
+        println!(
+            "repaired catalog at {}: quarantined the objects affected by {} issues",
+            path.display(),
+            issues.len(),
+        );
+    } else {
+        bail!(
+            "catalog at {} has {} issues; rerun with --fsck-repair to quarantine the affected objects",
+            path.display(),
+            issues.len(),
+        );
+    }
+    Ok(())
+}
+
 fn handle_panic(panic_info: &PanicInfo) {
     let _guard = PANIC_MUTEX.lock();
 
@@ -7,14 +7,22 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
+use std::fs;
 use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use anyhow::Context as _;
+use log::{error, info};
+use tokio::signal::unix::{signal, SignalKind};
 use tracing::span::{Attributes, Record};
 use tracing::subscriber::Interest;
 use tracing::{Event, Id, Metadata, Subscriber};
-use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::fmt::{self, MakeWriter};
 use tracing_subscriber::layer::{Context, Layer};
 use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{reload, Registry};
 
 /// A tracing [`Layer`] that applies a [`LevelFilter`] to one layer only.
 ///
@@ -117,3 +125,213 @@ where
         }
     }
 }
+
+/// A log filter that can be changed while the process is running.
+pub struct ReloadableLogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    /// The filter that was configured at startup.
+    default: String,
+}
+
+impl ReloadableLogFilter {
+    /// Constructs a filter layer that initially applies `default`, along with
+    /// a `ReloadableLogFilter` that controls it.
+    pub fn new(
+        default: String,
+    ) -> Result<(reload::Layer<EnvFilter, Registry>, ReloadableLogFilter), anyhow::Error> {
+        let (layer, handle) = reload::Layer::new(parse_log_filter(&default)?);
+        Ok((layer, ReloadableLogFilter { handle, default }))
+    }
+}
+
+impl coord::LogFilterControl for ReloadableLogFilter {
+    fn set(&self, filter: &str) -> Result<(), anyhow::Error> {
+        self.handle.reload(parse_log_filter(filter)?)?;
+        // Messages from the `log` crate are discarded before they reach
+        // tracing if they are more verbose than `log`'s global maximum level,
+        // which was derived from the filter at startup, so that level must
+        // track the new filter.
+        let level = LevelFilter::current();
+        log::set_max_level(if level == LevelFilter::OFF {
+            log::LevelFilter::Off
+        } else if level == LevelFilter::ERROR {
+            log::LevelFilter::Error
+        } else if level == LevelFilter::WARN {
+            log::LevelFilter::Warn
+        } else if level == LevelFilter::INFO {
+            log::LevelFilter::Info
+        } else if level == LevelFilter::DEBUG {
+            log::LevelFilter::Debug
+        } else {
+            log::LevelFilter::Trace
+        });
+        info!("log filter set to {:?}", filter);
+        Ok(())
+    }
+
+    fn reset(&self) -> Result<(), anyhow::Error> {
+        self.set(&self.default)
+    }
+}
+
+/// Constructs a layer that writes each log message to a writer produced by
+/// `make_writer`.
+///
+/// If `json` is set, each message is formatted as a JSON object on its own
+/// line, which includes the fields of the spans in which the message was
+/// emitted. Otherwise messages are formatted as text, with ANSI colors if
+/// `ansi` is set.
+pub fn format_layer<S, W>(json: bool, ansi: bool, make_writer: W) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: MakeWriter + Send + Sync + 'static,
+{
+    // Exactly one of the two layers is present.
+    let (text_layer, json_layer) = if json {
+        (None, Some(fmt::layer().json().with_writer(make_writer)))
+    } else {
+        (
+            Some(fmt::layer().with_ansi(ansi).with_writer(make_writer)),
+            None,
+        )
+    };
+    text_layer.and_then(json_layer)
+}
+
+/// Parses a log filter in the syntax of the `MZ_LOG` environment variable.
+pub fn parse_log_filter(filter: &str) -> Result<EnvFilter, anyhow::Error> {
+    Ok(EnvFilter::try_new(filter)?
+        // Prevent suppressing logs about panics.
+        .add_directive("panic=error".parse().unwrap()))
+}
+
+/// Reads a log filter from the file at `path`.
+pub fn read_log_filter_file(path: &Path) -> Result<String, anyhow::Error> {
+    let filter = fs::read_to_string(path)
+        .with_context(|| format!("reading log filter file: {}", path.display()))?;
+    Ok(filter.trim().to_string())
+}
+
+/// Reloads `log_filter` whenever the process receives SIGHUP.
+///
+/// If `path` is specified, the new filter is read from the file at that
+/// path. Otherwise the filter that was configured at startup is restored.
+pub async fn reload_log_filter_on_sighup(
+    log_filter: Arc<ReloadableLogFilter>,
+    path: Option<PathBuf>,
+) {
+    use coord::LogFilterControl;
+
+    let mut sighup = match signal(SignalKind::hangup()) {
+        Ok(sighup) => sighup,
+        Err(e) => {
+            error!("unable to install SIGHUP handler: {}", e);
+            return;
+        }
+    };
+    while sighup.recv().await.is_some() {
+        let res = match &path {
+            Some(path) => read_log_filter_file(path).and_then(|filter| log_filter.set(&filter)),
+            None => log_filter.reset(),
+        };
+        if let Err(e) = res {
+            error!("unable to reload log filter: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::Mutex;
+
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    /// A writer that captures everything written to it.
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_format() {
+        let buffer = Buffer::default();
+        let make_writer = {
+            let buffer = buffer.clone();
+            move || buffer.clone()
+        };
+        let subscriber =
+            tracing_subscriber::registry().with(format_layer(true, false, make_writer));
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("statement", conn_id = 1, statement_id = 7);
+            let _guard = span.enter();
+            tracing::info!("executing");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 1, "expected one message, got: {}", output);
+        let message: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert!(message["timestamp"].is_string());
+        assert_eq!(message["level"], "INFO");
+        assert_eq!(message["target"], module_path!());
+        assert_eq!(message["fields"]["message"], "executing");
+        // The fields of the enclosing spans identify what the message
+        // concerns.
+        assert_eq!(message["span"]["name"], "statement");
+        assert_eq!(message["span"]["conn_id"], 1);
+        assert_eq!(message["span"]["statement_id"], 7);
+        assert_eq!(message["spans"][0]["name"], "statement");
+        assert_eq!(message["spans"][0]["conn_id"], 1);
+    }
+
+    #[test]
+    fn test_reload_log_filter() {
+        use coord::LogFilterControl;
+
+        let buffer = Buffer::default();
+        let make_writer = {
+            let buffer = buffer.clone();
+            move || buffer.clone()
+        };
+        let (filter_layer, log_filter) = ReloadableLogFilter::new("info".into()).unwrap();
+        let subscriber = tracing_subscriber::registry()
+            .with(filter_layer)
+            .with(format_layer(false, false, make_writer));
+        let take_output = || String::from_utf8(buffer.0.lock().unwrap().split_off(0)).unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("hidden");
+            tracing::info!("shown");
+            let output = take_output();
+            assert!(!output.contains("hidden"), "{}", output);
+            assert!(output.contains("shown"), "{}", output);
+
+            log_filter
+                .set(&format!("info,{}=debug", module_path!()))
+                .unwrap();
+            tracing::debug!("now shown");
+            assert!(take_output().contains("now shown"));
+
+            // An invalid filter leaves the previous filter in place.
+            assert!(log_filter.set("info,foo=bogus").is_err());
+            tracing::debug!("still shown");
+            assert!(take_output().contains("still shown"));
+
+            log_filter.reset().unwrap();
+            tracing::debug!("hidden again");
+            assert!(!take_output().contains("hidden again"));
+        });
+    }
+}
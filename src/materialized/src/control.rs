@@ -0,0 +1,368 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! gRPC control-plane API.
+//!
+//! The control API lets infrastructure tooling, like a Kubernetes operator,
+//! manage a `materialized` process with typed requests and responses, rather
+//! than by parsing the text output of `SHOW` commands. The service is defined
+//! in `control/control.proto`.
+//!
+//! Requests are authenticated like pgwire connections, as described in the
+//! [`grpc`](crate::grpc) module, and run as the user they name. Because user
+//! names are only verified with `--tls-mode=verify-full`, the server refuses
+//! to start the control API on a non-loopback address in any other mode.
+
+use std::time::Instant;
+
+use futures::stream::StreamExt;
+use tonic::{Request, Response, Status};
+
+use coord::session::Session;
+use coord::ExecuteResponse;
+use dataflow_types::PeekResponse;
+use ore::collections::CollectionExt;
+use repr::{Datum, Row};
+use sql_parser::ast::{
+    DropObjectsStatement, Ident, ObjectType as SqlObjectType, Raw, Statement, UnresolvedObjectName,
+};
+use sql_parser::parser::parse_statements;
+
+use crate::grpc::{self, TlsMode};
+use crate::BUILD_INFO;
+
+/// Types generated from `control/control.proto`, including a client for the
+/// control API.
+pub mod proto {
+    tonic::include_proto!("materialize.control.v1");
+}
+
+use proto::control_server::{Control, ControlServer};
+use proto::ObjectType;
+
+/// Configures a [`Server`].
+pub struct Config {
+    pub coord_client: coord::Client,
+    pub start_time: Instant,
+    /// The TLS mode of the server's connections, if they use TLS.
+    pub tls_mode: Option<TlsMode>,
+}
+
+/// A gRPC control-plane server.
+pub struct Server {
+    coord_client: coord::Client,
+    start_time: Instant,
+    tls_mode: Option<TlsMode>,
+}
+
+impl Server {
+    pub fn new(config: Config) -> Server {
+        Server {
+            coord_client: config.coord_client,
+            start_time: config.start_time,
+            tls_mode: config.tls_mode,
+        }
+    }
+
+    /// Wraps the server in a gRPC service.
+    pub fn into_service(self) -> ControlServer<Server> {
+        ControlServer::new(self)
+    }
+
+    /// Executes `stmt` in a new session on behalf of `user`.
+    ///
+    /// Statements that return rows are rejected, except when `allow_rows` is
+    /// set, in which case the rows are collected and returned.
+    async fn execute_statement(
+        &self,
+        user: String,
+        stmt: Statement<Raw>,
+        allow_rows: bool,
+    ) -> Result<(ExecuteResponse, Vec<Row>), Status> {
+        let coord_client = self.coord_client.new_conn().map_err(internal_error)?;
+        let session = Session::new(coord_client.conn_id(), user);
+        let (mut coord_client, _) = coord_client
+            .startup(session)
+            .await
+            .map_err(internal_error)?;
+        let res = async {
+            const EMPTY_PORTAL: &str = "";
+            coord_client
+                .declare(EMPTY_PORTAL.into(), stmt, vec![], None)
+                .await
+                .map_err(invalid_argument)?;
+            let returns_rows = coord_client
+                .session()
+                .get_portal(EMPTY_PORTAL)
+                .map_or(false, |portal| portal.desc.relation_desc.is_some());
+            if returns_rows && !allow_rows {
+                return Err(Status::invalid_argument(
+                    "statements that return rows are not supported",
+                ));
+            }
+            let res = coord_client
+                .execute(EMPTY_PORTAL.into())
+                .await
+                .map_err(invalid_argument)?;
+            match res {
+                ExecuteResponse::SendingRows(mut batches) => {
+                    let mut rows = vec![];
+                    while let Some(batch) = batches.next().await {
+                        match batch {
+                            PeekResponse::Rows(batch) => rows.extend(batch),
+                            PeekResponse::Error(e) => return Err(Status::internal(e)),
+                            PeekResponse::Canceled => {
+                                return Err(Status::cancelled("query canceled"))
+                            }
+                        }
+                    }
+                    Ok((ExecuteResponse::EmptyQuery, rows))
+                }
+                ExecuteResponse::Cancelled => Err(Status::cancelled("statement canceled")),
+                res => Ok((res, vec![])),
+            }
+        }
+        .await;
+        coord_client.terminate().await;
+        res
+    }
+
+    /// Runs the query `sql` on behalf of `user` and returns its rows.
+    async fn query(&self, user: String, sql: &str) -> Result<Vec<Row>, Status> {
+        let stmt = parse_statements(sql)
+            .map_err(internal_error)?
+            .into_element();
+        let (_, rows) = self.execute_statement(user, stmt, true).await?;
+        Ok(rows)
+    }
+}
+
+#[tonic::async_trait]
+impl Control for Server {
+    async fn execute(
+        &self,
+        request: Request<proto::ExecuteRequest>,
+    ) -> Result<Response<proto::ExecuteResponse>, Status> {
+        let user = grpc::authenticate(self.tls_mode, &request)?;
+        let sql = request.into_inner().sql;
+        let stmts = parse_statements(&sql).map_err(invalid_argument)?;
+        if stmts.len() != 1 {
+            return Err(Status::invalid_argument("expected exactly 1 statement"));
+        }
+        let (res, _) = self
+            .execute_statement(user, stmts.into_element(), false)
+            .await?;
+        Ok(Response::new(describe_response(res)))
+    }
+
+    async fn list_objects(
+        &self,
+        request: Request<proto::ListObjectsRequest>,
+    ) -> Result<Response<proto::ListObjectsResponse>, Status> {
+        let user = grpc::authenticate(self.tls_mode, &request)?;
+        let request = request.into_inner();
+        let rows = self
+            .query(
+                user,
+                "SELECT o.id, d.name, s.name, o.name, o.type
+                FROM mz_catalog.mz_objects o
+                JOIN mz_catalog.mz_schemas s ON o.schema_id = s.id
+                LEFT JOIN mz_catalog.mz_databases d ON s.database_id = d.id
+                ORDER BY o.id",
+            )
+            .await?;
+        let mut objects = vec![];
+        for row in rows {
+            let datums = row.unpack();
+            let string = |datum: Datum| match datum {
+                Datum::Null => String::new(),
+                datum => datum.unwrap_str().to_owned(),
+            };
+            let typ = match datums[4].unwrap_str() {
+                "table" => ObjectType::Table,
+                "source" => ObjectType::Source,
+                "view" => ObjectType::View,
+                "sink" => ObjectType::Sink,
+                "index" => ObjectType::Index,
+                _ => ObjectType::Unspecified,
+            };
+            let object = proto::Object {
+                id: string(datums[0]),
+                name: Some(proto::ObjectName {
+                    database: string(datums[1]),
+                    schema: string(datums[2]),
+                    item: string(datums[3]),
+                }),
+                r#type: typ as i32,
+            };
+            let name = object.name.as_ref().unwrap();
+            if (request.r#type == ObjectType::Unspecified as i32 || request.r#type == object.r#type)
+                && (request.database.is_empty() || request.database == name.database)
+                && (request.schema.is_empty() || request.schema == name.schema)
+            {
+                objects.push(object);
+            }
+        }
+        Ok(Response::new(proto::ListObjectsResponse { objects }))
+    }
+
+    async fn drop_object(
+        &self,
+        request: Request<proto::DropObjectRequest>,
+    ) -> Result<Response<proto::DropObjectResponse>, Status> {
+        let user = grpc::authenticate(self.tls_mode, &request)?;
+        let request = request.into_inner();
+        let object_type = match ObjectType::from_i32(request.r#type) {
+            Some(ObjectType::Table) => SqlObjectType::Table,
+            Some(ObjectType::Source) => SqlObjectType::Source,
+            Some(ObjectType::View) => SqlObjectType::View,
+            Some(ObjectType::Sink) => SqlObjectType::Sink,
+            Some(ObjectType::Index) => SqlObjectType::Index,
+            Some(ObjectType::Unspecified) | None => {
+                return Err(Status::invalid_argument("object type must be specified"))
+            }
+        };
+        let name = match request.name {
+            Some(name) if !name.item.is_empty() => name,
+            _ => return Err(Status::invalid_argument("object name must be specified")),
+        };
+        let name = vec![name.database, name.schema, name.item]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .map(Ident::new)
+            .collect();
+        let stmt = Statement::DropObjects(DropObjectsStatement {
+            object_type,
+            if_exists: request.if_exists,
+            names: vec![UnresolvedObjectName(name)],
+            cascade: request.cascade,
+        });
+        self.execute_statement(user, stmt, false).await?;
+        Ok(Response::new(proto::DropObjectResponse {}))
+    }
+
+    async fn get_health(
+        &self,
+        request: Request<proto::GetHealthRequest>,
+    ) -> Result<Response<proto::GetHealthResponse>, Status> {
+        let user = grpc::authenticate(self.tls_mode, &request)?;
+        let rows = self
+            .query(user, "SELECT count(*) FROM mz_catalog.mz_stalled_dataflows")
+            .await?;
+        let stalled_dataflows = rows.into_element().unpack_first().unwrap_int64();
+        Ok(Response::new(proto::GetHealthResponse {
+            version: BUILD_INFO.version.into(),
+            build_sha: BUILD_INFO.sha.into(),
+            uptime_seconds: self.start_time.elapsed().as_secs(),
+            stalled_dataflows: stalled_dataflows as u64,
+        }))
+    }
+}
+
+/// Describes the outcome of a statement that does not return rows.
+fn describe_response(res: ExecuteResponse) -> proto::ExecuteResponse {
+    let mut rows_affected = 0;
+    let mut already_existed = false;
+    let command = match res {
+        ExecuteResponse::AlteredIndexLogicalCompaction => "ALTER INDEX".into(),
+        ExecuteResponse::AlteredObject(o) => format!("ALTER {}", o),
+        ExecuteResponse::AlteredSystemConfiguration => "ALTER SYSTEM".into(),
+        ExecuteResponse::ClosedCursor => "CLOSE CURSOR".into(),
+        ExecuteResponse::CreatedDatabase { existed } => {
+            already_existed = existed;
+            "CREATE DATABASE".into()
+        }
+        ExecuteResponse::CreatedSchema { existed } => {
+            already_existed = existed;
+            "CREATE SCHEMA".into()
+        }
+        ExecuteResponse::CreatedRole => "CREATE ROLE".into(),
+        ExecuteResponse::CreatedTable { existed } => {
+            already_existed = existed;
+            "CREATE TABLE".into()
+        }
+        ExecuteResponse::CreatedIndex { existed } => {
+            already_existed = existed;
+            "CREATE INDEX".into()
+        }
+        ExecuteResponse::CreatedSource { existed } => {
+            already_existed = existed;
+            "CREATE SOURCE".into()
+        }
+        ExecuteResponse::CreatedSink { existed } => {
+            already_existed = existed;
+            "CREATE SINK".into()
+        }
+        ExecuteResponse::CreatedView { existed } => {
+            already_existed = existed;
+            "CREATE VIEW".into()
+        }
+        ExecuteResponse::CreatedTask { existed } => {
+            already_existed = existed;
+            "CREATE TASK".into()
+        }
+        ExecuteResponse::CreatedType => "CREATE TYPE".into(),
+        ExecuteResponse::DeclaredCursor => "DECLARE CURSOR".into(),
+        ExecuteResponse::Deleted(n) => {
+            rows_affected = n as u64;
+            "DELETE".into()
+        }
+        ExecuteResponse::DiscardedTemp => "DISCARD TEMP".into(),
+        ExecuteResponse::DiscardedAll => "DISCARD ALL".into(),
+        ExecuteResponse::DroppedDatabase => "DROP DATABASE".into(),
+        ExecuteResponse::DroppedSchema => "DROP SCHEMA".into(),
+        ExecuteResponse::DroppedRole => "DROP ROLE".into(),
+        ExecuteResponse::DroppedSource => "DROP SOURCE".into(),
+        ExecuteResponse::DroppedIndex => "DROP INDEX".into(),
+        ExecuteResponse::DroppedSink => "DROP SINK".into(),
+        ExecuteResponse::DroppedTable => "DROP TABLE".into(),
+        ExecuteResponse::DroppedView => "DROP VIEW".into(),
+        ExecuteResponse::DroppedType => "DROP TYPE".into(),
+        ExecuteResponse::DroppedTask => "DROP TASK".into(),
+        ExecuteResponse::Inserted(n) => {
+            rows_affected = n as u64;
+            "INSERT".into()
+        }
+        ExecuteResponse::SetVariable { .. } => "SET".into(),
+        ExecuteResponse::ResetVariable { .. } => "RESET".into(),
+        ExecuteResponse::StartedTransaction => "BEGIN".into(),
+        ExecuteResponse::TransactionExited { tag, .. } => tag.into(),
+        ExecuteResponse::Updated(n) => {
+            rows_affected = n as u64;
+            "UPDATE".into()
+        }
+        // Statements that return rows are rejected before execution, and
+        // cancellations are reported as errors.
+        ExecuteResponse::Cancelled
+        | ExecuteResponse::CopyTo { .. }
+        | ExecuteResponse::EmptyQuery
+        | ExecuteResponse::Fetch { .. }
+        | ExecuteResponse::SendingRows(_)
+        | ExecuteResponse::Tailing { .. } => String::new(),
+    };
+    proto::ExecuteResponse {
+        command,
+        rows_affected,
+        already_existed,
+    }
+}
+
+fn internal_error<E>(e: E) -> Status
+where
+    E: ToString,
+{
+    Status::internal(e.to_string())
+}
+
+fn invalid_argument<E>(e: E) -> Status
+where
+    E: ToString,
+{
+    Status::invalid_argument(e.to_string())
+}
@@ -0,0 +1,564 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Arrow Flight server.
+//!
+//! [Arrow Flight] is a gRPC protocol for transferring Arrow record batches.
+//! Clients that consume results columnarly, like pandas and DataFusion, can
+//! fetch large query results over Flight far faster than over pgwire, as
+//! results need not be rendered as text and parsed again.
+//!
+//! The server supports the query execution subset of [Flight SQL]: a client
+//! calls `GetFlightInfo` with a `CommandStatementQuery` to learn the schema of
+//! a query's results, then calls `DoGet` with the returned ticket to execute
+//! the query. Tickets are stateless, as they contain the text of the query
+//! itself. Clients that do not speak Flight SQL can instead pass the text of
+//! a query as a descriptor command or as a ticket directly.
+//!
+//! Requests are authenticated like pgwire connections, as described in the
+//! [`grpc`](crate::grpc) module, and run as the user they name. The endpoint
+//! is read only: only `SELECT` statements are executed.
+//!
+//! [Arrow Flight]: https://arrow.apache.org/docs/format/Flight.html
+//! [Flight SQL]: https://arrow.apache.org/docs/format/FlightSql.html
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BinaryArray, BooleanArray, Date32Array, Float32Array, Float64Array, Int32Array,
+    Int64Array, StringArray, TimestampMicrosecondArray,
+};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use arrow::ipc::writer::IpcWriteOptions;
+use arrow::record_batch::RecordBatch;
+use arrow_flight::flight_service_server::{FlightService, FlightServiceServer};
+use arrow_flight::utils::{
+    flight_data_from_arrow_batch, flight_data_from_arrow_schema, flight_schema_from_arrow_schema,
+};
+use arrow_flight::{
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PutResult, SchemaResult, Ticket,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use futures::stream::{Stream, StreamExt};
+use prost::Message;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+
+use coord::session::{query_tag, Session};
+use coord::ExecuteResponse;
+use dataflow_types::PeekResponse;
+use ore::collections::CollectionExt;
+use repr::{Datum, RelationDesc, Row, ScalarType};
+use sql_parser::ast::{Raw, Statement};
+use sql_parser::parser::parse_statements;
+
+use crate::grpc::{self, TlsMode};
+
+/// The maximum number of rows in each record batch sent to clients.
+const MAX_BATCH_ROWS: usize = 8192;
+
+/// The number of record batches to buffer for each `DoGet` call before
+/// applying backpressure.
+const BATCH_BUFFER: usize = 4;
+
+const COMMAND_STATEMENT_QUERY_TYPE_URL: &str =
+    "type.googleapis.com/arrow.flight.protocol.sql.CommandStatementQuery";
+const TICKET_STATEMENT_QUERY_TYPE_URL: &str =
+    "type.googleapis.com/arrow.flight.protocol.sql.TicketStatementQuery";
+
+/// A Flight SQL request to execute a query.
+#[derive(Clone, PartialEq, prost::Message)]
+struct CommandStatementQuery {
+    #[prost(string, tag = "1")]
+    query: String,
+}
+
+/// A Flight SQL ticket for fetching the results of a query.
+#[derive(Clone, PartialEq, prost::Message)]
+struct TicketStatementQuery {
+    #[prost(bytes, tag = "1")]
+    statement_handle: Vec<u8>,
+}
+
+type FlightStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + Sync + 'static>>;
+
+/// Configures a [`Server`].
+pub struct Config {
+    pub coord_client: coord::Client,
+    /// The TLS mode of the server's connections, if they use TLS.
+    pub tls_mode: Option<TlsMode>,
+}
+
+/// An Arrow Flight server.
+pub struct Server {
+    coord_client: coord::Client,
+    tls_mode: Option<TlsMode>,
+}
+
+impl Server {
+    pub fn new(config: Config) -> Server {
+        Server {
+            coord_client: config.coord_client,
+            tls_mode: config.tls_mode,
+        }
+    }
+
+    /// Wraps the server in a gRPC service.
+    pub fn into_service(self) -> FlightServiceServer<Server> {
+        FlightServiceServer::new(self)
+    }
+
+    /// Starts a coordinator session for a single Flight request on behalf of
+    /// `user`.
+    async fn start_session(&self, user: String) -> Result<coord::SessionClient, Status> {
+        let coord_client = self.coord_client.new_conn().map_err(internal_error)?;
+        let session = Session::new(coord_client.conn_id(), user);
+        let (coord_client, _) = coord_client
+            .startup(session)
+            .await
+            .map_err(internal_error)?;
+        Ok(coord_client)
+    }
+
+    /// Computes the schema of the results of `sql` without executing it.
+    async fn describe(&self, user: String, sql: &str) -> Result<SchemaRef, Status> {
+        let mut coord_client = self.start_session(user).await?;
+        let res = async {
+            let stmt = parse_query(sql)?;
+            const EMPTY_STATEMENT: &str = "";
+            coord_client
+                .describe(EMPTY_STATEMENT.into(), Some(stmt), vec![], None)
+                .await
+                .map_err(invalid_argument)?;
+            let desc = coord_client
+                .session()
+                .get_prepared_statement(EMPTY_STATEMENT)
+                .expect("unnamed prepared statement should be present")
+                .desc();
+            if !desc.param_types.is_empty() {
+                return Err(Status::invalid_argument(
+                    "query parameters are not supported",
+                ));
+            }
+            match &desc.relation_desc {
+                Some(desc) => Ok(arrow_schema(desc)),
+                None => Err(Status::invalid_argument("query does not return rows")),
+            }
+        }
+        .await;
+        coord_client.terminate().await;
+        res
+    }
+}
+
+#[tonic::async_trait]
+impl FlightService for Server {
+    type HandshakeStream = FlightStream<HandshakeResponse>;
+    type ListFlightsStream = FlightStream<FlightInfo>;
+    type DoGetStream = FlightStream<FlightData>;
+    type DoPutStream = FlightStream<PutResult>;
+    type DoActionStream = FlightStream<arrow_flight::Result>;
+    type ListActionsStream = FlightStream<ActionType>;
+    type DoExchangeStream = FlightStream<FlightData>;
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let user = grpc::authenticate(self.tls_mode, &request)?;
+        let descriptor = request.into_inner();
+        let sql = decode_query(&descriptor.cmd)?;
+        let schema = self.describe(user, &sql).await?;
+        let options = IpcWriteOptions::default();
+        let ticket = match query_type_url(&descriptor.cmd) {
+            Some(COMMAND_STATEMENT_QUERY_TYPE_URL) => encode_any(
+                TICKET_STATEMENT_QUERY_TYPE_URL,
+                &TicketStatementQuery {
+                    statement_handle: sql.into_bytes(),
+                },
+            ),
+            _ => sql.into_bytes(),
+        };
+        Ok(Response::new(FlightInfo {
+            schema: flight_schema_from_arrow_schema(&schema, &options).schema,
+            flight_descriptor: Some(descriptor),
+            // An endpoint without locations indicates that the ticket can be
+            // redeemed on this server.
+            endpoint: vec![FlightEndpoint {
+                ticket: Some(Ticket { ticket }),
+                location: vec![],
+            }],
+            total_records: -1,
+            total_bytes: -1,
+        }))
+    }
+
+    async fn get_schema(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        let user = grpc::authenticate(self.tls_mode, &request)?;
+        let sql = decode_query(&request.into_inner().cmd)?;
+        let schema = self.describe(user, &sql).await?;
+        let options = IpcWriteOptions::default();
+        Ok(Response::new(flight_schema_from_arrow_schema(
+            &schema, &options,
+        )))
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let user = grpc::authenticate(self.tls_mode, &request)?;
+        let sql = decode_query(&request.into_inner().ticket)?;
+        let stmt = parse_query(&sql)?;
+        let tag = query_tag::extract(&sql);
+
+        let mut coord_client = self.start_session(user).await?;
+        let res = async {
+            coord_client.session().start_transaction();
+            const EMPTY_PORTAL: &str = "";
+            coord_client
+                .declare(EMPTY_PORTAL.into(), stmt, vec![], tag)
+                .await
+                .map_err(invalid_argument)?;
+            let desc = match coord_client.session().get_portal(EMPTY_PORTAL) {
+                Some(portal) if !portal.desc.param_types.is_empty() => {
+                    return Err(Status::invalid_argument(
+                        "query parameters are not supported",
+                    ))
+                }
+                Some(portal) => portal.desc.relation_desc.clone(),
+                None => None,
+            };
+            let desc = match desc {
+                Some(desc) => desc,
+                None => return Err(Status::invalid_argument("query does not return rows")),
+            };
+            match coord_client.execute(EMPTY_PORTAL.into()).await {
+                Ok(ExecuteResponse::SendingRows(batches)) => Ok((desc, batches)),
+                Ok(_) => Err(Status::invalid_argument("query does not return rows")),
+                Err(e) => Err(invalid_argument(e)),
+            }
+        }
+        .await;
+        let (desc, mut batches) = match res {
+            Ok(res) => res,
+            Err(e) => {
+                coord_client.terminate().await;
+                return Err(e);
+            }
+        };
+
+        // Results are converted to record batches as they arrive from the
+        // coordinator. The conversion runs in its own task, which owns the
+        // session, so that the response stream can be consumed from any
+        // thread. Dropping the response stream cancels the query.
+        let (tx, rx) = mpsc::channel(BATCH_BUFFER);
+        tokio::spawn(async move {
+            let schema = arrow_schema(&desc);
+            let column_types: Vec<_> = desc
+                .typ()
+                .column_types
+                .iter()
+                .map(|typ| typ.scalar_type.clone())
+                .collect();
+            let options = IpcWriteOptions::default();
+            if tx
+                .send(Ok(flight_data_from_arrow_schema(&schema, &options)))
+                .await
+                .is_ok()
+            {
+                'batches: while let Some(batch) = batches.next().await {
+                    let rows = match batch {
+                        PeekResponse::Rows(rows) => rows,
+                        PeekResponse::Error(e) => {
+                            let _ = tx.send(Err(Status::internal(e))).await;
+                            break;
+                        }
+                        PeekResponse::Canceled => {
+                            let _ = tx.send(Err(Status::cancelled("query canceled"))).await;
+                            break;
+                        }
+                    };
+                    for rows in rows.chunks(MAX_BATCH_ROWS) {
+                        let data = record_batch(&schema, &column_types, rows).map(|batch| {
+                            let (dictionaries, data) =
+                                flight_data_from_arrow_batch(&batch, &options);
+                            dictionaries.into_iter().chain(Some(data))
+                        });
+                        let data: Vec<_> = match data {
+                            Ok(data) => data.map(Ok).collect(),
+                            Err(e) => vec![Err(Status::internal(e.to_string()))],
+                        };
+                        for data in data {
+                            if tx.send(data).await.is_err() {
+                                break 'batches;
+                            }
+                        }
+                    }
+                }
+            }
+            coord_client.terminate().await;
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    /// Checks that the client's credentials are valid.
+    ///
+    /// Every request is authenticated on its own, so clients need not
+    /// handshake, and the handshake issues no token.
+    async fn handshake(
+        &self,
+        request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        grpc::authenticate(self.tls_mode, &request)?;
+        let response = HandshakeResponse {
+            protocol_version: 0,
+            payload: vec![],
+        };
+        Ok(Response::new(Box::pin(futures::stream::iter(Some(Ok(
+            response,
+        ))))))
+    }
+
+    async fn list_flights(
+        &self,
+        _: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented("list_flights is not supported"))
+    }
+
+    async fn do_put(
+        &self,
+        _: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("do_put is not supported"))
+    }
+
+    async fn do_action(
+        &self,
+        _: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("do_action is not supported"))
+    }
+
+    async fn list_actions(
+        &self,
+        _: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Err(Status::unimplemented("list_actions is not supported"))
+    }
+
+    async fn do_exchange(
+        &self,
+        _: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("do_exchange is not supported"))
+    }
+}
+
+/// Returns the type URL of the `google.protobuf.Any` message in `bytes`, if
+/// `bytes` contains one.
+fn query_type_url(bytes: &[u8]) -> Option<&'static str> {
+    match prost_types::Any::decode(bytes) {
+        Ok(any) if any.type_url == COMMAND_STATEMENT_QUERY_TYPE_URL => {
+            Some(COMMAND_STATEMENT_QUERY_TYPE_URL)
+        }
+        Ok(any) if any.type_url == TICKET_STATEMENT_QUERY_TYPE_URL => {
+            Some(TICKET_STATEMENT_QUERY_TYPE_URL)
+        }
+        _ => None,
+    }
+}
+
+/// Extracts the text of a query from a descriptor command or a ticket.
+///
+/// Flight SQL clients wrap queries in a `CommandStatementQuery` or a
+/// `TicketStatementQuery` packed into a `google.protobuf.Any`. Other clients
+/// send the text of the query as is.
+fn decode_query(bytes: &[u8]) -> Result<String, Status> {
+    let invalid_message = |e: prost::DecodeError| Status::invalid_argument(e.to_string());
+    let sql = match query_type_url(bytes) {
+        Some(type_url) => {
+            let any = prost_types::Any::decode(bytes).map_err(invalid_message)?;
+            if type_url == COMMAND_STATEMENT_QUERY_TYPE_URL {
+                let command =
+                    CommandStatementQuery::decode(&*any.value).map_err(invalid_message)?;
+                command.query.into_bytes()
+            } else {
+                let ticket = TicketStatementQuery::decode(&*any.value).map_err(invalid_message)?;
+                ticket.statement_handle
+            }
+        }
+        None => bytes.to_vec(),
+    };
+    String::from_utf8(sql).map_err(|_| Status::invalid_argument("query is not valid UTF-8"))
+}
+
+/// Packs `message` into a `google.protobuf.Any` with the specified type URL.
+fn encode_any<M>(type_url: &str, message: &M) -> Vec<u8>
+where
+    M: Message,
+{
+    let mut value = vec![];
+    message
+        .encode(&mut value)
+        .expect("encoding into a vec cannot fail");
+    let any = prost_types::Any {
+        type_url: type_url.into(),
+        value,
+    };
+    let mut buf = vec![];
+    any.encode(&mut buf)
+        .expect("encoding into a vec cannot fail");
+    buf
+}
+
+/// Parses `sql` as a single `SELECT` statement.
+///
+/// Other statements are rejected before they reach the coordinator, so that
+/// Flight requests cannot modify the catalog or any data.
+fn parse_query(sql: &str) -> Result<Statement<Raw>, Status> {
+    let stmts = parse_statements(sql).map_err(invalid_argument)?;
+    if stmts.len() != 1 {
+        return Err(Status::invalid_argument("expected exactly 1 statement"));
+    }
+    match stmts.into_element() {
+        stmt @ Statement::Select(_) => Ok(stmt),
+        _ => Err(Status::invalid_argument(
+            "only SELECT statements are supported",
+        )),
+    }
+}
+
+/// Computes the Arrow schema that corresponds to a relation description.
+fn arrow_schema(desc: &RelationDesc) -> SchemaRef {
+    let fields = desc
+        .iter()
+        .map(|(name, typ)| {
+            let name = name.map(|name| name.as_str()).unwrap_or("?column?");
+            Field::new(name, arrow_type(&typ.scalar_type), typ.nullable)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+/// Computes the Arrow type that represents a scalar type.
+///
+/// Types without a natural Arrow counterpart are represented as strings
+/// containing their PostgreSQL text encoding.
+fn arrow_type(typ: &ScalarType) -> DataType {
+    match typ {
+        ScalarType::Bool => DataType::Boolean,
+        ScalarType::Int32 => DataType::Int32,
+        ScalarType::Int64 => DataType::Int64,
+        ScalarType::Float32 => DataType::Float32,
+        ScalarType::Float64 => DataType::Float64,
+        ScalarType::Date => DataType::Date32,
+        ScalarType::Timestamp => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ScalarType::TimestampTz => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+        ScalarType::Bytes => DataType::Binary,
+        _ => DataType::Utf8,
+    }
+}
+
+/// Builds a record batch with the specified schema from `rows`.
+fn record_batch(
+    schema: &SchemaRef,
+    column_types: &[ScalarType],
+    rows: &[Row],
+) -> Result<RecordBatch, arrow::error::ArrowError> {
+    let rows: Vec<Vec<Datum>> = rows.iter().map(|row| row.unpack()).collect();
+    let columns = column_types
+        .iter()
+        .enumerate()
+        .map(|(i, typ)| {
+            let datums: Vec<Datum> = rows.iter().map(|row| row[i]).collect();
+            arrow_array(typ, &datums)
+        })
+        .collect();
+    RecordBatch::try_new(Arc::clone(schema), columns)
+}
+
+/// Builds an Arrow array of type [`arrow_type(typ)`](arrow_type) from
+/// `datums`.
+fn arrow_array(typ: &ScalarType, datums: &[Datum]) -> ArrayRef {
+    fn values<'a, T, F>(datums: &[Datum<'a>], f: F) -> Vec<Option<T>>
+    where
+        F: Fn(Datum<'a>) -> T,
+    {
+        datums
+            .iter()
+            .map(|datum| {
+                if datum.is_null() {
+                    None
+                } else {
+                    Some(f(*datum))
+                }
+            })
+            .collect()
+    }
+
+    let epoch = NaiveDate::from_ymd(1970, 1, 1);
+    match typ {
+        ScalarType::Bool => Arc::new(BooleanArray::from(values(datums, |d| d.unwrap_bool()))),
+        ScalarType::Int32 => Arc::new(Int32Array::from(values(datums, |d| d.unwrap_int32()))),
+        ScalarType::Int64 => Arc::new(Int64Array::from(values(datums, |d| d.unwrap_int64()))),
+        ScalarType::Float32 => Arc::new(Float32Array::from(values(datums, |d| d.unwrap_float32()))),
+        ScalarType::Float64 => Arc::new(Float64Array::from(values(datums, |d| d.unwrap_float64()))),
+        ScalarType::Date => Arc::new(Date32Array::from(values(datums, |d| {
+            (d.unwrap_date() - epoch).num_days() as i32
+        }))),
+        ScalarType::Timestamp => Arc::new(TimestampMicrosecondArray::from_opt_vec(
+            values(datums, |d| timestamp_micros(d.unwrap_timestamp())),
+            None,
+        )),
+        ScalarType::TimestampTz => Arc::new(TimestampMicrosecondArray::from_opt_vec(
+            values(datums, |d| {
+                timestamp_micros(d.unwrap_timestamptz().naive_utc())
+            }),
+            Some("UTC".into()),
+        )),
+        ScalarType::Bytes => Arc::new(BinaryArray::from(values(datums, |d| d.unwrap_bytes()))),
+        _ => {
+            let values = values(datums, |d| {
+                let mut buf = String::new();
+                pgrepr::Value::from_datum(d, typ)
+                    .expect("datum is not null")
+                    .encode_text(&mut buf);
+                buf
+            });
+            Arc::new(values.into_iter().collect::<StringArray>())
+        }
+    }
+}
+
+fn timestamp_micros(ts: NaiveDateTime) -> i64 {
+    ts.timestamp() * 1_000_000 + i64::from(ts.timestamp_subsec_micros())
+}
+
+fn internal_error<E>(e: E) -> Status
+where
+    E: ToString,
+{
+    Status::internal(e.to_string())
+}
+
+fn invalid_argument<E>(e: E) -> Status
+where
+    E: ToString,
+{
+    Status::invalid_argument(e.to_string())
+}
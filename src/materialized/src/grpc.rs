@@ -0,0 +1,235 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Connection handling and authentication for the gRPC servers.
+//!
+//! The Arrow Flight and control API servers authenticate clients the way the
+//! pgwire server does. Each request names the user it runs as in its `user`
+//! metadata entry, just as a pgwire client names its user in its startup
+//! message. If TLS is configured, clients must connect with TLS, and in
+//! [`TlsMode::VerifyUser`] they must additionally present a certificate whose
+//! Common Name (CN) field matches the user they name.
+
+use std::io::{self, IoSlice};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::stream::Stream;
+use log::{error, warn};
+use openssl::nid::Nid;
+use openssl::ssl::{Ssl, SslContext};
+use openssl::x509::X509;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_openssl::SslStream;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::transport::server::Connected;
+use tonic::transport::Certificate;
+use tonic::{Request, Status};
+
+use ore::str::StrExt;
+
+/// The request metadata entry that names the user a request runs as.
+const USER_METADATA_KEY: &str = "user";
+
+/// Configures a gRPC server's TLS encryption and authentication.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// The SSL context used to manage incoming TLS negotiations.
+    pub context: SslContext,
+    /// The TLS mode.
+    pub mode: TlsMode,
+}
+
+/// Specifies how strictly to enforce TLS encryption and authentication.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsMode {
+    /// Clients must negotiate TLS encryption.
+    Require,
+    /// Clients must negotiate TLS encryption and supply a certificate whose
+    /// Common Name (CN) field matches the user name they connect as.
+    VerifyUser,
+}
+
+/// Accepts connections from `listener`, negotiating TLS with each connection
+/// if `tls` is present.
+///
+/// TLS negotiations run in their own tasks, so that a slow client cannot
+/// delay the acceptance of other connections. Connections that fail to
+/// negotiate TLS are dropped. The listener is closed once the returned stream
+/// is dropped.
+pub fn incoming(
+    listener: TcpListener,
+    tls: Option<TlsConfig>,
+) -> impl Stream<Item = Result<Conn, io::Error>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            let conn = tokio::select! {
+                res = listener.accept() => match res {
+                    Ok((conn, _addr)) => conn,
+                    Err(e) => {
+                        error!("error accepting gRPC connection: {}", e);
+                        continue;
+                    }
+                },
+                _ = tx.closed() => break,
+            };
+            match &tls {
+                None => {
+                    if tx.send(Ok(Conn::Unencrypted(conn))).is_err() {
+                        break;
+                    }
+                }
+                Some(tls) => {
+                    let tx = tx.clone();
+                    let context = tls.context.clone();
+                    tokio::spawn(async move {
+                        match accept_tls(&context, conn).await {
+                            Ok(conn) => {
+                                let _ = tx.send(Ok(Conn::Ssl(conn)));
+                            }
+                            Err(e) => warn!("gRPC connection failed to negotiate TLS: {}", e),
+                        }
+                    });
+                }
+            }
+        }
+    });
+    UnboundedReceiverStream::new(rx)
+}
+
+async fn accept_tls(
+    context: &SslContext,
+    conn: TcpStream,
+) -> Result<SslStream<TcpStream>, anyhow::Error> {
+    let mut ssl_stream = SslStream::new(Ssl::new(context)?, conn)?;
+    Pin::new(&mut ssl_stream).accept().await?;
+    Ok(ssl_stream)
+}
+
+/// Determines the user on whose behalf `request` runs.
+///
+/// The user is named by the request's `user` metadata entry. In
+/// [`TlsMode::VerifyUser`], the client's certificate must vouch for that user.
+pub fn authenticate<T>(tls_mode: Option<TlsMode>, request: &Request<T>) -> Result<String, Status> {
+    let user = match request.metadata().get(USER_METADATA_KEY) {
+        Some(user) => user
+            .to_str()
+            .map_err(|_| Status::unauthenticated("user name is not valid ASCII"))?
+            .to_owned(),
+        None => {
+            return Err(Status::unauthenticated(format!(
+                "request must name its user in the {} metadata entry",
+                USER_METADATA_KEY.quoted()
+            )))
+        }
+    };
+    if let Some(TlsMode::VerifyUser) = tls_mode {
+        let cn_matches = request
+            .peer_certs()
+            .and_then(|certs| certs.first().cloned())
+            .and_then(|cert| X509::from_pem(cert.get_ref()).ok())
+            .map_or(false, |cert| {
+                cert.subject_name()
+                    .entries_by_nid(Nid::COMMONNAME)
+                    .any(|n| n.data().as_slice() == user.as_bytes())
+            });
+        if !cn_matches {
+            return Err(Status::unauthenticated(format!(
+                "certificate authentication failed for user {}",
+                user.quoted()
+            )));
+        }
+    }
+    Ok(user)
+}
+
+/// A connection to a gRPC server.
+#[derive(Debug)]
+pub enum Conn {
+    Unencrypted(TcpStream),
+    Ssl(SslStream<TcpStream>),
+}
+
+impl Connected for Conn {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Conn::Unencrypted(inner) => inner.peer_addr().ok(),
+            Conn::Ssl(inner) => inner.get_ref().peer_addr().ok(),
+        }
+    }
+
+    fn peer_certs(&self) -> Option<Vec<Certificate>> {
+        match self {
+            Conn::Unencrypted(_) => None,
+            Conn::Ssl(inner) => {
+                let cert = inner.ssl().peer_certificate()?;
+                let pem = cert.to_pem().ok()?;
+                Some(vec![Certificate::from_pem(pem)])
+            }
+        }
+    }
+}
+
+impl AsyncRead for Conn {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut ReadBuf,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Conn::Unencrypted(inner) => Pin::new(inner).poll_read(cx, buf),
+            Conn::Ssl(inner) => Pin::new(inner).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Conn {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Conn::Unencrypted(inner) => Pin::new(inner).poll_write(cx, buf),
+            Conn::Ssl(inner) => Pin::new(inner).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Conn::Unencrypted(inner) => Pin::new(inner).poll_write_vectored(cx, bufs),
+            Conn::Ssl(inner) => Pin::new(inner).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Conn::Unencrypted(inner) => inner.is_write_vectored(),
+            Conn::Ssl(inner) => inner.is_write_vectored(),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Conn::Unencrypted(inner) => Pin::new(inner).poll_flush(cx),
+            Conn::Ssl(inner) => Pin::new(inner).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Conn::Unencrypted(inner) => Pin::new(inner).poll_shutdown(cx),
+            Conn::Ssl(inner) => Pin::new(inner).poll_shutdown(cx),
+        }
+    }
+}
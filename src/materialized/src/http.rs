@@ -28,11 +28,15 @@ use tokio_openssl::SslStream;
 use ore::netio::SniffedStream;
 
 mod catalog;
+#[cfg(feature = "fault-injection")]
+mod faults;
+mod health;
 mod memory;
 mod metrics;
 mod prof;
 mod root;
 mod sql;
+mod tail;
 mod util;
 
 const SYSTEM_USER: &str = "mz_system";
@@ -148,6 +152,13 @@ impl Server {
                     Err(e) => return Ok(util::error_response(StatusCode::UNAUTHORIZED, e)),
                 };
 
+                // The liveness check must not depend on the coordinator
+                // starting a session for the request, as checking that the
+                // coordinator responds is its purpose.
+                if (req.method(), req.uri().path()) == (&Method::GET, "/api/livez") {
+                    return health::handle_livez(coord_client, user).await;
+                }
+
                 let coord_client = coord_client.new_conn()?;
                 let session = Session::new(coord_client.conn_id(), user);
                 let (mut coord_client, _) = match coord_client.startup(session).await {
@@ -160,6 +171,12 @@ impl Server {
                     }
                 };
 
+                // The WebSocket that streams `TAIL` output outlives the
+                // request, so its handler takes over the coordinator client.
+                if (req.method(), req.uri().path()) == (&Method::GET, "/api/tail") {
+                    return tail::handle_api_tail(req, coord_client).await;
+                }
+
                 let res = match (req.method(), req.uri().path()) {
                     (&Method::GET, "/") => root::handle_home(req, &mut coord_client).await,
                     (&Method::GET, "/metrics") => {
@@ -169,14 +186,34 @@ impl Server {
                         metrics::handle_status(req, &mut coord_client, start_time).await
                     }
                     (&Method::GET, "/prof") => prof::handle_prof(req, &mut coord_client).await,
+                    (&Method::GET, "/prof/cpu") => {
+                        prof::handle_prof_cpu(req, &mut coord_client).await
+                    }
+                    (&Method::GET, "/prof/heap") => {
+                        prof::handle_prof_heap(req, &mut coord_client).await
+                    }
                     (&Method::GET, "/memory") => {
                         memory::handle_memory(req, &mut coord_client).await
                     }
                     (&Method::POST, "/prof") => prof::handle_prof(req, &mut coord_client).await,
                     (&Method::POST, "/sql") => sql::handle_sql(req, &mut coord_client).await,
+                    (&Method::POST, "/api/sql") => {
+                        sql::handle_api_sql(req, &mut coord_client).await
+                    }
+                    (&Method::GET, "/api/readyz") => {
+                        health::handle_readyz(req, &mut coord_client).await
+                    }
                     (&Method::GET, "/internal/catalog") => {
                         catalog::handle_internal_catalog(req, &mut coord_client).await
                     }
+                    #[cfg(feature = "fault-injection")]
+                    (&Method::POST, "/internal/faults") => {
+                        faults::handle_inject_fault(req, &mut coord_client).await
+                    }
+                    #[cfg(feature = "fault-injection")]
+                    (&Method::DELETE, "/internal/faults") => {
+                        faults::handle_clear_faults(req, &mut coord_client).await
+                    }
                     _ => root::handle_static(req, &mut coord_client).await,
                 };
                 coord_client.terminate().await;
@@ -0,0 +1,48 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Fault injection HTTP endpoints.
+//!
+//! These endpoints are only available when materialized is built with the
+//! `fault-injection` feature. See [`dataflow::faults`] for details.
+
+use hyper::{Body, Request, Response, StatusCode};
+
+use dataflow::faults::{self, Fault};
+
+use crate::http::util;
+
+/// Injects the fault described by the JSON-encoded request body, e.g.
+/// `{"kind": "stall-partition", "topic": "data", "partition": 0}`.
+pub async fn handle_inject_fault(
+    req: Request<Body>,
+    _: &mut coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    let body = hyper::body::to_bytes(req).await?;
+    let fault: Fault = match serde_json::from_slice(&body) {
+        Ok(fault) => fault,
+        Err(e) => {
+            return Ok(util::error_response(
+                StatusCode::BAD_REQUEST,
+                format!("invalid fault: {}", e),
+            ))
+        }
+    };
+    faults::inject(fault);
+    Ok(Response::new(Body::from("ok")))
+}
+
+/// Clears all injected faults.
+pub async fn handle_clear_faults(
+    _: Request<Body>,
+    _: &mut coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    faults::clear();
+    Ok(Response::new(Body::from("ok")))
+}
@@ -0,0 +1,79 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Health check HTTP endpoints.
+//!
+//! These endpoints are intended for use as Kubernetes probes. Readiness
+//! indicates whether the process should receive traffic, while liveness
+//! indicates whether the process should be restarted.
+
+use std::time::Duration;
+
+use coord::session::Session;
+use hyper::{header, Body, Request, Response, StatusCode};
+use serde_json::json;
+
+/// How long the coordinator may take to respond before the process is
+/// considered dead.
+const LIVENESS_DEADLINE: Duration = Duration::from_secs(5);
+
+/// Reports whether the process is ready to serve queries.
+///
+/// The process is ready once its catalog is loaded and every source that
+/// existed at startup has connected, or once the rehydration budget has
+/// elapsed.
+pub async fn handle_readyz(
+    _: Request<Body>,
+    coord_client: &mut coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    let readiness = coord_client.check_readiness().await?;
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = json!({
+        "ready": readiness.ready,
+        "rehydrating_sources": readiness.rehydrating_sources,
+    });
+    Ok(Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap())
+}
+
+/// Reports whether the coordinator responds within [`LIVENESS_DEADLINE`].
+///
+/// Unlike other handlers, this handler is not handed a session, as starting a
+/// session itself requires a response from the coordinator.
+pub async fn handle_livez(
+    coord_client: coord::Client,
+    user: String,
+) -> Result<Response<Body>, anyhow::Error> {
+    let ping = async {
+        let coord_client = coord_client.new_conn()?;
+        let session = Session::new(coord_client.conn_id(), user);
+        let (coord_client, _) = coord_client.startup(session).await?;
+        coord_client.terminate().await;
+        Ok::<_, coord::CoordError>(())
+    };
+    let (status, body) = match tokio::time::timeout(LIVENESS_DEADLINE, ping).await {
+        Ok(Ok(())) => (StatusCode::OK, "ok".to_string()),
+        Ok(Err(e)) => (StatusCode::SERVICE_UNAVAILABLE, e.to_string()),
+        Err(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("coordinator did not respond within {:?}", LIVENESS_DEADLINE),
+        ),
+    };
+    Ok(Response::builder()
+        .status(status)
+        .body(Body::from(body))
+        .unwrap())
+}
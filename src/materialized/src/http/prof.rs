@@ -17,7 +17,8 @@ use std::time::Duration;
 
 use askama::Template;
 use cfg_if::cfg_if;
-use hyper::{Body, Request, Response};
+use hyper::{header, Body, Request, Response, StatusCode};
+use url::form_urlencoded;
 
 use prof::{ProfStartTime, StackProfile};
 
@@ -37,6 +38,105 @@ pub async fn handle_prof(
     }
 }
 
+/// Serves a jemalloc heap profile, suitable for use with `jeprof`.
+///
+/// Heap profiling must first be activated, e.g. via the `/prof` page.
+pub async fn handle_prof_heap(
+    req: Request<Body>,
+    _: &mut coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    cfg_if! {
+        if #[cfg(target_os = "macos")] {
+            disabled::handle_heap(req).await
+        } else {
+            enabled::handle_heap(req).await
+        }
+    }
+}
+
+/// Profiles CPU usage and serves the profile in the protobuf format
+/// understood by pprof.
+///
+/// The `seconds` and `frequency` query parameters control the duration of
+/// the profile and the sampling frequency, in hertz, respectively.
+#[allow(clippy::drop_copy, clippy::unit_arg)]
+pub async fn handle_prof_cpu(
+    req: Request<Body>,
+    _: &mut coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    const MAX_SECONDS: u64 = 300;
+
+    let params: HashMap<_, _> =
+        form_urlencoded::parse(req.uri().query().unwrap_or("").as_bytes()).collect();
+    let seconds = match params.get("seconds").map(|s| s.parse::<u64>()) {
+        None => 10,
+        Some(Ok(seconds)) if seconds > 0 && seconds <= MAX_SECONDS => seconds,
+        Some(_) => {
+            return Ok(util::error_response(
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "`seconds` parameter must be an integer between 1 and {}",
+                    MAX_SECONDS
+                ),
+            ))
+        }
+    };
+    let frequency = match params.get("frequency").map(|s| s.parse::<u32>()) {
+        None => 99,
+        Some(Ok(frequency)) if frequency > 0 => frequency,
+        Some(_) => {
+            return Ok(util::error_response(
+                StatusCode::BAD_REQUEST,
+                "`frequency` parameter must be a positive integer",
+            ))
+        }
+    };
+
+    let ctl_lock = lock_mem_prof().await?;
+    // SAFETY: We ensure above that memory profiling is off.
+    // Since we hold the mutex, nobody else can be turning it back on in the intervening time.
+    let profile =
+        unsafe { prof::time::prof_time_pprof(Duration::from_secs(seconds), frequency) }.await;
+    // Fail with a compile error if we weren't holding the jemalloc lock.
+    drop(ctl_lock);
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"cpu.pb\"",
+        )
+        .body(Body::from(profile?))
+        .unwrap())
+}
+
+cfg_if! {
+    if #[cfg(target_os = "macos")] {
+        type MemProfLock = ();
+
+
+        async fn lock_mem_prof() -> anyhow::Result<MemProfLock> {
+            Ok(())
+        }
+    } else {
+        type MemProfLock =
+            Option<tokio::sync::MutexGuard<'static, prof::jemalloc::JemallocProfCtl>>;
+
+        /// Deactivates memory profiling, which cannot run at the same time as
+        /// CPU profiling, and prevents it from being reactivated until the
+        /// returned lock is dropped.
+        async fn lock_mem_prof() -> anyhow::Result<MemProfLock> {
+            match prof::jemalloc::PROF_CTL.as_ref() {
+                Some(ctl) => {
+                    let mut borrow = ctl.lock().await;
+                    borrow.deactivate()?;
+                    Ok(Some(borrow))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+}
+
 #[allow(dead_code)]
 enum MemProfilingStatus {
     Disabled,
@@ -64,20 +164,7 @@ struct FlamegraphTemplate<'a> {
 async fn time_prof<'a>(
     params: &HashMap<Cow<'a, str>, Cow<'a, str>>,
 ) -> anyhow::Result<Response<Body>> {
-    let ctl_lock;
-    cfg_if! {
-        if #[cfg(target_os = "macos")] {
-            ctl_lock = ();
-        } else {
-            ctl_lock = if let Some(ctl) = prof::jemalloc::PROF_CTL.as_ref() {
-                let mut borrow = ctl.lock().await;
-                borrow.deactivate()?;
-                Some(borrow)
-            } else {
-                None
-            };
-        }
-    }
+    let ctl_lock = lock_mem_prof().await?;
     let merge_threads = params.get("threads").map(AsRef::as_ref) == Some("merge");
     // SAFETY: We ensure above that memory profiling is off.
     // Since we hold the mutex, nobody else can be turning it back on in the intervening time.
@@ -133,6 +220,13 @@ mod disabled {
     use crate::http::util;
     use crate::BUILD_INFO;
 
+    pub async fn handle_heap(_: Request<Body>) -> anyhow::Result<Response<Body>> {
+        Ok(util::error_response(
+            StatusCode::BAD_REQUEST,
+            "heap profiling is not supported on this platform",
+        ))
+    }
+
     pub async fn handle(req: Request<Body>) -> anyhow::Result<Response<Body>> {
         match req.method() {
             &Method::GET => Ok(util::template_response(ProfTemplate {
@@ -214,6 +308,32 @@ mod enabled {
         }
     }
 
+    pub async fn handle_heap(req: Request<Body>) -> anyhow::Result<Response<Body>> {
+        let prof_ctl = match &*PROF_CTL {
+            Some(prof_ctl) => prof_ctl,
+            None => return super::disabled::handle_heap(req).await,
+        };
+        let mut borrow = prof_ctl.lock().await;
+        if borrow.get_md().start_time.is_none() {
+            return Ok(util::error_response(
+                StatusCode::BAD_REQUEST,
+                "heap profiling is not active; activate it via the /prof page or by \
+                 POSTing `action=activate` to /prof",
+            ));
+        }
+        let mut f = borrow.dump()?;
+        let mut buf = Vec::new();
+        f.read_to_end(&mut buf)?;
+        Ok(Response::builder()
+            .header(header::CONTENT_TYPE, "application/octet-stream")
+            .header(
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"jeprof.heap\"",
+            )
+            .body(Body::from(buf))
+            .unwrap())
+    }
+
     pub async fn handle_post(
         body: Request<Body>,
         prof_ctl: &Arc<Mutex<JemallocProfCtl>>,
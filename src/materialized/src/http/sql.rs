@@ -9,17 +9,19 @@
 
 use std::collections::HashMap;
 
-use anyhow::bail;
+use anyhow::{anyhow, bail};
+use futures::stream::StreamExt;
 use hyper::{header, Body, Request, Response, StatusCode};
-use serde::Serialize;
-use serde_json::{Number, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Number, Value};
 use url::form_urlencoded;
 
 use crate::http::util;
+use coord::session::query_tag;
 use coord::ExecuteResponse;
 use dataflow_types::PeekResponse;
 use ore::collections::CollectionExt;
-use repr::{Datum, ScalarType};
+use repr::{Datum, RowArena, ScalarType};
 use sql_parser::parser::parse_statements;
 
 pub async fn handle_sql(
@@ -33,7 +35,7 @@ pub async fn handle_sql(
             Some(sql) => sql,
             None => bail!("expected `sql` parameter"),
         };
-        let res = query_sql(coord_client, sql.to_string()).await?;
+        let res = query_sql(coord_client, sql.to_string(), vec![]).await?;
         Ok(Response::builder()
             .header(header::CONTENT_TYPE, "application/json")
             .body(Body::from(serde_json::to_string(&res)?))
@@ -46,47 +48,125 @@ pub async fn handle_sql(
     }
 }
 
-/// Executes a single SQL statement as the specified user.
+/// A request to the `/api/sql` endpoint.
+#[derive(Deserialize)]
+struct SqlRequest {
+    /// The SQL statement to execute.
+    query: String,
+    /// The values of the statement's parameters, like `$1`, in order.
+    #[serde(default)]
+    params: Vec<Value>,
+}
+
+/// Handles a JSON-encoded [`SqlRequest`].
+///
+/// Unlike [`handle_sql`], which serves the web UI, this endpoint is intended
+/// for programmatic use, so errors are reported as JSON too.
+pub async fn handle_api_sql(
+    req: Request<Body>,
+    coord_client: &mut coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    let res = async {
+        let body = hyper::body::to_bytes(req).await?;
+        let SqlRequest { query, params } =
+            serde_json::from_slice(&body).map_err(|e| anyhow!("invalid request body: {}", e))?;
+        query_sql(coord_client, query, params).await
+    }
+    .await;
+    let (status, body) = match res {
+        Ok(res) => (StatusCode::OK, serde_json::to_string(&res)?),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            json!({ "error": e.to_string() }).to_string(),
+        ),
+    };
+    Ok(Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap())
+}
+
+/// Executes a single SQL statement as the specified user, binding `params` to
+/// the statement's parameters.
 async fn query_sql(
     coord_client: &mut coord::SessionClient,
     sql: String,
+    params: Vec<Value>,
 ) -> anyhow::Result<SqlResult> {
     let stmts = parse_statements(&sql)?;
     if stmts.len() != 1 {
         bail!("expected exactly 1 statement");
     }
     let stmt = stmts.into_element();
+    let tag = query_tag::extract(&sql);
 
     coord_client.session().start_transaction();
 
+    const EMPTY_STATEMENT: &str = "";
     const EMPTY_PORTAL: &str = "";
-    let params = vec![];
     coord_client
-        .declare(EMPTY_PORTAL.into(), stmt, params)
+        .describe(EMPTY_STATEMENT.into(), Some(stmt), vec![], tag)
         .await?;
-    let desc = coord_client
+    let prepared = coord_client
         .session()
-        .get_portal(EMPTY_PORTAL)
-        .map(|portal| portal.desc.clone())
-        .expect("unnamed portal should be present");
-    if !desc.param_types.is_empty() {
-        bail!("parameters are not supported");
+        .get_prepared_statement(EMPTY_STATEMENT)
+        .expect("unnamed prepared statement should be present");
+    let desc = prepared.desc().clone();
+    let stmt = prepared.sql().cloned();
+    let tag = prepared.tag().map(String::from);
+
+    if desc.param_types.len() != params.len() {
+        bail!(
+            "request supplies {} parameters, but statement requires {}",
+            params.len(),
+            desc.param_types.len()
+        );
     }
+    let buf = RowArena::new();
+    let mut datums = Vec::with_capacity(params.len());
+    for (i, (param, typ)) in params.into_iter().zip(&desc.param_types).enumerate() {
+        let raw = match param {
+            Value::Null => {
+                datums.push(pgrepr::null_datum(typ));
+                continue;
+            }
+            Value::String(s) => s,
+            // Other JSON values are passed in their textual form, so that,
+            // e.g., numbers can be bound to any numeric type and objects to
+            // `jsonb`.
+            param => param.to_string(),
+        };
+        match pgrepr::Value::decode(pgrepr::Format::Text, typ, raw.as_bytes()) {
+            Ok(value) => datums.push(value.into_datum(&buf, typ)),
+            Err(e) => bail!("unable to decode parameter ${}: {}", i + 1, e),
+        }
+    }
+
+    let result_formats = vec![pgrepr::Format::Text; desc.arity()];
+    coord_client.session().set_portal(
+        EMPTY_PORTAL.into(),
+        desc.clone(),
+        stmt,
+        datums,
+        result_formats,
+        tag,
+    )?;
 
     let res = coord_client.execute(EMPTY_PORTAL.into()).await?;
 
-    let rows = match res {
-        ExecuteResponse::SendingRows(rows) => {
-            let response = rows.await;
-            response
-        }
+    let mut batches = match res {
+        ExecuteResponse::SendingRows(batches) => batches,
         _ => bail!("unsupported statement type"),
     };
-    let rows = match rows {
-        PeekResponse::Rows(rows) => rows,
-        PeekResponse::Error(e) => bail!("{}", e),
-        PeekResponse::Canceled => bail!("execution canceled"),
-    };
+    let mut rows = vec![];
+    while let Some(batch) = batches.next().await {
+        match batch {
+            PeekResponse::Rows(batch) => rows.extend(batch),
+            PeekResponse::Error(e) => bail!("{}", e),
+            PeekResponse::Canceled => bail!("execution canceled"),
+        }
+    }
     let mut sql_rows: Vec<Vec<Value>> = vec![];
     let (col_names, col_types) = match desc.relation_desc {
         Some(desc) => (
@@ -102,31 +182,8 @@ async fn query_sql(
         sql_rows.push(
             datums
                 .iter()
-                .enumerate()
-                .map(|(idx, datum)| match datum {
-                    // Convert some common things to a native JSON value. This doesn't need to be
-                    // too exhaustive because the SQL-over-HTTP interface is currently not hooked
-                    // up to arbitrary external user queries.
-                    Datum::Null | Datum::JsonNull => Value::Null,
-                    Datum::False => Value::Bool(false),
-                    Datum::True => Value::Bool(true),
-                    Datum::Int32(n) => Value::Number(Number::from(*n)),
-                    Datum::Int64(n) => Value::Number(Number::from(*n)),
-                    Datum::Float32(n) => float_to_json(n.into_inner() as f64),
-                    Datum::Float64(n) => float_to_json(n.into_inner()),
-                    Datum::String(s) => Value::String(s.to_string()),
-                    Datum::Decimal(d) => Value::String(if col_types.len() > idx {
-                        match col_types[idx].scalar_type {
-                            ScalarType::Decimal(_precision, scale) => {
-                                d.with_scale(scale).to_string()
-                            }
-                            _ => datum.to_string(),
-                        }
-                    } else {
-                        datum.to_string()
-                    }),
-                    _ => Value::String(datum.to_string()),
-                })
+                .zip(&col_types)
+                .map(|(datum, typ)| datum_to_json(datum, &typ.scalar_type))
                 .collect(),
         );
     }
@@ -142,6 +199,28 @@ struct SqlResult {
     col_names: Vec<Option<String>>,
 }
 
+/// Converts a datum of type `typ` to a JSON value.
+pub fn datum_to_json(datum: &Datum, typ: &ScalarType) -> Value {
+    match datum {
+        // Convert some common things to a native JSON value. This doesn't need to be
+        // too exhaustive because the SQL-over-HTTP interface is currently not hooked
+        // up to arbitrary external user queries.
+        Datum::Null | Datum::JsonNull => Value::Null,
+        Datum::False => Value::Bool(false),
+        Datum::True => Value::Bool(true),
+        Datum::Int32(n) => Value::Number(Number::from(*n)),
+        Datum::Int64(n) => Value::Number(Number::from(*n)),
+        Datum::Float32(n) => float_to_json(n.into_inner() as f64),
+        Datum::Float64(n) => float_to_json(n.into_inner()),
+        Datum::String(s) => Value::String(s.to_string()),
+        Datum::Decimal(d) => Value::String(match typ {
+            ScalarType::Decimal(_precision, scale) => d.with_scale(*scale).to_string(),
+            _ => datum.to_string(),
+        }),
+        _ => Value::String(datum.to_string()),
+    }
+}
+
 // Convert most floats to a JSON Number. JSON Numbers don't support NaN or
 // Infinity, so those will still be rendered as strings.
 fn float_to_json(f: f64) -> Value {
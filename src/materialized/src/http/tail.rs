@@ -0,0 +1,255 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Streaming of `TAIL` results over WebSockets.
+//!
+//! A client opens a WebSocket to `/api/tail?query=TAIL+...`. Once the
+//! connection is established, the server sends a `columns` frame naming the
+//! tailed object's columns, followed by an `update` frame for every change to
+//! the object and a `progress` frame whenever the object's timestamp advances.
+//! The `PROGRESS` option is always enabled, so that clients know when they
+//! have seen all updates for a timestamp.
+//!
+//! The fields of an `update` frame follow the envelope of the `TAIL`: the
+//! default envelope reports a `diff` alongside each row, `ENVELOPE UPSERT`
+//! reports the `state` of each key, and `ENVELOPE DEBEZIUM` reports the
+//! `before` and `after` records of each change as the row.
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+use futures::sink::SinkExt;
+use futures::stream::StreamExt;
+use hyper::{header, Body, Request, Response, StatusCode};
+use log::warn;
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use url::form_urlencoded;
+
+use crate::http::sql::datum_to_json;
+use crate::http::util;
+use coord::session::query_tag;
+use coord::ExecuteResponse;
+use ore::collections::CollectionExt;
+use repr::{Datum, RelationDesc, Row, ScalarType};
+use sql_parser::ast::{
+    Ident, Statement, TailEnvelope, Value as SqlValue, WithOption, WithOptionValue,
+};
+use sql_parser::parser::parse_statements;
+
+/// Returns the number of leading columns in the output of a `TAIL` with
+/// `envelope` that describe an update, rather than the tailed object.
+///
+/// Every envelope starts with `timestamp` and `progressed`. The default
+/// envelope follows them with `diff` and `ENVELOPE UPSERT` with `state`, while
+/// the `before` and `after` records of `ENVELOPE DEBEZIUM` hold the object's
+/// columns.
+fn metadata_columns(envelope: Option<&TailEnvelope>) -> usize {
+    match envelope {
+        None | Some(TailEnvelope::Upsert { .. }) => 3,
+        Some(TailEnvelope::Debezium { .. }) => 2,
+    }
+}
+
+/// Handles a WebSocket upgrade request to the `/api/tail` endpoint.
+///
+/// Unlike other handlers, this handler takes ownership of the coordinator
+/// client, as the `TAIL` outlives the request that started it. The client is
+/// terminated when the WebSocket closes.
+pub async fn handle_api_tail(
+    mut req: Request<Body>,
+    mut coord_client: coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    let res = async {
+        let accept_key = match req.headers().get(header::SEC_WEBSOCKET_KEY) {
+            Some(key) if is_upgrade_request(&req) => derive_accept_key(key.as_bytes()),
+            _ => bail!("expected a WebSocket upgrade request"),
+        };
+        let query: HashMap<_, _> =
+            form_urlencoded::parse(req.uri().query().unwrap_or("").as_bytes()).collect();
+        let query = match query.get("query") {
+            Some(query) => query.to_string(),
+            None => bail!("expected `query` parameter"),
+        };
+        let tail = start_tail(&mut coord_client, &query).await?;
+        Ok((accept_key, tail))
+    }
+    .await;
+    let (accept_key, (rx, desc, metadata_columns)) = match res {
+        Ok(res) => res,
+        Err(e) => {
+            coord_client.terminate().await;
+            return Ok(util::error_response(StatusCode::BAD_REQUEST, e.to_string()));
+        }
+    };
+
+    let on_upgrade = hyper::upgrade::on(&mut req);
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => {
+                let ws = WebSocketStream::from_raw_socket(upgraded, Role::Server, None).await;
+                stream_tail(ws, rx, desc, metadata_columns).await;
+            }
+            Err(e) => warn!("unable to upgrade /api/tail connection: {}", e),
+        }
+        coord_client.terminate().await;
+    });
+
+    Ok(Response::builder()
+        .status(StatusCode::SWITCHING_PROTOCOLS)
+        .header(header::CONNECTION, "upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_ACCEPT, accept_key)
+        .body(Body::empty())
+        .unwrap())
+}
+
+fn is_upgrade_request(req: &Request<Body>) -> bool {
+    let header_contains = |name, value: &str| {
+        req.headers().get_all(name).iter().any(|v| {
+            v.to_str()
+                .unwrap_or("")
+                .split(',')
+                .any(|v| v.trim().eq_ignore_ascii_case(value))
+        })
+    };
+    header_contains(header::CONNECTION, "upgrade") && header_contains(header::UPGRADE, "websocket")
+}
+
+/// Starts executing the `TAIL` statement in `sql`, with the `PROGRESS` option
+/// enabled.
+///
+/// Returns the channel on which batches of `TAIL` output arrive, along with
+/// the description of that output and the number of its leading columns that
+/// describe each update.
+async fn start_tail(
+    coord_client: &mut coord::SessionClient,
+    sql: &str,
+) -> anyhow::Result<(mpsc::UnboundedReceiver<Vec<Row>>, RelationDesc, usize)> {
+    let stmts = parse_statements(sql)?;
+    if stmts.len() != 1 {
+        bail!("expected exactly 1 statement");
+    }
+    let mut tail = match stmts.into_element() {
+        Statement::Tail(tail) => tail,
+        _ => bail!("expected a TAIL statement"),
+    };
+    tail.options
+        .retain(|option| !option.key.as_str().eq_ignore_ascii_case("progress"));
+    tail.options.push(WithOption {
+        key: Ident::new("progress"),
+        value: Some(WithOptionValue::Value(SqlValue::Boolean(true))),
+    });
+    let metadata_columns = metadata_columns(tail.envelope.as_ref());
+    let tag = query_tag::extract(sql);
+
+    coord_client.session().start_transaction();
+
+    const EMPTY_PORTAL: &str = "";
+    coord_client
+        .declare(EMPTY_PORTAL.into(), Statement::Tail(tail), vec![], tag)
+        .await?;
+    let desc = coord_client
+        .session()
+        .get_portal(EMPTY_PORTAL)
+        .and_then(|portal| portal.desc.relation_desc.clone())
+        .expect("TAIL statements have a relation description");
+
+    match coord_client.execute(EMPTY_PORTAL.into()).await? {
+        ExecuteResponse::Tailing { rx } => Ok((rx, desc, metadata_columns)),
+        _ => bail!("unexpected response to TAIL statement"),
+    }
+}
+
+/// Forwards `TAIL` output from `rx` to `ws` until either the `TAIL` ends or
+/// the client goes away.
+async fn stream_tail<S>(
+    mut ws: WebSocketStream<S>,
+    mut rx: mpsc::UnboundedReceiver<Vec<Row>>,
+    desc: RelationDesc,
+    metadata_columns: usize,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let col_types: Vec<ScalarType> = desc
+        .typ()
+        .column_types
+        .iter()
+        .map(|typ| typ.scalar_type.clone())
+        .collect();
+    let mut col_names: Vec<Option<String>> = desc
+        .iter_names()
+        .map(|name| name.map(|name| name.to_string()))
+        .collect();
+    let metadata_names: Vec<String> = col_names
+        .drain(..metadata_columns)
+        .map(|name| name.unwrap_or_default())
+        .collect();
+    let frame = json!({ "type": "columns", "col_names": col_names });
+    if ws.send(Message::Text(frame.to_string())).await.is_err() {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            rows = rx.recv() => match rows {
+                Some(rows) => {
+                    for row in rows {
+                        let frame = encode_row(&row, &col_types, &metadata_names);
+                        if ws.send(Message::Text(frame.to_string())).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                None => {
+                    let frame = json!({ "type": "error", "error": "TAIL terminated" });
+                    let _ = ws.send(Message::Text(frame.to_string())).await;
+                    break;
+                }
+            },
+            // Clients are not expected to send anything but pings, which are
+            // answered automatically, and close frames.
+            msg = ws.next() => match msg {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => (),
+            },
+        }
+    }
+    let _ = ws.close(None).await;
+}
+
+/// Encodes a row of `TAIL` output as a JSON frame.
+///
+/// The leading columns of the row, named by `metadata_names`, describe the
+/// update. Those after `timestamp` and `progressed` become fields of the frame
+/// of the same name.
+fn encode_row(row: &Row, col_types: &[ScalarType], metadata_names: &[String]) -> Value {
+    let datums = row.unpack();
+    let timestamp = datum_to_json(&datums[0], &col_types[0]);
+    if datums[1] == Datum::True {
+        return json!({ "type": "progress", "timestamp": timestamp });
+    }
+    let metadata_columns = metadata_names.len();
+    let values: Vec<Value> = datums[metadata_columns..]
+        .iter()
+        .zip(&col_types[metadata_columns..])
+        .map(|(datum, typ)| datum_to_json(datum, typ))
+        .collect();
+    let mut frame = json!({ "type": "update", "timestamp": timestamp });
+    for (i, name) in metadata_names.iter().enumerate().skip(2) {
+        frame[name] = datum_to_json(&datums[i], &col_types[i]);
+    }
+    frame["row"] = Value::Array(values);
+    frame
+}
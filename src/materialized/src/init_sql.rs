@@ -0,0 +1,238 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Execution of SQL statements at startup.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use futures::StreamExt;
+use log::info;
+use serde::Serialize;
+
+use coord::session::{EndTransactionAction, Session, TransactionStatus};
+use coord::{ExecuteResponse, SessionClient};
+use dataflow_types::PeekResponse;
+use sql_parser::ast::{IfExistsBehavior, Raw, Statement};
+use sql_parser::parser::parse_statements;
+
+/// The user as which init SQL executes.
+const INIT_USER: &str = "materialize";
+
+/// Runs the SQL statements in the file or directory at `path`.
+///
+/// If `path` is a directory, the `.sql` files directly within it run in order
+/// of their names. All statements run in order in a single session, and
+/// statements outside of an explicit transaction are committed as they run.
+///
+/// Init SQL runs on every startup, so `CREATE` statements are run as if they
+/// specified `IF NOT EXISTS`, and skip objects that were created by a previous
+/// run. `CREATE TYPE` and `CREATE ROLE`, which cannot skip existing objects,
+/// are rejected. Other statements, like `INSERT`, run every time.
+///
+/// Stops at the first statement that fails, and returns an error that names
+/// the statement and the file that contains it.
+pub async fn run(coord_client: &coord::Client, path: &Path) -> Result<(), anyhow::Error> {
+    let files = find_files(path)?;
+    let conn_client = coord_client.new_conn()?;
+    let session = Session::new(conn_client.conn_id(), INIT_USER.into());
+    let (mut session_client, _) = conn_client.startup(session).await?;
+    let res = async {
+        for file in files {
+            let sql = fs::read_to_string(&file)
+                .with_context(|| format!("reading init SQL file {}", file.display()))?;
+            let stmts = parse_statements(&sql)
+                .with_context(|| format!("parsing init SQL file {}", file.display()))?;
+            info!(
+                "running {} init SQL statements from {}",
+                stmts.len(),
+                file.display()
+            );
+            for stmt in stmts {
+                let sql = stmt.to_string();
+                let context = || format!("executing `{}` from {}", sql, file.display());
+                let stmt = skip_existing(stmt).with_context(context)?;
+                execute(&mut session_client, stmt)
+                    .await
+                    .with_context(context)?;
+            }
+        }
+        Ok(())
+    }
+    .await;
+    session_client.terminate().await;
+    res
+}
+
+/// A problem with a statement in the init SQL, found by [`check`].
+#[derive(Debug, Serialize)]
+pub struct CheckFailure {
+    /// The file that contains the statement.
+    pub file: PathBuf,
+    /// The position of the statement in the file, starting from one, or
+    /// `None` if the file could not be parsed.
+    pub statement: Option<usize>,
+    /// The text of the statement, if the file could be parsed.
+    pub sql: Option<String>,
+    /// A description of the problem.
+    pub error: String,
+}
+
+/// Checks whether the SQL statements in the file or directory at `path` can
+/// run against the catalog described by `catalog_config`, without modifying
+/// the catalog or starting any dataflows.
+///
+/// Each statement is purified and planned as [`run`] would run it. Objects
+/// created by a statement are visible to the statements that follow. If
+/// `check_connectors` is false, purification does not connect to the external
+/// systems that sources use, and so skips the checks that need a connection.
+///
+/// Returns a failure for every statement that would fail, rather than stopping
+/// at the first.
+pub async fn check(
+    catalog_config: &coord::catalog::Config<'_>,
+    path: &Path,
+    check_connectors: bool,
+) -> Result<Vec<CheckFailure>, anyhow::Error> {
+    let mut failures = vec![];
+    let mut stmts = vec![];
+    let mut positions = vec![];
+    for file in find_files(path)? {
+        let sql = fs::read_to_string(&file)
+            .with_context(|| format!("reading init SQL file {}", file.display()))?;
+        let parsed = match parse_statements(&sql) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                failures.push(CheckFailure {
+                    file,
+                    statement: None,
+                    sql: None,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+        for (i, stmt) in parsed.into_iter().enumerate() {
+            let sql = stmt.to_string();
+            let purified = match skip_existing(stmt) {
+                Ok(stmt) if check_connectors => sql::pure::purify(stmt).await,
+                Ok(stmt) => sql::pure::purify_offline(stmt).await,
+                Err(e) => Err(e),
+            };
+            match purified {
+                Ok(stmt) => {
+                    stmts.push(stmt);
+                    positions.push((file.clone(), i + 1, sql));
+                }
+                Err(e) => failures.push(CheckFailure {
+                    file: file.clone(),
+                    statement: Some(i + 1),
+                    sql: Some(sql),
+                    error: format!("{:#}", e),
+                }),
+            }
+        }
+    }
+    for (i, e) in coord::catalog::Catalog::check_statements(catalog_config, stmts)? {
+        let (file, statement, sql) = positions[i].clone();
+        failures.push(CheckFailure {
+            file,
+            statement: Some(statement),
+            sql: Some(sql),
+            error: format!("{:#}", e),
+        });
+    }
+    failures.sort_by(|a, b| (&a.file, a.statement).cmp(&(&b.file, b.statement)));
+    Ok(failures)
+}
+
+/// Returns the files named by the `--init-sql` path `path`.
+fn find_files(path: &Path) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let metadata =
+        fs::metadata(path).with_context(|| format!("reading init SQL path {}", path.display()))?;
+    if !metadata.is_dir() {
+        return Ok(vec![path.into()]);
+    }
+    let mut files = vec![];
+    for entry in fs::read_dir(path)
+        .with_context(|| format!("reading init SQL directory {}", path.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension().map_or(false, |ext| ext == "sql") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Rewrites `stmt` to skip the creation of objects that already exist.
+///
+/// Returns an error if `stmt` creates an object but has no way to skip it if
+/// it already exists, as the statement would fail on the next startup.
+fn skip_existing(mut stmt: Statement<Raw>) -> Result<Statement<Raw>, anyhow::Error> {
+    match &mut stmt {
+        Statement::CreateDatabase(stmt) => stmt.if_not_exists = true,
+        Statement::CreateSchema(stmt) => stmt.if_not_exists = true,
+        Statement::CreateSource(stmt) => stmt.if_not_exists = true,
+        Statement::CreateSink(stmt) => stmt.if_not_exists = true,
+        Statement::CreateTask(stmt) => stmt.if_not_exists = true,
+        Statement::CreateTable(stmt) => stmt.if_not_exists = true,
+        Statement::CreateIndex(stmt) => stmt.if_not_exists = true,
+        Statement::CreateView(stmt) if stmt.if_exists == IfExistsBehavior::Error => {
+            stmt.if_exists = IfExistsBehavior::Skip
+        }
+        Statement::CreateType(_) => bail!(
+            "CREATE TYPE is not supported in init SQL, because it would fail on the next startup"
+        ),
+        Statement::CreateRole(_) => bail!(
+            "CREATE ROLE is not supported in init SQL, because it would fail on the next startup"
+        ),
+        _ => (),
+    }
+    Ok(stmt)
+}
+
+/// Executes `stmt` as if it were sent in a simple query message of its own.
+async fn execute(
+    session_client: &mut SessionClient,
+    stmt: Statement<Raw>,
+) -> Result<(), anyhow::Error> {
+    const EMPTY_PORTAL: &str = "";
+    session_client.session().start_transaction_implicit(1);
+    session_client
+        .declare(EMPTY_PORTAL.into(), stmt, vec![], None)
+        .await?;
+    let res = session_client.execute(EMPTY_PORTAL.into()).await;
+    session_client.session().remove_portal(EMPTY_PORTAL);
+    match res? {
+        ExecuteResponse::SendingRows(mut batches) => {
+            while let Some(batch) = batches.next().await {
+                match batch {
+                    PeekResponse::Rows(_) => (),
+                    PeekResponse::Error(e) => bail!(e),
+                    PeekResponse::Canceled => bail!("query canceled"),
+                }
+            }
+        }
+        ExecuteResponse::Tailing { .. } => bail!("TAIL is not supported in init SQL"),
+        _ => (),
+    }
+    let implicit = matches!(
+        session_client.session().transaction(),
+        TransactionStatus::Started(_) | TransactionStatus::InTransactionImplicit(_)
+    );
+    if implicit {
+        session_client
+            .end_transaction(EndTransactionAction::Commit)
+            .await?;
+    }
+    Ok(())
+}
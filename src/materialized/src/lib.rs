@@ -20,8 +20,10 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use anyhow::bail;
 use compile_time_run::run_command_str;
 use futures::StreamExt;
+use log::error;
 use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod, SslVerifyMode};
 use tokio::net::TcpListener;
 use tokio::runtime::Runtime;
@@ -29,11 +31,15 @@ use tokio::sync::oneshot;
 use tokio_stream::wrappers::TcpListenerStream;
 
 use build_info::BuildInfo;
-use coord::{CacheConfig, LoggingConfig};
+use coord::{CacheConfig, LoggingConfig, QuotaConfig};
 
 use crate::mux::Mux;
 
+pub mod control;
+mod flight;
+mod grpc;
 mod http;
+pub mod init_sql;
 mod mux;
 mod server_metrics;
 mod version_check;
@@ -97,14 +103,52 @@ pub struct Config {
     /// able to use the arrangement in a query that has other constraints on the
     /// timestamps used (e.g. when joined with other arrangements).
     pub logical_compaction_window: Option<Duration>,
+    /// Whether to automatically widen the logical compaction window of indexes
+    /// that are queried at historical timestamps.
+    pub auto_tune_logical_compaction_window: bool,
     /// The interval at which sources should be timestamped.
     pub timestamp_frequency: Duration,
+    /// Resource limits to enforce on users and schemas.
+    pub quotas: QuotaConfig,
+    /// The duration above which executed statements are logged, if any.
+    pub slow_query_threshold: Option<Duration>,
+    /// The maximum size of the statement history log, in bytes. Zero disables
+    /// the statement history.
+    pub statement_history_size: u64,
+    /// The duration after which a dataflow whose frontier does not advance is
+    /// reported as stalled, if stall detection is enabled.
+    pub stalled_dataflow_threshold: Option<Duration>,
+    /// How long a transient dataflow shared by the queries of the same
+    /// expression may go unused before it is torn down, if transient
+    /// dataflows are shared.
+    pub shared_dataflow_idle_timeout: Option<Duration>,
+    /// The number of queries of the same expression after which its transient
+    /// dataflow is shared.
+    pub shared_dataflow_min_peeks: usize,
+    /// The freshness beyond which an index is reported as breaching its SLO,
+    /// if any.
+    pub freshness_slo: Option<Duration>,
+    /// How long after startup to report readiness even if some sources have
+    /// not yet connected, if ever.
+    pub rehydration_budget: Option<Duration>,
+    /// How long a lazy materialized view may go unqueried before its dataflow
+    /// is torn down.
+    pub lazy_view_idle_timeout: Duration,
+    /// Controls the process's log filter, if it can be changed at runtime via
+    /// `ALTER SYSTEM SET log_filter`.
+    pub log_filter: Option<Arc<dyn coord::LogFilterControl>>,
 
     // === Connection options. ===
     /// The IP address and port to listen on.
     pub listen_addr: SocketAddr,
     /// TLS encryption configuration.
     pub tls: Option<TlsConfig>,
+    /// The IP address and port on which to serve Arrow Flight requests, if
+    /// any.
+    pub flight_listen_addr: Option<SocketAddr>,
+    /// The IP address and port on which to serve the gRPC control API, if
+    /// any.
+    pub control_listen_addr: Option<SocketAddr>,
 
     // === Storage options. ===
     /// The directory in which `materialized` should store its own metadata.
@@ -113,6 +157,9 @@ pub struct Config {
     /// An optional symbiosis endpoint. See the
     /// [`symbiosis`](../symbiosis/index.html) crate for details.
     pub symbiosis_url: Option<String>,
+    /// A file, or a directory of `.sql` files, containing SQL statements to
+    /// run after the catalog is loaded and before connections are served.
+    pub init_sql: Option<PathBuf>,
     /// Whether to permit usage of experimental features.
     pub experimental_mode: bool,
     /// An optional telemetry endpoint. Use None to disable telemetry.
@@ -162,9 +209,28 @@ pub async fn serve(
     let start_time = Instant::now();
     let workers = config.workers;
 
+    // The control API can drop any object in the catalog, so it must only be
+    // reachable by clients whose identity has been verified, or by clients on
+    // the same machine.
+    if let Some(addr) = config.control_listen_addr {
+        let verifies_user = matches!(
+            config.tls,
+            Some(TlsConfig {
+                mode: TlsMode::VerifyFull { .. },
+                ..
+            })
+        );
+        if !addr.ip().is_loopback() && !verifies_user {
+            bail!(
+                "the control API must listen on a loopback address unless TLS is \
+                 configured with --tls-mode=verify-full"
+            );
+        }
+    }
+
     // Validate TLS configuration, if present.
-    let (pgwire_tls, http_tls) = match &config.tls {
-        None => (None, None),
+    let (pgwire_tls, http_tls, grpc_tls) = match &config.tls {
+        None => (None, None, None),
         Some(tls_config) => {
             let context = {
                 // Mozilla publishes three presets: old, intermediate, and modern. They
@@ -190,13 +256,20 @@ pub async fn serve(
                 },
             };
             let http_tls = http::TlsConfig {
-                context,
+                context: context.clone(),
                 mode: match tls_config.mode {
                     TlsMode::Require | TlsMode::VerifyCa { .. } => http::TlsMode::Require,
                     TlsMode::VerifyFull { .. } => http::TlsMode::AssumeUser,
                 },
             };
-            (Some(pgwire_tls), Some(http_tls))
+            let grpc_tls = grpc::TlsConfig {
+                context,
+                mode: match tls_config.mode {
+                    TlsMode::Require | TlsMode::VerifyCa { .. } => grpc::TlsMode::Require,
+                    TlsMode::VerifyFull { .. } => grpc::TlsMode::VerifyUser,
+                },
+            };
+            (Some(pgwire_tls), Some(http_tls), Some(grpc_tls))
         }
     };
 
@@ -208,6 +281,22 @@ pub async fn serve(
     // Initialize network listener.
     let listener = TcpListener::bind(&config.listen_addr).await?;
     let local_addr = listener.local_addr()?;
+    let flight_listener = match config.flight_listen_addr {
+        Some(addr) => Some(TcpListener::bind(addr).await?),
+        None => None,
+    };
+    let flight_local_addr = match &flight_listener {
+        Some(listener) => Some(listener.local_addr()?),
+        None => None,
+    };
+    let control_listener = match config.control_listen_addr {
+        Some(addr) => Some(TcpListener::bind(addr).await?),
+        None => None,
+    };
+    let control_local_addr = match &control_listener {
+        Some(listener) => Some(listener.local_addr()?),
+        None => None,
+    };
 
     // Initialize coordinator.
     let (coord_handle, coord_client) = coord::serve(
@@ -220,13 +309,32 @@ pub async fn serve(
             timestamp_frequency: config.timestamp_frequency,
             cache: config.cache,
             logical_compaction_window: config.logical_compaction_window,
+            auto_tune_logical_compaction_window: config.auto_tune_logical_compaction_window,
             experimental_mode: config.experimental_mode,
+            quotas: config.quotas,
+            slow_query_threshold: config.slow_query_threshold,
+            statement_history_size: config.statement_history_size,
+            stalled_dataflow_threshold: config.stalled_dataflow_threshold,
+            shared_dataflow_idle_timeout: config.shared_dataflow_idle_timeout,
+            shared_dataflow_min_peeks: config.shared_dataflow_min_peeks,
+            freshness_slo: config.freshness_slo,
+            rehydration_budget: config.rehydration_budget,
+            lazy_view_idle_timeout: config.lazy_view_idle_timeout,
+            log_filter: config.log_filter,
             build_info: &BUILD_INFO,
+            now: ore::now::system_time_fn(),
+            manual_ticks: false,
         },
         runtime,
     )
     .await?;
 
+    // Run the init SQL before serving any connections, so that clients never
+    // observe a partially initialized schema.
+    if let Some(path) = &config.init_sql {
+        init_sql::run(&coord_client, path).await?;
+    }
+
     // Launch task to serve connections.
     //
     // The lifetime of this task is controlled by a trigger that activates on
@@ -235,6 +343,47 @@ pub async fn serve(
     // should be rejected. Once all existing user connections have gracefully
     // terminated, this task exits.
     let (drain_trigger, drain_tripwire) = oneshot::channel();
+    let (flight_drain_trigger, flight_drain_tripwire) = oneshot::channel::<()>();
+    if let Some(listener) = flight_listener {
+        let service = flight::Server::new(flight::Config {
+            coord_client: coord_client.clone(),
+            tls_mode: grpc_tls.as_ref().map(|tls| tls.mode),
+        })
+        .into_service();
+        let incoming = grpc::incoming(listener, grpc_tls.clone());
+        tokio::spawn(async move {
+            let res = tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(incoming, async {
+                    let _ = flight_drain_tripwire.await;
+                })
+                .await;
+            if let Err(e) = res {
+                error!("Arrow Flight server failed: {}", e);
+            }
+        });
+    }
+    let (control_drain_trigger, control_drain_tripwire) = oneshot::channel::<()>();
+    if let Some(listener) = control_listener {
+        let service = control::Server::new(control::Config {
+            coord_client: coord_client.clone(),
+            start_time,
+            tls_mode: grpc_tls.as_ref().map(|tls| tls.mode),
+        })
+        .into_service();
+        let incoming = grpc::incoming(listener, grpc_tls);
+        tokio::spawn(async move {
+            let res = tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(incoming, async {
+                    let _ = control_drain_tripwire.await;
+                })
+                .await;
+            if let Err(e) = res {
+                error!("control API server failed: {}", e);
+            }
+        });
+    }
     tokio::spawn(async move {
         // TODO(benesch): replace with `listener.incoming()` if that is
         // restored when the `Stream` trait stabilizes.
@@ -266,7 +415,11 @@ pub async fn serve(
 
     Ok(Server {
         local_addr,
+        flight_local_addr,
+        control_local_addr,
         _drain_trigger: drain_trigger,
+        _flight_drain_trigger: flight_drain_trigger,
+        _control_drain_trigger: control_drain_trigger,
         _coord_handle: coord_handle,
     })
 }
@@ -274,8 +427,12 @@ pub async fn serve(
 /// A running `materialized` server.
 pub struct Server {
     local_addr: SocketAddr,
+    flight_local_addr: Option<SocketAddr>,
+    control_local_addr: Option<SocketAddr>,
     // Drop order matters for these fields.
     _drain_trigger: oneshot::Sender<()>,
+    _flight_drain_trigger: oneshot::Sender<()>,
+    _control_drain_trigger: oneshot::Sender<()>,
     _coord_handle: coord::Handle,
 }
 
@@ -283,4 +440,16 @@ impl Server {
     pub fn local_addr(&self) -> SocketAddr {
         self.local_addr
     }
+
+    /// Returns the address on which Arrow Flight requests are served, if the
+    /// Arrow Flight endpoint is enabled.
+    pub fn flight_local_addr(&self) -> Option<SocketAddr> {
+        self.flight_local_addr
+    }
+
+    /// Returns the address on which the gRPC control API is served, if the
+    /// control API is enabled.
+    pub fn control_local_addr(&self) -> Option<SocketAddr> {
+        self.control_local_addr
+    }
 }
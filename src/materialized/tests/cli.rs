@@ -53,3 +53,47 @@ fn test_threads() {
     // has started correctly, since it runs forever. The success code path is
     // well exercised by integration tests, so it's not a big deal.
 }
+
+#[test]
+fn test_check() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = tempfile::tempdir()?;
+    let data_directory = dir.path().join("mzdata");
+    let init_sql = dir.path().join("init.sql");
+
+    std::fs::write(
+        &init_sql,
+        "CREATE TABLE t (a int); CREATE VIEW v AS SELECT a FROM t;",
+    )?;
+    cmd()
+        .arg("--check")
+        .arg("--init-sql")
+        .arg(&init_sql)
+        .arg("-D")
+        .arg(&data_directory)
+        .assert()
+        .success()
+        .stdout("configuration and init SQL are valid\n");
+
+    std::fs::write(
+        &init_sql,
+        "CREATE TABLE t (a int); CREATE VIEW v AS SELECT b FROM t; CREATE VIEW w AS SELECT * FROM v;",
+    )?;
+    cmd()
+        .arg("--check")
+        .arg("--init-sql")
+        .arg(&init_sql)
+        .arg("-D")
+        .arg(&data_directory)
+        .assert()
+        .failure()
+        .stdout(
+            predicate::str::contains(r#""statement":2"#)
+                .and(predicate::str::contains(r#""statement":3"#))
+                .and(predicate::str::contains(r#""statement":1"#).not()),
+        )
+        .stderr("materialized: 2 init SQL statements failed to check\n");
+
+    // The check must not create the data directory.
+    assert!(!data_directory.exists());
+    Ok(())
+}
@@ -12,8 +12,11 @@
 use std::convert::TryInto;
 use std::error::Error;
 use std::io::{Read, Write};
+use std::mem;
 use std::net::TcpStream;
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::Duration;
 
 use bytes::BytesMut;
@@ -26,6 +29,7 @@ use postgres::SimpleQueryMessage;
 use postgres_array::{Array, Dimension};
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc;
+use uuid::Uuid;
 
 use ore::collections::CollectionExt;
 use pgrepr::{Numeric, Record};
@@ -145,6 +149,61 @@ fn test_read_many_rows() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_large_result() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let server = util::start_server(util::Config::default())?;
+    let mut client = server.connect(postgres::NoTls)?;
+    // Large enough that the rows span many write chunks.
+    let query = "SELECT
+            n,
+            n::int8 * 1000000000,
+            n::float8 / 4,
+            n % 2 = 0,
+            'row ' || n,
+            CASE WHEN n % 3 = 0 THEN NULL ELSE n::text::bytea END
+        FROM generate_series(1, 100000) AS n";
+
+    // The extended query protocol returns the results in the binary format.
+    let rows = client.query(query, &[])?;
+    assert_eq!(rows.len(), 100_000);
+    for (i, row) in rows.iter().enumerate() {
+        let n: i32 = (i + 1).try_into()?;
+        assert_eq!(row.get::<_, i32>(0), n);
+        assert_eq!(row.get::<_, i64>(1), i64::from(n) * 1_000_000_000);
+        assert_eq!(row.get::<_, f64>(2), f64::from(n) / 4.0);
+        assert_eq!(row.get::<_, bool>(3), n % 2 == 0);
+        assert_eq!(row.get::<_, String>(4), format!("row {}", n));
+        let bytes = row.get::<_, Option<Vec<u8>>>(5);
+        if n % 3 == 0 {
+            assert_eq!(bytes, None);
+        } else {
+            assert_eq!(bytes, Some(n.to_string().into_bytes()));
+        }
+    }
+
+    // The simple query protocol returns the results in the text format.
+    let messages = client.simple_query(query)?;
+    let rows: Vec<_> = messages
+        .into_iter()
+        .filter_map(|m| match m {
+            SimpleQueryMessage::Row(row) => Some(row),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(rows.len(), 100_000);
+    let last = &rows[rows.len() - 1];
+    assert_eq!(last.get(0), Some("100000"));
+    assert_eq!(last.get(1), Some("100000000000000"));
+    assert_eq!(last.get(2), Some("25000"));
+    assert_eq!(last.get(3), Some("t"));
+    assert_eq!(last.get(4), Some("row 100000"));
+    assert_eq!(last.get(5), Some("\\x313030303030"));
+
+    Ok(())
+}
+
 #[test]
 fn test_conn_startup() -> Result<(), Box<dyn Error>> {
     ore::test::init_logging();
@@ -306,6 +365,103 @@ fn test_conn_user() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_conn_limits() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default().quotas(coord::QuotaConfig {
+        max_connections: Some(3),
+        max_connections_per_role: Some(2),
+        ..Default::default()
+    });
+    let server = util::start_server(config)?;
+    let mut client = server.connect(postgres::NoTls)?;
+    client.batch_execute("CREATE ROLE rj LOGIN SUPERUSER")?;
+    let _client = server.connect(postgres::NoTls)?;
+
+    // The "materialize" role has reached its limit.
+    let err = server.connect(postgres::NoTls).unwrap_db_error();
+    assert_eq!(err.severity(), "FATAL");
+    assert_eq!(*err.code(), SqlState::TOO_MANY_CONNECTIONS);
+    assert_eq!(
+        err.message(),
+        "too many connections for role \"materialize\" (limit 2)"
+    );
+
+    // Other roles can still connect, until the server reaches its limit.
+    let _client = server.pg_config().user("rj").connect(postgres::NoTls)?;
+    let err = server
+        .pg_config()
+        .user("rj")
+        .connect(postgres::NoTls)
+        .unwrap_db_error();
+    assert_eq!(err.severity(), "FATAL");
+    assert_eq!(*err.code(), SqlState::TOO_MANY_CONNECTIONS);
+    assert_eq!(err.message(), "sorry, too many clients already (limit 3)");
+
+    Ok(())
+}
+
+#[test]
+fn test_idle_timeouts() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let server = util::start_server(util::Config::default())?;
+
+    // The idle session timeout does not apply inside a transaction.
+    let mut client = server.connect(postgres::NoTls)?;
+    client.batch_execute("SET idle_session_timeout = 100")?;
+    client.batch_execute("BEGIN")?;
+    thread::sleep(Duration::from_millis(500));
+    client.batch_execute("COMMIT")?;
+    thread::sleep(Duration::from_millis(500));
+    assert!(client.batch_execute("SELECT 1").is_err());
+
+    // And vice versa.
+    let mut client = server.connect(postgres::NoTls)?;
+    client.batch_execute("SET idle_in_transaction_session_timeout = 100")?;
+    thread::sleep(Duration::from_millis(500));
+    client.batch_execute("BEGIN")?;
+    thread::sleep(Duration::from_millis(500));
+    assert!(client.batch_execute("SELECT 1").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_notices() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let server = util::start_server(util::Config::default())?;
+    let notices = Arc::new(Mutex::new(vec![]));
+    let mut client = server
+        .pg_config()
+        .notice_callback({
+            let notices = Arc::clone(&notices);
+            move |notice| notices.lock().unwrap().push(notice)
+        })
+        .connect(postgres::NoTls)?;
+
+    client.batch_execute("CREATE TABLE t (a int, b int)")?;
+    client.batch_execute("CREATE INDEX t_a_idx ON t (a)")?;
+
+    // A filter that an index can serve does not produce a notice.
+    client.batch_execute("SELECT * FROM t WHERE a = 1")?;
+    assert!(notices.lock().unwrap().is_empty());
+
+    // But a filter that no index can serve does.
+    client.batch_execute("SELECT * FROM t WHERE b = 1")?;
+    let notices = mem::take(&mut *notices.lock().unwrap());
+    assert_eq!(notices.len(), 1);
+    assert_eq!(notices[0].severity(), "NOTICE");
+    assert_eq!(
+        notices[0].message(),
+        "index unused, full scan of \"materialize.public.t\" performed"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn test_simple_query_no_hang() -> Result<(), Box<dyn Error>> {
     ore::test::init_logging();
@@ -424,6 +580,33 @@ fn test_record_types() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+#[test]
+fn test_uuid() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let server = util::start_server(util::Config::default())?;
+    let mut client = server.connect(postgres::NoTls)?;
+
+    // UUIDs round trip through binary parameters and results.
+    let id = Uuid::parse_str("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11")?;
+    let row = client.query_one("SELECT $1::uuid, $1::uuid::text", &[&id])?;
+    assert_eq!(row.get::<_, Uuid>(0), id);
+    assert_eq!(row.get::<_, String>(1), id.to_string());
+
+    // UUIDs can be stored in tables and looked up by equality.
+    client.batch_execute("CREATE TABLE events (id uuid, payload text)")?;
+    client.execute("INSERT INTO events VALUES ($1, 'a')", &[&id])?;
+    client.execute("INSERT INTO events VALUES (gen_random_uuid(), 'b')", &[])?;
+    let row = client.query_one("SELECT payload FROM events WHERE id = $1", &[&id])?;
+    assert_eq!(row.get::<_, String>(0), "a");
+
+    // Generated UUIDs are version 4.
+    let row = client.query_one("SELECT id FROM events WHERE payload = 'b'", &[])?;
+    assert_eq!(row.get::<_, Uuid>(0).get_version_num(), 4);
+
+    Ok(())
+}
+
 #[test]
 fn test_pgtest() -> Result<(), Box<dyn Error>> {
     ore::test::init_logging();
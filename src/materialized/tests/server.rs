@@ -10,10 +10,29 @@
 //! Integration tests for Materialize server.
 
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error::Error;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
+use arrow::array::{Float64Array, Int32Array, StringArray};
+use arrow::datatypes::Schema;
+use arrow_flight::flight_service_client::FlightServiceClient;
+use arrow_flight::utils::flight_data_to_arrow_batch;
+use arrow_flight::{FlightDescriptor, HandshakeRequest, Ticket};
+use materialized::control::proto::control_client::ControlClient;
+use materialized::control::proto::{
+    DropObjectRequest, ExecuteRequest, GetHealthRequest, ListObjectsRequest, ObjectName, ObjectType,
+};
+use prost::Message as _;
 use reqwest::{blocking::Client, StatusCode, Url};
+use serde_json::json;
 use tempfile::NamedTempFile;
+use tungstenite::Message;
+
+use util::PostgresErrorExt;
 
 pub mod util;
 
@@ -175,6 +194,284 @@ fn test_experimental_mode_on_init_or_never() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+// Ensures that init SQL runs at startup, that its CREATE statements are
+// idempotent across restarts, and that failing statements prevent startup.
+#[test]
+fn test_init_sql() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let data_dir = tempfile::tempdir()?;
+    let init_dir = tempfile::tempdir()?;
+    std::fs::write(
+        init_dir.path().join("01-tables.sql"),
+        "CREATE TABLE t (a int); CREATE VIEW v AS SELECT a + 1 AS b FROM t;",
+    )?;
+    std::fs::write(
+        init_dir.path().join("02-indexes.sql"),
+        "CREATE INDEX v_idx ON v (b);",
+    )?;
+    std::fs::write(init_dir.path().join("README"), "not SQL")?;
+    let config = util::Config::default()
+        .data_directory(data_dir.path())
+        .init_sql(init_dir.path());
+
+    for _ in 0..2 {
+        let server = util::start_server(config.clone())?;
+        let mut client = server.connect(postgres::NoTls)?;
+        let rows = client.query("SELECT name FROM mz_indexes WHERE name = 'v_idx'", &[])?;
+        assert_eq!(rows.len(), 1);
+    }
+
+    std::fs::write(init_dir.path().join("03-bad.sql"), "SELECT * FROM missing;")?;
+    match util::start_server(config.clone()) {
+        Ok(_) => panic!("unexpected success"),
+        Err(e) => {
+            if !e.to_string().contains("03-bad.sql") {
+                return Err(e);
+            }
+        }
+    }
+
+    // Statements that cannot skip existing objects are rejected, even though
+    // they would succeed on this startup.
+    std::fs::write(
+        init_dir.path().join("03-bad.sql"),
+        "CREATE TYPE int4_list AS LIST (element_type = int4);",
+    )?;
+    match util::start_server(config) {
+        Ok(_) => panic!("unexpected success"),
+        Err(e) => {
+            if !e.to_string().contains("03-bad.sql") {
+                return Err(e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Ensures that feature flags enable experimental features outside of
+// experimental mode, and that objects created while a flag was enabled
+// survive a reboot with the flag disabled.
+#[test]
+fn test_feature_flags() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let data_dir = tempfile::tempdir()?;
+    let config = util::Config::default().data_directory(data_dir.path());
+
+    {
+        let server = util::start_server(config.clone())?;
+        let mut client = server.connect(postgres::NoTls)?;
+
+        let row = client.query_one("SHOW enable_array_constructor", &[])?;
+        assert_eq!(row.get::<_, String>(0), "off");
+        let err = client
+            .batch_execute("CREATE VIEW v AS SELECT ARRAY[1, 2] AS a")
+            .unwrap_db_error();
+        assert_eq!(
+            err.message(),
+            "ARRAY is an experimental feature; enable it with SET enable_array_constructor = on"
+        );
+
+        client.batch_execute("SET enable_array_constructor = on")?;
+        client.batch_execute("CREATE VIEW v AS SELECT ARRAY[1, 2] AS a")?;
+
+        // Flags are independent of one another.
+        let err = client
+            .query_one("SELECT list_ndims(LIST[[1]])", &[])
+            .unwrap_db_error();
+        assert_eq!(
+            err.message(),
+            "list_ndims is an experimental feature; enable it with SET enable_multidimensional_lists = on"
+        );
+
+        // Flags can be enabled for every new session.
+        client.batch_execute("ALTER SYSTEM SET enable_multidimensional_lists = on")?;
+        let mut client = server.connect(postgres::NoTls)?;
+        let row = client.query_one("SELECT list_ndims(LIST[[1]])", &[])?;
+        assert_eq!(row.get::<_, i32>(0), 2);
+    }
+
+    {
+        let server = util::start_server(config)?;
+        let mut client = server.connect(postgres::NoTls)?;
+        let row = client.query_one("SHOW enable_array_constructor", &[])?;
+        assert_eq!(row.get::<_, String>(0), "off");
+        let row = client.query_one("SELECT a::text FROM v", &[])?;
+        assert_eq!(row.get::<_, String>(0), "{1,2}");
+    }
+
+    Ok(())
+}
+
+// Ensures that the per-schema dataflow quota is enforced.
+// Ensures that configuration parameter defaults set via `ALTER SYSTEM` and
+// `ALTER ROLE` apply to new sessions and survive a reboot.
+#[test]
+fn test_parameter_defaults() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let data_dir = tempfile::tempdir()?;
+    let config = util::Config::default().data_directory(data_dir.path());
+
+    fn show(server: &util::Server, name: &str) -> Result<String, Box<dyn Error>> {
+        let mut client = server.connect(postgres::NoTls)?;
+        Ok(client
+            .query_one(format!("SHOW {}", name).as_str(), &[])?
+            .get(0))
+    }
+
+    {
+        let server = util::start_server(config.clone())?;
+        let mut client = server.connect(postgres::NoTls)?;
+        assert_eq!(show(&server, "extra_float_digits")?, "3");
+
+        client.batch_execute("ALTER SYSTEM SET extra_float_digits = 1")?;
+        client.batch_execute("ALTER SYSTEM SET sql_safe_updates = on")?;
+        assert_eq!(show(&server, "extra_float_digits")?, "1");
+        assert_eq!(show(&server, "sql_safe_updates")?, "on");
+
+        client.batch_execute("ALTER ROLE materialize SET extra_float_digits = 2")?;
+        assert_eq!(show(&server, "extra_float_digits")?, "2");
+
+        // Existing sessions are unaffected by new defaults.
+        let row = client.query_one("SHOW extra_float_digits", &[])?;
+        assert_eq!(row.get::<_, String>(0), "3");
+
+        // RESET restores the session to the role and system defaults.
+        let mut client = server.connect(postgres::NoTls)?;
+        client.batch_execute("SET extra_float_digits = 0")?;
+        client.batch_execute("RESET extra_float_digits")?;
+        let row = client.query_one("SHOW extra_float_digits", &[])?;
+        assert_eq!(row.get::<_, String>(0), "2");
+        client.batch_execute("SET sql_safe_updates = off")?;
+        client.batch_execute("SET sql_safe_updates TO DEFAULT")?;
+        let row = client.query_one("SHOW sql_safe_updates", &[])?;
+        assert_eq!(row.get::<_, String>(0), "on");
+
+        let err = client
+            .batch_execute("ALTER SYSTEM SET extra_float_digits = 'bogus'")
+            .unwrap_db_error();
+        assert_eq!(
+            err.message(),
+            "parameter \"extra_float_digits\" requires a \"integer\" value"
+        );
+        let err = client
+            .batch_execute("ALTER SYSTEM SET bogus = 1")
+            .unwrap_db_error();
+        assert_eq!(
+            err.message(),
+            "unrecognized configuration parameter \"bogus\""
+        );
+        let err = client
+            .batch_execute("ALTER ROLE bogus SET extra_float_digits = 1")
+            .unwrap_db_error();
+        assert_eq!(err.message(), "unknown role 'bogus'");
+    }
+
+    {
+        let server = util::start_server(config.clone())?;
+        let mut client = server.connect(postgres::NoTls)?;
+        assert_eq!(show(&server, "extra_float_digits")?, "2");
+        assert_eq!(show(&server, "sql_safe_updates")?, "on");
+
+        client.batch_execute("ALTER ROLE materialize RESET extra_float_digits")?;
+        assert_eq!(show(&server, "extra_float_digits")?, "1");
+
+        client.batch_execute("ALTER SYSTEM RESET ALL")?;
+        assert_eq!(show(&server, "extra_float_digits")?, "3");
+        assert_eq!(show(&server, "sql_safe_updates")?, "off");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_dataflow_quota() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default().quotas(coord::QuotaConfig {
+        max_dataflows_per_schema: Some(2),
+        ..Default::default()
+    });
+    let server = util::start_server(config)?;
+    let mut client = server.connect(postgres::NoTls)?;
+
+    client.batch_execute("CREATE TABLE t (a int)")?;
+    client.batch_execute("CREATE MATERIALIZED VIEW v AS SELECT * FROM t")?;
+    let err = client
+        .batch_execute("CREATE INDEX i ON t (a)")
+        .unwrap_db_error();
+    assert_eq!(
+        err.message(),
+        "schema \"materialize.public\" has reached its limit of 2 dataflows"
+    );
+
+    // Unmaterialized views do not count against the quota.
+    client.batch_execute("CREATE VIEW w AS SELECT * FROM t")?;
+
+    // Other schemas have their own quota.
+    client.batch_execute("CREATE SCHEMA s")?;
+    client.batch_execute("CREATE MATERIALIZED VIEW s.v AS SELECT * FROM t")?;
+
+    // Dropping a dataflow frees up room in the schema.
+    client.batch_execute("DROP VIEW v")?;
+    client.batch_execute("CREATE INDEX i ON t (a)")?;
+
+    Ok(())
+}
+
+#[test]
+fn test_arrangement_quota() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default().quotas(coord::QuotaConfig {
+        max_arrangement_bytes_per_schema: Some(10_000),
+        ..Default::default()
+    });
+    let server = util::start_server(config)?;
+    let mut client = server.connect(postgres::NoTls)?;
+
+    client.batch_execute("CREATE TABLE t (a int)")?;
+
+    // An empty index is within the limit.
+    client.batch_execute("CREATE MATERIALIZED VIEW v AS SELECT * FROM t")?;
+    client.batch_execute("DROP VIEW v")?;
+
+    // Once the workers report that the table's index holds more than the
+    // limit, new dataflows in the schema are rejected.
+    client.batch_execute("INSERT INTO t SELECT * FROM generate_series(1, 1000)")?;
+    let deadline = Instant::now() + Duration::from_secs(30);
+    let err = loop {
+        match client.batch_execute("CREATE MATERIALIZED VIEW v AS SELECT * FROM t") {
+            Ok(()) => client.batch_execute("DROP VIEW v")?,
+            Err(err) => break err,
+        }
+        if Instant::now() > deadline {
+            return Err("arrangement quota was never enforced".into());
+        }
+        thread::sleep(Duration::from_millis(100));
+    };
+    let message = err.as_db_error().map(|e| e.message()).unwrap_or_default();
+    assert!(
+        message.starts_with("the indexes in schema \"materialize.public\" hold ")
+            && message.ends_with(" bytes, exceeding its limit of 10000"),
+        "unexpected error: {}",
+        message
+    );
+
+    // Other schemas are unaffected.
+    client.batch_execute("CREATE SCHEMA s")?;
+    client.batch_execute("CREATE TABLE s.t (a int)")?;
+
+    // Dropping the index frees up room in the schema.
+    client.batch_execute("DROP TABLE t")?;
+    client.batch_execute("CREATE TABLE t (a int)")?;
+
+    Ok(())
+}
+
 // Test the /sql POST endpoint of the HTTP server.
 #[test]
 fn test_http_sql() -> Result<(), Box<dyn Error>> {
@@ -218,3 +515,520 @@ fn test_http_sql() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+// Test the /api/livez and /api/readyz endpoints of the HTTP server.
+#[test]
+fn test_health_probes() -> Result<(), Box<dyn Error>> {
+    let server = util::start_server(util::Config::default())?;
+    let base = format!("http://{}", server.inner.local_addr());
+
+    let res = Client::new().get(&format!("{}/api/livez", base)).send()?;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // A server without sources is ready as soon as it starts.
+    let res = Client::new().get(&format!("{}/api/readyz", base)).send()?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.text()?, r#"{"ready":true,"rehydrating_sources":[]}"#);
+    drop(server);
+
+    // A file source has no offsets for the timestamper to track, so after a
+    // restart it connects once its dataflow binds its first timestamp.
+    let data_dir = tempfile::tempdir()?;
+    let config = util::Config::default().data_directory(data_dir.path());
+    let source_file = NamedTempFile::new()?;
+    {
+        let server = util::start_server(config.clone())?;
+        let mut client = server.connect(postgres::NoTls)?;
+        client.batch_execute(&format!(
+            "CREATE MATERIALIZED SOURCE src FROM FILE '{}' FORMAT BYTES",
+            source_file.path().display()
+        ))?;
+    }
+    let server = util::start_server(config)?;
+    let url = format!("http://{}/api/readyz", server.inner.local_addr());
+    let deadline = Instant::now() + Duration::from_secs(30);
+    loop {
+        let res = Client::new().get(&url).send()?;
+        if res.status() == StatusCode::OK {
+            assert_eq!(res.text()?, r#"{"ready":true,"rehydrating_sources":[]}"#);
+            break;
+        }
+        if Instant::now() > deadline {
+            return Err(format!("file source never connected: {}", res.text()?).into());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    Ok(())
+}
+
+// Test that ALTER SYSTEM SET and RESET change the log filter of the process.
+#[test]
+fn test_alter_system_log_filter() -> Result<(), Box<dyn Error>> {
+    /// Records the filter that is installed, validating it as the real
+    /// control does.
+    struct TestLogFilter(Mutex<String>);
+
+    impl coord::LogFilterControl for TestLogFilter {
+        fn set(&self, filter: &str) -> Result<(), anyhow::Error> {
+            tracing_subscriber::EnvFilter::try_new(filter)?;
+            *self.0.lock().unwrap() = filter.into();
+            Ok(())
+        }
+
+        fn reset(&self) -> Result<(), anyhow::Error> {
+            self.set("info")
+        }
+    }
+
+    let log_filter = Arc::new(TestLogFilter(Mutex::new("info".into())));
+    let config = util::Config::default().log_filter(log_filter.clone());
+    let server = util::start_server(config)?;
+    let mut client = server.connect(postgres::NoTls)?;
+    let current = || log_filter.0.lock().unwrap().clone();
+
+    client.batch_execute("ALTER SYSTEM SET log_filter = 'info,dataflow::source=debug'")?;
+    assert_eq!(current(), "info,dataflow::source=debug");
+
+    // An invalid filter is reported to the client and leaves the filter
+    // unchanged.
+    assert!(client
+        .batch_execute("ALTER SYSTEM SET log_filter = 'dataflow=bogus'")
+        .is_err());
+    assert_eq!(current(), "info,dataflow::source=debug");
+
+    client.batch_execute("ALTER SYSTEM RESET log_filter")?;
+    assert_eq!(current(), "info");
+
+    let err = client
+        .batch_execute("ALTER SYSTEM SET bogus = 'info'")
+        .unwrap_db_error();
+    assert_eq!(
+        err.message(),
+        "unrecognized configuration parameter \"bogus\""
+    );
+
+    Ok(())
+}
+
+// Test the /prof/cpu and /prof/heap endpoints of the HTTP server.
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn test_prof_endpoints() -> Result<(), Box<dyn Error>> {
+    let server = util::start_server(util::Config::default())?;
+    let base = format!("http://{}", server.inner.local_addr());
+    let client = Client::new();
+
+    // Invalid parameters are rejected before profiling begins.
+    for query in &["seconds=0", "seconds=301", "seconds=x", "frequency=0"] {
+        let res = client.get(&format!("{}/prof/cpu?{}", base, query)).send()?;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "query: {}", query);
+    }
+
+    // A CPU profile is a pprof protobuf message, whose first field is the
+    // list of sample types (field 1, length delimited).
+    let res = client
+        .get(&format!("{}/prof/cpu?seconds=1&frequency=999", base))
+        .send()?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["content-type"], "application/octet-stream");
+    let profile = res.bytes()?;
+    assert_eq!(profile.first(), Some(&0x0a));
+
+    // A heap profile is only available while heap profiling is active.
+    let res = client.get(&format!("{}/prof/heap", base)).send()?;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert!(res.text()?.contains("heap profiling is not active"));
+
+    let res = client
+        .post(&format!("{}/prof", base))
+        .form(&[("action", "activate")])
+        .send()?;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client.get(&format!("{}/prof/heap", base)).send()?;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.text()?.starts_with("heap_v2/"));
+
+    let res = client
+        .post(&format!("{}/prof", base))
+        .form(&[("action", "deactivate")])
+        .send()?;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    Ok(())
+}
+
+// Test the /api/sql POST endpoint of the HTTP server.
+#[test]
+fn test_http_api_sql() -> Result<(), Box<dyn Error>> {
+    let server = util::start_server(util::Config::default())?;
+    let url = Url::parse(&format!("http://{}/api/sql", server.inner.local_addr()))?;
+
+    struct TestCase {
+        request: serde_json::Value,
+        status: StatusCode,
+        body: &'static str,
+    }
+
+    let tests = vec![
+        // Regular query works.
+        TestCase {
+            request: json!({ "query": "select 1+2 as col" }),
+            status: StatusCode::OK,
+            body: r#"{"rows":[[3]],"col_names":["col"]}"#,
+        },
+        // Parameters are bound in order.
+        TestCase {
+            request: json!({
+                "query": "select $1::int + $2::int as sum, $3::text as t, $4::text as n",
+                "params": [1, "2", "foo", null],
+            }),
+            status: StatusCode::OK,
+            body: r#"{"rows":[[3,"foo",null]],"col_names":["sum","t","n"]}"#,
+        },
+        // The number of parameters must match.
+        TestCase {
+            request: json!({ "query": "select $1::int", "params": [] }),
+            status: StatusCode::BAD_REQUEST,
+            body: r#"{"error":"request supplies 0 parameters, but statement requires 1"}"#,
+        },
+        // Parameters must be valid for their type.
+        TestCase {
+            request: json!({ "query": "select $1::int", "params": ["foo"] }),
+            status: StatusCode::BAD_REQUEST,
+            body: r#"{"error":"unable to decode parameter $1: invalid input syntax for type integer: invalid digit found in string: \"foo\""}"#,
+        },
+    ];
+
+    for tc in tests {
+        let res = Client::new().post(url.clone()).json(&tc.request).send()?;
+        assert_eq!(res.status(), tc.status);
+        assert_eq!(res.text()?, tc.body);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_http_api_tail() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let server = util::start_server(util::Config::default())?;
+    let mut client = server.connect(postgres::NoTls)?;
+    client.batch_execute("CREATE TABLE t (a int, b text)")?;
+    client.batch_execute("INSERT INTO t VALUES (1, 'one')")?;
+
+    // Requests that are not WebSocket upgrades are rejected.
+    let url = format!("http://{}/api/tail?query=TAIL+t", server.inner.local_addr());
+    let res = Client::new().get(&url).send()?;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Statements other than TAIL are rejected before the upgrade.
+    let url = format!("ws://{}/api/tail?query=SELECT+1", server.inner.local_addr());
+    assert!(tungstenite::connect(url).is_err());
+
+    let url = format!("ws://{}/api/tail?query=TAIL+t", server.inner.local_addr());
+    let (mut ws, _) = tungstenite::connect(url)?;
+    let mut next_frame = || -> Result<serde_json::Value, Box<dyn Error>> {
+        loop {
+            if let Message::Text(text) = ws.read_message()? {
+                return Ok(serde_json::from_str(&text)?);
+            }
+        }
+    };
+
+    assert_eq!(
+        next_frame()?,
+        json!({ "type": "columns", "col_names": ["a", "b"] })
+    );
+    let frame = next_frame()?;
+    assert_eq!(frame["type"], "update");
+    assert_eq!(frame["diff"], 1);
+    assert_eq!(frame["row"], json!([1, "one"]));
+    // The PROGRESS option is enabled even though the query did not request
+    // it.
+    loop {
+        let frame = next_frame()?;
+        if frame["type"] == "progress" {
+            break;
+        }
+        assert_eq!(frame["type"], "update");
+    }
+
+    // The fields of an update follow the envelope of the TAIL.
+    let url = format!(
+        "ws://{}/api/tail?query=TAIL+t+ENVELOPE+UPSERT+KEY+(a)",
+        server.inner.local_addr()
+    );
+    let (mut ws, _) = tungstenite::connect(url)?;
+    let mut next_frame = || -> Result<serde_json::Value, Box<dyn Error>> {
+        loop {
+            if let Message::Text(text) = ws.read_message()? {
+                return Ok(serde_json::from_str(&text)?);
+            }
+        }
+    };
+    assert_eq!(
+        next_frame()?,
+        json!({ "type": "columns", "col_names": ["a", "b"] })
+    );
+    let frame = next_frame()?;
+    assert_eq!(frame["type"], "update");
+    assert_eq!(frame["state"], "upsert");
+    assert_eq!(frame["row"], json!([1, "one"]));
+    assert!(frame.get("diff").is_none());
+
+    let url = format!(
+        "ws://{}/api/tail?query=TAIL+t+ENVELOPE+DEBEZIUM",
+        server.inner.local_addr()
+    );
+    let (mut ws, _) = tungstenite::connect(url)?;
+    let mut next_frame = || -> Result<serde_json::Value, Box<dyn Error>> {
+        loop {
+            if let Message::Text(text) = ws.read_message()? {
+                return Ok(serde_json::from_str(&text)?);
+            }
+        }
+    };
+    assert_eq!(
+        next_frame()?,
+        json!({ "type": "columns", "col_names": ["before", "after"] })
+    );
+    let frame = next_frame()?;
+    assert_eq!(frame["type"], "update");
+    assert!(frame.get("diff").is_none());
+    let row = frame["row"].as_array().unwrap();
+    assert_eq!(row.len(), 2);
+    assert_eq!(row[0], serde_json::Value::Null);
+    assert!(row[1].is_string());
+
+    Ok(())
+}
+
+/// Wraps `message` in a gRPC request on behalf of the `materialize` user.
+fn grpc_request<T>(message: T) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    request
+        .metadata_mut()
+        .insert("user", "materialize".parse().unwrap());
+    request
+}
+
+#[test]
+fn test_flight() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct CommandStatementQuery {
+        #[prost(string, tag = "1")]
+        query: String,
+    }
+
+    let server = util::start_server(util::Config::default().enable_flight())?;
+    let mut pg_client = server.connect(postgres::NoTls)?;
+    let addr = server.inner.flight_local_addr().unwrap();
+    server.runtime.block_on(async {
+        let mut client = FlightServiceClient::connect(format!("http://{}", addr)).await?;
+        let query = "SELECT * FROM (VALUES (1, 'a', NULL), (2, 'b', 1.5::float8)) AS t (i, s, f) \
+                     ORDER BY i";
+
+        // Plain-text tickets and Flight SQL tickets fetch the same results.
+        let mut command = vec![];
+        CommandStatementQuery {
+            query: query.into(),
+        }
+        .encode(&mut command)?;
+        let mut any = vec![];
+        prost_types::Any {
+            type_url: "type.googleapis.com/arrow.flight.protocol.sql.CommandStatementQuery".into(),
+            value: command,
+        }
+        .encode(&mut any)?;
+        let info = client
+            .get_flight_info(grpc_request(FlightDescriptor::new_cmd(any)))
+            .await?
+            .into_inner();
+        let sql_ticket = info.endpoint[0].ticket.clone().unwrap();
+        let text_ticket = Ticket {
+            ticket: query.as_bytes().to_vec(),
+        };
+
+        for ticket in vec![sql_ticket, text_ticket] {
+            let mut stream = client.do_get(grpc_request(ticket)).await?.into_inner();
+            let schema = Arc::new(Schema::try_from(&stream.message().await?.unwrap())?);
+            let names: Vec<_> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+            assert_eq!(names, vec!["i", "s", "f"]);
+
+            let data = stream.message().await?.unwrap();
+            let batch = flight_data_to_arrow_batch(&data, Arc::clone(&schema), &[])?;
+            let i = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<Int32Array>()
+                .unwrap();
+            let s = batch
+                .column(1)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .unwrap();
+            let f = batch
+                .column(2)
+                .as_any()
+                .downcast_ref::<Float64Array>()
+                .unwrap();
+            assert_eq!(i.values(), &[1, 2]);
+            assert_eq!((s.value(0), s.value(1)), ("a", "b"));
+            assert!(f.is_null(0));
+            assert_eq!(f.value(1), 1.5);
+            assert!(stream.message().await?.is_none());
+        }
+
+        // Requests must name their user.
+        let ticket = Ticket {
+            ticket: b"SELECT 1".to_vec(),
+        };
+        match client.do_get(ticket).await {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => assert_eq!(e.code(), tonic::Code::Unauthenticated),
+        }
+
+        // The handshake checks the request's credentials.
+        let handshake = futures::stream::iter(vec![HandshakeRequest::default()]);
+        assert!(client.handshake(grpc_request(handshake)).await.is_ok());
+        let handshake = futures::stream::iter(vec![HandshakeRequest::default()]);
+        match client.handshake(handshake).await {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => assert_eq!(e.code(), tonic::Code::Unauthenticated),
+        }
+
+        // Statements other than SELECT are rejected without being executed.
+        for sql in &["CREATE VIEW v AS SELECT 1", "SHOW VIEWS"] {
+            let ticket = Ticket {
+                ticket: sql.as_bytes().to_vec(),
+            };
+            match client.do_get(grpc_request(ticket)).await {
+                Ok(_) => panic!("unexpected success"),
+                Err(e) => {
+                    assert_eq!(e.code(), tonic::Code::InvalidArgument);
+                    assert_eq!(e.message(), "only SELECT statements are supported");
+                }
+            }
+        }
+
+        Ok::<_, Box<dyn Error>>(())
+    })?;
+    let views = pg_client.query("SHOW VIEWS", &[])?;
+    assert!(views.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_control_api() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let server = util::start_server(util::Config::default().enable_control())?;
+    let addr = server.inner.control_local_addr().unwrap();
+    server.runtime.block_on(async {
+        let mut client = ControlClient::connect(format!("http://{}", addr)).await?;
+        let list_tables = || {
+            grpc_request(ListObjectsRequest {
+                r#type: ObjectType::Table as i32,
+                database: "materialize".into(),
+                schema: "public".into(),
+            })
+        };
+
+        // Requests must name their user.
+        match client.get_health(GetHealthRequest {}).await {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => assert_eq!(e.code(), tonic::Code::Unauthenticated),
+        }
+
+        let res = client
+            .execute(grpc_request(ExecuteRequest {
+                sql: "CREATE TABLE t (a int)".into(),
+            }))
+            .await?
+            .into_inner();
+        assert_eq!(res.command, "CREATE TABLE");
+        assert!(!res.already_existed);
+
+        let res = client
+            .execute(grpc_request(ExecuteRequest {
+                sql: "CREATE TABLE IF NOT EXISTS t (a int)".into(),
+            }))
+            .await?
+            .into_inner();
+        assert!(res.already_existed);
+
+        let res = client
+            .execute(grpc_request(ExecuteRequest {
+                sql: "INSERT INTO t VALUES (1), (2)".into(),
+            }))
+            .await?
+            .into_inner();
+        assert_eq!((res.command.as_str(), res.rows_affected), ("INSERT", 2));
+
+        let objects = client
+            .list_objects(list_tables())
+            .await?
+            .into_inner()
+            .objects;
+        let names: Vec<_> = objects
+            .iter()
+            .map(|o| o.name.as_ref().unwrap().item.as_str())
+            .collect();
+        assert_eq!(names, vec!["t"]);
+
+        // Statements that return rows are rejected.
+        match client
+            .execute(grpc_request(ExecuteRequest {
+                sql: "SELECT 1".into(),
+            }))
+            .await
+        {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => assert_eq!(e.code(), tonic::Code::InvalidArgument),
+        }
+
+        client
+            .drop_object(grpc_request(DropObjectRequest {
+                name: Some(ObjectName {
+                    database: "materialize".into(),
+                    schema: "public".into(),
+                    item: "t".into(),
+                }),
+                r#type: ObjectType::Table as i32,
+                if_exists: false,
+                cascade: false,
+            }))
+            .await?;
+        let objects = client
+            .list_objects(list_tables())
+            .await?
+            .into_inner()
+            .objects;
+        assert!(objects.is_empty());
+
+        let health = client
+            .get_health(grpc_request(GetHealthRequest {}))
+            .await?
+            .into_inner();
+        assert_eq!(health.stalled_dataflows, 0);
+        assert!(!health.version.is_empty());
+
+        Ok::<_, Box<dyn Error>>(())
+    })?;
+
+    // Without TLS to verify users, the control API must not be reachable from
+    // other machines.
+    let config = util::Config::default()
+        .control_listen_addr(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0));
+    match util::start_server(config) {
+        Ok(_) => panic!("unexpected success"),
+        Err(e) => assert!(e.to_string().contains("loopback address")),
+    }
+
+    Ok(())
+}
@@ -25,6 +25,7 @@ use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
 use log::info;
+use postgres::error::SqlState;
 use tempfile::NamedTempFile;
 
 use util::{MzTimestamp, PostgresErrorExt};
@@ -218,9 +219,12 @@ fn test_tail_basic() -> Result<(), Box<dyn Error>> {
     let err = client_reads
         .batch_execute("TAIL v AS OF 1")
         .unwrap_db_error();
-    assert!(err
-        .message()
-        .starts_with("Timestamp (1) is not valid for all inputs"));
+    assert!(err.message().starts_with(
+        "Timestamp (1) is not valid for all inputs: \
+         index \"materialize.public.t_primary_idx\" has been compacted to timestamp"
+    ));
+    assert_eq!(*err.code(), SqlState::SNAPSHOT_TOO_OLD);
+    assert!(err.hint().unwrap().contains("logical_compaction_window"));
 
     Ok(())
 }
@@ -268,6 +272,129 @@ fn test_tail_progress() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Test that FETCH from a TAIL with progress rows returns only the latest
+/// progress row, so that a FETCH that times out without data reports how far
+/// the TAIL has progressed.
+#[test]
+fn test_tail_fetch_progress() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default().workers(2);
+    let server = util::start_server(config)?;
+    let mut client_writes = server.connect(postgres::NoTls)?;
+    let mut client_reads = server.connect(postgres::NoTls)?;
+
+    client_writes.batch_execute("CREATE TABLE t (data text)")?;
+    client_reads.batch_execute(
+        "BEGIN;
+         DECLARE c CURSOR FOR TAIL t WITH (PROGRESS);",
+    )?;
+
+    // With no changes, each FETCH returns at most the latest progress row, and
+    // progress never goes backwards.
+    let mut last_ts = None;
+    while last_ts.is_none() {
+        let rows = client_reads.query("FETCH ALL c WITH (timeout = '1s')", &[])?;
+        assert!(rows.len() <= 1);
+        if let Some(row) = rows.first() {
+            assert_eq!(row.get::<_, bool>("progressed"), true);
+            last_ts = Some(row.get::<_, MzTimestamp>("timestamp"));
+        }
+    }
+    let rows = client_reads.query("FETCH ALL c WITH (timeout = '1s')", &[])?;
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].get::<_, bool>("progressed"), true);
+    assert!(rows[0].get::<_, MzTimestamp>("timestamp") >= last_ts.unwrap());
+
+    // Progress rows do not count towards the number of rows requested, and are
+    // not sent before the data rows that follow them.
+    client_writes.batch_execute("INSERT INTO t VALUES ('a'), ('b')")?;
+    let first = loop {
+        let rows = client_reads.query("FETCH 1 c", &[])?;
+        assert_eq!(rows.len(), 1);
+        if !rows[0].get::<_, bool>("progressed") {
+            break rows[0].get::<_, String>("data");
+        }
+    };
+    let rows = client_reads.query("FETCH ALL c", &[])?;
+    assert!(rows.len() == 1 || rows.len() == 2);
+    assert_eq!(rows[0].get::<_, bool>("progressed"), false);
+    assert_ne!(rows[0].get::<_, String>("data"), first);
+    if let Some(row) = rows.get(1) {
+        assert_eq!(row.get::<_, bool>("progressed"), true);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_tail_envelopes() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default().workers(2);
+    let server = util::start_server(config)?;
+    let mut client_writes = server.connect(postgres::NoTls)?;
+    let mut client_reads = server.connect(postgres::NoTls)?;
+
+    client_writes.batch_execute("CREATE TABLE t (id int, data text)")?;
+    client_reads.batch_execute(
+        "BEGIN;
+         DECLARE c CURSOR FOR TAIL t ENVELOPE UPSERT KEY (id);",
+    )?;
+
+    let updates = [
+        ("INSERT INTO t VALUES (1, 'a')", "upsert", Some("a")),
+        ("UPDATE t SET data = 'b' WHERE id = 1", "upsert", Some("b")),
+        ("DELETE FROM t WHERE id = 1", "delete", None),
+    ];
+    for (sql, state, data) in &updates {
+        client_writes.batch_execute(sql)?;
+        let row = client_reads.query_one("FETCH ALL c", &[])?;
+        assert_eq!(row.get::<_, String>("state"), *state);
+        assert_eq!(row.get::<_, i32>("id"), 1);
+        assert_eq!(row.get::<_, Option<String>>("data").as_deref(), *data);
+    }
+
+    // Records are only representable in the text format, so use the simple
+    // query protocol to inspect the before and after columns.
+    client_reads.batch_execute(
+        "COMMIT;
+         BEGIN;
+         DECLARE c CURSOR FOR TAIL t WITH (SNAPSHOT = false) ENVELOPE DEBEZIUM KEY (id);",
+    )?;
+    let updates = [
+        ("INSERT INTO t VALUES (2, 'c')", None, Some("(2,c)")),
+        (
+            "UPDATE t SET data = 'd' WHERE id = 2",
+            Some("(2,c)"),
+            Some("(2,d)"),
+        ),
+        ("DELETE FROM t WHERE id = 2", Some("(2,d)"), None),
+    ];
+    for (sql, before, after) in &updates {
+        client_writes.batch_execute(sql)?;
+        let rows: Vec<_> = client_reads
+            .simple_query("FETCH ALL c")?
+            .into_iter()
+            .filter_map(|msg| match msg {
+                postgres::SimpleQueryMessage::Row(row) => Some(row),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get("before"), *before);
+        assert_eq!(rows[0].get("after"), *after);
+    }
+
+    client_reads.batch_execute("COMMIT")?;
+    let err = client_reads
+        .batch_execute("TAIL t ENVELOPE UPSERT KEY (nope)")
+        .unwrap_db_error();
+    assert_eq!(err.message(), "column \"nope\" does not exist");
+
+    Ok(())
+}
+
 #[test]
 fn test_tail_fetch_timeout() -> Result<(), Box<dyn Error>> {
     ore::test::init_logging();
@@ -535,3 +662,278 @@ fn test_temporary_views() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+// Tests that concurrent inserts from many connections are all committed, and
+// that each connection observes its own writes as soon as its insert returns.
+#[test]
+fn test_concurrent_inserts() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    const CONNS: usize = 8;
+    const INSERTS_PER_CONN: usize = 50;
+
+    let server = util::start_server(util::Config::default())?;
+    let mut client = server.connect(postgres::NoTls)?;
+    client.batch_execute("CREATE TABLE t (conn int, i int)")?;
+
+    let mut threads = vec![];
+    for conn in 0..CONNS {
+        let mut client = server.connect(postgres::NoTls)?;
+        threads.push(thread::spawn(move || -> Result<(), postgres::Error> {
+            for i in 0..INSERTS_PER_CONN {
+                client.execute(
+                    "INSERT INTO t VALUES ($1, $2)",
+                    &[&(conn as i32), &(i as i32)],
+                )?;
+                let count: i64 = client
+                    .query_one("SELECT count(*) FROM t WHERE conn = $1", &[&(conn as i32)])?
+                    .get(0);
+                assert_eq!(count, i as i64 + 1);
+            }
+            Ok(())
+        }));
+    }
+    for thread in threads {
+        thread.join().unwrap()?;
+    }
+
+    let count: i64 = client.query_one("SELECT count(*) FROM t", &[])?.get(0);
+    assert_eq!(count, (CONNS * INSERTS_PER_CONN) as i64);
+
+    Ok(())
+}
+
+// Tests that EXPLAIN TIMESTAMP reports the timestamp that the query actually
+// runs at, and the frontiers of the indexes that determine it.
+#[test]
+fn test_explain_timestamp() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    struct Explanation {
+        timestamp: u64,
+        since: Vec<u64>,
+        upper: Vec<u64>,
+        indexes: Vec<(String, Vec<u64>, Vec<u64>, bool)>,
+    }
+
+    fn parse_frontier(line: &str, label: &str) -> Vec<u64> {
+        let list = line
+            .trim()
+            .strip_prefix(label)
+            .unwrap_or_else(|| panic!("expected {:?}, got {:?}", label, line))
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        list.split(", ")
+            .filter(|t| !t.is_empty())
+            .map(|t| t.parse().unwrap())
+            .collect()
+    }
+
+    fn explain(client: &mut postgres::Client, query: &str) -> Result<Explanation, Box<dyn Error>> {
+        let explanation: String = client
+            .query_one(&*format!("EXPLAIN TIMESTAMP FOR {}", query), &[])?
+            .get(0);
+        let mut lines = explanation.lines();
+        let timestamp = lines.next().unwrap().strip_prefix("timestamp: ").unwrap();
+        let mut explanation = Explanation {
+            timestamp: timestamp.parse()?,
+            since: parse_frontier(lines.next().unwrap(), "since:"),
+            upper: parse_frontier(lines.next().unwrap(), "upper:"),
+            indexes: vec![],
+        };
+        while let Some(line) = lines.next() {
+            if line.is_empty() {
+                continue;
+            }
+            let name = line.strip_prefix("index ").unwrap().to_string();
+            let since = parse_frontier(lines.next().unwrap(), "since:");
+            let upper = parse_frontier(lines.next().unwrap(), "upper:");
+            let holding = lines.next().unwrap().trim() == "holding reads: true";
+            explanation.indexes.push((name, since, upper, holding));
+        }
+        Ok(explanation)
+    }
+
+    let server = util::start_server(util::Config::default())?;
+    let mut client = server.connect(postgres::NoTls)?;
+
+    // The introspection indexes are not tables, so queries against them read
+    // at the latest timestamp that all of their indexes have completed. Wait
+    // for the first such timestamp.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let e = loop {
+        match explain(
+            &mut client,
+            "SELECT * FROM mz_catalog.mz_dataflow_operators",
+        ) {
+            Ok(e) => break e,
+            Err(_) if Instant::now() < deadline => sleep(Duration::from_millis(100)),
+            Err(e) => return Err(e),
+        }
+    };
+    assert_eq!(e.indexes.len(), 1);
+    assert!(e.indexes[0]
+        .0
+        .starts_with("mz_catalog.mz_dataflow_operators_primary_idx"));
+    assert_eq!(e.since, e.indexes[0].1);
+    assert_eq!(e.upper, e.indexes[0].2);
+    assert!(e.since[0] <= e.timestamp);
+    assert_eq!(e.upper, vec![e.timestamp + 1]);
+    // The only index is necessarily the one that limits the timestamp.
+    assert!(e.indexes[0].3);
+
+    // Queries against tables read at the latest input timestamp, so that they
+    // observe all completed writes.
+    client.batch_execute("CREATE TABLE t (a int)")?;
+    client.batch_execute("INSERT INTO t VALUES (1)")?;
+    let MzTimestamp(insert_ts) = client
+        .query_one("SELECT mz_logical_timestamp() FROM t", &[])?
+        .get(0);
+    let e = explain(&mut client, "SELECT * FROM t")?;
+    assert_eq!(e.indexes.len(), 1);
+    assert!(e.indexes[0]
+        .0
+        .starts_with("materialize.public.t_primary_idx"));
+    assert!(e.timestamp >= insert_ts);
+    assert!(e.since[0] <= e.timestamp);
+    let holding = match e.indexes[0].2.first() {
+        Some(upper) => *upper <= e.timestamp + 1,
+        None => false,
+    };
+    assert_eq!(e.indexes[0].3, holding);
+
+    // The query runs at or after the explained timestamp.
+    let MzTimestamp(query_ts) = client
+        .query_one("SELECT mz_logical_timestamp() FROM t", &[])?
+        .get(0);
+    assert!(query_ts >= e.timestamp);
+
+    Ok(())
+}
+
+// Tests that the first query of a lazy view that reads from an index waits for
+// the view's new index to catch up, rather than returning empty results.
+#[test]
+fn test_lazy_view_first_query() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let server = util::start_server(util::Config::default())?;
+    let mut client = server.connect(postgres::NoTls)?;
+
+    let mut file = NamedTempFile::new()?;
+    file.write_all(b"a\nb\n")?;
+    file.as_file_mut().sync_all()?;
+    client.batch_execute(&*format!(
+        "CREATE MATERIALIZED SOURCE f FROM FILE '{}' FORMAT TEXT",
+        file.path().display()
+    ))?;
+    let deadline = Instant::now() + Duration::from_secs(30);
+    loop {
+        if let Ok(row) = client.query_one("SELECT count(*) FROM f", &[]) {
+            if row.get::<_, i64>(0) == 2 {
+                break;
+            }
+        }
+        if Instant::now() > deadline {
+            return Err("source never ingested the file".into());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+
+    client.batch_execute(
+        "CREATE MATERIALIZED VIEW v WITH (lazy = true) AS SELECT upper(text) AS u FROM f",
+    )?;
+    let rows = client.query("SELECT u FROM v ORDER BY u", &[])?;
+    let rows: Vec<String> = rows.into_iter().map(|row| row.get(0)).collect();
+    assert_eq!(rows, vec!["A", "B"]);
+
+    Ok(())
+}
+
+/// Waits until exactly `n` shared dataflows are running.
+fn wait_for_shared_dataflows(
+    client: &mut postgres::Client,
+    n: usize,
+) -> Result<(), Box<dyn Error>> {
+    // Filtering the log's index is served without a dataflow of its own, and
+    // so does not disturb the count.
+    let deadline = Instant::now() + Duration::from_secs(30);
+    loop {
+        let rows = client.query(
+            "SELECT name FROM mz_catalog.mz_dataflow_operators
+             WHERE name LIKE 'Dataflow: shared-view-%'",
+            &[],
+        )?;
+        if rows.len() == n {
+            return Ok(());
+        }
+        if Instant::now() > deadline {
+            return Err(format!("expected {} shared dataflows, found {}", n, rows.len()).into());
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+fn query_counts(client: &mut postgres::Client) -> Result<Vec<(i32, i64)>, Box<dyn Error>> {
+    Ok(client
+        .query("SELECT a, count(*) FROM t GROUP BY a ORDER BY a", &[])?
+        .into_iter()
+        .map(|row| (row.get(0), row.get(1)))
+        .collect())
+}
+
+#[test]
+fn test_shared_dataflows() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default().shared_dataflows(Duration::from_secs(3600), 2);
+    let server = util::start_server(config)?;
+    let mut client = server.connect(postgres::NoTls)?;
+
+    client.batch_execute("CREATE TABLE t (a int, b int)")?;
+    client.batch_execute("INSERT INTO t VALUES (1, 1), (1, 2), (2, 3)")?;
+
+    // The first peek of the query builds a dataflow that it tears down.
+    assert_eq!(query_counts(&mut client)?, vec![(1, 2), (2, 1)]);
+    wait_for_shared_dataflows(&mut client, 0)?;
+
+    // The second keeps its dataflow around...
+    assert_eq!(query_counts(&mut client)?, vec![(1, 2), (2, 1)]);
+    wait_for_shared_dataflows(&mut client, 1)?;
+
+    // ...for later peeks to read from, which see changes to the table.
+    client.batch_execute("INSERT INTO t VALUES (2, 4), (3, 5)")?;
+    assert_eq!(query_counts(&mut client)?, vec![(1, 2), (2, 2), (3, 1)]);
+    wait_for_shared_dataflows(&mut client, 1)?;
+
+    // Dropping the table tears down the dataflow.
+    client.batch_execute("DROP TABLE t")?;
+    wait_for_shared_dataflows(&mut client, 0)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_shared_dataflows_min_peeks() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let config = util::Config::default().shared_dataflows(Duration::from_secs(1), 1);
+    let server = util::start_server(config)?;
+    let mut client = server.connect(postgres::NoTls)?;
+
+    client.batch_execute("CREATE TABLE t (a int, b int)")?;
+    client.batch_execute("INSERT INTO t VALUES (1, 1), (1, 2), (2, 3)")?;
+
+    // With a minimum of one peek, the first peek's dataflow is shared.
+    assert_eq!(query_counts(&mut client)?, vec![(1, 2), (2, 1)]);
+    wait_for_shared_dataflows(&mut client, 1)?;
+    assert_eq!(query_counts(&mut client)?, vec![(1, 2), (2, 1)]);
+    wait_for_shared_dataflows(&mut client, 1)?;
+
+    // The dataflow is torn down once it goes unqueried for the idle timeout.
+    thread::sleep(Duration::from_secs(2));
+    wait_for_shared_dataflows(&mut client, 0)?;
+
+    Ok(())
+}
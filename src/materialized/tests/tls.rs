@@ -9,12 +9,19 @@
 
 //! Integration tests for TLS encryption and authentication.
 
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fs;
 use std::iter;
 use std::net::{IpAddr, Ipv4Addr};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use arrow::array::StringArray;
+use arrow::datatypes::Schema;
+use arrow_flight::flight_service_client::FlightServiceClient;
+use arrow_flight::utils::flight_data_to_arrow_batch;
+use arrow_flight::Ticket;
 use hyper::client::HttpConnector;
 use hyper::http::uri::Scheme;
 use hyper::{body, Body, Request, StatusCode, Uri};
@@ -36,6 +43,7 @@ use postgres_openssl::MakeTlsConnector;
 use serde::Deserialize;
 use tempfile::TempDir;
 use tokio::runtime::Runtime;
+use tonic::transport::Endpoint;
 
 use materialized::TlsMode;
 use ore::assert_contains;
@@ -691,3 +699,85 @@ fn test_tls() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn test_tls_flight() -> Result<(), Box<dyn Error>> {
+    ore::test::init_logging();
+
+    let ca = Ca::new()?;
+    let (server_cert, server_key) =
+        ca.request_cert("server", vec![IpAddr::V4(Ipv4Addr::LOCALHOST)])?;
+    let (client_cert, client_key) = ca.request_client_cert("materialize")?;
+
+    let config = util::Config::default()
+        .with_tls(
+            TlsMode::VerifyFull {
+                ca: ca.ca_cert_path(),
+            },
+            &server_cert,
+            &server_key,
+        )
+        .enable_flight();
+    let server = util::start_server(config)?;
+    let port = server.inner.flight_local_addr().unwrap().port();
+    let ticket = |user: &str| {
+        let mut request = tonic::Request::new(Ticket {
+            ticket: b"SELECT current_user".to_vec(),
+        });
+        request.metadata_mut().insert("user", user.parse().unwrap());
+        request
+    };
+
+    let runtime = Runtime::new()?;
+    runtime.block_on(async {
+        // Unencrypted connections are rejected.
+        let endpoint = Endpoint::from_shared(format!("http://{}:{}", Ipv4Addr::LOCALHOST, port))?;
+        let res = match endpoint.connect().await {
+            Ok(channel) => FlightServiceClient::new(channel)
+                .do_get(ticket("materialize"))
+                .await
+                .map(|_| ()),
+            Err(e) => Err(tonic::Status::unavailable(e.to_string())),
+        };
+        assert!(res.is_err());
+
+        let mut connector = SslConnector::builder(SslMethod::tls())?;
+        connector.set_ca_file(ca.ca_cert_path())?;
+        connector.set_certificate_file(&client_cert, SslFiletype::PEM)?;
+        connector.set_private_key_file(&client_key, SslFiletype::PEM)?;
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        let connector = HttpsConnector::with_connector(http, connector)?;
+        let channel = Endpoint::from_shared(format!("https://{}:{}", Ipv4Addr::LOCALHOST, port))?
+            .connect_with_connector(connector)
+            .await?;
+        let mut client = FlightServiceClient::new(channel);
+
+        // Requests that name the user in the client's certificate succeed and
+        // run as that user.
+        let mut stream = client.do_get(ticket("materialize")).await?.into_inner();
+        let schema = Arc::new(Schema::try_from(&stream.message().await?.unwrap())?);
+        let data = stream.message().await?.unwrap();
+        let batch = flight_data_to_arrow_batch(&data, schema, &[])?;
+        let user = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(user.value(0), "materialize");
+
+        // Requests that name another user are rejected.
+        match client.do_get(ticket("other")).await {
+            Ok(_) => panic!("unexpected success"),
+            Err(e) => {
+                assert_eq!(e.code(), tonic::Code::Unauthenticated);
+                assert_eq!(
+                    e.message(),
+                    "certificate authentication failed for user \"other\""
+                );
+            }
+        }
+
+        Ok::<_, Box<dyn Error>>(())
+    })
+}
@@ -25,20 +25,34 @@ use tokio::runtime::Runtime;
 #[derive(Clone)]
 pub struct Config {
     data_directory: Option<PathBuf>,
+    init_sql: Option<PathBuf>,
     logging_granularity: Option<Duration>,
     tls: Option<materialized::TlsConfig>,
     experimental_mode: bool,
     workers: usize,
+    quotas: coord::QuotaConfig,
+    flight: bool,
+    control_listen_addr: Option<SocketAddr>,
+    log_filter: Option<Arc<dyn coord::LogFilterControl>>,
+    shared_dataflow_idle_timeout: Option<Duration>,
+    shared_dataflow_min_peeks: usize,
 }
 
 impl Default for Config {
     fn default() -> Config {
         Config {
             data_directory: None,
+            init_sql: None,
             logging_granularity: Some(Duration::from_millis(10)),
             tls: None,
             experimental_mode: false,
             workers: 1,
+            quotas: coord::QuotaConfig::default(),
+            flight: false,
+            control_listen_addr: None,
+            log_filter: None,
+            shared_dataflow_idle_timeout: None,
+            shared_dataflow_min_peeks: 2,
         }
     }
 }
@@ -54,6 +68,11 @@ impl Config {
         self
     }
 
+    pub fn init_sql(mut self, init_sql: impl Into<PathBuf>) -> Self {
+        self.init_sql = Some(init_sql.into());
+        self
+    }
+
     pub fn with_tls(
         mut self,
         mode: TlsMode,
@@ -77,6 +96,37 @@ impl Config {
         self.workers = workers;
         self
     }
+
+    pub fn quotas(mut self, quotas: coord::QuotaConfig) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    pub fn enable_flight(mut self) -> Self {
+        self.flight = true;
+        self
+    }
+
+    pub fn enable_control(mut self) -> Self {
+        self.control_listen_addr = Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0));
+        self
+    }
+
+    pub fn control_listen_addr(mut self, addr: SocketAddr) -> Self {
+        self.control_listen_addr = Some(addr);
+        self
+    }
+
+    pub fn log_filter(mut self, log_filter: Arc<dyn coord::LogFilterControl>) -> Self {
+        self.log_filter = Some(log_filter);
+        self
+    }
+
+    pub fn shared_dataflows(mut self, idle_timeout: Duration, min_peeks: usize) -> Self {
+        self.shared_dataflow_idle_timeout = Some(idle_timeout);
+        self.shared_dataflow_min_peeks = min_peeks;
+        self
+    }
 }
 
 pub fn start_server(config: Config) -> Result<Server, Box<dyn Error>> {
@@ -101,14 +151,32 @@ pub fn start_server(config: Config) -> Result<Server, Box<dyn Error>> {
                     log_logging: false,
                 }),
             timestamp_frequency: Duration::from_millis(10),
+            quotas: config.quotas,
+            slow_query_threshold: None,
+            statement_history_size: 64 << 20,
+            stalled_dataflow_threshold: None,
+            shared_dataflow_idle_timeout: config.shared_dataflow_idle_timeout,
+            shared_dataflow_min_peeks: config.shared_dataflow_min_peeks,
+            freshness_slo: None,
+            rehydration_budget: None,
+            lazy_view_idle_timeout: Duration::from_secs(300),
+            log_filter: config.log_filter,
             cache: None,
             logical_compaction_window: None,
+            auto_tune_logical_compaction_window: false,
             workers: config.workers,
             timely_worker: timely::WorkerConfig::default(),
             data_directory,
+            init_sql: config.init_sql,
             symbiosis_url: None,
             listen_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
             tls: config.tls,
+            flight_listen_addr: if config.flight {
+                Some(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0))
+            } else {
+                None
+            },
+            control_listen_addr: config.control_listen_addr,
             experimental_mode: config.experimental_mode,
             telemetry_url: None,
         },
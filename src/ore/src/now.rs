@@ -0,0 +1,64 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Now utilities.
+
+use std::convert::TryInto;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// A function that returns the current time, in milliseconds since the Unix
+/// epoch.
+///
+/// Components that take a `NowFn` rather than consulting the system clock
+/// directly can be driven by a virtual clock in tests.
+pub type NowFn = Arc<dyn Fn() -> u64 + Send + Sync>;
+
+/// Returns the current system time, in milliseconds since the Unix epoch.
+pub fn system_time() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .expect("failed to get millis since epoch")
+        .as_millis()
+        .try_into()
+        .expect("current time did not fit into u64")
+}
+
+/// Returns a [`NowFn`] that reads the system clock.
+pub fn system_time_fn() -> NowFn {
+    Arc::new(system_time)
+}
+
+/// A clock that only advances when told to.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClock(Arc<AtomicU64>);
+
+impl ManualClock {
+    /// Constructs a new clock that reads `now`.
+    pub fn new(now: u64) -> ManualClock {
+        ManualClock(Arc::new(AtomicU64::new(now)))
+    }
+
+    /// Returns the time that the clock currently reads.
+    pub fn now(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Sets the time that the clock reads.
+    pub fn set(&self, now: u64) {
+        self.0.store(now, Ordering::SeqCst)
+    }
+
+    /// Returns a [`NowFn`] that reads this clock.
+    pub fn now_fn(&self) -> NowFn {
+        let clock = self.clone();
+        Arc::new(move || clock.now())
+    }
+}
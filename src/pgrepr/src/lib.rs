@@ -26,9 +26,9 @@ mod value;
 pub mod oid;
 
 pub use format::Format;
-pub use types::{Type, LIST, MAP};
+pub use types::{typmod, Type, GEOGRAPHY, GEOMETRY, LIST, MAP};
 pub use value::interval::Interval;
 pub use value::jsonb::Jsonb;
 pub use value::numeric::Numeric;
 pub use value::record::Record;
-pub use value::{null_datum, values_from_row, Value};
+pub use value::{encode_datum, null_datum, values_from_row, Value};
@@ -10,6 +10,8 @@
 #![allow(missing_docs)]
 
 //! Reserved OIDs through Materialized.
+pub const TYPE_GEOGRAPHY_OID: u32 = 16_439;
+pub const TYPE_GEOMETRY_OID: u32 = 16_438;
 pub const TYPE_LIST_OID: u32 = 16_384;
 pub const TYPE_MAP_OID: u32 = 16_385;
 pub const FUNC_CEIL_F32_OID: u32 = 16_386;
@@ -24,6 +26,9 @@ pub const FUNC_LIST_LENGTH_MAX_OID: u32 = 16_394;
 pub const FUNC_LIST_LENGTH_OID: u32 = 16_395;
 pub const FUNC_LIST_NDIMS_OID: u32 = 16_396;
 pub const FUNC_LIST_PREPEND_OID: u32 = 16_397;
+pub const FUNC_MAP_BUILD_OID: u32 = 16_457;
+pub const FUNC_MAP_KEYS_OID: u32 = 16_458;
+pub const FUNC_MAP_VALUES_OID: u32 = 16_459;
 pub const FUNC_MAX_BOOL_OID: u32 = 16_398;
 pub const FUNC_MIN_BOOL_OID: u32 = 16_399;
 pub const FUNC_MZ_ALL_OID: u32 = 16_400;
@@ -38,9 +43,29 @@ pub const FUNC_MZ_IS_MATERIALIZED_OID: u32 = 16_408;
 pub const FUNC_MZ_LOGICAL_TIMESTAMP_OID: u32 = 16_409;
 pub const FUNC_MZ_RENDER_TYPEMOD_OID: u32 = 16_410;
 pub const FUNC_MZ_VERSION_OID: u32 = 16_411;
+pub const FUNC_OBJECT_JSON_OID: u32 = 16_435;
+pub const FUNC_RANGE_AGG_INT4_OID: u32 = 16_436;
+pub const FUNC_RANGE_AGG_TSTZ_OID: u32 = 16_437;
 pub const FUNC_REGEXP_EXTRACT_OID: u32 = 16_412;
 pub const FUNC_REPEAT_OID: u32 = 16_413;
 pub const FUNC_ROUND_F32_OID: u32 = 16_434;
+pub const FUNC_ST_ASBINARY_OID: u32 = 16_440;
+pub const FUNC_ST_ASGEOJSON_OID: u32 = 16_441;
+pub const FUNC_ST_ASTEXT_OID: u32 = 16_442;
+pub const FUNC_ST_CONTAINS_OID: u32 = 16_443;
+pub const FUNC_ST_DISTANCE_GEOG_OID: u32 = 16_444;
+pub const FUNC_ST_DISTANCE_GEOM_OID: u32 = 16_445;
+pub const FUNC_ST_DWITHIN_GEOG_OID: u32 = 16_446;
+pub const FUNC_ST_DWITHIN_GEOM_OID: u32 = 16_447;
+pub const FUNC_ST_GEOGFROMTEXT_OID: u32 = 16_448;
+pub const FUNC_ST_GEOMFROMGEOJSON_OID: u32 = 16_449;
+pub const FUNC_ST_GEOMFROMTEXT_OID: u32 = 16_450;
+pub const FUNC_ST_GEOMFROMTEXT_SRID_OID: u32 = 16_451;
+pub const FUNC_ST_GEOMFROMWKB_OID: u32 = 16_452;
+pub const FUNC_ST_GEOMFROMWKB_SRID_OID: u32 = 16_453;
+pub const FUNC_ST_MAKEPOINT_OID: u32 = 16_454;
+pub const FUNC_ST_SETSRID_OID: u32 = 16_455;
+pub const FUNC_ST_SRID_OID: u32 = 16_456;
 pub const FUNC_UNNEST_LIST_OID: u32 = 16_416;
 pub const OP_CONCAT_ELEMENY_LIST_OID: u32 = 16_417;
 pub const OP_CONCAT_LIST_ELEMENT_OID: u32 = 16_418;
@@ -7,10 +7,13 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use crate::oid;
+use std::convert::TryFrom;
+
 use lazy_static::lazy_static;
 use repr::ScalarType;
 
+use crate::oid;
+
 /// The type of a [`Value`](crate::Value).
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Type {
@@ -18,14 +21,24 @@ pub enum Type {
     Array(Box<Type>),
     /// A boolean value.
     Bool,
+    /// A fixed-length string, padded with spaces.
+    BpChar,
     /// A byte array, i.e., a variable-length binary string.
     Bytea,
+    /// An IPv4 or IPv6 network.
+    Cidr,
     /// A date.
     Date,
     /// A 4-byte floating point number.
     Float4,
     /// An 8-byte floating point number.
     Float8,
+    /// A spatial object on the surface of the Earth.
+    Geography,
+    /// A spatial object in a planar coordinate system.
+    Geometry,
+    /// An IPv4 or IPv6 host address, with an optional subnet mask.
+    Inet,
     /// A 4-byte signed integer.
     Int4,
     /// An 8-byte signed integer.
@@ -45,6 +58,8 @@ pub enum Type {
     Numeric,
     /// An object identifier.
     Oid,
+    /// A contiguous range of values.
+    Range(Box<Type>),
     /// A sequence of heterogeneous values.
     Record(Vec<Type>),
     /// A variable-length string.
@@ -55,8 +70,14 @@ pub enum Type {
     Timestamp,
     /// A date and time, with a timezone.
     TimestampTz,
+    /// A query for full-text search.
+    TsQuery,
+    /// A document for full-text search.
+    TsVector,
     /// A universally unique identifier.
     Uuid,
+    /// A variable-length string with an optional length limit.
+    VarChar,
 }
 
 lazy_static! {
@@ -80,18 +101,45 @@ lazy_static! {
         postgres_types::Kind::Pseudo,
         "mz_catalog".to_owned(),
     );
+
+    /// The [`Type::Geography`] type.
+    ///
+    /// PostgreSQL has no built-in geospatial types, so the OID is allocated
+    /// from our own range rather than matching the OID that PostGIS would
+    /// assign.
+    pub static ref GEOGRAPHY: postgres_types::Type = postgres_types::Type::new(
+        "geography".to_owned(),
+        oid::TYPE_GEOGRAPHY_OID,
+        postgres_types::Kind::Simple,
+        "pg_catalog".to_owned(),
+    );
+
+    /// The [`Type::Geometry`] type.
+    pub static ref GEOMETRY: postgres_types::Type = postgres_types::Type::new(
+        "geometry".to_owned(),
+        oid::TYPE_GEOMETRY_OID,
+        postgres_types::Kind::Simple,
+        "pg_catalog".to_owned(),
+    );
 }
 
 impl Type {
     /// Returns the type corresponding to the provided OID, if the OID is known.
     pub fn from_oid(oid: u32) -> Option<Type> {
+        match oid {
+            oid::TYPE_GEOGRAPHY_OID => return Some(Type::Geography),
+            oid::TYPE_GEOMETRY_OID => return Some(Type::Geometry),
+            _ => (),
+        }
         let ty = postgres_types::Type::from_oid(oid)?;
         match ty {
             postgres_types::Type::BOOL => Some(Type::Bool),
             postgres_types::Type::BYTEA => Some(Type::Bytea),
+            postgres_types::Type::CIDR => Some(Type::Cidr),
             postgres_types::Type::DATE => Some(Type::Date),
             postgres_types::Type::FLOAT4 => Some(Type::Float4),
             postgres_types::Type::FLOAT8 => Some(Type::Float8),
+            postgres_types::Type::INET => Some(Type::Inet),
             postgres_types::Type::INT2 => Some(Type::Int4),
             postgres_types::Type::INT4 => Some(Type::Int4),
             postgres_types::Type::INT8 => Some(Type::Int8),
@@ -99,13 +147,17 @@ impl Type {
             postgres_types::Type::JSONB => Some(Type::Jsonb),
             postgres_types::Type::NUMERIC => Some(Type::Numeric),
             postgres_types::Type::OID => Some(Type::Oid),
-            postgres_types::Type::TEXT
-            | postgres_types::Type::CHAR
-            | postgres_types::Type::VARCHAR => Some(Type::Text),
+            postgres_types::Type::TEXT | postgres_types::Type::CHAR => Some(Type::Text),
+            postgres_types::Type::BPCHAR => Some(Type::BpChar),
+            postgres_types::Type::VARCHAR => Some(Type::VarChar),
             postgres_types::Type::TIME => Some(Type::Time),
             postgres_types::Type::TIMESTAMP => Some(Type::Timestamp),
             postgres_types::Type::TIMESTAMPTZ => Some(Type::TimestampTz),
+            postgres_types::Type::TSQUERY => Some(Type::TsQuery),
+            postgres_types::Type::TS_VECTOR => Some(Type::TsVector),
             postgres_types::Type::UUID => Some(Type::Uuid),
+            postgres_types::Type::INT4_RANGE => Some(Type::Range(Box::new(Type::Int4))),
+            postgres_types::Type::TSTZ_RANGE => Some(Type::Range(Box::new(Type::TimestampTz))),
             _ => None,
         }
     }
@@ -115,10 +167,15 @@ impl Type {
             Type::Array(t) => match &**t {
                 Type::Array(_) => unreachable!(),
                 Type::Bool => &postgres_types::Type::BOOL_ARRAY,
+                Type::BpChar => &postgres_types::Type::BPCHAR_ARRAY,
                 Type::Bytea => &postgres_types::Type::BYTEA_ARRAY,
+                Type::Cidr => &postgres_types::Type::CIDR_ARRAY,
                 Type::Date => &postgres_types::Type::DATE_ARRAY,
                 Type::Float4 => &postgres_types::Type::FLOAT4_ARRAY,
                 Type::Float8 => &postgres_types::Type::FLOAT8_ARRAY,
+                Type::Geography => unreachable!(),
+                Type::Geometry => unreachable!(),
+                Type::Inet => &postgres_types::Type::INET_ARRAY,
                 Type::Int4 => &postgres_types::Type::INT4_ARRAY,
                 Type::Int8 => &postgres_types::Type::INT8_ARRAY,
                 Type::Interval => &postgres_types::Type::INTERVAL_ARRAY,
@@ -127,18 +184,31 @@ impl Type {
                 Type::Map { .. } => unreachable!(),
                 Type::Numeric => &postgres_types::Type::NUMERIC_ARRAY,
                 Type::Oid => &postgres_types::Type::OID_ARRAY,
+                Type::Range(t) => match &**t {
+                    Type::Int4 => &postgres_types::Type::INT4_RANGE_ARRAY,
+                    Type::TimestampTz => &postgres_types::Type::TSTZ_RANGE_ARRAY,
+                    _ => unreachable!(),
+                },
                 Type::Record(_) => &postgres_types::Type::RECORD_ARRAY,
                 Type::Text => &postgres_types::Type::TEXT_ARRAY,
                 Type::Time => &postgres_types::Type::TIME_ARRAY,
                 Type::Timestamp => &postgres_types::Type::TIMESTAMP_ARRAY,
                 Type::TimestampTz => &postgres_types::Type::TIMESTAMPTZ_ARRAY,
+                Type::TsQuery => &postgres_types::Type::TSQUERY_ARRAY,
+                Type::TsVector => &postgres_types::Type::TS_VECTOR_ARRAY,
                 Type::Uuid => &postgres_types::Type::UUID_ARRAY,
+                Type::VarChar => &postgres_types::Type::VARCHAR_ARRAY,
             },
             Type::Bool => &postgres_types::Type::BOOL,
+            Type::BpChar => &postgres_types::Type::BPCHAR,
             Type::Bytea => &postgres_types::Type::BYTEA,
+            Type::Cidr => &postgres_types::Type::CIDR,
             Type::Date => &postgres_types::Type::DATE,
             Type::Float4 => &postgres_types::Type::FLOAT4,
             Type::Float8 => &postgres_types::Type::FLOAT8,
+            Type::Geography => &GEOGRAPHY,
+            Type::Geometry => &GEOMETRY,
+            Type::Inet => &postgres_types::Type::INET,
             Type::Int4 => &postgres_types::Type::INT4,
             Type::Int8 => &postgres_types::Type::INT8,
             Type::Interval => &postgres_types::Type::INTERVAL,
@@ -147,12 +217,20 @@ impl Type {
             Type::Map { .. } => &MAP,
             Type::Numeric => &postgres_types::Type::NUMERIC,
             Type::Oid => &postgres_types::Type::OID,
+            Type::Range(t) => match &**t {
+                Type::Int4 => &postgres_types::Type::INT4_RANGE,
+                Type::TimestampTz => &postgres_types::Type::TSTZ_RANGE,
+                _ => unreachable!(),
+            },
             Type::Record(_) => &postgres_types::Type::RECORD,
             Type::Text => &postgres_types::Type::TEXT,
             Type::Time => &postgres_types::Type::TIME,
             Type::Timestamp => &postgres_types::Type::TIMESTAMP,
             Type::TimestampTz => &postgres_types::Type::TIMESTAMPTZ,
+            Type::TsQuery => &postgres_types::Type::TSQUERY,
+            Type::TsVector => &postgres_types::Type::TS_VECTOR,
             Type::Uuid => &postgres_types::Type::UUID,
+            Type::VarChar => &postgres_types::Type::VARCHAR,
         }
     }
 
@@ -172,13 +250,19 @@ impl Type {
             &postgres_types::Type::JSONB_ARRAY => "jsonb[]",
             &postgres_types::Type::NUMERIC_ARRAY => "numeric[]",
             &postgres_types::Type::OID_ARRAY => "oid[]",
+            &postgres_types::Type::INT4_RANGE_ARRAY => "int4range[]",
+            &postgres_types::Type::TSTZ_RANGE_ARRAY => "tstzrange[]",
             &postgres_types::Type::RECORD_ARRAY => "record[]",
             &postgres_types::Type::TEXT_ARRAY => "text[]",
             &postgres_types::Type::TIME_ARRAY => "time[]",
             &postgres_types::Type::TIMESTAMP_ARRAY => "timestamp[]",
             &postgres_types::Type::TIMESTAMPTZ_ARRAY => "timestamp with time zone[]",
             &postgres_types::Type::UUID_ARRAY => "uuid[]",
+            &postgres_types::Type::BPCHAR_ARRAY => "character[]",
+            &postgres_types::Type::VARCHAR_ARRAY => "character varying[]",
             &postgres_types::Type::BOOL => "boolean",
+            &postgres_types::Type::BPCHAR => "character",
+            &postgres_types::Type::VARCHAR => "character varying",
             &postgres_types::Type::FLOAT4 => "real",
             &postgres_types::Type::FLOAT8 => "double precision",
             &postgres_types::Type::INT4 => "integer",
@@ -201,10 +285,15 @@ impl Type {
         match self {
             Type::Array(_) => -1,
             Type::Bool => 1,
+            Type::BpChar => -1,
             Type::Bytea => -1,
+            Type::Cidr => -1,
             Type::Date => 4,
             Type::Float4 => 4,
             Type::Float8 => 8,
+            Type::Geography => -1,
+            Type::Geometry => -1,
+            Type::Inet => -1,
             Type::Int4 => 4,
             Type::Int8 => 8,
             Type::Interval => 16,
@@ -213,12 +302,16 @@ impl Type {
             Type::Map { .. } => -1,
             Type::Numeric => -1,
             Type::Oid => 4,
+            Type::Range(_) => -1,
             Type::Record(_) => -1,
             Type::Text => -1,
             Type::Time => 4,
             Type::Timestamp => 8,
             Type::TimestampTz => 8,
+            Type::TsQuery => -1,
+            Type::TsVector => -1,
             Type::Uuid => 16,
+            Type::VarChar => -1,
         }
     }
 
@@ -232,10 +325,15 @@ impl Type {
         match self {
             Type::Array(t) => ScalarType::Array(Box::new(t.to_scalar_type_lossy())),
             Type::Bool => ScalarType::Bool,
+            Type::BpChar => ScalarType::Char { length: None },
             Type::Bytea => ScalarType::Bytes,
+            Type::Cidr => ScalarType::Cidr,
             Type::Date => ScalarType::Date,
             Type::Float4 => ScalarType::Float32,
             Type::Float8 => ScalarType::Float64,
+            Type::Geography => ScalarType::Geography,
+            Type::Geometry => ScalarType::Geometry,
+            Type::Inet => ScalarType::Inet,
             Type::Int4 => ScalarType::Int32,
             Type::Int8 => ScalarType::Int64,
             Type::Interval => ScalarType::Interval,
@@ -250,6 +348,7 @@ impl Type {
             },
             Type::Numeric => ScalarType::Decimal(0, 0),
             Type::Oid => ScalarType::Oid,
+            Type::Range(t) => ScalarType::Range(Box::new(t.to_scalar_type_lossy())),
             Type::Record(_) => ScalarType::Record {
                 fields: vec![],
                 custom_oid: None,
@@ -259,7 +358,10 @@ impl Type {
             Type::Time => ScalarType::Time,
             Type::Timestamp => ScalarType::Timestamp,
             Type::TimestampTz => ScalarType::TimestampTz,
+            Type::TsQuery => ScalarType::TsQuery,
+            Type::TsVector => ScalarType::TsVector,
             Type::Uuid => ScalarType::Uuid,
+            Type::VarChar => ScalarType::VarChar { length: None },
         }
     }
 }
@@ -270,10 +372,14 @@ impl From<&ScalarType> for Type {
             ScalarType::Array(t) => Type::Array(Box::new(From::from(&**t))),
             ScalarType::Bool => Type::Bool,
             ScalarType::Bytes => Type::Bytea,
+            ScalarType::Cidr => Type::Cidr,
             ScalarType::Date => Type::Date,
             ScalarType::Decimal(_, _) => Type::Numeric,
             ScalarType::Float64 => Type::Float8,
             ScalarType::Float32 => Type::Float4,
+            ScalarType::Geography => Type::Geography,
+            ScalarType::Geometry => Type::Geometry,
+            ScalarType::Inet => Type::Inet,
             ScalarType::Int32 => Type::Int4,
             ScalarType::Int64 => Type::Int8,
             ScalarType::Interval => Type::Interval,
@@ -285,6 +391,7 @@ impl From<&ScalarType> for Type {
                 value_type: Box::new(From::from(&**value_type)),
             },
             ScalarType::Oid => Type::Oid,
+            ScalarType::Range(t) => Type::Range(Box::new(From::from(&**t))),
             ScalarType::Record { fields, .. } => Type::Record(
                 fields
                     .iter()
@@ -292,10 +399,41 @@ impl From<&ScalarType> for Type {
                     .collect(),
             ),
             ScalarType::String => Type::Text,
+            ScalarType::Char { .. } => Type::BpChar,
+            ScalarType::VarChar { .. } => Type::VarChar,
             ScalarType::Time => Type::Time,
             ScalarType::Timestamp => Type::Timestamp,
             ScalarType::TimestampTz => Type::TimestampTz,
+            ScalarType::TsQuery => Type::TsQuery,
+            ScalarType::TsVector => Type::TsVector,
             ScalarType::Uuid => Type::Uuid,
         }
     }
 }
+
+/// Returns the PostgreSQL type modifier for `typ`, or -1 if the type has no
+/// modifier.
+///
+/// The type modifier is reported in the `RowDescription` message and in the
+/// `atttypmod` column of `pg_attribute`.
+pub fn typmod(typ: &ScalarType) -> i32 {
+    match typ {
+        // NUMERIC types pack their precision and size into the type modifier.
+        // The high order bits store the precision while the low order bits
+        // store the scale + 4 (!).
+        //
+        // https://github.com/postgres/postgres/blob/e435c1e7d/src/backend/utils/adt/numeric.c#L6364-L6367
+        ScalarType::Decimal(precision, scale) => {
+            ((i32::from(*precision) << 16) | i32::from(*scale)) + 4
+        }
+        // Character types store their length + 4, the size of the header of a
+        // variable-length datum.
+        ScalarType::Char {
+            length: Some(length),
+        }
+        | ScalarType::VarChar {
+            length: Some(length),
+        } => i32::try_from(*length).expect("character length fits in i32") + 4,
+        _ => -1,
+    }
+}
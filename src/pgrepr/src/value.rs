@@ -8,9 +8,10 @@
 // by the Apache License, Version 2.0.
 
 use std::collections::BTreeMap;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::error::Error;
 use std::io;
+use std::net::IpAddr;
 use std::str;
 
 use bytes::{BufMut, BytesMut};
@@ -22,7 +23,10 @@ use uuid::Uuid;
 use ore::fmt::FormatBuffer;
 use repr::adt::array::ArrayDimension;
 use repr::adt::decimal::MAX_DECIMAL_PRECISION;
+use repr::adt::geo::{self, Shape};
+use repr::adt::inet::Inet;
 use repr::adt::jsonb::JsonbRef;
+use repr::adt::range::RangeBound;
 use repr::strconv::{self, Nestable};
 use repr::{ColumnName, Datum, RelationType, Row, RowArena, RowPacker, ScalarType};
 
@@ -45,14 +49,24 @@ pub enum Value {
     },
     /// A boolean value.
     Bool(bool),
+    /// A fixed-length string, padded with spaces.
+    BpChar(String),
     /// A byte array, i.e., a variable-length binary string.
     Bytea(Vec<u8>),
+    /// An IPv4 or IPv6 network.
+    Cidr(Inet),
     /// A date.
     Date(NaiveDate),
     /// A 4-byte floating point number.
     Float4(f32),
     /// An 8-byte floating point number.
     Float8(f64),
+    /// A spatial object on the surface of the Earth, with its SRID.
+    Geography(i32, Shape),
+    /// A spatial object in a planar coordinate system, with its SRID.
+    Geometry(i32, Shape),
+    /// An IPv4 or IPv6 host address, with an optional subnet mask.
+    Inet(Inet),
     /// A 4-byte signed integer.
     Int4(i32),
     /// An 8-byte signed integer.
@@ -67,6 +81,8 @@ pub enum Value {
     Map(BTreeMap<String, Option<Value>>),
     /// An arbitrary precision number.
     Numeric(Numeric),
+    /// A contiguous range of values, or `None` if the range is empty.
+    Range(Option<(RangeBound<Box<Value>>, RangeBound<Box<Value>>)>),
     /// A sequence of heterogeneous values.
     Record(Vec<Option<Value>>),
     /// A time.
@@ -77,8 +93,14 @@ pub enum Value {
     TimestampTz(DateTime<Utc>),
     /// A variable-length string.
     Text(String),
+    /// A query for full-text search, in its canonical text form.
+    TsQuery(String),
+    /// A document for full-text search, in its canonical text form.
+    TsVector(String),
     /// A universally unique identifier.
     Uuid(Uuid),
+    /// A variable-length string with an optional length limit.
+    VarChar(String),
 }
 
 impl Value {
@@ -106,10 +128,22 @@ impl Value {
             (Datum::Interval(iv), ScalarType::Interval) => Some(Value::Interval(Interval(iv))),
             (Datum::Bytes(b), ScalarType::Bytes) => Some(Value::Bytea(b.to_vec())),
             (Datum::String(s), ScalarType::String) => Some(Value::Text(s.to_owned())),
+            (Datum::String(s), ScalarType::Char { .. }) => Some(Value::BpChar(s.to_owned())),
+            (Datum::String(s), ScalarType::VarChar { .. }) => Some(Value::VarChar(s.to_owned())),
+            (Datum::String(s), ScalarType::TsQuery) => Some(Value::TsQuery(s.to_owned())),
+            (Datum::String(s), ScalarType::TsVector) => Some(Value::TsVector(s.to_owned())),
             (_, ScalarType::Jsonb) => {
                 Some(Value::Jsonb(Jsonb(JsonbRef::from_datum(datum).to_owned())))
             }
             (Datum::Uuid(u), ScalarType::Uuid) => Some(Value::Uuid(u)),
+            (Datum::Inet(i), ScalarType::Inet) => Some(Value::Inet(i)),
+            (Datum::Inet(i), ScalarType::Cidr) => Some(Value::Cidr(i)),
+            (Datum::Geometry(g), ScalarType::Geometry) => {
+                Some(Value::Geometry(g.srid(), g.shape()))
+            }
+            (Datum::Geometry(g), ScalarType::Geography) => {
+                Some(Value::Geography(g.srid(), g.shape()))
+            }
             (Datum::Array(array), ScalarType::Array(elem_type)) => {
                 let dims = array.dims().into_iter().collect();
                 let elements = array
@@ -141,6 +175,16 @@ impl Value {
                     .collect();
                 Some(Value::Map(entries))
             }
+            (Datum::Range(range), ScalarType::Range(elem_type)) => {
+                let elem = |d| {
+                    let v = Value::from_datum(d, elem_type).expect("range bounds are not null");
+                    Box::new(v)
+                };
+                let bounds = range
+                    .bounds()
+                    .map(|(lower, upper)| (lower.map(elem), upper.map(elem)));
+                Some(Value::Range(bounds))
+            }
             _ => panic!("can't serialize {}::{:?}", datum, typ),
         }
     }
@@ -161,10 +205,24 @@ impl Value {
             }
             Value::Bool(true) => (Datum::True, ScalarType::Bool),
             Value::Bool(false) => (Datum::False, ScalarType::Bool),
+            Value::BpChar(s) => (
+                Datum::String(buf.push_string(s)),
+                ScalarType::Char { length: None },
+            ),
             Value::Bytea(b) => (Datum::Bytes(buf.push_bytes(b)), ScalarType::Bytes),
+            Value::Cidr(i) => (Datum::Inet(i), ScalarType::Cidr),
             Value::Date(d) => (Datum::Date(d), ScalarType::Date),
             Value::Float4(f) => (Datum::Float32(f.into()), ScalarType::Float32),
             Value::Float8(f) => (Datum::Float64(f.into()), ScalarType::Float64),
+            Value::Geography(srid, shape) => (
+                buf.make_datum(|packer| packer.push_geometry(srid, &shape)),
+                ScalarType::Geography,
+            ),
+            Value::Geometry(srid, shape) => (
+                buf.make_datum(|packer| packer.push_geometry(srid, &shape)),
+                ScalarType::Geometry,
+            ),
+            Value::Inet(i) => (Datum::Inet(i), ScalarType::Inet),
             Value::Int4(i) => match typ {
                 Type::Oid => (Datum::Int32(i), ScalarType::Int32),
                 Type::Int4 => (Datum::Int32(i), ScalarType::Int32),
@@ -219,6 +277,20 @@ impl Value {
                 Datum::from(d.0.significand()),
                 ScalarType::Decimal(MAX_DECIMAL_PRECISION, d.0.scale()),
             ),
+            Value::Range(bounds) => {
+                let elem_pg_type = match typ {
+                    Type::Range(t) => &*t,
+                    _ => panic!("Value::Range should have type Type::Range. Found {:?}", typ),
+                };
+                let bounds = bounds.map(|(lower, upper)| {
+                    let elem = |elem: Box<Value>| elem.into_datum(buf, &elem_pg_type).0;
+                    (lower.map(elem), upper.map(elem))
+                });
+                let datum = buf
+                    .try_make_datum(|packer| packer.push_range(bounds))
+                    .expect("Value::decode produces only valid ranges");
+                (datum, null_datum(typ).1)
+            }
             Value::Record(_) => {
                 // This situation is handled gracefully by Value::decode; if we
                 // wind up here it's a programming error.
@@ -229,7 +301,13 @@ impl Value {
             Value::TimestampTz(ts) => (Datum::TimestampTz(ts), ScalarType::TimestampTz),
             Value::Interval(iv) => (Datum::Interval(iv.0), ScalarType::Interval),
             Value::Text(s) => (Datum::String(buf.push_string(s)), ScalarType::String),
+            Value::TsQuery(s) => (Datum::String(buf.push_string(s)), ScalarType::TsQuery),
+            Value::TsVector(s) => (Datum::String(buf.push_string(s)), ScalarType::TsVector),
             Value::Uuid(u) => (Datum::Uuid(u), ScalarType::Uuid),
+            Value::VarChar(s) => (
+                Datum::String(buf.push_string(s)),
+                ScalarType::VarChar { length: None },
+            ),
         }
     }
 
@@ -259,7 +337,12 @@ impl Value {
             }
             Value::Bool(b) => strconv::format_bool(buf, *b),
             Value::Bytea(b) => strconv::format_bytes(buf, b),
+            Value::Cidr(i) => strconv::format_cidr(buf, *i),
             Value::Date(d) => strconv::format_date(buf, *d),
+            Value::Geography(srid, shape) | Value::Geometry(srid, shape) => {
+                strconv::format_geometry(buf, *srid, shape)
+            }
+            Value::Inet(i) => strconv::format_inet(buf, *i),
             Value::Int4(i) => strconv::format_int32(buf, *i),
             Value::Int8(i) => strconv::format_int64(buf, *i),
             Value::Interval(iv) => strconv::format_interval(buf, iv.0),
@@ -275,14 +358,22 @@ impl Value {
                 Some(elem) => elem.encode_text(buf.nonnull_buffer()),
             }),
             Value::Numeric(n) => strconv::format_decimal(buf, &n.0),
+            Value::Range(bounds) => strconv::format_range(
+                buf,
+                bounds
+                    .as_ref()
+                    .map(|(lower, upper)| (lower.as_ref(), upper.as_ref())),
+                |buf, elem| elem.encode_text(buf),
+            ),
             Value::Record(elems) => strconv::format_record(buf, elems, |buf, elem| match elem {
                 None => buf.write_null(),
                 Some(elem) => elem.encode_text(buf.nonnull_buffer()),
             }),
-            Value::Text(s) => strconv::format_string(buf, s),
+            Value::Text(s) | Value::BpChar(s) | Value::VarChar(s) => strconv::format_string(buf, s),
             Value::Time(t) => strconv::format_time(buf, *t),
             Value::Timestamp(ts) => strconv::format_timestamp(buf, *ts),
             Value::TimestampTz(ts) => strconv::format_timestamptz(buf, *ts),
+            Value::TsQuery(s) | Value::TsVector(s) => strconv::format_string(buf, s),
             Value::Uuid(u) => strconv::format_uuid(buf, *u),
         }
     }
@@ -312,9 +403,23 @@ impl Value {
             }
             Value::Bool(b) => b.to_sql(&PgType::BOOL, buf),
             Value::Bytea(b) => b.to_sql(&PgType::BYTEA, buf),
+            Value::Cidr(i) => {
+                encode_inet(buf, *i, true);
+                Ok(postgres_types::IsNull::No)
+            }
             Value::Date(d) => d.to_sql(&PgType::DATE, buf),
             Value::Float4(f) => f.to_sql(&PgType::FLOAT4, buf),
             Value::Float8(f) => f.to_sql(&PgType::FLOAT8, buf),
+            Value::Geography(srid, shape) | Value::Geometry(srid, shape) => {
+                let mut ewkb = vec![];
+                geo::encode_ewkb(&mut ewkb, *srid, shape);
+                buf.put_slice(&ewkb);
+                Ok(postgres_types::IsNull::No)
+            }
+            Value::Inet(i) => {
+                encode_inet(buf, *i, false);
+                Ok(postgres_types::IsNull::No)
+            }
             Value::Int4(i) => i.to_sql(&PgType::INT4, buf),
             Value::Int8(i) => i.to_sql(&PgType::INT8, buf),
             Value::Interval(iv) => iv.to_sql(&PgType::INTERVAL, buf),
@@ -330,6 +435,33 @@ impl Value {
                 Ok(postgres_types::IsNull::No)
             }
             Value::Numeric(n) => n.to_sql(&PgType::NUMERIC, buf),
+            Value::Range(bounds) => {
+                let elem_type = match ty {
+                    Type::Range(elem_type) => elem_type,
+                    _ => unreachable!(),
+                };
+                match bounds {
+                    None => buf.put_u8(RANGE_EMPTY),
+                    Some((lower, upper)) => {
+                        let mut flags = 0;
+                        match &lower.value {
+                            None => flags |= RANGE_LB_INF,
+                            Some(_) if lower.inclusive => flags |= RANGE_LB_INC,
+                            Some(_) => (),
+                        }
+                        match &upper.value {
+                            None => flags |= RANGE_UB_INF,
+                            Some(_) if upper.inclusive => flags |= RANGE_UB_INC,
+                            Some(_) => (),
+                        }
+                        buf.put_u8(flags);
+                        for value in lower.value.iter().chain(&upper.value) {
+                            encode_element(buf, Some(&**value), elem_type)?;
+                        }
+                    }
+                }
+                Ok(postgres_types::IsNull::No)
+            }
             Value::Record(fields) => {
                 let nfields = pg_len("record field length", fields.len())?;
                 buf.put_i32(nfields);
@@ -344,9 +476,16 @@ impl Value {
                 Ok(postgres_types::IsNull::No)
             }
             Value::Text(s) => s.to_sql(&PgType::TEXT, buf),
+            Value::BpChar(s) => s.to_sql(&PgType::BPCHAR, buf),
+            Value::VarChar(s) => s.to_sql(&PgType::VARCHAR, buf),
             Value::Time(t) => t.to_sql(&PgType::TIME, buf),
             Value::Timestamp(ts) => ts.to_sql(&PgType::TIMESTAMP, buf),
             Value::TimestampTz(ts) => ts.to_sql(&PgType::TIMESTAMPTZ, buf),
+            Value::TsQuery(_) | Value::TsVector(_) => {
+                // for now just use text encoding
+                self.encode_text(buf);
+                Ok(postgres_types::IsNull::No)
+            }
             Value::Uuid(u) => u.to_sql(&PgType::UUID, buf),
         }
         .expect("encode_binary should never trigger a to_sql failure");
@@ -377,9 +516,19 @@ impl Value {
             Type::Array(_) => return Err("input of array types is not implemented".into()),
             Type::Bool => Value::Bool(strconv::parse_bool(raw)?),
             Type::Bytea => Value::Bytea(strconv::parse_bytes(raw)?),
+            Type::Cidr => Value::Cidr(strconv::parse_cidr(raw)?),
             Type::Date => Value::Date(strconv::parse_date(raw)?),
             Type::Float4 => Value::Float4(strconv::parse_float32(raw)?),
             Type::Float8 => Value::Float8(strconv::parse_float64(raw)?),
+            Type::Geography => {
+                let (srid, shape) = strconv::parse_geography(raw)?;
+                Value::Geography(srid, shape)
+            }
+            Type::Geometry => {
+                let (srid, shape) = strconv::parse_geometry(raw)?;
+                Value::Geometry(srid, shape)
+            }
+            Type::Inet => Value::Inet(strconv::parse_inet(raw)?),
             Type::Int4 | Type::Oid => Value::Int4(strconv::parse_int32(raw)?),
             Type::Int8 => Value::Int8(strconv::parse_int64(raw)?),
             Type::Interval => Value::Interval(Interval(strconv::parse_interval(raw)?)),
@@ -396,13 +545,23 @@ impl Value {
                 |elem_text| Value::decode_text(value_type, elem_text.as_bytes()).map(Some),
             )?),
             Type::Numeric => Value::Numeric(Numeric(strconv::parse_decimal(raw)?)),
+            Type::Range(elem_type) => {
+                let bounds = strconv::parse_range(raw, |elem_text| {
+                    Value::decode_text(elem_type, elem_text.as_bytes()).map(Box::new)
+                })?;
+                canonicalize_range(elem_type, bounds)?
+            }
             Type::Record(_) => {
                 return Err("input of anonymous composite types is not implemented".into())
             }
             Type::Text => Value::Text(raw.to_owned()),
+            Type::BpChar => Value::BpChar(raw.to_owned()),
+            Type::VarChar => Value::VarChar(raw.to_owned()),
             Type::Time => Value::Time(strconv::parse_time(raw)?),
             Type::Timestamp => Value::Timestamp(strconv::parse_timestamp(raw)?),
             Type::TimestampTz => Value::TimestampTz(strconv::parse_timestamptz(raw)?),
+            Type::TsQuery => Value::TsQuery(strconv::parse_tsquery(raw)?.to_string()),
+            Type::TsVector => Value::TsVector(strconv::parse_tsvector(raw)?.to_string()),
             Type::Uuid => Value::Uuid(Uuid::parse_str(raw)?),
         })
     }
@@ -414,9 +573,23 @@ impl Value {
             Type::Array(_) => Err("input of array types is not implemented".into()),
             Type::Bool => bool::from_sql(ty.inner(), raw).map(Value::Bool),
             Type::Bytea => Vec::<u8>::from_sql(ty.inner(), raw).map(Value::Bytea),
+            Type::Cidr => match decode_inet(raw)? {
+                i if i.is_network() => Ok(Value::Cidr(i)),
+                _ => Err("invalid external \"cidr\" value".into()),
+            },
             Type::Date => chrono::NaiveDate::from_sql(ty.inner(), raw).map(Value::Date),
             Type::Float4 => f32::from_sql(ty.inner(), raw).map(Value::Float4),
             Type::Float8 => f64::from_sql(ty.inner(), raw).map(Value::Float8),
+            Type::Geography => {
+                let (srid, shape) = geo::decode_ewkb(raw)?;
+                let srid = geo::check_geography(srid, &shape)?;
+                Ok(Value::Geography(srid, shape))
+            }
+            Type::Geometry => {
+                let (srid, shape) = geo::decode_ewkb(raw)?;
+                Ok(Value::Geometry(srid, shape))
+            }
+            Type::Inet => decode_inet(raw).map(Value::Inet),
             Type::Int4 | Type::Oid => i32::from_sql(ty.inner(), raw).map(Value::Int4),
             Type::Int8 => i64::from_sql(ty.inner(), raw).map(Value::Int8),
             Type::Interval => Interval::from_sql(ty.inner(), raw).map(Value::Interval),
@@ -424,11 +597,16 @@ impl Value {
             Type::List(_) => Value::decode_text(ty, raw), // just using the text encoding for now
             Type::Map { .. } => Value::decode_text(ty, raw), // just using the text encoding for now
             Type::Numeric => Numeric::from_sql(ty.inner(), raw).map(Value::Numeric),
+            Type::Range(elem_type) => decode_range(elem_type, raw),
             Type::Record(_) => Err("input of anonymous composite types is not implemented".into()),
             Type::Text => String::from_sql(ty.inner(), raw).map(Value::Text),
+            Type::BpChar => String::from_sql(ty.inner(), raw).map(Value::BpChar),
+            Type::VarChar => String::from_sql(ty.inner(), raw).map(Value::VarChar),
             Type::Time => NaiveTime::from_sql(ty.inner(), raw).map(Value::Time),
             Type::Timestamp => NaiveDateTime::from_sql(ty.inner(), raw).map(Value::Timestamp),
             Type::TimestampTz => DateTime::<Utc>::from_sql(ty.inner(), raw).map(Value::TimestampTz),
+            Type::TsQuery => Value::decode_text(ty, raw), // just using the text encoding for now
+            Type::TsVector => Value::decode_text(ty, raw), // just using the text encoding for now
             Type::Uuid => Uuid::from_sql(ty.inner(), raw).map(Value::Uuid),
         }
     }
@@ -448,6 +626,126 @@ fn encode_element(buf: &mut BytesMut, elem: Option<&Value>, ty: &Type) -> Result
     Ok(())
 }
 
+// See the `range_send` function in PostgreSQL's rangetypes.c for details on
+// the encoding.
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LB_INC: u8 = 0x02;
+const RANGE_UB_INC: u8 = 0x04;
+const RANGE_LB_INF: u8 = 0x08;
+const RANGE_UB_INF: u8 = 0x10;
+
+fn decode_range(elem_type: &Type, raw: &[u8]) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    let (flags, mut raw) = match raw.split_first() {
+        Some((flags, raw)) => (*flags, raw),
+        None => return Err("missing flags in external range value".into()),
+    };
+    if flags & RANGE_EMPTY != 0 {
+        return canonicalize_range(elem_type, None);
+    }
+    let lower = decode_range_bound(
+        elem_type,
+        &mut raw,
+        flags & RANGE_LB_INF != 0,
+        flags & RANGE_LB_INC != 0,
+    )?;
+    let upper = decode_range_bound(
+        elem_type,
+        &mut raw,
+        flags & RANGE_UB_INF != 0,
+        flags & RANGE_UB_INC != 0,
+    )?;
+    canonicalize_range(elem_type, Some((lower, upper)))
+}
+
+/// Decodes the next bound of an external range value from `raw`, advancing
+/// `raw` past the bound.
+fn decode_range_bound(
+    elem_type: &Type,
+    raw: &mut &[u8],
+    unbounded: bool,
+    inclusive: bool,
+) -> Result<RangeBound<Box<Value>>, Box<dyn Error + Sync + Send>> {
+    if unbounded {
+        return Ok(RangeBound {
+            value: None,
+            inclusive: false,
+        });
+    }
+    let bytes: &[u8] = *raw;
+    let (len, rest) = match bytes {
+        [a, b, c, d, rest @ ..] => (i32::from_be_bytes([*a, *b, *c, *d]), rest),
+        _ => return Err("missing bound in external range value".into()),
+    };
+    let len = match usize::try_from(len) {
+        Ok(len) if len <= rest.len() => len,
+        _ => return Err("invalid bound length in external range value".into()),
+    };
+    let (elem, rest) = rest.split_at(len);
+    *raw = rest;
+    Ok(RangeBound {
+        value: Some(Box::new(Value::decode_binary(elem_type, elem)?)),
+        inclusive,
+    })
+}
+
+/// Constructs a range value with the specified bounds in canonical form.
+///
+/// Returns an error if the bounds do not describe a valid range, e.g.
+/// because the lower bound is greater than the upper bound.
+fn canonicalize_range(
+    elem_type: &Type,
+    bounds: Option<(RangeBound<Box<Value>>, RangeBound<Box<Value>>)>,
+) -> Result<Value, Box<dyn Error + Sync + Send>> {
+    let arena = RowArena::new();
+    let bounds = bounds.map(|(lower, upper)| {
+        let elem = |elem: Box<Value>| elem.into_datum(&arena, elem_type).0;
+        (lower.map(elem), upper.map(elem))
+    });
+    let datum = arena.try_make_datum(|packer| packer.push_range(bounds))?;
+    let (_, scalar_type) = null_datum(&Type::Range(Box::new(elem_type.clone())));
+    Ok(Value::from_datum(datum, &scalar_type).expect("ranges are never null"))
+}
+
+// See the `network_send` function in PostgreSQL's network.c for details on
+// the encoding.
+const PGSQL_AF_INET: u8 = 2;
+const PGSQL_AF_INET6: u8 = 3;
+
+fn encode_inet(buf: &mut BytesMut, inet: Inet, is_cidr: bool) {
+    let family = match inet.addr() {
+        IpAddr::V4(_) => PGSQL_AF_INET,
+        IpAddr::V6(_) => PGSQL_AF_INET6,
+    };
+    buf.put_u8(family);
+    buf.put_u8(inet.prefix_len());
+    buf.put_u8(is_cidr.into());
+    match inet.addr() {
+        IpAddr::V4(addr) => {
+            buf.put_u8(4);
+            buf.put_slice(&addr.octets());
+        }
+        IpAddr::V6(addr) => {
+            buf.put_u8(16);
+            buf.put_slice(&addr.octets());
+        }
+    }
+}
+
+fn decode_inet(raw: &[u8]) -> Result<Inet, Box<dyn Error + Sync + Send>> {
+    let (family, prefix_len, addr) = match raw {
+        [family, prefix_len, _is_cidr, len, addr @ ..] if usize::from(*len) == addr.len() => {
+            (*family, *prefix_len, addr)
+        }
+        _ => return Err("invalid length in external \"inet\" value".into()),
+    };
+    let addr = match (family, addr.len()) {
+        (PGSQL_AF_INET, 4) => IpAddr::from(<[u8; 4]>::try_from(addr)?),
+        (PGSQL_AF_INET6, 16) => IpAddr::from(<[u8; 16]>::try_from(addr)?),
+        _ => return Err("invalid address family in external \"inet\" value".into()),
+    };
+    Inet::new(addr, prefix_len).ok_or_else(|| "invalid bits in external \"inet\" value".into())
+}
+
 fn pg_len(what: &str, len: usize) -> Result<i32, io::Error> {
     len.try_into().map_err(|_| {
         io::Error::new(
@@ -466,9 +764,13 @@ pub fn null_datum(ty: &Type) -> (Datum<'static>, ScalarType) {
         }
         Type::Bool => ScalarType::Bool,
         Type::Bytea => ScalarType::Bytes,
+        Type::Cidr => ScalarType::Cidr,
         Type::Date => ScalarType::Date,
         Type::Float4 => ScalarType::Float32,
         Type::Float8 => ScalarType::Float64,
+        Type::Geography => ScalarType::Geography,
+        Type::Geometry => ScalarType::Geometry,
+        Type::Inet => ScalarType::Inet,
         Type::Int4 => ScalarType::Int32,
         Type::Int8 => ScalarType::Int64,
         Type::Interval => ScalarType::Interval,
@@ -482,10 +784,18 @@ pub fn null_datum(ty: &Type) -> (Datum<'static>, ScalarType) {
         }
         Type::Numeric => ScalarType::Decimal(MAX_DECIMAL_PRECISION, 0),
         Type::Oid => ScalarType::Oid,
+        Type::Range(t) => {
+            let (_, elem_type) = null_datum(t);
+            ScalarType::Range(Box::new(elem_type))
+        }
         Type::Text => ScalarType::String,
+        Type::BpChar => ScalarType::Char { length: None },
+        Type::VarChar => ScalarType::VarChar { length: None },
         Type::Time => ScalarType::Time,
         Type::Timestamp => ScalarType::Timestamp,
         Type::TimestampTz => ScalarType::TimestampTz,
+        Type::TsQuery => ScalarType::TsQuery,
+        Type::TsVector => ScalarType::TsVector,
         Type::Uuid => ScalarType::Uuid,
         Type::Record(fields) => {
             let fields = fields
@@ -529,3 +839,71 @@ pub fn values_from_row(row: Row, typ: &RelationType) -> Vec<Option<Value>> {
         .map(|(col, typ)| Value::from_datum(col, &typ.scalar_type))
         .collect()
 }
+
+/// Serializes a Materialize datum of type `typ` to `buf` in the specified
+/// `format`.
+///
+/// Calling this function is equivalent to converting the datum with
+/// [`Value::from_datum`] and serializing the result with [`Value::encode`],
+/// but avoids allocating an intermediate `Value` for the common scalar types.
+///
+/// Panics if `datum` is `Datum::Null`, which PostgreSQL does not encode.
+pub fn encode_datum(
+    datum: Datum,
+    typ: &ScalarType,
+    format: Format,
+    buf: &mut BytesMut,
+) -> Result<(), io::Error> {
+    match (format, datum, typ) {
+        (_, Datum::Null, _) => panic!("encode_datum impossibly called on a null datum"),
+        (Format::Text, Datum::True, ScalarType::Bool) => {
+            strconv::format_bool(buf, true);
+        }
+        (Format::Text, Datum::False, ScalarType::Bool) => {
+            strconv::format_bool(buf, false);
+        }
+        (Format::Text, Datum::Int32(i), ScalarType::Int32)
+        | (Format::Text, Datum::Int32(i), ScalarType::Oid) => {
+            strconv::format_int32(buf, i);
+        }
+        (Format::Text, Datum::Int64(i), ScalarType::Int64) => {
+            strconv::format_int64(buf, i);
+        }
+        (Format::Text, Datum::Float32(f), ScalarType::Float32) => {
+            strconv::format_float32(buf, *f);
+        }
+        (Format::Text, Datum::Float64(f), ScalarType::Float64) => {
+            strconv::format_float64(buf, *f);
+        }
+        (Format::Text, Datum::Bytes(b), ScalarType::Bytes) => {
+            strconv::format_bytes(buf, b);
+        }
+        (_, Datum::String(s), ScalarType::String)
+        | (_, Datum::String(s), ScalarType::Char { .. })
+        | (_, Datum::String(s), ScalarType::VarChar { .. }) => buf.put_slice(s.as_bytes()),
+        // Documents are serialized straight from the datum, as they can be
+        // arbitrarily large and are not worth copying.
+        (Format::Text, _, ScalarType::Jsonb) => {
+            strconv::format_jsonb(buf, JsonbRef::from_datum(datum));
+        }
+        (Format::Binary, _, ScalarType::Jsonb) => {
+            buf.put_u8(1); // version
+            JsonbRef::from_datum(datum)
+                .to_writer(buf.writer())
+                .expect("encode_datum should never trigger a jsonb serialization failure");
+        }
+        (Format::Binary, Datum::True, ScalarType::Bool) => buf.put_u8(1),
+        (Format::Binary, Datum::False, ScalarType::Bool) => buf.put_u8(0),
+        (Format::Binary, Datum::Int32(i), ScalarType::Int32)
+        | (Format::Binary, Datum::Int32(i), ScalarType::Oid) => buf.put_i32(i),
+        (Format::Binary, Datum::Int64(i), ScalarType::Int64) => buf.put_i64(i),
+        (Format::Binary, Datum::Float32(f), ScalarType::Float32) => buf.put_f32(*f),
+        (Format::Binary, Datum::Float64(f), ScalarType::Float64) => buf.put_f64(*f),
+        (Format::Binary, Datum::Bytes(b), ScalarType::Bytes) => buf.put_slice(b),
+        _ => {
+            let value = Value::from_datum(datum, typ).expect("datum is not null");
+            value.encode(&Type::from(typ), format, buf)?;
+        }
+    }
+    Ok(())
+}
@@ -14,25 +14,27 @@
 //!
 //! [1]: https://www.postgresql.org/docs/11/protocol-message-formats.html
 
-use std::collections::HashMap;
+use std::cmp;
+use std::collections::{HashMap, VecDeque};
 use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
+use std::io::IoSlice;
 use std::str;
 
 use async_trait::async_trait;
 use byteorder::{ByteOrder, NetworkEndian};
-use bytes::{Buf, BufMut, BytesMut};
-use futures::{sink, SinkExt, TryStreamExt};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use futures::TryStreamExt;
 use lazy_static::lazy_static;
 use log::trace;
 use prometheus::{register_uint_counter, UIntCounter};
-use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, Interest, Ready};
+use tokio::io::{self, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, Interest, Ready};
 use tokio_util::codec::{Decoder, Encoder, Framed};
 
 use ore::cast::CastFrom;
-use ore::future::OreSinkExt;
 use ore::netio::{self, AsyncReady};
+use repr::ScalarType;
 
 use crate::message::{
     BackendMessage, ErrorResponse, FrontendMessage, FrontendStartupMessage, TransactionStatus,
@@ -48,6 +50,25 @@ lazy_static! {
     .unwrap();
 }
 
+/// The size at which the write buffer is split off into a chunk that is
+/// queued for writing.
+const CHUNK_SIZE: usize = 64 << 10;
+
+/// The number of queued bytes at which the queued chunks are written to the
+/// connection, even if the connection has not been flushed.
+const MAX_QUEUED_SIZE: usize = 1 << 20;
+
+/// The maximum number of chunks to pass to a single vectored write.
+const MAX_WRITE_CHUNKS: usize = 64;
+
+/// The maximum size of a message from the client once the connection has
+/// started up.
+///
+/// Queries and bound parameters can carry very large values, like multi-megabyte
+/// documents, so this matches PostgreSQL's limit on such messages rather than
+/// the much smaller limit on startup messages.
+const MAX_MESSAGE_SIZE: usize = 1 << 30;
+
 pub const REJECT_ENCRYPTION: u8 = b'N';
 pub const ACCEPT_SSL_ENCRYPTION: u8 = b'S';
 
@@ -67,9 +88,18 @@ impl fmt::Display for CodecError {
 }
 
 /// A connection that manages the encoding and decoding of pgwire frames.
+///
+/// Backend messages are encoded directly into a reusable write buffer. Full
+/// chunks of the buffer are queued, and the queue is written to the connection
+/// with vectored writes when it grows large or when the connection is flushed,
+/// so that sending many small messages, like the rows of a large result, costs
+/// neither an allocation nor a system call per message.
 pub struct FramedConn<A> {
     conn_id: u32,
-    inner: sink::Buffer<Framed<Conn<A>, Codec>, BackendMessage>,
+    inner: Framed<Conn<A>, Codec>,
+    write_buf: BytesMut,
+    queued: VecDeque<Bytes>,
+    queued_size: usize,
 }
 
 impl<A> FramedConn<A>
@@ -87,7 +117,10 @@ where
     pub fn new(conn_id: u32, inner: Conn<A>) -> FramedConn<A> {
         FramedConn {
             conn_id,
-            inner: Framed::new(inner, Codec::new()).buffer(32),
+            inner: Framed::new(inner, Codec::new()),
+            write_buf: BytesMut::with_capacity(CHUNK_SIZE),
+            queued: VecDeque::new(),
+            queued_size: 0,
         }
     }
 
@@ -121,7 +154,16 @@ where
     {
         let message = message.into();
         trace!("cid={} send={:?}", self.conn_id, message);
-        Ok(self.inner.enqueue(message).await?)
+        self.inner
+            .codec_mut()
+            .encode(message, &mut self.write_buf)?;
+        if self.write_buf.len() >= CHUNK_SIZE {
+            self.queue_write_buf();
+            if self.queued_size >= MAX_QUEUED_SIZE {
+                self.write_queued().await?;
+            }
+        }
+        Ok(())
     }
 
     /// Encodes and sends the backend messages in the `messages` iterator to the
@@ -144,7 +186,54 @@ where
 
     /// Flushes all outstanding messages.
     pub async fn flush(&mut self) -> Result<(), io::Error> {
-        self.inner.flush().await
+        self.queue_write_buf();
+        self.write_queued().await?;
+        self.inner.get_mut().flush().await
+    }
+
+    /// Moves the contents of the write buffer to the back of the write queue.
+    ///
+    /// The write buffer keeps its allocation, which it reclaims once the
+    /// queued chunk has been written and dropped.
+    fn queue_write_buf(&mut self) {
+        if !self.write_buf.is_empty() {
+            let chunk = self.write_buf.split().freeze();
+            self.queued_size += chunk.len();
+            self.queued.push_back(chunk);
+        }
+        self.write_buf.reserve(CHUNK_SIZE);
+    }
+
+    /// Writes the queued chunks to the connection.
+    async fn write_queued(&mut self) -> Result<(), io::Error> {
+        let conn = self.inner.get_mut();
+        while !self.queued.is_empty() {
+            let mut n = {
+                let slices: Vec<_> = self
+                    .queued
+                    .iter()
+                    .take(MAX_WRITE_CHUNKS)
+                    .map(|chunk| IoSlice::new(chunk))
+                    .collect();
+                conn.write_vectored(&slices).await?
+            };
+            if n == 0 {
+                return Err(io::ErrorKind::WriteZero.into());
+            }
+            BYTES_SENT.inc_by(u64::cast_from(n));
+            self.queued_size -= n;
+            while n > 0 {
+                let chunk = self.queued.front_mut().expect("wrote more than was queued");
+                if n < chunk.len() {
+                    chunk.advance(n);
+                    n = 0;
+                } else {
+                    n -= chunk.len();
+                    self.queued.pop_front();
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Injects state that affects how certain backend messages are encoded.
@@ -155,8 +244,8 @@ where
     /// type information in the codec before sending any data row messages. This
     /// violates the abstraction boundary a bit but results in much better
     /// performance.
-    pub fn set_encode_state(&mut self, encode_state: Vec<(pgrepr::Type, pgrepr::Format)>) {
-        self.inner.get_mut().codec_mut().encode_state = encode_state;
+    pub fn set_encode_state(&mut self, encode_state: Vec<(ScalarType, pgrepr::Format)>) {
+        self.inner.codec_mut().encode_state = encode_state;
     }
 }
 
@@ -165,7 +254,7 @@ where
     A: AsyncRead + AsyncWrite + Unpin,
 {
     pub fn inner(&self) -> &Conn<A> {
-        self.inner.get_ref().get_ref()
+        self.inner.get_ref()
     }
 }
 
@@ -175,13 +264,13 @@ where
     A: AsyncRead + AsyncWrite + AsyncReady + Send + Sync + Unpin,
 {
     async fn ready(&self, interest: Interest) -> io::Result<Ready> {
-        self.inner.get_ref().get_ref().ready(interest).await
+        self.inner.get_ref().ready(interest).await
     }
 }
 
 struct Codec {
     decode_state: DecodeState,
-    encode_state: Vec<(pgrepr::Type, pgrepr::Format)>,
+    encode_state: Vec<(ScalarType, pgrepr::Format)>,
 }
 
 impl Codec {
@@ -269,13 +358,13 @@ impl Encoder<BackendMessage> for Codec {
                     dst.put_format_i16(f.format);
                 }
             }
-            BackendMessage::DataRow(fields) => {
-                dst.put_length_i16(fields.len())?;
-                for (f, (ty, format)) in fields.iter().zip(&self.encode_state) {
-                    if let Some(f) = f {
+            BackendMessage::DataRow(row) => {
+                dst.put_length_i16(self.encode_state.len())?;
+                for (datum, (typ, format)) in row.iter().zip(&self.encode_state) {
+                    if !datum.is_null() {
                         let base = dst.len();
                         dst.put_u32(0);
-                        f.encode(ty, *format, dst)?;
+                        pgrepr::encode_datum(datum, typ, *format, dst)?;
                         let len = dst.len() - base - 4;
                         let len = i32::try_from(len).map_err(|_| {
                             io::Error::new(
@@ -353,8 +442,6 @@ impl Encoder<BackendMessage> for Codec {
         }
 
         let len = dst.len() - base;
-        // TODO: consider finding some way to not do this per-row
-        BYTES_SENT.inc_by(u64::cast_from(dst.len() - base));
 
         // Overwrite length placeholder with true length.
         let len = i32::try_from(len).map_err(|_| {
@@ -418,7 +505,7 @@ where
         // surface the unexpected EOF.
         _ => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "early eof")),
     };
-    let frame_len = parse_frame_len(&frame_len)?;
+    let frame_len = parse_frame_len(&frame_len, netio::MAX_FRAME_SIZE)?;
 
     let mut buf = BytesMut::new();
     buf.resize(frame_len, b'0');
@@ -452,9 +539,9 @@ enum DecodeState {
     Data(u8, usize),
 }
 
-fn parse_frame_len(src: &[u8]) -> Result<usize, io::Error> {
+fn parse_frame_len(src: &[u8], max_size: usize) -> Result<usize, io::Error> {
     let n = usize::cast_from(NetworkEndian::read_u32(src));
-    if n > netio::MAX_FRAME_SIZE {
+    if n > max_size {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
             netio::FrameTooBig,
@@ -480,9 +567,12 @@ impl Decoder for Codec {
                         return Ok(None);
                     }
                     let msg_type = src[0];
-                    let frame_len = parse_frame_len(&src[1..])?;
+                    let frame_len = parse_frame_len(&src[1..], MAX_MESSAGE_SIZE)?;
                     src.advance(5);
-                    src.reserve(frame_len);
+                    // Don't trust the client's claimed length with a large
+                    // up-front allocation. Very large messages grow the
+                    // buffer as their data arrives instead.
+                    src.reserve(cmp::min(frame_len, CHUNK_SIZE));
                     self.decode_state = DecodeState::Data(msg_type, frame_len);
                 }
 
@@ -12,12 +12,12 @@ use std::convert::TryFrom;
 use std::io;
 
 use bytes::BytesMut;
-use coord::{CoordError, StartupMessage};
+use coord::{CoordError, Notice, StartupMessage};
 use itertools::Itertools;
 use postgres::error::SqlState;
 
 use coord::session::TransactionStatus as CoordTransactionStatus;
-use repr::{ColumnName, RelationDesc, RelationType, Row, ScalarType};
+use repr::{ColumnName, Datum, RelationDesc, RelationType, Row};
 
 // Pgwire protocol versions are represented as 32-bit integers, where the
 // high 16 bits represent the major version and the low 16 bits represent the
@@ -209,7 +209,9 @@ pub enum BackendMessage {
     EmptyQueryResponse,
     ReadyForQuery(TransactionStatus),
     RowDescription(Vec<FieldDescription>),
-    DataRow(Vec<Option<pgrepr::Value>>),
+    /// A row of data, encoded according to the column types and formats that
+    /// were last installed with `FramedConn::set_encode_state`.
+    DataRow(Row),
     ParameterStatus(&'static str, String),
     BackendKeyData {
         conn_id: u32,
@@ -312,17 +314,26 @@ impl ErrorResponse {
         // a various classes of uncategorized errors that use this error code
         // inappropriately.
         let code = match e {
+            CoordError::ArrangementQuotaExceeded { .. } => SqlState::CONFIGURATION_LIMIT_EXCEEDED,
             CoordError::Catalog(_) => SqlState::INTERNAL_ERROR,
+            CoordError::CheckViolation { .. } => SqlState::CHECK_VIOLATION,
             CoordError::ConstrainedParameter(_) => SqlState::INVALID_PARAMETER_VALUE,
+            CoordError::ConnectionQuotaExceeded { .. } => SqlState::TOO_MANY_CONNECTIONS,
+            CoordError::DataflowQuotaExceeded { .. } => SqlState::CONFIGURATION_LIMIT_EXCEEDED,
             CoordError::DuplicateCursor(_) => SqlState::DUPLICATE_CURSOR,
             CoordError::Eval(_) => SqlState::INTERNAL_ERROR,
             CoordError::IdExhaustionError => SqlState::INTERNAL_ERROR,
             CoordError::InvalidParameterType(_) => SqlState::INVALID_PARAMETER_VALUE,
+            CoordError::NotNullViolation(_) => SqlState::NOT_NULL_VIOLATION,
             CoordError::OperationProhibitsTransaction(_) => SqlState::ACTIVE_SQL_TRANSACTION,
             CoordError::OperationRequiresTransaction(_) => SqlState::NO_ACTIVE_SQL_TRANSACTION,
+            CoordError::Overloaded(_) => SqlState::INSUFFICIENT_RESOURCES,
+            CoordError::PeekQuotaExceeded { .. } => SqlState::CONFIGURATION_LIMIT_EXCEEDED,
             CoordError::ReadOnlyTransaction => SqlState::READ_ONLY_SQL_TRANSACTION,
             CoordError::ReadOnlyParameter(_) => SqlState::CANT_CHANGE_RUNTIME_PARAM,
+            CoordError::RoleConnectionQuotaExceeded { .. } => SqlState::TOO_MANY_CONNECTIONS,
             CoordError::SqlCatalog(_) => SqlState::INTERNAL_ERROR,
+            CoordError::TimestampCompacted { .. } => SqlState::SNAPSHOT_TOO_OLD,
             CoordError::Transform(_) => SqlState::INTERNAL_ERROR,
             CoordError::UnknownCursor(_) => SqlState::INVALID_CURSOR_NAME,
             CoordError::UnknownParameter(_) => SqlState::UNDEFINED_OBJECT,
@@ -355,6 +366,21 @@ impl ErrorResponse {
         }
     }
 
+    pub fn from_notice(notice: Notice) -> ErrorResponse {
+        let (severity, code) = match &notice {
+            Notice::Plan(_) => (Severity::Warning, SqlState::WARNING),
+            Notice::IndexFullScan { .. } => (Severity::Notice, SqlState::SUCCESSFUL_COMPLETION),
+        };
+        ErrorResponse {
+            severity,
+            code,
+            message: notice.to_string(),
+            detail: notice.detail(),
+            hint: notice.hint(),
+            position: None,
+        }
+    }
+
     pub fn with_position(mut self, position: usize) -> ErrorResponse {
         self.position = Some(position);
         self
@@ -426,16 +452,12 @@ pub fn encode_copy_row_binary(
 
     out.extend(&count.to_be_bytes());
     let mut buf = BytesMut::new();
-    for (field, typ) in row
-        .iter()
-        .zip(&typ.column_types)
-        .map(|(datum, typ)| (pgrepr::Value::from_datum(datum, &typ.scalar_type), typ))
-    {
-        match field {
-            None => out.extend(&NULL_BYTES),
-            Some(field) => {
+    for (datum, typ) in row.iter().zip(&typ.column_types) {
+        match datum {
+            Datum::Null => out.extend(&NULL_BYTES),
+            datum => {
                 buf.clear();
-                field.encode_binary(&pgrepr::Type::from(&typ.scalar_type), &mut buf)?;
+                pgrepr::encode_datum(datum, &typ.scalar_type, pgrepr::Format::Binary, &mut buf)?;
                 out.extend(
                     &i32::try_from(buf.len())
                         .map_err(|_| {
@@ -500,17 +522,7 @@ pub fn encode_row_description(
                 column_id: 0,
                 type_oid: pg_type.oid(),
                 type_len: pg_type.typlen(),
-                type_mod: match &typ.scalar_type {
-                    // NUMERIC types pack their precision and size into the
-                    // type_mod field. The high order bits store the precision
-                    // while the low order bits store the scale + 4 (!).
-                    //
-                    // https://github.com/postgres/postgres/blob/e435c1e7d/src/backend/utils/adt/numeric.c#L6364-L6367
-                    ScalarType::Decimal(precision, scale) => {
-                        ((i32::from(*precision) << 16) | i32::from(*scale)) + 4
-                    }
-                    _ => -1,
-                },
+                type_mod: pgrepr::typmod(&typ.scalar_type),
                 format: *format,
             }
         })
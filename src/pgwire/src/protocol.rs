@@ -7,7 +7,6 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::cmp;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::future::Future;
@@ -28,9 +27,10 @@ use tokio::time::{self, Duration, Instant};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 use coord::session::{
-    EndTransactionAction, Portal, PortalState, RowBatchStream, Session, TransactionStatus,
+    query_tag, EndTransactionAction, Portal, PortalState, RowBatchStream, Session,
+    TransactionStatus,
 };
-use coord::ExecuteResponse;
+use coord::{CoordError, ExecuteResponse};
 use dataflow_types::PeekResponse;
 use ore::cast::CastFrom;
 use ore::netio::AsyncReady;
@@ -169,8 +169,15 @@ where
 
     // Construct session.
     let mut session = Session::new(conn.id(), user);
+    let mut param_warnings = vec![];
     for (name, value) in params {
-        let _ = session.vars_mut().set(&name, &value);
+        // Unknown parameters are reported to the client as warnings once the
+        // connection is established. Other errors are ignored, as clients
+        // commonly send parameters like `client_encoding` whose values cannot
+        // be changed.
+        if let Err(e @ CoordError::UnknownParameter(_)) = session.vars_mut().set(&name, &value) {
+            param_warnings.push(ErrorResponse::from_coord(Severity::Warning, e));
+        }
     }
 
     // Register session with coordinator.
@@ -198,6 +205,7 @@ where
         for startup_message in startup.messages {
             buf.push(ErrorResponse::from_startup_message(startup_message).into());
         }
+        buf.extend(param_warnings.into_iter().map(BackendMessage::from));
         buf.push(BackendMessage::ReadyForQuery(session.transaction().into()));
         conn.send_all(buf).await?;
         conn.flush().await?;
@@ -247,7 +255,7 @@ where
     }
 
     async fn advance_ready(&mut self) -> Result<State, io::Error> {
-        let message = self.conn.recv().await?;
+        let message = self.recv().await?;
         let timer = Instant::now();
         let name = match &message {
             Some(message) => message.name(),
@@ -320,21 +328,61 @@ where
     }
 
     async fn advance_drain(&mut self) -> Result<State, io::Error> {
-        match self.conn.recv().await? {
+        match self.recv().await? {
             Some(FrontendMessage::Sync) => self.sync().await,
             None => Ok(State::Done),
             _ => Ok(State::Drain),
         }
     }
 
-    async fn one_query(&mut self, stmt: Statement<Raw>) -> Result<State, io::Error> {
+    /// Receives the next message from the client.
+    ///
+    /// If the client remains idle for longer than the session's
+    /// `idle_in_transaction_session_timeout` or `idle_session_timeout` permits,
+    /// whichever applies to the current transaction status, the client is sent
+    /// a fatal error and the connection is treated as closed.
+    async fn recv(&mut self) -> Result<Option<FrontendMessage>, io::Error> {
+        let session = self.coord_client.session();
+        let (timeout, code, msg) = match session.transaction() {
+            TransactionStatus::InTransaction(_) | TransactionStatus::Failed => (
+                session.vars().idle_in_transaction_session_timeout(),
+                SqlState::IDLE_IN_TRANSACTION_SESSION_TIMEOUT,
+                "terminating connection due to idle-in-transaction timeout",
+            ),
+            _ => (
+                session.vars().idle_session_timeout(),
+                // The `postgres` crate does not yet know about this error
+                // code, which is new in PostgreSQL 14.
+                SqlState::from_code("57P05"),
+                "terminating connection due to idle-session timeout",
+            ),
+        };
+        let timeout = match timeout {
+            None => return self.conn.recv().await,
+            Some(timeout) => timeout,
+        };
+        match time::timeout(timeout, self.conn.recv()).await {
+            Ok(message) => message,
+            Err(_) => {
+                self.conn.send(ErrorResponse::fatal(code, msg)).await?;
+                self.conn.flush().await?;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn one_query(
+        &mut self,
+        stmt: Statement<Raw>,
+        tag: Option<String>,
+    ) -> Result<State, io::Error> {
         // Bind the portal. Note that this does not set the empty string prepared
         // statement.
         let param_types = vec![];
         const EMPTY_PORTAL: &str = "";
         if let Err(e) = self
             .coord_client
-            .declare(EMPTY_PORTAL.to_string(), stmt.clone(), param_types)
+            .declare(EMPTY_PORTAL.to_string(), stmt.clone(), param_types, tag)
             .await
         {
             return self
@@ -369,7 +417,9 @@ where
             }
         }
 
-        let result = match self.coord_client.execute(EMPTY_PORTAL.to_string()).await {
+        let response = self.coord_client.execute(EMPTY_PORTAL.to_string()).await;
+        self.send_notices().await?;
+        let result = match response {
             Ok(response) => {
                 self.send_execute_response(
                     response,
@@ -408,6 +458,7 @@ where
         };
 
         let num_stmts = stmts.len();
+        let tag = query_tag::extract(&sql);
 
         // Compare with postgres' backend/tcop/postgres.c exec_simple_query.
         for stmt in stmts {
@@ -428,7 +479,7 @@ where
                 .session()
                 .start_transaction_implicit(num_stmts);
 
-            match self.one_query(stmt).await? {
+            match self.one_query(stmt, tag.clone()).await? {
                 State::Ready => (),
                 State::Drain => break,
                 State::Done => return Ok(State::Done),
@@ -490,7 +541,7 @@ where
         }
         match self
             .coord_client
-            .describe(name, maybe_stmt, param_types)
+            .describe(name, maybe_stmt, param_types, query_tag::extract(&sql))
             .await
         {
             Ok(()) => {
@@ -597,12 +648,16 @@ where
         };
 
         let desc = stmt.desc().clone();
+        let tag = stmt.tag().map(String::from);
         let stmt = stmt.sql().cloned();
-        if let Err(err) =
-            self.coord_client
-                .session()
-                .set_portal(portal_name, desc, stmt, params, result_formats)
-        {
+        if let Err(err) = self.coord_client.session().set_portal(
+            portal_name,
+            desc,
+            stmt,
+            params,
+            result_formats,
+            tag,
+        ) {
             return self
                 .error(ErrorResponse::from_coord(Severity::Error, err))
                 .await;
@@ -653,7 +708,9 @@ where
                     // Postgres).
                     self.coord_client.session().start_transaction_implicit(1);
 
-                    match self.coord_client.execute(portal_name.clone()).await {
+                    let response = self.coord_client.execute(portal_name.clone()).await;
+                    self.send_notices().await?;
+                    match response {
                         Ok(response) => {
                             self.send_execute_response(
                                 response,
@@ -867,6 +924,15 @@ where
         self.flush().await
     }
 
+    /// Sends the notices that the coordinator has queued on the session.
+    async fn send_notices(&mut self) -> Result<(), io::Error> {
+        let notices = self.coord_client.session().drain_notices();
+        for notice in notices {
+            self.conn.send(ErrorResponse::from_notice(notice)).await?;
+        }
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn send_execute_response(
         &mut self,
@@ -930,6 +996,9 @@ where
             ExecuteResponse::CreatedView { existed } => {
                 created!(existed, SqlState::DUPLICATE_OBJECT, "view")
             }
+            ExecuteResponse::CreatedTask { existed } => {
+                created!(existed, SqlState::DUPLICATE_OBJECT, "task")
+            }
             ExecuteResponse::CreatedType => command_complete!("CREATE TYPE"),
             ExecuteResponse::DeclaredCursor => {
                 self.complete_portal(&portal_name);
@@ -947,6 +1016,7 @@ where
             ExecuteResponse::DroppedTable => command_complete!("DROP TABLE"),
             ExecuteResponse::DroppedView => command_complete!("DROP VIEW"),
             ExecuteResponse::DroppedType => command_complete!("DROP TYPE"),
+            ExecuteResponse::DroppedTask => command_complete!("DROP TASK"),
             ExecuteResponse::EmptyQuery => {
                 self.conn.send(BackendMessage::EmptyQueryResponse).await?;
                 Ok(State::Ready)
@@ -978,31 +1048,16 @@ where
             ExecuteResponse::SendingRows(rx) => {
                 let row_desc =
                     row_desc.expect("missing row description for ExecuteResponse::SendingRows");
-                match rx.await {
-                    PeekResponse::Canceled => {
-                        self.error(ErrorResponse::error(
-                            SqlState::QUERY_CANCELED,
-                            "canceling statement due to user request",
-                        ))
-                        .await
-                    }
-                    PeekResponse::Error(text) => {
-                        self.error(ErrorResponse::error(SqlState::INTERNAL_ERROR, text))
-                            .await
-                    }
-                    PeekResponse::Rows(rows) => {
-                        self.send_rows(
-                            row_desc,
-                            portal_name,
-                            Box::new(stream::iter(vec![rows])),
-                            max_rows,
-                            get_response,
-                            fetch_portal_name,
-                            timeout,
-                        )
-                        .await
-                    }
-                }
+                self.send_rows(
+                    row_desc,
+                    portal_name,
+                    rx,
+                    max_rows,
+                    get_response,
+                    fetch_portal_name,
+                    timeout,
+                )
+                .await
             }
             ExecuteResponse::SetVariable { name } => {
                 // This code is somewhat awkwardly structured because we
@@ -1024,6 +1079,20 @@ where
                 }
                 command_complete!("SET")
             }
+            ExecuteResponse::ResetVariable { name } => {
+                // Resetting a variable may change the value of any variable
+                // the client is tracking, in the case of `RESET ALL`.
+                let msgs: Vec<_> = self
+                    .coord_client
+                    .session()
+                    .vars()
+                    .notify_set()
+                    .filter(|v| name == "all" || v.name().eq_ignore_ascii_case(&name))
+                    .map(|v| BackendMessage::ParameterStatus(v.name(), v.value()))
+                    .collect();
+                self.conn.send_all(msgs).await?;
+                command_complete!("RESET")
+            }
             ExecuteResponse::StartedTransaction => command_complete!("BEGIN"),
             ExecuteResponse::TransactionExited { tag, was_implicit } => {
                 // In Postgres, if a user sends a COMMIT or ROLLBACK in an implicit
@@ -1044,7 +1113,7 @@ where
                 self.send_rows(
                     row_desc,
                     portal_name,
-                    Box::new(UnboundedReceiverStream::new(rx)),
+                    Box::new(UnboundedReceiverStream::new(rx).map(PeekResponse::Rows)),
                     max_rows,
                     get_response,
                     fetch_portal_name,
@@ -1056,24 +1125,10 @@ where
                 let row_desc =
                     row_desc.expect("missing row description for ExecuteResponse::CopyTo");
                 let rows: RowBatchStream = match *resp {
-                    ExecuteResponse::Tailing { rx } => Box::new(UnboundedReceiverStream::new(rx)),
-                    ExecuteResponse::SendingRows(rx) => match rx.await {
-                        // TODO(mjibson): This logic is duplicated from SendingRows. Dedup?
-                        PeekResponse::Canceled => {
-                            return self
-                                .error(ErrorResponse::error(
-                                    SqlState::QUERY_CANCELED,
-                                    "canceling statement due to user request",
-                                ))
-                                .await;
-                        }
-                        PeekResponse::Error(text) => {
-                            return self
-                                .error(ErrorResponse::error(SqlState::INTERNAL_ERROR, text))
-                                .await;
-                        }
-                        PeekResponse::Rows(rows) => Box::new(stream::iter(vec![rows])),
-                    },
+                    ExecuteResponse::Tailing { rx } => {
+                        Box::new(UnboundedReceiverStream::new(rx).map(PeekResponse::Rows))
+                    }
+                    ExecuteResponse::SendingRows(rx) => rx,
                     _ => {
                         return self
                             .error(ErrorResponse::error(
@@ -1088,6 +1143,7 @@ where
             ExecuteResponse::Updated(n) => command_complete!("UPDATE {}", n),
             ExecuteResponse::AlteredObject(o) => command_complete!("ALTER {}", o),
             ExecuteResponse::AlteredIndexLogicalCompaction => command_complete!("ALTER INDEX"),
+            ExecuteResponse::AlteredSystemConfiguration => command_complete!("ALTER SYSTEM"),
         }
     }
 
@@ -1117,6 +1173,26 @@ where
             .result_formats
             .clone();
 
+        // When a FETCH reads from a TAIL that emits progress rows, only the
+        // most recent progress row is worth sending, and only if no data
+        // follows it: data arrives in timestamp order, so each progress row
+        // supersedes the ones before it. Holding progress rows back until the
+        // end of the FETCH means that a FETCH with a timeout returns exactly
+        // one progress row when no changes occurred, and no rows at all when
+        // the TAIL has not yet caught up.
+        let coalesce_progress = fetch_portal_name.is_some()
+            && matches!(
+                self.coord_client
+                    .session()
+                    .get_portal(&portal_name)
+                    .and_then(|portal| portal.stmt.as_ref()),
+                Some(Statement::Tail(_))
+            )
+            && row_desc.typ().column_types.len() > 1
+            && row_desc.get_name(1).map(|name| name.as_str()) == Some("progressed");
+        let is_progress = |row: &Row| coalesce_progress && row.iter().nth(1) == Some(Datum::True);
+        let mut pending_progress = None;
+
         let (mut wait_once, mut deadline) = match timeout {
             ExecuteTimeout::None => (false, None),
             ExecuteTimeout::Seconds(t) => {
@@ -1134,7 +1210,12 @@ where
             tokio::select! {
                 _ = time::sleep_until(deadline.unwrap_or_else(time::Instant::now)), if deadline.is_some() => FetchResult::Rows(None),
                 _ = canceled => FetchResult::Cancelled,
-                batch = rows.next() => FetchResult::Rows(batch),
+                batch = rows.next() => match batch {
+                    None => FetchResult::Rows(None),
+                    Some(PeekResponse::Rows(rows)) => FetchResult::Rows(Some(rows)),
+                    Some(PeekResponse::Error(text)) => FetchResult::Error(text),
+                    Some(PeekResponse::Canceled) => FetchResult::Cancelled,
+                },
             }
         };
 
@@ -1175,7 +1256,7 @@ where
                 .typ()
                 .column_types
                 .iter()
-                .map(|ty| pgrepr::Type::from(&ty.scalar_type))
+                .map(|ty| ty.scalar_type.clone())
                 .zip(result_formats)
                 .collect(),
         );
@@ -1191,7 +1272,7 @@ where
         loop {
             match batch {
                 FetchResult::Rows(None) => break,
-                FetchResult::Rows(Some(mut batch_rows)) => {
+                FetchResult::Rows(Some(batch_rows)) => {
                     // If wait_once is true: the first time this fn is called it blocks (same as
                     // deadline == None). The second time this fn is called it should behave the
                     // same a 0s timeout.
@@ -1200,20 +1281,32 @@ where
                         wait_once = false;
                     }
 
-                    //  let mut batch_rows = batch_rows;
-                    // Drain panics if it's > len, so cap it.
-                    let drain_rows = cmp::min(want_rows, batch_rows.len());
+                    // Take rows until the client has as many as it wants. Progress rows do
+                    // not count towards that limit, and are held back until the end.
+                    let mut batch_rows = batch_rows.into_iter().peekable();
+                    let mut send_rows = Vec::new();
+                    while let Some(row) = batch_rows.peek() {
+                        if is_progress(row) {
+                            pending_progress = batch_rows.next();
+                        } else if want_rows > 0 {
+                            pending_progress = None;
+                            send_rows.extend(batch_rows.next());
+                            want_rows -= 1;
+                        } else {
+                            break;
+                        }
+                    }
+                    total_sent_rows += send_rows.len();
                     self.conn
-                        .send_all(batch_rows.drain(..drain_rows).map(|row| {
-                            BackendMessage::DataRow(pgrepr::values_from_row(row, row_desc.typ()))
-                        }))
+                        .send_all(send_rows.into_iter().map(BackendMessage::DataRow))
                         .await?;
-                    total_sent_rows += drain_rows;
-                    want_rows -= drain_rows;
                     // If we have sent the number of requested rows, put the remainder of the batch
                     // back and stop sending.
-                    if want_rows == 0 && !batch_rows.is_empty() {
-                        rows = Box::new(stream::iter(vec![batch_rows]).chain(rows));
+                    let batch_rows: Vec<_> = batch_rows.collect();
+                    if !batch_rows.is_empty() {
+                        rows = Box::new(
+                            stream::iter(vec![PeekResponse::Rows(batch_rows)]).chain(rows),
+                        );
                         break;
                     }
                     self.conn.flush().await?;
@@ -1228,9 +1321,19 @@ where
                         ))
                         .await;
                 }
+                FetchResult::Error(text) => {
+                    return self
+                        .error(ErrorResponse::error(SqlState::INTERNAL_ERROR, text))
+                        .await;
+                }
             }
         }
 
+        if let Some(row) = pending_progress {
+            self.conn.send(BackendMessage::DataRow(row)).await?;
+            total_sent_rows += 1;
+        }
+
         ROWS_RETURNED.inc_by(u64::cast_from(total_sent_rows));
 
         let portal = self
@@ -1337,7 +1440,20 @@ where
                 },
                 batch = stream.next() => match batch {
                     None => break,
-                    Some(rows) => {
+                    Some(PeekResponse::Error(text)) => {
+                        return self
+                            .error(ErrorResponse::error(SqlState::INTERNAL_ERROR, text))
+                            .await;
+                    }
+                    Some(PeekResponse::Canceled) => {
+                        return self
+                            .error(ErrorResponse::error(
+                                SqlState::QUERY_CANCELED,
+                                "canceling statement due to user request",
+                            ))
+                            .await;
+                    }
+                    Some(PeekResponse::Rows(rows)) => {
                         count += rows.len();
                         for row in rows {
                             encode_fn(row, typ, &mut out)?;
@@ -1512,6 +1628,7 @@ fn is_txn_exit_stmt(stmt: Option<&Statement<Raw>>) -> bool {
 enum FetchResult {
     Rows(Option<Vec<Row>>),
     Cancelled,
+    Error(String),
 }
 
 impl FetchResult {
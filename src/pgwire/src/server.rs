@@ -8,6 +8,7 @@
 // by the Apache License, Version 2.0.
 
 use std::fmt;
+use std::io::IoSlice;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
@@ -16,6 +17,7 @@ use log::trace;
 use openssl::ssl::{Ssl, SslContext};
 use tokio::io::{self, AsyncRead, AsyncWrite, AsyncWriteExt, Interest, ReadBuf, Ready};
 use tokio_openssl::SslStream;
+use tracing::Instrument;
 
 use ore::netio::AsyncReady;
 
@@ -99,6 +101,7 @@ impl Server {
                         version,
                         params,
                     })
+                    .instrument(tracing::info_span!("conn", conn_id))
                     .await?;
                     conn.flush().await?;
                     return Ok(());
@@ -175,6 +178,24 @@ where
         }
     }
 
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        bufs: &[IoSlice],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Conn::Unencrypted(inner) => Pin::new(inner).poll_write_vectored(cx, bufs),
+            Conn::Ssl(inner) => Pin::new(inner).poll_write_vectored(cx, bufs),
+        }
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        match self {
+            Conn::Unencrypted(inner) => inner.is_write_vectored(),
+            Conn::Ssl(inner) => inner.is_write_vectored(),
+        }
+    }
+
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
         match self.get_mut() {
             Conn::Unencrypted(inner) => Pin::new(inner).poll_flush(cx),
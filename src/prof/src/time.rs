@@ -10,6 +10,7 @@
 use std::os::raw::c_int;
 
 use anyhow::bail;
+use pprof::protos::Message;
 use pprof::ProfilerGuard;
 use tokio::time::{self, Duration};
 
@@ -52,3 +53,26 @@ pub async unsafe fn prof_time(
 
     Ok(profile)
 }
+
+/// Like [`prof_time`], but returns the profile encoded in the protobuf format
+/// understood by pprof.
+///
+/// # Safety
+///
+/// Nothing else must be attempting to unwind backtraces while this is called.
+/// In particular, jemalloc memory profiling must be off.
+pub async unsafe fn prof_time_pprof(
+    total_time: Duration,
+    sample_freq: u32,
+) -> anyhow::Result<Vec<u8>> {
+    if sample_freq > (1e6 as u32) {
+        bail!("Sub-microsecond intervals are not supported.");
+    }
+    let pg = ProfilerGuard::new(sample_freq as c_int)?;
+    time::sleep(total_time).await;
+    let report = pg.report().build()?;
+    let profile = report.pprof()?;
+    let mut buf = Vec::with_capacity(profile.encoded_len());
+    profile.encode(&mut buf)?;
+    Ok(buf)
+}
@@ -13,7 +13,7 @@ use chrono::NaiveDate;
 use criterion::{criterion_group, criterion_main, Bencher, Criterion};
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
-use repr::{Datum, Row};
+use repr::{Datum, Row, RowOffsets};
 
 fn bench_sort_datums(rows: Vec<Vec<Datum>>, b: &mut Bencher) {
     b.iter_with_setup(|| rows.clone(), |mut rows| rows.sort())
@@ -87,6 +87,36 @@ fn bench_filter_packed(filter: Datum, rows: Vec<Vec<Datum>>, b: &mut Bencher) {
     )
 }
 
+fn bench_access_unpack(index: usize, rows: Vec<Vec<Datum>>, b: &mut Bencher) {
+    let rows = rows.into_iter().map(Row::pack).collect::<Vec<_>>();
+    b.iter(|| {
+        rows.iter()
+            .map(|row| row.unpack()[index])
+            .filter(|d| !d.is_null())
+            .count()
+    })
+}
+
+fn bench_access_nth(index: usize, rows: Vec<Vec<Datum>>, b: &mut Bencher) {
+    let rows = rows.into_iter().map(Row::pack).collect::<Vec<_>>();
+    b.iter(|| {
+        rows.iter()
+            .map(|row| row.iter().nth(index).unwrap())
+            .filter(|d| !d.is_null())
+            .count()
+    })
+}
+
+fn bench_access_offsets(index: usize, rows: Vec<Vec<Datum>>, b: &mut Bencher) {
+    let rows = rows.into_iter().map(Row::pack).collect::<Vec<_>>();
+    let mut offsets = RowOffsets::new();
+    b.iter(|| {
+        rows.iter()
+            .filter(|row| !offsets.index(row).get(index).is_null())
+            .count()
+    })
+}
+
 fn bench_pack_pack(rows: Vec<Vec<Datum>>, b: &mut Bencher) {
     b.iter(|| rows.iter().map(Row::pack).collect::<Vec<_>>())
 }
@@ -217,5 +247,29 @@ fn bench_filter(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_sort, bench_pack, bench_filter);
+fn bench_access(c: &mut Criterion) {
+    let num_rows = 10_000;
+    let num_cols = 32;
+    let mut rng = seeded_rng();
+    let string_data = (0..num_rows * num_cols)
+        .map(|_| format!("value {}", rng.gen::<i64>()))
+        .collect::<Vec<_>>();
+    let wide_rows = string_data
+        .chunks(num_cols)
+        .map(|chunk| chunk.iter().map(|s| Datum::String(s)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let last = num_cols - 1;
+
+    c.bench_function("access_unpack_last", |b| {
+        bench_access_unpack(last, wide_rows.clone(), b)
+    });
+    c.bench_function("access_nth_last", |b| {
+        bench_access_nth(last, wide_rows.clone(), b)
+    });
+    c.bench_function("access_offsets_last", |b| {
+        bench_access_offsets(last, wide_rows.clone(), b)
+    });
+}
+
+criterion_group!(benches, bench_sort, bench_pack, bench_filter, bench_access);
 criterion_main!(benches);
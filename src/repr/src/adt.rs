@@ -18,8 +18,13 @@
 //! [PostgreSQL ADTs]: https://github.com/postgres/postgres/tree/master/src/backend/utils/adt
 
 pub mod array;
+pub mod char;
 pub mod datetime;
 pub mod decimal;
+pub mod geo;
+pub mod inet;
 pub mod interval;
 pub mod jsonb;
+pub mod range;
 pub mod regex;
+pub mod tsearch;
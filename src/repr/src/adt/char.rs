@@ -0,0 +1,126 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Fixed- and limited-length character string types.
+//!
+//! Values of both `character(n)` and `character varying(n)` are stored as
+//! ordinary strings. The functions in this module enforce their length
+//! limits when a string is converted to one of these types.
+
+use std::fmt;
+
+/// The maximum length, in characters, of a `character(n)` or
+/// `character varying(n)` type.
+pub const MAX_LENGTH: usize = 10_485_760;
+
+/// An error that indicates that a string was too long for the length of a
+/// `character(n)` or `character varying(n)` type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthExceededError;
+
+impl fmt::Display for LengthExceededError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("value too long for type")
+    }
+}
+
+impl std::error::Error for LengthExceededError {}
+
+/// Converts `s` to a value of type `character(length)`.
+///
+/// Strings shorter than `length` are padded with trailing spaces. Strings
+/// longer than `length` are truncated if `fail_on_len` is false. Otherwise,
+/// they are an error, unless the characters past `length` are all spaces.
+/// If `length` is `None`, `s` is returned unchanged.
+pub fn format_str_pad(
+    s: &str,
+    length: Option<usize>,
+    fail_on_len: bool,
+) -> Result<String, LengthExceededError> {
+    let length = match length {
+        Some(length) => length,
+        None => return Ok(s.to_owned()),
+    };
+    let s = truncate(s, length, fail_on_len)?;
+    let mut out = String::with_capacity(s.len() + length);
+    out.push_str(s);
+    for _ in s.chars().count()..length {
+        out.push(' ');
+    }
+    Ok(out)
+}
+
+/// Removes the padding from a value of type `character(n)`.
+///
+/// Trailing spaces are insignificant in `character(n)` values, and are
+/// removed when the value is converted to another string type.
+pub fn format_str_trim(s: &str) -> &str {
+    s.trim_end_matches(' ')
+}
+
+/// Converts `s` to a value of type `character varying(length)`.
+///
+/// Strings longer than `length` are truncated if `fail_on_len` is false.
+/// Otherwise, they are an error, unless the characters past `length` are all
+/// spaces. If `length` is `None`, `s` is returned unchanged.
+pub fn format_varchar(
+    s: &str,
+    length: Option<usize>,
+    fail_on_len: bool,
+) -> Result<&str, LengthExceededError> {
+    match length {
+        Some(length) => truncate(s, length, fail_on_len),
+        None => Ok(s),
+    }
+}
+
+fn truncate(s: &str, length: usize, fail_on_len: bool) -> Result<&str, LengthExceededError> {
+    match s.char_indices().nth(length) {
+        None => Ok(s),
+        Some((i, _)) => {
+            if fail_on_len && s[i..].chars().any(|c| c != ' ') {
+                Err(LengthExceededError)
+            } else {
+                Ok(&s[..i])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_str_pad() {
+        assert_eq!(format_str_pad("ab", Some(4), true).unwrap(), "ab  ");
+        assert_eq!(format_str_pad("ab", None, true).unwrap(), "ab");
+        assert_eq!(format_str_pad("你好", Some(3), true).unwrap(), "你好 ");
+        assert_eq!(format_str_pad("abc  ", Some(4), true).unwrap(), "abc ");
+        assert_eq!(format_str_pad("abcde", Some(4), false).unwrap(), "abcd");
+        assert_eq!(
+            format_str_pad("abcde", Some(4), true),
+            Err(LengthExceededError)
+        );
+        assert_eq!(format_str_trim("ab  "), "ab");
+        assert_eq!(format_str_trim("  "), "");
+    }
+
+    #[test]
+    fn test_format_varchar() {
+        assert_eq!(format_varchar("ab", Some(4), true), Ok("ab"));
+        assert_eq!(format_varchar("ab   ", Some(4), true), Ok("ab  "));
+        assert_eq!(format_varchar("你好吗", Some(2), false), Ok("你好"));
+        assert_eq!(
+            format_varchar("你好吗", Some(2), true),
+            Err(LengthExceededError)
+        );
+        assert_eq!(format_varchar("abcde", None, true), Ok("abcde"));
+    }
+}
@@ -0,0 +1,1452 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Geospatial data types.
+//!
+//! The types in this module implement a subset of [PostGIS]'s `geometry` and
+//! `geography` types: two-dimensional points, linestrings, polygons, and their
+//! multi-part variants.
+//!
+//! [PostGIS]: https://postgis.net
+
+use std::convert::{TryFrom, TryInto};
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The spatial reference identifier (SRID) of the WGS 84 longitude/latitude
+/// coordinate system, which is the only coordinate system that the `geography`
+/// type supports.
+pub const SRID_WGS84: i32 = 4326;
+
+/// The mean radius of the Earth, in meters.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+// Byte orders, flags, and type codes of the (extended) well-known binary
+// format. See the "Well-known binary" section of the OGC Simple Features
+// specification and PostGIS's `liblwgeom/lwin_wkb.c` for details.
+const WKB_BIG_ENDIAN: u8 = 0;
+const WKB_LITTLE_ENDIAN: u8 = 1;
+const EWKB_Z_FLAG: u32 = 0x8000_0000;
+const EWKB_M_FLAG: u32 = 0x4000_0000;
+const EWKB_SRID_FLAG: u32 = 0x2000_0000;
+const WKB_POINT: u32 = 1;
+const WKB_LINESTRING: u32 = 2;
+const WKB_POLYGON: u32 = 3;
+const WKB_MULTIPOINT: u32 = 4;
+const WKB_MULTILINESTRING: u32 = 5;
+const WKB_MULTIPOLYGON: u32 = 6;
+const WKB_GEOMETRYCOLLECTION: u32 = 7;
+
+/// A geometry, as stored in a [`Datum::Geometry`](crate::Datum::Geometry).
+///
+/// Geometries are stored in a canonical extended well-known binary (EWKB)
+/// encoding, so that equal geometries have equal representations: the encoding
+/// is always little endian, and includes the SRID if and only if it is
+/// nonzero. Geometries are ordered by this encoding, which is consistent but
+/// otherwise meaningless.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Geometry<'a> {
+    pub(crate) ewkb: &'a [u8],
+}
+
+impl<'a> Geometry<'a> {
+    /// Returns the spatial reference identifier of the geometry, or zero if
+    /// the geometry has no spatial reference system.
+    pub fn srid(&self) -> i32 {
+        let ty = u32::from_le_bytes(self.ewkb[1..5].try_into().unwrap());
+        if ty & EWKB_SRID_FLAG != 0 {
+            i32::from_le_bytes(self.ewkb[5..9].try_into().unwrap())
+        } else {
+            0
+        }
+    }
+
+    /// Decodes the shape of the geometry.
+    pub fn shape(&self) -> Shape {
+        let mut reader = WkbReader::new(self.ewkb);
+        let (_srid, shape) = reader
+            .read_shape(true)
+            .expect("geometry datums are valid EWKB");
+        shape
+    }
+
+    /// Returns the canonical EWKB encoding of the geometry.
+    pub fn as_ewkb(&self) -> &'a [u8] {
+        self.ewkb
+    }
+}
+
+/// A two-dimensional coordinate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Coord {
+    /// The X coordinate, or the longitude in degrees for geographies.
+    pub x: f64,
+    /// The Y coordinate, or the latitude in degrees for geographies.
+    pub y: f64,
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.x, self.y)
+    }
+}
+
+/// The decoded shape of a [`Geometry`].
+///
+/// The [`Display`](fmt::Display) implementation formats the shape as
+/// well-known text (WKT).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Shape {
+    /// A single point.
+    Point(Coord),
+    /// A sequence of two or more points joined by straight segments.
+    LineString(Vec<Coord>),
+    /// An area bounded by an exterior ring, less the areas bounded by any
+    /// interior rings. Each ring is a closed linestring.
+    Polygon(Vec<Vec<Coord>>),
+    /// A collection of points.
+    MultiPoint(Vec<Coord>),
+    /// A collection of linestrings.
+    MultiLineString(Vec<Vec<Coord>>),
+    /// A collection of polygons.
+    MultiPolygon(Vec<Vec<Vec<Coord>>>),
+}
+
+impl Shape {
+    /// Checks that the shape is valid: all coordinates are finite,
+    /// linestrings have at least two points, polygon rings are closed and have
+    /// at least four points, and multi-part shapes have at least one part.
+    pub fn validate(&self) -> Result<(), InvalidGeometryError> {
+        fn check_line(line: &[Coord]) -> Result<(), InvalidGeometryError> {
+            if line.len() < 2 {
+                return Err(malformed("linestrings must have at least two points"));
+            }
+            Ok(())
+        }
+
+        fn check_polygon(rings: &[Vec<Coord>]) -> Result<(), InvalidGeometryError> {
+            if rings.is_empty() {
+                return Err(empty());
+            }
+            for ring in rings {
+                if ring.len() < 4 {
+                    return Err(malformed("polygon rings must have at least four points"));
+                }
+                if ring[0] != ring[ring.len() - 1] {
+                    return Err(malformed("polygon rings must be closed"));
+                }
+            }
+            Ok(())
+        }
+
+        match self {
+            Shape::Point(_) => (),
+            Shape::LineString(line) => check_line(line)?,
+            Shape::Polygon(rings) => check_polygon(rings)?,
+            Shape::MultiPoint(points) if points.is_empty() => return Err(empty()),
+            Shape::MultiPoint(_) => (),
+            Shape::MultiLineString(lines) if lines.is_empty() => return Err(empty()),
+            Shape::MultiLineString(lines) => lines.iter().try_for_each(|l| check_line(l))?,
+            Shape::MultiPolygon(polygons) if polygons.is_empty() => return Err(empty()),
+            Shape::MultiPolygon(polygons) => polygons.iter().try_for_each(|p| check_polygon(p))?,
+        }
+        if self
+            .vertices()
+            .iter()
+            .any(|c| !c.x.is_finite() || !c.y.is_finite())
+        {
+            return Err(malformed("coordinates must be finite"));
+        }
+        Ok(())
+    }
+
+    /// Reports whether `other` lies within `self`, with at least one point of
+    /// `other` in the interior of `self`.
+    ///
+    /// This matches the semantics of PostGIS's `ST_Contains` function. In
+    /// particular, a shape does not contain shapes that lie entirely within its
+    /// boundary, e.g., a polygon does not contain the points on its edges.
+    pub fn contains(&self, other: &Shape) -> bool {
+        match self.dimension() {
+            0 => {
+                let vertices = self.vertices();
+                other.dimension() == 0 && other.vertices().iter().all(|v| vertices.contains(v))
+            }
+            1 => lineal_contains(self, other),
+            _ => polygonal_contains(self, other),
+        }
+    }
+
+    /// Returns the minimum Cartesian distance between `self` and `other`.
+    pub fn distance(&self, other: &Shape) -> f64 {
+        distance::<Planar>(self, other)
+    }
+
+    /// Returns the minimum distance in meters between `self` and `other` along
+    /// the surface of the Earth, interpreting coordinates as longitudes and
+    /// latitudes in degrees.
+    ///
+    /// The Earth is modeled as a sphere with a radius of 6,371,008.8 meters,
+    /// and segments as great circle arcs. When determining whether a point lies
+    /// within a polygon, however, the polygon's edges are treated as straight
+    /// lines in longitude/latitude space.
+    pub fn spherical_distance(&self, other: &Shape) -> f64 {
+        distance::<Spherical>(self, other)
+    }
+
+    /// Returns the topological dimension of the shape: 0 for points, 1 for
+    /// linestrings, and 2 for polygons.
+    fn dimension(&self) -> u8 {
+        match self {
+            Shape::Point(_) | Shape::MultiPoint(_) => 0,
+            Shape::LineString(_) | Shape::MultiLineString(_) => 1,
+            Shape::Polygon(_) | Shape::MultiPolygon(_) => 2,
+        }
+    }
+
+    /// Returns every vertex of the shape.
+    fn vertices(&self) -> Vec<Coord> {
+        match self {
+            Shape::Point(c) => vec![*c],
+            Shape::LineString(cs) | Shape::MultiPoint(cs) => cs.clone(),
+            Shape::Polygon(rings) | Shape::MultiLineString(rings) => rings.concat(),
+            Shape::MultiPolygon(polygons) => polygons.iter().flatten().flatten().copied().collect(),
+        }
+    }
+
+    /// Returns the linestrings that make up the shape, including the rings of
+    /// polygons.
+    fn lines(&self) -> Vec<&[Coord]> {
+        match self {
+            Shape::Point(_) | Shape::MultiPoint(_) => vec![],
+            Shape::LineString(line) => vec![line.as_slice()],
+            Shape::Polygon(rings) | Shape::MultiLineString(rings) => {
+                rings.iter().map(|r| r.as_slice()).collect()
+            }
+            Shape::MultiPolygon(polygons) => {
+                polygons.iter().flatten().map(|r| r.as_slice()).collect()
+            }
+        }
+    }
+
+    /// Returns the segments of the shape's linestrings and polygon rings.
+    fn segments(&self) -> Vec<(Coord, Coord)> {
+        self.lines()
+            .into_iter()
+            .flat_map(|line| line.windows(2).map(|w| (w[0], w[1])))
+            .collect()
+    }
+
+    /// Returns the polygons that make up the shape, as lists of rings.
+    fn polygons(&self) -> Vec<&[Vec<Coord>]> {
+        match self {
+            Shape::Polygon(rings) => vec![rings.as_slice()],
+            Shape::MultiPolygon(polygons) => polygons.iter().map(|p| p.as_slice()).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Shape::Point(c) => write!(f, "POINT({})", c),
+            Shape::LineString(line) => {
+                f.write_str("LINESTRING")?;
+                write_wkt_coords(f, line)
+            }
+            Shape::Polygon(rings) => {
+                f.write_str("POLYGON")?;
+                write_wkt_rings(f, rings)
+            }
+            Shape::MultiPoint(points) => {
+                f.write_str("MULTIPOINT")?;
+                write_wkt_coords(f, points)
+            }
+            Shape::MultiLineString(lines) => {
+                f.write_str("MULTILINESTRING")?;
+                write_wkt_rings(f, lines)
+            }
+            Shape::MultiPolygon(polygons) => {
+                f.write_str("MULTIPOLYGON(")?;
+                for (i, rings) in polygons.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_wkt_rings(f, rings)?;
+                }
+                f.write_str(")")
+            }
+        }
+    }
+}
+
+fn write_wkt_coords(f: &mut fmt::Formatter, coords: &[Coord]) -> fmt::Result {
+    f.write_str("(")?;
+    for (i, c) in coords.iter().enumerate() {
+        if i > 0 {
+            f.write_str(",")?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str(")")
+}
+
+fn write_wkt_rings(f: &mut fmt::Formatter, rings: &[Vec<Coord>]) -> fmt::Result {
+    f.write_str("(")?;
+    for (i, ring) in rings.iter().enumerate() {
+        if i > 0 {
+            f.write_str(",")?;
+        }
+        write_wkt_coords(f, ring)?;
+    }
+    f.write_str(")")
+}
+
+/// Checks that a shape is valid as a geography, returning its SRID.
+///
+/// Geographies must use the WGS 84 coordinate system, and their coordinates
+/// must be valid longitudes and latitudes. A shape without an SRID is assumed
+/// to use WGS 84.
+pub fn check_geography(srid: i32, shape: &Shape) -> Result<i32, InvalidGeometryError> {
+    if srid != 0 && srid != SRID_WGS84 {
+        return Err(InvalidGeometryError::InvalidGeographySrid(srid));
+    }
+    if shape
+        .vertices()
+        .iter()
+        .any(|c| !(-180.0..=180.0).contains(&c.x) || !(-90.0..=90.0).contains(&c.y))
+    {
+        return Err(InvalidGeometryError::CoordinateOutOfRange);
+    }
+    Ok(SRID_WGS84)
+}
+
+// --------------------------------------------------------------------------------
+// well-known binary
+
+/// Encodes a shape as canonical EWKB.
+///
+/// The SRID is omitted if it is zero.
+pub fn encode_ewkb(buf: &mut Vec<u8>, srid: i32, shape: &Shape) {
+    write_wkb_shape(buf, shape, if srid != 0 { Some(srid) } else { None });
+}
+
+/// Encodes a shape as little-endian well-known binary (WKB), which does not
+/// include an SRID.
+pub fn encode_wkb(buf: &mut Vec<u8>, shape: &Shape) {
+    write_wkb_shape(buf, shape, None);
+}
+
+/// Decodes a shape and its SRID from well-known binary or extended well-known
+/// binary.
+///
+/// The SRID is zero if the input does not specify one.
+pub fn decode_ewkb(data: &[u8]) -> Result<(i32, Shape), InvalidGeometryError> {
+    let mut reader = WkbReader::new(data);
+    let (srid, shape) = reader.read_shape(true)?;
+    if reader.pos != data.len() {
+        return Err(malformed("unexpected trailing bytes"));
+    }
+    shape.validate()?;
+    Ok((srid, shape))
+}
+
+fn write_wkb_shape(buf: &mut Vec<u8>, shape: &Shape, srid: Option<i32>) {
+    match shape {
+        Shape::Point(c) => {
+            write_wkb_header(buf, WKB_POINT, srid);
+            write_wkb_coord(buf, *c);
+        }
+        Shape::LineString(line) => {
+            write_wkb_header(buf, WKB_LINESTRING, srid);
+            write_wkb_coords(buf, line);
+        }
+        Shape::Polygon(rings) => {
+            write_wkb_header(buf, WKB_POLYGON, srid);
+            write_wkb_rings(buf, rings);
+        }
+        Shape::MultiPoint(points) => {
+            write_wkb_header(buf, WKB_MULTIPOINT, srid);
+            write_wkb_len(buf, points.len());
+            for c in points {
+                write_wkb_header(buf, WKB_POINT, None);
+                write_wkb_coord(buf, *c);
+            }
+        }
+        Shape::MultiLineString(lines) => {
+            write_wkb_header(buf, WKB_MULTILINESTRING, srid);
+            write_wkb_len(buf, lines.len());
+            for line in lines {
+                write_wkb_header(buf, WKB_LINESTRING, None);
+                write_wkb_coords(buf, line);
+            }
+        }
+        Shape::MultiPolygon(polygons) => {
+            write_wkb_header(buf, WKB_MULTIPOLYGON, srid);
+            write_wkb_len(buf, polygons.len());
+            for rings in polygons {
+                write_wkb_header(buf, WKB_POLYGON, None);
+                write_wkb_rings(buf, rings);
+            }
+        }
+    }
+}
+
+fn write_wkb_header(buf: &mut Vec<u8>, ty: u32, srid: Option<i32>) {
+    buf.push(WKB_LITTLE_ENDIAN);
+    match srid {
+        None => buf.extend(&ty.to_le_bytes()),
+        Some(srid) => {
+            buf.extend(&(ty | EWKB_SRID_FLAG).to_le_bytes());
+            buf.extend(&srid.to_le_bytes());
+        }
+    }
+}
+
+fn write_wkb_len(buf: &mut Vec<u8>, len: usize) {
+    let len = u32::try_from(len).expect("geometry too large for WKB");
+    buf.extend(&len.to_le_bytes());
+}
+
+fn write_wkb_coord(buf: &mut Vec<u8>, c: Coord) {
+    buf.extend(&c.x.to_le_bytes());
+    buf.extend(&c.y.to_le_bytes());
+}
+
+fn write_wkb_coords(buf: &mut Vec<u8>, coords: &[Coord]) {
+    write_wkb_len(buf, coords.len());
+    for c in coords {
+        write_wkb_coord(buf, *c);
+    }
+}
+
+fn write_wkb_rings(buf: &mut Vec<u8>, rings: &[Vec<Coord>]) {
+    write_wkb_len(buf, rings.len());
+    for ring in rings {
+        write_wkb_coords(buf, ring);
+    }
+}
+
+struct WkbReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    little_endian: bool,
+}
+
+impl<'a> WkbReader<'a> {
+    fn new(data: &'a [u8]) -> WkbReader<'a> {
+        WkbReader {
+            data,
+            pos: 0,
+            little_endian: true,
+        }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], InvalidGeometryError> {
+        if self.data.len() - self.pos < n {
+            return Err(malformed("unexpected end of input"));
+        }
+        let bytes = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, InvalidGeometryError> {
+        let bytes = self.take(4)?.try_into().unwrap();
+        Ok(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_f64(&mut self) -> Result<f64, InvalidGeometryError> {
+        let bytes = self.take(8)?.try_into().unwrap();
+        Ok(if self.little_endian {
+            f64::from_le_bytes(bytes)
+        } else {
+            f64::from_be_bytes(bytes)
+        })
+    }
+
+    fn read_len(&mut self) -> Result<usize, InvalidGeometryError> {
+        let len = usize::try_from(self.read_u32()?).unwrap();
+        // Every element occupies at least one byte, so a length greater than
+        // the number of remaining bytes is certainly invalid. Checking this
+        // up front avoids allocating huge vectors for malicious input.
+        if len > self.data.len() - self.pos {
+            return Err(malformed("unexpected end of input"));
+        }
+        Ok(len)
+    }
+
+    fn read_coord(&mut self) -> Result<Coord, InvalidGeometryError> {
+        let x = self.read_f64()?;
+        let y = self.read_f64()?;
+        Ok(Coord { x, y })
+    }
+
+    fn read_coords(&mut self) -> Result<Vec<Coord>, InvalidGeometryError> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read_coord()).collect()
+    }
+
+    fn read_rings(&mut self) -> Result<Vec<Vec<Coord>>, InvalidGeometryError> {
+        let len = self.read_len()?;
+        (0..len).map(|_| self.read_coords()).collect()
+    }
+
+    /// Reads the byte order and type of a shape, returning the type without
+    /// flags and the SRID, if any.
+    fn read_header(&mut self, allow_srid: bool) -> Result<(u32, i32), InvalidGeometryError> {
+        self.little_endian = match self.take(1)?[0] {
+            WKB_BIG_ENDIAN => false,
+            WKB_LITTLE_ENDIAN => true,
+            b => return Err(malformed(format!("invalid byte order {}", b))),
+        };
+        let ty = self.read_u32()?;
+        // ISO WKB indicates Z and M coordinates by adding multiples of 1000 to
+        // the type, while EWKB uses flags.
+        if ty & (EWKB_Z_FLAG | EWKB_M_FLAG) != 0 || (ty & 0xffff) >= 1000 {
+            return Err(unsupported(
+                "geometries with Z or M coordinates are not supported",
+            ));
+        }
+        let srid = if ty & EWKB_SRID_FLAG == 0 {
+            0
+        } else if allow_srid {
+            self.read_u32()? as i32
+        } else {
+            return Err(malformed("unexpected SRID in multi-part geometry"));
+        };
+        Ok((ty & !EWKB_SRID_FLAG, srid))
+    }
+
+    /// Reads the header of a part of a multi-part shape, which must have type
+    /// `expected`.
+    fn read_part_header(&mut self, expected: u32) -> Result<(), InvalidGeometryError> {
+        let (ty, _srid) = self.read_header(false)?;
+        if ty != expected {
+            return Err(malformed(format!(
+                "unexpected geometry type {} in multi-part geometry",
+                ty
+            )));
+        }
+        Ok(())
+    }
+
+    fn read_shape(&mut self, allow_srid: bool) -> Result<(i32, Shape), InvalidGeometryError> {
+        let (ty, srid) = self.read_header(allow_srid)?;
+        let shape = match ty {
+            WKB_POINT => Shape::Point(self.read_coord()?),
+            WKB_LINESTRING => Shape::LineString(self.read_coords()?),
+            WKB_POLYGON => Shape::Polygon(self.read_rings()?),
+            WKB_MULTIPOINT => {
+                let len = self.read_len()?;
+                let mut points = Vec::with_capacity(len);
+                for _ in 0..len {
+                    self.read_part_header(WKB_POINT)?;
+                    points.push(self.read_coord()?);
+                }
+                Shape::MultiPoint(points)
+            }
+            WKB_MULTILINESTRING => {
+                let len = self.read_len()?;
+                let mut lines = Vec::with_capacity(len);
+                for _ in 0..len {
+                    self.read_part_header(WKB_LINESTRING)?;
+                    lines.push(self.read_coords()?);
+                }
+                Shape::MultiLineString(lines)
+            }
+            WKB_MULTIPOLYGON => {
+                let len = self.read_len()?;
+                let mut polygons = Vec::with_capacity(len);
+                for _ in 0..len {
+                    self.read_part_header(WKB_POLYGON)?;
+                    polygons.push(self.read_rings()?);
+                }
+                Shape::MultiPolygon(polygons)
+            }
+            WKB_GEOMETRYCOLLECTION => return Err(collections_unsupported()),
+            _ => return Err(malformed(format!("unknown geometry type {}", ty))),
+        };
+        Ok((srid, shape))
+    }
+}
+
+// --------------------------------------------------------------------------------
+// well-known text
+
+/// Parses a shape and its SRID from well-known text (WKT) or extended
+/// well-known text (EWKT), i.e., WKT with an optional `SRID=<srid>;` prefix.
+///
+/// The SRID is zero if the input does not specify one.
+pub fn parse_ewkt(s: &str) -> Result<(i32, Shape), InvalidGeometryError> {
+    let mut s = s.trim();
+    let mut srid = 0;
+    if s.get(..5).map(|p| p.eq_ignore_ascii_case("SRID=")) == Some(true) {
+        let end = s
+            .find(';')
+            .ok_or_else(|| malformed("missing ';' after SRID"))?;
+        srid = s[5..end]
+            .trim()
+            .parse()
+            .map_err(|_| malformed("invalid SRID"))?;
+        s = &s[end + 1..];
+    }
+    let mut parser = WktParser { s, pos: 0 };
+    let shape = parser.parse_shape()?;
+    if parser.peek().is_some() {
+        return Err(malformed(format!(
+            "unexpected character at position {}",
+            parser.pos
+        )));
+    }
+    shape.validate()?;
+    Ok((srid, shape))
+}
+
+struct WktParser<'a> {
+    s: &'a str,
+    pos: usize,
+}
+
+impl<'a> WktParser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.s[self.pos..]
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+        self.rest().chars().next()
+    }
+
+    fn consume(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), InvalidGeometryError> {
+        if self.consume(c) {
+            Ok(())
+        } else {
+            Err(malformed(format!(
+                "expected '{}' at position {}",
+                c, self.pos
+            )))
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        self.peek();
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or_else(|| rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn word(&mut self) -> &'a str {
+        self.take_while(|c| c.is_ascii_alphabetic())
+    }
+
+    fn number(&mut self) -> Result<f64, InvalidGeometryError> {
+        let start = self.pos;
+        self.take_while(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+            .parse()
+            .map_err(|_| malformed(format!("invalid number at position {}", start)))
+    }
+
+    fn coord(&mut self) -> Result<Coord, InvalidGeometryError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(Coord { x, y })
+    }
+
+    fn list<T>(
+        &mut self,
+        mut f: impl FnMut(&mut Self) -> Result<T, InvalidGeometryError>,
+    ) -> Result<Vec<T>, InvalidGeometryError> {
+        self.expect('(')?;
+        let mut elems = vec![f(self)?];
+        while self.consume(',') {
+            elems.push(f(self)?);
+        }
+        self.expect(')')?;
+        Ok(elems)
+    }
+
+    fn coords(&mut self) -> Result<Vec<Coord>, InvalidGeometryError> {
+        self.list(Self::coord)
+    }
+
+    fn rings(&mut self) -> Result<Vec<Vec<Coord>>, InvalidGeometryError> {
+        self.list(Self::coords)
+    }
+
+    fn parse_shape(&mut self) -> Result<Shape, InvalidGeometryError> {
+        let ty = self.word().to_ascii_uppercase();
+        match self.word().to_ascii_uppercase().as_str() {
+            "" => (),
+            "EMPTY" => return Err(empty()),
+            "Z" | "M" | "ZM" => {
+                return Err(unsupported(
+                    "geometries with Z or M coordinates are not supported",
+                ))
+            }
+            _ => return Err(malformed(format!("unexpected word after {}", ty))),
+        }
+        Ok(match ty.as_str() {
+            "POINT" => {
+                self.expect('(')?;
+                let c = self.coord()?;
+                self.expect(')')?;
+                Shape::Point(c)
+            }
+            "LINESTRING" => Shape::LineString(self.coords()?),
+            "POLYGON" => Shape::Polygon(self.rings()?),
+            // The points of a multipoint may or may not be individually
+            // parenthesized.
+            "MULTIPOINT" => Shape::MultiPoint(self.list(|p| {
+                if p.consume('(') {
+                    let c = p.coord()?;
+                    p.expect(')')?;
+                    Ok(c)
+                } else {
+                    p.coord()
+                }
+            })?),
+            "MULTILINESTRING" => Shape::MultiLineString(self.rings()?),
+            "MULTIPOLYGON" => Shape::MultiPolygon(self.list(Self::rings)?),
+            "GEOMETRYCOLLECTION" => return Err(collections_unsupported()),
+            "" => return Err(malformed("missing geometry type")),
+            _ => return Err(malformed(format!("unknown geometry type {}", ty))),
+        })
+    }
+}
+
+// --------------------------------------------------------------------------------
+// GeoJSON
+
+/// Parses a shape and its SRID from a GeoJSON geometry object.
+///
+/// The SRID is taken from the object's `crs` member, if present, and is
+/// otherwise 4326, per the GeoJSON specification.
+pub fn parse_geojson(s: &str) -> Result<(i32, Shape), InvalidGeometryError> {
+    let value: serde_json::Value =
+        serde_json::from_str(s).map_err(|e| malformed(format!("invalid GeoJSON: {}", e)))?;
+    let srid = match value.get("crs") {
+        None | Some(serde_json::Value::Null) => SRID_WGS84,
+        Some(crs) => {
+            let name = crs
+                .pointer("/properties/name")
+                .and_then(|n| n.as_str())
+                .ok_or_else(|| malformed("invalid GeoJSON crs"))?;
+            name.rsplit(':')
+                .next()
+                .and_then(|code| code.parse().ok())
+                .ok_or_else(|| malformed(format!("unsupported GeoJSON crs {}", name)))?
+        }
+    };
+    let shape = geojson_shape(&value)?;
+    shape.validate()?;
+    Ok((srid, shape))
+}
+
+fn geojson_shape(value: &serde_json::Value) -> Result<Shape, InvalidGeometryError> {
+    let ty = value
+        .get("type")
+        .and_then(|t| t.as_str())
+        .ok_or_else(|| malformed("missing GeoJSON type"))?;
+    let coords = || {
+        value
+            .get("coordinates")
+            .ok_or_else(|| malformed("missing GeoJSON coordinates"))
+    };
+    Ok(match ty {
+        "Point" => Shape::Point(geojson_coord(coords()?)?),
+        "LineString" => Shape::LineString(geojson_coords(coords()?)?),
+        "Polygon" => Shape::Polygon(geojson_rings(coords()?)?),
+        "MultiPoint" => Shape::MultiPoint(geojson_coords(coords()?)?),
+        "MultiLineString" => Shape::MultiLineString(geojson_rings(coords()?)?),
+        "MultiPolygon" => Shape::MultiPolygon(geojson_array(coords()?, geojson_rings)?),
+        "GeometryCollection" => return Err(collections_unsupported()),
+        _ => return Err(malformed(format!("unknown GeoJSON type {}", ty))),
+    })
+}
+
+fn geojson_array<T>(
+    value: &serde_json::Value,
+    f: fn(&serde_json::Value) -> Result<T, InvalidGeometryError>,
+) -> Result<Vec<T>, InvalidGeometryError> {
+    value
+        .as_array()
+        .ok_or_else(|| malformed("GeoJSON coordinates must be arrays"))?
+        .iter()
+        .map(f)
+        .collect()
+}
+
+fn geojson_coord(value: &serde_json::Value) -> Result<Coord, InvalidGeometryError> {
+    let position = geojson_array(value, |n| {
+        n.as_f64()
+            .ok_or_else(|| malformed("GeoJSON positions must contain numbers"))
+    })?;
+    match position.as_slice() {
+        [x, y] => Ok(Coord { x: *x, y: *y }),
+        [_, _, ..] => Err(unsupported(
+            "geometries with Z or M coordinates are not supported",
+        )),
+        _ => Err(malformed("GeoJSON positions must have two coordinates")),
+    }
+}
+
+fn geojson_coords(value: &serde_json::Value) -> Result<Vec<Coord>, InvalidGeometryError> {
+    geojson_array(value, geojson_coord)
+}
+
+fn geojson_rings(value: &serde_json::Value) -> Result<Vec<Vec<Coord>>, InvalidGeometryError> {
+    geojson_array(value, geojson_coords)
+}
+
+/// Formats a shape as a GeoJSON geometry object.
+///
+/// As in PostGIS, the object includes a `crs` member only if the SRID is
+/// neither 0 nor 4326.
+pub fn format_geojson(srid: i32, shape: &Shape) -> String {
+    fn coord(buf: &mut String, c: Coord) {
+        buf.push_str(&format!("[{},{}]", c.x, c.y));
+    }
+
+    fn coords(buf: &mut String, cs: &[Coord]) {
+        buf.push('[');
+        for (i, c) in cs.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            coord(buf, *c);
+        }
+        buf.push(']');
+    }
+
+    fn rings(buf: &mut String, rs: &[Vec<Coord>]) {
+        buf.push('[');
+        for (i, r) in rs.iter().enumerate() {
+            if i > 0 {
+                buf.push(',');
+            }
+            coords(buf, r);
+        }
+        buf.push(']');
+    }
+
+    let ty = match shape {
+        Shape::Point(_) => "Point",
+        Shape::LineString(_) => "LineString",
+        Shape::Polygon(_) => "Polygon",
+        Shape::MultiPoint(_) => "MultiPoint",
+        Shape::MultiLineString(_) => "MultiLineString",
+        Shape::MultiPolygon(_) => "MultiPolygon",
+    };
+    let mut buf = format!("{{\"type\":\"{}\",", ty);
+    if srid != 0 && srid != SRID_WGS84 {
+        buf.push_str(&format!(
+            "\"crs\":{{\"type\":\"name\",\"properties\":{{\"name\":\"EPSG:{}\"}}}},",
+            srid
+        ));
+    }
+    buf.push_str("\"coordinates\":");
+    match shape {
+        Shape::Point(c) => coord(&mut buf, *c),
+        Shape::LineString(cs) | Shape::MultiPoint(cs) => coords(&mut buf, cs),
+        Shape::Polygon(rs) | Shape::MultiLineString(rs) => rings(&mut buf, rs),
+        Shape::MultiPolygon(polygons) => {
+            buf.push('[');
+            for (i, rs) in polygons.iter().enumerate() {
+                if i > 0 {
+                    buf.push(',');
+                }
+                rings(&mut buf, rs);
+            }
+            buf.push(']');
+        }
+    }
+    buf.push('}');
+    buf
+}
+
+// --------------------------------------------------------------------------------
+// spatial predicates
+
+/// The location of a point relative to an area.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Location {
+    Interior,
+    Boundary,
+    Exterior,
+}
+
+/// Returns twice the signed area of the triangle `abc`, which is positive if
+/// the points are in counterclockwise order, negative if they are in clockwise
+/// order, and zero if they are collinear.
+fn orientation(a: Coord, b: Coord, c: Coord) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Reports whether `p` lies on the segment from `a` to `b`.
+fn on_segment(p: Coord, a: Coord, b: Coord) -> bool {
+    orientation(a, b, p) == 0.0
+        && (a.x.min(b.x)..=a.x.max(b.x)).contains(&p.x)
+        && (a.y.min(b.y)..=a.y.max(b.y)).contains(&p.y)
+}
+
+/// Returns the position of the projection of `p` onto the line through `a`
+/// and `b`, where 0 is `a` and 1 is `b`.
+fn project(p: Coord, a: Coord, b: Coord) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    ((p.x - a.x) * dx + (p.y - a.y) * dy) / (dx * dx + dy * dy)
+}
+
+/// Returns the point at position `t` along the segment from `a` to `b`.
+fn interpolate(a: Coord, b: Coord, t: f64) -> Coord {
+    Coord {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+    }
+}
+
+fn locate_in_ring(p: Coord, ring: &[Coord]) -> Location {
+    let mut inside = false;
+    for w in ring.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        if on_segment(p, a, b) {
+            return Location::Boundary;
+        }
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+    }
+    if inside {
+        Location::Interior
+    } else {
+        Location::Exterior
+    }
+}
+
+fn locate_in_polygon(p: Coord, rings: &[Vec<Coord>]) -> Location {
+    let location = locate_in_ring(p, &rings[0]);
+    if location != Location::Interior {
+        return location;
+    }
+    for hole in &rings[1..] {
+        match locate_in_ring(p, hole) {
+            Location::Interior => return Location::Exterior,
+            Location::Boundary => return Location::Boundary,
+            Location::Exterior => (),
+        }
+    }
+    Location::Interior
+}
+
+fn locate(p: Coord, polygons: &[&[Vec<Coord>]]) -> Location {
+    let mut location = Location::Exterior;
+    for rings in polygons {
+        match locate_in_polygon(p, rings) {
+            Location::Interior => return Location::Interior,
+            Location::Boundary => location = Location::Boundary,
+            Location::Exterior => (),
+        }
+    }
+    location
+}
+
+/// Splits the segment from `a` to `b` at every point where it meets one of
+/// `edges`.
+fn split_segment(a: Coord, b: Coord, edges: &[(Coord, Coord)]) -> Vec<(Coord, Coord)> {
+    if a == b {
+        return vec![(a, b)];
+    }
+    let mut ts = vec![0.0, 1.0];
+    for (c, d) in edges {
+        for p in &[*c, *d] {
+            if on_segment(*p, a, b) {
+                ts.push(project(*p, a, b));
+            }
+        }
+        let (r, s, q) = (sub(b, a), sub(*d, *c), sub(*c, a));
+        let denom = perp_dot(r, s);
+        if denom != 0.0 {
+            let t = perp_dot(q, s) / denom;
+            let u = perp_dot(q, r) / denom;
+            if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+                ts.push(t);
+            }
+        }
+    }
+    ts.sort_by(|x, y| x.partial_cmp(y).unwrap());
+    ts.dedup();
+    ts.windows(2)
+        .map(|w| (interpolate(a, b, w[0]), interpolate(a, b, w[1])))
+        .collect()
+}
+
+fn sub(a: Coord, b: Coord) -> Coord {
+    Coord {
+        x: a.x - b.x,
+        y: a.y - b.y,
+    }
+}
+
+/// Returns the two-dimensional cross product of `u` and `v`.
+fn perp_dot(u: Coord, v: Coord) -> f64 {
+    u.x * v.y - u.y * v.x
+}
+
+/// Implements [`Shape::contains`] for polygons and multipolygons.
+fn polygonal_contains(shape: &Shape, other: &Shape) -> bool {
+    let polygons = shape.polygons();
+    if other.dimension() == 0 {
+        let locations: Vec<_> = other
+            .vertices()
+            .into_iter()
+            .map(|p| locate(p, &polygons))
+            .collect();
+        return !locations.contains(&Location::Exterior) && locations.contains(&Location::Interior);
+    }
+
+    // Split every segment of `other` where it meets the boundary of `shape`.
+    // Each resulting piece then lies entirely in the interior, on the
+    // boundary, or in the exterior of `shape`, which its midpoint reveals.
+    let edges = shape.segments();
+    let mut any_interior = false;
+    for (a, b) in other.segments() {
+        for (p, q) in split_segment(a, b, &edges) {
+            match locate(interpolate(p, q, 0.5), &polygons) {
+                Location::Interior => any_interior = true,
+                Location::Boundary => (),
+                Location::Exterior => return false,
+            }
+        }
+    }
+    if other.dimension() == 2 {
+        // The boundary of `other` lies within `shape`, but a hole in `shape`
+        // might still lie within `other`.
+        let other_polygons = other.polygons();
+        shape
+            .vertices()
+            .into_iter()
+            .all(|v| locate(v, &other_polygons) != Location::Interior)
+    } else {
+        any_interior
+    }
+}
+
+/// Implements [`Shape::contains`] for linestrings and multilinestrings.
+fn lineal_contains(shape: &Shape, other: &Shape) -> bool {
+    let segments = shape.segments();
+    match other.dimension() {
+        0 => {
+            // The boundary of a lineal shape consists of the endpoints of its
+            // linestrings that are shared by an odd number of linestrings.
+            let mut endpoints = vec![];
+            for line in shape.lines() {
+                endpoints.push(line[0]);
+                endpoints.push(line[line.len() - 1]);
+            }
+            let on_boundary = |p: &Coord| endpoints.iter().filter(|e| *e == p).count() % 2 == 1;
+            let vertices = other.vertices();
+            vertices
+                .iter()
+                .all(|p| segments.iter().any(|(a, b)| on_segment(*p, *a, *b)))
+                && vertices.iter().any(|p| !on_boundary(p))
+        }
+        1 => other
+            .segments()
+            .into_iter()
+            .all(|(a, b)| segment_covered(a, b, &segments)),
+        _ => false,
+    }
+}
+
+/// Reports whether the segment from `a` to `b` lies within the union of
+/// `segments`.
+fn segment_covered(a: Coord, b: Coord, segments: &[(Coord, Coord)]) -> bool {
+    if a == b {
+        return segments.iter().any(|(c, d)| on_segment(a, *c, *d));
+    }
+    let mut intervals: Vec<(f64, f64)> = segments
+        .iter()
+        .filter(|(c, d)| orientation(a, b, *c) == 0.0 && orientation(a, b, *d) == 0.0)
+        .map(|(c, d)| {
+            let (t0, t1) = (project(*c, a, b), project(*d, a, b));
+            (t0.min(t1), t0.max(t1))
+        })
+        .collect();
+    intervals.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap());
+    let mut covered = 0.0;
+    for (lo, hi) in intervals {
+        if lo > covered {
+            break;
+        }
+        covered = f64::max(covered, hi);
+    }
+    covered >= 1.0
+}
+
+// --------------------------------------------------------------------------------
+// distance
+
+/// A means of measuring the distance between coordinates.
+trait Metric {
+    /// Returns the distance between `p` and `q`.
+    fn point_distance(p: Coord, q: Coord) -> f64;
+
+    /// Returns the distance between `p` and the segment from `a` to `b`.
+    fn segment_distance(p: Coord, a: Coord, b: Coord) -> f64;
+
+    /// Reports whether the segment from `a` to `b` intersects the segment
+    /// from `c` to `d`.
+    fn segments_intersect(a: Coord, b: Coord, c: Coord, d: Coord) -> bool;
+}
+
+fn distance<M: Metric>(a: &Shape, b: &Shape) -> f64 {
+    // Shapes are at distance zero if either has a vertex within the other's
+    // polygons, or if any of their segments intersect.
+    let (a_vertices, b_vertices) = (a.vertices(), b.vertices());
+    let (a_polygons, b_polygons) = (a.polygons(), b.polygons());
+    if a_vertices
+        .iter()
+        .any(|v| locate(*v, &b_polygons) != Location::Exterior)
+        || b_vertices
+            .iter()
+            .any(|v| locate(*v, &a_polygons) != Location::Exterior)
+    {
+        return 0.0;
+    }
+    let (a_segments, b_segments) = (a.segments(), b.segments());
+    for (p, q) in &a_segments {
+        for (r, s) in &b_segments {
+            if M::segments_intersect(*p, *q, *r, *s) {
+                return 0.0;
+            }
+        }
+    }
+
+    // Otherwise, the nearest points of the shapes include a vertex of one of
+    // the shapes.
+    let nearest = |v: Coord, vertices: &[Coord], segments: &[(Coord, Coord)]| {
+        if segments.is_empty() {
+            vertices
+                .iter()
+                .map(|w| M::point_distance(v, *w))
+                .fold(f64::INFINITY, f64::min)
+        } else {
+            segments
+                .iter()
+                .map(|(p, q)| M::segment_distance(v, *p, *q))
+                .fold(f64::INFINITY, f64::min)
+        }
+    };
+    let a_nearest = a_vertices
+        .iter()
+        .map(|v| nearest(*v, &b_vertices, &b_segments));
+    let b_nearest = b_vertices
+        .iter()
+        .map(|v| nearest(*v, &a_vertices, &a_segments));
+    a_nearest.chain(b_nearest).fold(f64::INFINITY, f64::min)
+}
+
+/// Measures distances in the Cartesian plane.
+struct Planar;
+
+impl Metric for Planar {
+    fn point_distance(p: Coord, q: Coord) -> f64 {
+        (p.x - q.x).hypot(p.y - q.y)
+    }
+
+    fn segment_distance(p: Coord, a: Coord, b: Coord) -> f64 {
+        if a == b {
+            return Self::point_distance(p, a);
+        }
+        let t = project(p, a, b).max(0.0).min(1.0);
+        Self::point_distance(p, interpolate(a, b, t))
+    }
+
+    fn segments_intersect(a: Coord, b: Coord, c: Coord, d: Coord) -> bool {
+        let opposite = |o1: f64, o2: f64| (o1 < 0.0 && o2 > 0.0) || (o1 > 0.0 && o2 < 0.0);
+        (opposite(orientation(a, b, c), orientation(a, b, d))
+            && opposite(orientation(c, d, a), orientation(c, d, b)))
+            || on_segment(c, a, b)
+            || on_segment(d, a, b)
+            || on_segment(a, c, d)
+            || on_segment(b, c, d)
+    }
+}
+
+/// Measures distances along the surface of a spherical Earth, treating
+/// coordinates as longitudes and latitudes in degrees.
+struct Spherical;
+
+type Vector = [f64; 3];
+
+impl Spherical {
+    /// Converts a coordinate to a unit vector.
+    fn vector(c: Coord) -> Vector {
+        let (lon, lat) = (c.x.to_radians(), c.y.to_radians());
+        [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+    }
+
+    /// Reports whether `x`, which lies on the great circle through `a` and
+    /// `b` with normal `n`, lies on the shorter arc between them.
+    fn on_arc(x: Vector, a: Vector, b: Vector, n: Vector) -> bool {
+        dot(cross(a, x), n) >= 0.0 && dot(cross(x, b), n) >= 0.0
+    }
+}
+
+impl Metric for Spherical {
+    fn point_distance(p: Coord, q: Coord) -> f64 {
+        // The haversine formula.
+        let (lat1, lat2) = (p.y.to_radians(), q.y.to_radians());
+        let (dlat, dlon) = (lat2 - lat1, (q.x - p.x).to_radians());
+        let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
+    }
+
+    fn segment_distance(p: Coord, a: Coord, b: Coord) -> f64 {
+        let endpoints = || f64::min(Self::point_distance(p, a), Self::point_distance(p, b));
+        let (vp, va, vb) = (Self::vector(p), Self::vector(a), Self::vector(b));
+        let n = cross(va, vb);
+        let len = norm(n);
+        if len < 1e-15 {
+            return endpoints();
+        }
+        let n = scale(n, 1.0 / len);
+        // The nearest point on the great circle is the projection of `p` onto
+        // the circle's plane. If it lies on the arc, the distance is the angle
+        // between `p` and the plane.
+        let sin_angle = dot(vp, n);
+        let c = sub3(vp, scale(n, sin_angle));
+        if norm(c) > 1e-15 && Self::on_arc(c, va, vb, n) {
+            EARTH_RADIUS * sin_angle.abs().min(1.0).asin()
+        } else {
+            endpoints()
+        }
+    }
+
+    fn segments_intersect(a: Coord, b: Coord, c: Coord, d: Coord) -> bool {
+        let (va, vb, vc, vd) = (
+            Self::vector(a),
+            Self::vector(b),
+            Self::vector(c),
+            Self::vector(d),
+        );
+        let (n1, n2) = (cross(va, vb), cross(vc, vd));
+        let x = cross(n1, n2);
+        if norm(x) < 1e-15 {
+            // The arcs lie on the same great circle (or are degenerate), so
+            // they intersect only if an endpoint of one lies on the other.
+            const EPSILON: f64 = 1e-6;
+            return Self::segment_distance(c, a, b) < EPSILON
+                || Self::segment_distance(d, a, b) < EPSILON
+                || Self::segment_distance(a, c, d) < EPSILON
+                || Self::segment_distance(b, c, d) < EPSILON;
+        }
+        // The great circles intersect at two antipodal points.
+        [x, scale(x, -1.0)]
+            .iter()
+            .any(|x| Self::on_arc(*x, va, vb, n1) && Self::on_arc(*x, vc, vd, n2))
+    }
+}
+
+fn dot(a: Vector, b: Vector) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: Vector, b: Vector) -> Vector {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(a: Vector) -> f64 {
+    dot(a, a).sqrt()
+}
+
+fn scale(a: Vector, s: f64) -> Vector {
+    [a[0] * s, a[1] * s, a[2] * s]
+}
+
+fn sub3(a: Vector, b: Vector) -> Vector {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+// --------------------------------------------------------------------------------
+// errors
+
+/// An error that can occur when constructing or operating on a geometry.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum InvalidGeometryError {
+    /// The geometry is malformed.
+    Malformed(String),
+    /// The geometry uses a feature that is not supported.
+    Unsupported(String),
+    /// An operation was applied to geometries with different SRIDs.
+    MixedSrid(i32, i32),
+    /// A geography has an SRID other than 4326.
+    InvalidGeographySrid(i32),
+    /// A geography has coordinates that are not valid longitudes and
+    /// latitudes.
+    CoordinateOutOfRange,
+}
+
+impl fmt::Display for InvalidGeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidGeometryError::Malformed(details) => write!(f, "invalid geometry: {}", details),
+            InvalidGeometryError::Unsupported(feature) => f.write_str(feature),
+            InvalidGeometryError::MixedSrid(a, b) => {
+                write!(f, "operation on mixed SRID geometries ({} != {})", a, b)
+            }
+            InvalidGeometryError::InvalidGeographySrid(srid) => write!(
+                f,
+                "geography only supports SRID {}, not {}",
+                SRID_WGS84, srid
+            ),
+            InvalidGeometryError::CoordinateOutOfRange => {
+                f.write_str("coordinate values are out of range [-180 -90, 180 90] for geography")
+            }
+        }
+    }
+}
+
+impl Error for InvalidGeometryError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+fn malformed<S: Into<String>>(details: S) -> InvalidGeometryError {
+    InvalidGeometryError::Malformed(details.into())
+}
+
+fn unsupported<S: Into<String>>(feature: S) -> InvalidGeometryError {
+    InvalidGeometryError::Unsupported(feature.into())
+}
+
+fn empty() -> InvalidGeometryError {
+    unsupported("empty geometries are not supported")
+}
+
+fn collections_unsupported() -> InvalidGeometryError {
+    unsupported("geometry collections are not supported")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shape(wkt: &str) -> Shape {
+        parse_ewkt(wkt).unwrap().1
+    }
+
+    #[test]
+    fn test_wkt_round_trip() {
+        for wkt in &[
+            "POINT(1 2)",
+            "LINESTRING(0 0,1.5 -2)",
+            "POLYGON((0 0,4 0,4 4,0 4,0 0),(1 1,2 1,2 2,1 1))",
+            "MULTIPOINT(0 0,1 1)",
+            "MULTILINESTRING((0 0,1 1),(2 2,3 3))",
+            "MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((5 5,6 5,6 6,5 5)))",
+        ] {
+            assert_eq!(shape(wkt).to_string(), *wkt);
+        }
+        assert_eq!(
+            parse_ewkt(" srid=4326; multipoint ( (0 0), (1 1) ) ").unwrap(),
+            (4326, shape("MULTIPOINT(0 0,1 1)"))
+        );
+    }
+
+    #[test]
+    fn test_wkb_round_trip() {
+        let s = shape("MULTIPOLYGON(((0 0,1 0,1 1,0 0)),((5 5,6 5,6 6,5 5)))");
+        for srid in &[0, 3857] {
+            let mut buf = vec![];
+            encode_ewkb(&mut buf, *srid, &s);
+            assert_eq!(decode_ewkb(&buf).unwrap(), (*srid, s.clone()));
+        }
+
+        // A big-endian WKB point.
+        let wkb = hex::decode("00000000013FF00000000000004000000000000000").unwrap();
+        assert_eq!(decode_ewkb(&wkb).unwrap(), (0, shape("POINT(1 2)")));
+    }
+
+    #[test]
+    fn test_contains() {
+        let square = shape("POLYGON((0 0,10 0,10 10,0 10,0 0))");
+        let holey = shape("POLYGON((0 0,10 0,10 10,0 10,0 0),(4 4,6 4,6 6,4 6,4 4))");
+        let notch = shape("POLYGON((0 0,10 0,10 10,5 5,0 10,0 0))");
+        for (a, b, expected) in &[
+            (&square, "POINT(5 5)", true),
+            (&square, "POINT(10 5)", false),
+            (&square, "MULTIPOINT(10 5,5 5)", true),
+            (&square, "LINESTRING(0 0,10 0)", false),
+            (&square, "LINESTRING(0 0,10 10)", true),
+            (&square, "LINESTRING(5 5,15 5)", false),
+            (&square, "POLYGON((1 1,2 1,2 2,1 1))", true),
+            (&square, "POLYGON((0 0,10 0,10 10,0 10,0 0))", true),
+            (&holey, "POINT(5 5)", false),
+            (&holey, "POINT(2 2)", true),
+            (&holey, "POLYGON((1 1,9 1,9 9,1 9,1 1))", false),
+            (&notch, "LINESTRING(1 9,9 9)", false),
+            (&notch, "LINESTRING(1 1,9 1)", true),
+        ] {
+            assert_eq!(a.contains(&shape(b)), *expected, "{} contains {}", a, b);
+        }
+
+        let line = shape("LINESTRING(0 0,5 0,10 0)");
+        assert!(line.contains(&shape("POINT(5 0)")));
+        assert!(!line.contains(&shape("POINT(0 0)")));
+        assert!(line.contains(&shape("LINESTRING(2 0,8 0)")));
+        assert!(!line.contains(&shape("LINESTRING(2 0,12 0)")));
+        assert!(shape("POINT(1 1)").contains(&shape("POINT(1 1)")));
+    }
+
+    #[test]
+    fn test_distance() {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        let square = shape("POLYGON((0 0,10 0,10 10,0 10,0 0))");
+        assert!(close(square.distance(&shape("POINT(5 5)")), 0.0));
+        assert!(close(square.distance(&shape("POINT(13 14)")), 5.0));
+        assert!(close(square.distance(&shape("LINESTRING(-5 5,15 5)")), 0.0));
+        assert!(close(
+            shape("LINESTRING(0 0,0 10)").distance(&shape("LINESTRING(3 5,7 5)")),
+            3.0
+        ));
+
+        // One degree of longitude along the equator.
+        let d = shape("POINT(0 0)").spherical_distance(&shape("POINT(1 0)"));
+        assert!((d - 111_195.08).abs() < 0.01, "{}", d);
+        // The nearest point of an arc may lie between its endpoints.
+        let d = shape("POINT(0 1)").spherical_distance(&shape("LINESTRING(-1 0,1 0)"));
+        assert!((d - 111_195.08).abs() < 0.01, "{}", d);
+        let d = shape("LINESTRING(-1 -1,1 1)").spherical_distance(&shape("LINESTRING(-1 1,1 -1)"));
+        assert!(close(d, 0.0), "{}", d);
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Network address data types.
+
+use std::cmp::Ordering;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// An IPv4 or IPv6 host address with an optional subnet mask, or an IPv4 or
+/// IPv6 network.
+///
+/// The same representation backs both the `inet` and `cidr` SQL types. Values
+/// of type `cidr` additionally guarantee that no bits are set in the address
+/// to the right of the subnet mask.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Inet {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Inet {
+    /// Constructs a new network address from an IP address and the length of
+    /// its subnet mask, in bits.
+    ///
+    /// Returns `None` if `prefix_len` is longer than the address.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Inet> {
+        if prefix_len > max_prefix_len(&addr) {
+            return None;
+        }
+        Some(Inet { addr, prefix_len })
+    }
+
+    /// Returns the IP address, including any bits to the right of the subnet
+    /// mask.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Returns the length of the subnet mask, in bits.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Reports whether the subnet mask covers the entire address, i.e.,
+    /// whether the value identifies a single host.
+    pub fn is_host(&self) -> bool {
+        self.prefix_len == max_prefix_len(&self.addr)
+    }
+
+    /// Reports whether no bits are set in the address to the right of the
+    /// subnet mask, i.e., whether the value is a valid `cidr`.
+    pub fn is_network(&self) -> bool {
+        self.addr == self.network_addr()
+    }
+
+    /// Returns the address with all bits to the right of the subnet mask
+    /// cleared.
+    pub fn network_addr(&self) -> IpAddr {
+        mask(self.addr, self.prefix_len)
+    }
+
+    /// Returns the network that contains the address, i.e., the address with
+    /// all bits to the right of the subnet mask cleared.
+    pub fn network(&self) -> Inet {
+        Inet {
+            addr: self.network_addr(),
+            prefix_len: self.prefix_len,
+        }
+    }
+
+    /// Reports whether `other` is within or equal to the network identified
+    /// by `self`.
+    pub fn contains(&self, other: &Inet) -> bool {
+        same_family(&self.addr, &other.addr)
+            && self.prefix_len <= other.prefix_len
+            && self.network_addr() == mask(other.addr, self.prefix_len)
+    }
+
+    /// Reports whether `other` is strictly within the network identified by
+    /// `self`.
+    pub fn contains_strict(&self, other: &Inet) -> bool {
+        self.prefix_len < other.prefix_len && self.contains(other)
+    }
+}
+
+impl Ord for Inet {
+    /// Orders addresses as PostgreSQL does: IPv4 addresses sort before IPv6
+    /// addresses, then addresses sort by their network, then by the length of
+    /// their subnet mask, and finally by their full address.
+    fn cmp(&self, other: &Inet) -> Ordering {
+        let common_prefix_len = self.prefix_len.min(other.prefix_len);
+        family_rank(&self.addr)
+            .cmp(&family_rank(&other.addr))
+            .then_with(|| {
+                to_u128(mask(self.addr, common_prefix_len))
+                    .cmp(&to_u128(mask(other.addr, common_prefix_len)))
+            })
+            .then_with(|| self.prefix_len.cmp(&other.prefix_len))
+            .then_with(|| to_u128(self.addr).cmp(&to_u128(other.addr)))
+    }
+}
+
+impl PartialOrd for Inet {
+    fn partial_cmp(&self, other: &Inet) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Returns the number of bits in `addr`.
+pub fn max_prefix_len(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn same_family(a: &IpAddr, b: &IpAddr) -> bool {
+    family_rank(a) == family_rank(b)
+}
+
+fn family_rank(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 0,
+        IpAddr::V6(_) => 1,
+    }
+}
+
+/// Returns the bits of `addr`, aligned to the right.
+fn to_u128(addr: IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(a) => u128::from(u32::from(a)),
+        IpAddr::V6(a) => u128::from(a),
+    }
+}
+
+/// Clears all but the leftmost `prefix_len` bits of `addr`.
+fn mask(addr: IpAddr, prefix_len: u8) -> IpAddr {
+    match addr {
+        IpAddr::V4(a) => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(prefix_len))
+                .unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(a) & mask))
+        }
+        IpAddr::V6(a) => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(prefix_len))
+                .unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(a) & mask))
+        }
+    }
+}
@@ -0,0 +1,309 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A range data type.
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::row::DatumList;
+use crate::Datum;
+
+/// Set in a range's flags if the range is empty.
+pub(crate) const RANGE_EMPTY: u8 = 0x01;
+/// Set in a range's flags if the lower bound is inclusive.
+pub(crate) const RANGE_LB_INC: u8 = 0x02;
+/// Set in a range's flags if the upper bound is inclusive.
+pub(crate) const RANGE_UB_INC: u8 = 0x04;
+
+/// A contiguous range of values of some element type.
+///
+/// Ranges are always stored in canonical form, so that equal ranges have
+/// equal representations: ranges that contain no values are the empty range,
+/// unbounded ends are never inclusive, and ranges over discrete element types
+/// have an inclusive lower bound and an exclusive upper bound.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Range<'a> {
+    /// A combination of the `RANGE_*` flags.
+    pub(crate) flags: u8,
+    /// The lower and upper bound, in that order, where `Datum::Null` indicates
+    /// an unbounded end. Empty if the range is empty.
+    pub(crate) bounds: DatumList<'a>,
+}
+
+impl<'a> Range<'a> {
+    /// Reports whether the range contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.flags & RANGE_EMPTY != 0
+    }
+
+    /// Returns the lower bound of the range, or `None` if the range is empty.
+    pub fn lower(&self) -> Option<RangeBound<Datum<'a>>> {
+        self.bounds().map(|(lower, _)| lower)
+    }
+
+    /// Returns the upper bound of the range, or `None` if the range is empty.
+    pub fn upper(&self) -> Option<RangeBound<Datum<'a>>> {
+        self.bounds().map(|(_, upper)| upper)
+    }
+
+    /// Returns the lower and upper bounds of the range, or `None` if the range
+    /// is empty.
+    pub fn bounds(&self) -> Option<(RangeBound<Datum<'a>>, RangeBound<Datum<'a>>)> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut bounds = self.bounds.iter();
+        let lower = bounds.next().unwrap();
+        let upper = bounds.next().unwrap();
+        Some((
+            RangeBound::new(lower, self.flags & RANGE_LB_INC != 0),
+            RangeBound::new(upper, self.flags & RANGE_UB_INC != 0),
+        ))
+    }
+
+    /// Reports whether `elem` lies within the range.
+    pub fn contains_elem(&self, elem: Datum) -> bool {
+        match self.bounds() {
+            None => false,
+            Some((lower, upper)) => {
+                let above_lower = match lower.value {
+                    None => true,
+                    Some(v) => v < elem || (v == elem && lower.inclusive),
+                };
+                let below_upper = match upper.value {
+                    None => true,
+                    Some(v) => elem < v || (v == elem && upper.inclusive),
+                };
+                above_lower && below_upper
+            }
+        }
+    }
+
+    /// Reports whether every value in `other` lies within the range.
+    pub fn contains_range(&self, other: &Range) -> bool {
+        match (self.bounds(), other.bounds()) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some((l1, u1)), Some((l2, u2))) => {
+                cmp_lower(&l1, &l2) != Ordering::Greater && cmp_upper(&u1, &u2) != Ordering::Less
+            }
+        }
+    }
+
+    /// Reports whether the range and `other` have any values in common.
+    pub fn overlaps(&self, other: &Range) -> bool {
+        match (self.bounds(), other.bounds()) {
+            (Some((l1, u1)), Some((l2, u2))) => {
+                !lower_follows_upper(&l1, &u2) && !lower_follows_upper(&l2, &u1)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Ord for Range<'_> {
+    /// Orders ranges as PostgreSQL does: the empty range sorts first, and
+    /// other ranges sort by their lower bound and then by their upper bound.
+    fn cmp(&self, other: &Range) -> Ordering {
+        match (self.bounds(), other.bounds()) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (Some((l1, u1)), Some((l2, u2))) => {
+                cmp_lower(&l1, &l2).then_with(|| cmp_upper(&u1, &u2))
+            }
+        }
+    }
+}
+
+impl PartialOrd for Range<'_> {
+    fn partial_cmp(&self, other: &Range) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// One end of a [`Range`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct RangeBound<T> {
+    /// The value of the bound, or `None` if the range is unbounded at this
+    /// end.
+    pub value: Option<T>,
+    /// Whether the bound's value is itself within the range.
+    pub inclusive: bool,
+}
+
+impl<T> RangeBound<T> {
+    /// Converts from `&RangeBound<T>` to `RangeBound<&T>`.
+    pub fn as_ref(&self) -> RangeBound<&T> {
+        RangeBound {
+            value: self.value.as_ref(),
+            inclusive: self.inclusive,
+        }
+    }
+
+    /// Maps the bound's value, if any, with `f`.
+    pub fn map<U, F>(self, f: F) -> RangeBound<U>
+    where
+        F: FnOnce(T) -> U,
+    {
+        RangeBound {
+            value: self.value.map(f),
+            inclusive: self.inclusive,
+        }
+    }
+}
+
+impl<'a> RangeBound<Datum<'a>> {
+    /// Constructs a bound from a datum, where `Datum::Null` indicates an
+    /// unbounded end.
+    pub fn new(value: Datum<'a>, inclusive: bool) -> RangeBound<Datum<'a>> {
+        RangeBound {
+            value: if value.is_null() { None } else { Some(value) },
+            inclusive,
+        }
+    }
+
+    /// Returns the bound's value as a datum, where `Datum::Null` indicates an
+    /// unbounded end.
+    pub fn datum(&self) -> Datum<'a> {
+        self.value.unwrap_or(Datum::Null)
+    }
+}
+
+/// Compares two lower bounds, where lesser bounds admit more values.
+fn cmp_lower(a: &RangeBound<Datum>, b: &RangeBound<Datum>) -> Ordering {
+    match (a.value, b.value) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(x), Some(y)) => x.cmp(&y).then_with(|| b.inclusive.cmp(&a.inclusive)),
+    }
+}
+
+/// Compares two upper bounds, where greater bounds admit more values.
+fn cmp_upper(a: &RangeBound<Datum>, b: &RangeBound<Datum>) -> Ordering {
+    match (a.value, b.value) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => x.cmp(&y).then_with(|| a.inclusive.cmp(&b.inclusive)),
+    }
+}
+
+/// Reports whether every value admitted by the lower bound `lower` is greater
+/// than every value admitted by the upper bound `upper`.
+fn lower_follows_upper(lower: &RangeBound<Datum>, upper: &RangeBound<Datum>) -> bool {
+    match (lower.value, upper.value) {
+        (Some(l), Some(u)) => l > u || (l == u && !(lower.inclusive && upper.inclusive)),
+        _ => false,
+    }
+}
+
+/// Merges ranges that overlap or are adjacent to one another.
+///
+/// Returns the bounds of the disjoint, non-adjacent ranges that together
+/// contain exactly the values in `ranges`, in ascending order. Empty ranges
+/// contribute no values and so are dropped.
+pub fn merge<'a, I>(ranges: I) -> Vec<(RangeBound<Datum<'a>>, RangeBound<Datum<'a>>)>
+where
+    I: IntoIterator<Item = Range<'a>>,
+{
+    let mut ranges: Vec<_> = ranges.into_iter().filter_map(|r| r.bounds()).collect();
+    ranges.sort_by(|(l1, u1), (l2, u2)| cmp_lower(l1, l2).then_with(|| cmp_upper(u1, u2)));
+    let mut merged: Vec<(RangeBound<Datum>, RangeBound<Datum>)> = vec![];
+    for (lower, upper) in ranges {
+        if let Some((_, last_upper)) = merged.last_mut() {
+            // Since the ranges are sorted by their lower bound, this range
+            // extends the last merged range unless a gap separates the two.
+            let adjacent = match (lower.value, last_upper.value) {
+                (Some(l), Some(u)) => l == u && (lower.inclusive || last_upper.inclusive),
+                _ => false,
+            };
+            if !lower_follows_upper(&lower, last_upper) || adjacent {
+                if cmp_upper(&upper, last_upper) == Ordering::Greater {
+                    *last_upper = upper;
+                }
+                continue;
+            }
+        }
+        merged.push((lower, upper));
+    }
+    merged
+}
+
+/// Converts the bounds of a range into canonical form.
+///
+/// Returns `None` if the bounds describe the empty range.
+pub(crate) fn canonicalize<'a>(
+    mut lower: RangeBound<Datum<'a>>,
+    mut upper: RangeBound<Datum<'a>>,
+) -> Result<Option<(RangeBound<Datum<'a>>, RangeBound<Datum<'a>>)>, InvalidRangeError> {
+    if let (Some(l), Some(u)) = (lower.value, upper.value) {
+        if l > u {
+            return Err(InvalidRangeError::MisorderedBounds);
+        }
+    }
+
+    // Unbounded ends never include their (nonexistent) value.
+    lower.inclusive &= lower.value.is_some();
+    upper.inclusive &= upper.value.is_some();
+
+    // Bounds over discrete types are adjusted to be inclusive below and
+    // exclusive above, so that e.g. `(1,4]` and `[2,5)` are the same range.
+    if let Some(Datum::Int32(l)) = lower.value {
+        if !lower.inclusive {
+            let l = l.checked_add(1).ok_or(InvalidRangeError::BoundOutOfRange)?;
+            lower = RangeBound::new(Datum::Int32(l), true);
+        }
+    }
+    if let Some(Datum::Int32(u)) = upper.value {
+        if upper.inclusive {
+            let u = u.checked_add(1).ok_or(InvalidRangeError::BoundOutOfRange)?;
+            upper = RangeBound::new(Datum::Int32(u), false);
+        }
+    }
+
+    if let (Some(l), Some(u)) = (lower.value, upper.value) {
+        if l == u && !(lower.inclusive && upper.inclusive) {
+            return Ok(None);
+        }
+    }
+    Ok(Some((lower, upper)))
+}
+
+/// An error that can occur when constructing a range.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum InvalidRangeError {
+    /// The lower bound of the range is greater than its upper bound.
+    MisorderedBounds,
+    /// Converting a bound of a discrete range to canonical form overflowed
+    /// the element type.
+    BoundOutOfRange,
+}
+
+impl fmt::Display for InvalidRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidRangeError::MisorderedBounds => {
+                f.write_str("range lower bound must be less than or equal to range upper bound")
+            }
+            InvalidRangeError::BoundOutOfRange => f.write_str("integer out of range"),
+        }
+    }
+}
+
+impl Error for InvalidRangeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
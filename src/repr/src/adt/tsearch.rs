@@ -0,0 +1,707 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Full-text search data types.
+//!
+//! The types in this module implement a subset of PostgreSQL's [text search]
+//! types: `tsvector`, a sorted list of lexemes with optional positions, and
+//! `tsquery`, a boolean combination of lexemes to search for. Only the
+//! `simple` text search configuration is supported.
+//!
+//! [text search]: https://www.postgresql.org/docs/current/datatype-textsearch.html
+
+use std::fmt;
+
+/// The maximum length of a lexeme, in bytes.
+const MAX_LEXEME_LEN: usize = 2047;
+
+/// The maximum number of positions stored for a single lexeme.
+const MAX_POSITIONS: usize = 256;
+
+/// The maximum position. Larger positions are silently clamped.
+const MAX_POSITION: u16 = 16383;
+
+const PHRASE_UNSUPPORTED: &str = "phrase search operators are not supported";
+
+/// The weight of an occurrence of a lexeme, from least (`D`) to most (`A`)
+/// important.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Weight {
+    D,
+    C,
+    B,
+    A,
+}
+
+impl Weight {
+    fn from_char(c: char) -> Option<Weight> {
+        match c.to_ascii_uppercase() {
+            'A' => Some(Weight::A),
+            'B' => Some(Weight::B),
+            'C' => Some(Weight::C),
+            'D' => Some(Weight::D),
+            _ => None,
+        }
+    }
+
+    fn as_char(self) -> char {
+        match self {
+            Weight::A => 'A',
+            Weight::B => 'B',
+            Weight::C => 'C',
+            Weight::D => 'D',
+        }
+    }
+
+    /// Returns the bit that represents this weight in a weight mask.
+    fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// An occurrence of a lexeme in a document.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Position {
+    /// The 1-based position of the occurrence.
+    pub pos: u16,
+    /// The weight of the occurrence.
+    pub weight: Weight,
+}
+
+/// A lexeme in a [`TsVector`], with the positions at which it occurs.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Lexeme {
+    /// The normalized text of the lexeme.
+    pub text: String,
+    /// The positions of the lexeme, in ascending order. A lexeme may have no
+    /// positions if its `tsvector` was constructed without them.
+    pub positions: Vec<Position>,
+}
+
+/// A document, represented as a list of lexemes.
+///
+/// Lexemes are sorted and unique, and their positions are sorted and unique,
+/// so that equal documents have equal representations.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TsVector {
+    lexemes: Vec<Lexeme>,
+}
+
+impl TsVector {
+    /// Constructs a document from an arbitrary list of lexemes, merging
+    /// duplicate lexemes and positions.
+    ///
+    /// When the same position of a lexeme appears more than once, the highest
+    /// weight wins. Lexemes keep at most their first 256 positions.
+    fn from_lexemes(mut lexemes: Vec<Lexeme>) -> TsVector {
+        lexemes.sort_by(|a, b| a.text.cmp(&b.text));
+        let mut out: Vec<Lexeme> = Vec::with_capacity(lexemes.len());
+        for lexeme in lexemes {
+            match out.last_mut() {
+                Some(last) if last.text == lexeme.text => last.positions.extend(lexeme.positions),
+                _ => out.push(lexeme),
+            }
+        }
+        for lexeme in &mut out {
+            lexeme
+                .positions
+                .sort_by(|a, b| a.pos.cmp(&b.pos).then(b.weight.cmp(&a.weight)));
+            lexeme.positions.dedup_by_key(|p| p.pos);
+            lexeme.positions.truncate(MAX_POSITIONS);
+        }
+        TsVector { lexemes: out }
+    }
+
+    /// Returns the lexemes in the document, in sorted order.
+    pub fn lexemes(&self) -> &[Lexeme] {
+        &self.lexemes
+    }
+
+    /// Returns the lexemes that match `text`, or, if `prefix` is true, that
+    /// begin with `text`.
+    fn find<'a>(&'a self, text: &'a str, prefix: bool) -> impl Iterator<Item = &'a Lexeme> {
+        let start = match self.lexemes.binary_search_by(|l| l.text.as_str().cmp(text)) {
+            Ok(i) | Err(i) => i,
+        };
+        self.lexemes[start..]
+            .iter()
+            .take_while(move |l| l.text == text || (prefix && l.text.starts_with(text)))
+    }
+}
+
+impl fmt::Display for TsVector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, lexeme) in self.lexemes.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write_quoted(f, &lexeme.text)?;
+            for (j, position) in lexeme.positions.iter().enumerate() {
+                f.write_str(if j == 0 { ":" } else { "," })?;
+                write!(f, "{}", position.pos)?;
+                if position.weight != Weight::D {
+                    write!(f, "{}", position.weight.as_char())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes `text` surrounded by single quotes, doubling any embedded single
+/// quotes or backslashes.
+fn write_quoted(f: &mut fmt::Formatter, text: &str) -> fmt::Result {
+    f.write_str("'")?;
+    for c in text.chars() {
+        if c == '\'' || c == '\\' {
+            write!(f, "{}", c)?;
+        }
+        write!(f, "{}", c)?;
+    }
+    f.write_str("'")
+}
+
+/// Parses a [`TsVector`] from its text representation.
+///
+/// The input is a whitespace-separated list of lexemes, each of which is
+/// optionally quoted and optionally followed by a colon and a comma-separated
+/// list of positions. Each position may be followed by a weight letter. The
+/// lexemes are not normalized.
+pub fn parse_tsvector(s: &str) -> Result<TsVector, String> {
+    let mut lexemes = vec![];
+    let mut chars = s.chars().peekable();
+    loop {
+        while chars.peek().map_or(false, |c| c.is_whitespace()) {
+            chars.next();
+        }
+        let text = match chars.peek() {
+            None => break,
+            Some('\'') => {
+                chars.next();
+                read_quoted(&mut chars)?
+            }
+            Some(_) => {
+                let mut text = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || (c == ':' && next_is_digit(&chars)) {
+                        break;
+                    }
+                    chars.next();
+                    match c {
+                        '\\' => text.push(chars.next().ok_or("unexpected end of input")?),
+                        _ => text.push(c),
+                    }
+                }
+                text
+            }
+        };
+        if text.is_empty() {
+            return Err("empty lexeme".into());
+        }
+        if text.len() > MAX_LEXEME_LEN {
+            return Err(format!(
+                "word is too long ({} bytes, max {} bytes)",
+                text.len(),
+                MAX_LEXEME_LEN
+            ));
+        }
+        let mut positions = vec![];
+        if chars.peek() == Some(&':') {
+            chars.next();
+            loop {
+                let mut digits = String::new();
+                while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                    digits.push(*c);
+                    chars.next();
+                }
+                let pos = match digits.parse::<u64>() {
+                    Ok(0) | Err(_) => return Err("invalid position".into()),
+                    Ok(pos) if pos > u64::from(MAX_POSITION) => MAX_POSITION,
+                    Ok(pos) => pos as u16,
+                };
+                let weight = match chars.peek().and_then(|c| Weight::from_char(*c)) {
+                    Some(weight) => {
+                        chars.next();
+                        weight
+                    }
+                    None => Weight::D,
+                };
+                positions.push(Position { pos, weight });
+                if chars.peek() == Some(&',') {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+        }
+        match chars.peek() {
+            Some(c) if !c.is_whitespace() => {
+                return Err(format!("unexpected character \"{}\"", c));
+            }
+            _ => (),
+        }
+        lexemes.push(Lexeme { text, positions });
+    }
+    Ok(TsVector::from_lexemes(lexemes))
+}
+
+/// Reports whether the character after the next character in `chars` is an
+/// ASCII digit.
+fn next_is_digit(chars: &std::iter::Peekable<std::str::Chars>) -> bool {
+    let mut chars = chars.clone();
+    chars.next();
+    chars.next().map_or(false, |c| c.is_ascii_digit())
+}
+
+/// Reads the remainder of a single-quoted string whose opening quote has
+/// already been consumed. Within the string, a doubled quote or a backslash
+/// escapes the following character.
+fn read_quoted(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    let mut text = String::new();
+    loop {
+        match chars.next() {
+            None => return Err("unterminated quoted string".into()),
+            Some('\\') => text.push(chars.next().ok_or("unexpected end of input")?),
+            Some('\'') if chars.peek() == Some(&'\'') => {
+                chars.next();
+                text.push('\'');
+            }
+            Some('\'') => return Ok(text),
+            Some(c) => text.push(c),
+        }
+    }
+}
+
+/// A node in the tree of a [`TsQuery`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum QueryNode {
+    /// Matches documents that contain a lexeme.
+    Operand {
+        /// The lexeme to search for.
+        lexeme: String,
+        /// Whether to match any lexeme that begins with `lexeme`.
+        prefix: bool,
+        /// A mask of the weights that the lexeme must occur with, or zero to
+        /// match the lexeme at any weight.
+        weights: u8,
+    },
+    /// Matches documents that do not match the inner node.
+    Not(Box<QueryNode>),
+    /// Matches documents that match both nodes.
+    And(Box<QueryNode>, Box<QueryNode>),
+    /// Matches documents that match either node.
+    Or(Box<QueryNode>, Box<QueryNode>),
+}
+
+impl QueryNode {
+    /// Returns the binding strength of the node's operator, for the purposes
+    /// of deciding where parentheses are required.
+    fn priority(&self) -> u8 {
+        match self {
+            QueryNode::Or(..) => 1,
+            QueryNode::And(..) => 2,
+            QueryNode::Not(_) => 3,
+            QueryNode::Operand { .. } => 4,
+        }
+    }
+
+    fn matches(&self, doc: &TsVector) -> bool {
+        match self {
+            QueryNode::Operand {
+                lexeme,
+                prefix,
+                weights,
+            } => doc.find(lexeme, *prefix).any(|l| {
+                // Weight restrictions are ignored for lexemes without
+                // positions, as in PostgreSQL.
+                *weights == 0
+                    || l.positions.is_empty()
+                    || l.positions.iter().any(|p| weights & p.weight.mask() != 0)
+            }),
+            QueryNode::Not(inner) => !inner.matches(doc),
+            QueryNode::And(left, right) => left.matches(doc) && right.matches(doc),
+            QueryNode::Or(left, right) => left.matches(doc) || right.matches(doc),
+        }
+    }
+
+    fn fmt_with_priority(&self, f: &mut fmt::Formatter, parent_priority: u8) -> fmt::Result {
+        let priority = self.priority();
+        let parenthesize = priority < parent_priority;
+        if parenthesize {
+            f.write_str("( ")?;
+        }
+        match self {
+            QueryNode::Operand {
+                lexeme,
+                prefix,
+                weights,
+            } => {
+                write_quoted(f, lexeme)?;
+                if *prefix || *weights != 0 {
+                    f.write_str(":")?;
+                }
+                if *prefix {
+                    f.write_str("*")?;
+                }
+                for weight in &[Weight::A, Weight::B, Weight::C, Weight::D] {
+                    if weights & weight.mask() != 0 {
+                        write!(f, "{}", weight.as_char())?;
+                    }
+                }
+            }
+            QueryNode::Not(inner) => {
+                f.write_str("!")?;
+                inner.fmt_with_priority(f, priority)?;
+            }
+            QueryNode::And(left, right) | QueryNode::Or(left, right) => {
+                left.fmt_with_priority(f, priority)?;
+                f.write_str(match self {
+                    QueryNode::And(..) => " & ",
+                    _ => " | ",
+                })?;
+                right.fmt_with_priority(f, priority)?;
+            }
+        }
+        if parenthesize {
+            f.write_str(" )")?;
+        }
+        Ok(())
+    }
+}
+
+/// A query against a [`TsVector`].
+///
+/// A query whose operands were all discarded during normalization is empty,
+/// and matches no documents.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TsQuery {
+    root: Option<QueryNode>,
+}
+
+impl TsQuery {
+    /// Returns the root of the query tree, or `None` if the query is empty.
+    pub fn root(&self) -> Option<&QueryNode> {
+        self.root.as_ref()
+    }
+
+    /// Reports whether `doc` matches the query.
+    pub fn matches(&self, doc: &TsVector) -> bool {
+        match &self.root {
+            None => false,
+            Some(root) => root.matches(doc),
+        }
+    }
+}
+
+impl fmt::Display for TsQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.root {
+            None => Ok(()),
+            Some(root) => root.fmt_with_priority(f, 0),
+        }
+    }
+}
+
+/// Parses a [`TsQuery`] from its text representation.
+///
+/// The text of each operand is passed to `normalize`, which returns the
+/// lexemes to search for. An operand that normalizes to no lexemes is
+/// discarded, along with any operator that it leaves without operands. An
+/// operand that normalizes to multiple lexemes matches documents that contain
+/// all of them.
+pub fn parse_tsquery<F>(s: &str, normalize: F) -> Result<TsQuery, String>
+where
+    F: FnMut(&str) -> Vec<String>,
+{
+    let mut parser = QueryParser {
+        chars: s.chars().peekable(),
+        normalize,
+    };
+    parser.skip_whitespace();
+    if parser.chars.peek().is_none() {
+        return Ok(TsQuery::default());
+    }
+    let root = parser.parse_or()?;
+    parser.skip_whitespace();
+    match parser.chars.next() {
+        None => Ok(TsQuery { root }),
+        Some(c) => Err(format!("unexpected character \"{}\"", c)),
+    }
+}
+
+struct QueryParser<'a, F> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    normalize: F,
+}
+
+impl<'a, F> QueryParser<'a, F>
+where
+    F: FnMut(&str) -> Vec<String>,
+{
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().map_or(false, |c| c.is_whitespace()) {
+            self.chars.next();
+        }
+    }
+
+    fn consume(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&c) {
+            self.chars.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Option<QueryNode>, String> {
+        let mut node = self.parse_and()?;
+        while self.consume('|') {
+            let right = self.parse_and()?;
+            node = combine(node, right, QueryNode::Or);
+        }
+        Ok(node)
+    }
+
+    fn parse_and(&mut self) -> Result<Option<QueryNode>, String> {
+        let mut node = self.parse_unary()?;
+        while self.consume('&') {
+            let right = self.parse_unary()?;
+            node = combine(node, right, QueryNode::And);
+        }
+        if self.chars.peek() == Some(&'<') {
+            return Err(PHRASE_UNSUPPORTED.into());
+        }
+        Ok(node)
+    }
+
+    fn parse_unary(&mut self) -> Result<Option<QueryNode>, String> {
+        if self.consume('!') {
+            let inner = self.parse_unary()?;
+            return Ok(inner.map(|inner| QueryNode::Not(Box::new(inner))));
+        }
+        if self.consume('(') {
+            let inner = self.parse_or()?;
+            if !self.consume(')') {
+                return Err("missing closing parenthesis".into());
+            }
+            return Ok(inner);
+        }
+        self.parse_operand()
+    }
+
+    fn parse_operand(&mut self) -> Result<Option<QueryNode>, String> {
+        self.skip_whitespace();
+        let text = match self.chars.peek() {
+            None => return Err("unexpected end of input".into()),
+            Some('<') => return Err(PHRASE_UNSUPPORTED.into()),
+            Some('\'') => {
+                self.chars.next();
+                read_quoted(&mut self.chars)?
+            }
+            Some(_) => {
+                let mut text = String::new();
+                while let Some(&c) = self.chars.peek() {
+                    if c.is_whitespace() || "!&|()<:'".contains(c) {
+                        break;
+                    }
+                    self.chars.next();
+                    match c {
+                        '\\' => text.push(self.chars.next().ok_or("unexpected end of input")?),
+                        _ => text.push(c),
+                    }
+                }
+                text
+            }
+        };
+        if text.is_empty() {
+            return Err("empty operand".into());
+        }
+        let mut prefix = false;
+        let mut weights = 0;
+        if self.chars.peek() == Some(&':') {
+            self.chars.next();
+            while let Some(&c) = self.chars.peek() {
+                match Weight::from_char(c) {
+                    Some(weight) => weights |= weight.mask(),
+                    None if c == '*' => prefix = true,
+                    None => break,
+                }
+                self.chars.next();
+            }
+        }
+        // PostgreSQL would join multiple lexemes with the phrase operator,
+        // which is not yet supported, so require that all of them appear
+        // instead.
+        let mut node = None;
+        for lexeme in (self.normalize)(&text) {
+            let operand = QueryNode::Operand {
+                lexeme,
+                prefix,
+                weights,
+            };
+            node = combine(node, Some(operand), QueryNode::And);
+        }
+        Ok(node)
+    }
+}
+
+/// Combines two optional nodes with a binary operator. If either node is
+/// missing, the other node is returned unchanged.
+fn combine<F>(left: Option<QueryNode>, right: Option<QueryNode>, op: F) -> Option<QueryNode>
+where
+    F: FnOnce(Box<QueryNode>, Box<QueryNode>) -> QueryNode,
+{
+    match (left, right) {
+        (Some(left), Some(right)) => Some(op(Box::new(left), Box::new(right))),
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+/// A text search configuration, which determines how documents and queries
+/// are broken into lexemes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum TextSearchConfig {
+    /// Splits text into words made of letters and digits, and converts each
+    /// word to lowercase. There is no stemming and there are no stop words.
+    Simple,
+}
+
+impl TextSearchConfig {
+    /// Looks up a text search configuration by name.
+    pub fn from_name(name: &str) -> Option<TextSearchConfig> {
+        match name.to_lowercase().as_str() {
+            "simple" | "pg_catalog.simple" => Some(TextSearchConfig::Simple),
+            _ => None,
+        }
+    }
+
+    /// Splits `text` into normalized words.
+    fn words<'a>(&self, text: &'a str) -> impl Iterator<Item = String> + 'a {
+        match self {
+            TextSearchConfig::Simple => text
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|w| !w.is_empty())
+                .map(|w| w.to_lowercase()),
+        }
+    }
+
+    /// Converts a document to a [`TsVector`].
+    ///
+    /// Each word is recorded at its 1-based position in the document. Words
+    /// that are too long to be lexemes are skipped.
+    pub fn to_tsvector(&self, doc: &str) -> TsVector {
+        let lexemes = self
+            .words(doc)
+            .enumerate()
+            .filter(|(_, w)| w.len() <= MAX_LEXEME_LEN)
+            .map(|(i, text)| Lexeme {
+                text,
+                positions: vec![Position {
+                    pos: if i < usize::from(MAX_POSITION) {
+                        i as u16 + 1
+                    } else {
+                        MAX_POSITION
+                    },
+                    weight: Weight::D,
+                }],
+            })
+            .collect();
+        TsVector::from_lexemes(lexemes)
+    }
+
+    /// Converts a query to a [`TsQuery`], normalizing each of its operands
+    /// into lexemes.
+    pub fn to_tsquery(&self, query: &str) -> Result<TsQuery, String> {
+        parse_tsquery(query, |text| self.words(text).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tsquery(s: &str) -> TsQuery {
+        parse_tsquery(s, |text| vec![text.to_owned()]).unwrap()
+    }
+
+    #[test]
+    fn test_tsvector_round_trip() {
+        for (input, expected) in &[
+            ("", ""),
+            ("b a b", "'a' 'b'"),
+            ("a:3,1,2A,1B", "'a':1B,2A,3"),
+            ("'it''s' 'a b' c\\ d", "'a b' 'c d' 'it''s'"),
+            ("a:b x:99999", "'a:b' 'x':16383"),
+        ] {
+            assert_eq!(parse_tsvector(input).unwrap().to_string(), *expected);
+        }
+        for input in &["''", "a:0", "'a", "'a'b", "a:1x"] {
+            assert!(parse_tsvector(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_tsquery_round_trip() {
+        for (input, expected) in &[
+            ("  ", ""),
+            ("a & b | c", "'a' & 'b' | 'c'"),
+            ("a & (b | c)", "'a' & ( 'b' | 'c' )"),
+            ("!(a & b) & !!c", "!( 'a' & 'b' ) & !!'c'"),
+            ("fox:*ba", "'fox':*AB"),
+        ] {
+            assert_eq!(tsquery(input).to_string(), *expected);
+        }
+        for input in &["a &", "(a", "a b", "a <-> b", "''"] {
+            assert!(
+                parse_tsquery(input, |t| vec![t.to_owned()]).is_err(),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_simple_config() {
+        let config = TextSearchConfig::from_name("Simple").unwrap();
+        assert_eq!(
+            config
+                .to_tsvector("The quick brown fox, the END.")
+                .to_string(),
+            "'brown':3 'end':6 'fox':4 'quick':2 'the':1,5"
+        );
+        assert_eq!(
+            config
+                .to_tsquery("Fat:* & (Rats-Cats | !Dogs)")
+                .unwrap()
+                .to_string(),
+            "'fat':* & ( 'rats' & 'cats' | !'dogs' )"
+        );
+        assert_eq!(config.to_tsquery("... & !--").unwrap(), TsQuery::default());
+    }
+
+    #[test]
+    fn test_matches() {
+        let doc = parse_tsvector("cat:1 fat:2A rat:3 dog").unwrap();
+        for (query, expected) in &[
+            ("cat & rat", true),
+            ("cat & !rat", false),
+            ("fish | dog", true),
+            ("ca:*", true),
+            ("c:* & !d:*", false),
+            ("fat:a", true),
+            ("fat:bc", false),
+            ("dog:a", true),
+        ] {
+            assert_eq!(tsquery(query).matches(&doc), *expected, "{}", query);
+        }
+        assert!(!TsQuery::default().matches(&doc));
+    }
+}
@@ -24,7 +24,12 @@ use crate::adt::array::{
     Array, ArrayDimension, ArrayDimensions, InvalidArrayError, MAX_ARRAY_DIMENSIONS,
 };
 use crate::adt::decimal::Significand;
+use crate::adt::geo::{self, Geometry, Shape};
+use crate::adt::inet::Inet;
 use crate::adt::interval::Interval;
+use crate::adt::range::{
+    self, InvalidRangeError, Range, RangeBound, RANGE_EMPTY, RANGE_LB_INC, RANGE_UB_INC,
+};
 use crate::Datum;
 use fmt::Debug;
 
@@ -68,6 +73,9 @@ use fmt::Debug;
 /// assert_eq!(datums[1], Datum::Int32(1));
 /// ```
 ///
+/// If you only need a few of the `Datum`s in a wide `Row`, use `RowOffsets` to
+/// find them without decoding the others.
+///
 /// # Performance
 ///
 /// Rows are dynamically sized, but up to a fixed size their data is stored in-line.
@@ -106,6 +114,35 @@ pub struct DatumListIter<'a> {
     offset: usize,
 }
 
+/// A reusable table of the byte offsets of the datums in a `Row`.
+///
+/// Reading the `i`th datum of a `Row` with `Row::iter` must walk past the `i`
+/// datums that precede it. When a row is accessed at several positions, it is
+/// cheaper to index it once with `RowOffsets::index`, which skips over the
+/// datums without decoding them, and then to decode only the datums that are
+/// needed with `IndexedRow::get`.
+///
+/// ```
+/// # use repr::{Row, Datum, RowOffsets};
+/// let row = Row::pack_slice(&[Datum::Int32(0), Datum::String("one"), Datum::Int64(2)]);
+/// let mut offsets = RowOffsets::new();
+/// let indexed = offsets.index(&row);
+/// assert_eq!(indexed.get(2), Datum::Int64(2));
+/// assert_eq!(indexed.get(1), Datum::String("one"));
+/// ```
+#[derive(Debug, Default)]
+pub struct RowOffsets {
+    offsets: Vec<usize>,
+}
+
+/// A `Row` whose datums can be read in constant time, produced by
+/// `RowOffsets::index`.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexedRow<'a> {
+    data: &'a [u8],
+    offsets: &'a [usize],
+}
+
 #[derive(Debug)]
 pub struct DatumDictIter<'a> {
     data: &'a [u8],
@@ -113,6 +150,12 @@ pub struct DatumDictIter<'a> {
     prev_key: Option<&'a str>,
 }
 
+/// The largest buffer that a [`RowPacker`] retains across rows.
+///
+/// Packers are often long lived and reused for many rows, so a single very
+/// large row would otherwise pin its allocation for the life of the packer.
+const MAX_RETAINED_CAPACITY: usize = 1 << 20;
+
 /// `RowPacker` is used to build a `Row`.
 ///
 /// ```
@@ -208,6 +251,9 @@ enum Tag {
     Dict,
     JsonNull,
     Dummy,
+    Range,
+    Inet,
+    Geometry,
 }
 
 // --------------------------------------------------------------------------------
@@ -368,9 +414,64 @@ unsafe fn read_datum<'a>(data: &'a [u8], offset: &mut usize) -> Datum<'a> {
         }
         Tag::JsonNull => Datum::JsonNull,
         Tag::Dummy => Datum::Dummy,
+        Tag::Range => {
+            // See the comment in `Row::push_range` for details on the encoding
+            // of ranges.
+            let flags = read_copy::<u8>(data, offset);
+            let bytes = read_untagged_bytes(data, offset);
+            Datum::Range(Range {
+                flags,
+                bounds: DatumList { data: bytes },
+            })
+        }
+        Tag::Inet => Datum::Inet(read_copy::<Inet>(data, offset)),
+        Tag::Geometry => {
+            let ewkb = read_untagged_bytes(data, offset);
+            Datum::Geometry(Geometry { ewkb })
+        }
     }
 }
 
+/// Advances `offset` past the datum that starts at byte `offset`, without
+/// decoding it.
+///
+/// # Safety
+///
+/// This function is safe if a `Datum` was previously written at this offset by `push_datum`.
+/// Otherwise it could compute an invalid offset.
+unsafe fn skip_datum(data: &[u8], offset: &mut usize) {
+    let tag = read_copy::<Tag>(data, offset);
+    *offset += match tag {
+        Tag::Null | Tag::False | Tag::True | Tag::JsonNull | Tag::Dummy => 0,
+        Tag::Int32 => size_of::<i32>(),
+        Tag::Int64 => size_of::<i64>(),
+        Tag::Float32 => size_of::<f32>(),
+        Tag::Float64 => size_of::<f64>(),
+        Tag::Date => size_of::<NaiveDate>(),
+        Tag::Time => size_of::<NaiveTime>(),
+        Tag::Timestamp => size_of::<NaiveDateTime>(),
+        Tag::TimestampTz => size_of::<DateTime<Utc>>(),
+        Tag::Interval => size_of::<i32>() + size_of::<i128>(),
+        Tag::Decimal => size_of::<Significand>(),
+        Tag::BytesTiny | Tag::StringTiny => read_copy::<u8>(data, offset) as usize,
+        Tag::BytesShort | Tag::StringShort => read_copy::<u16>(data, offset) as usize,
+        Tag::BytesLong | Tag::StringLong => read_copy::<u32>(data, offset) as usize,
+        Tag::BytesHuge | Tag::StringHuge => read_copy::<usize>(data, offset),
+        Tag::Uuid | Tag::List | Tag::Dict => read_copy::<usize>(data, offset),
+        Tag::Array => {
+            let ndims = read_copy::<u8>(data, offset);
+            *offset += usize::from(ndims) * size_of::<usize>() * 2;
+            read_copy::<usize>(data, offset)
+        }
+        Tag::Range => {
+            *offset += size_of::<u8>();
+            read_copy::<usize>(data, offset)
+        }
+        Tag::Inet => size_of::<Inet>(),
+        Tag::Geometry => read_copy::<usize>(data, offset),
+    };
+}
+
 // --------------------------------------------------------------------------------
 // writing data
 
@@ -421,11 +522,15 @@ fn push_datum(data: &mut Vec<u8>, datum: Datum) {
             data.push(Tag::Int64 as u8);
             push_copy!(data, i, i64);
         }
+        // All NaNs are equal, so they are encoded with a single bit pattern to
+        // keep rows that are equal under `Datum` equality byte-identical.
         Datum::Float32(f) => {
+            let f = if f.is_nan() { f32::NAN } else { f.into_inner() };
             data.push(Tag::Float32 as u8);
             push_copy!(data, f.to_bits(), u32);
         }
         Datum::Float64(f) => {
+            let f = if f.is_nan() { f64::NAN } else { f.into_inner() };
             data.push(Tag::Float64 as u8);
             push_copy!(data, f.to_bits(), u64);
         }
@@ -496,6 +601,23 @@ fn push_datum(data: &mut Vec<u8>, datum: Datum) {
         }
         Datum::JsonNull => data.push(Tag::JsonNull as u8),
         Datum::Dummy => data.push(Tag::Dummy as u8),
+        Datum::Range(range) => {
+            // See the comment in `Row::push_range` for details on the encoding
+            // of ranges.
+            data.push(Tag::Range as u8);
+            data.push(range.flags);
+            push_untagged_bytes(data, &range.bounds.data);
+        }
+        Datum::Inet(i) => {
+            data.push(Tag::Inet as u8);
+            push_copy!(data, i, Inet);
+        }
+        Datum::Geometry(geometry) => {
+            // See the comment in `Row::push_geometry` for details on the
+            // encoding of geometries.
+            data.push(Tag::Geometry as u8);
+            push_untagged_bytes(data, geometry.ewkb);
+        }
     }
 }
 
@@ -545,6 +667,9 @@ pub fn datum_size(datum: &Datum) -> usize {
         Datum::Map(dict) => 1 + size_of::<usize>() + dict.data.len(),
         Datum::JsonNull => 1,
         Datum::Dummy => 1,
+        Datum::Range(range) => 1 + size_of::<u8>() + size_of::<usize>() + range.bounds.data.len(),
+        Datum::Inet(_) => 1 + size_of::<Inet>(),
+        Datum::Geometry(geometry) => 1 + size_of::<usize>() + geometry.ewkb.len(),
     }
 }
 
@@ -606,6 +731,31 @@ impl Row {
         unsafe { Row::new(bytes) }
     }
 
+    /// Returns a row equal to `self` in which every top-level negative zero
+    /// float is replaced by positive zero.
+    ///
+    /// Rows compare by their encodings, but `-0` and `0` are equal datums.
+    /// Rows used as grouping, join, or index keys must be normalized with this
+    /// method so that equal keys land in the same group. Negative zeros are not
+    /// normalized in general, as PostgreSQL preserves them in output.
+    pub fn normalize_zeros(self) -> Row {
+        fn is_negative_zero(datum: &Datum) -> bool {
+            match datum {
+                Datum::Float32(f) => **f == 0.0 && f.is_sign_negative(),
+                Datum::Float64(f) => **f == 0.0 && f.is_sign_negative(),
+                _ => false,
+            }
+        }
+        if !self.iter().any(|d| is_negative_zero(&d)) {
+            return self;
+        }
+        Row::pack(self.iter().map(|d| match d {
+            Datum::Float32(_) if is_negative_zero(&d) => Datum::Float32(OrderedFloat(0.0)),
+            Datum::Float64(_) if is_negative_zero(&d) => Datum::Float64(OrderedFloat(0.0)),
+            d => d,
+        }))
+    }
+
     /// Unpack `self` into a `Vec<Datum>` for efficient random access.
     pub fn unpack(&self) -> Vec<Datum> {
         // It's usually cheaper to unpack twice to figure out the right length than it is to grow the vec as we go
@@ -633,6 +783,11 @@ impl Row {
     pub fn data(&self) -> &[u8] {
         &self.data
     }
+
+    /// Returns the number of bytes in the packed representation of the row.
+    pub fn byte_len(&self) -> usize {
+        self.data.len()
+    }
 }
 
 impl<'a> IntoIterator for &'a Row {
@@ -701,6 +856,62 @@ impl<'a> Iterator for DatumListIter<'a> {
             Some(unsafe { read_datum(self.data, &mut self.offset) })
         }
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        // Skip the preceding datums without decoding them.
+        for _ in 0..n {
+            if self.offset >= self.data.len() {
+                return None;
+            }
+            unsafe { skip_datum(self.data, &mut self.offset) };
+        }
+        self.next()
+    }
+}
+
+impl RowOffsets {
+    /// Creates an empty offset table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indexes the datums in `row`, reusing the allocation of any previously
+    /// indexed row.
+    pub fn index<'a>(&'a mut self, row: &'a Row) -> IndexedRow<'a> {
+        self.offsets.clear();
+        let data = &row.data[..];
+        let mut offset = 0;
+        while offset < data.len() {
+            self.offsets.push(offset);
+            unsafe { skip_datum(data, &mut offset) };
+        }
+        IndexedRow {
+            data,
+            offsets: &self.offsets,
+        }
+    }
+}
+
+impl<'a> IndexedRow<'a> {
+    /// Returns the number of datums in the row.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Reports whether the row contains no datums.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Returns the `i`th datum in the row.
+    ///
+    /// Panics if `i` is out of bounds.
+    pub fn get(&self, i: usize) -> Datum<'a> {
+        let mut offset = self.offsets[i];
+        // Unsafety justified in that `offsets` was computed by `RowOffsets::index`
+        // from the start of each datum in `data`.
+        unsafe { read_datum(self.data, &mut offset) }
+    }
 }
 
 impl<'a> DatumMap<'a> {
@@ -783,8 +994,15 @@ impl RowPacker {
     }
 
     /// Clears the row packer.
+    ///
+    /// If the packer has grown to hold an unusually large row, its buffer is
+    /// released rather than retained for reuse.
     pub fn clear(&mut self) {
-        self.data.clear();
+        if self.data.capacity() > MAX_RETAINED_CAPACITY {
+            self.data = Vec::new();
+        } else {
+            self.data.clear();
+        }
     }
 
     /// Push `datum` onto the end of `self`
@@ -812,7 +1030,7 @@ impl RowPacker {
         I: IntoIterator<Item = D>,
         D: Borrow<Datum<'a>>,
     {
-        self.data.clear();
+        self.clear();
         for datum in iter {
             self.push(*datum.borrow());
         }
@@ -860,7 +1078,7 @@ impl RowPacker {
     /// allocator, as opposed to creating new row packers for each row.
     pub fn finish_and_reuse(&mut self) -> Row {
         let data = SmallVec::from(&self.data[..]);
-        self.data.clear();
+        self.clear();
         Row { data }
     }
 
@@ -874,8 +1092,8 @@ impl RowPacker {
     /// contents of `self`.
     pub fn finish_into(&mut self, row: &mut Row) {
         row.data.clear();
-        row.data.extend(self.data.iter().cloned());
-        self.data.clear();
+        row.data.extend_from_slice(&self.data);
+        self.clear();
     }
 
     /// Pushes a [`DatumList`] that is built from a closure.
@@ -1044,6 +1262,67 @@ impl RowPacker {
         Ok(())
     }
 
+    /// Pushes a range with the specified bounds, converting the bounds to
+    /// canonical form.
+    ///
+    /// Pass `None` to push the empty range. Returns an error if the lower bound
+    /// is greater than the upper bound, or if the bounds cannot be converted
+    /// to canonical form.
+    pub fn push_range<'a>(
+        &mut self,
+        bounds: Option<(RangeBound<Datum<'a>>, RangeBound<Datum<'a>>)>,
+    ) -> Result<(), InvalidRangeError> {
+        // Ranges are encoded as follows.
+        //
+        // u8      flags
+        // usize   bounds data size in bytes
+        // u8      bounds data, which is the lower and then the upper bound,
+        //         where `Datum::Null` indicates an unbounded end, or nothing
+        //         if the range is empty
+
+        let bounds = match bounds {
+            None => None,
+            Some((lower, upper)) => range::canonicalize(lower, upper)?,
+        };
+        self.data.push(Tag::Range as u8);
+        match bounds {
+            None => {
+                self.data.push(RANGE_EMPTY);
+                push_copy!(&mut self.data, 0, usize);
+            }
+            Some((lower, upper)) => {
+                let mut flags = 0;
+                if lower.inclusive {
+                    flags |= RANGE_LB_INC;
+                }
+                if upper.inclusive {
+                    flags |= RANGE_UB_INC;
+                }
+                self.data.push(flags);
+                let off = self.data.len();
+                push_copy!(&mut self.data, 0, usize); // dummy length fixed up below
+                self.push(lower.datum());
+                self.push(upper.datum());
+                let len = self.data.len() - off - size_of::<usize>();
+                self.data[off..off + size_of::<usize>()].copy_from_slice(&len.to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    /// Pushes a geometry with the specified SRID and shape.
+    pub fn push_geometry(&mut self, srid: i32, shape: &Shape) {
+        // Geometries are encoded as follows.
+        //
+        // usize   EWKB data size in bytes
+        // u8      EWKB data, in the canonical encoding described by
+        //         `Geometry`
+        let mut ewkb = vec![];
+        geo::encode_ewkb(&mut ewkb, srid, shape);
+        self.data.push(Tag::Geometry as u8);
+        push_untagged_bytes(&mut self.data, &ewkb);
+    }
+
     /// Convenience function to push a `DatumList` from an iter of `Datum`s
     ///
     /// See [`push_dict_with`] if you need to be able to handle errors
@@ -1253,6 +1532,15 @@ mod tests {
             let datums3 = row.unpack();
             assert_eq!(datums, datums2);
             assert_eq!(datums, datums3);
+
+            let mut offsets = RowOffsets::new();
+            let indexed = offsets.index(&row);
+            assert_eq!(indexed.len(), datums.len());
+            for (i, datum) in datums.iter().enumerate() {
+                assert_eq!(row.iter().nth(i), Some(*datum));
+                assert_eq!(indexed.get(i), *datum);
+            }
+            assert_eq!(row.iter().nth(datums.len()), None);
         }
 
         round_trip(vec![]);
@@ -1282,6 +1570,12 @@ mod tests {
             Datum::Bytes(&[0, 2, 1, 255]),
             Datum::String(""),
             Datum::String("العَرَبِيَّة"),
+            Datum::Time(NaiveTime::from_hms(14, 32, 11)),
+            Datum::Decimal(Significand::new(-1_234_567)),
+            Datum::Uuid(Uuid::from_u128(0x8a2c_1f70_3e4b_4d2a_9a7e_1b2c_3d4e_5f60)),
+            Datum::Inet(Inet::new("10.1.2.3".parse().unwrap(), 16).unwrap()),
+            Datum::Inet(Inet::new("2001:db8::1".parse().unwrap(), 128).unwrap()),
+            Datum::JsonNull,
         ]);
     }
 
@@ -1312,6 +1606,133 @@ mod tests {
         assert_eq!(arr1, arr2);
     }
 
+    #[test]
+    fn test_range() {
+        let bound = |v: Option<i32>, inclusive| RangeBound {
+            value: v.map(Datum::Int32),
+            inclusive,
+        };
+        let pack = |lower, upper| {
+            let mut packer = RowPacker::new();
+            packer.push_range(Some((lower, upper)))?;
+            packer.push(Datum::Int32(42));
+            Ok::<_, InvalidRangeError>(packer.finish())
+        };
+
+        // Discrete ranges are canonicalized to `[lower, upper)`.
+        let row = pack(bound(Some(1), false), bound(Some(4), true)).unwrap();
+        let mut datums = row.iter();
+        let range = datums.next().unwrap().unwrap_range();
+        assert_eq!(datums.next(), Some(Datum::Int32(42)));
+        assert_eq!(
+            range.bounds(),
+            Some((bound(Some(2), true), bound(Some(5), false)))
+        );
+        assert_eq!(
+            row,
+            pack(bound(Some(2), true), bound(Some(5), false)).unwrap()
+        );
+        assert!(range.contains_elem(Datum::Int32(2)));
+        assert!(!range.contains_elem(Datum::Int32(5)));
+
+        // Unbounded ends are never inclusive.
+        let row = pack(bound(None, true), bound(Some(1), false)).unwrap();
+        let unbounded = row.unpack_first().unwrap_range();
+        assert_eq!(unbounded.lower(), Some(bound(None, false)));
+        assert!(!unbounded.overlaps(&range) && !unbounded.contains_range(&range));
+        assert!(unbounded < range);
+
+        // Ranges without any values are empty.
+        let row = pack(bound(Some(3), false), bound(Some(4), false)).unwrap();
+        let empty = row.unpack_first().unwrap_range();
+        assert!(empty.is_empty());
+        assert!(range.contains_range(&empty) && !range.overlaps(&empty));
+        assert!(empty < unbounded);
+
+        // Overlapping and adjacent ranges merge, and empty ranges vanish.
+        let row = pack(bound(Some(5), true), bound(Some(7), false)).unwrap();
+        let adjacent = row.unpack_first().unwrap_range();
+        assert_eq!(
+            range::merge(vec![adjacent, empty, range, unbounded]),
+            vec![
+                (bound(None, false), bound(Some(1), false)),
+                (bound(Some(2), true), bound(Some(7), false)),
+            ]
+        );
+
+        assert_eq!(
+            pack(bound(Some(4), true), bound(Some(3), true)),
+            Err(InvalidRangeError::MisorderedBounds)
+        );
+        assert_eq!(
+            pack(bound(Some(i32::MAX), false), bound(None, false)),
+            Err(InvalidRangeError::BoundOutOfRange)
+        );
+
+        // Pack a previously-constructed `Datum::Range` and verify that it
+        // unpacks correctly.
+        let row = Row::pack_slice(&[Datum::Range(range)]);
+        assert_eq!(row.unpack_first().unwrap_range(), range);
+        assert_eq!(datum_size(&Datum::Range(range)), row.data.len());
+    }
+
+    #[test]
+    fn test_geometry() {
+        let (srid, shape) = geo::parse_ewkt("SRID=4326;LINESTRING(0 0,1 2)").unwrap();
+        let mut packer = RowPacker::new();
+        packer.push_geometry(srid, &shape);
+        packer.push_geometry(0, &shape);
+        packer.push(Datum::Int32(42));
+        let row = packer.finish();
+        let datums = row.unpack();
+        let (g1, g2) = (datums[0].unwrap_geometry(), datums[1].unwrap_geometry());
+        assert_eq!((g1.srid(), g1.shape()), (4326, shape.clone()));
+        assert_eq!((g2.srid(), g2.shape()), (0, shape));
+        assert_ne!(g1, g2);
+        assert_eq!(datums[2], Datum::Int32(42));
+
+        let row = Row::pack_slice(&[Datum::Geometry(g1)]);
+        assert_eq!(row.unpack_first().unwrap_geometry(), g1);
+        assert_eq!(datum_size(&Datum::Geometry(g1)), row.data.len());
+    }
+
+    #[test]
+    fn test_row_offsets_nested() {
+        // Datums that contain other datums must be skipped as a whole.
+        let mut packer = RowPacker::new();
+        packer.push(Datum::Int32(1));
+        packer
+            .push_array(
+                &[ArrayDimension {
+                    lower_bound: 1,
+                    length: 2,
+                }],
+                vec![Datum::String("a"), Datum::String("b")],
+            )
+            .unwrap();
+        packer.push_list_with(|packer| {
+            packer.push(Datum::Int64(2));
+            packer.push(Datum::String("c"));
+        });
+        packer.push_dict_with(|packer| {
+            packer.push(Datum::String("d"));
+            packer.push(Datum::Int32(3));
+        });
+        packer.push(Datum::String("last"));
+        let row = packer.finish();
+
+        let mut offsets = RowOffsets::new();
+        let indexed = offsets.index(&row);
+        assert_eq!(indexed.len(), 5);
+        assert_eq!(indexed.get(4), Datum::String("last"));
+        assert_eq!(indexed.get(0), Datum::Int32(1));
+        assert_eq!(row.iter().nth(4), Some(Datum::String("last")));
+        assert_eq!(
+            indexed.get(2).unwrap_list().iter().collect::<Vec<_>>(),
+            vec![Datum::Int64(2), Datum::String("c")]
+        );
+    }
+
     #[test]
     fn test_multidimensional_array() {
         let datums = vec![
@@ -1470,6 +1891,38 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_float_encodings() {
+        // NaNs with different bit patterns encode identically.
+        let nan = Row::pack_slice(&[Datum::Float64(OrderedFloat(f64::NAN))]);
+        for bits in &[0x7ff8_0000_0000_0001, 0xfff8_0000_0000_0000] {
+            let f = f64::from_bits(*bits);
+            assert!(f.is_nan());
+            assert_eq!(Row::pack_slice(&[Datum::Float64(OrderedFloat(f))]), nan);
+        }
+        let nan = Row::pack_slice(&[Datum::Float32(OrderedFloat(f32::NAN))]);
+        let f = -f32::NAN;
+        assert_eq!(Row::pack_slice(&[Datum::Float32(OrderedFloat(f))]), nan);
+
+        // Negative zero is preserved, unless explicitly normalized.
+        let row = Row::pack_slice(&[
+            Datum::Int32(1),
+            Datum::Float32(OrderedFloat(-0.0)),
+            Datum::Float64(OrderedFloat(-0.0)),
+        ]);
+        match row.unpack()[2] {
+            Datum::Float64(f) => assert!(f.is_sign_negative()),
+            d => panic!("unexpected datum {:?}", d),
+        }
+        let normalized = Row::pack_slice(&[
+            Datum::Int32(1),
+            Datum::Float32(OrderedFloat(0.0)),
+            Datum::Float64(OrderedFloat(0.0)),
+        ]);
+        assert_ne!(row, normalized);
+        assert_eq!(row.normalize_zeros(), normalized);
+    }
+
     #[test]
     fn test_datum_sizes() {
         // Test the claims about various datum sizes.
@@ -1495,4 +1948,21 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_packer_releases_large_buffers() {
+        let mut packer = RowPacker::new();
+        let small = Row::pack_slice(&[Datum::String("small")]);
+        assert_eq!(packer.pack(&[Datum::String("small")]), small);
+        assert!(packer.data.capacity() > 0);
+        assert!(packer.data.capacity() <= MAX_RETAINED_CAPACITY);
+
+        let big = "a".repeat(2 * MAX_RETAINED_CAPACITY);
+        let row = packer.pack(&[Datum::String(&big)]);
+        assert_eq!(row.unpack_first(), Datum::String(&big));
+        assert_eq!(packer.data.capacity(), 0);
+
+        // The packer remains usable after releasing its buffer.
+        assert_eq!(packer.pack(&[Datum::String("small")]), small);
+    }
 }
@@ -19,7 +19,10 @@ use uuid::Uuid;
 
 use crate::adt::array::Array;
 use crate::adt::decimal::Significand;
+use crate::adt::geo::{self, Geometry};
+use crate::adt::inet::Inet;
 use crate::adt::interval::Interval;
+use crate::adt::range::Range;
 use crate::{ColumnName, ColumnType, DatumList, DatumMap};
 
 /// A single value.
@@ -74,6 +77,12 @@ pub enum Datum<'a> {
     JsonNull,
     /// A universally unique identifier.
     Uuid(Uuid),
+    /// An IPv4 or IPv6 host or network address.
+    Inet(Inet),
+    /// A geometry or geography.
+    Geometry(Geometry<'a>),
+    /// A contiguous range of values.
+    Range(Range<'a>),
     /// A placeholder value.
     ///
     /// Dummy values are never meant to be observed. Many operations on `Datum`
@@ -298,6 +307,32 @@ impl<'a> Datum<'a> {
         }
     }
 
+    /// Unwraps the network address value within this datum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the datum is not [`Datum::Inet`].
+    #[track_caller]
+    pub fn unwrap_inet(&self) -> Inet {
+        match self {
+            Datum::Inet(i) => *i,
+            _ => panic!("Datum::unwrap_inet called on {:?}", self),
+        }
+    }
+
+    /// Unwraps the geometry value within this datum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the datum is not [`Datum::Geometry`].
+    #[track_caller]
+    pub fn unwrap_geometry(&self) -> Geometry<'a> {
+        match self {
+            Datum::Geometry(g) => *g,
+            _ => panic!("Datum::unwrap_geometry called on {:?}", self),
+        }
+    }
+
     /// Unwraps the array value within this datum.
     ///
     /// # Panics
@@ -311,6 +346,19 @@ impl<'a> Datum<'a> {
         }
     }
 
+    /// Unwraps the range value within this datum.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the datum is not [`Datum::Range`].
+    #[track_caller]
+    pub fn unwrap_range(&self) -> Range<'a> {
+        match self {
+            Datum::Range(range) => *range,
+            _ => panic!("Datum::unwrap_range called on {:?}", self),
+        }
+    }
+
     /// Unwraps the list value within this datum.
     ///
     /// # Panics
@@ -389,9 +437,19 @@ impl<'a> Datum<'a> {
                     (Datum::Bytes(_), ScalarType::Bytes) => true,
                     (Datum::Bytes(_), _) => false,
                     (Datum::String(_), ScalarType::String) => true,
+                    (Datum::String(_), ScalarType::Char { .. }) => true,
+                    (Datum::String(_), ScalarType::VarChar { .. }) => true,
+                    (Datum::String(_), ScalarType::TsVector) => true,
+                    (Datum::String(_), ScalarType::TsQuery) => true,
                     (Datum::String(_), _) => false,
                     (Datum::Uuid(_), ScalarType::Uuid) => true,
                     (Datum::Uuid(_), _) => false,
+                    (Datum::Inet(_), ScalarType::Inet) => true,
+                    (Datum::Inet(i), ScalarType::Cidr) => i.is_network(),
+                    (Datum::Inet(_), _) => false,
+                    (Datum::Geometry(_), ScalarType::Geometry) => true,
+                    (Datum::Geometry(g), ScalarType::Geography) => g.srid() == geo::SRID_WGS84,
+                    (Datum::Geometry(_), _) => false,
                     (Datum::Array(array), ScalarType::Array(t)) => {
                         array.elements.iter().all(|e| match e {
                             Datum::Null => true,
@@ -400,6 +458,12 @@ impl<'a> Datum<'a> {
                         })
                     }
                     (Datum::Array(_), _) => false,
+                    (Datum::Range(range), ScalarType::Range(t)) => range
+                        .bounds()
+                        .into_iter()
+                        .flat_map(|(lower, upper)| lower.value.into_iter().chain(upper.value))
+                        .all(|e| is_instance_of_scalar(e, t)),
+                    (Datum::Range(_), _) => false,
                     (Datum::List(list), ScalarType::List { element_type, .. }) => list
                         .iter()
                         .all(|e| e.is_null() || is_instance_of_scalar(e, element_type)),
@@ -545,6 +609,12 @@ impl From<Uuid> for Datum<'static> {
     }
 }
 
+impl From<Inet> for Datum<'static> {
+    fn from(inet: Inet) -> Datum<'static> {
+        Datum::Inet(inet)
+    }
+}
+
 impl<'a, T> From<Option<T>> for Datum<'a>
 where
     Datum<'a>: From<T>,
@@ -613,6 +683,8 @@ impl fmt::Display for Datum<'_> {
                 f.write_str("\"")
             }
             Datum::Uuid(u) => write!(f, "{}", u),
+            Datum::Inet(i) => write!(f, "{}/{}", i.addr(), i.prefix_len()),
+            Datum::Geometry(g) => write!(f, "SRID={};{}", g.srid(), g.shape()),
             Datum::Array(array) => {
                 f.write_str("{")?;
                 write_delimited(f, ", ", &array.elements, |f, e| write!(f, "{}", e))?;
@@ -630,6 +702,20 @@ impl fmt::Display for Datum<'_> {
             }
             Datum::JsonNull => f.write_str("json_null"),
             Datum::Dummy => f.write_str("dummy"),
+            Datum::Range(range) => match range.bounds() {
+                None => f.write_str("empty"),
+                Some((lower, upper)) => {
+                    f.write_char(if lower.inclusive { '[' } else { '(' })?;
+                    if let Some(v) = lower.value {
+                        write!(f, "{}", v)?;
+                    }
+                    f.write_char(',')?;
+                    if let Some(v) = upper.value {
+                        write!(f, "{}", v)?;
+                    }
+                    f.write_char(if upper.inclusive { ']' } else { ')' })
+                }
+            },
         }
     }
 }
@@ -675,6 +761,15 @@ pub enum ScalarType {
     Bytes,
     /// The type of [`Datum::String`].
     String,
+    /// The type of [`Datum::String`]s that are padded with spaces to a fixed
+    /// length.
+    ///
+    /// If `length` is `None`, values are not padded, as with PostgreSQL's
+    /// unbounded `bpchar` type. See [`crate::adt::char`].
+    Char { length: Option<usize> },
+    /// The type of [`Datum::String`]s whose length is limited to `length`
+    /// characters, if specified.
+    VarChar { length: Option<usize> },
     /// The type of a datum that may represent any valid JSON value.
     ///
     /// Valid datum variants for this type are:
@@ -689,6 +784,29 @@ pub enum ScalarType {
     Jsonb,
     /// The type of [`Datum::Uuid`].
     Uuid,
+    /// The type of [`Datum::Inet`] that may represent any host or network
+    /// address.
+    Inet,
+    /// The type of [`Datum::Inet`] that may represent only network addresses,
+    /// i.e., addresses with no bits set to the right of the subnet mask.
+    Cidr,
+    /// The type of [`Datum::Geometry`] that represents shapes in an arbitrary
+    /// spatial reference system, using planar coordinates.
+    Geometry,
+    /// The type of [`Datum::Geometry`] that represents shapes on the surface
+    /// of the Earth, using longitude and latitude in the WGS 84 spatial
+    /// reference system.
+    Geography,
+    /// A document for full-text search.
+    ///
+    /// Values are represented as [`Datum::String`]s in their canonical text
+    /// form. See [`crate::adt::tsearch::TsVector`].
+    TsVector,
+    /// A query for full-text search.
+    ///
+    /// Values are represented as [`Datum::String`]s in their canonical text
+    /// form. See [`crate::adt::tsearch::TsQuery`].
+    TsQuery,
     /// The type of [`Datum::Array`].
     ///
     /// Elements within the array are of the specified type. It is illegal for
@@ -722,6 +840,11 @@ pub enum ScalarType {
         value_type: Box<ScalarType>,
         custom_oid: Option<u32>,
     },
+    /// The type of [`Datum::Range`].
+    ///
+    /// The bounds of the range are of the specified type, which must be
+    /// [`ScalarType::Int32`] or [`ScalarType::TimestampTz`].
+    Range(Box<ScalarType>),
 }
 
 impl<'a> ScalarType {
@@ -736,6 +859,21 @@ impl<'a> ScalarType {
             _ => panic!("ScalarType::unwrap_decimal_parts called on {:?}", self),
         }
     }
+
+    /// Returns the length of a [`ScalarType::Char`] or
+    /// [`ScalarType::VarChar`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scalar type is not [`ScalarType::Char`] or
+    /// [`ScalarType::VarChar`].
+    pub fn unwrap_char_length(&self) -> Option<usize> {
+        match self {
+            ScalarType::Char { length } | ScalarType::VarChar { length } => *length,
+            _ => panic!("ScalarType::unwrap_char_length called on {:?}", self),
+        }
+    }
+
     /// Returns the [`ScalarType`] of elements in a [`ScalarType::List`].
     ///
     /// # Panics
@@ -778,6 +916,18 @@ impl<'a> ScalarType {
         }
     }
 
+    /// Returns the [`ScalarType`] of the bounds of a [`ScalarType::Range`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if called on anything other than a [`ScalarType::Range`].
+    pub fn unwrap_range_element_type(&self) -> &ScalarType {
+        match self {
+            ScalarType::Range(s) => &**s,
+            _ => panic!("ScalarType::unwrap_range_element_type called on {:?}", self),
+        }
+    }
+
     /// Returns the [`ScalarType`] of values in a [`ScalarType::Map`].
     ///
     /// # Panics
@@ -844,6 +994,12 @@ impl PartialEq for ScalarType {
             | (Bytes, Bytes)
             | (String, String)
             | (Uuid, Uuid)
+            | (Inet, Inet)
+            | (Cidr, Cidr)
+            | (Geometry, Geometry)
+            | (Geography, Geography)
+            | (TsVector, TsVector)
+            | (TsQuery, TsQuery)
             | (Jsonb, Jsonb)
             | (Oid, Oid) => true,
             (
@@ -857,7 +1013,7 @@ impl PartialEq for ScalarType {
                 },
             ) => element_l.eq(element_r) && oid_l == oid_r,
 
-            (Array(a), Array(b)) => a.eq(b),
+            (Array(a), Array(b)) | (Range(a), Range(b)) => a.eq(b),
             (
                 Record {
                     fields: fields_a,
@@ -880,6 +1036,8 @@ impl PartialEq for ScalarType {
                     custom_oid: oid_r,
                 },
             ) => value_l.eq(value_r) && oid_l == oid_r,
+            (Char { length: l }, Char { length: r })
+            | (VarChar { length: l }, VarChar { length: r }) => l == r,
 
             (Bool, _)
             | (Int32, _)
@@ -894,13 +1052,22 @@ impl PartialEq for ScalarType {
             | (Interval, _)
             | (Bytes, _)
             | (String, _)
+            | (Char { .. }, _)
+            | (VarChar { .. }, _)
             | (Jsonb, _)
             | (Uuid, _)
+            | (Inet, _)
+            | (Cidr, _)
+            | (Geometry, _)
+            | (Geography, _)
+            | (TsVector, _)
+            | (TsQuery, _)
             | (Array(_), _)
             | (List { .. }, _)
             | (Record { .. }, _)
             | (Oid, _)
-            | (Map { .. }, _) => false,
+            | (Map { .. }, _)
+            | (Range(_), _) => false,
         }
     }
 }
@@ -960,6 +1127,24 @@ impl Hash for ScalarType {
                 value_type.hash(state);
                 custom_oid.hash(state);
             }
+            Range(t) => {
+                state.write_u8(19);
+                t.hash(state);
+            }
+            Inet => state.write_u8(20),
+            Cidr => state.write_u8(21),
+            Geometry => state.write_u8(22),
+            Geography => state.write_u8(23),
+            TsVector => state.write_u8(24),
+            TsQuery => state.write_u8(25),
+            Char { length } => {
+                state.write_u8(26);
+                length.hash(state);
+            }
+            VarChar { length } => {
+                state.write_u8(27);
+                length.hash(state);
+            }
         }
     }
 }
@@ -46,8 +46,12 @@ use ore::str::StrExt;
 use crate::adt::array::ArrayDimension;
 use crate::adt::datetime::{self, DateTimeField, ParsedDateTime};
 use crate::adt::decimal::Decimal;
+use crate::adt::geo::{self, Shape};
+use crate::adt::inet::{self, Inet};
 use crate::adt::interval::Interval;
 use crate::adt::jsonb::{Jsonb, JsonbRef};
+use crate::adt::range::RangeBound;
+use crate::adt::tsearch::{self, TsQuery, TsVector};
 
 macro_rules! bail {
     ($($arg:tt)*) => { return Err(format!($($arg)*)) };
@@ -560,6 +564,125 @@ where
     Nestable::Yes
 }
 
+/// Parses an [`Inet`] from `s`.
+///
+/// The accepted format is an IPv4 or IPv6 address, optionally followed by a
+/// slash and the length of the subnet mask in bits. If the subnet mask is
+/// omitted, it is assumed to cover the entire address.
+pub fn parse_inet(s: &str) -> Result<Inet, ParseError> {
+    parse_inet_inner(s).ok_or_else(|| ParseError::invalid_input_syntax("inet", s))
+}
+
+fn parse_inet_inner(s: &str) -> Option<Inet> {
+    let s = s.trim();
+    let (addr, prefix_len) = match s.find('/') {
+        None => (s, None),
+        Some(i) => (&s[..i], Some(&s[i + 1..])),
+    };
+    let addr = addr.parse().ok()?;
+    let prefix_len = match prefix_len {
+        None => inet::max_prefix_len(&addr),
+        Some(prefix_len) => prefix_len.parse().ok()?,
+    };
+    Inet::new(addr, prefix_len)
+}
+
+/// Formats an [`Inet`] as an `inet`.
+///
+/// The subnet mask is omitted if it covers the entire address.
+pub fn format_inet<F>(buf: &mut F, inet: Inet) -> Nestable
+where
+    F: FormatBuffer,
+{
+    if inet.is_host() {
+        write!(buf, "{}", inet.addr());
+    } else {
+        write!(buf, "{}/{}", inet.addr(), inet.prefix_len());
+    }
+    Nestable::Yes
+}
+
+/// Parses an [`Inet`] from `s` as a `cidr`.
+///
+/// The accepted format is the same as for [`parse_inet`], except that the
+/// address must not have any bits set to the right of the subnet mask.
+pub fn parse_cidr(s: &str) -> Result<Inet, ParseError> {
+    match parse_inet_inner(s) {
+        None => Err(ParseError::invalid_input_syntax("cidr", s)),
+        Some(inet) if !inet.is_network() => Err(ParseError::invalid_input_syntax("cidr", s)
+            .with_details("Value has bits set to right of mask.")),
+        Some(inet) => Ok(inet),
+    }
+}
+
+/// Formats an [`Inet`] as a `cidr`.
+pub fn format_cidr<F>(buf: &mut F, cidr: Inet) -> Nestable
+where
+    F: FormatBuffer,
+{
+    write!(buf, "{}/{}", cidr.addr(), cidr.prefix_len());
+    Nestable::Yes
+}
+
+/// Parses a `geometry` from `s`.
+///
+/// The accepted formats are hex-encoded EWKB, as produced by
+/// [`format_geometry`], and EWKT, i.e., WKT with an optional `SRID=n;` prefix.
+pub fn parse_geometry(s: &str) -> Result<(i32, Shape), ParseError> {
+    parse_geometry_inner(s)
+        .map_err(|e| ParseError::invalid_input_syntax("geometry", s).with_details(e))
+}
+
+fn parse_geometry_inner(s: &str) -> Result<(i32, Shape), geo::InvalidGeometryError> {
+    let s = s.trim();
+    if !s.is_empty() && s.len() % 2 == 0 && s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        let ewkb = hex::decode(s).expect("validated as hex above");
+        geo::decode_ewkb(&ewkb)
+    } else {
+        geo::parse_ewkt(s)
+    }
+}
+
+/// Parses a `geography` from `s`.
+///
+/// The accepted formats are the same as for [`parse_geometry`], but the
+/// spatial reference system must be unspecified or WGS 84, and all
+/// coordinates must be valid longitudes and latitudes.
+pub fn parse_geography(s: &str) -> Result<(i32, Shape), ParseError> {
+    parse_geometry_inner(s)
+        .and_then(|(srid, shape)| Ok((geo::check_geography(srid, &shape)?, shape)))
+        .map_err(|e| ParseError::invalid_input_syntax("geography", s).with_details(e))
+}
+
+/// Formats a `geometry` or `geography` as hex-encoded EWKB.
+pub fn format_geometry<F>(buf: &mut F, srid: i32, shape: &Shape) -> Nestable
+where
+    F: FormatBuffer,
+{
+    let mut ewkb = vec![];
+    geo::encode_ewkb(&mut ewkb, srid, shape);
+    buf.write_str(&hex::encode_upper(ewkb));
+    Nestable::Yes
+}
+
+/// Parses a [`TsVector`] from `s`.
+///
+/// Lexemes are taken as written; they are not normalized according to any
+/// text search configuration.
+pub fn parse_tsvector(s: &str) -> Result<TsVector, ParseError> {
+    tsearch::parse_tsvector(s)
+        .map_err(|e| ParseError::invalid_input_syntax("tsvector", s).with_details(e))
+}
+
+/// Parses a [`TsQuery`] from `s`.
+///
+/// Operands are taken as written; they are not normalized according to any
+/// text search configuration.
+pub fn parse_tsquery(s: &str) -> Result<TsQuery, ParseError> {
+    tsearch::parse_tsquery(s, |text| vec![text.to_owned()])
+        .map_err(|e| ParseError::invalid_input_syntax("tsquery", s).with_details(e))
+}
+
 fn format_nanos_to_micros<F>(buf: &mut F, nanos: u32)
 where
     F: FormatBuffer,
@@ -672,6 +795,136 @@ where
     Ok(elems)
 }
 
+/// Parses a PostgreSQL array literal, like `{{1,2},{3,4}}`, into its
+/// dimensions and its elements in row-major order.
+pub fn parse_array<'a, T, E>(
+    s: &'a str,
+    make_null: impl FnMut() -> T,
+    gen_elem: impl FnMut(Cow<'a, str>) -> Result<T, E>,
+) -> Result<(Vec<ArrayDimension>, Vec<T>), ParseError>
+where
+    E: fmt::Display,
+{
+    parse_array_inner(s, make_null, gen_elem)
+        .map_err(|details| ParseError::invalid_input_syntax("array", s).with_details(details))
+}
+
+fn parse_array_inner<'a, T, E>(
+    s: &'a str,
+    mut make_null: impl FnMut() -> T,
+    mut gen_elem: impl FnMut(Cow<'a, str>) -> Result<T, E>,
+) -> Result<(Vec<ArrayDimension>, Vec<T>), String>
+where
+    E: fmt::Display,
+{
+    let mut elems = vec![];
+    let buf = &mut LexBuf::new(s);
+
+    // Simplifies calls to `gen_elem` by handling errors
+    let mut gen = |elem| gen_elem(elem).map_err(|e| e.to_string());
+    let is_special_char = |c| matches!(c, '{' | '}' | ',' | '\\' | '"');
+    let is_end_of_literal = |c| matches!(c, ',' | '}');
+    let mismatched_dims =
+        || "multidimensional arrays must have sub-arrays with matching dimensions".to_string();
+
+    buf.take_while(|ch| ch.is_ascii_whitespace());
+    if !buf.consume('{') {
+        bail!(
+            "expected '{{', found {}",
+            match buf.next() {
+                Some(c) => format!("{}", c),
+                None => "empty string".to_string(),
+            }
+        )
+    }
+
+    // The length of each dimension, once known.
+    let mut dim_lengths: Vec<Option<usize>> = vec![];
+    // The number of items seen so far in each sub-array that is open.
+    let mut counts = vec![0];
+    // The depth at which elements appear, once known.
+    let mut elem_depth = None;
+
+    while let Some(count) = counts.last().copied() {
+        buf.take_while(|ch| ch.is_ascii_whitespace());
+        // Check for terminals.
+        match buf.next() {
+            Some('}') => {
+                let depth = counts.len();
+                counts.pop();
+                if count == 0 {
+                    // Only the outermost array may be empty.
+                    if depth > 1 || elem_depth.is_some() {
+                        return Err(mismatched_dims());
+                    }
+                } else {
+                    if dim_lengths.len() < depth {
+                        dim_lengths.resize(depth, None);
+                    }
+                    match dim_lengths[depth - 1] {
+                        Some(length) if length != count => return Err(mismatched_dims()),
+                        _ => dim_lengths[depth - 1] = Some(count),
+                    }
+                }
+                if let Some(parent_count) = counts.last_mut() {
+                    *parent_count += 1;
+                }
+                continue;
+            }
+            None => bail!("unexpected end of input"),
+            _ if count == 0 => {
+                buf.prev();
+            }
+            Some(',') => {}
+            Some(c) => bail!("expected ',' or '}}', got '{}'", c),
+        }
+
+        buf.take_while(|ch| ch.is_ascii_whitespace());
+        // Get elements or open sub-arrays.
+        let elem = match buf.peek() {
+            Some('{') => {
+                if let Some(elem_depth) = elem_depth {
+                    if counts.len() >= elem_depth {
+                        return Err(mismatched_dims());
+                    }
+                }
+                buf.next();
+                counts.push(0);
+                continue;
+            }
+            Some('"') => gen(lex_quoted_element(buf)?)?,
+            Some(_) => match lex_unquoted_element(buf, is_special_char, is_end_of_literal)? {
+                Some(elem) => gen(elem)?,
+                None => make_null(),
+            },
+            None => bail!("unexpected end of input"),
+        };
+        match elem_depth {
+            Some(elem_depth) if elem_depth != counts.len() => return Err(mismatched_dims()),
+            _ => elem_depth = Some(counts.len()),
+        }
+        elems.push(elem);
+        *counts.last_mut().unwrap() += 1;
+    }
+
+    buf.take_while(|ch| ch.is_ascii_whitespace());
+    if let Some(c) = buf.next() {
+        bail!(
+            "malformed array literal; contains '{}' after terminal '}}'",
+            c
+        )
+    }
+
+    let dims = dim_lengths
+        .into_iter()
+        .map(|length| ArrayDimension {
+            lower_bound: 1,
+            length: length.expect("every dimension has a length"),
+        })
+        .collect();
+    Ok((dims, elems))
+}
+
 fn lex_quoted_element<'a>(buf: &mut LexBuf<'a>) -> Result<Cow<'a, str>, String> {
     assert!(buf.consume('"'));
     let s = buf.take_while(|ch| !matches!(ch, '"' | '\\'));
@@ -986,6 +1239,139 @@ where
     Nestable::Yes
 }
 
+pub fn parse_range<'a, T, E>(
+    s: &'a str,
+    gen_elem: impl FnMut(Cow<'a, str>) -> Result<T, E>,
+) -> Result<Option<(RangeBound<T>, RangeBound<T>)>, ParseError>
+where
+    E: fmt::Display,
+{
+    parse_range_inner(s, gen_elem)
+        .map_err(|details| ParseError::invalid_input_syntax("range", s).with_details(details))
+}
+
+// Returns `None` if `s` describes the empty range.
+fn parse_range_inner<'a, T, E>(
+    s: &'a str,
+    mut gen_elem: impl FnMut(Cow<'a, str>) -> Result<T, E>,
+) -> Result<Option<(RangeBound<T>, RangeBound<T>)>, String>
+where
+    E: fmt::Display,
+{
+    if s.trim().eq_ignore_ascii_case("empty") {
+        return Ok(None);
+    }
+
+    let buf = &mut LexBuf::new(s);
+    let mut gen = |elem| gen_elem(elem).map_err(|e| e.to_string());
+
+    buf.take_while(|ch| ch.is_ascii_whitespace());
+    let lower_inclusive = match buf.next() {
+        Some('[') => true,
+        Some('(') => false,
+        Some(c) => bail!("expected '[' or '(', found '{}'", c),
+        None => bail!("unexpected end of input"),
+    };
+    let lower = lex_range_bound(buf)?.map(&mut gen).transpose()?;
+    if !buf.consume(',') {
+        bail!("missing comma after lower bound")
+    }
+    let upper = lex_range_bound(buf)?.map(&mut gen).transpose()?;
+    let upper_inclusive = match buf.next() {
+        Some(']') => true,
+        Some(')') => false,
+        Some(c) => bail!("expected ']' or ')', found '{}'", c),
+        None => bail!("unexpected end of input"),
+    };
+    buf.take_while(|ch| ch.is_ascii_whitespace());
+    if let Some(c) = buf.next() {
+        bail!("junk after right parenthesis or bracket: '{}'", c)
+    }
+
+    Ok(Some((
+        RangeBound {
+            value: lower,
+            inclusive: lower_inclusive,
+        },
+        RangeBound {
+            value: upper,
+            inclusive: upper_inclusive,
+        },
+    )))
+}
+
+// Result of `None` indicates that the range is unbounded at this end.
+fn lex_range_bound<'a>(buf: &mut LexBuf<'a>) -> Result<Option<Cow<'a, str>>, String> {
+    let is_special_char = |c| matches!(c, ',' | '(' | ')' | '[' | ']' | '"' | '\\');
+    let s = buf.take_while(|ch| !is_special_char(ch));
+
+    // `Cow::Borrowed` optimization for bounds without quotes or escapes.
+    match buf.peek() {
+        Some(',') | Some(')') | Some(']') => {
+            return Ok(if s.is_empty() { None } else { Some(s.into()) });
+        }
+        _ => {}
+    }
+
+    let mut s = s.to_string();
+    let mut in_quotes = false;
+    loop {
+        match buf.next() {
+            Some('\\') => match buf.next() {
+                Some(c) => s.push(c),
+                None => bail!("unexpected end of input"),
+            },
+            Some('"') if in_quotes && buf.peek() == Some('"') => {
+                buf.next();
+                s.push('"');
+            }
+            Some('"') => in_quotes = !in_quotes,
+            Some(c) if in_quotes => s.push(c),
+            Some(',') | Some(')') | Some(']') => {
+                buf.prev();
+                break;
+            }
+            Some(c @ '(') | Some(c @ '[') => bail!("unexpected '{}' in range bound", c),
+            Some(c) => s.push(c),
+            None => bail!("unexpected end of input"),
+        }
+    }
+    Ok(Some(s.into()))
+}
+
+pub fn format_range<F, T>(
+    buf: &mut F,
+    bounds: Option<(RangeBound<T>, RangeBound<T>)>,
+    mut format_elem: impl FnMut(&mut F, T) -> Nestable,
+) -> Nestable
+where
+    F: FormatBuffer,
+{
+    let (lower, upper) = match bounds {
+        None => {
+            buf.write_str("empty");
+            return Nestable::Yes;
+        }
+        Some(bounds) => bounds,
+    };
+    buf.write_char(if lower.inclusive { '[' } else { '(' });
+    if let Some(elem) = lower.value {
+        let start = buf.len();
+        if let Nestable::MayNeedEscaping = format_elem(buf, elem) {
+            escape_elem::<_, RangeElementEscaper>(buf, start);
+        }
+    }
+    buf.write_char(',');
+    if let Some(elem) = upper.value {
+        let start = buf.len();
+        if let Nestable::MayNeedEscaping = format_elem(buf, elem) {
+            escape_elem::<_, RangeElementEscaper>(buf, start);
+        }
+    }
+    buf.write_char(if upper.inclusive { ']' } else { ')' });
+    Nestable::MayNeedEscaping
+}
+
 pub trait ElementEscaper {
     fn needs_escaping(elem: &[u8]) -> bool;
     fn escape_char(c: u8) -> u8;
@@ -1043,7 +1429,23 @@ impl ElementEscaper for RecordElementEscaper {
     }
 }
 
-/// Escapes a list, record, or map element in place.
+struct RangeElementEscaper;
+
+impl ElementEscaper for RangeElementEscaper {
+    fn needs_escaping(elem: &[u8]) -> bool {
+        elem.is_empty()
+            || elem.iter().any(|c| {
+                matches!(c, b'(' | b')' | b'[' | b']' | b',' | b'"' | b'\\')
+                    || c.is_ascii_whitespace()
+            })
+    }
+
+    fn escape_char(c: u8) -> u8 {
+        c
+    }
+}
+
+/// Escapes a list, record, map, or range element in place.
 ///
 /// The element must start at `start` and extend to the end of the buffer. The
 /// buffer will be resized if escaping is necessary to account for the
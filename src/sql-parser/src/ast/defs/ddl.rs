@@ -125,13 +125,19 @@ impl_display!(CsrSeed);
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Format<T: AstInfo> {
-    Bytes,
+    Bytes {
+        framing: Option<BytesFraming>,
+    },
     Avro(AvroSchema<T>),
     Protobuf {
         message_name: String,
         schema: Schema,
     },
-    Regex(String),
+    Regex {
+        regex: String,
+        types: Vec<RegexColumnType<T>>,
+        strict: bool,
+    },
     Csv {
         header_row: bool,
         n_cols: Option<usize>,
@@ -147,8 +153,30 @@ pub enum Envelope<T: AstInfo> {
     Debezium,
     Upsert(Option<Format<T>>),
     CdcV2,
+    Deduplicate {
+        key: Vec<Ident>,
+        keep: DeduplicateKeep,
+    },
+}
+
+/// Which record to retain when an append-only source sees a key more than
+/// once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeduplicateKeep {
+    First,
+    Last,
 }
 
+impl AstDisplay for DeduplicateKeep {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            Self::First => f.write_str("FIRST"),
+            Self::Last => f.write_str("LAST"),
+        }
+    }
+}
+impl_display!(DeduplicateKeep);
+
 impl<T: AstInfo> Default for Envelope<T> {
     fn default() -> Self {
         Self::None
@@ -175,6 +203,12 @@ impl<T: AstInfo> AstDisplay for Envelope<T> {
             Self::CdcV2 => {
                 f.write_str("MATERIALIZE");
             }
+            Self::Deduplicate { key, keep } => {
+                f.write_str("NONE DEDUPLICATE BY (");
+                f.write_node(&display::comma_separated(key));
+                f.write_str(") KEEP ");
+                f.write_node(keep);
+            }
         }
     }
 }
@@ -183,7 +217,13 @@ impl_display_t!(Envelope);
 impl<T: AstInfo> AstDisplay for Format<T> {
     fn fmt(&self, f: &mut AstFormatter) {
         match self {
-            Self::Bytes => f.write_str("BYTES"),
+            Self::Bytes { framing } => {
+                f.write_str("BYTES");
+                if let Some(framing) = framing {
+                    f.write_str(" ");
+                    f.write_node(framing);
+                }
+            }
             Self::Avro(inner) => {
                 f.write_str("AVRO USING ");
                 f.write_node(inner);
@@ -197,10 +237,22 @@ impl<T: AstInfo> AstDisplay for Format<T> {
                 f.write_str("' USING ");
                 f.write_str(schema);
             }
-            Self::Regex(regex) => {
+            Self::Regex {
+                regex,
+                types,
+                strict,
+            } => {
                 f.write_str("REGEX '");
                 f.write_node(&display::escape_single_quote_string(regex));
                 f.write_str("'");
+                if !types.is_empty() {
+                    f.write_str(" TYPES (");
+                    f.write_node(&display::comma_separated(types));
+                    f.write_str(")");
+                }
+                if *strict {
+                    f.write_str(" STRICT");
+                }
             }
             Self::Csv {
                 header_row,
@@ -227,9 +279,62 @@ impl<T: AstInfo> AstDisplay for Format<T> {
 }
 impl_display_t!(Format);
 
+/// A type annotation for a named capture group in `FORMAT REGEX`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegexColumnType<T: AstInfo> {
+    pub name: Ident,
+    pub data_type: DataType<T>,
+}
+
+impl<T: AstInfo> AstDisplay for RegexColumnType<T> {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_node(&self.name);
+        f.write_str(" ");
+        f.write_node(&self.data_type);
+    }
+}
+impl_display_t!(RegexColumnType);
+
+/// How `FORMAT BYTES` splits a byte stream into records.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BytesFraming {
+    /// Records are separated by a delimiter.
+    Delimited(String),
+    /// Each record is preceded by its length, encoded as a big-endian
+    /// unsigned integer of the specified width in bytes.
+    LengthPrefixed(u64),
+    /// Every record has the specified width in bytes.
+    FixedWidth(u64),
+}
+
+impl AstDisplay for BytesFraming {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            Self::Delimited(delimiter) => {
+                f.write_str("DELIMITED BY '");
+                f.write_node(&display::escape_single_quote_string(delimiter));
+                f.write_str("'");
+            }
+            Self::LengthPrefixed(width) => {
+                f.write_str("LENGTH PREFIXED BY ");
+                f.write_str(width);
+            }
+            Self::FixedWidth(width) => {
+                f.write_str("FIXED WIDTH ");
+                f.write_str(width);
+            }
+        }
+    }
+}
+impl_display!(BytesFraming);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Compression {
     Gzip,
+    Zstd,
+    Bzip2,
+    /// Detect the compression of each file or object from its extension.
+    Auto,
     None,
 }
 
@@ -243,6 +348,9 @@ impl AstDisplay for Compression {
     fn fmt(&self, f: &mut AstFormatter) {
         match self {
             Self::Gzip => f.write_str("GZIP"),
+            Self::Zstd => f.write_str("ZSTD"),
+            Self::Bzip2 => f.write_str("BZIP2"),
+            Self::Auto => f.write_str("AUTO"),
             Self::None => f.write_str("NONE"),
         }
     }
@@ -272,6 +380,7 @@ pub enum Connector<T: AstInfo> {
         key_sources: Vec<S3KeySource>,
         /// The argument to the MATCHING clause: `MATCHING 'a/**/*.json'`
         pattern: Option<String>,
+        compression: Compression,
     },
     Postgres {
         /// The postgres connection string
@@ -325,6 +434,7 @@ impl<T: AstInfo> AstDisplay for Connector<T> {
             Connector::S3 {
                 key_sources,
                 pattern,
+                compression,
             } => {
                 f.write_str("S3 OBJECTS FROM");
                 f.write_node(&display::comma_separated(key_sources));
@@ -333,6 +443,10 @@ impl<T: AstInfo> AstDisplay for Connector<T> {
                     f.write_str(&display::escape_single_quote_string(pattern));
                     f.write_str("'");
                 }
+                if compression != &Default::default() {
+                    f.write_str(" COMPRESSION ");
+                    f.write_node(compression);
+                }
             }
             Connector::Postgres {
                 conn,
@@ -385,6 +499,46 @@ impl AstDisplay for S3KeySource {
 }
 impl_display!(S3KeySource);
 
+/// A key constraint declared on a source or view, which is trusted rather
+/// than verified.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeyConstraint {
+    /// `PRIMARY KEY (<columns>) NOT ENFORCED`
+    PrimaryKeyNotEnforced { columns: Vec<Ident> },
+    /// `FOREIGN KEY (<columns>) REFERENCES <foreign_table> (<referred_columns>) NOT ENFORCED`
+    ForeignKeyNotEnforced {
+        columns: Vec<Ident>,
+        foreign_table: UnresolvedObjectName,
+        referred_columns: Vec<Ident>,
+    },
+}
+
+impl AstDisplay for KeyConstraint {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            KeyConstraint::PrimaryKeyNotEnforced { columns } => {
+                f.write_str("PRIMARY KEY (");
+                f.write_node(&display::comma_separated(columns));
+                f.write_str(") NOT ENFORCED");
+            }
+            KeyConstraint::ForeignKeyNotEnforced {
+                columns,
+                foreign_table,
+                referred_columns,
+            } => {
+                f.write_str("FOREIGN KEY (");
+                f.write_node(&display::comma_separated(columns));
+                f.write_str(") REFERENCES ");
+                f.write_node(foreign_table);
+                f.write_str(" (");
+                f.write_node(&display::comma_separated(referred_columns));
+                f.write_str(") NOT ENFORCED");
+            }
+        }
+    }
+}
+impl_display!(KeyConstraint);
+
 /// A table-level constraint, specified in a `CREATE TABLE` or an
 /// `ALTER TABLE ADD <constraint>` statement.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -160,6 +160,8 @@ pub enum Expr<T: AstInfo> {
     Array(Vec<Expr<T>>),
     /// `LIST[<expr>*]`
     List(Vec<Expr<T>>),
+    /// `MAP[<expr> => <expr>*]`
+    Map(Vec<MapEntry<T>>),
     /// `<expr>[<expr>]`
     SubscriptIndex {
         expr: Box<Expr<T>>,
@@ -417,6 +419,11 @@ impl<T: AstInfo> AstDisplay for Expr<T> {
                 }
                 f.write_str("]");
             }
+            Expr::Map(entries) => {
+                f.write_str("MAP[");
+                f.write_node(&display::comma_separated(entries));
+                f.write_str("]");
+            }
             Expr::SubscriptIndex { expr, subscript } => {
                 f.write_node(&expr);
                 f.write_str("[");
@@ -517,7 +524,7 @@ impl<T: AstInfo> Expr<T> {
     pub fn call(name: Vec<&str>, args: Vec<Expr<T>>) -> Expr<T> {
         Expr::Function(Function {
             name: UnresolvedObjectName(name.into_iter().map(Into::into).collect()),
-            args: FunctionArgs::Args(args),
+            args: FunctionArgs::args(args),
             filter: None,
             over: None,
             distinct: false,
@@ -556,6 +563,22 @@ impl<T: AstInfo> AstDisplay for SubscriptPosition<T> {
 }
 impl_display_t!(SubscriptPosition);
 
+/// An entry in a `MAP[...]` expression.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MapEntry<T: AstInfo> {
+    pub key: Expr<T>,
+    pub value: Expr<T>,
+}
+
+impl<T: AstInfo> AstDisplay for MapEntry<T> {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_node(&self.key);
+        f.write_str(" => ");
+        f.write_node(&self.value);
+    }
+}
+impl_display_t!(MapEntry);
+
 /// A window specification (i.e. `OVER (PARTITION BY .. ORDER BY .. etc.)`)
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct WindowSpec<T: AstInfo> {
@@ -701,15 +724,34 @@ impl_display_t!(Function);
 pub enum FunctionArgs<T: AstInfo> {
     /// The special star argument, as in `count(*)`.
     Star,
-    /// A normal list of arguments.
-    Args(Vec<Expr<T>>),
+    /// A normal list of arguments, optionally followed by an `ORDER BY`
+    /// clause, as in `array_agg(x ORDER BY y)`.
+    Args {
+        args: Vec<Expr<T>>,
+        order_by: Vec<OrderByExpr<T>>,
+    },
+}
+
+impl<T: AstInfo> FunctionArgs<T> {
+    pub fn args(args: Vec<Expr<T>>) -> Self {
+        FunctionArgs::Args {
+            args,
+            order_by: vec![],
+        }
+    }
 }
 
 impl<T: AstInfo> AstDisplay for FunctionArgs<T> {
     fn fmt(&self, f: &mut AstFormatter) {
         match self {
             FunctionArgs::Star => f.write_str("*"),
-            FunctionArgs::Args(args) => f.write_node(&display::comma_separated(&args)),
+            FunctionArgs::Args { args, order_by } => {
+                f.write_node(&display::comma_separated(&args));
+                if !order_by.is_empty() {
+                    f.write_str(" ORDER BY ");
+                    f.write_node(&display::comma_separated(&order_by));
+                }
+            }
         }
     }
 }
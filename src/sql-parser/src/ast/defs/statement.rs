@@ -20,8 +20,8 @@
 
 use crate::ast::display::{self, AstDisplay, AstFormatter};
 use crate::ast::{
-    AstInfo, ColumnDef, Connector, DataType, Envelope, Expr, Format, Ident, Query, TableConstraint,
-    UnresolvedObjectName, Value,
+    AstInfo, ColumnDef, Connector, DataType, Envelope, Expr, Format, Ident, Query, Schema,
+    TableConstraint, UnresolvedObjectName, Value,
 };
 
 /// A top-level statement (SELECT, INSERT, CREATE, etc.)
@@ -37,6 +37,7 @@ pub enum Statement<T: AstInfo> {
     CreateSchema(CreateSchemaStatement),
     CreateSource(CreateSourceStatement<T>),
     CreateSink(CreateSinkStatement<T>),
+    CreateTask(CreateTaskStatement<T>),
     CreateView(CreateViewStatement<T>),
     CreateTable(CreateTableStatement<T>),
     CreateIndex(CreateIndexStatement<T>),
@@ -44,10 +45,17 @@ pub enum Statement<T: AstInfo> {
     CreateRole(CreateRoleStatement),
     AlterObjectRename(AlterObjectRenameStatement),
     AlterIndexOptions(AlterIndexOptionsStatement),
+    AlterSourceOptions(AlterSourceOptionsStatement<T>),
+    AlterSourceSchema(AlterSourceSchemaStatement),
+    AlterSystemSet(AlterSystemSetStatement),
+    AlterSystemReset(AlterSystemResetStatement),
+    AlterRoleSet(AlterRoleSetStatement),
+    AlterRoleReset(AlterRoleResetStatement),
     Discard(DiscardStatement),
     DropDatabase(DropDatabaseStatement),
     DropObjects(DropObjectsStatement),
     SetVariable(SetVariableStatement),
+    ResetVariable(ResetVariableStatement),
     ShowDatabases(ShowDatabasesStatement<T>),
     ShowObjects(ShowObjectsStatement<T>),
     ShowIndexes(ShowIndexesStatement<T>),
@@ -88,6 +96,7 @@ impl<T: AstInfo> AstDisplay for Statement<T> {
             Statement::CreateSchema(stmt) => f.write_node(stmt),
             Statement::CreateSource(stmt) => f.write_node(stmt),
             Statement::CreateSink(stmt) => f.write_node(stmt),
+            Statement::CreateTask(stmt) => f.write_node(stmt),
             Statement::CreateView(stmt) => f.write_node(stmt),
             Statement::CreateTable(stmt) => f.write_node(stmt),
             Statement::CreateIndex(stmt) => f.write_node(stmt),
@@ -95,10 +104,17 @@ impl<T: AstInfo> AstDisplay for Statement<T> {
             Statement::CreateType(stmt) => f.write_node(stmt),
             Statement::AlterObjectRename(stmt) => f.write_node(stmt),
             Statement::AlterIndexOptions(stmt) => f.write_node(stmt),
+            Statement::AlterSourceOptions(stmt) => f.write_node(stmt),
+            Statement::AlterSourceSchema(stmt) => f.write_node(stmt),
+            Statement::AlterSystemSet(stmt) => f.write_node(stmt),
+            Statement::AlterSystemReset(stmt) => f.write_node(stmt),
+            Statement::AlterRoleSet(stmt) => f.write_node(stmt),
+            Statement::AlterRoleReset(stmt) => f.write_node(stmt),
             Statement::Discard(stmt) => f.write_node(stmt),
             Statement::DropDatabase(stmt) => f.write_node(stmt),
             Statement::DropObjects(stmt) => f.write_node(stmt),
             Statement::SetVariable(stmt) => f.write_node(stmt),
+            Statement::ResetVariable(stmt) => f.write_node(stmt),
             Statement::ShowDatabases(stmt) => f.write_node(stmt),
             Statement::ShowObjects(stmt) => f.write_node(stmt),
             Statement::ShowIndexes(stmt) => f.write_node(stmt),
@@ -347,6 +363,7 @@ impl_display!(CreateSchemaStatement);
 pub struct CreateSourceStatement<T: AstInfo> {
     pub name: UnresolvedObjectName,
     pub col_names: Vec<Ident>,
+    pub key_constraints: Vec<KeyConstraint>,
     pub connector: Connector<T>,
     pub with_options: Vec<SqlOption<T>>,
     pub format: Option<Format<T>>,
@@ -367,9 +384,15 @@ impl<T: AstInfo> AstDisplay for CreateSourceStatement<T> {
         }
         f.write_node(&self.name);
         f.write_str(" ");
-        if !self.col_names.is_empty() {
+        if !self.col_names.is_empty() || !self.key_constraints.is_empty() {
             f.write_str("(");
             f.write_node(&display::comma_separated(&self.col_names));
+            if !self.key_constraints.is_empty() {
+                if !self.col_names.is_empty() {
+                    f.write_str(", ");
+                }
+                f.write_node(&display::comma_separated(&self.key_constraints));
+            }
             f.write_str(") ");
         }
         f.write_str("FROM ");
@@ -446,12 +469,39 @@ impl<T: AstInfo> AstDisplay for CreateSinkStatement<T> {
 }
 impl_display_t!(CreateSinkStatement);
 
+/// `CREATE TASK`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreateTaskStatement<T: AstInfo> {
+    pub name: UnresolvedObjectName,
+    /// The interval at which the task runs, e.g. `'5 minutes'`.
+    pub schedule: String,
+    /// The statement that the task runs.
+    pub insert: InsertStatement<T>,
+    pub if_not_exists: bool,
+}
+
+impl<T: AstInfo> AstDisplay for CreateTaskStatement<T> {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str("CREATE TASK ");
+        if self.if_not_exists {
+            f.write_str("IF NOT EXISTS ");
+        }
+        f.write_node(&self.name);
+        f.write_str(" SCHEDULE '");
+        f.write_node(&display::escape_single_quote_string(&self.schedule));
+        f.write_str("' AS ");
+        f.write_node(&self.insert);
+    }
+}
+impl_display_t!(CreateTaskStatement);
+
 /// `CREATE VIEW`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CreateViewStatement<T: AstInfo> {
     /// View name
     pub name: UnresolvedObjectName,
     pub columns: Vec<Ident>,
+    pub key_constraints: Vec<KeyConstraint>,
     pub with_options: Vec<SqlOption<T>>,
     pub query: Query<T>,
     pub if_exists: IfExistsBehavior,
@@ -487,9 +537,15 @@ impl<T: AstInfo> AstDisplay for CreateViewStatement<T> {
             f.write_str(")");
         }
 
-        if !self.columns.is_empty() {
+        if !self.columns.is_empty() || !self.key_constraints.is_empty() {
             f.write_str(" (");
             f.write_node(&display::comma_separated(&self.columns));
+            if !self.key_constraints.is_empty() {
+                if !self.columns.is_empty() {
+                    f.write_str(", ");
+                }
+                f.write_node(&display::comma_separated(&self.key_constraints));
+            }
             f.write_str(")");
         }
 
@@ -551,6 +607,8 @@ pub struct CreateIndexStatement<T: AstInfo> {
     /// key_parts will be inferred from the named object.
     pub key_parts: Option<Vec<Expr<T>>>,
     pub with_options: Vec<WithOption>,
+    /// Optional predicate that restricts the index to matching rows.
+    pub predicate: Option<Expr<T>>,
     pub if_not_exists: bool,
 }
 
@@ -580,6 +638,10 @@ impl<T: AstInfo> AstDisplay for CreateIndexStatement<T> {
             f.write_node(&display::comma_separated(&self.with_options));
             f.write_str(")");
         }
+        if let Some(predicate) = &self.predicate {
+            f.write_str(" WHERE ");
+            f.write_node(predicate);
+        }
     }
 }
 impl_display_t!(CreateIndexStatement);
@@ -745,6 +807,130 @@ impl AstDisplay for AlterIndexOptionsStatement {
 
 impl_display!(AlterIndexOptionsStatement);
 
+/// `ALTER SOURCE ... SET`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterSourceOptionsStatement<T: AstInfo> {
+    pub source_name: UnresolvedObjectName,
+    pub if_exists: bool,
+    pub options: Vec<SqlOption<T>>,
+}
+
+impl<T: AstInfo> AstDisplay for AlterSourceOptionsStatement<T> {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str("ALTER SOURCE ");
+        if self.if_exists {
+            f.write_str("IF EXISTS ");
+        }
+        f.write_node(&self.source_name);
+        f.write_str(" SET (");
+        f.write_node(&display::comma_separated(&self.options));
+        f.write_str(")");
+    }
+}
+impl_display_t!(AlterSourceOptionsStatement);
+
+/// `ALTER SOURCE ... SET SCHEMA FROM ...`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterSourceSchemaStatement {
+    pub source_name: UnresolvedObjectName,
+    pub if_exists: bool,
+    pub schema: Schema,
+}
+
+impl AstDisplay for AlterSourceSchemaStatement {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str("ALTER SOURCE ");
+        if self.if_exists {
+            f.write_str("IF EXISTS ");
+        }
+        f.write_node(&self.source_name);
+        f.write_str(" SET SCHEMA FROM ");
+        match &self.schema {
+            Schema::File(path) => {
+                f.write_str("FILE '");
+                f.write_node(&display::escape_single_quote_string(
+                    &path.display().to_string(),
+                ));
+                f.write_str("'");
+            }
+            Schema::Inline(inner) => {
+                f.write_str("'");
+                f.write_node(&display::escape_single_quote_string(inner));
+                f.write_str("'");
+            }
+        }
+    }
+}
+impl_display!(AlterSourceSchemaStatement);
+
+/// `ALTER SYSTEM SET <variable> = <value>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterSystemSetStatement {
+    pub variable: Ident,
+    pub value: SetVariableValue,
+}
+
+impl AstDisplay for AlterSystemSetStatement {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str("ALTER SYSTEM SET ");
+        f.write_node(&self.variable);
+        f.write_str(" = ");
+        f.write_node(&self.value);
+    }
+}
+impl_display!(AlterSystemSetStatement);
+
+/// `ALTER SYSTEM RESET <variable>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterSystemResetStatement {
+    pub variable: Ident,
+}
+
+impl AstDisplay for AlterSystemResetStatement {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str("ALTER SYSTEM RESET ");
+        f.write_node(&self.variable);
+    }
+}
+impl_display!(AlterSystemResetStatement);
+
+/// `ALTER ROLE <role> SET <variable> = <value>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterRoleSetStatement {
+    pub name: Ident,
+    pub variable: Ident,
+    pub value: SetVariableValue,
+}
+
+impl AstDisplay for AlterRoleSetStatement {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str("ALTER ROLE ");
+        f.write_node(&self.name);
+        f.write_str(" SET ");
+        f.write_node(&self.variable);
+        f.write_str(" = ");
+        f.write_node(&self.value);
+    }
+}
+impl_display!(AlterRoleSetStatement);
+
+/// `ALTER ROLE <role> RESET <variable>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterRoleResetStatement {
+    pub name: Ident,
+    pub variable: Ident,
+}
+
+impl AstDisplay for AlterRoleResetStatement {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str("ALTER ROLE ");
+        f.write_node(&self.name);
+        f.write_str(" RESET ");
+        f.write_node(&self.variable);
+    }
+}
+impl_display!(AlterRoleResetStatement);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DiscardStatement {
     pub target: DiscardTarget,
@@ -850,6 +1036,20 @@ impl AstDisplay for SetVariableStatement {
 }
 impl_display!(SetVariableStatement);
 
+/// `RESET <variable>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ResetVariableStatement {
+    pub variable: Ident,
+}
+
+impl AstDisplay for ResetVariableStatement {
+    fn fmt(&self, f: &mut AstFormatter) {
+        f.write_str("RESET ");
+        f.write_node(&self.variable);
+    }
+}
+impl_display!(ResetVariableStatement);
+
 /// `SHOW <variable>`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ShowVariableStatement {
@@ -920,6 +1120,7 @@ impl<T: AstInfo> AstDisplay for ShowObjectsStatement<T> {
             ObjectType::Sink => "SINKS",
             ObjectType::Type => "TYPES",
             ObjectType::Role => "ROLES",
+            ObjectType::Task => "TASKS",
             ObjectType::Object => "OBJECTS",
             ObjectType::Index => unreachable!(),
         });
@@ -1131,6 +1332,7 @@ pub struct TailStatement<T: AstInfo> {
     pub name: UnresolvedObjectName,
     pub options: Vec<WithOption>,
     pub as_of: Option<Expr<T>>,
+    pub envelope: Option<TailEnvelope>,
 }
 
 impl<T: AstInfo> AstDisplay for TailStatement<T> {
@@ -1146,10 +1348,44 @@ impl<T: AstInfo> AstDisplay for TailStatement<T> {
             f.write_str(" AS OF ");
             f.write_node(as_of);
         }
+        if let Some(envelope) = &self.envelope {
+            f.write_str(" ENVELOPE ");
+            f.write_node(envelope);
+        }
     }
 }
 impl_display_t!(TailStatement);
 
+/// The envelope of a `TAIL`, which determines how changes are reported.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TailEnvelope {
+    /// `ENVELOPE UPSERT KEY (...)`
+    Upsert { key: Vec<Ident> },
+    /// `ENVELOPE DEBEZIUM [KEY (...)]`
+    Debezium { key: Option<Vec<Ident>> },
+}
+
+impl AstDisplay for TailEnvelope {
+    fn fmt(&self, f: &mut AstFormatter) {
+        match self {
+            TailEnvelope::Upsert { key } => {
+                f.write_str("UPSERT KEY (");
+                f.write_node(&display::comma_separated(key));
+                f.write_str(")");
+            }
+            TailEnvelope::Debezium { key } => {
+                f.write_str("DEBEZIUM");
+                if let Some(key) = key {
+                    f.write_str(" KEY (");
+                    f.write_node(&display::comma_separated(key));
+                    f.write_str(")");
+                }
+            }
+        }
+    }
+}
+impl_display!(TailEnvelope);
+
 /// `EXPLAIN ...`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ExplainStatement<T: AstInfo> {
@@ -1197,6 +1433,7 @@ pub enum ObjectType {
     Index,
     Type,
     Role,
+    Task,
     Object,
 }
 
@@ -1211,6 +1448,7 @@ impl AstDisplay for ObjectType {
             ObjectType::Index => "INDEX",
             ObjectType::Type => "TYPE",
             ObjectType::Role => "ROLE",
+            ObjectType::Task => "TASK",
             ObjectType::Object => "OBJECT",
         })
     }
@@ -1423,6 +1661,13 @@ pub enum ExplainStage {
     DecorrelatedPlan,
     /// The expr::MirRelationExpr after optimization
     OptimizedPlan,
+    /// The timestamp at which the query would execute, and the frontiers of
+    /// its inputs
+    Timestamp,
+    /// The indexes that would benefit the query
+    IndexAdvice,
+    /// The operators of the dataflow that maintains a source or sink
+    Dataflow,
 }
 
 impl AstDisplay for ExplainStage {
@@ -1431,6 +1676,9 @@ impl AstDisplay for ExplainStage {
             ExplainStage::RawPlan => f.write_str("RAW PLAN"),
             ExplainStage::DecorrelatedPlan => f.write_str("DECORRELATED PLAN"),
             ExplainStage::OptimizedPlan => f.write_str("OPTIMIZED PLAN"),
+            ExplainStage::Timestamp => f.write_str("TIMESTAMP"),
+            ExplainStage::IndexAdvice => f.write_str("INDEX ADVICE"),
+            ExplainStage::Dataflow => f.write_str("DATAFLOW"),
         }
     }
 }
@@ -1439,6 +1687,8 @@ impl_display!(ExplainStage);
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Explainee<T: AstInfo> {
     View(UnresolvedObjectName),
+    Source(UnresolvedObjectName),
+    Sink(UnresolvedObjectName),
     Query(Query<T>),
 }
 
@@ -1454,6 +1704,14 @@ impl<T: AstInfo> AstDisplay for Explainee<T> {
                 f.write_str("VIEW ");
                 f.write_node(&name);
             }
+            Explainee::Source(name) => {
+                f.write_str("SOURCE ");
+                f.write_node(&name);
+            }
+            Explainee::Sink(name) => {
+                f.write_str("SINK ");
+                f.write_node(&name);
+            }
             Explainee::Query(query) => f.write_node(query),
         }
     }
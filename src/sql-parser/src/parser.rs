@@ -210,6 +210,7 @@ impl<'a> Parser<'a> {
                 Token::Keyword(ALTER) => Ok(self.parse_alter()?),
                 Token::Keyword(COPY) => Ok(self.parse_copy()?),
                 Token::Keyword(SET) => Ok(self.parse_set()?),
+                Token::Keyword(RESET) => Ok(self.parse_reset()?),
                 Token::Keyword(SHOW) => Ok(self.parse_show()?),
                 Token::Keyword(START) => Ok(self.parse_start_transaction()?),
                 // `BEGIN` is a nonstandard but common alias for the
@@ -318,6 +319,9 @@ impl<'a> Parser<'a> {
             }
             Token::Keyword(ARRAY) => self.parse_array(),
             Token::Keyword(LIST) => self.parse_list(),
+            Token::Keyword(MAP) if self.peek_token() == Some(Token::LBracket) => {
+                self.parse_map_expr()
+            }
             Token::Keyword(CASE) => self.parse_case_expr(),
             Token::Keyword(CAST) => self.parse_cast_expr(),
             Token::Keyword(COALESCE) => self.parse_coalesce_expr(),
@@ -663,7 +667,7 @@ impl<'a> Parser<'a> {
         self.expect_token(&Token::RParen)?;
         Ok(Expr::Function(Function {
             name: UnresolvedObjectName::unqualified("date_part"),
-            args: FunctionArgs::Args(vec![Expr::Value(Value::String(field)), expr]),
+            args: FunctionArgs::args(vec![Expr::Value(Value::String(field)), expr]),
             filter: None,
             over: None,
             distinct: false,
@@ -712,7 +716,7 @@ impl<'a> Parser<'a> {
         self.expect_token(&Token::RParen)?;
         Ok(Expr::Function(Function {
             name: UnresolvedObjectName::unqualified(name),
-            args: FunctionArgs::Args(exprs),
+            args: FunctionArgs::args(exprs),
             filter: None,
             over: None,
             distinct: false,
@@ -934,7 +938,7 @@ impl<'a> Parser<'a> {
                     self.expect_keywords(&[TIME, ZONE])?;
                     Ok(Expr::Function(Function {
                         name: UnresolvedObjectName(vec!["timezone".into()]),
-                        args: FunctionArgs::Args(vec![self.parse_subexpr(precedence)?, expr]),
+                        args: FunctionArgs::args(vec![self.parse_subexpr(precedence)?, expr]),
                         filter: None,
                         over: None,
                         distinct: false,
@@ -1095,6 +1099,9 @@ impl<'a> Parser<'a> {
                 Token::Keyword(ILIKE) => Precedence::Like,
                 Token::Keyword(LIKE) => Precedence::Like,
                 Token::Op(s) => match s.as_str() {
+                    // `=>` separates the keys and values of map expressions
+                    // and is not itself an infix operator.
+                    "=>" => Precedence::Zero,
                     "<" | "<=" | "<>" | "!=" | ">" | ">=" => Precedence::Cmp,
                     "+" | "-" => Precedence::PlusMinus,
                     "/" | "%" => Precedence::MultiplyDivide,
@@ -1340,7 +1347,7 @@ impl<'a> Parser<'a> {
             } else {
                 self.expected(
                     self.peek_pos(),
-                    "VIEW or MATERIALIZED VIEW after CREATE TEMPORARY",
+                    "TABLE, VIEW, or MATERIALIZED VIEW after CREATE TEMPORARY",
                     self.peek_token(),
                 )
             }
@@ -1365,6 +1372,8 @@ impl<'a> Parser<'a> {
             self.parse_create_source()
         } else if self.parse_keyword(SINK) {
             self.parse_create_sink()
+        } else if self.parse_keyword(TASK) {
+            self.parse_create_task()
         } else if self.parse_keyword(DEFAULT) {
             self.expect_keyword(INDEX)?;
             self.prev_token();
@@ -1381,7 +1390,7 @@ impl<'a> Parser<'a> {
         } else {
             self.expected(
                 self.peek_pos(),
-                "DATABASE, INDEX, ROLE, SCHEMA, SINK, SOURCE, TYPE, USER, or [MATERIALIZED] VIEW after CREATE",
+                "DATABASE, INDEX, ROLE, SCHEMA, SINK, SOURCE, TASK, TYPE, USER, or [MATERIALIZED] VIEW after CREATE",
                 self.peek_token(),
             )
         }
@@ -1420,7 +1429,20 @@ impl<'a> Parser<'a> {
             }
         } else if self.parse_keyword(REGEX) {
             let regex = self.parse_literal_string()?;
-            Format::Regex(regex)
+            let types = if self.parse_keyword(TYPES) {
+                self.expect_token(&Token::LParen)?;
+                let types = self.parse_comma_separated(Parser::parse_regex_column_type)?;
+                self.expect_token(&Token::RParen)?;
+                types
+            } else {
+                vec![]
+            };
+            let strict = self.parse_one_of_keywords(&[STRICT, LENIENT]) == Some(STRICT);
+            Format::Regex {
+                regex,
+                types,
+                strict,
+            }
         } else if self.parse_keyword(CSV) {
             self.expect_keyword(WITH)?;
             let (header_row, n_cols) = if self.parse_keyword(HEADER) || self.parse_keyword(HEADERS)
@@ -1450,7 +1472,16 @@ impl<'a> Parser<'a> {
         } else if self.parse_keyword(TEXT) {
             Format::Text
         } else if self.parse_keyword(BYTES) {
-            Format::Bytes
+            let framing = if self.parse_keywords(&[DELIMITED, BY]) {
+                Some(BytesFraming::Delimited(self.parse_literal_string()?))
+            } else if self.parse_keywords(&[LENGTH, PREFIXED, BY]) {
+                Some(BytesFraming::LengthPrefixed(self.parse_literal_uint()?))
+            } else if self.parse_keywords(&[FIXED, WIDTH]) {
+                Some(BytesFraming::FixedWidth(self.parse_literal_uint()?))
+            } else {
+                None
+            };
+            Format::Bytes { framing }
         } else {
             return self.expected(
                 self.peek_pos(),
@@ -1520,6 +1551,12 @@ impl<'a> Parser<'a> {
         Ok(avro_schema)
     }
 
+    fn parse_regex_column_type(&mut self) -> Result<RegexColumnType<Raw>, ParserError> {
+        let name = self.parse_identifier()?;
+        let data_type = self.parse_data_type()?;
+        Ok(RegexColumnType { name, data_type })
+    }
+
     fn parse_schema(&mut self) -> Result<Schema, ParserError> {
         self.expect_keyword(SCHEMA)?;
         let schema = if self.parse_keyword(FILE) {
@@ -1532,7 +1569,22 @@ impl<'a> Parser<'a> {
 
     fn parse_envelope(&mut self) -> Result<Envelope<Raw>, ParserError> {
         let envelope = if self.parse_keyword(NONE) {
-            Envelope::None
+            if self.parse_keyword(DEDUPLICATE) {
+                self.expect_keyword(BY)?;
+                let key = self.parse_parenthesized_column_list(Mandatory)?;
+                let keep = if self.parse_keyword(KEEP) {
+                    match self.expect_one_of_keywords(&[FIRST, LAST])? {
+                        FIRST => DeduplicateKeep::First,
+                        LAST => DeduplicateKeep::Last,
+                        _ => unreachable!(),
+                    }
+                } else {
+                    DeduplicateKeep::First
+                };
+                Envelope::Deduplicate { key, keep }
+            } else {
+                Envelope::None
+            }
         } else if self.parse_keyword(DEBEZIUM) {
             Envelope::Debezium
         } else if self.parse_keyword(UPSERT) {
@@ -1559,8 +1611,18 @@ impl<'a> Parser<'a> {
             Compression::None
         } else if self.parse_keyword(GZIP) {
             Compression::Gzip
+        } else if self.parse_keyword(ZSTD) {
+            Compression::Zstd
+        } else if self.parse_keyword(BZIP2) {
+            Compression::Bzip2
+        } else if self.parse_keyword(AUTO) {
+            Compression::Auto
         } else {
-            return self.expected(self.peek_pos(), "NONE or GZIP", self.peek_token());
+            return self.expected(
+                self.peek_pos(),
+                "NONE, GZIP, ZSTD, BZIP2, or AUTO",
+                self.peek_token(),
+            );
         };
         Ok(compression)
     }
@@ -1570,7 +1632,7 @@ impl<'a> Parser<'a> {
         self.expect_keyword(SOURCE)?;
         let if_not_exists = self.parse_if_not_exists()?;
         let name = self.parse_object_name()?;
-        let col_names = self.parse_parenthesized_column_list(Optional)?;
+        let (col_names, key_constraints) = self.parse_columns_and_key_constraints()?;
         self.expect_keyword(FROM)?;
         let connector = self.parse_connector()?;
         let with_options = self.parse_opt_with_sql_options()?;
@@ -1588,6 +1650,7 @@ impl<'a> Parser<'a> {
         Ok(Statement::CreateSource(CreateSourceStatement {
             name,
             col_names,
+            key_constraints,
             connector,
             with_options,
             format,
@@ -1647,6 +1710,21 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    fn parse_create_task(&mut self) -> Result<Statement<Raw>, ParserError> {
+        let if_not_exists = self.parse_if_not_exists()?;
+        let name = self.parse_object_name()?;
+        self.expect_keyword(SCHEDULE)?;
+        let schedule = self.parse_literal_string()?;
+        self.expect_keywords(&[AS, INSERT])?;
+        let insert = self.parse_insert_statement()?;
+        Ok(Statement::CreateTask(CreateTaskStatement {
+            name,
+            schedule,
+            insert,
+            if_not_exists,
+        }))
+    }
+
     fn parse_connector(&mut self) -> Result<Connector<Raw>, ParserError> {
         match self.expect_one_of_keywords(&[FILE, KAFKA, KINESIS, AVRO, S3, POSTGRES])? {
             POSTGRES => {
@@ -1712,6 +1790,7 @@ impl<'a> Parser<'a> {
                 // FROM S3 OBJECTS FROM
                 // (SCAN BUCKET '<bucket>' | SQS NOTIFICATIONS '<channel>')+
                 // MATCHING '<pattern>'
+                // COMPRESSION (NONE | GZIP | ZSTD | BZIP2 | AUTO)
                 self.expect_keywords(&[OBJECTS, FROM])?;
                 let mut key_sources = Vec::new();
                 while let Some(keyword) = self.parse_one_of_keywords(&[SCAN, SQS]) {
@@ -1737,9 +1816,15 @@ impl<'a> Parser<'a> {
                 } else {
                     None
                 };
+                let compression = if self.parse_keyword(COMPRESSION) {
+                    self.parse_compression()?
+                } else {
+                    Compression::None
+                };
                 Ok(Connector::S3 {
                     key_sources,
                     pattern,
+                    compression,
                 })
             }
             _ => unreachable!(),
@@ -1763,7 +1848,7 @@ impl<'a> Parser<'a> {
         // Many dialects support `OR REPLACE` | `OR ALTER` right after `CREATE`, but we don't (yet).
         // ANSI SQL and Postgres support RECURSIVE here, but we don't support it either.
         let name = self.parse_object_name()?;
-        let columns = self.parse_parenthesized_column_list(Optional)?;
+        let (columns, key_constraints) = self.parse_columns_and_key_constraints()?;
         let with_options = self.parse_opt_with_sql_options()?;
         self.expect_keyword(AS)?;
         let query = self.parse_query()?;
@@ -1771,6 +1856,7 @@ impl<'a> Parser<'a> {
         Ok(Statement::CreateView(CreateViewStatement {
             name,
             columns,
+            key_constraints,
             query,
             temporary,
             materialized,
@@ -1816,11 +1902,18 @@ impl<'a> Parser<'a> {
 
         let with_options = self.parse_opt_with_options()?;
 
+        let predicate = if self.parse_keyword(WHERE) {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
         Ok(Statement::CreateIndex(CreateIndexStatement {
             name,
             on_name,
             key_parts,
             with_options,
+            predicate,
             if_not_exists,
         }))
     }
@@ -1911,7 +2004,7 @@ impl<'a> Parser<'a> {
 
     fn parse_drop(&mut self) -> Result<Statement<Raw>, ParserError> {
         let object_type = match self.parse_one_of_keywords(&[
-            DATABASE, INDEX, ROLE, SCHEMA, SINK, SOURCE, TABLE, TYPE, USER, VIEW,
+            DATABASE, INDEX, ROLE, SCHEMA, SINK, SOURCE, TABLE, TASK, TYPE, USER, VIEW,
         ]) {
             Some(DATABASE) => {
                 return Ok(Statement::DropDatabase(DropDatabaseStatement {
@@ -1925,11 +2018,12 @@ impl<'a> Parser<'a> {
             Some(SINK) => ObjectType::Sink,
             Some(SOURCE) => ObjectType::Source,
             Some(TABLE) => ObjectType::Table,
+            Some(TASK) => ObjectType::Task,
             Some(TYPE) => ObjectType::Type,
             Some(VIEW) => ObjectType::View,
             _ => return self.expected(
                 self.peek_pos(),
-                "DATABASE, INDEX, ROLE, SCHEMA, SINK, SOURCE, TABLE, TYPE, USER, VIEW after DROP",
+                "DATABASE, INDEX, ROLE, SCHEMA, SINK, SOURCE, TABLE, TASK, TYPE, USER, VIEW after DROP",
                 self.peek_token(),
             ),
         };
@@ -2198,6 +2292,12 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_alter(&mut self) -> Result<Statement<Raw>, ParserError> {
+        if self.parse_keyword(SYSTEM) {
+            return self.parse_alter_system();
+        } else if self.parse_keyword(ROLE) {
+            return self.parse_alter_role();
+        }
+
         let object_type = match self.expect_one_of_keywords(&[INDEX, SINK, SOURCE, VIEW, TABLE])? {
             INDEX => ObjectType::Index,
             SINK => ObjectType::Sink,
@@ -2210,8 +2310,29 @@ impl<'a> Parser<'a> {
         let if_exists = self.parse_if_exists()?;
         let name = self.parse_object_name()?;
 
-        // We support `ALTER INDEX ... {RESET, SET} and `ALTER <object type> RENAME
-        if object_type == ObjectType::Index {
+        // We support `ALTER INDEX ... {RESET, SET}`, `ALTER SOURCE ... SET` and
+        // `ALTER <object type> RENAME`
+        if object_type == ObjectType::Source && self.parse_keyword(SET) {
+            if self.parse_keyword(SCHEMA) {
+                self.expect_keyword(FROM)?;
+                let schema = if self.parse_keyword(FILE) {
+                    Schema::File(self.parse_literal_string()?.into())
+                } else {
+                    Schema::Inline(self.parse_literal_string()?)
+                };
+                return Ok(Statement::AlterSourceSchema(AlterSourceSchemaStatement {
+                    source_name: name,
+                    if_exists,
+                    schema,
+                }));
+            }
+            let options = self.parse_options()?;
+            return Ok(Statement::AlterSourceOptions(AlterSourceOptionsStatement {
+                source_name: name,
+                if_exists,
+                options,
+            }));
+        } else if object_type == ObjectType::Index {
             let options = match self.parse_one_of_keywords(&[RESET, SET]) {
                 Some(RESET) => {
                     self.expect_token(&Token::LParen)?;
@@ -2349,6 +2470,27 @@ impl<'a> Parser<'a> {
         Ok(Expr::List(self.parse_sequence(Self::parse_list)?))
     }
 
+    fn parse_map_expr(&mut self) -> Result<Expr<Raw>, ParserError> {
+        self.expect_token(&Token::LBracket)?;
+        let mut entries = vec![];
+        loop {
+            if let Some(Token::RBracket) = self.peek_token() {
+                break;
+            }
+            let key = self.parse_expr()?;
+            if !self.consume_token(&Token::Op("=>".to_owned())) {
+                return self.expected(self.peek_pos(), "=>", self.peek_token());
+            }
+            let value = self.parse_expr()?;
+            entries.push(MapEntry { key, value });
+            if !self.consume_token(&Token::Comma) {
+                break;
+            }
+        }
+        self.expect_token(&Token::RBracket)?;
+        Ok(Expr::Map(entries))
+    }
+
     fn parse_sequence<F>(&mut self, mut f: F) -> Result<Vec<Expr<Raw>>, ParserError>
     where
         F: FnMut(&mut Self) -> Result<Expr<Raw>, ParserError>,
@@ -2691,6 +2833,54 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parses the optional column list of a `CREATE SOURCE` or `CREATE VIEW`
+    /// statement, which may end with any number of key constraints.
+    fn parse_columns_and_key_constraints(
+        &mut self,
+    ) -> Result<(Vec<Ident>, Vec<KeyConstraint>), ParserError> {
+        let mut columns = vec![];
+        let mut key_constraints = vec![];
+        if !self.consume_token(&Token::LParen) {
+            return Ok((columns, key_constraints));
+        }
+        loop {
+            if let Some(key_constraint) = self.parse_optional_key_constraint()? {
+                key_constraints.push(key_constraint);
+            } else if key_constraints.is_empty() {
+                columns.push(self.parse_identifier()?);
+            } else {
+                // Key constraints must come after all columns.
+                return self.expected(self.peek_pos(), "PRIMARY or FOREIGN", self.peek_token());
+            }
+            if self.consume_token(&Token::RParen) {
+                break;
+            }
+            self.expect_token(&Token::Comma)?;
+        }
+        Ok((columns, key_constraints))
+    }
+
+    fn parse_optional_key_constraint(&mut self) -> Result<Option<KeyConstraint>, ParserError> {
+        if self.parse_keywords(&[PRIMARY, KEY]) {
+            let columns = self.parse_parenthesized_column_list(Mandatory)?;
+            self.expect_keywords(&[NOT, ENFORCED])?;
+            Ok(Some(KeyConstraint::PrimaryKeyNotEnforced { columns }))
+        } else if self.parse_keywords(&[FOREIGN, KEY]) {
+            let columns = self.parse_parenthesized_column_list(Mandatory)?;
+            self.expect_keyword(REFERENCES)?;
+            let foreign_table = self.parse_object_name()?;
+            let referred_columns = self.parse_parenthesized_column_list(Mandatory)?;
+            self.expect_keywords(&[NOT, ENFORCED])?;
+            Ok(Some(KeyConstraint::ForeignKeyNotEnforced {
+                columns,
+                foreign_table,
+                referred_columns,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn parse_optional_precision(&mut self) -> Result<Option<u64>, ParserError> {
         if self.consume_token(&Token::LParen) {
             let n = self.parse_literal_uint()?;
@@ -3034,6 +3224,65 @@ impl<'a> Parser<'a> {
         })
     }
 
+    fn parse_alter_system(&mut self) -> Result<Statement<Raw>, ParserError> {
+        match self.expect_one_of_keywords(&[SET, RESET])? {
+            SET => {
+                let variable = self.parse_identifier()?;
+                if !self.consume_token(&Token::Eq) {
+                    self.expect_keyword(TO)?;
+                }
+                let value = self.parse_set_variable_value()?;
+                Ok(Statement::AlterSystemSet(AlterSystemSetStatement {
+                    variable,
+                    value,
+                }))
+            }
+            RESET => {
+                let variable = self.parse_identifier()?;
+                Ok(Statement::AlterSystemReset(AlterSystemResetStatement {
+                    variable,
+                }))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn parse_alter_role(&mut self) -> Result<Statement<Raw>, ParserError> {
+        let name = self.parse_identifier()?;
+        match self.expect_one_of_keywords(&[SET, RESET])? {
+            SET => {
+                let variable = self.parse_identifier()?;
+                if !self.consume_token(&Token::Eq) {
+                    self.expect_keyword(TO)?;
+                }
+                let value = self.parse_set_variable_value()?;
+                Ok(Statement::AlterRoleSet(AlterRoleSetStatement {
+                    name,
+                    variable,
+                    value,
+                }))
+            }
+            RESET => {
+                let variable = self.parse_identifier()?;
+                Ok(Statement::AlterRoleReset(AlterRoleResetStatement {
+                    name,
+                    variable,
+                }))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn parse_set_variable_value(&mut self) -> Result<SetVariableValue, ParserError> {
+        let token = self.peek_token();
+        Ok(match (self.parse_value(), token) {
+            (Ok(value), _) => SetVariableValue::Literal(value),
+            (Err(_), Some(Token::Keyword(kw))) => SetVariableValue::Ident(kw.into_ident()),
+            (Err(_), Some(Token::Ident(id))) => SetVariableValue::Ident(Ident::new(id)),
+            (Err(_), other) => self.expected(self.peek_pos(), "variable value", other)?,
+        })
+    }
+
     fn parse_set(&mut self) -> Result<Statement<Raw>, ParserError> {
         let modifier = self.parse_one_of_keywords(&[SESSION, LOCAL]);
         let mut variable = self.parse_identifier()?;
@@ -3044,13 +3293,7 @@ impl<'a> Parser<'a> {
             normal = true;
         }
         if normal {
-            let token = self.peek_token();
-            let value = match (self.parse_value(), token) {
-                (Ok(value), _) => SetVariableValue::Literal(value),
-                (Err(_), Some(Token::Keyword(kw))) => SetVariableValue::Ident(kw.into_ident()),
-                (Err(_), Some(Token::Ident(id))) => SetVariableValue::Ident(Ident::new(id)),
-                (Err(_), other) => self.expected(self.peek_pos(), "variable value", other)?,
-            };
+            let value = self.parse_set_variable_value()?;
             Ok(Statement::SetVariable(SetVariableStatement {
                 local: modifier == Some(LOCAL),
                 variable,
@@ -3065,6 +3308,13 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_reset(&mut self) -> Result<Statement<Raw>, ParserError> {
+        let variable = self.parse_identifier()?;
+        Ok(Statement::ResetVariable(ResetVariableStatement {
+            variable,
+        }))
+    }
+
     fn parse_show(&mut self) -> Result<Statement<Raw>, ParserError> {
         if self.parse_keyword(DATABASES) {
             return Ok(Statement::ShowDatabases(ShowDatabasesStatement {
@@ -3110,7 +3360,7 @@ impl<'a> Parser<'a> {
         if self.parse_one_of_keywords(&[COLUMNS, FIELDS]).is_some() {
             self.parse_show_columns(extended, full)
         } else if let Some(object_type) = self.parse_one_of_keywords(&[
-            OBJECTS, ROLES, SCHEMAS, SINKS, SOURCES, TABLES, TYPES, USERS, VIEWS,
+            OBJECTS, ROLES, SCHEMAS, SINKS, SOURCES, TABLES, TASKS, TYPES, USERS, VIEWS,
         ]) {
             Ok(Statement::ShowObjects(ShowObjectsStatement {
                 object_type: match object_type {
@@ -3120,6 +3370,7 @@ impl<'a> Parser<'a> {
                     SINKS => ObjectType::Sink,
                     SOURCES => ObjectType::Source,
                     TABLES => ObjectType::Table,
+                    TASKS => ObjectType::Task,
                     TYPES => ObjectType::Type,
                     VIEWS => ObjectType::View,
                     val => panic!(
@@ -3433,6 +3684,10 @@ impl<'a> Parser<'a> {
 
     /// Parse an INSERT statement
     fn parse_insert(&mut self) -> Result<Statement<Raw>, ParserError> {
+        Ok(Statement::Insert(self.parse_insert_statement()?))
+    }
+
+    fn parse_insert_statement(&mut self) -> Result<InsertStatement<Raw>, ParserError> {
         self.expect_keyword(INTO)?;
         let table_name = self.parse_object_name()?;
         let columns = self.parse_parenthesized_column_list(Optional)?;
@@ -3441,11 +3696,11 @@ impl<'a> Parser<'a> {
         } else {
             InsertSource::Query(self.parse_query()?)
         };
-        Ok(Statement::Insert(InsertStatement {
+        Ok(InsertStatement {
             table_name,
             columns,
             source,
-        }))
+        })
     }
 
     fn parse_update(&mut self) -> Result<Statement<Raw>, ParserError> {
@@ -3477,11 +3732,18 @@ impl<'a> Parser<'a> {
             self.expect_token(&Token::RParen)?;
             Ok(FunctionArgs::Star)
         } else if self.consume_token(&Token::RParen) {
-            Ok(FunctionArgs::Args(vec![]))
+            Ok(FunctionArgs::args(vec![]))
         } else {
             let args = self.parse_comma_separated(Parser::parse_expr)?;
+            // Aggregate functions may order their inputs, as in
+            // `array_agg(x ORDER BY y)`.
+            let order_by = if self.parse_keywords(&[ORDER, BY]) {
+                self.parse_comma_separated(Parser::parse_order_by_expr)?
+            } else {
+                vec![]
+            };
             self.expect_token(&Token::RParen)?;
-            Ok(FunctionArgs::Args(args))
+            Ok(FunctionArgs::Args { args, order_by })
         }
     }
 
@@ -3612,10 +3874,31 @@ impl<'a> Parser<'a> {
         let name = self.parse_object_name()?;
         let options = self.parse_opt_with_options()?;
         let as_of = self.parse_optional_as_of()?;
+        let envelope = if self.parse_keyword(ENVELOPE) {
+            match self.expect_one_of_keywords(&[UPSERT, DEBEZIUM])? {
+                UPSERT => {
+                    self.expect_keyword(KEY)?;
+                    let key = self.parse_parenthesized_column_list(Mandatory)?;
+                    Some(TailEnvelope::Upsert { key })
+                }
+                DEBEZIUM => {
+                    let key = if self.parse_keyword(KEY) {
+                        Some(self.parse_parenthesized_column_list(Mandatory)?)
+                    } else {
+                        None
+                    };
+                    Some(TailEnvelope::Debezium { key })
+                }
+                _ => unreachable!(),
+            }
+        } else {
+            None
+        };
         Ok(Statement::Tail(TailStatement {
             name,
             options,
             as_of,
+            envelope,
         }))
     }
 
@@ -3627,35 +3910,64 @@ impl<'a> Parser<'a> {
             typed: self.parse_keyword(TYPED),
         };
 
-        // (RAW | DECORRELATED | OPTIMIZED)? PLAN
-        let stage = match self.parse_one_of_keywords(&[RAW, DECORRELATED, OPTIMIZED, PLAN]) {
+        // ((RAW | DECORRELATED | OPTIMIZED)? PLAN | TIMESTAMP | INDEX ADVICE | DATAFLOW) FOR
+        let stage = match self.parse_one_of_keywords(&[
+            RAW,
+            DECORRELATED,
+            OPTIMIZED,
+            PLAN,
+            TIMESTAMP,
+            INDEX,
+            DATAFLOW,
+        ]) {
             Some(RAW) => {
                 self.expect_keywords(&[PLAN, FOR])?;
-                ExplainStage::RawPlan
+                Some(ExplainStage::RawPlan)
             }
             Some(DECORRELATED) => {
                 self.expect_keywords(&[PLAN, FOR])?;
-                ExplainStage::DecorrelatedPlan
+                Some(ExplainStage::DecorrelatedPlan)
             }
             Some(OPTIMIZED) => {
                 self.expect_keywords(&[PLAN, FOR])?;
-                ExplainStage::OptimizedPlan
+                Some(ExplainStage::OptimizedPlan)
             }
             Some(PLAN) => {
                 self.expect_keyword(FOR)?;
-                ExplainStage::OptimizedPlan
+                Some(ExplainStage::OptimizedPlan)
+            }
+            Some(TIMESTAMP) => {
+                self.expect_keyword(FOR)?;
+                Some(ExplainStage::Timestamp)
             }
-            None => ExplainStage::OptimizedPlan,
+            Some(INDEX) => {
+                self.expect_keywords(&[ADVICE, FOR])?;
+                Some(ExplainStage::IndexAdvice)
+            }
+            Some(DATAFLOW) => {
+                self.expect_keyword(FOR)?;
+                Some(ExplainStage::Dataflow)
+            }
+            None => None,
             _ => unreachable!(),
         };
 
-        // VIEW view_name | query
-        let explainee = if self.parse_keyword(VIEW) {
-            Explainee::View(self.parse_object_name()?)
-        } else {
-            Explainee::Query(self.parse_query()?)
+        // VIEW view_name | SOURCE source_name | SINK sink_name | query
+        let explainee = match self.parse_one_of_keywords(&[VIEW, SOURCE, SINK]) {
+            Some(VIEW) => Explainee::View(self.parse_object_name()?),
+            Some(SOURCE) => Explainee::Source(self.parse_object_name()?),
+            Some(SINK) => Explainee::Sink(self.parse_object_name()?),
+            None => Explainee::Query(self.parse_query()?),
+            _ => unreachable!(),
         };
 
+        // Sources and sinks are explained by their dataflow, everything else
+        // by its optimized plan.
+        let stage = stage.unwrap_or_else(|| match explainee {
+            Explainee::Source(_) | Explainee::Sink(_) => ExplainStage::Dataflow,
+            Explainee::View(_) | Explainee::Query(_) => ExplainStage::OptimizedPlan,
+        });
+
         Ok(Statement::Explain(ExplainStatement {
             stage,
             explainee,
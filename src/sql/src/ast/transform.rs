@@ -18,8 +18,8 @@ use crate::ast::visit::{self, Visit};
 use crate::ast::visit_mut::{self, VisitMut};
 use crate::ast::{
     AstInfo, CreateIndexStatement, CreateSinkStatement, CreateSourceStatement,
-    CreateTableStatement, CreateViewStatement, Expr, Ident, Query, Raw, Statement,
-    UnresolvedObjectName,
+    CreateTableStatement, CreateTaskStatement, CreateViewStatement, Expr, Ident, InsertSource,
+    KeyConstraint, Query, Raw, Statement, UnresolvedObjectName,
 };
 use crate::names::FullName;
 
@@ -82,16 +82,45 @@ pub fn create_stmt_rename_refs(
         Statement::CreateSink(CreateSinkStatement { from, .. }) => {
             maybe_update_object_name(from);
         }
-        Statement::CreateView(CreateViewStatement { query, .. }) => {
+        Statement::CreateView(CreateViewStatement {
+            query,
+            key_constraints,
+            ..
+        }) => {
+            rewrite_key_constraints(key_constraints, maybe_update_object_name);
             rewrite_query(from_name, to_item_name, query)?;
         }
-        Statement::CreateSource(_) | Statement::CreateTable(_) => {}
+        Statement::CreateTask(CreateTaskStatement { insert, .. }) => {
+            maybe_update_object_name(&mut insert.table_name);
+            if let InsertSource::Query(query) = &mut insert.source {
+                rewrite_query(from_name, to_item_name, query)?;
+            }
+        }
+        Statement::CreateSource(CreateSourceStatement {
+            key_constraints, ..
+        }) => {
+            rewrite_key_constraints(key_constraints, maybe_update_object_name);
+        }
+        Statement::CreateTable(_) => {}
         _ => unreachable!("Internal error: only catalog items need to update item refs"),
     }
 
     Ok(())
 }
 
+/// Rewrites the items referenced by foreign keys in `key_constraints` with
+/// `update_object_name`.
+fn rewrite_key_constraints<F>(key_constraints: &mut [KeyConstraint], update_object_name: F)
+where
+    F: Fn(&mut UnresolvedObjectName),
+{
+    for key_constraint in key_constraints {
+        if let KeyConstraint::ForeignKeyNotEnforced { foreign_table, .. } = key_constraint {
+            update_object_name(foreign_table);
+        }
+    }
+}
+
 /// Rewrites `query`'s references of `from` to `to` or errors if too ambiguous.
 fn rewrite_query(from: FullName, to: String, query: &mut Query<Raw>) -> Result<(), String> {
     let from_ident = Ident::new(from.item.clone());
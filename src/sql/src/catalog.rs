@@ -133,6 +133,14 @@ pub trait Catalog: fmt::Debug + ExprHumanizer {
 
     /// Returns the configuration of the catalog.
     fn config(&self) -> &CatalogConfig;
+
+    /// Reports whether the specified experimental feature is enabled for the
+    /// session that is issuing the query.
+    ///
+    /// Note that all features are enabled in experimental mode, regardless of
+    /// the value returned by this method. See
+    /// [`StatementContext::require_feature`](crate::plan::StatementContext::require_feature).
+    fn feature_enabled(&self, feature: Feature) -> bool;
 }
 
 /// Configuration associated with a catalog.
@@ -255,6 +263,8 @@ pub enum CatalogItemType {
     Type,
     /// A func.
     Func,
+    /// A task.
+    Task,
 }
 
 impl fmt::Display for CatalogItemType {
@@ -267,6 +277,7 @@ impl fmt::Display for CatalogItemType {
             CatalogItemType::Index => f.write_str("index"),
             CatalogItemType::Type => f.write_str("type"),
             CatalogItemType::Func => f.write_str("func"),
+            CatalogItemType::Task => f.write_str("task"),
         }
     }
 }
@@ -325,6 +336,42 @@ impl Error for CatalogError {}
 #[derive(Debug)]
 pub struct DummyCatalog;
 
+/// An experimental feature that can be enabled with a feature flag.
+///
+/// Unlike experimental mode, which enables every experimental feature for the
+/// lifetime of the catalog, feature flags are ordinary configuration
+/// parameters: they can be enabled per session with `SET`, or for every
+/// session with `ALTER SYSTEM SET` or `ALTER ROLE ... SET`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// The `ARRAY` constructor.
+    ArrayConstructor,
+    /// Functions and operators that inspect the dimensions of nested lists,
+    /// like `list_ndims`, `list_length_max`, and layered slicing.
+    MultidimensionalLists,
+    /// The `repeat` table function.
+    RepeatFunction,
+}
+
+impl Feature {
+    /// All features that can be enabled with a feature flag.
+    pub const ALL: &'static [Feature] = &[
+        Feature::ArrayConstructor,
+        Feature::MultidimensionalLists,
+        Feature::RepeatFunction,
+    ];
+
+    /// Returns the name of the configuration parameter that enables the
+    /// feature.
+    pub fn flag_name(&self) -> &'static str {
+        match self {
+            Feature::ArrayConstructor => "enable_array_constructor",
+            Feature::MultidimensionalLists => "enable_multidimensional_lists",
+            Feature::RepeatFunction => "enable_repeat_function",
+        }
+    }
+}
+
 const DUMMY_CONFIG: CatalogConfig = CatalogConfig {
     startup_time: SystemTime::UNIX_EPOCH,
     nonce: 0,
@@ -401,6 +448,10 @@ impl Catalog for DummyCatalog {
     fn config(&self) -> &CatalogConfig {
         &DUMMY_CONFIG
     }
+
+    fn feature_enabled(&self, _: Feature) -> bool {
+        false
+    }
 }
 
 impl ExprHumanizer for DummyCatalog {
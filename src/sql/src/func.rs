@@ -19,13 +19,15 @@ use std::rc::Rc;
 use anyhow::{bail, Context};
 use itertools::Itertools;
 use lazy_static::lazy_static;
+use uuid::Uuid;
 
 use ore::collections::CollectionExt;
 use pgrepr::oid;
+use repr::adt::char::{format_str_pad, format_str_trim};
 use repr::{ColumnName, Datum, RelationType, ScalarBaseType, ScalarType};
 use sql_parser::ast::{Expr, Raw, UnresolvedObjectName};
 
-use crate::catalog::CatalogItemType;
+use crate::catalog::{CatalogItemType, Feature};
 use crate::names::PartialName;
 use crate::plan::expr::{
     AggregateFunc, BinaryFunc, CoercibleScalarExpr, HirScalarExpr, NullaryFunc, TableFunc,
@@ -67,8 +69,10 @@ pub enum TypeCategory {
     Bool,
     DateTime,
     List,
+    NetworkAddress,
     Numeric,
     Pseudo,
+    Range,
     String,
     Timespan,
     UserDefined,
@@ -90,7 +94,13 @@ impl TypeCategory {
         match typ {
             ScalarType::Array(..) => Self::Array,
             ScalarType::Bool => Self::Bool,
-            ScalarType::Bytes | ScalarType::Jsonb | ScalarType::Uuid => Self::UserDefined,
+            ScalarType::Bytes
+            | ScalarType::Jsonb
+            | ScalarType::Uuid
+            | ScalarType::Geometry
+            | ScalarType::Geography
+            | ScalarType::TsVector
+            | ScalarType::TsQuery => Self::UserDefined,
             ScalarType::Date
             | ScalarType::Time
             | ScalarType::Timestamp
@@ -101,9 +111,13 @@ impl TypeCategory {
             | ScalarType::Int32
             | ScalarType::Int64
             | ScalarType::Oid => Self::Numeric,
+            ScalarType::Inet | ScalarType::Cidr => Self::NetworkAddress,
             ScalarType::Interval => Self::Timespan,
             ScalarType::List { .. } => Self::List,
-            ScalarType::String => Self::String,
+            ScalarType::Range(..) => Self::Range,
+            ScalarType::String | ScalarType::Char { .. } | ScalarType::VarChar { .. } => {
+                Self::String
+            }
             ScalarType::Record { .. } => Self::Pseudo,
             ScalarType::Map { .. } => Self::Pseudo,
         }
@@ -113,10 +127,12 @@ impl TypeCategory {
         match param {
             ParamType::Any
             | ParamType::ArrayAny
+            | ParamType::ArrayElementAny
             | ParamType::ListAny
             | ParamType::ListElementAny
             | ParamType::NonVecAny
             | ParamType::MapAny => Self::Pseudo,
+            ParamType::CharAny => Self::String,
             ParamType::DecimalAny => Self::Numeric,
             ParamType::Plain(t) => Self::from_type(t),
         }
@@ -131,9 +147,10 @@ impl TypeCategory {
     /// ```
     fn preferred_type(&self) -> Option<ScalarType> {
         match self {
-            Self::Array | Self::List | Self::Pseudo | Self::UserDefined => None,
+            Self::Array | Self::List | Self::Pseudo | Self::Range | Self::UserDefined => None,
             Self::Bool => Some(ScalarType::Bool),
             Self::DateTime => Some(ScalarType::TimestampTz),
+            Self::NetworkAddress => Some(ScalarType::Inet),
             Self::Numeric => Some(ScalarType::Float64),
             Self::String => Some(ScalarType::String),
             Self::Timespan => Some(ScalarType::Interval),
@@ -409,12 +426,15 @@ impl ParamList {
     ///
     /// Polymorphic type consistency constraints include:
     /// - All arguments passed to `ArrayAny` must be `ScalarType::Array`s with
-    ///   the same types of elements.
+    ///   the same types of elements. All arguments passed to `ArrayElementAny`
+    ///   must also be of these elements' type.
     /// - All arguments passed to `ListAny` must be `ScalarType::List`s with the
     ///   same types of elements. All arguments passed to `ListElementAny` must
     ///   also be of these elements' type.
     /// - All arguments passed to `MapAny` must be `ScalarType::Map`s with the
     ///   same type of value in each key, value pair.
+    /// - All arguments passed to `CharAny` must be `ScalarType::Char`s with the
+    ///   same length, which must be specified.
     ///
     /// Returns `Some` if the constraints were successfully resolved, or `None`
     /// otherwise.
@@ -501,6 +521,7 @@ impl ParamList {
         fn complex_base_eq(l: &ScalarType, r: &ScalarType) -> bool {
             match (l, r) {
                 (ScalarType::Array(l), ScalarType::Array(r))
+                | (ScalarType::Range(l), ScalarType::Range(r))
                 | (
                     ScalarType::List {
                         element_type: l, ..
@@ -578,6 +599,14 @@ impl ParamList {
                         return None;
                     }
                 }
+                (ParamType::ArrayElementAny, Some(t), None) => {
+                    constrained_type = Some(ScalarType::Array(Box::new(t.clone())));
+                }
+                (ParamType::ArrayElementAny, Some(t), Some(constrained_array)) => {
+                    if !complex_base_eq(t, constrained_array.unwrap_array_element_type()) {
+                        return None;
+                    }
+                }
                 (ParamType::ListAny, Some(typ), None) | (ParamType::MapAny, Some(typ), None) => {
                     constrained_type = Some(typ.clone());
                     custom_oid_lock = typ.is_custom_type();
@@ -658,6 +687,17 @@ impl ParamList {
                         return None;
                     }
                 }
+                (ParamType::CharAny, Some(t), None) => {
+                    if t.unwrap_char_length().is_none() {
+                        return None;
+                    }
+                    constrained_type = Some(t.clone());
+                }
+                (ParamType::CharAny, Some(t), Some(constrained)) => {
+                    if t != constrained {
+                        return None;
+                    }
+                }
                 // These checks don't need to be more exhaustive (e.g. failing
                 // if arguments passed to `ListAny` are not `ScalartType::List`)
                 // because we've already done general type checking in
@@ -721,9 +761,18 @@ pub enum ParamType {
     /// PostgreSQL definition, as parameters of type `DecimalAny` are considered
     /// to exactly match arguments of decimal type.
     DecimalAny,
+    /// A polymorphic pseudotype permitting a `ScalarType::Char` of any
+    /// length. Like `DecimalAny`, parameters of type `CharAny` are considered
+    /// to exactly match arguments of character type. For more details, see
+    /// [`resolve_polymorphic_types`].
+    CharAny,
     /// A polymorphic pseudotype permitting any array type.  For more details,
     /// see [`resolve_polymorphic_types`].
     ArrayAny,
+    /// A polymorphic pseudotype permitting all types, with more constraints
+    /// than `Any`, i.e. it is subject to polymorphic constraints. For more
+    /// details, see [`resolve_polymorphic_types`].
+    ArrayElementAny,
     /// A polymorphic pseudotype permitting a `ScalarType::List` of any element
     /// type.  For more details, see [`resolve_polymorphic_types`].
     ListAny,
@@ -752,9 +801,10 @@ impl ParamType {
         match self {
             ArrayAny => matches!(t, Array(..)),
             ListAny => matches!(t, List{..}),
-            Any | ListElementAny => true,
+            Any | ArrayElementAny | ListElementAny => true,
             NonVecAny => !t.is_vec(),
             MapAny => matches!(t, Map { .. }),
+            CharAny => matches!(t, Char { .. }),
             DecimalAny => {
                 typeconv::can_cast(ecx, CastContext::Implicit, t, &ScalarType::Decimal(0, 0))
             }
@@ -784,7 +834,8 @@ impl ParamType {
     fn is_polymorphic(&self) -> bool {
         use ParamType::*;
         match self {
-            ArrayAny | ListAny | MapAny | ListElementAny | NonVecAny => true,
+            ArrayAny | ArrayElementAny | ListAny | MapAny | ListElementAny | NonVecAny
+            | CharAny => true,
             Any | DecimalAny | Plain(_) => false,
         }
     }
@@ -804,7 +855,9 @@ impl ParamType {
             },
             ParamType::Any => postgres_types::Type::ANY.oid(),
             ParamType::ArrayAny => postgres_types::Type::ANYARRAY.oid(),
+            ParamType::ArrayElementAny => postgres_types::Type::ANYELEMENT.oid(),
             ParamType::DecimalAny => postgres_types::Type::NUMERIC.oid(),
+            ParamType::CharAny => postgres_types::Type::BPCHAR.oid(),
             ParamType::ListAny => pgrepr::LIST.oid(),
             ParamType::ListElementAny => postgres_types::Type::ANYELEMENT.oid(),
             ParamType::MapAny => pgrepr::MAP.oid(),
@@ -818,6 +871,7 @@ impl PartialEq<ScalarType> for ParamType {
         match self {
             ParamType::Plain(s) => s == other,
             ParamType::DecimalAny => matches!(other, ScalarType::Decimal(_, _)),
+            ParamType::CharAny => matches!(other, ScalarType::Char { .. }),
             // All other types are pseudotypes, which do not equal concrete
             // types.
             _ => false,
@@ -1157,6 +1211,10 @@ fn coerce_args_to_types(
             ParamType::ArrayAny | ParamType::ListAny | ParamType::MapAny => {
                 do_convert(arg, &get_constrained_ty())?
             }
+            ParamType::ArrayElementAny => {
+                let constrained_array = get_constrained_ty();
+                do_convert(arg, constrained_array.unwrap_array_element_type())?
+            }
             ParamType::ListElementAny => {
                 let constrained_list = get_constrained_ty();
                 do_convert(arg, &constrained_list.unwrap_list_element_type())?
@@ -1167,6 +1225,28 @@ fn coerce_args_to_types(
                 do_convert(arg, &ty)?
             }
 
+            // Arbitrary character parameter. Values of the constrained
+            // `character(n)` type are all padded to the same length, so they
+            // are passed through as is. String literals are padded to match
+            // rather than cast, so that a literal too long for the type
+            // compares unequal to its values instead of failing the query.
+            ParamType::CharAny => {
+                let ty = get_constrained_ty();
+                match arg {
+                    CoercibleScalarExpr::LiteralString(s) => {
+                        let s = format_str_trim(&s);
+                        match format_str_pad(s, ty.unwrap_char_length(), true) {
+                            Ok(s) => HirScalarExpr::literal(Datum::String(&s), ty),
+                            Err(_) => HirScalarExpr::literal(
+                                Datum::String(s),
+                                ScalarType::Char { length: None },
+                            ),
+                        }
+                    }
+                    arg => do_convert(arg, &ty)?,
+                }
+            }
+
             // Arbitrary decimal parameter. Converts to decimal but suppresses
             // decimal -> decimal casts, to avoid casting to the default scale
             // of 0.
@@ -1289,6 +1369,19 @@ lazy_static! {
             "array_lower" => Scalar {
                 params!(ArrayAny, Int64) => BinaryFunc::ArrayLower, 2091;
             },
+            "array_position" => Scalar {
+                params!(ArrayAny, ArrayElementAny) => Operation::variadic(|_ecx, exprs| Ok(HirScalarExpr::CallVariadic {
+                    func: VariadicFunc::ArrayPosition,
+                    exprs,
+                })), 3277;
+                params!(ArrayAny, ArrayElementAny, Int32) => Operation::variadic(|_ecx, exprs| Ok(HirScalarExpr::CallVariadic {
+                    func: VariadicFunc::ArrayPosition,
+                    exprs,
+                })), 3278;
+            },
+            "array_remove" => Scalar {
+                params!(ArrayAny, ArrayElementAny) => BinaryFunc::ArrayRemove, 3167;
+            },
             "array_to_string" => Scalar {
                 params!(ArrayAny, String) => Operation::variadic(array_to_string), 395;
                 params!(ArrayAny, String, String) => Operation::variadic(array_to_string), 384;
@@ -1416,14 +1509,58 @@ lazy_static! {
                 params!(Oid, Int32) => sql_op!(
                     "CASE
                         WHEN $1 IS NULL THEN NULL
-                        ELSE coalesce((SELECT concat(name, mz_internal.mz_render_typemod($1, $2)) FROM mz_catalog.mz_types WHERE oid = $1), '???')
+                        ELSE coalesce((
+                            SELECT concat(
+                                CASE
+                                    WHEN name = 'bool' THEN 'boolean'
+                                    WHEN name = 'bpchar' THEN 'character'
+                                    WHEN name = 'char' THEN '\"char\"'
+                                    WHEN name = 'float4' THEN 'real'
+                                    WHEN name = 'float8' THEN 'double precision'
+                                    WHEN name = 'int2' THEN 'smallint'
+                                    WHEN name = 'int4' THEN 'integer'
+                                    WHEN name = 'int8' THEN 'bigint'
+                                    WHEN name = 'time' THEN 'time without time zone'
+                                    WHEN name = 'timestamp' THEN 'timestamp without time zone'
+                                    WHEN name = 'timestamptz' THEN 'timestamp with time zone'
+                                    WHEN name = 'varchar' THEN 'character varying'
+                                    ELSE name
+                                END,
+                                mz_internal.mz_render_typemod(oid, $2),
+                                suffix
+                            )
+                            FROM (
+                                SELECT
+                                    coalesce(e.name, t.name) AS name,
+                                    coalesce(e.oid, t.oid) AS oid,
+                                    CASE WHEN e.oid IS NULL THEN '' ELSE '[]' END AS suffix
+                                FROM mz_catalog.mz_types AS t
+                                LEFT JOIN mz_catalog.mz_array_types AS a ON a.type_id = t.id
+                                LEFT JOIN mz_catalog.mz_types AS e ON e.id = a.element_id
+                                WHERE t.oid = $1
+                            ) AS typ
+                        ), '???')
                     END"
                 ), 1081;
             },
+            "gen_random_uuid" => Scalar {
+                params!() => Operation::nullary(gen_random_uuid), 3432;
+            },
             "hmac" => Scalar {
                 params!(String, String, String) => VariadicFunc::HmacString, 44156;
                 params!(Bytes, Bytes, String) => VariadicFunc::HmacBytes, 44157;
             },
+            "host" => Scalar {
+                params!(Inet) => UnaryFunc::InetHost, 699;
+            },
+            "int4range" => Scalar {
+                params!(Int32, Int32) => VariadicFunc::RangeCreate { elem_type: Int32 }, 3840;
+                params!(Int32, Int32, String) => VariadicFunc::RangeCreate { elem_type: Int32 }, 3841;
+            },
+            "isempty" => Scalar {
+                params!(Plain(Range(Box::new(Int32)))) => UnaryFunc::RangeIsEmpty, 3850;
+                params!(Plain(Range(Box::new(TimestampTz)))) => UnaryFunc::RangeIsEmpty, 3850;
+            },
             "jsonb_array_length" => Scalar {
                 params!(Jsonb) => UnaryFunc::JsonbArrayLength, 3207;
             },
@@ -1491,6 +1628,16 @@ lazy_static! {
             },
             "lower" => Scalar {
                 params!(String) => UnaryFunc::Lower, 870;
+                params!(Plain(Range(Box::new(Int32)))) => UnaryFunc::RangeLower, 3848;
+                params!(Plain(Range(Box::new(TimestampTz)))) => UnaryFunc::RangeLower, 3848;
+            },
+            "lower_inc" => Scalar {
+                params!(Plain(Range(Box::new(Int32)))) => UnaryFunc::RangeLowerInc, 3851;
+                params!(Plain(Range(Box::new(TimestampTz)))) => UnaryFunc::RangeLowerInc, 3851;
+            },
+            "lower_inf" => Scalar {
+                params!(Plain(Range(Box::new(Int32)))) => UnaryFunc::RangeLowerInf, 3853;
+                params!(Plain(Range(Box::new(TimestampTz)))) => UnaryFunc::RangeLowerInf, 3853;
             },
             "lpad" => Scalar {
                 params!(String, Int64) => VariadicFunc::PadLeading, 879;
@@ -1503,6 +1650,13 @@ lazy_static! {
             "make_timestamp" => Scalar {
                 params!(Int64, Int64, Int64, Int64, Int64, Float64) => VariadicFunc::MakeTimestamp, 3461;
             },
+            "masklen" => Scalar {
+                params!(Inet) => UnaryFunc::InetMasklen, 697;
+            },
+            "md5" => Scalar {
+                params!(String) => sql_op!("encode(digest($1, 'md5'), 'hex')"), 2311;
+                params!(Bytes) => sql_op!("encode(digest($1, 'md5'), 'hex')"), 2321;
+            },
             "mod" => Scalar {
                 params!(DecimalAny, DecimalAny) => Operation::nullary(|_ecx| catalog_name_only!("mod")), 1728;
                 params!(Int32, Int32) => Operation::nullary(|_ecx| catalog_name_only!("mod")), 941;
@@ -1588,6 +1742,18 @@ lazy_static! {
                 params!(String) => UnaryFunc::TrimTrailingWhitespace, 882;
                 params!(String, String) => BinaryFunc::TrimTrailing, 876;
             },
+            "sha224" => Scalar {
+                params!(Bytes) => sql_op!("digest($1, 'sha224')"), 3419;
+            },
+            "sha256" => Scalar {
+                params!(Bytes) => sql_op!("digest($1, 'sha256')"), 3420;
+            },
+            "sha384" => Scalar {
+                params!(Bytes) => sql_op!("digest($1, 'sha384')"), 3421;
+            },
+            "sha512" => Scalar {
+                params!(Bytes) => sql_op!("digest($1, 'sha512')"), 3422;
+            },
             "sin" => Scalar {
                 params!(Float64) => UnaryFunc::Sin, 1604;
             },
@@ -1597,6 +1763,49 @@ lazy_static! {
             "split_part" => Scalar {
                 params!(String, String, Int64) => VariadicFunc::SplitPart, 2088;
             },
+            "st_asbinary" => Scalar {
+                params!(Geometry) => UnaryFunc::GeometryAsBinary, oid::FUNC_ST_ASBINARY_OID;
+            },
+            "st_asgeojson" => Scalar {
+                params!(Geometry) => UnaryFunc::GeometryAsGeoJson, oid::FUNC_ST_ASGEOJSON_OID;
+            },
+            "st_astext" => Scalar {
+                params!(Geometry) => UnaryFunc::GeometryAsText, oid::FUNC_ST_ASTEXT_OID;
+            },
+            "st_contains" => Scalar {
+                params!(Geometry, Geometry) => BinaryFunc::GeometryContains, oid::FUNC_ST_CONTAINS_OID;
+            },
+            "st_distance" => Scalar {
+                params!(Geometry, Geometry) => BinaryFunc::GeometryDistance, oid::FUNC_ST_DISTANCE_GEOM_OID;
+                params!(Geography, Geography) => BinaryFunc::GeographyDistance, oid::FUNC_ST_DISTANCE_GEOG_OID;
+            },
+            "st_dwithin" => Scalar {
+                params!(Geometry, Geometry, Float64) => sql_op!("st_distance($1, $2) <= $3"), oid::FUNC_ST_DWITHIN_GEOM_OID;
+                params!(Geography, Geography, Float64) => sql_op!("st_distance($1, $2) <= $3"), oid::FUNC_ST_DWITHIN_GEOG_OID;
+            },
+            "st_geogfromtext" => Scalar {
+                params!(String) => sql_op!("st_geomfromtext($1)::geography"), oid::FUNC_ST_GEOGFROMTEXT_OID;
+            },
+            "st_geomfromgeojson" => Scalar {
+                params!(String) => UnaryFunc::GeometryFromGeoJson, oid::FUNC_ST_GEOMFROMGEOJSON_OID;
+            },
+            "st_geomfromtext" => Scalar {
+                params!(String) => UnaryFunc::GeometryFromText, oid::FUNC_ST_GEOMFROMTEXT_OID;
+                params!(String, Int32) => sql_op!("st_setsrid(st_geomfromtext($1), $2)"), oid::FUNC_ST_GEOMFROMTEXT_SRID_OID;
+            },
+            "st_geomfromwkb" => Scalar {
+                params!(Bytes) => UnaryFunc::GeometryFromWkb, oid::FUNC_ST_GEOMFROMWKB_OID;
+                params!(Bytes, Int32) => sql_op!("st_setsrid(st_geomfromwkb($1), $2)"), oid::FUNC_ST_GEOMFROMWKB_SRID_OID;
+            },
+            "st_makepoint" => Scalar {
+                params!(Float64, Float64) => BinaryFunc::MakePoint, oid::FUNC_ST_MAKEPOINT_OID;
+            },
+            "st_setsrid" => Scalar {
+                params!(Geometry, Int32) => BinaryFunc::GeometrySetSrid, oid::FUNC_ST_SETSRID_OID;
+            },
+            "st_srid" => Scalar {
+                params!(Geometry) => UnaryFunc::GeometrySrid, oid::FUNC_ST_SRID_OID;
+            },
             "stddev" => Scalar {
                 params!(DecimalAny) => Operation::nullary(|_ecx| catalog_name_only!("stddev")), 2159;
                 params!(Float32) => Operation::nullary(|_ecx| catalog_name_only!("stddev")), 2157;
@@ -1669,8 +1878,30 @@ lazy_static! {
             "to_timestamp" => Scalar {
                 params!(Float64) => UnaryFunc::ToTimestamp, 1158;
             },
+            "to_tsquery" => Scalar {
+                params!(String) => sql_op!("to_tsquery('simple', $1)"), 3750;
+                params!(String, String) => BinaryFunc::ToTsQuery, 3746;
+            },
+            "to_tsvector" => Scalar {
+                params!(String) => sql_op!("to_tsvector('simple', $1)"), 3749;
+                params!(String, String) => BinaryFunc::ToTsVector, 3745;
+            },
+            "tstzrange" => Scalar {
+                params!(TimestampTz, TimestampTz) => VariadicFunc::RangeCreate { elem_type: TimestampTz }, 3933;
+                params!(TimestampTz, TimestampTz, String) => VariadicFunc::RangeCreate { elem_type: TimestampTz }, 3934;
+            },
             "upper" => Scalar {
                 params!(String) => UnaryFunc::Upper, 871;
+                params!(Plain(Range(Box::new(Int32)))) => UnaryFunc::RangeUpper, 3849;
+                params!(Plain(Range(Box::new(TimestampTz)))) => UnaryFunc::RangeUpper, 3849;
+            },
+            "upper_inc" => Scalar {
+                params!(Plain(Range(Box::new(Int32)))) => UnaryFunc::RangeUpperInc, 3852;
+                params!(Plain(Range(Box::new(TimestampTz)))) => UnaryFunc::RangeUpperInc, 3852;
+            },
+            "upper_inf" => Scalar {
+                params!(Plain(Range(Box::new(Int32)))) => UnaryFunc::RangeUpperInf, 3854;
+                params!(Plain(Range(Box::new(TimestampTz)))) => UnaryFunc::RangeUpperInf, 3854;
             },
             "variance" => Scalar {
                 params!(DecimalAny) => Operation::nullary(|_ecx| catalog_name_only!("variance")), 2153;
@@ -1706,7 +1937,12 @@ lazy_static! {
 
             // Aggregates.
             "array_agg" => Aggregate {
-                params!(Any) => Operation::unary(|_ecx, _e| unsupported!("array_agg")), 4053;
+                params!(Any) => Operation::unary(|ecx, e| {
+                    if let ScalarType::Array(_) = ecx.scalar_type(&e) {
+                        unsupported!("array_agg on arrays")
+                    }
+                    Ok((e, AggregateFunc::ArrayAgg { order_by: vec![] }))
+                }), 2335;
             },
             "bool_and" => Aggregate {
                 params!(Any) => Operation::unary(|_ecx, _e| unsupported!("bool_and")), 2517;
@@ -1763,6 +1999,10 @@ lazy_static! {
                     Ok((e, AggregateFunc::JsonbAgg))
                 }), 3267;
             },
+            "range_agg" => Aggregate {
+                params!(Plain(Range(Box::new(Int32)))) => AggregateFunc::RangeAgg, oid::FUNC_RANGE_AGG_INT4_OID;
+                params!(Plain(Range(Box::new(TimestampTz)))) => AggregateFunc::RangeAgg, oid::FUNC_RANGE_AGG_TSTZ_OID;
+            },
             "string_agg" => Aggregate {
                 params!(Any, String) => Operation::binary(|_ecx, _lhs, _rhs| unsupported!("string_agg")), 3538;
             },
@@ -1911,7 +2151,7 @@ lazy_static! {
             },
             "list_ndims" => Scalar {
                 vec![ListAny] => Operation::unary(|ecx, e| {
-                    ecx.require_experimental_mode("list_ndims")?;
+                    ecx.require_feature(Feature::MultidimensionalLists, "list_ndims")?;
                     let d = ecx.scalar_type(&e).unwrap_list_n_dims();
                     Ok(HirScalarExpr::literal(Datum::Int32(d as i32), ScalarType::Int32))
                 }), oid::FUNC_LIST_NDIMS_OID;
@@ -1921,7 +2161,7 @@ lazy_static! {
             },
             "list_length_max" => Scalar {
                 vec![ListAny, Plain(Int64)] => Operation::binary(|ecx, lhs, rhs| {
-                    ecx.require_experimental_mode("list_length_max")?;
+                    ecx.require_feature(Feature::MultidimensionalLists, "list_length_max")?;
                     let max_dim = ecx.scalar_type(&lhs).unwrap_list_n_dims();
                     Ok(lhs.call_binary(rhs, BinaryFunc::ListLengthMax{ max_dim }))
                 }), oid::FUNC_LIST_LENGTH_MAX_OID;
@@ -1929,6 +2169,41 @@ lazy_static! {
             "list_prepend" => Scalar {
                 vec![ListElementAny, ListAny] => BinaryFunc::ElementListConcat, oid::FUNC_LIST_PREPEND_OID;
             },
+            "map_build" => Scalar {
+                vec![ListAny] => Operation::unary(|ecx, e| {
+                    let ty = ecx.scalar_type(&e);
+                    let value_type = match ty.unwrap_list_element_type() {
+                        ScalarType::Record { fields, .. }
+                            if fields.len() == 2 && fields[0].1.scalar_type == ScalarType::String =>
+                        {
+                            fields[1].1.scalar_type.clone()
+                        }
+                        _ => bail!(
+                            "map_build input must be a list of records with a text key \
+                            and a value, not type {}",
+                            ecx.humanize_scalar_type(&ty)
+                        ),
+                    };
+                    Ok(e.call_unary(UnaryFunc::MapBuildFromRecordList { value_type }))
+                }), oid::FUNC_MAP_BUILD_OID;
+            },
+            "map_keys" => Scalar {
+                vec![MapAny] => UnaryFunc::MapKeys, oid::FUNC_MAP_KEYS_OID;
+            },
+            "map_values" => Scalar {
+                vec![MapAny] => Operation::unary(|ecx, e| {
+                    let ty = ecx.scalar_type(&e);
+                    match ty.unwrap_map_value_type() {
+                        ScalarType::Array(_) | ScalarType::List { .. } | ScalarType::Map { .. } => {
+                            bail!(
+                                "map_values does not support type {}",
+                                ecx.humanize_scalar_type(&ty)
+                            )
+                        }
+                        _ => Ok(e.call_unary(UnaryFunc::MapValues)),
+                    }
+                }), oid::FUNC_MAP_VALUES_OID;
+            },
             "mz_cluster_id" => Scalar {
                 params!() => Operation::nullary(mz_cluster_id), oid::FUNC_MZ_CLUSTER_ID_OID;
             },
@@ -1963,7 +2238,7 @@ lazy_static! {
             },
             "repeat" => Table {
                 params!(Int64) => Operation::unary(move |ecx, n| {
-                    ecx.require_experimental_mode("repeat")?;
+                    ecx.require_feature(Feature::RepeatFunction, "repeat")?;
                     Ok(TableFuncPlan {
                         func: TableFunc::Repeat,
                         exprs: vec![n],
@@ -2034,6 +2309,11 @@ lazy_static! {
             },
             "mz_render_typemod" => Scalar {
                 params!(Oid, Int32) => BinaryFunc::MzRenderTypemod, oid::FUNC_MZ_RENDER_TYPEMOD_OID;
+            },
+            "object_json" => Scalar {
+                params!(Oid) => sql_op!(
+                    "(SELECT json FROM mz_internal.mz_object_json WHERE oid = $1)"
+                ), oid::FUNC_OBJECT_JSON_OID;
             }
         }
     };
@@ -2049,6 +2329,16 @@ fn plan_current_timestamp(ecx: &ExprContext, name: &str) -> Result<HirScalarExpr
     }
 }
 
+fn gen_random_uuid(ecx: &ExprContext) -> Result<HirScalarExpr, anyhow::Error> {
+    match ecx.qcx.lifetime {
+        QueryLifetime::OneShot => Ok(HirScalarExpr::literal(
+            Datum::Uuid(Uuid::new_v4()),
+            ScalarType::Uuid,
+        )),
+        QueryLifetime::Static => bail!("gen_random_uuid cannot be used in static queries"),
+    }
+}
+
 fn mz_cluster_id(ecx: &ExprContext) -> Result<HirScalarExpr, anyhow::Error> {
     Ok(HirScalarExpr::literal(
         Datum::from(ecx.catalog().config().cluster_id),
@@ -2318,6 +2608,11 @@ lazy_static! {
                           .call_binary(rhs, JsonbContainsJsonb))
                 }), oid::OP_CONTAINS_STRING_JSONB_OID;
                 params!(MapAny, MapAny) => MapContainsMap, oid::OP_CONTAINS_MAP_MAP_OID;
+                params!(ArrayAny, ArrayAny) => ArrayContainsArray, 2751;
+                params!(Plain(Range(Box::new(Int32))), Int32) => RangeContainsElem, 3889;
+                params!(Plain(Range(Box::new(TimestampTz))), TimestampTz) => RangeContainsElem, 3889;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => RangeContainsRange, 3890;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => RangeContainsRange, 3890;
             },
             "<@" => Scalar {
                 params!(Jsonb, Jsonb) =>  Operation::binary(|_ecx, lhs, rhs| {
@@ -2339,6 +2634,21 @@ lazy_static! {
                 params!(MapAny, MapAny) => Operation::binary(|_ecx, lhs, rhs| {
                     Ok(rhs.call_binary(lhs, MapContainsMap))
                 }), oid::OP_CONTAINED_MAP_MAP_OID;
+                params!(ArrayAny, ArrayAny) => Operation::binary(|_ecx, lhs, rhs| {
+                    Ok(rhs.call_binary(lhs, ArrayContainsArray))
+                }), 2752;
+                params!(Int32, Plain(Range(Box::new(Int32)))) => Operation::binary(|_ecx, lhs, rhs| {
+                    Ok(rhs.call_binary(lhs, RangeContainsElem))
+                }), 3891;
+                params!(TimestampTz, Plain(Range(Box::new(TimestampTz)))) => Operation::binary(|_ecx, lhs, rhs| {
+                    Ok(rhs.call_binary(lhs, RangeContainsElem))
+                }), 3891;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => Operation::binary(|_ecx, lhs, rhs| {
+                    Ok(rhs.call_binary(lhs, RangeContainsRange))
+                }), 3892;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => Operation::binary(|_ecx, lhs, rhs| {
+                    Ok(rhs.call_binary(lhs, RangeContainsRange))
+                }), 3892;
             },
             "?" => Scalar {
                 params!(Jsonb, String) => JsonbContainsString, 3247;
@@ -2350,6 +2660,36 @@ lazy_static! {
             "?|" => Scalar {
                 params!(MapAny, Plain(Array(Box::new(String)))) => MapContainsAnyKeys, oid::OP_CONTAINS_ANY_KEYS_MAP_OID;
             },
+            // RANGE
+            "&&" => Scalar {
+                params!(ArrayAny, ArrayAny) => ArrayOverlaps, 2750;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => RangeOverlaps, 3888;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => RangeOverlaps, 3888;
+            },
+            // INET
+            "<<" => Scalar {
+                params!(Inet, Inet) => InetContainedBy, 931;
+            },
+            "<<=" => Scalar {
+                params!(Inet, Inet) => InetContainedByOrEquals, 932;
+            },
+            ">>" => Scalar {
+                params!(Inet, Inet) => InetContains, 933;
+            },
+            ">>=" => Scalar {
+                params!(Inet, Inet) => InetContainsOrEquals, 934;
+            },
+            // FULL-TEXT SEARCH
+            "@@" => Scalar {
+                params!(TsVector, TsQuery) => TsMatch, 3636;
+                params!(TsQuery, TsVector) => Operation::binary(|_ecx, lhs, rhs| {
+                    Ok(rhs.call_binary(lhs, TsMatch))
+                }), 3637;
+                params!(String, TsQuery) => Operation::binary(|_ecx, lhs, rhs| {
+                    let config = HirScalarExpr::literal(Datum::String("simple"), ScalarType::String);
+                    Ok(config.call_binary(lhs, ToTsVector).call_binary(rhs, TsMatch))
+                }), 3763;
+            },
             // COMPARISON OPS
             // n.b. Decimal impls are separated from other types because they
             // require a function pointer, which you cannot dynamically generate.
@@ -2360,6 +2700,7 @@ lazy_static! {
                         Ok(lexpr.call_binary(rexpr, BinaryFunc::Lt))
                     })
                 }, 1754;
+                params!(CharAny, CharAny) => BinaryFunc::Lt, 1058;
                 params!(Bool, Bool) => BinaryFunc::Lt, 58;
                 params!(Int32, Int32) => BinaryFunc::Lt, 97;
                 params!(Int64, Int64) => BinaryFunc::Lt, 412;
@@ -2371,10 +2712,13 @@ lazy_static! {
                 params!(Timestamp, Timestamp) => BinaryFunc::Lt, 2062;
                 params!(TimestampTz, TimestampTz) => BinaryFunc::Lt, 1322;
                 params!(Uuid, Uuid) => BinaryFunc::Lt, 2974;
+                params!(Inet, Inet) => BinaryFunc::Lt, 1203;
                 params!(Interval, Interval) => BinaryFunc::Lt, 1332;
                 params!(Bytes, Bytes) => BinaryFunc::Lt, 1957;
                 params!(String, String) => BinaryFunc::Lt, 664;
                 params!(Jsonb, Jsonb) => BinaryFunc::Lt, 3242;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => BinaryFunc::Lt, 3884;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => BinaryFunc::Lt, 3884;
             },
             "<=" => Scalar {
                 params!(DecimalAny, DecimalAny) => {
@@ -2383,6 +2727,7 @@ lazy_static! {
                         Ok(lexpr.call_binary(rexpr, BinaryFunc::Lte))
                     })
                 }, 1755;
+                params!(CharAny, CharAny) => BinaryFunc::Lte, 1059;
                 params!(Bool, Bool) => BinaryFunc::Lte, 1694;
                 params!(Int32, Int32) => BinaryFunc::Lte, 523;
                 params!(Int64, Int64) => BinaryFunc::Lte, 414;
@@ -2394,10 +2739,13 @@ lazy_static! {
                 params!(Timestamp, Timestamp) => BinaryFunc::Lte, 2063;
                 params!(TimestampTz, TimestampTz) => BinaryFunc::Lte, 1323;
                 params!(Uuid, Uuid) => BinaryFunc::Lte, 2976;
+                params!(Inet, Inet) => BinaryFunc::Lte, 1204;
                 params!(Interval, Interval) => BinaryFunc::Lte, 1333;
                 params!(Bytes, Bytes) => BinaryFunc::Lte, 1958;
                 params!(String, String) => BinaryFunc::Lte, 665;
                 params!(Jsonb, Jsonb) => BinaryFunc::Lte, 3244;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => BinaryFunc::Lte, 3885;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => BinaryFunc::Lte, 3885;
             },
             ">" => Scalar {
                 params!(DecimalAny, DecimalAny) => {
@@ -2406,6 +2754,7 @@ lazy_static! {
                         Ok(lexpr.call_binary(rexpr, BinaryFunc::Gt))
                     })
                 }, 1756;
+                params!(CharAny, CharAny) => BinaryFunc::Gt, 1060;
                 params!(Bool, Bool) => BinaryFunc::Gt, 59;
                 params!(Int32, Int32) => BinaryFunc::Gt, 521;
                 params!(Int64, Int64) => BinaryFunc::Gt, 413;
@@ -2417,10 +2766,13 @@ lazy_static! {
                 params!(Timestamp, Timestamp) => BinaryFunc::Gt, 2064;
                 params!(TimestampTz, TimestampTz) => BinaryFunc::Gt, 1324;
                 params!(Uuid, Uuid) => BinaryFunc::Gt, 2975;
+                params!(Inet, Inet) => BinaryFunc::Gt, 1205;
                 params!(Interval, Interval) => BinaryFunc::Gt, 1334;
                 params!(Bytes, Bytes) => BinaryFunc::Gt, 1959;
                 params!(String, String) => BinaryFunc::Gt, 666;
                 params!(Jsonb, Jsonb) => BinaryFunc::Gt, 3243;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => BinaryFunc::Gt, 3887;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => BinaryFunc::Gt, 3887;
             },
             ">=" => Scalar {
                 params!(DecimalAny, DecimalAny) => {
@@ -2429,6 +2781,7 @@ lazy_static! {
                         Ok(lexpr.call_binary(rexpr, BinaryFunc::Gte))
                     })
                 }, 1757;
+                params!(CharAny, CharAny) => BinaryFunc::Gte, 1061;
                 params!(Bool, Bool) => BinaryFunc::Gte, 1695;
                 params!(Int32, Int32) => BinaryFunc::Gte, 525;
                 params!(Int64, Int64) => BinaryFunc::Gte, 415;
@@ -2440,10 +2793,13 @@ lazy_static! {
                 params!(Timestamp, Timestamp) => BinaryFunc::Gte, 2065;
                 params!(TimestampTz, TimestampTz) => BinaryFunc::Gte, 1325;
                 params!(Uuid, Uuid) => BinaryFunc::Gte, 2977;
+                params!(Inet, Inet) => BinaryFunc::Gte, 1206;
                 params!(Interval, Interval) => BinaryFunc::Gte, 1335;
                 params!(Bytes, Bytes) => BinaryFunc::Gte, 1960;
                 params!(String, String) => BinaryFunc::Gte, 667;
                 params!(Jsonb, Jsonb) => BinaryFunc::Gte, 3245;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => BinaryFunc::Gte, 3886;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => BinaryFunc::Gte, 3886;
             },
             "=" => Scalar {
                 params!(DecimalAny, DecimalAny) => {
@@ -2452,6 +2808,7 @@ lazy_static! {
                         Ok(lexpr.call_binary(rexpr, BinaryFunc::Eq))
                     })
                 }, 1752;
+                params!(CharAny, CharAny) => BinaryFunc::Eq, 1054;
                 params!(Bool, Bool) => BinaryFunc::Eq, 91;
                 params!(Int32, Int32) => BinaryFunc::Eq, 96;
                 params!(Int64, Int64) => BinaryFunc::Eq, 410;
@@ -2463,10 +2820,13 @@ lazy_static! {
                 params!(Timestamp, Timestamp) => BinaryFunc::Eq, 2060;
                 params!(TimestampTz, TimestampTz) => BinaryFunc::Eq, 1320;
                 params!(Uuid, Uuid) => BinaryFunc::Eq, 2972;
+                params!(Inet, Inet) => BinaryFunc::Eq, 1201;
                 params!(Interval, Interval) => BinaryFunc::Eq, 1330;
                 params!(Bytes, Bytes) => BinaryFunc::Eq, 1955;
                 params!(String, String) => BinaryFunc::Eq, 98;
                 params!(Jsonb, Jsonb) => BinaryFunc::Eq, 3240;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => BinaryFunc::Eq, 3882;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => BinaryFunc::Eq, 3882;
             },
             "<>" => Scalar {
                 params!(DecimalAny, DecimalAny) => {
@@ -2475,6 +2835,7 @@ lazy_static! {
                         Ok(lexpr.call_binary(rexpr, BinaryFunc::NotEq))
                     })
                 }, 1753;
+                params!(CharAny, CharAny) => BinaryFunc::NotEq, 1057;
                 params!(Bool, Bool) => BinaryFunc::NotEq, 85;
                 params!(Int32, Int32) => BinaryFunc::NotEq, 518;
                 params!(Int64, Int64) => BinaryFunc::NotEq, 411;
@@ -2486,10 +2847,13 @@ lazy_static! {
                 params!(Timestamp, Timestamp) => BinaryFunc::NotEq, 2061;
                 params!(TimestampTz, TimestampTz) => BinaryFunc::NotEq, 1321;
                 params!(Uuid, Uuid) => BinaryFunc::NotEq, 2973;
+                params!(Inet, Inet) => BinaryFunc::NotEq, 1202;
                 params!(Interval, Interval) => BinaryFunc::NotEq, 1331;
                 params!(Bytes, Bytes) => BinaryFunc::NotEq, 1956;
                 params!(String, String) => BinaryFunc::NotEq, 531;
                 params!(Jsonb, Jsonb) => BinaryFunc::NotEq, 3241;
+                params!(Plain(Range(Box::new(Int32))), Plain(Range(Box::new(Int32)))) => BinaryFunc::NotEq, 3883;
+                params!(Plain(Range(Box::new(TimestampTz))), Plain(Range(Box::new(TimestampTz)))) => BinaryFunc::NotEq, 3883;
             }
         }
     };
@@ -11,16 +11,19 @@
 
 use std::collections::BTreeMap;
 use std::convert;
+use std::error::Error;
 use std::fs::File;
 use std::io::Read;
 use std::sync::{Arc, Mutex};
 
 use anyhow::bail;
 use log::{debug, error, info, warn};
+use rdkafka::client::OAuthToken;
 use rdkafka::consumer::{BaseConsumer, Consumer};
 use reqwest::Url;
 
 use ccsr::tls::{Certificate, Identity};
+use kafka_util::{credentials, oauth};
 use sql_parser::ast::Value;
 
 enum ValType {
@@ -28,6 +31,7 @@ enum ValType {
     String,
     // Number with range [lower, upper]
     Number(i32, i32),
+    Boolean,
 }
 
 // Describes Kafka cluster configurations users can suppply using `CREATE
@@ -58,6 +62,18 @@ impl Config {
         Config::new(name, ValType::Path)
     }
 
+    // Shorthand for number config options with range [lower, upper]. The
+    // ranges of librdkafka options come from
+    // https://github.com/edenhill/librdkafka/blob/master/CONFIGURATION.md
+    fn number(name: &'static str, lower: i32, upper: i32) -> Self {
+        Config::new(name, ValType::Number(lower, upper))
+    }
+
+    // Shorthand for boolean config options.
+    fn boolean(name: &'static str) -> Self {
+        Config::new(name, ValType::Boolean)
+    }
+
     // Builds a new config that transforms the parameter according to `f` after
     // it is validated.
     fn transform(mut self, f: fn(String) -> String) -> Self {
@@ -83,7 +99,10 @@ impl Config {
                 Ok(parsed_n) if *lower <= parsed_n && parsed_n <= *upper => n.to_string(),
                 _ => bail!("must be a number between {} and {}", lower, upper),
             },
-            _ => bail!("unexpected value type"),
+            (ValType::Boolean, Value::Boolean(b)) => b.to_string(),
+            (ValType::Path, _) | (ValType::String, _) => bail!("must be a string"),
+            (ValType::Number(..), _) => bail!("must be a number"),
+            (ValType::Boolean, _) => bail!("must be a boolean"),
         };
         Ok((self.transform)(val))
     }
@@ -117,10 +136,11 @@ fn extract(
 /// expected file paths.
 /// - If any of the values in `with_options` are not
 ///   `sql_parser::ast::Value::String`.
+/// - If `sasl_oauthbearer_config` does not describe a valid token provider.
 pub fn extract_config(
     with_options: &mut BTreeMap<String, Value>,
 ) -> Result<BTreeMap<String, String>, anyhow::Error> {
-    extract(
+    let config = extract(
         with_options,
         &[
             Config::string("client_id"),
@@ -140,6 +160,7 @@ pub fn extract_config(
             Config::path("sasl_kerberos_keytab"),
             Config::string("sasl_username"),
             Config::string("sasl_password"),
+            Config::path("sasl_password_file"),
             Config::string("sasl_kerberos_kinit_cmd"),
             Config::string("sasl_kerberos_min_time_before_relogin"),
             Config::string("sasl_kerberos_principal"),
@@ -148,12 +169,43 @@ pub fn extract_config(
             // mixed case, while librdkafka requires all uppercase (e.g., `PLAIN`,
             // not `plain`).
             Config::string("sasl_mechanisms").transform(|s| s.to_uppercase()),
+            Config::string("sasl_oauthbearer_config"),
             Config::path("ssl_ca_location"),
             Config::path("ssl_certificate_location"),
             Config::path("ssl_key_location"),
             Config::string("ssl_key_password"),
+            // Advanced tuning options. These are passed through to librdkafka
+            // verbatim, overriding Materialize's defaults, so only options
+            // that cannot affect correctness belong here.
+            Config::number("fetch_message_max_bytes", 1, 1_000_000_000),
+            Config::number("fetch_max_bytes", 0, 2_147_483_135),
+            Config::number("fetch_min_bytes", 1, 100_000_000),
+            Config::number("fetch_wait_max_ms", 0, 300_000),
+            Config::number("queued_min_messages", 1, 10_000_000),
+            Config::number("queued_max_messages_kbytes", 1, 2_097_151),
+            Config::number("queue_buffering_max_messages", 1, 10_000_000),
+            Config::number("queue_buffering_max_kbytes", 1, 2_147_483_647),
+            Config::number("queue_buffering_max_ms", 0, 900_000),
+            Config::number("batch_num_messages", 1, 1_000_000),
+            Config::number("message_max_bytes", 1_000, 1_000_000_000),
+            Config::number("socket_timeout_ms", 10, 300_000),
+            Config::number("socket_send_buffer_bytes", 0, 100_000_000),
+            Config::number("socket_receive_buffer_bytes", 0, 100_000_000),
+            Config::boolean("socket_keepalive_enable"),
         ],
-    )
+    )?;
+    // librdkafka only consults the token provider once it connects to a
+    // broker, so surface configuration errors eagerly.
+    if let Some(oauthbearer_config) = config.get("sasl.oauthbearer.config") {
+        if let Err(e) = oauth::provider_from_config(oauthbearer_config) {
+            bail!(
+                "Invalid WITH option sasl_oauthbearer_config='{}': {}",
+                oauthbearer_config,
+                e
+            )
+        }
+    }
+    Ok(config)
 }
 
 /// Create a new `rdkafka::ClientConfig` with the provided
@@ -170,9 +222,7 @@ pub fn extract_config(
 pub fn test_config(broker: &str, options: &BTreeMap<String, String>) -> Result<(), anyhow::Error> {
     let mut config = rdkafka::ClientConfig::new();
     config.set("bootstrap.servers", broker);
-    for (k, v) in options {
-        config.set(k, v);
-    }
+    credentials::set_config_options(&mut config, options)?;
 
     match config.create_with_context(RDKafkaErrCheckContext::default()) {
         Ok(consumer) => {
@@ -216,6 +266,12 @@ struct RDKafkaErrCheckContext {
 impl rdkafka::consumer::ConsumerContext for RDKafkaErrCheckContext {}
 
 impl rdkafka::client::ClientContext for RDKafkaErrCheckContext {
+    const ENABLE_REFRESH_OAUTH_TOKEN: bool = true;
+
+    fn generate_oauth_token(&self, config: Option<&str>) -> Result<OAuthToken, Box<dyn Error>> {
+        oauth::generate_token(config)
+    }
+
     // `librdkafka` doesn't seem to propagate all Kerberos errors up the stack,
     // but does log them, so we are currently relying on the `log` callback for
     // error handling in situations we're aware of, e.g. cannot log into
@@ -295,3 +351,52 @@ pub fn generate_ccsr_client_config(
 
     Ok(client_config)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_tuning_options() -> Result<(), anyhow::Error> {
+        let mut with_options = BTreeMap::new();
+        with_options.insert("fetch_min_bytes".to_string(), Value::Number("1024".into()));
+        with_options.insert("socket_keepalive_enable".to_string(), Value::Boolean(true));
+        with_options.insert("format".to_string(), Value::String("bytes".into()));
+        let config = extract_config(&mut with_options)?;
+        assert_eq!(config["fetch.min.bytes"], "1024");
+        assert_eq!(config["socket.keepalive.enable"], "true");
+        // Options that are not Kafka configuration options are left alone.
+        assert_eq!(with_options.keys().collect::<Vec<_>>(), vec!["format"]);
+
+        for (name, value, expected) in vec![
+            (
+                "fetch_min_bytes",
+                Value::Number("0".into()),
+                "Invalid WITH option fetch_min_bytes=0: must be a number between 1 and 100000000",
+            ),
+            (
+                "fetch_min_bytes",
+                Value::String("1024".into()),
+                "Invalid WITH option fetch_min_bytes='1024': must be a number",
+            ),
+            (
+                "socket_keepalive_enable",
+                Value::Number("1".into()),
+                "Invalid WITH option socket_keepalive_enable=1: must be a boolean",
+            ),
+            (
+                "client_id",
+                Value::Boolean(true),
+                "Invalid WITH option client_id=true: must be a string",
+            ),
+        ] {
+            let mut with_options = BTreeMap::new();
+            with_options.insert(name.to_string(), value);
+            match extract_config(&mut with_options) {
+                Ok(_) => panic!("option {} unexpectedly accepted", name),
+                Err(e) => assert_eq!(e.to_string(), expected),
+            }
+        }
+        Ok(())
+    }
+}
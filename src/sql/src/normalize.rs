@@ -25,14 +25,15 @@ use sql_parser::ast::display::AstDisplay;
 use sql_parser::ast::visit_mut::{self, VisitMut};
 use sql_parser::ast::{
     AstInfo, Connector, CreateIndexStatement, CreateSinkStatement, CreateSourceStatement,
-    CreateTableStatement, CreateTypeStatement, CreateViewStatement, Function, FunctionArgs, Ident,
-    IfExistsBehavior, Query, Raw, RawName, SqlOption, Statement, TableFactor, UnresolvedObjectName,
-    Value,
+    CreateTableStatement, CreateTaskStatement, CreateTypeStatement, CreateViewStatement, Expr,
+    Function, FunctionArgs, Ident, IfExistsBehavior, InsertStatement, KeyConstraint, Query, Raw,
+    RawName, SqlOption, Statement, TableFactor, UnresolvedObjectName, Value,
 };
 
 use crate::names::{DatabaseSpecifier, FullName, PartialName};
 use crate::plan::error::PlanError;
 use crate::plan::statement::StatementContext;
+use crate::plan::transform_ast;
 
 /// Normalizes a single identifier.
 pub fn ident(ident: Ident) -> String {
@@ -174,10 +175,13 @@ pub fn create_statement(
 
             match &mut func.args {
                 FunctionArgs::Star => (),
-                FunctionArgs::Args(args) => {
+                FunctionArgs::Args { args, order_by } => {
                     for arg in args {
                         self.visit_expr_mut(arg);
                     }
+                    for expr in order_by {
+                        self.visit_order_by_expr_mut(expr);
+                    }
                 }
             }
             if let Some(over) = &mut func.over {
@@ -193,6 +197,28 @@ pub fn create_statement(
                         self.visit_table_alias_mut(alias);
                     }
                 }
+                TableFactor::Function {
+                    ref mut name,
+                    args,
+                    alias,
+                } if transform_ast::is_window_func(name) => {
+                    // The first argument to a window function names the
+                    // relation that it windows, and the remaining arguments
+                    // are column names and intervals.
+                    if let FunctionArgs::Args { args, .. } = args {
+                        if let Some(Expr::Identifier(names)) = args.first_mut() {
+                            let mut relation = UnresolvedObjectName(names.clone());
+                            self.visit_unresolved_object_name_mut(&mut relation);
+                            *names = relation.0;
+                        }
+                        for expr in args.iter_mut().skip(1) {
+                            self.visit_expr_mut(expr);
+                        }
+                    }
+                    if let Some(alias) = alias {
+                        self.visit_table_alias_mut(alias);
+                    }
+                }
                 TableFactor::Function {
                     ref mut name,
                     args,
@@ -205,10 +231,13 @@ pub fn create_statement(
 
                     match args {
                         FunctionArgs::Star => (),
-                        FunctionArgs::Args(args) => {
+                        FunctionArgs::Args { args, order_by } => {
                             for expr in args {
                                 self.visit_expr_mut(expr);
                             }
+                            for expr in order_by {
+                                self.visit_order_by_expr_mut(expr);
+                            }
                         }
                     }
                     if let Some(alias) = alias {
@@ -260,6 +289,7 @@ pub fn create_statement(
         Statement::CreateSource(CreateSourceStatement {
             name,
             col_names: _,
+            key_constraints,
             connector,
             with_options: _,
             format: _,
@@ -268,6 +298,11 @@ pub fn create_statement(
             materialized,
         }) => {
             *name = allocate_name(name)?;
+            for key_constraint in key_constraints {
+                if let KeyConstraint::ForeignKeyNotEnforced { foreign_table, .. } = key_constraint {
+                    *foreign_table = resolve_item(foreign_table)?;
+                }
+            }
             *if_not_exists = false;
             *materialized = false;
             if let Connector::Postgres { columns, .. } = connector {
@@ -320,6 +355,7 @@ pub fn create_statement(
         Statement::CreateView(CreateViewStatement {
             name,
             columns: _,
+            key_constraints,
             query,
             temporary,
             materialized,
@@ -331,6 +367,11 @@ pub fn create_statement(
             } else {
                 allocate_name(name)?
             };
+            for key_constraint in key_constraints {
+                if let KeyConstraint::ForeignKeyNotEnforced { foreign_table, .. } = key_constraint {
+                    *foreign_table = resolve_item(foreign_table)?;
+                }
+            }
             {
                 let mut normalizer = QueryNormalizer::new(scx);
                 normalizer.visit_query_mut(query);
@@ -347,6 +388,7 @@ pub fn create_statement(
             on_name,
             key_parts,
             with_options: _,
+            predicate,
             if_not_exists,
         }) => {
             *on_name = resolve_item(on_name)?;
@@ -359,6 +401,12 @@ pub fn create_statement(
                     }
                 }
             }
+            if let Some(predicate) = predicate {
+                normalizer.visit_expr_mut(predicate);
+                if let Some(err) = normalizer.err {
+                    return Err(err);
+                }
+            }
             *if_not_exists = false;
         }
 
@@ -380,6 +428,27 @@ pub fn create_statement(
             }
         }
 
+        Statement::CreateTask(CreateTaskStatement {
+            name,
+            schedule: _,
+            insert:
+                InsertStatement {
+                    table_name,
+                    columns: _,
+                    source,
+                },
+            if_not_exists,
+        }) => {
+            *name = allocate_name(name)?;
+            *table_name = resolve_item(table_name)?;
+            let mut normalizer = QueryNormalizer::new(scx);
+            normalizer.visit_insert_source_mut(source);
+            if let Some(err) = normalizer.err {
+                return Err(err);
+            }
+            *if_not_exists = false;
+        }
+
         _ => unreachable!(),
     }
 
@@ -549,6 +618,7 @@ mod tests {
             catalog: &DummyCatalog,
             ids: HashSet::new(),
             param_types: Rc::new(RefCell::new(BTreeMap::new())),
+            notices: Rc::new(RefCell::new(vec![])),
         };
 
         let parsed = sql_parser::parser::parse_statements(
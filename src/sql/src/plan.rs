@@ -33,7 +33,7 @@ use enum_kinds::EnumKind;
 use serde::{Deserialize, Serialize};
 
 use ::expr::{GlobalId, RowSetFinishing};
-use dataflow_types::{SinkConnectorBuilder, SinkEnvelope, SourceConnector};
+use dataflow_types::{SinkConnectorBuilder, SinkEnvelope, SourceConnector, TailEnvelope};
 use repr::{ColumnName, RelationDesc, Row, ScalarType, Timestamp};
 
 use crate::ast::{ExplainOptions, ExplainStage, Expr, FetchDirection, ObjectType, Raw, Statement};
@@ -43,6 +43,7 @@ pub(crate) mod error;
 pub(crate) mod explain;
 pub(crate) mod expr;
 pub(crate) mod lowering;
+pub(crate) mod notice;
 pub(crate) mod plan_utils;
 pub(crate) mod query;
 pub(crate) mod scope;
@@ -54,6 +55,7 @@ pub(crate) mod typeconv;
 pub use self::expr::HirRelationExpr;
 pub use error::PlanError;
 pub use explain::Explanation;
+pub use notice::PlanNotice;
 // This is used by sqllogictest to turn SQL values into `Datum`s.
 pub use query::{
     plan_default_expr, resolve_names, resolve_names_data_type, scalar_type_from_sql,
@@ -81,6 +83,7 @@ pub enum Plan {
         source: Source,
         if_not_exists: bool,
         materialized: bool,
+        depends_on: Vec<GlobalId>,
     },
     CreateSink {
         name: FullName,
@@ -117,6 +120,12 @@ pub enum Plan {
         typ: Type,
         depends_on: Vec<GlobalId>,
     },
+    CreateTask {
+        name: FullName,
+        task: Task,
+        if_not_exists: bool,
+        depends_on: Vec<GlobalId>,
+    },
     DiscardTemp,
     DiscardAll,
     DropDatabase {
@@ -139,6 +148,25 @@ pub enum Plan {
         name: String,
         value: String,
     },
+    AlterSystemSet {
+        name: String,
+        value: String,
+    },
+    AlterSystemReset {
+        name: String,
+    },
+    AlterRoleSet {
+        role_name: String,
+        name: String,
+        value: String,
+    },
+    AlterRoleReset {
+        role_name: String,
+        name: String,
+    },
+    ResetVariable {
+        name: String,
+    },
     StartTransaction,
     CommitTransaction,
     AbortTransaction,
@@ -156,6 +184,8 @@ pub enum Plan {
         emit_progress: bool,
         object_columns: usize,
         desc: RelationDesc,
+        envelope: TailEnvelope,
+        key_indices: Option<Vec<usize>>,
     },
     SendRows(Vec<Row>),
     ExplainPlan {
@@ -164,6 +194,13 @@ pub enum Plan {
         row_set_finishing: Option<RowSetFinishing>,
         stage: ExplainStage,
         options: ExplainOptions,
+        /// Whether the explained view was created with the
+        /// `prefer_delta_joins` option, or `None` if the explainee is a query,
+        /// whose joins follow the session's setting.
+        prefer_delta_joins: Option<bool>,
+    },
+    ExplainDataflow {
+        id: GlobalId,
     },
     SendDiffs {
         id: GlobalId,
@@ -187,6 +224,10 @@ pub enum Plan {
         id: GlobalId,
         options: Vec<IndexOptionName>,
     },
+    AlterSource {
+        id: GlobalId,
+        source: Source,
+    },
     AlterItemRename {
         id: GlobalId,
         to_name: String,
@@ -211,9 +252,20 @@ pub struct Table {
     pub create_sql: String,
     pub desc: RelationDesc,
     pub defaults: Vec<Expr<Raw>>,
+    pub checks: Vec<TableCheck>,
     pub temporary: bool,
 }
 
+/// A `CHECK` constraint on a table.
+#[derive(Clone, Debug)]
+pub struct TableCheck {
+    /// The name of the constraint, either as declared or as generated.
+    pub name: String,
+    /// The condition that rows of the table must not violate. A row violates
+    /// the constraint only if the condition evaluates to false, not null.
+    pub expr: ::expr::MirScalarExpr,
+}
+
 #[derive(Clone, Debug)]
 pub struct Source {
     pub create_sql: String,
@@ -221,6 +273,7 @@ pub struct Source {
     pub bare_desc: RelationDesc,
     pub expr: ::expr::MirRelationExpr,
     pub column_names: Vec<Option<ColumnName>>, // Column names for the transformed source; i.e. the expr
+    pub foreign_keys: Vec<::expr::ForeignKey>,
 }
 
 #[derive(Clone, Debug)]
@@ -237,6 +290,13 @@ pub struct View {
     pub expr: ::expr::MirRelationExpr,
     pub column_names: Vec<Option<ColumnName>>,
     pub temporary: bool,
+    pub prefer_delta_joins: bool,
+    /// Whether the view is materialized on demand rather than eagerly.
+    pub lazy: bool,
+    /// The number of bytes of reduction state per worker that are kept in
+    /// memory, if the state of the view's reductions spills to disk.
+    pub reduce_hot_set_size: Option<usize>,
+    pub foreign_keys: Vec<::expr::ForeignKey>,
 }
 
 #[derive(Clone, Debug)]
@@ -244,6 +304,9 @@ pub struct Index {
     pub create_sql: String,
     pub on: GlobalId,
     pub keys: Vec<::expr::MirScalarExpr>,
+    /// The predicates that rows must satisfy to be included in the index.
+    /// Empty unless the index is a partial index.
+    pub predicates: Vec<::expr::MirScalarExpr>,
 }
 
 #[derive(Clone, Debug)]
@@ -252,6 +315,17 @@ pub struct Type {
     pub inner: TypeInner,
 }
 
+#[derive(Clone, Debug)]
+pub struct Task {
+    pub create_sql: String,
+    /// The table into which the task inserts.
+    pub target: GlobalId,
+    /// The rows that the task inserts each time it runs.
+    pub query: ::expr::MirRelationExpr,
+    /// The interval at which the task runs.
+    pub schedule: Duration,
+}
+
 #[derive(Clone, Debug)]
 pub enum TypeInner {
     List {
@@ -300,6 +374,9 @@ pub enum IndexOption {
     /// Configures the logical compaction window for an index. `None` disables
     /// logical compaction entirely.
     LogicalCompactionWindow(Option<Duration>),
+    /// Configures how often updates to an index are emitted. `None` emits
+    /// updates as soon as they are computed.
+    RefreshInterval(Option<Duration>),
 }
 
 /// A vector of values to which parameter references should be bound.
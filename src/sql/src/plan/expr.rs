@@ -95,6 +95,8 @@ pub enum HirRelationExpr {
         limit: Option<usize>,
         /// Number of records to skip
         offset: usize,
+        /// User hint: expected number of values per group key.
+        expected_group_size: Option<usize>,
     },
     Negate {
         input: Box<HirRelationExpr>,
@@ -357,7 +359,7 @@ pub struct AggregateExpr {
 /// here than in `expr`, as these aggregates may be applied over empty
 /// result sets and should be null in those cases, whereas `expr` variants
 /// only return null values when supplied nulls as input.
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub enum AggregateFunc {
     MaxInt32,
     MaxInt64,
@@ -393,6 +395,14 @@ pub enum AggregateFunc {
     /// layer, this function filters out `Datum::Null`, for consistency with
     /// the other aggregate functions.
     JsonbAgg,
+    /// Accumulates ranges into an array of the smallest set of disjoint,
+    /// non-adjacent ranges that contain the same values.
+    RangeAgg,
+    /// Accumulates records of a value and its sort keys into an array of the
+    /// values, ordered by the keys.
+    ArrayAgg {
+        order_by: Vec<ColumnOrder>,
+    },
     /// Accumulates any number of `Datum::Dummy`s into `Datum::Dummy`.
     ///
     /// Useful for removing an expensive aggregation while maintaining the shape
@@ -433,6 +443,8 @@ impl AggregateFunc {
             AggregateFunc::Any => expr::AggregateFunc::Any,
             AggregateFunc::All => expr::AggregateFunc::All,
             AggregateFunc::JsonbAgg => expr::AggregateFunc::JsonbAgg,
+            AggregateFunc::RangeAgg => expr::AggregateFunc::RangeAgg,
+            AggregateFunc::ArrayAgg { order_by } => expr::AggregateFunc::ArrayAgg { order_by },
             AggregateFunc::Dummy => expr::AggregateFunc::Dummy,
         }
     }
@@ -459,6 +471,13 @@ impl AggregateFunc {
             AggregateFunc::Any => ScalarType::Bool,
             AggregateFunc::All => ScalarType::Bool,
             AggregateFunc::JsonbAgg => ScalarType::Jsonb,
+            AggregateFunc::RangeAgg => ScalarType::Array(Box::new(input_type.scalar_type)),
+            AggregateFunc::ArrayAgg { .. } => match input_type.scalar_type {
+                ScalarType::Record { fields, .. } => {
+                    ScalarType::Array(Box::new(fields[0].1.scalar_type.clone()))
+                }
+                _ => unreachable!("array_agg input is always a record"),
+            },
             AggregateFunc::SumInt32 => ScalarType::Int64,
             AggregateFunc::SumInt64 => {
                 ScalarType::Decimal(repr::adt::decimal::MAX_DECIMAL_PRECISION, 0)
@@ -668,6 +687,7 @@ impl HirRelationExpr {
         order_key: Vec<ColumnOrder>,
         limit: Option<usize>,
         offset: usize,
+        expected_group_size: Option<usize>,
     ) -> Self {
         HirRelationExpr::TopK {
             input: Box::new(self),
@@ -675,6 +695,7 @@ impl HirRelationExpr {
             order_key,
             limit,
             offset,
+            expected_group_size,
         }
     }
 
@@ -1035,6 +1056,7 @@ impl HirRelationExpr {
                     order_key: finishing.order_by,
                     limit: finishing.limit,
                     offset: finishing.offset,
+                    expected_group_size: None,
                 }),
                 outputs: finishing.project,
             }
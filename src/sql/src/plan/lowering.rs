@@ -455,6 +455,7 @@ impl HirRelationExpr {
                 order_key,
                 limit,
                 offset,
+                expected_group_size,
             } => {
                 // TopK is uncomplicated, except that we must group by the columns of `get_outer` as well.
                 let input = input.applied_to(id_gen, get_outer.clone(), col_map);
@@ -468,7 +469,13 @@ impl HirRelationExpr {
                         desc: column_order.desc,
                     })
                     .collect();
-                input.top_k(applied_group_key, applied_order_key, limit, offset)
+                input.top_k(
+                    applied_group_key,
+                    applied_order_key,
+                    limit,
+                    offset,
+                    expected_group_size,
+                )
             }
             Negate { input } => {
                 // Negate is uncomplicated.
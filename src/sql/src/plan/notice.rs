@@ -0,0 +1,53 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::fmt;
+
+use ore::str::StrExt;
+
+/// A notice that the planner reports to the user.
+///
+/// Notices describe conditions that do not prevent a statement from being
+/// planned, but that the user likely wants to know about.
+#[derive(Clone, Debug)]
+pub enum PlanNotice {
+    /// The key schema of the named source could not be used, and so the
+    /// source was created without a key.
+    SourceKeyIgnored { source: String, error: String },
+}
+
+impl PlanNotice {
+    /// Reports additional details about the notice, if any are available.
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            PlanNotice::SourceKeyIgnored { error, .. } => Some(error.clone()),
+        }
+    }
+
+    /// Reports a hint for the user about how the notice could be addressed.
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            PlanNotice::SourceKeyIgnored { .. } => {
+                Some("Specify WITH (ignore_source_keys = true) to silence this warning.".into())
+            }
+        }
+    }
+}
+
+impl fmt::Display for PlanNotice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlanNotice::SourceKeyIgnored { source, .. } => write!(
+                f,
+                "key schema of source {} cannot be used and will be ignored",
+                source.quoted()
+            ),
+        }
+    }
+}
@@ -13,6 +13,7 @@ use std::fmt;
 
 use anyhow::bail;
 
+use ore::str::StrExt;
 use repr::RelationDesc;
 
 use crate::ast::Ident;
@@ -53,3 +54,38 @@ pub fn maybe_rename_columns(
 
     Ok(desc.with_names(new_names))
 }
+
+/// Resolves the columns named by a key constraint of kind `kind`, like
+/// `PRIMARY KEY`, to their indices in `desc`.
+///
+/// Returns an error if any column does not exist in `desc` or is named more
+/// than once.
+pub fn plan_key_columns(
+    kind: &str,
+    context: impl fmt::Display,
+    desc: &RelationDesc,
+    columns: &[Ident],
+) -> Result<Vec<usize>, anyhow::Error> {
+    let mut key = Vec::with_capacity(columns.len());
+    for column in columns {
+        let name = normalize::column_name(column.clone());
+        let i = match desc.get_by_name(&name) {
+            Some((i, _)) => i,
+            None => bail!(
+                "{} column {} does not exist in {}",
+                kind,
+                name.as_str().quoted(),
+                context
+            ),
+        };
+        if key.contains(&i) {
+            bail!(
+                "{} column {} specified more than once",
+                kind,
+                name.as_str().quoted()
+            );
+        }
+        key.push(i);
+    }
+    Ok(key)
+}
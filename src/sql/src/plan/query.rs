@@ -37,25 +37,27 @@ use sql_parser::ast::visit::{self, Visit};
 use sql_parser::ast::{
     AstInfo, Cte, DataType, Distinct, Expr, Function, FunctionArgs, Ident, InsertSource,
     JoinConstraint, JoinOperator, Limit, OrderByExpr, Query, Raw, RawName, Select, SelectItem,
-    SetExpr, SetOperator, TableAlias, TableFactor, TableWithJoins, UnresolvedObjectName, Value,
-    Values,
+    SetExpr, SetOperator, SqlOption, TableAlias, TableFactor, TableWithJoins, UnresolvedObjectName,
+    Value, Values,
 };
 
 use ::expr::{GlobalId, Id, RowSetFinishing};
+use repr::adt::char::MAX_LENGTH as MAX_CHAR_LENGTH;
 use repr::adt::decimal::{Decimal, MAX_DECIMAL_PRECISION};
 use repr::{
     strconv, ColumnName, ColumnType, Datum, RelationDesc, RelationType, RowArena, ScalarType,
     Timestamp,
 };
 
-use crate::catalog::{Catalog, CatalogItemType};
+use crate::catalog::{Catalog, CatalogItemType, Feature};
 use crate::func::{self, Func, FuncSpec};
 use crate::names::PartialName;
 use crate::normalize;
 use crate::plan::error::PlanError;
 use crate::plan::expr::{
-    AbstractColumnType, AbstractExpr, AggregateExpr, BinaryFunc, CoercibleScalarExpr, ColumnOrder,
-    ColumnRef, HirRelationExpr, HirScalarExpr, JoinKind, UnaryFunc, VariadicFunc,
+    AbstractColumnType, AbstractExpr, AggregateExpr, AggregateFunc, BinaryFunc,
+    CoercibleScalarExpr, ColumnOrder, ColumnRef, HirRelationExpr, HirScalarExpr, JoinKind,
+    UnaryFunc, VariadicFunc,
 };
 use crate::plan::plan_utils;
 use crate::plan::scope::{Scope, ScopeItem, ScopeItemName};
@@ -630,6 +632,29 @@ pub fn plan_default_expr(
     Ok((hir, qcx.ids.into_iter().collect()))
 }
 
+/// Plans the conversion of a nullable string, read from the first column of
+/// its input, to `target_ty`.
+pub fn plan_cast_from_string(
+    scx: &StatementContext,
+    name: &'static str,
+    target_ty: &ScalarType,
+) -> Result<::expr::MirScalarExpr, anyhow::Error> {
+    let qcx = QueryContext::root(scx, QueryLifetime::Static);
+    let ecx = &ExprContext {
+        qcx: &qcx,
+        name,
+        scope: &Scope::empty(None),
+        relation_type: &RelationType::new(vec![ScalarType::String.nullable(true)]),
+        allow_aggregates: false,
+        allow_subqueries: false,
+    };
+    let expr = HirScalarExpr::Column(ColumnRef {
+        level: 0,
+        column: 0,
+    });
+    typeconv::plan_cast(name, ecx, CastContext::Explicit, expr, target_ty)?.lower_uncorrelated()
+}
+
 pub fn plan_index_exprs<'a>(
     scx: &'a StatementContext,
     on_desc: &RelationDesc,
@@ -662,6 +687,59 @@ pub fn plan_index_exprs<'a>(
     Ok((out, qcx.ids.into_iter().collect()))
 }
 
+/// Plans the `WHERE` clause of a partial index on a relation described by
+/// `on_desc`.
+pub fn plan_index_predicate<'a>(
+    scx: &'a StatementContext,
+    on_desc: &RelationDesc,
+    mut predicate: Expr<Raw>,
+) -> Result<(::expr::MirScalarExpr, Vec<GlobalId>), anyhow::Error> {
+    let scope = Scope::from_source(None, on_desc.iter_names(), Some(Scope::empty(None)));
+    let mut qcx = QueryContext::root(scx, QueryLifetime::Static);
+
+    transform_ast::transform_expr(scx, &mut predicate)?;
+    let predicate = resolve_names_expr(&mut qcx, predicate)?;
+
+    let ecx = &ExprContext {
+        qcx: &qcx,
+        name: "WHERE clause",
+        scope: &scope,
+        relation_type: on_desc.typ(),
+        allow_aggregates: false,
+        allow_subqueries: false,
+    };
+    let predicate = plan_expr(ecx, &predicate)?
+        .type_as(ecx, &ScalarType::Bool)?
+        .lower_uncorrelated()?;
+    Ok((predicate, qcx.ids.into_iter().collect()))
+}
+
+/// Plans a `CHECK` constraint on a table described by `on_desc`.
+pub fn plan_check_constraint<'a>(
+    scx: &'a StatementContext,
+    on_desc: &RelationDesc,
+    mut check: Expr<Raw>,
+) -> Result<(::expr::MirScalarExpr, Vec<GlobalId>), anyhow::Error> {
+    let scope = Scope::from_source(None, on_desc.iter_names(), Some(Scope::empty(None)));
+    let mut qcx = QueryContext::root(scx, QueryLifetime::Static);
+
+    transform_ast::transform_expr(scx, &mut check)?;
+    let check = resolve_names_expr(&mut qcx, check)?;
+
+    let ecx = &ExprContext {
+        qcx: &qcx,
+        name: "CHECK constraint",
+        scope: &scope,
+        relation_type: on_desc.typ(),
+        allow_aggregates: false,
+        allow_subqueries: false,
+    };
+    let check = plan_expr(ecx, &check)?
+        .type_as(ecx, &ScalarType::Bool)?
+        .lower_uncorrelated()?;
+    Ok((check, qcx.ids.into_iter().collect()))
+}
+
 fn plan_expr_or_col_index(
     ecx: &ExprContext,
     e: &Expr<Aug>,
@@ -799,12 +877,19 @@ fn plan_subquery(
 ) -> Result<(HirRelationExpr, Scope), anyhow::Error> {
     let (mut expr, scope, finishing) = plan_query(qcx, q)?;
     if finishing.limit.is_some() || finishing.offset > 0 {
+        // A correlated subquery is evaluated once per outer row, so its limit
+        // applies to groups whose size is given by the subquery's own hint.
+        let expected_group_size = match &q.body {
+            SetExpr::Select(s) => plan_expected_group_size(&s.options)?,
+            _ => None,
+        };
         expr = HirRelationExpr::TopK {
             input: Box::new(expr),
             group_key: vec![],
             order_key: finishing.order_by,
             limit: finishing.limit,
             offset: finishing.offset,
+            expected_group_size,
         };
     }
     Ok((expr.project(finishing.project), scope))
@@ -1037,15 +1122,7 @@ fn plan_view_select(
     } = s;
 
     // Extract hints about group size if there are any
-    let mut options = crate::normalize::options(options);
-
-    let option = options.remove("expected_group_size");
-
-    let expected_group_size = match option {
-        Some(Value::Number(n)) => Some(n.parse::<usize>()?),
-        Some(_) => bail!("expected_group_size must be a number"),
-        None => None,
-    };
+    let expected_group_size = plan_expected_group_size(options)?;
 
     // Step 1. Handle FROM clause, including joins.
     let (mut relation_expr, from_scope) =
@@ -1358,6 +1435,7 @@ fn plan_view_select(
                     group_key: distinct_key,
                     limit: Some(1),
                     offset: 0,
+                    expected_group_size,
                 }
             }
         }
@@ -1383,6 +1461,19 @@ fn plan_view_select(
     })
 }
 
+/// Extracts the hint about the expected number of rows in each group, if any,
+/// from the options of a `SELECT`.
+fn plan_expected_group_size<T: AstInfo>(
+    options: &[SqlOption<T>],
+) -> Result<Option<usize>, anyhow::Error> {
+    let mut options = crate::normalize::options(options);
+    match options.remove("expected_group_size") {
+        Some(Value::Number(n)) => Ok(Some(n.parse::<usize>()?)),
+        Some(_) => bail!("expected_group_size must be a number"),
+        None => Ok(None),
+    }
+}
+
 /// Plans an expression in a `GROUP BY` clause.
 ///
 /// For historical reasons, PostgreSQL allows `GROUP BY` expressions to refer to
@@ -1616,7 +1707,13 @@ fn plan_table_function(
     };
     let args = match args {
         FunctionArgs::Star => bail!("{} does not accept * as an argument", name),
-        FunctionArgs::Args(args) => plan_exprs(ecx, args)?,
+        FunctionArgs::Args { order_by, .. } if !order_by.is_empty() => {
+            bail!(
+                "ORDER BY specified, but {}() is not an aggregate function",
+                name
+            );
+        }
+        FunctionArgs::Args { args, .. } => plan_exprs(ecx, args)?,
     };
     let name = normalize::unresolved_object_name(name.clone())?;
     let tf = func::select_impl(ecx, FuncSpec::Func(&name), impls, args)?;
@@ -1709,6 +1806,7 @@ fn invent_column_name(ecx: &ExprContext, expr: &Expr<Aug>) -> Option<ScopeItemNa
         Expr::NullIf { .. } => Some("nullif".into()),
         Expr::Array { .. } => Some("array".into()),
         Expr::List { .. } => Some("list".into()),
+        Expr::Map { .. } => Some("map".into()),
         Expr::Cast { expr, .. } => return invent_column_name(ecx, expr),
         Expr::FieldAccess { field, .. } => Some(normalize::column_name(field.clone())),
         Expr::Exists { .. } => Some("exists".into()),
@@ -2147,6 +2245,7 @@ pub fn plan_expr<'a>(
         }
         Expr::Array(exprs) => plan_array(ecx, exprs, None)?,
         Expr::List(exprs) => plan_list(ecx, exprs, None)?,
+        Expr::Map(entries) => plan_map(ecx, entries, None)?,
         Expr::Row { exprs } => {
             let mut out = vec![];
             for e in exprs {
@@ -2160,16 +2259,17 @@ pub fn plan_expr<'a>(
         Expr::Cast { expr, data_type } => {
             let to_scalar_type = scalar_type_from_sql(ecx.qcx.scx, data_type)?;
             let expr = match &**expr {
-                // Special case a direct cast of an ARRAY or LIST expression so
-                // we can pass in the target type as a type hint. This is
-                // a limited form of the coercion that we do for string literals
-                // via CoercibleScalarExpr. We used to let CoercibleScalarExpr
-                // handle ARRAY/LIST coercion too, but doing so causes
-                // PostgreSQL compatibility trouble.
+                // Special case a direct cast of an ARRAY, LIST, or MAP
+                // expression so we can pass in the target type as a type hint.
+                // This is a limited form of the coercion that we do for string
+                // literals via CoercibleScalarExpr. We used to let
+                // CoercibleScalarExpr handle ARRAY/LIST coercion too, but doing
+                // so causes PostgreSQL compatibility trouble.
                 //
                 // See: https://github.com/postgres/postgres/blob/31f403e95/src/backend/parser/parse_expr.c#L2762-L2768
                 Expr::Array(exprs) => plan_array(ecx, exprs, Some(&to_scalar_type))?,
                 Expr::List(exprs) => plan_list(ecx, exprs, Some(&to_scalar_type))?,
+                Expr::Map(entries) => plan_map(ecx, entries, Some(&to_scalar_type))?,
                 _ => plan_expr(ecx, expr)?,
             };
             let expr = typeconv::plan_coerce(ecx, expr, &to_scalar_type)?;
@@ -2250,25 +2350,49 @@ pub fn plan_expr<'a>(
             }
         }
         Expr::WildcardAccess(expr) => plan_expr(ecx, expr)?,
-        Expr::SubscriptIndex { expr, subscript } => {
-            let expr = plan_expr(ecx, expr)?.type_as_any(ecx)?;
-            let ty = ecx.scalar_type(&expr);
-            let func = match &ty {
-                ScalarType::List { .. } => BinaryFunc::ListIndex,
-                ScalarType::Array(_) => BinaryFunc::ArrayIndex,
-                ty => bail!("cannot subscript type {}", ecx.humanize_scalar_type(&ty)),
-            };
-
-            expr.call_binary(
-                plan_expr(ecx, subscript)?.cast_to(
-                    "subscript (indexing)",
-                    ecx,
-                    CastContext::Explicit,
-                    &ScalarType::Int64,
-                )?,
-                func,
-            )
-            .into()
+        Expr::SubscriptIndex { .. } => {
+            // Gather the full chain of subscripts, e.g. `a[1][2]`, as arrays
+            // must consume all of their subscripts at once.
+            let mut base = e;
+            let mut subscripts = vec![];
+            while let Expr::SubscriptIndex { expr, subscript } = base {
+                subscripts.push(subscript);
+                base = &**expr;
+            }
+            subscripts.reverse();
+
+            let mut expr = plan_expr(ecx, base)?.type_as_any(ecx)?;
+            let mut subscripts = subscripts.into_iter().peekable();
+            while subscripts.peek().is_some() {
+                let ty = ecx.scalar_type(&expr);
+                expr = match &ty {
+                    ScalarType::List { .. } => {
+                        let subscript = plan_subscript(ecx, subscripts.next().unwrap())?;
+                        expr.call_binary(subscript, BinaryFunc::ListIndex)
+                    }
+                    ScalarType::Map { .. } => {
+                        let key = plan_expr(ecx, subscripts.next().unwrap())?.cast_to(
+                            "subscript (map key)",
+                            ecx,
+                            CastContext::Explicit,
+                            &ScalarType::String,
+                        )?;
+                        expr.call_binary(key, BinaryFunc::MapGetValue)
+                    }
+                    ScalarType::Array(_) => {
+                        let mut exprs = vec![expr];
+                        for subscript in subscripts.by_ref() {
+                            exprs.push(plan_subscript(ecx, subscript)?);
+                        }
+                        HirScalarExpr::CallVariadic {
+                            func: VariadicFunc::ArrayIndex,
+                            exprs,
+                        }
+                    }
+                    ty => bail!("cannot subscript type {}", ecx.humanize_scalar_type(&ty)),
+                };
+            }
+            expr.into()
         }
 
         Expr::SubscriptSlice { expr, positions } => {
@@ -2278,7 +2402,10 @@ pub fn plan_expr<'a>(
                 "subscript expression must contain at least one position"
             );
             if positions.len() > 1 {
-                ecx.require_experimental_mode("layered/multidimensional slicing")?;
+                ecx.require_feature(
+                    Feature::MultidimensionalLists,
+                    "layered/multidimensional slicing",
+                )?;
             }
             let expr = plan_expr(ecx, expr)?.type_as_any(ecx)?;
             let ty = ecx.scalar_type(&expr);
@@ -2394,7 +2521,7 @@ fn plan_array(
     exprs: &[Expr<Aug>],
     type_hint: Option<&ScalarType>,
 ) -> Result<CoercibleScalarExpr, anyhow::Error> {
-    ecx.qcx.scx.require_experimental_mode("ARRAY")?;
+    ecx.require_feature(Feature::ArrayConstructor, "ARRAY")?;
     let (elem_type, exprs) = if exprs.is_empty() {
         if let Some(ScalarType::Array(elem_type)) = type_hint {
             ((**elem_type).clone(), vec![])
@@ -2460,6 +2587,50 @@ fn plan_list(
     .into())
 }
 
+fn plan_map(
+    ecx: &ExprContext,
+    entries: &[MapEntry<Aug>],
+    type_hint: Option<&ScalarType>,
+) -> Result<CoercibleScalarExpr, anyhow::Error> {
+    let (value_type, exprs) = if entries.is_empty() {
+        if let Some(ScalarType::Map { value_type, .. }) = type_hint {
+            ((**value_type).clone(), vec![])
+        } else {
+            bail!("cannot determine type of empty map");
+        }
+    } else {
+        let type_hint = match type_hint {
+            Some(ScalarType::Map { value_type, .. }) => Some(&**value_type),
+            _ => None,
+        };
+        let key_ecx = ecx.with_name("MAP key");
+        let mut keys = vec![];
+        let mut values = vec![];
+        for MapEntry { key, value } in entries {
+            keys.push(plan_expr(&key_ecx, key)?.type_as(&key_ecx, &ScalarType::String)?);
+            values.push(match value {
+                // Special case nested MAP expressions so we can plumb the
+                // type hint through.
+                Expr::Map(entries) => plan_map(ecx, entries, type_hint)?,
+                _ => plan_expr(ecx, value)?,
+            });
+        }
+        let values = coerce_homogeneous_exprs("MAP expression", ecx, values, type_hint)?;
+        let value_type = ecx.scalar_type(&values[0]);
+        let exprs = keys
+            .into_iter()
+            .zip(values)
+            .flat_map(|(k, v)| vec![k, v])
+            .collect();
+        (value_type, exprs)
+    };
+    Ok(HirScalarExpr::CallVariadic {
+        func: VariadicFunc::MapBuild { value_type },
+        exprs,
+    }
+    .into())
+}
+
 /// Coerces a list of expressions such that all input expressions will be cast
 /// to the same type. If successful, returns a new list of expressions in the
 /// same order as the input, where each expression has the appropriate casts to
@@ -2503,6 +2674,20 @@ pub fn coerce_homogeneous_exprs(
     Ok(out)
 }
 
+/// Plans a single index into a list or array, which must be coercible to an
+/// integer.
+fn plan_subscript(
+    ecx: &ExprContext,
+    subscript: &Expr<Aug>,
+) -> Result<HirScalarExpr, anyhow::Error> {
+    plan_expr(ecx, subscript)?.cast_to(
+        "subscript (indexing)",
+        ecx,
+        CastContext::Explicit,
+        &ScalarType::Int64,
+    )
+}
+
 fn plan_aggregate(
     ecx: &ExprContext,
     sql_func: &Function<Aug>,
@@ -2526,17 +2711,49 @@ fn plan_aggregate(
     // rules to all aggregates, not just `count`, since we may one day support
     // user-defined aggregates, including user-defined aggregates that take no
     // parameters.
-    let args = match &sql_func.args {
-        FunctionArgs::Star => vec![],
-        FunctionArgs::Args(args) if args.is_empty() => {
+    let (args, order_by_exprs) = match &sql_func.args {
+        FunctionArgs::Star => (vec![], &[][..]),
+        FunctionArgs::Args { args, .. } if args.is_empty() => {
             bail!(
                 "{}(*) must be used to call a parameterless aggregate function",
                 name
             );
         }
-        FunctionArgs::Args(args) => plan_exprs(ecx, args)?,
+        FunctionArgs::Args { args, order_by } => {
+            if sql_func.distinct && order_by.iter().any(|o| !args.contains(&o.expr)) {
+                bail!("in an aggregate with DISTINCT, ORDER BY expressions must appear in argument list");
+            }
+            (plan_exprs(ecx, args)?, &order_by[..])
+        }
     };
-    let (mut expr, func) = func::select_impl(ecx, FuncSpec::Func(&name), impls, args)?;
+    let (mut expr, mut func) = func::select_impl(ecx, FuncSpec::Func(&name), impls, args)?;
+    match &mut func {
+        AggregateFunc::ArrayAgg { order_by } => {
+            // `array_agg` must not drop nulls and must respect `ORDER BY`, so
+            // it aggregates records of each value and its sort keys.
+            let mut exprs = vec![expr];
+            for obe in order_by_exprs {
+                order_by.push(ColumnOrder {
+                    column: exprs.len(),
+                    desc: !obe.asc.unwrap_or(true),
+                });
+                exprs.push(plan_expr(ecx, &obe.expr)?.type_as_any(ecx)?);
+            }
+            let field_names = (1..=exprs.len())
+                .map(|i| ColumnName::from(format!("f{}", i)))
+                .collect();
+            expr = HirScalarExpr::CallVariadic {
+                func: VariadicFunc::RecordCreate { field_names },
+                exprs,
+            };
+        }
+        AggregateFunc::JsonbAgg if !order_by_exprs.is_empty() => {
+            unsupported!("ORDER BY in jsonb_agg");
+        }
+        // The results of the remaining aggregate functions do not depend on the
+        // order of their inputs.
+        _ => (),
+    }
     if let Some(filter) = &sql_func.filter {
         // If a filter is present, as in
         //
@@ -2690,7 +2907,11 @@ fn plan_function<'a>(
             "* argument is invalid with non-aggregate function {}",
             sql_func.name
         ),
-        FunctionArgs::Args(args) => plan_exprs(ecx, args)?,
+        FunctionArgs::Args { order_by, .. } if !order_by.is_empty() => bail!(
+            "ORDER BY specified, but {}() is not an aggregate function",
+            sql_func.name
+        ),
+        FunctionArgs::Args { args, .. } => plan_exprs(ecx, args)?,
     };
 
     let name = normalize::unresolved_object_name(sql_func.name.clone())?;
@@ -2715,7 +2936,7 @@ pub fn resolve_func(
     // message.
     let cexprs = match args {
         sql_parser::ast::FunctionArgs::Star => vec![],
-        sql_parser::ast::FunctionArgs::Args(args) => plan_exprs(ecx, &args)?,
+        sql_parser::ast::FunctionArgs::Args { args, .. } => plan_exprs(ecx, &args)?,
     };
 
     let types: Vec<_> = cexprs
@@ -2910,17 +3131,37 @@ pub fn scalar_type_from_sql(
                         let (precision, scale) = unwrap_numeric_typ_mod(typ_mod)?;
                         ScalarType::Decimal(precision, scale)
                     }
-                    ScalarType::String => {
-                        // TODO(justin): we should look up in the catalog to see
-                        // if this type is actually a length-parameterized
-                        // string.
-                        match name.raw_name().item.as_str() {
-                            n @ "char" | n @ "varchar" => {
-                                validate_typ_mod(n, &typ_mod, &[("length", 1, 10_485_760)])?
-                            }
-                            _ => {}
+                    // `char` is the parser's spelling of `character`, which
+                    // has a default length of 1.
+                    ScalarType::String if name.raw_name().item == "char" => {
+                        validate_typ_mod(
+                            "char",
+                            &typ_mod,
+                            &[("length", 1, MAX_CHAR_LENGTH as u64)],
+                        )?;
+                        ScalarType::Char {
+                            length: Some(typ_mod.first().map_or(1, |l| *l as usize)),
+                        }
+                    }
+                    ScalarType::Char { .. } => {
+                        validate_typ_mod(
+                            "char",
+                            &typ_mod,
+                            &[("length", 1, MAX_CHAR_LENGTH as u64)],
+                        )?;
+                        ScalarType::Char {
+                            length: typ_mod.first().map(|l| *l as usize),
+                        }
+                    }
+                    ScalarType::VarChar { .. } => {
+                        validate_typ_mod(
+                            "varchar",
+                            &typ_mod,
+                            &[("length", 1, MAX_CHAR_LENGTH as u64)],
+                        )?;
+                        ScalarType::VarChar {
+                            length: typ_mod.first().map(|l| *l as usize),
                         }
-                        ScalarType::String
                     }
                     t => {
                         validate_typ_mod(&name.to_string(), &typ_mod, &[])?;
@@ -3003,9 +3244,17 @@ pub fn scalar_type_from_pg(ty: &pgrepr::Type) -> Result<ScalarType, anyhow::Erro
         pgrepr::Type::TimestampTz => Ok(ScalarType::TimestampTz),
         pgrepr::Type::Interval => Ok(ScalarType::Interval),
         pgrepr::Type::Bytea => Ok(ScalarType::Bytes),
+        pgrepr::Type::BpChar => Ok(ScalarType::Char { length: None }),
         pgrepr::Type::Text => Ok(ScalarType::String),
+        pgrepr::Type::VarChar => Ok(ScalarType::VarChar { length: None }),
         pgrepr::Type::Jsonb => Ok(ScalarType::Jsonb),
         pgrepr::Type::Uuid => Ok(ScalarType::Uuid),
+        pgrepr::Type::Inet => Ok(ScalarType::Inet),
+        pgrepr::Type::Cidr => Ok(ScalarType::Cidr),
+        pgrepr::Type::Geometry => Ok(ScalarType::Geometry),
+        pgrepr::Type::Geography => Ok(ScalarType::Geography),
+        pgrepr::Type::TsVector => Ok(ScalarType::TsVector),
+        pgrepr::Type::TsQuery => Ok(ScalarType::TsQuery),
         pgrepr::Type::Array(t) => Ok(ScalarType::Array(Box::new(scalar_type_from_pg(t)?))),
         pgrepr::Type::List(l) => Ok(ScalarType::List {
             element_type: Box::new(scalar_type_from_pg(l)?),
@@ -3019,6 +3268,7 @@ pub fn scalar_type_from_pg(ty: &pgrepr::Type) -> Result<ScalarType, anyhow::Erro
             value_type: Box::new(scalar_type_from_pg(value_type)?),
             custom_oid: None,
         }),
+        pgrepr::Type::Range(t) => Ok(ScalarType::Range(Box::new(scalar_type_from_pg(t)?))),
     }
 }
 
@@ -3284,8 +3534,12 @@ impl<'a> ExprContext<'a> {
             .derived_context(self.scope.clone(), self.relation_type)
     }
 
-    pub fn require_experimental_mode(&self, feature_name: &str) -> Result<(), anyhow::Error> {
-        self.qcx.scx.require_experimental_mode(feature_name)
+    pub fn require_feature(
+        &self,
+        feature: Feature,
+        feature_name: &str,
+    ) -> Result<(), anyhow::Error> {
+        self.qcx.scx.require_feature(feature, feature_name)
     }
 
     pub fn param_types(&self) -> &RefCell<BTreeMap<usize, ScalarType>> {
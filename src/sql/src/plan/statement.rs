@@ -22,10 +22,13 @@ use ore::collections::CollectionExt;
 use repr::{ColumnType, RelationDesc, ScalarType};
 
 use crate::ast::{Ident, ObjectType, Raw, Statement, UnresolvedObjectName};
-use crate::catalog::{Catalog, CatalogDatabase, CatalogItem, CatalogItemType, CatalogSchema};
+use crate::catalog::{
+    Catalog, CatalogDatabase, CatalogItem, CatalogItemType, CatalogSchema, Feature,
+};
 use crate::names::{DatabaseSpecifier, FullName, PartialName};
 use crate::normalize;
 use crate::plan::error::PlanError;
+use crate::plan::notice::PlanNotice;
 use crate::plan::query;
 use crate::plan::{Params, Plan, PlanContext};
 
@@ -101,6 +104,7 @@ pub fn describe(
         pcx: &PlanContext::default(),
         ids: HashSet::new(),
         param_types: Rc::new(RefCell::new(param_types)),
+        notices: Rc::new(RefCell::new(vec![])),
     };
 
     let desc = match stmt {
@@ -111,6 +115,7 @@ pub fn describe(
         Statement::CreateSource(stmt) => ddl::describe_create_source(&scx, stmt)?,
         Statement::CreateView(stmt) => ddl::describe_create_view(&scx, stmt)?,
         Statement::CreateSink(stmt) => ddl::describe_create_sink(&scx, stmt)?,
+        Statement::CreateTask(stmt) => ddl::describe_create_task(&scx, stmt)?,
         Statement::CreateIndex(stmt) => ddl::describe_create_index(&scx, stmt)?,
         Statement::CreateType(stmt) => ddl::describe_create_type(&scx, stmt)?,
         Statement::CreateRole(stmt) => ddl::describe_create_role(&scx, stmt)?,
@@ -118,6 +123,12 @@ pub fn describe(
         Statement::DropObjects(stmt) => ddl::describe_drop_objects(&scx, stmt)?,
         Statement::AlterObjectRename(stmt) => ddl::describe_alter_object_rename(&scx, stmt)?,
         Statement::AlterIndexOptions(stmt) => ddl::describe_alter_index_options(&scx, stmt)?,
+        Statement::AlterSourceOptions(stmt) => ddl::describe_alter_source_options(&scx, stmt)?,
+        Statement::AlterSourceSchema(stmt) => ddl::describe_alter_source_schema(&scx, stmt)?,
+        Statement::AlterSystemSet(stmt) => scl::describe_alter_system_set(&scx, stmt)?,
+        Statement::AlterSystemReset(stmt) => scl::describe_alter_system_reset(&scx, stmt)?,
+        Statement::AlterRoleSet(stmt) => scl::describe_alter_role_set(&scx, stmt)?,
+        Statement::AlterRoleReset(stmt) => scl::describe_alter_role_reset(&scx, stmt)?,
 
         // `SHOW` statements.
         Statement::ShowColumns(stmt) => show::show_columns(&scx, stmt)?.describe()?,
@@ -132,6 +143,7 @@ pub fn describe(
 
         // SCL statements.
         Statement::SetVariable(stmt) => scl::describe_set_variable(&scx, stmt)?,
+        Statement::ResetVariable(stmt) => scl::describe_reset_variable(&scx, stmt)?,
         Statement::ShowVariable(stmt) => scl::describe_show_variable(&scx, stmt)?,
         Statement::Discard(stmt) => scl::describe_discard(&scx, stmt)?,
         Statement::Declare(stmt) => scl::describe_declare(&scx, stmt)?,
@@ -167,12 +179,14 @@ pub fn describe(
 /// The returned plan is tied to the state of the provided catalog. If the state
 /// of the catalog changes after planning, the validity of the plan is not
 /// guaranteed.
+///
+/// Any notices generated during planning are returned alongside the plan.
 pub fn plan(
     pcx: &PlanContext,
     catalog: &dyn Catalog,
     stmt: Statement<Raw>,
     params: &Params,
-) -> Result<Plan, anyhow::Error> {
+) -> Result<(Plan, Vec<PlanNotice>), anyhow::Error> {
     let param_types = params
         .types
         .iter()
@@ -185,9 +199,10 @@ pub fn plan(
         catalog,
         ids: HashSet::new(),
         param_types: Rc::new(RefCell::new(param_types)),
+        notices: Rc::new(RefCell::new(vec![])),
     };
 
-    match stmt {
+    let plan = match stmt {
         // DDL statements.
         Statement::CreateDatabase(stmt) => ddl::plan_create_database(scx, stmt),
         Statement::CreateSchema(stmt) => ddl::plan_create_schema(scx, stmt),
@@ -195,12 +210,19 @@ pub fn plan(
         Statement::CreateSource(stmt) => ddl::plan_create_source(scx, stmt),
         Statement::CreateView(stmt) => ddl::plan_create_view(scx, stmt, params),
         Statement::CreateSink(stmt) => ddl::plan_create_sink(scx, stmt),
+        Statement::CreateTask(stmt) => ddl::plan_create_task(scx, stmt, params),
         Statement::CreateIndex(stmt) => ddl::plan_create_index(scx, stmt),
         Statement::CreateType(stmt) => ddl::plan_create_type(scx, stmt),
         Statement::CreateRole(stmt) => ddl::plan_create_role(scx, stmt),
         Statement::DropDatabase(stmt) => ddl::plan_drop_database(scx, stmt),
         Statement::DropObjects(stmt) => ddl::plan_drop_objects(scx, stmt),
         Statement::AlterIndexOptions(stmt) => ddl::plan_alter_index_options(scx, stmt),
+        Statement::AlterSourceOptions(stmt) => ddl::plan_alter_source_options(scx, stmt),
+        Statement::AlterSourceSchema(stmt) => ddl::plan_alter_source_schema(scx, stmt),
+        Statement::AlterSystemSet(stmt) => scl::plan_alter_system_set(scx, stmt),
+        Statement::AlterSystemReset(stmt) => scl::plan_alter_system_reset(scx, stmt),
+        Statement::AlterRoleSet(stmt) => scl::plan_alter_role_set(scx, stmt),
+        Statement::AlterRoleReset(stmt) => scl::plan_alter_role_reset(scx, stmt),
         Statement::AlterObjectRename(stmt) => ddl::plan_alter_object_rename(scx, stmt),
 
         // DML statements.
@@ -225,6 +247,7 @@ pub fn plan(
 
         // SCL statements.
         Statement::SetVariable(stmt) => scl::plan_set_variable(scx, stmt),
+        Statement::ResetVariable(stmt) => scl::plan_reset_variable(scx, stmt),
         Statement::ShowVariable(stmt) => scl::plan_show_variable(scx, stmt),
         Statement::Discard(stmt) => scl::plan_discard(scx, stmt),
         Statement::Declare(stmt) => scl::plan_declare(scx, stmt),
@@ -236,7 +259,8 @@ pub fn plan(
         Statement::SetTransaction(stmt) => tcl::plan_set_transaction(scx, stmt),
         Statement::Rollback(stmt) => tcl::plan_rollback(scx, stmt),
         Statement::Commit(stmt) => tcl::plan_commit(scx, stmt),
-    }
+    }?;
+    Ok((plan, scx.notices.replace(vec![])))
 }
 
 /// Whether a SQL object type can be interpreted as matching the type of the given catalog item.
@@ -251,6 +275,7 @@ impl PartialEq<ObjectType> for CatalogItemType {
             (CatalogItemType::Source, ObjectType::Source)
             | (CatalogItemType::Table, ObjectType::Table)
             | (CatalogItemType::Sink, ObjectType::Sink)
+            | (CatalogItemType::Task, ObjectType::Task)
             | (CatalogItemType::View, ObjectType::View)
             | (CatalogItemType::Index, ObjectType::Index)
             | (CatalogItemType::Type, ObjectType::Type) => true,
@@ -274,9 +299,17 @@ pub struct StatementContext<'a> {
     /// The types of the parameters in the query. This is filled in as planning
     /// occurs.
     pub param_types: Rc<RefCell<BTreeMap<usize, ScalarType>>>,
+    /// The notices generated while planning the statement. This is filled in
+    /// as planning occurs.
+    pub notices: Rc<RefCell<Vec<PlanNotice>>>,
 }
 
 impl<'a> StatementContext<'a> {
+    /// Records a notice to be reported to the user once planning completes.
+    pub fn add_notice(&self, notice: PlanNotice) {
+        self.notices.borrow_mut().push(notice);
+    }
+
     pub fn allocate_name(&self, name: PartialName) -> FullName {
         FullName {
             database: match name.database {
@@ -364,6 +397,23 @@ impl<'a> StatementContext<'a> {
         Ok(())
     }
 
+    /// Returns an error unless `feature` is enabled, either by its feature
+    /// flag or by experimental mode.
+    pub fn require_feature(
+        &self,
+        feature: Feature,
+        feature_name: &str,
+    ) -> Result<(), anyhow::Error> {
+        if !self.experimental_mode() && !self.catalog.feature_enabled(feature) {
+            bail!(
+                "{} is an experimental feature; enable it with SET {} = on",
+                feature_name,
+                feature.flag_name()
+            )
+        }
+        Ok(())
+    }
+
     pub fn finalize_param_types(self) -> Result<Vec<ScalarType>, anyhow::Error> {
         let param_types = Rc::try_unwrap(self.param_types).unwrap().into_inner();
         let mut out = vec![];
@@ -30,13 +30,14 @@ use repr::ColumnName;
 use reqwest::Url;
 
 use dataflow_types::{
-    AvroEncoding, AvroOcfEncoding, AvroOcfSinkConnectorBuilder, Consistency, CsvEncoding,
-    DataEncoding, ExternalSourceConnector, FileSourceConnector, KafkaSinkConnectorBuilder,
-    KafkaSourceConnector, KinesisSourceConnector, PostgresSourceConnector, ProtobufEncoding,
-    RegexEncoding, S3SourceConnector, SinkConnectorBuilder, SinkEnvelope, SourceConnector,
-    SourceEnvelope,
+    AvroEncoding, AvroOcfEncoding, AvroOcfSinkConnectorBuilder, BytesEncoding, Consistency,
+    CsvEncoding, DataEncoding, DeduplicateKeep, EventTime, ExternalSourceConnector,
+    FileSourceConnector, Framing, InvalidBytes, KafkaSinkConnectorBuilder, KafkaSourceConnector,
+    KinesisSourceConnector, PostgresSourceConnector, ProtobufEncoding, RegexEncoding,
+    S3SourceConnector, SinkConnectorBuilder, SinkEnvelope, SourceConnector, SourceEnvelope,
+    TextEncoding, Transcoding, UpsertMergeStrategy,
 };
-use expr::GlobalId;
+use expr::{ForeignKey, GlobalId, MirScalarExpr};
 use interchange::avro::{self, DebeziumDeduplicationStrategy, Encoder};
 use interchange::envelopes;
 use ore::collections::CollectionExt;
@@ -46,13 +47,15 @@ use repr::{strconv, RelationDesc, RelationType, ScalarType};
 
 use crate::ast::display::AstDisplay;
 use crate::ast::{
-    AlterIndexOptionsList, AlterIndexOptionsStatement, AlterObjectRenameStatement, AvroSchema,
+    AlterIndexOptionsList, AlterIndexOptionsStatement, AlterObjectRenameStatement,
+    AlterSourceOptionsStatement, AlterSourceSchemaStatement, AvroSchema, BytesFraming,
     ColumnOption, Compression, Connector, CreateDatabaseStatement, CreateIndexStatement,
     CreateRoleOption, CreateRoleStatement, CreateSchemaStatement, CreateSinkStatement,
-    CreateSourceStatement, CreateTableStatement, CreateTypeAs, CreateTypeStatement,
-    CreateViewStatement, DataType, DropDatabaseStatement, DropObjectsStatement, Envelope, Expr,
-    Format, Ident, IfExistsBehavior, ObjectType, Raw, SqlOption, Statement, UnresolvedObjectName,
-    Value, WithOption,
+    CreateSourceStatement, CreateTableStatement, CreateTaskStatement, CreateTypeAs,
+    CreateTypeStatement, CreateViewStatement, DataType, DropDatabaseStatement,
+    DropObjectsStatement, Envelope, Expr, Format, Ident, IfExistsBehavior, InsertStatement,
+    KeyConstraint, ObjectType, Raw, RegexColumnType, SqlOption, Statement, TableConstraint,
+    UnresolvedObjectName, Value, WithOption,
 };
 use crate::catalog::{CatalogItem, CatalogItemType};
 use crate::kafka_util;
@@ -60,11 +63,12 @@ use crate::names::{DatabaseSpecifier, FullName, SchemaName};
 use crate::normalize;
 use crate::plan::error::PlanError;
 use crate::plan::expr::{ColumnRef, HirScalarExpr, JoinKind};
+use crate::plan::notice::PlanNotice;
 use crate::plan::query::{resolve_names_data_type, QueryLifetime};
 use crate::plan::statement::{StatementContext, StatementDesc};
 use crate::plan::{
     self, plan_utils, query, HirRelationExpr, Index, IndexOption, IndexOptionName, Params, Plan,
-    Sink, Source, Table, Type, TypeInner, View,
+    Sink, Source, Table, TableCheck, Task, Type, TypeInner, View,
 };
 use crate::pure::Schema;
 
@@ -144,9 +148,6 @@ pub fn plan_create_table(
     if !with_options.is_empty() {
         unsupported!("WITH options");
     }
-    if !constraints.is_empty() {
-        unsupported!("CREATE TABLE with constraints")
-    }
 
     let names: Vec<_> = columns
         .iter()
@@ -165,15 +166,25 @@ pub fn plan_create_table(
     let mut column_types = Vec::with_capacity(columns.len());
     let mut defaults = Vec::with_capacity(columns.len());
     let mut depends_on = Vec::new();
+    // The declared name, if any, and the condition of each CHECK constraint.
+    let mut check_exprs = Vec::new();
 
-    for c in columns {
+    for (c, column_name) in columns.iter().zip(&names) {
         let (aug_data_type, ids) = resolve_names_data_type(scx, c.data_type.clone())?;
         let ty = plan::scalar_type_from_sql(scx, &aug_data_type)?;
-        let mut nullable = true;
+        let mut nullable = None;
         let mut default = Expr::null();
         for option in &c.options {
             match &option.option {
-                ColumnOption::NotNull => nullable = false,
+                ColumnOption::Null | ColumnOption::NotNull => {
+                    let option_nullable = matches!(option.option, ColumnOption::Null);
+                    if nullable.replace(option_nullable) == Some(!option_nullable) {
+                        bail!(
+                            "conflicting NULL/NOT NULL declarations for column {}",
+                            column_name.as_str().quoted()
+                        );
+                    }
+                }
                 ColumnOption::Default(expr) => {
                     // Ensure expression can be planned and yields the correct
                     // type.
@@ -181,14 +192,24 @@ pub fn plan_create_table(
                     depends_on.extend(expr_depends_on);
                     default = expr.clone();
                 }
+                ColumnOption::Check(expr) => check_exprs.push((option.name.clone(), expr.clone())),
                 other => unsupported!(format!("CREATE TABLE with column constraint: {}", other)),
             }
         }
-        column_types.push(ty.nullable(nullable));
+        column_types.push(ty.nullable(nullable.unwrap_or(true)));
         defaults.push(default);
         depends_on.extend(ids);
     }
 
+    for constraint in constraints {
+        match constraint {
+            TableConstraint::Check { name, expr } => {
+                check_exprs.push((name.clone(), (**expr).clone()))
+            }
+            other => unsupported!(format!("CREATE TABLE with constraint: {}", other)),
+        }
+    }
+
     let typ = RelationType::new(column_types);
 
     let temporary = *temporary;
@@ -197,13 +218,54 @@ pub fn plan_create_table(
     } else {
         scx.allocate_name(normalize::unresolved_object_name(name.to_owned())?)
     };
-    let desc = RelationDesc::new(typ, names.into_iter().map(Some));
+    let desc = RelationDesc::new(typ, names.iter().cloned().map(Some));
+
+    // Plan the CHECK constraints. As in PostgreSQL, an unnamed constraint is
+    // named after the table and, if it references exactly one column, that
+    // column, and is suffixed with a number if that name is already taken.
+    let mut checks: Vec<TableCheck> = Vec::with_capacity(check_exprs.len());
+    for (check_name, expr) in check_exprs {
+        let (expr, expr_depends_on) = query::plan_check_constraint(scx, &desc, expr)?;
+        depends_on.extend(expr_depends_on);
+        let check_name = match check_name {
+            Some(check_name) => {
+                let check_name = normalize::ident(check_name);
+                if checks.iter().any(|check| check.name == check_name) {
+                    bail!(
+                        "constraint {} for relation {} already exists",
+                        check_name.quoted(),
+                        name.item.quoted()
+                    );
+                }
+                check_name
+            }
+            None => {
+                let support = expr.support();
+                let prefix = match support.iter().exactly_one() {
+                    Ok(column) => format!("{}_{}_check", name.item, names[*column]),
+                    Err(_) => format!("{}_check", name.item),
+                };
+                let mut check_name = prefix.clone();
+                let mut i = 0;
+                while checks.iter().any(|check| check.name == check_name) {
+                    i += 1;
+                    check_name = format!("{}{}", prefix, i);
+                }
+                check_name
+            }
+        };
+        checks.push(TableCheck {
+            name: check_name,
+            expr,
+        });
+    }
 
     let create_sql = normalize::create_statement(&scx, Statement::CreateTable(stmt.clone()))?;
     let table = Table {
         create_sql,
         desc,
         defaults,
+        checks,
         temporary,
     };
     Ok(Plan::CreateTable {
@@ -280,6 +342,7 @@ pub fn plan_create_source(
     let CreateSourceStatement {
         name,
         col_names,
+        key_constraints,
         connector,
         with_options,
         format,
@@ -293,7 +356,31 @@ pub fn plan_create_source(
             .ok_or_else(|| anyhow!("Source format must be specified"))?;
 
         Ok(match format {
-            Format::Bytes => DataEncoding::Bytes,
+            Format::Bytes { framing } => {
+                let framing = match framing {
+                    None => Framing::default(),
+                    Some(BytesFraming::Delimited(delimiter)) => {
+                        if delimiter.is_empty() {
+                            bail!("FORMAT BYTES delimiter must not be empty");
+                        }
+                        Framing::Delimited(delimiter.as_bytes().to_vec())
+                    }
+                    Some(BytesFraming::LengthPrefixed(width)) => match width {
+                        1 | 2 | 4 | 8 => Framing::LengthPrefixed(*width as usize),
+                        _ => bail!(
+                            "FORMAT BYTES length prefix must be 1, 2, 4, or 8 bytes wide, but got {}",
+                            width
+                        ),
+                    },
+                    Some(BytesFraming::FixedWidth(width)) => {
+                        if *width == 0 {
+                            bail!("FORMAT BYTES record width must be greater than zero");
+                        }
+                        Framing::FixedWidth(*width as usize)
+                    }
+                };
+                DataEncoding::Bytes(BytesEncoding { framing })
+            }
             Format::Avro(schema) => {
                 let Schema {
                     key_schema,
@@ -378,9 +465,45 @@ pub fn plan_create_source(
                     message_name: message_name.to_owned(),
                 })
             }
-            Format::Regex(regex) => {
+            Format::Regex {
+                regex,
+                types,
+                strict,
+            } => {
                 let regex = Regex::new(regex)?;
-                DataEncoding::Regex(RegexEncoding { regex })
+                let mut group_types = BTreeMap::new();
+                for RegexColumnType { name, data_type } in types {
+                    let name = normalize::ident(name.clone());
+                    let (data_type, ids) = resolve_names_data_type(scx, data_type.clone())?;
+                    if ids.iter().any(|id| id.is_user()) {
+                        unsupported!("custom types in FORMAT REGEX");
+                    }
+                    let ty = plan::scalar_type_from_sql(scx, &data_type)?;
+                    if group_types.insert(name.clone(), ty).is_some() {
+                        bail!("type for capture group {} specified more than once", name);
+                    }
+                }
+                // Groups without a type annotation are text, as they
+                // have always been.
+                let casts = regex
+                    .capture_names()
+                    .skip(1)
+                    .map(
+                        |name| match name.and_then(|name| group_types.remove(name)) {
+                            Some(ty) => query::plan_cast_from_string(scx, "FORMAT REGEX", &ty),
+                            None => Ok(MirScalarExpr::Column(0)),
+                        },
+                    )
+                    .collect::<Result<_, _>>()?;
+                if let Some(name) = group_types.keys().next() {
+                    bail!("regex has no capture group named {}", name);
+                }
+                DataEncoding::Regex(RegexEncoding {
+                    regex,
+                    casts,
+                    strict: *strict,
+                    transcoding: None,
+                })
             }
             Format::Csv {
                 header_row,
@@ -405,17 +528,32 @@ pub fn plan_create_source(
                         0..=127 => *delimiter as u8,
                         _ => bail!("CSV delimiter must be an ASCII character"),
                     },
+                    transcoding: None,
                 })
             }
             Format::Json => unsupported!("JSON sources"),
-            Format::Text => DataEncoding::Text,
+            Format::Text => DataEncoding::Text(TextEncoding::default()),
         })
     };
 
+    // Only file sources read a continuous stream of bytes that needs to be
+    // split into records; other sources receive discrete messages.
+    let framed =
+        |format: &Option<Format<Raw>>| matches!(format, Some(Format::Bytes { framing: Some(_) }));
+    let key_framed = match envelope {
+        Envelope::Upsert(key_format) => framed(key_format),
+        _ => false,
+    };
+    if (framed(format) && !matches!(connector, Connector::File { .. })) || key_framed {
+        unsupported!("FORMAT BYTES framing for non-file sources");
+    }
+
     let mut with_options = normalize::options(with_options);
+    let retention = extract_retention_options(&mut with_options)?;
+    let event_time = extract_event_time_options(&mut with_options)?;
 
     let mut consistency = Consistency::RealTime;
-    let mut ts_frequency = Duration::from_secs(1);
+    let ts_frequency = extract_timestamp_frequency_option(&mut with_options)?;
 
     let (external_connector, mut encoding) = match connector {
         Connector::Kafka { broker, topic, .. } => {
@@ -433,8 +571,6 @@ pub fn plan_create_source(
                 Some(_) => bail!("group_id_prefix must be a string"),
             };
 
-            ts_frequency = extract_timestamp_frequency_option(&mut with_options)?;
-
             // THIS IS EXPERIMENTAL - DO NOT DOCUMENT IT
             // until we have had time to think about what the right UX/design is on a non-urgent timeline!
             // In particular, we almost certainly want the offsets to be specified per-partition.
@@ -518,14 +654,10 @@ pub fn plan_create_source(
                 Some(Value::String(topic)) => Consistency::BringYourOwn(topic),
                 Some(_) => bail!("consistency must be a string"),
             };
-            ts_frequency = extract_timestamp_frequency_option(&mut with_options)?;
 
             let connector = ExternalSourceConnector::File(FileSourceConnector {
                 path: path.clone().into(),
-                compression: match compression {
-                    Compression::Gzip => dataflow_types::Compression::Gzip,
-                    Compression::None => dataflow_types::Compression::None,
-                },
+                compression: plan_compression(compression),
                 tail,
             });
             let encoding = get_encoding(format)?;
@@ -534,6 +666,7 @@ pub fn plan_create_source(
         Connector::S3 {
             key_sources,
             pattern,
+            compression,
         } => {
             scx.require_experimental_mode("S3 Sources")?;
             let aws_info = normalize::aws_connect_info(&mut with_options, None)?;
@@ -565,6 +698,7 @@ pub fn plan_create_source(
                     })
                     .transpose()?,
                 aws_info,
+                compression: plan_compression(compression),
             });
             let encoding = get_encoding(format)?;
             (connector, encoding)
@@ -632,8 +766,6 @@ pub fn plan_create_source(
                 Some(_) => bail!("consistency must be a string"),
             };
 
-            ts_frequency = extract_timestamp_frequency_option(&mut with_options)?;
-
             let connector = ExternalSourceConnector::AvroOcf(FileSourceConnector {
                 path: path.clone().into(),
                 compression: dataflow_types::Compression::None,
@@ -670,7 +802,7 @@ pub fn plan_create_source(
     // a schema used to decode records.
 
     // TODO: remove bails as more support for upsert is added.
-    let envelope = match &envelope {
+    let mut envelope = match &envelope {
         sql_parser::ast::Envelope::None => SourceEnvelope::None,
         sql_parser::ast::Envelope::Debezium => {
             let dedup_strat = match with_options.remove("deduplication") {
@@ -731,10 +863,26 @@ pub fn plan_create_source(
                             *value_schema = key_schema.take().unwrap();
                         }
                     }
-                    DataEncoding::Bytes | DataEncoding::Text => {}
+                    DataEncoding::Bytes(_) | DataEncoding::Text(_) => {}
                     _ => unsupported!("format for upsert key"),
                 }
-                SourceEnvelope::Upsert(key_encoding)
+                let merge = match with_options.remove("upsert_merge") {
+                    None => UpsertMergeStrategy::Replace,
+                    Some(Value::String(s)) => match s.as_str() {
+                        "replace" => UpsertMergeStrategy::Replace,
+                        "json_merge_patch" => match &encoding {
+                            DataEncoding::Bytes(_) | DataEncoding::Text(_) => {
+                                UpsertMergeStrategy::JsonMergePatch
+                            }
+                            _ => bail!(
+                                "upsert_merge 'json_merge_patch' requires a TEXT or BYTES value format"
+                            ),
+                        },
+                        _ => bail!("upsert_merge must be one of 'replace' or 'json_merge_patch'"),
+                    },
+                    _ => bail!("upsert_merge must be one of 'replace' or 'json_merge_patch'"),
+                };
+                SourceEnvelope::Upsert(key_encoding, merge)
             }
             _ => unsupported!("upsert envelope for non-Kafka sources"),
         },
@@ -751,14 +899,28 @@ pub fn plan_create_source(
             }
             SourceEnvelope::CdcV2
         }
+        sql_parser::ast::Envelope::Deduplicate { keep, .. } => {
+            if let Connector::Postgres { .. } = connector {
+                unsupported!("ENVELOPE NONE DEDUPLICATE for Postgres sources");
+            }
+            // The key columns are resolved below, once the final column
+            // names of the source are known.
+            SourceEnvelope::Deduplicate {
+                key_indices: vec![],
+                keep: match keep {
+                    sql_parser::ast::DeduplicateKeep::First => DeduplicateKeep::First,
+                    sql_parser::ast::DeduplicateKeep::Last => DeduplicateKeep::Last,
+                },
+            }
+        }
     };
 
-    if let SourceEnvelope::Upsert(key_encoding) = &envelope {
+    if let SourceEnvelope::Upsert(key_encoding, _) = &envelope {
         match &mut encoding {
             DataEncoding::Avro(AvroEncoding { key_schema, .. }) => {
                 *key_schema = None;
             }
-            DataEncoding::Bytes | DataEncoding::Text => {
+            DataEncoding::Bytes(_) | DataEncoding::Text(_) => {
                 if let DataEncoding::Avro(_) = &key_encoding {
                     unsupported!("Avro key for this format");
                 }
@@ -767,6 +929,30 @@ pub fn plan_create_source(
         }
     }
 
+    if let Some(transcoding) = extract_transcoding(&mut with_options)? {
+        if let SourceEnvelope::Upsert(..) = envelope {
+            unsupported!("the encoding option with ENVELOPE UPSERT");
+        }
+        // S3 sources split objects into lines at every `\n` byte, which is
+        // only correct for character sets that encode a newline that way.
+        if let (Connector::S3 { .. }, Framing::AlignedDelimited { .. }) =
+            (connector, transcoding.line_framing())
+        {
+            unsupported!(format!(
+                "the {} encoding for S3 sources",
+                transcoding.charset
+            ));
+        }
+        match &mut encoding {
+            DataEncoding::Text(TextEncoding { transcoding: t })
+            | DataEncoding::Csv(CsvEncoding { transcoding: t, .. })
+            | DataEncoding::Regex(RegexEncoding { transcoding: t, .. }) => {
+                *t = Some(transcoding);
+            }
+            _ => bail!("the encoding option requires a TEXT, CSV, or REGEX format"),
+        }
+    }
+
     let mut bare_desc = encoding.desc(&envelope)?;
     let ignore_source_keys = match with_options.remove("ignore_source_keys") {
         None => false,
@@ -787,6 +973,10 @@ pub fn plan_create_source(
                         .map(Some)
                         .unwrap_or_else(|e| {
                             warn!("Not using key due to error: {}", e);
+                            scx.add_notice(PlanNotice::SourceKeyIgnored {
+                                source: name.to_string(),
+                                error: e.to_string(),
+                            });
                             None
                         })
                 });
@@ -815,12 +1005,66 @@ pub fn plan_create_source(
         }
     }
 
+    if let (
+        sql_parser::ast::Envelope::Deduplicate { key, .. },
+        SourceEnvelope::Deduplicate { key_indices, .. },
+    ) = (&stmt.envelope, &mut envelope)
+    {
+        for ident in key {
+            let column_name = normalize::column_name(ident.clone());
+            let (i, _) = match bare_desc.get_by_name(&column_name) {
+                Some(column) => column,
+                None => bail!(
+                    "DEDUPLICATE BY column {} does not exist in source {}",
+                    column_name.as_str().quoted(),
+                    name
+                ),
+            };
+            if key_indices.contains(&i) {
+                bail!(
+                    "DEDUPLICATE BY column {} specified more than once",
+                    column_name.as_str().quoted()
+                );
+            }
+            key_indices.push(i);
+        }
+        // At most one record per key is ever present in the source.
+        bare_desc = bare_desc.with_key(key_indices.clone());
+    }
+
+    let event_time = match event_time {
+        None => None,
+        Some(_) if !matches!(envelope, SourceEnvelope::None) => {
+            bail!("event_time_column is only supported with ENVELOPE NONE")
+        }
+        Some(_) if !matches!(consistency, Consistency::RealTime) => {
+            bail!("event_time_column cannot be combined with consistency")
+        }
+        Some(event_time) => Some(plan_event_time(scx, &bare_desc, event_time)?),
+    };
+
     let if_not_exists = *if_not_exists;
     let materialized = *materialized;
+    let key_constraints = key_constraints.clone();
     let name = scx.allocate_name(normalize::unresolved_object_name(name.clone())?);
     let create_sql = normalize::create_statement(&scx, Statement::CreateSource(stmt))?;
 
-    let (expr, column_names) = plan_source_envelope(&bare_desc, &envelope, post_transform_key);
+    let (mut expr, column_names) = plan_source_envelope(&bare_desc, &envelope, post_transform_key);
+    let (primary_key, foreign_keys) = plan_key_constraints(
+        scx,
+        format!("source {}", name),
+        &RelationDesc::new(expr.typ(), column_names.clone()),
+        &key_constraints,
+    )?;
+    if let Some(key) = primary_key {
+        expr = expr.declare_keys(vec![key]);
+    }
+    let depends_on = foreign_keys.iter().map(|fk| fk.parent).collect();
+    if let Some(retention) = retention {
+        let desc = RelationDesc::new(expr.typ(), column_names.clone());
+        let predicate = plan_retention_predicate(scx, &desc, retention)?;
+        expr = expr.filter(vec![predicate]);
+    }
     let source = Source {
         create_sql,
         connector: SourceConnector::External {
@@ -829,10 +1073,12 @@ pub fn plan_create_source(
             envelope,
             consistency,
             ts_frequency,
+            event_time,
         },
         expr,
         bare_desc,
         column_names,
+        foreign_keys,
     };
 
     if !with_options.is_empty() {
@@ -847,6 +1093,7 @@ pub fn plan_create_source(
         source,
         if_not_exists,
         materialized,
+        depends_on,
     })
 }
 
@@ -866,14 +1113,39 @@ pub fn plan_create_view(
     let CreateViewStatement {
         name,
         columns,
+        key_constraints,
         query,
         temporary,
         materialized,
         if_exists,
         with_options,
     } = &mut stmt;
+    let mut with_options = normalize::options(with_options);
+    let prefer_delta_joins = match with_options.remove("prefer_delta_joins") {
+        None => false,
+        Some(Value::Boolean(b)) => b,
+        Some(_) => bail!("prefer_delta_joins must be a boolean"),
+    };
+    let lazy = match with_options.remove("lazy") {
+        None => false,
+        Some(Value::Boolean(b)) => b,
+        Some(_) => bail!("lazy must be a boolean"),
+    };
+    let reduce_hot_set_size = match with_options.remove("reduce_hot_set_size") {
+        None => None,
+        Some(Value::Number(n)) => match n.parse::<usize>() {
+            Ok(0) => bail!("reduce_hot_set_size must be greater than zero"),
+            Ok(n) => Some(n),
+            _ => bail!("reduce_hot_set_size must be a positive integer"),
+        },
+        Some(_) => bail!("reduce_hot_set_size must be a positive integer"),
+    };
+    let retention = extract_retention_options(&mut with_options)?;
     if !with_options.is_empty() {
-        unsupported!("WITH options");
+        bail!(
+            "unexpected parameters for CREATE VIEW: {}",
+            with_options.keys().join(",")
+        )
     }
     let name = if *temporary {
         scx.allocate_temporary_name(normalize::unresolved_object_name(name.to_owned())?)
@@ -884,12 +1156,12 @@ pub fn plan_create_view(
         mut expr,
         mut desc,
         finishing,
-        depends_on,
+        mut depends_on,
     } = query::plan_root_query(scx, query.clone(), QueryLifetime::Static)?;
     expr.bind_parameters(&params)?;
     //TODO: materialize#724 - persist finishing information with the view?
     expr.finish(finishing);
-    let relation_expr = expr.lower();
+    let mut relation_expr = expr.lower();
     let replace = if *if_exists == IfExistsBehavior::Replace {
         if let Ok(item) = scx.catalog.resolve_item(&name.clone().into()) {
             if relation_expr.global_uses().contains(&item.id()) {
@@ -907,6 +1179,20 @@ pub fn plan_create_view(
         None
     };
     desc = plan_utils::maybe_rename_columns(format!("view {}", name), desc, columns)?;
+    let (primary_key, foreign_keys) =
+        plan_key_constraints(scx, format!("view {}", name), &desc, key_constraints)?;
+    if let Some(key) = primary_key {
+        relation_expr = relation_expr.declare_keys(vec![key]);
+    }
+    for fk in &foreign_keys {
+        if !depends_on.contains(&fk.parent) {
+            depends_on.push(fk.parent);
+        }
+    }
+    if let Some(retention) = retention {
+        let predicate = plan_retention_predicate(scx, &desc, retention)?;
+        relation_expr = relation_expr.filter(vec![predicate]);
+    }
     let temporary = *temporary;
     let materialize = *materialized; // Normalize for `raw_sql` below.
     let if_not_exists = *if_exists == IfExistsBehavior::Skip;
@@ -917,6 +1203,10 @@ pub fn plan_create_view(
             expr: relation_expr,
             column_names: desc.iter_names().map(|n| n.cloned()).collect(),
             temporary,
+            prefer_delta_joins,
+            lazy,
+            reduce_hot_set_size,
+            foreign_keys,
         },
         replace,
         materialize,
@@ -925,6 +1215,262 @@ pub fn plan_create_view(
     })
 }
 
+/// Plans the key constraints declared on the source or view described by
+/// `desc`, returning its declared primary key, if any, and its declared
+/// foreign keys.
+fn plan_key_constraints(
+    scx: &StatementContext,
+    context: String,
+    desc: &RelationDesc,
+    key_constraints: &[KeyConstraint],
+) -> Result<(Option<Vec<usize>>, Vec<ForeignKey>), anyhow::Error> {
+    let mut primary_key = None;
+    let mut foreign_keys = vec![];
+    for key_constraint in key_constraints {
+        match key_constraint {
+            KeyConstraint::PrimaryKeyNotEnforced { columns } => {
+                if primary_key.is_some() {
+                    bail!("multiple primary keys for {} are not allowed", context);
+                }
+                let key = plan_utils::plan_key_columns("PRIMARY KEY", &context, desc, columns)?;
+                primary_key = Some(key);
+            }
+            KeyConstraint::ForeignKeyNotEnforced {
+                columns,
+                foreign_table,
+                referred_columns,
+            } => {
+                let key = plan_utils::plan_key_columns("FOREIGN KEY", &context, desc, columns)?;
+                let parent = scx.resolve_item(foreign_table.clone())?;
+                match parent.item_type() {
+                    CatalogItemType::Table | CatalogItemType::Source | CatalogItemType::View => (),
+                    CatalogItemType::Func
+                    | CatalogItemType::Index
+                    | CatalogItemType::Sink
+                    | CatalogItemType::Task
+                    | CatalogItemType::Type => bail!(
+                        "{} cannot be referenced by a foreign key because it is a {}",
+                        parent.name(),
+                        parent.item_type()
+                    ),
+                }
+                let parent_desc = parent.desc()?;
+                let parent_context = format!("{} {}", parent.item_type(), parent.name());
+                let parent_key = plan_utils::plan_key_columns(
+                    "FOREIGN KEY",
+                    &parent_context,
+                    parent_desc,
+                    referred_columns,
+                )?;
+                if key.len() != parent_key.len() {
+                    bail!("number of referencing and referenced columns for foreign key disagree");
+                }
+                for i in 0..key.len() {
+                    let typ = &desc.typ().column_types[key[i]].scalar_type;
+                    let parent_typ = &parent_desc.typ().column_types[parent_key[i]].scalar_type;
+                    if typ != parent_typ {
+                        bail!(
+                            "foreign key columns {} and {} are of incompatible types: {} and {}",
+                            normalize::column_name(columns[i].clone()).as_str().quoted(),
+                            normalize::column_name(referred_columns[i].clone())
+                                .as_str()
+                                .quoted(),
+                            scx.humanize_scalar_type(typ),
+                            scx.humanize_scalar_type(parent_typ),
+                        );
+                    }
+                }
+                // The referenced columns must determine at most one record of
+                // the parent, or joins against it could multiply records.
+                let is_key = parent_desc.typ().keys.iter().any(|k| {
+                    k.len() == parent_key.len() && k.iter().all(|c| parent_key.contains(c))
+                });
+                if !is_key {
+                    bail!(
+                        "there is no primary key matching given keys for referenced {}",
+                        parent_context
+                    );
+                }
+                foreign_keys.push(ForeignKey {
+                    columns: key,
+                    parent: parent.id(),
+                    parent_columns: parent_key,
+                });
+            }
+        }
+    }
+    Ok((primary_key, foreign_keys))
+}
+
+/// The `retention` and `retention_column` options of a source or view.
+struct Retention {
+    /// How long rows are retained.
+    window: Duration,
+    /// The column that records the time from which a row's retention is
+    /// measured, if specified.
+    column: Option<ColumnName>,
+}
+
+fn extract_retention_options(
+    with_options: &mut BTreeMap<String, Value>,
+) -> Result<Option<Retention>, anyhow::Error> {
+    let window = match with_options.remove("retention") {
+        None => None,
+        Some(Value::String(s)) => Some(parse_duration::parse(&s)?),
+        Some(_) => bail!("retention must be a string"),
+    };
+    let column = match with_options.remove("retention_column") {
+        None => None,
+        Some(Value::String(s)) => Some(ColumnName::from(s)),
+        Some(_) => bail!("retention_column must be a string"),
+    };
+    match window {
+        Some(window) => Ok(Some(Retention { window, column })),
+        None if column.is_some() => bail!("retention_column requires retention"),
+        None => Ok(None),
+    }
+}
+
+/// Plans a predicate that retains the rows of a relation described by `desc`
+/// until the retention window has passed since the time recorded in the
+/// retention column.
+///
+/// The predicate is a temporal filter, and so rows are retracted as the
+/// logical timestamp advances with the system clock. If no retention column
+/// is specified, the relation must have exactly one column of a time type.
+fn plan_retention_predicate(
+    scx: &StatementContext,
+    desc: &RelationDesc,
+    Retention { window, column }: Retention,
+) -> Result<::expr::MirScalarExpr, anyhow::Error> {
+    let column = match column {
+        Some(column) => column,
+        None => {
+            let mut time_columns = desc.iter().filter(|(_, typ)| {
+                matches!(
+                    typ.scalar_type,
+                    ScalarType::Timestamp | ScalarType::TimestampTz
+                )
+            });
+            match (time_columns.next(), time_columns.next()) {
+                (Some((Some(name), _)), None) => name.clone(),
+                _ => bail!(
+                    "retention_column must be specified unless the view has exactly one \
+                     timestamp column"
+                ),
+            }
+        }
+    };
+    let typ = match desc.get_by_name(&column) {
+        Some((_, typ)) => typ,
+        None => bail!(
+            "retention_column {} does not exist",
+            column.as_str().quoted()
+        ),
+    };
+    let ident = Ident::new(column.as_str()).to_ast_string_stable();
+    // Temporal filters compare against the logical timestamp, which is
+    // measured in milliseconds since the Unix epoch.
+    let millis = match typ.scalar_type {
+        ScalarType::Timestamp | ScalarType::TimestampTz => {
+            format!("(extract(epoch FROM {}) * 1000)::numeric(38, 0)", ident)
+        }
+        ScalarType::Int32 | ScalarType::Int64 | ScalarType::Decimal(_, 0) => {
+            format!("{}::numeric(38, 0)", ident)
+        }
+        _ => bail!(
+            "retention_column {} must be a timestamp or an integer number of \
+             milliseconds since the Unix epoch, not {}",
+            column.as_str().quoted(),
+            scx.humanize_scalar_type(&typ.scalar_type)
+        ),
+    };
+    let predicate = sql_parser::parser::parse_expr(&format!(
+        "mz_catalog.mz_logical_timestamp() < {} + {}",
+        millis,
+        window.as_millis()
+    ))?;
+    let (predicate, _) = query::plan_index_predicate(scx, desc, predicate)?;
+    Ok(predicate)
+}
+
+/// The `event_time_column`, `event_time_lateness`, and `clock_skew` options of
+/// a source.
+struct EventTimeOptions {
+    column: ColumnName,
+    lateness: Option<Duration>,
+    clock_skew: Option<Duration>,
+}
+
+fn extract_event_time_options(
+    with_options: &mut BTreeMap<String, Value>,
+) -> Result<Option<EventTimeOptions>, anyhow::Error> {
+    let column = match with_options.remove("event_time_column") {
+        None => None,
+        Some(Value::String(s)) => Some(ColumnName::from(s)),
+        Some(_) => bail!("event_time_column must be a string"),
+    };
+    let lateness = match with_options.remove("event_time_lateness") {
+        None => None,
+        Some(Value::String(s)) => Some(parse_duration::parse(&s)?),
+        Some(_) => bail!("event_time_lateness must be a string"),
+    };
+    let clock_skew = match with_options.remove("clock_skew") {
+        None => None,
+        Some(Value::String(s)) => Some(parse_duration::parse(&s)?),
+        Some(_) => bail!("clock_skew must be a string"),
+    };
+    match column {
+        Some(column) => Ok(Some(EventTimeOptions {
+            column,
+            lateness,
+            clock_skew,
+        })),
+        None if lateness.is_some() => bail!("event_time_lateness requires event_time_column"),
+        None if clock_skew.is_some() => bail!("clock_skew requires event_time_column"),
+        None => Ok(None),
+    }
+}
+
+/// Resolves the event-time column of a source described by `desc`.
+///
+/// Event times, like the logical timestamps they become, are measured in
+/// milliseconds since the Unix epoch, so the column must be a time type or an
+/// integer number of milliseconds.
+fn plan_event_time(
+    scx: &StatementContext,
+    desc: &RelationDesc,
+    EventTimeOptions {
+        column,
+        lateness,
+        clock_skew,
+    }: EventTimeOptions,
+) -> Result<EventTime, anyhow::Error> {
+    let (index, typ) = match desc.get_by_name(&column) {
+        Some(column) => column,
+        None => bail!(
+            "event_time_column {} does not exist",
+            column.as_str().quoted()
+        ),
+    };
+    if !matches!(
+        typ.scalar_type,
+        ScalarType::Timestamp | ScalarType::TimestampTz | ScalarType::Int32 | ScalarType::Int64
+    ) {
+        bail!(
+            "event_time_column {} must be a timestamp or an integer number of \
+             milliseconds since the Unix epoch, not {}",
+            column.as_str().quoted(),
+            scx.humanize_scalar_type(&typ.scalar_type)
+        );
+    }
+    Ok(EventTime {
+        column: index,
+        lateness: lateness.unwrap_or_default(),
+        clock_skew: clock_skew.unwrap_or_else(|| Duration::from_secs(1)),
+    })
+}
+
 #[allow(clippy::too_many_arguments)]
 fn kafka_sink_builder(
     format: Option<Format<Raw>>,
@@ -1077,6 +1623,7 @@ pub fn plan_create_sink(
         Some(Envelope::Upsert(None)) => SinkEnvelope::Upsert,
         Some(Envelope::CdcV2) => unsupported!("CDCv2 sinks"),
         Some(Envelope::None) => unsupported!("\"ENVELOPE NONE\" sinks"),
+        Some(Envelope::Deduplicate { .. }) => unsupported!("\"ENVELOPE NONE DEDUPLICATE\" sinks"),
         Some(Envelope::Upsert(Some(_))) => unsupported!("Upsert sinks with custom key encodings"),
     };
     let name = scx.allocate_name(normalize::unresolved_object_name(name)?);
@@ -1212,6 +1759,7 @@ pub fn plan_create_index(
         on_name,
         key_parts,
         with_options,
+        predicate,
         if_not_exists,
     } = &mut stmt;
     let on = scx.resolve_item(on_name.clone())?;
@@ -1247,6 +1795,14 @@ pub fn plan_create_index(
         }
     };
     let (keys, exprs_depend_on) = query::plan_index_exprs(scx, on_desc, filled_key_parts.clone())?;
+    let (predicates, predicate_depends_on) = match predicate {
+        Some(predicate) => {
+            let (predicate, depends_on) =
+                query::plan_index_predicate(scx, on_desc, predicate.clone())?;
+            (vec![predicate], depends_on)
+        }
+        None => (vec![], vec![]),
+    };
 
     let index_name = if let Some(name) = name {
         FullName {
@@ -1305,6 +1861,7 @@ pub fn plan_create_index(
     let create_sql = normalize::create_statement(scx, Statement::CreateIndex(stmt))?;
     let mut depends_on = vec![on.id()];
     depends_on.extend(exprs_depend_on);
+    depends_on.extend(predicate_depends_on);
 
     Ok(Plan::CreateIndex {
         name: index_name,
@@ -1312,6 +1869,7 @@ pub fn plan_create_index(
             create_sql,
             on: on.id(),
             keys,
+            predicates,
         },
         options,
         if_not_exists,
@@ -1433,12 +1991,65 @@ pub fn plan_create_type(
     })
 }
 
+pub fn describe_create_task(
+    _: &StatementContext,
+    _: CreateTaskStatement<Raw>,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_create_task(
+    scx: &StatementContext,
+    stmt: CreateTaskStatement<Raw>,
+    params: &Params,
+) -> Result<Plan, anyhow::Error> {
+    let create_sql = normalize::create_statement(scx, Statement::CreateTask(stmt.clone()))?;
+    let CreateTaskStatement {
+        name,
+        schedule,
+        insert:
+            InsertStatement {
+                table_name,
+                columns,
+                source,
+            },
+        if_not_exists,
+    } = stmt;
+    let name = scx.allocate_name(normalize::unresolved_object_name(name)?);
+    let schedule = match parse_duration::parse(&schedule) {
+        Ok(schedule) if schedule > Duration::from_secs(0) => schedule,
+        _ => bail!(
+            "invalid task schedule {}: must be a positive interval",
+            schedule.quoted()
+        ),
+    };
+    let (target, mut expr) = query::plan_insert_query(scx, table_name, columns, source)?;
+    expr.bind_parameters(&params)?;
+    let query = expr.lower();
+    let mut depends_on = query.global_uses();
+    depends_on.push(target);
+    depends_on.sort();
+    depends_on.dedup();
+    Ok(Plan::CreateTask {
+        name,
+        task: Task {
+            create_sql,
+            target,
+            query,
+            schedule,
+        },
+        if_not_exists,
+        depends_on,
+    })
+}
+
 fn extract_timestamp_frequency_option(
     with_options: &mut BTreeMap<String, Value>,
 ) -> Result<Duration, anyhow::Error> {
     match with_options.remove("timestamp_frequency_ms") {
         None => Ok(Duration::from_secs(1)),
         Some(Value::Number(n)) => match n.parse::<u64>() {
+            Ok(0) => bail!("timestamp_frequency_ms must be greater than zero"),
             Ok(n) => Ok(Duration::from_millis(n)),
             _ => bail!("timestamp_frequency_ms must be an u64"),
         },
@@ -1446,6 +2057,47 @@ fn extract_timestamp_frequency_option(
     }
 }
 
+/// Extracts the `encoding` and `encoding_errors` options, which describe how
+/// to convert a text source's data to UTF-8.
+///
+/// Specifying only `encoding_errors` converts from UTF-8, which replaces or
+/// drops invalid UTF-8 sequences rather than the entire record.
+fn extract_transcoding(
+    with_options: &mut BTreeMap<String, Value>,
+) -> Result<Option<Transcoding>, anyhow::Error> {
+    let charset = match with_options.remove("encoding") {
+        None => None,
+        Some(Value::String(s)) => Some(s),
+        Some(_) => bail!("encoding must be a string"),
+    };
+    let invalid_bytes = match with_options.remove("encoding_errors") {
+        None => None,
+        Some(Value::String(s)) => match s.as_str() {
+            "strict" => Some(InvalidBytes::Strict),
+            "replace" => Some(InvalidBytes::Replace),
+            "ignore" => Some(InvalidBytes::Ignore),
+            _ => bail!("encoding_errors must be one of 'strict', 'replace', or 'ignore'"),
+        },
+        Some(_) => bail!("encoding_errors must be a string"),
+    };
+    if charset.is_none() && invalid_bytes.is_none() {
+        return Ok(None);
+    }
+    let charset = charset.as_deref().unwrap_or("utf8");
+    let invalid_bytes = invalid_bytes.unwrap_or(InvalidBytes::Strict);
+    Ok(Some(Transcoding::new(charset, invalid_bytes)?))
+}
+
+fn plan_compression(compression: &Compression) -> dataflow_types::Compression {
+    match compression {
+        Compression::Gzip => dataflow_types::Compression::Gzip,
+        Compression::Zstd => dataflow_types::Compression::Zstd,
+        Compression::Bzip2 => dataflow_types::Compression::Bzip2,
+        Compression::Auto => dataflow_types::Compression::Auto,
+        Compression::None => dataflow_types::Compression::None,
+    }
+}
+
 pub fn describe_create_role(
     _: &StatementContext,
     _: CreateRoleStatement,
@@ -1540,6 +2192,7 @@ pub fn plan_drop_objects(
         | ObjectType::View
         | ObjectType::Index
         | ObjectType::Sink
+        | ObjectType::Task
         | ObjectType::Type => plan_drop_items(scx, object_type, if_exists, names, cascade),
         ObjectType::Role => plan_drop_role(scx, if_exists, names),
         ObjectType::Object => unreachable!("cannot drop generic OBJECT, must provide object type"),
@@ -1675,6 +2328,7 @@ pub fn plan_drop_item(
                     | CatalogItemType::Source
                     | CatalogItemType::View
                     | CatalogItemType::Sink
+                    | CatalogItemType::Task
                     | CatalogItemType::Type => {
                         bail!(
                             "cannot drop {}: still depended upon by catalog item '{}'",
@@ -1693,6 +2347,7 @@ pub fn plan_drop_item(
 with_options! {
     struct IndexWithOptions {
         logical_compaction_window: String,
+        refresh_interval: String,
     }
 }
 
@@ -1716,6 +2371,18 @@ fn plan_index_options(with_opts: Vec<WithOption>) -> Result<Vec<IndexOption>, an
         }
     };
 
+    match with_opts.refresh_interval.as_deref() {
+        None => (),
+        Some("off") => out.push(IndexOption::RefreshInterval(None)),
+        Some(s) => {
+            let interval = parse_duration::parse(s)?;
+            if interval.as_millis() == 0 {
+                bail!("refresh_interval must be at least 1ms");
+            }
+            out.push(IndexOption::RefreshInterval(Some(interval)))
+        }
+    };
+
     Ok(out)
 }
 
@@ -1749,6 +2416,7 @@ pub fn plan_alter_index_options(
                 .into_iter()
                 .filter_map(|o| match normalize::ident(o).as_str() {
                     "logical_compaction_window" => Some(IndexOptionName::LogicalCompactionWindow),
+                    "refresh_interval" => Some(IndexOptionName::RefreshInterval),
                     // Follow Postgres and don't complain if unknown parameters
                     // are passed into `ALTER INDEX ... RESET`.
                     _ => None,
@@ -1763,6 +2431,181 @@ pub fn plan_alter_index_options(
     }
 }
 
+pub fn describe_alter_source_options(
+    _: &StatementContext,
+    _: AlterSourceOptionsStatement<Raw>,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_alter_source_options(
+    scx: &StatementContext,
+    AlterSourceOptionsStatement {
+        source_name,
+        if_exists,
+        options,
+    }: AlterSourceOptionsStatement<Raw>,
+) -> Result<Plan, anyhow::Error> {
+    let entry = match scx.resolve_item(source_name) {
+        Ok(source) => source,
+        Err(_) if if_exists => {
+            return Ok(Plan::AlterNoop {
+                object_type: ObjectType::Source,
+            });
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if entry.item_type() != CatalogItemType::Source {
+        bail!("{} is a {} not a source", entry.name(), entry.item_type())
+    }
+    let id = entry.id();
+
+    let parsed = crate::parse::parse(entry.create_sql())
+        .expect("Sql for existing source should be valid sql");
+    let mut stmt = match parsed.into_last() {
+        Statement::CreateSource(stmt) => stmt,
+        _ => panic!("Sql for existing source should parse as a source"),
+    };
+    let broker = match &mut stmt.connector {
+        Connector::Kafka { broker, .. } => broker,
+        _ => unsupported!("ALTER SOURCE ... SET for non-Kafka sources"),
+    };
+    if normalize::options(&stmt.with_options).contains_key("consistency") {
+        unsupported!("ALTER SOURCE ... SET for BYO consistency sources")
+    }
+
+    // Only connection options may change. Everything else about the source,
+    // like its topic, format and envelope, determines the data that dependent
+    // views have already seen.
+    let mut new_options = normalize::options(&options);
+    if new_options.contains_key("start_offset") {
+        bail!(
+            "cannot alter start_offset of source {}, as it determines the data that has \
+             already been ingested",
+            entry.name()
+        )
+    }
+    match new_options.remove("broker") {
+        None => (),
+        Some(Value::String(s)) => {
+            *broker = s;
+            if !broker.contains(':') {
+                *broker += ":9092";
+            }
+        }
+        Some(_) => bail!("broker must be a string"),
+    }
+    kafka_util::extract_config(&mut new_options)?;
+    if !new_options.is_empty() {
+        bail!(
+            "cannot alter parameters for source: {}",
+            new_options.keys().join(",")
+        )
+    }
+
+    let options: Vec<_> = options
+        .into_iter()
+        .filter(|o| normalize::ident(o.name().clone()) != "broker")
+        .collect();
+    stmt.with_options.retain(|o| {
+        let name = normalize::ident(o.name().clone());
+        !options
+            .iter()
+            .any(|new| normalize::ident(new.name().clone()) == name)
+    });
+    stmt.with_options.extend(options);
+
+    // Verify that the new connection options work together with the
+    // unchanged ones before committing to them.
+    let config_options = kafka_util::extract_config(&mut normalize::options(&stmt.with_options))?;
+    kafka_util::test_config(broker, &config_options)?;
+
+    match plan_create_source(scx, stmt)? {
+        Plan::CreateSource { source, .. } => Ok(Plan::AlterSource { id, source }),
+        _ => unreachable!("plan_create_source always returns Plan::CreateSource"),
+    }
+}
+
+pub fn describe_alter_source_schema(
+    _: &StatementContext,
+    _: AlterSourceSchemaStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_alter_source_schema(
+    scx: &StatementContext,
+    AlterSourceSchemaStatement {
+        source_name,
+        if_exists,
+        schema,
+    }: AlterSourceSchemaStatement,
+) -> Result<Plan, anyhow::Error> {
+    let entry = match scx.resolve_item(source_name) {
+        Ok(source) => source,
+        Err(_) if if_exists => {
+            return Ok(Plan::AlterNoop {
+                object_type: ObjectType::Source,
+            });
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if entry.item_type() != CatalogItemType::Source {
+        bail!("{} is a {} not a source", entry.name(), entry.item_type())
+    }
+    let id = entry.id();
+
+    let parsed = crate::parse::parse(entry.create_sql())
+        .expect("Sql for existing source should be valid sql");
+    let old_stmt = match parsed.into_last() {
+        Statement::CreateSource(stmt) => stmt,
+        _ => panic!("Sql for existing source should parse as a source"),
+    };
+    let mut stmt = old_stmt.clone();
+    match &mut stmt.format {
+        Some(Format::Protobuf {
+            schema: old_schema, ..
+        }) => *old_schema = schema,
+        _ => unsupported!("ALTER SOURCE ... SET SCHEMA for non-Protobuf sources"),
+    }
+
+    let old_source = match plan_create_source(scx, old_stmt)? {
+        Plan::CreateSource { source, .. } => source,
+        _ => unreachable!("plan_create_source always returns Plan::CreateSource"),
+    };
+    let source = match plan_create_source(scx, stmt)? {
+        Plan::CreateSource { source, .. } => source,
+        _ => unreachable!("plan_create_source always returns Plan::CreateSource"),
+    };
+
+    // Dependent views were planned against the source's current columns, and
+    // running dataflows mix messages decoded with the old and new descriptors,
+    // so the new descriptors may only evolve the message in ways that leave
+    // its columns untouched.
+    if source.bare_desc != old_source.bare_desc {
+        let humanize = |desc: &RelationDesc| {
+            desc.iter()
+                .map(|(name, typ)| {
+                    format!(
+                        "{} {}",
+                        name.map(|n| n.as_str()).unwrap_or("?column?"),
+                        scx.humanize_column_type(typ)
+                    )
+                })
+                .join(", ")
+        };
+        bail!(
+            "new schema for source {} must not change its columns: \
+             expected ({}), but got ({})",
+            entry.name(),
+            humanize(&old_source.bare_desc),
+            humanize(&source.bare_desc)
+        )
+    }
+
+    Ok(Plan::AlterSource { id, source })
+}
+
 pub fn describe_alter_object_rename(
     _: &StatementContext,
     _: AlterObjectRenameStatement,
@@ -17,16 +17,20 @@ use std::convert::TryFrom;
 
 use anyhow::bail;
 
+use dataflow_types::TailEnvelope;
 use expr::MirRelationExpr;
+use interchange::envelopes;
 use ore::collections::CollectionExt;
-use repr::{RelationDesc, ScalarType};
+use ore::str::StrExt;
+use repr::{RelationDesc, RelationType, ScalarType};
 
 use crate::ast::{
     CopyDirection, CopyRelation, CopyStatement, CopyTarget, CreateViewStatement, DeleteStatement,
-    ExplainStage, ExplainStatement, Explainee, InsertStatement, Query, Raw, SelectStatement,
-    Statement, TailStatement, UpdateStatement,
+    ExplainStage, ExplainStatement, Explainee, Ident, InsertStatement, Query, Raw, SelectStatement,
+    Statement, TailStatement, UnresolvedObjectName, UpdateStatement, Value,
 };
 use crate::catalog::CatalogItemType;
+use crate::normalize;
 use crate::plan::query;
 use crate::plan::query::QueryLifetime;
 use crate::plan::statement::{StatementContext, StatementDesc};
@@ -139,6 +143,9 @@ pub fn describe_explain(
             ExplainStage::RawPlan => "Raw Plan",
             ExplainStage::DecorrelatedPlan => "Decorrelated Plan",
             ExplainStage::OptimizedPlan { .. } => "Optimized Plan",
+            ExplainStage::Timestamp => "Timestamp",
+            ExplainStage::IndexAdvice => "Index Advice",
+            ExplainStage::Dataflow => "Dataflow",
         },
         ScalarType::String.nullable(false),
     )))
@@ -166,8 +173,20 @@ pub fn plan_explain(
     }: ExplainStatement<Raw>,
     params: &Params,
 ) -> Result<Plan, anyhow::Error> {
+    let explainee = match explainee {
+        Explainee::Source(name) => {
+            return plan_explain_dataflow(scx, name, CatalogItemType::Source, stage)
+        }
+        Explainee::Sink(name) => {
+            return plan_explain_dataflow(scx, name, CatalogItemType::Sink, stage)
+        }
+        explainee => explainee,
+    };
+    if stage == ExplainStage::Dataflow {
+        bail!("EXPLAIN DATAFLOW is only supported for sources and sinks");
+    }
     let is_view = matches!(explainee, Explainee::View(_));
-    let (scx, query) = match explainee {
+    let (scx, query, prefer_delta_joins) = match explainee {
         Explainee::View(name) => {
             let view = scx.resolve_item(name.clone())?;
             if view.item_type() != CatalogItemType::View {
@@ -175,19 +194,29 @@ pub fn plan_explain(
             }
             let parsed = crate::parse::parse(view.create_sql())
                 .expect("Sql for existing view should be valid sql");
-            let query = match parsed.into_last() {
-                Statement::CreateView(CreateViewStatement { query, .. }) => query,
+            let (query, with_options) = match parsed.into_last() {
+                Statement::CreateView(CreateViewStatement {
+                    query,
+                    with_options,
+                    ..
+                }) => (query, with_options),
                 _ => panic!("Sql for existing view should parse as a view"),
             };
+            let prefer_delta_joins = matches!(
+                normalize::options(&with_options).get("prefer_delta_joins"),
+                Some(Value::Boolean(true))
+            );
             let scx = StatementContext {
                 pcx: view.plan_cx(),
                 catalog: scx.catalog,
                 ids: HashSet::new(),
                 param_types: scx.param_types.clone(),
+                notices: scx.notices.clone(),
             };
-            (scx, query)
+            (scx, query, Some(prefer_delta_joins))
         }
-        Explainee::Query(query) => (scx.clone(), query),
+        Explainee::Query(query) => (scx.clone(), query, None),
+        Explainee::Source(_) | Explainee::Sink(_) => unreachable!(),
     };
     // Previouly we would bail here for ORDER BY and LIMIT; this has been relaxed to silently
     // report the plan without the ORDER BY and LIMIT decorations (which are done in post).
@@ -214,9 +243,33 @@ pub fn plan_explain(
         row_set_finishing: finishing,
         stage,
         options,
+        prefer_delta_joins,
     })
 }
 
+/// Plans an `EXPLAIN` of the dataflow of the source or sink `name`, which
+/// must be of type `item_type`.
+fn plan_explain_dataflow(
+    scx: &StatementContext,
+    name: UnresolvedObjectName,
+    item_type: CatalogItemType,
+    stage: ExplainStage,
+) -> Result<Plan, anyhow::Error> {
+    if stage != ExplainStage::Dataflow {
+        bail!("EXPLAIN {} is not supported for {}s", stage, item_type);
+    }
+    let item = scx.resolve_item(name.clone())?;
+    if item.item_type() != item_type {
+        bail!(
+            "Expected {} to be a {}, not a {}",
+            name,
+            item_type,
+            item.item_type()
+        );
+    }
+    Ok(Plan::ExplainDataflow { id: item.id() })
+}
+
 /// Plans and decorrelates a `Query`. Like `query::plan_root_query`, but returns
 /// an `::expr::MirRelationExpr`, which cannot include correlated expressions.
 pub fn plan_query(
@@ -249,10 +302,17 @@ with_options! {
 
 pub fn describe_tail(
     scx: &StatementContext,
-    TailStatement { name, options, .. }: TailStatement<Raw>,
+    TailStatement {
+        name,
+        options,
+        envelope,
+        ..
+    }: TailStatement<Raw>,
 ) -> Result<StatementDesc, anyhow::Error> {
     let sql_object = scx.resolve_item(name)?;
     let options = TailOptions::try_from(options)?;
+    let object_desc = sql_object.desc()?;
+    let (envelope, key_indices) = plan_tail_envelope(object_desc, envelope)?;
     const MAX_U64_DIGITS: u8 = 20;
     let mut desc = RelationDesc::empty().with_column(
         "timestamp",
@@ -261,18 +321,79 @@ pub fn describe_tail(
     if options.progress.unwrap_or(false) {
         desc = desc.with_column("progressed", ScalarType::Bool.nullable(false));
     }
-    let desc = desc
-        .with_column("diff", ScalarType::Int64.nullable(true))
-        .concat(sql_object.desc()?.clone());
+    let desc = match envelope {
+        TailEnvelope::Diffs => desc
+            .with_column("diff", ScalarType::Int64.nullable(true))
+            .concat(object_desc.clone()),
+        TailEnvelope::Upsert => {
+            // Deletions carry only the key, so every other column may be null.
+            let key_indices = key_indices.unwrap_or_default();
+            let (names, types): (Vec<_>, Vec<_>) = object_desc
+                .iter()
+                .enumerate()
+                .map(|(i, (name, typ))| {
+                    let nullable = typ.nullable || !key_indices.contains(&i);
+                    (name.cloned(), typ.clone().nullable(nullable))
+                })
+                .unzip();
+            desc.with_column("state", ScalarType::String.nullable(true))
+                .concat(RelationDesc::new(RelationType::new(types), names))
+        }
+        TailEnvelope::Debezium => desc.concat(envelopes::dbz_desc(object_desc.clone())),
+    };
     Ok(StatementDesc::new(Some(desc)))
 }
 
+/// Resolves the envelope of a `TAIL` of an object described by `desc`.
+///
+/// Returns the envelope along with the indices of the columns by which changes
+/// are grouped, if the envelope groups changes.
+fn plan_tail_envelope(
+    desc: &RelationDesc,
+    envelope: Option<sql_parser::ast::TailEnvelope>,
+) -> Result<(TailEnvelope, Option<Vec<usize>>), anyhow::Error> {
+    let resolve_key = |key: Vec<Ident>| -> Result<Vec<usize>, anyhow::Error> {
+        let mut key_indices = vec![];
+        for ident in key {
+            let name = normalize::column_name(ident);
+            let i = match desc.get_by_name(&name) {
+                Some((i, _)) => i,
+                None => bail!("column {} does not exist", name.as_str().quoted()),
+            };
+            if key_indices.contains(&i) {
+                bail!(
+                    "column {} specified more than once in KEY",
+                    name.as_str().quoted()
+                );
+            }
+            key_indices.push(i);
+        }
+        Ok(key_indices)
+    };
+    Ok(match envelope {
+        None => (TailEnvelope::Diffs, None),
+        Some(sql_parser::ast::TailEnvelope::Upsert { key }) => {
+            (TailEnvelope::Upsert, Some(resolve_key(key)?))
+        }
+        Some(sql_parser::ast::TailEnvelope::Debezium { key }) => {
+            // Without a key, only identical rows are paired, so every change
+            // is reported as either an insertion or a deletion.
+            let key_indices = match key {
+                Some(key) => resolve_key(key)?,
+                None => (0..desc.arity()).collect(),
+            };
+            (TailEnvelope::Debezium, Some(key_indices))
+        }
+    })
+}
+
 pub fn plan_tail(
     scx: &StatementContext,
     TailStatement {
         name,
         options,
         as_of,
+        envelope,
     }: TailStatement<Raw>,
     copy_to: Option<CopyFormat>,
 ) -> Result<Plan, anyhow::Error> {
@@ -280,6 +401,7 @@ pub fn plan_tail(
     let ts = as_of.map(|e| query::eval_as_of(scx, e)).transpose()?;
     let options = TailOptions::try_from(options)?;
     let desc = entry.desc()?.clone();
+    let (envelope, key_indices) = plan_tail_envelope(&desc, envelope)?;
 
     match entry.item_type() {
         CatalogItemType::Table | CatalogItemType::Source | CatalogItemType::View => {
@@ -291,11 +413,14 @@ pub fn plan_tail(
                 emit_progress: options.progress.unwrap_or(false),
                 object_columns: entry.desc()?.arity(),
                 desc,
+                envelope,
+                key_indices,
             })
         }
         CatalogItemType::Func
         | CatalogItemType::Index
         | CatalogItemType::Sink
+        | CatalogItemType::Task
         | CatalogItemType::Type => bail!(
             "'{}' cannot be tailed because it is a {}",
             entry.name(),
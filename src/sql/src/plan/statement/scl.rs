@@ -20,9 +20,12 @@ use repr::adt::interval::Interval;
 use repr::{RelationDesc, ScalarType};
 
 use crate::ast::{
-    CloseStatement, DeclareStatement, DiscardStatement, DiscardTarget, FetchStatement, Raw,
-    SetVariableStatement, SetVariableValue, ShowVariableStatement, Value,
+    AlterRoleResetStatement, AlterRoleSetStatement, AlterSystemResetStatement,
+    AlterSystemSetStatement, CloseStatement, DeclareStatement, DiscardStatement, DiscardTarget,
+    FetchStatement, Raw, ResetVariableStatement, SetVariableStatement, SetVariableValue,
+    ShowVariableStatement, Value,
 };
+use crate::normalize;
 use crate::plan::statement::{StatementContext, StatementDesc};
 use crate::plan::{ExecuteTimeout, Plan};
 
@@ -44,13 +47,117 @@ pub fn plan_set_variable(
     if local {
         unsupported!("SET LOCAL");
     }
-    Ok(Plan::SetVariable {
+    match value {
+        // `SET <variable> TO DEFAULT` is equivalent to `RESET <variable>`.
+        SetVariableValue::Ident(ident) if ident.as_str() == unicase::Ascii::new("DEFAULT") => {
+            Ok(Plan::ResetVariable {
+                name: variable.to_string(),
+            })
+        }
+        value => Ok(Plan::SetVariable {
+            name: variable.to_string(),
+            value: set_variable_value_to_string(value),
+        }),
+    }
+}
+
+pub fn describe_reset_variable(
+    _: &StatementContext,
+    _: ResetVariableStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_reset_variable(
+    _: &StatementContext,
+    ResetVariableStatement { variable }: ResetVariableStatement,
+) -> Result<Plan, anyhow::Error> {
+    Ok(Plan::ResetVariable {
+        name: variable.to_string(),
+    })
+}
+
+fn set_variable_value_to_string(value: SetVariableValue) -> String {
+    match value {
+        SetVariableValue::Literal(Value::String(s)) => s,
+        SetVariableValue::Literal(lit) => lit.to_string(),
+        SetVariableValue::Ident(ident) => ident.into_string(),
+    }
+}
+
+pub fn describe_alter_system_set(
+    _: &StatementContext,
+    _: AlterSystemSetStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_alter_system_set(
+    _: &StatementContext,
+    AlterSystemSetStatement { variable, value }: AlterSystemSetStatement,
+) -> Result<Plan, anyhow::Error> {
+    Ok(Plan::AlterSystemSet {
+        name: variable.to_string(),
+        value: set_variable_value_to_string(value),
+    })
+}
+
+pub fn describe_alter_system_reset(
+    _: &StatementContext,
+    _: AlterSystemResetStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_alter_system_reset(
+    _: &StatementContext,
+    AlterSystemResetStatement { variable }: AlterSystemResetStatement,
+) -> Result<Plan, anyhow::Error> {
+    Ok(Plan::AlterSystemReset {
+        name: variable.to_string(),
+    })
+}
+
+pub fn describe_alter_role_set(
+    _: &StatementContext,
+    _: AlterRoleSetStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_alter_role_set(
+    scx: &StatementContext,
+    AlterRoleSetStatement {
+        name,
+        variable,
+        value,
+    }: AlterRoleSetStatement,
+) -> Result<Plan, anyhow::Error> {
+    let role_name = normalize::ident(name);
+    scx.catalog.resolve_role(&role_name)?;
+    Ok(Plan::AlterRoleSet {
+        role_name,
+        name: variable.to_string(),
+        value: set_variable_value_to_string(value),
+    })
+}
+
+pub fn describe_alter_role_reset(
+    _: &StatementContext,
+    _: AlterRoleResetStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_alter_role_reset(
+    scx: &StatementContext,
+    AlterRoleResetStatement { name, variable }: AlterRoleResetStatement,
+) -> Result<Plan, anyhow::Error> {
+    let role_name = normalize::ident(name);
+    scx.catalog.resolve_role(&role_name)?;
+    Ok(Plan::AlterRoleReset {
+        role_name,
         name: variable.to_string(),
-        value: match value {
-            SetVariableValue::Literal(Value::String(s)) => s,
-            SetVariableValue::Literal(lit) => lit.to_string(),
-            SetVariableValue::Ident(ident) => ident.into_string(),
-        },
     })
 }
 
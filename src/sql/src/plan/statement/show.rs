@@ -184,6 +184,7 @@ pub fn show_objects<'a>(
         ObjectType::Source => show_sources(scx, full, materialized, from, filter),
         ObjectType::View => show_views(scx, full, materialized, from, filter),
         ObjectType::Sink => show_sinks(scx, full, from, filter),
+        ObjectType::Task => show_tasks(scx, full, from, filter),
         ObjectType::Type => show_types(scx, extended, full, from, filter),
         ObjectType::Object => show_all_objects(scx, extended, full, from, filter),
         ObjectType::Role => unsupported!("SHOW ROLES"),
@@ -386,6 +387,34 @@ fn show_sinks<'a>(
     Ok(ShowSelect::new(scx, query, filter))
 }
 
+fn show_tasks<'a>(
+    scx: &'a StatementContext<'a>,
+    full: bool,
+    from: Option<UnresolvedObjectName>,
+    filter: Option<ShowStatementFilter<Raw>>,
+) -> Result<ShowSelect<'a>, anyhow::Error> {
+    let schema = if let Some(from) = from {
+        scx.resolve_schema(from)?
+    } else {
+        scx.resolve_default_schema()?
+    };
+
+    let query = if full {
+        format!(
+            "SELECT name, mz_internal.mz_classify_object_id(id) AS type
+            FROM mz_catalog.mz_tasks
+            WHERE schema_id = {}",
+            schema.id(),
+        )
+    } else {
+        format!(
+            "SELECT name FROM mz_catalog.mz_tasks WHERE schema_id = {}",
+            schema.id(),
+        )
+    };
+    Ok(ShowSelect::new(scx, query, filter))
+}
+
 fn show_types<'a>(
     scx: &'a StatementContext<'a>,
     extended: bool,
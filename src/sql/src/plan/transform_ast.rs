@@ -18,7 +18,8 @@ use uuid::Uuid;
 
 use sql_parser::ast::visit_mut::{self, VisitMut};
 use sql_parser::ast::{
-    Expr, Function, FunctionArgs, Ident, Query, Raw, Select, SelectItem, TableAlias, TableFactor,
+    Cte, DataType, Distinct, Expr, Function, FunctionArgs, Ident, IntervalValue, Join,
+    JoinConstraint, JoinOperator, Query, Raw, RawName, Select, SelectItem, TableAlias, TableFactor,
     TableWithJoins, UnresolvedObjectName, Value,
 };
 
@@ -40,6 +41,10 @@ fn run_transforms<F, A>(scx: &StatementContext, mut f: F, ast: &mut A) -> Result
 where
     F: for<'ast> FnMut(&mut dyn VisitMut<'ast, Raw>, &'ast mut A),
 {
+    let mut window_func_rewriter = WindowFuncRewriter::new();
+    f(&mut window_func_rewriter, ast);
+    window_func_rewriter.status?;
+
     let mut func_rewriter = FuncRewriter::new(scx);
     f(&mut func_rewriter, ast);
     func_rewriter.status?;
@@ -51,6 +56,390 @@ where
     desugarer.status
 }
 
+/// The table functions that assign the rows of a relation to time windows.
+const WINDOW_FUNCS: &[&str] = &["tumble", "hop", "session"];
+
+/// Reports whether `name` names one of the windowing table functions, whose
+/// first argument is a relation rather than an expression.
+pub fn is_window_func(name: &UnresolvedObjectName) -> bool {
+    match name.0.as_slice() {
+        [ident] => WINDOW_FUNCS.contains(&normalize::ident(ident.clone()).as_str()),
+        _ => false,
+    }
+}
+
+// Rewrites the windowing table functions to subqueries over the relation that
+// they window.
+//
+// Specifically:
+//
+//   * `tumble($rel, $col, $size)` assigns each row of `$rel` to the window of
+//     length `$size` that contains the time in `$col`, where windows are
+//     aligned to the Unix epoch.
+//
+//   * `hop($rel, $col, $size, $slide)` assigns each row to every window of
+//     length `$size` that contains its time, where a window starts every
+//     `$slide`. Rows appear once per window.
+//
+//   * `session($rel, $col, $gap, $key...)` assigns each row to a session of
+//     the rows with the same `$key` columns, where a session ends once no row
+//     has arrived for `$gap`.
+//
+// Each produces the columns of `$rel` followed by `window_start` and
+// `window_end`, and retains each row with a temporal filter from the time in
+// `$col` until its window ends. For `tumble` and `hop`, a view over the window
+// function thus only maintains state for open windows. `session`, however,
+// cannot know how long ago the session of a new row started, so it compares
+// every row with every earlier row with the same key: its state grows with the
+// whole history of `$rel`, and its work quadratically with the number of rows
+// per key. It is only suitable for relations whose rows are themselves
+// retired, e.g., by a temporal filter.
+//
+// The expansion is built directly as an AST, and refers to `$rel` and to the
+// columns it computes only by generated names, so that it cannot be confused
+// by the names of the columns of `$rel`.
+struct WindowFuncRewriter {
+    status: Result<(), anyhow::Error>,
+}
+
+impl WindowFuncRewriter {
+    fn new() -> WindowFuncRewriter {
+        WindowFuncRewriter { status: Ok(()) }
+    }
+
+    fn rewrite_table_factor(
+        name: &UnresolvedObjectName,
+        args: &FunctionArgs<Raw>,
+        alias: Option<TableAlias>,
+    ) -> Result<TableFactor<Raw>, anyhow::Error> {
+        let func = normalize::ident(name.0[0].clone());
+        let args = match args {
+            FunctionArgs::Args { args, order_by } if order_by.is_empty() => args,
+            _ => bail!("invalid arguments to {}", func),
+        };
+        let arity_ok = match func.as_str() {
+            "tumble" => args.len() == 3,
+            "hop" => args.len() == 4,
+            _ => args.len() >= 3,
+        };
+        if !arity_ok {
+            bail!("wrong number of arguments to {}", func);
+        }
+        let relation = match &args[0] {
+            Expr::Identifier(names) => UnresolvedObjectName(names.clone()),
+            _ => bail!("the first argument to {} must name a relation", func),
+        };
+        let column = |arg: &Expr<Raw>| match arg {
+            Expr::Identifier(names) if names.len() == 1 => Ok(names[0].clone()),
+            _ => bail!("{} expects a column name in place of {}", func, arg),
+        };
+        let time = column(&args[1])?;
+
+        let r = fresh_ident("r");
+        let r_time = qualified(&r, &time);
+        let query = match func.as_str() {
+            "tumble" => {
+                // The window that contains the row starts at its time rounded
+                // down to a multiple of `$size`.
+                let size = args[2].clone();
+                let epoch = date_part_epoch(r_time.clone());
+                let size_s = date_part_epoch(size.clone());
+                let window_start = r_time.clone().minus(seconds(
+                    epoch
+                        .clone()
+                        .minus(floor(epoch.divide(size_s.clone())).multiply(size_s)),
+                ));
+                let window_end = window_start.clone().binop("+", size);
+                Query::select(
+                    Select::default()
+                        .from(unjoined(table(relation, r.clone())))
+                        .project(wildcard(&r))
+                        .project(named(window_start, "window_start"))
+                        .project(named(window_end.clone(), "window_end"))
+                        .selection(Some(retain(r_time, window_end))),
+                )
+            }
+            "hop" => {
+                // The latest window that contains the row starts at its time
+                // rounded down to a multiple of `$slide`; each earlier window
+                // starts `$slide` before the next, until a window ends before
+                // the row's time.
+                let size = args[2].clone();
+                let epoch = date_part_epoch(r_time.clone());
+                let size_s = date_part_epoch(size.clone());
+                let slide_s = date_part_epoch(args[3].clone());
+                let w = fresh_ident("w");
+                let n = fresh_ident("n");
+                let window_start = r_time.clone().minus(seconds(
+                    epoch
+                        .clone()
+                        .minus(floor(epoch.divide(slide_s.clone())).multiply(slide_s.clone()))
+                        .binop("+", qualified(&w, &n).multiply(slide_s.clone())),
+                ));
+                let window_end = window_start.clone().binop("+", size);
+                let windows = TableFactor::Function {
+                    name: UnresolvedObjectName::unqualified("generate_series"),
+                    args: FunctionArgs::args(vec![
+                        Expr::number("0"),
+                        cast(floor(size_s.divide(slide_s)), "int8", vec![]),
+                    ]),
+                    alias: Some(table_alias(w, vec![n])),
+                };
+                Query::select(
+                    Select::default()
+                        .from(unjoined(table(relation, r.clone())))
+                        .from(unjoined(windows))
+                        .project(wildcard(&r))
+                        .project(named(window_start, "window_start"))
+                        .project(named(window_end.clone(), "window_end"))
+                        .selection(Some(
+                            window_end
+                                .clone()
+                                .gt(r_time.clone())
+                                .and(retain(r_time, window_end)),
+                        )),
+                )
+            }
+            _ => {
+                let gap = args[2].clone();
+                let keys = args[3..]
+                    .iter()
+                    .map(column)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let keys_eq = |lhs: &Ident, lhs_keys: &[Ident], rhs: &Ident, rhs_keys: &[Ident]| {
+                    lhs_keys
+                        .iter()
+                        .zip(rhs_keys)
+                        .map(|(lk, rk)| qualified(lhs, lk).equals(qualified(rhs, rk)))
+                        .fold(Expr::Value(Value::Boolean(true)), Expr::and)
+                };
+
+                // A row starts a session if no row with the same key arrived
+                // within `$gap` before it. Each row belongs to the latest
+                // session that started at or before its time, and a session
+                // ends `$gap` after its last row.
+                //
+                // The CTE `s` maps each key and time to the start of its
+                // session, and the CTE `e` maps the start of each session to
+                // its end.
+                let (a, b, c) = (fresh_ident("a"), fresh_ident("b"), fresh_ident("c"));
+                let (a_time, b_time, c_time) = (
+                    qualified(&a, &time),
+                    qualified(&b, &time),
+                    qualified(&c, &time),
+                );
+                let previous = Select::default()
+                    .from(unjoined(table(relation.clone(), c.clone())))
+                    .project(item(Expr::number("1")))
+                    .selection(Some(
+                        keys_eq(&c, &keys, &b, &keys)
+                            .and(c_time.clone().lt(b_time.clone()))
+                            .and(c_time.gt_eq(b_time.clone().minus(gap.clone()))),
+                    ));
+                let session_start = Select::default()
+                    .from(unjoined(table(relation.clone(), b.clone())))
+                    .project(item(b_time.clone().call_unary(vec!["max"])))
+                    .selection(Some(
+                        keys_eq(&b, &keys, &a, &keys)
+                            .and(b_time.lt_eq(a_time.clone()))
+                            .and(Expr::Exists(Box::new(Query::select(previous))).negate()),
+                    ));
+                let mut s_select = Select::default()
+                    .from(unjoined(table(relation.clone(), a.clone())))
+                    .project(item(a_time))
+                    .project(item(Expr::Subquery(Box::new(Query::select(session_start)))));
+                for key in &keys {
+                    s_select = s_select.project(item(qualified(&a, key)));
+                }
+                s_select.distinct = Some(Distinct::EntireRow);
+
+                let (s, e) = (fresh_ident("s"), fresh_ident("e"));
+                let (t, start, end) = (fresh_ident("t"), fresh_ident("start"), fresh_ident("end"));
+                let s_keys: Vec<_> = keys.iter().map(|_| fresh_ident("k")).collect();
+                let mut e_select = Select::default()
+                    .from(unjoined(table(cte_name(&s), s.clone())))
+                    .project(item(qualified(&s, &start)))
+                    .project(item(
+                        qualified(&s, &t).call_unary(vec!["max"]).binop("+", gap),
+                    ));
+                for key in &s_keys {
+                    e_select = e_select.project(item(qualified(&s, key)));
+                    e_select.group_by.push(qualified(&s, key));
+                }
+                e_select.group_by.push(qualified(&s, &start));
+                let ctes = vec![
+                    Cte {
+                        alias: table_alias(
+                            s.clone(),
+                            [&[t.clone(), start.clone()], &*s_keys].concat(),
+                        ),
+                        id: (),
+                        query: Query::select(s_select),
+                    },
+                    Cte {
+                        alias: table_alias(
+                            e.clone(),
+                            [&[start.clone(), end.clone()], &*s_keys].concat(),
+                        ),
+                        id: (),
+                        query: Query::select(e_select),
+                    },
+                ];
+
+                let mut from = unjoined(table(relation, r.clone()));
+                from.joins.push(Join {
+                    relation: table(cte_name(&s), s.clone()),
+                    join_operator: JoinOperator::Inner(JoinConstraint::On(
+                        keys_eq(&r, &keys, &s, &s_keys)
+                            .and(r_time.clone().equals(qualified(&s, &t))),
+                    )),
+                });
+                from.joins.push(Join {
+                    relation: table(cte_name(&e), e.clone()),
+                    join_operator: JoinOperator::Inner(JoinConstraint::On(
+                        keys_eq(&s, &s_keys, &e, &s_keys)
+                            .and(qualified(&s, &start).equals(qualified(&e, &start))),
+                    )),
+                });
+                let window_end = qualified(&e, &end);
+                Query {
+                    ctes,
+                    ..Query::select(
+                        Select::default()
+                            .from(from)
+                            .project(wildcard(&r))
+                            .project(named(qualified(&e, &start), "window_start"))
+                            .project(named(window_end.clone(), "window_end"))
+                            .selection(Some(retain(r_time, window_end))),
+                    )
+                }
+            }
+        };
+        Ok(TableFactor::Derived {
+            lateral: false,
+            subquery: Box::new(query),
+            alias: Some(alias.unwrap_or_else(|| TableAlias {
+                name: Ident::new(func),
+                columns: vec![],
+                strict: false,
+            })),
+        })
+    }
+}
+
+impl<'ast> VisitMut<'ast, Raw> for WindowFuncRewriter {
+    fn visit_table_factor_mut(&mut self, table_factor: &'ast mut TableFactor<Raw>) {
+        visit_mut::visit_table_factor_mut(self, table_factor);
+        if self.status.is_err() {
+            return;
+        }
+        if let TableFactor::Function { name, args, alias } = table_factor {
+            if is_window_func(name) {
+                match Self::rewrite_table_factor(name, args, alias.take()) {
+                    Ok(new) => *table_factor = new,
+                    Err(e) => self.status = Err(e),
+                }
+            }
+        }
+    }
+}
+
+/// Generates a name, based on `name`, that is distinct from every name that
+/// the query that contains a window function could refer to.
+fn fresh_ident(name: &str) -> Ident {
+    Ident::new(format!("{}_{}", name, Uuid::new_v4()))
+}
+
+fn qualified(table: &Ident, column: &Ident) -> Expr<Raw> {
+    Expr::Identifier(vec![table.clone(), column.clone()])
+}
+
+fn cte_name(name: &Ident) -> UnresolvedObjectName {
+    UnresolvedObjectName(vec![name.clone()])
+}
+
+fn table_alias(name: Ident, columns: Vec<Ident>) -> TableAlias {
+    TableAlias {
+        name,
+        columns,
+        strict: false,
+    }
+}
+
+fn table(name: UnresolvedObjectName, alias: Ident) -> TableFactor<Raw> {
+    TableFactor::Table {
+        name: RawName::Name(name),
+        alias: Some(table_alias(alias, vec![])),
+    }
+}
+
+fn unjoined(relation: TableFactor<Raw>) -> TableWithJoins<Raw> {
+    TableWithJoins {
+        relation,
+        joins: vec![],
+    }
+}
+
+fn item(expr: Expr<Raw>) -> SelectItem<Raw> {
+    SelectItem::Expr { expr, alias: None }
+}
+
+fn named(expr: Expr<Raw>, name: &str) -> SelectItem<Raw> {
+    SelectItem::Expr {
+        expr,
+        alias: Some(Ident::new(name)),
+    }
+}
+
+fn wildcard(table: &Ident) -> SelectItem<Raw> {
+    item(Expr::QualifiedWildcard(vec![table.clone()]))
+}
+
+fn cast(expr: Expr<Raw>, name: &str, typ_mod: Vec<u64>) -> Expr<Raw> {
+    Expr::Cast {
+        expr: Box::new(expr),
+        data_type: DataType::Other {
+            name: RawName::Name(UnresolvedObjectName::unqualified(name)),
+            typ_mod,
+        },
+    }
+}
+
+fn floor(expr: Expr<Raw>) -> Expr<Raw> {
+    expr.call_unary(vec!["floor"])
+}
+
+/// Computes `extract(epoch FROM $expr)`.
+fn date_part_epoch(expr: Expr<Raw>) -> Expr<Raw> {
+    Expr::call(
+        vec!["date_part"],
+        vec![Expr::Value(Value::String("epoch".into())), expr],
+    )
+}
+
+/// Converts the number of seconds `expr` to an interval.
+fn seconds(expr: Expr<Raw>) -> Expr<Raw> {
+    expr.multiply(Expr::Value(Value::Interval(IntervalValue {
+        value: "1 second".into(),
+        ..Default::default()
+    })))
+}
+
+/// Computes a temporal filter that retains a row from `time` until `end`.
+fn retain(time: Expr<Raw>, end: Expr<Raw>) -> Expr<Raw> {
+    // Temporal filters compare against the logical timestamp, which is
+    // measured in milliseconds since the Unix epoch.
+    let millis = |time| {
+        cast(
+            date_part_epoch(time).multiply(Expr::number("1000")),
+            "numeric",
+            vec![38, 0],
+        )
+    };
+    let now = || Expr::call_nullary(vec!["mz_logical_timestamp"]);
+    now().gt_eq(millis(time)).and(now().lt(millis(end)))
+}
+
 // Transforms various functions to forms that are more easily handled by the
 // planner.
 //
@@ -103,7 +492,7 @@ impl<'a> FuncRewriter<'a> {
     ) -> Expr<Raw> {
         Expr::Function(Function {
             name,
-            args: FunctionArgs::Args(vec![expr]),
+            args: FunctionArgs::args(vec![expr]),
             filter,
             over: None,
             distinct,
@@ -191,7 +580,9 @@ impl<'a> FuncRewriter<'a> {
         match expr {
             Expr::Function(Function {
                 name,
-                args: FunctionArgs::Args(args),
+                // None of the functions rewritten here depend on the order of
+                // their inputs, so any `ORDER BY` can be ignored.
+                args: FunctionArgs::Args { args, .. },
                 filter,
                 distinct,
                 over: None,
@@ -382,7 +773,7 @@ impl Desugarer {
                                 Ident::new("mz_catalog"),
                                 Ident::new("unnest"),
                             ]),
-                            args: FunctionArgs::Args(vec![right.take()]),
+                            args: FunctionArgs::args(vec![right.take()]),
                             alias: Some(TableAlias {
                                 name: Ident::new("_"),
                                 columns: vec![binding.clone()],
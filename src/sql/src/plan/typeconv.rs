@@ -239,6 +239,7 @@ lazy_static! {
 
             // BYTES
             (Bytes, String) => Assignment: CastBytesToString,
+            (Bytes, Uuid) => Explicit: CastBytesToUuid,
 
             // STRING
             (String, Bool) => Explicit: CastStringToBool,
@@ -259,6 +260,26 @@ lazy_static! {
             (String, Bytes) => Explicit: CastStringToBytes,
             (String, Jsonb) => Explicit: CastStringToJsonb,
             (String, Uuid) => Explicit: CastStringToUuid,
+            (String, Inet) => Explicit: CastStringToInet,
+            (String, Cidr) => Explicit: CastStringToCidr,
+            (String, Geometry) => Explicit: CastStringToGeometry,
+            (String, Geography) => Explicit: CastStringToGeography,
+            (String, TsVector) => Explicit: CastStringToTsVector,
+            (String, TsQuery) => Explicit: CastStringToTsQuery,
+            (String, Char) => Implicit: CastTemplate::new(|_ecx, ccx, _from_type, to_type| {
+                let length = to_type.unwrap_char_length();
+                Some(move |e: HirScalarExpr| e.call_unary(CastStringToChar {
+                    length,
+                    fail_on_len: ccx != CastContext::Explicit,
+                }))
+            }),
+            (String, VarChar) => Implicit: CastTemplate::new(|_ecx, ccx, _from_type, to_type| {
+                let length = to_type.unwrap_char_length();
+                Some(move |e: HirScalarExpr| e.call_unary(CastStringToVarChar {
+                    length,
+                    fail_on_len: ccx != CastContext::Explicit,
+                }))
+            }),
             (String, List) => Explicit: CastTemplate::new(|ecx, ccx, from_type, to_type| {
                 let return_ty = to_type.clone();
                 let to_el_type = to_type.unwrap_list_element_type();
@@ -268,6 +289,15 @@ lazy_static! {
                     cast_expr: Box::new(cast_expr),
                 }))
             }),
+            (String, Array) => Explicit: CastTemplate::new(|ecx, ccx, from_type, to_type| {
+                let return_ty = to_type.clone();
+                let to_elem_type = to_type.unwrap_array_element_type();
+                let cast_expr = plan_hypothetical_cast(ecx, ccx, from_type, to_elem_type)?;
+                Some(|e: HirScalarExpr| e.call_unary(UnaryFunc::CastStringToArray {
+                    return_ty,
+                    cast_expr: Box::new(cast_expr),
+                }))
+            }),
             (String, Map) => Explicit: CastTemplate::new(|ecx, ccx, from_type, to_type| {
                 let return_ty = to_type.clone();
                 let to_val_type = to_type.unwrap_map_value_type();
@@ -277,6 +307,15 @@ lazy_static! {
                     cast_expr: Box::new(cast_expr),
                 }))
             }),
+            (String, Range) => Explicit: CastTemplate::new(|ecx, ccx, from_type, to_type| {
+                let return_ty = to_type.clone();
+                let to_elem_type = to_type.unwrap_range_element_type();
+                let cast_expr = plan_hypothetical_cast(ecx, ccx, from_type, to_elem_type)?;
+                Some(|e: HirScalarExpr| e.call_unary(UnaryFunc::CastStringToRange {
+                    return_ty,
+                    cast_expr: Box::new(cast_expr),
+                }))
+            }),
 
             // RECORD
             (Record, String) => Assignment: CastTemplate::new(|_ecx, _ccx, from_type, _to_type| {
@@ -311,6 +350,20 @@ lazy_static! {
                 let ty = from_type.clone();
                 Some(|e: HirScalarExpr| e.call_unary(CastMapToString { ty }))
             }),
+            (Map, Jsonb) => Explicit: CastTemplate::new(|ecx, _ccx, from_type, _to_type| {
+                let from_val_type = from_type.unwrap_map_value_type();
+                let cast_expr =
+                    plan_hypothetical(ecx, from_val_type, |ecx, e| Some(to_jsonb(ecx, e)))?;
+                Some(|e: HirScalarExpr| e.call_unary(UnaryFunc::CastMapToJsonb {
+                    cast_expr: Box::new(cast_expr),
+                }))
+            }),
+
+            // RANGE
+            (Range, String) => Assignment: CastTemplate::new(|_ecx, _ccx, from_type, _to_type| {
+                let ty = from_type.clone();
+                Some(|e: HirScalarExpr| e.call_unary(CastRangeToString { ty }))
+            }),
 
             // JSONB
             (Jsonb, Bool) => Explicit: CastJsonbToBool,
@@ -320,9 +373,76 @@ lazy_static! {
             (Jsonb, Float64) => Explicit: CastJsonbToFloat64,
             (Jsonb, Decimal) => Explicit: CastTemplate::new(from_jsonb_f64_cast),
             (Jsonb, String) => Assignment: CastJsonbToString,
+            (Jsonb, Map) => Explicit: CastTemplate::new(|ecx, ccx, from_type, to_type| {
+                let return_ty = to_type.clone();
+                let to_val_type = to_type.unwrap_map_value_type();
+                let cast_expr = plan_hypothetical_cast(ecx, ccx, from_type, to_val_type)?;
+                Some(|e: HirScalarExpr| e.call_unary(UnaryFunc::CastJsonbToMap {
+                    return_ty,
+                    cast_expr: Box::new(cast_expr),
+                }))
+            }),
 
             // UUID
-            (Uuid, String) => Assignment: CastUuidToString
+            (Uuid, String) => Assignment: CastUuidToString,
+            (Uuid, Bytes) => Explicit: CastUuidToBytes,
+
+            // INET
+            (Inet, String) => Assignment: CastInetToString,
+            (Inet, Cidr) => Assignment: CastInetToCidr,
+
+            // CIDR
+            (Cidr, String) => Assignment: CastInetToString,
+            (Cidr, Inet) => Implicit: CastCidrToInet,
+
+            // GEOMETRY
+            (Geometry, String) => Assignment: CastGeometryToString,
+            (Geometry, Geography) => Implicit: CastGeometryToGeography,
+
+            // GEOGRAPHY
+            (Geography, String) => Assignment: CastGeometryToString,
+            (Geography, Geometry) => Explicit: CastGeographyToGeometry,
+
+            // TSVECTOR
+            (TsVector, String) => Assignment: CastTsVectorToString,
+
+            // TSQUERY
+            (TsQuery, String) => Assignment: CastTsQueryToString,
+
+            // CHAR
+            (Char, String) => Implicit: CastCharToString,
+            (Char, Char) => Implicit: CastTemplate::new(|_ecx, ccx, _from_type, to_type| {
+                let length = to_type.unwrap_char_length();
+                Some(move |e: HirScalarExpr| e.call_unary(CastStringToChar {
+                    length,
+                    fail_on_len: ccx != CastContext::Explicit,
+                }))
+            }),
+            (Char, VarChar) => Implicit: CastTemplate::new(|_ecx, ccx, _from_type, to_type| {
+                let length = to_type.unwrap_char_length();
+                Some(move |e: HirScalarExpr| e.call_unary(CastCharToString)
+                    .call_unary(CastStringToVarChar {
+                        length,
+                        fail_on_len: ccx != CastContext::Explicit,
+                    }))
+            }),
+
+            // VARCHAR
+            (VarChar, String) => Implicit: CastVarCharToString,
+            (VarChar, Char) => Implicit: CastTemplate::new(|_ecx, ccx, _from_type, to_type| {
+                let length = to_type.unwrap_char_length();
+                Some(move |e: HirScalarExpr| e.call_unary(CastStringToChar {
+                    length,
+                    fail_on_len: ccx != CastContext::Explicit,
+                }))
+            }),
+            (VarChar, VarChar) => Implicit: CastTemplate::new(|_ecx, ccx, _from_type, to_type| {
+                let length = to_type.unwrap_char_length();
+                Some(move |e: HirScalarExpr| e.call_unary(CastStringToVarChar {
+                    length,
+                    fail_on_len: ccx != CastContext::Explicit,
+                }))
+            })
         }
     };
 }
@@ -371,7 +491,10 @@ fn get_cast(
         }
     }
 
-    let imp = VALID_CASTS.get(&(from.into(), to.into()))?;
+    let imp = match VALID_CASTS.get(&(from.into(), to.into())) {
+        Some(imp) => imp,
+        None => return get_cast_via_string(ecx, ccx, from, to),
+    };
     let template = match (ccx, imp.context) {
         (Explicit, Implicit) | (Explicit, Assignment) | (Explicit, Explicit) => Some(&imp.template),
         (Assignment, Implicit) | (Assignment, Assignment) => Some(&imp.template),
@@ -381,6 +504,25 @@ fn get_cast(
     template.and_then(|template| (template.0)(ecx, ccx, from, to))
 }
 
+/// Gets a cast to or from a `character` or `character varying` type for
+/// which there is no direct cast, by way of `ScalarType::String`.
+fn get_cast_via_string(
+    ecx: &ExprContext,
+    ccx: CastContext,
+    from: &ScalarType,
+    to: &ScalarType,
+) -> Option<Cast> {
+    let is_char =
+        |t: &ScalarType| matches!(t, ScalarType::Char { .. } | ScalarType::VarChar { .. });
+    if (!is_char(from) && !is_char(to)) || from == &ScalarType::String || to == &ScalarType::String
+    {
+        return None;
+    }
+    let to_string = get_cast(ecx, ccx, from, &ScalarType::String)?;
+    let from_string = get_cast(ecx, ccx, &ScalarType::String, to)?;
+    Some(Box::new(move |expr| from_string(to_string(expr))))
+}
+
 pub fn rescale_decimal(expr: HirScalarExpr, s1: u8, s2: u8) -> HirScalarExpr {
     match s1.cmp(&s2) {
         Ordering::Less => {
@@ -451,6 +593,9 @@ pub fn to_jsonb(ecx: &ExprContext, expr: HirScalarExpr) -> HirScalarExpr {
                 exprs,
             }
         }
+        Map { .. } => plan_cast("to_jsonb", ecx, CastContext::Explicit, expr, &Jsonb)
+            .expect("cast known to exist")
+            .call_unary(UnaryFunc::CastJsonbOrNullToJsonb),
         Jsonb => expr,
         _ => to_string(ecx, expr).call_unary(UnaryFunc::CastJsonbOrNullToJsonb),
     }
@@ -474,7 +619,10 @@ fn guess_compatible_cast_type(types: &[ScalarType]) -> Option<&ScalarType> {
         ScalarType::Date => 5,
         ScalarType::Timestamp => 6,
         ScalarType::TimestampTz => 7,
-        _ => 8,
+        // [`TypeCategory::String`]
+        ScalarType::Char { .. } => 8,
+        ScalarType::VarChar { .. } => 9,
+        _ => 10,
     })
 }
 
@@ -518,7 +666,12 @@ pub fn guess_best_common_type(
 
     // Determine best cast type among known types.
     if let Some(btt) = guess_compatible_cast_type(&known_types) {
-        if let ScalarType::Decimal(_, _) = btt {
+        // Strings of differing lengths have no common length.
+        if let ScalarType::Char { .. } = btt {
+            return Some(ScalarType::Char { length: None });
+        } else if let ScalarType::VarChar { .. } = btt {
+            return Some(ScalarType::VarChar { length: None });
+        } else if let ScalarType::Decimal(_, _) = btt {
             // Determine best decimal scale (i.e. largest).
             let mut max_s = 0;
             for t in known_types {
@@ -548,8 +701,22 @@ pub fn plan_coerce<'a>(
         LiteralNull => HirScalarExpr::literal_null(coerce_to.clone()),
 
         LiteralString(s) => {
+            // Coercing a literal to a length-limited string type ignores the
+            // length, so that the cast that follows the coercion enforces the
+            // length according to its context.
+            let coerce_to = match coerce_to {
+                ScalarType::Char { .. } => ScalarType::Char { length: None },
+                ScalarType::VarChar { .. } => ScalarType::VarChar { length: None },
+                _ => coerce_to.clone(),
+            };
             let lit = HirScalarExpr::literal(Datum::String(&s), ScalarType::String);
-            plan_cast("string literal", ecx, CastContext::Explicit, lit, coerce_to)?
+            plan_cast(
+                "string literal",
+                ecx,
+                CastContext::Explicit,
+                lit,
+                &coerce_to,
+            )?
         }
 
         LiteralRecord(exprs) => {
@@ -596,6 +763,19 @@ fn plan_hypothetical_cast(
     from: &ScalarType,
     to: &ScalarType,
 ) -> Option<::expr::MirScalarExpr> {
+    plan_hypothetical(ecx, from, |ecx, col_expr| {
+        plan_cast("plan_hypothetical_cast", ecx, ccx, col_expr, to).ok()
+    })
+}
+
+/// Plans the expression produced by `f` against the first column of some
+/// imaginary row, where the first column is assumed to have type `from`.
+///
+/// If `f` returns `None`, so does this function.
+fn plan_hypothetical<F>(ecx: &ExprContext, from: &ScalarType, f: F) -> Option<::expr::MirScalarExpr>
+where
+    F: FnOnce(&ExprContext, HirScalarExpr) -> Option<HirScalarExpr>,
+{
     // Reconstruct an expression context where the expression is evaluated on
     // the "first column" of some imaginary row.
     let mut scx = ecx.qcx.scx.clone();
@@ -610,7 +790,7 @@ fn plan_hypothetical_cast(
     };
     let ecx = ExprContext {
         qcx: &qcx,
-        name: "plan_hypothetical_cast",
+        name: "plan_hypothetical",
         scope: &Scope::empty(None),
         relation_type: &relation_type,
         allow_aggregates: false,
@@ -622,17 +802,10 @@ fn plan_hypothetical_cast(
         column: 0,
     });
 
-    // Determine the `ScalarExpr` required to cast our column to the target
-    // component type.
-    Some(
-        plan_cast("plan_hypothetical_cast", &ecx, ccx, col_expr, to)
-            .ok()?
-            .lower_uncorrelated()
-            .expect(
-                "lower_uncorrelated should not fail given that there is no correlation \
+    Some(f(&ecx, col_expr)?.lower_uncorrelated().expect(
+        "lower_uncorrelated should not fail given that there is no correlation \
                 in the input col_expr",
-            ),
-    )
+    ))
 }
 
 /// Plans a cast between [`ScalarType`]s, specifying which types of casts are
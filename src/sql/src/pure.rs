@@ -20,7 +20,8 @@ use tokio::time::Duration;
 
 use repr::strconv;
 use sql_parser::ast::{
-    AvroSchema, Connector, CreateSourceStatement, CsrSeed, Format, Ident, Raw, Statement,
+    AlterSourceSchemaStatement, AvroSchema, Connector, CreateSourceStatement, CsrSeed, Format,
+    Ident, Raw, Statement,
 };
 
 use crate::kafka_util;
@@ -35,7 +36,24 @@ use crate::normalize;
 /// time to complete. As a result purification does *not* have access to a
 /// [`Catalog`](crate::catalog::Catalog), as that would require locking access
 /// to the catalog for an unbounded amount of time.
-pub async fn purify(mut stmt: Statement<Raw>) -> Result<Statement<Raw>, anyhow::Error> {
+pub async fn purify(stmt: Statement<Raw>) -> Result<Statement<Raw>, anyhow::Error> {
+    purify_inner(stmt, true).await
+}
+
+/// Like [`purify`], but never connects to external systems, like Kafka
+/// brokers or schema registries.
+///
+/// Checks that would require a connection, like validating credentials, are
+/// skipped. Statements that cannot be purified without a connection, like
+/// those that fetch a schema from a Confluent Schema Registry, are rejected.
+pub async fn purify_offline(stmt: Statement<Raw>) -> Result<Statement<Raw>, anyhow::Error> {
+    purify_inner(stmt, false).await
+}
+
+async fn purify_inner(
+    mut stmt: Statement<Raw>,
+    connect: bool,
+) -> Result<Statement<Raw>, anyhow::Error> {
     if let Statement::CreateSource(CreateSourceStatement {
         col_names,
         connector,
@@ -57,7 +75,9 @@ pub async fn purify(mut stmt: Statement<Raw>) -> Result<Statement<Raw>, anyhow::
 
                 // Verify that the provided security options are valid and then test them.
                 config_options = kafka_util::extract_config(&mut with_options_map)?;
-                kafka_util::test_config(&broker, &config_options)?;
+                if connect {
+                    kafka_util::test_config(&broker, &config_options)?;
+                }
             }
             Connector::AvroOcf { path, .. } => {
                 let path = path.clone();
@@ -81,8 +101,10 @@ pub async fn purify(mut stmt: Statement<Raw>) -> Result<Statement<Raw>, anyhow::
             }
             Connector::S3 { .. } => {
                 let aws_info = normalize::aws_connect_info(&mut with_options_map, None)?;
-                aws_util::aws::validate_credentials(aws_info.clone(), Duration::from_secs(1))
-                    .await?;
+                if connect {
+                    aws_util::aws::validate_credentials(aws_info.clone(), Duration::from_secs(1))
+                        .await?;
+                }
             }
             Connector::Kinesis { arn } => {
                 let region = arn
@@ -92,16 +114,21 @@ pub async fn purify(mut stmt: Statement<Raw>) -> Result<Statement<Raw>, anyhow::
                     .ok_or_else(|| anyhow!("Provided ARN does not include an AWS region"))?;
 
                 let aws_info = normalize::aws_connect_info(&mut with_options_map, Some(region))?;
-                aws_util::aws::validate_credentials(aws_info, Duration::from_secs(1)).await?;
+                if connect {
+                    aws_util::aws::validate_credentials(aws_info, Duration::from_secs(1)).await?;
+                }
             }
             Connector::Postgres { .. } => (),
         }
 
-        purify_format(format, connector, col_names, file, &config_options).await?;
+        purify_format(format, connector, col_names, file, &config_options, connect).await?;
         if let sql_parser::ast::Envelope::Upsert(format) = envelope {
-            purify_format(format, connector, col_names, None, &config_options).await?;
+            purify_format(format, connector, col_names, None, &config_options, connect).await?;
         }
     }
+    if let Statement::AlterSourceSchema(AlterSourceSchemaStatement { schema, .. }) = &mut stmt {
+        purify_protobuf_schema(schema).await?;
+    }
     Ok(stmt)
 }
 
@@ -111,6 +138,7 @@ async fn purify_format(
     col_names: &mut Vec<Ident>,
     file: Option<tokio::fs::File>,
     connector_options: &BTreeMap<String, String>,
+    connect: bool,
 ) -> Result<(), anyhow::Error> {
     match format {
         Some(Format::Avro(schema)) => match schema {
@@ -125,6 +153,13 @@ async fn purify_format(
                     bail!("Confluent Schema Registry is only supported with Kafka sources")
                 };
                 if seed.is_none() {
+                    if !connect {
+                        bail!(
+                            "cannot fetch the schema for topic {} from the schema registry \
+                             without connecting to it",
+                            topic
+                        );
+                    }
                     let url = url.parse()?;
 
                     let ccsr_config = kafka_util::generate_ccsr_client_config(
@@ -156,14 +191,7 @@ async fn purify_format(
             }
             _ => {}
         },
-        Some(Format::Protobuf { schema, .. }) => {
-            if let sql_parser::ast::Schema::File(path) = schema {
-                let descriptors = tokio::fs::read(path).await?;
-                let mut buf = String::new();
-                strconv::format_bytes(&mut buf, &descriptors);
-                *schema = sql_parser::ast::Schema::Inline(buf);
-            }
-        }
+        Some(Format::Protobuf { schema, .. }) => purify_protobuf_schema(schema).await?,
         Some(Format::Csv {
             header_row,
             delimiter,
@@ -191,6 +219,17 @@ async fn purify_format(
     Ok(())
 }
 
+/// Inlines the descriptors of a Protobuf schema that refers to a file.
+async fn purify_protobuf_schema(schema: &mut sql_parser::ast::Schema) -> Result<(), anyhow::Error> {
+    if let sql_parser::ast::Schema::File(path) = schema {
+        let descriptors = tokio::fs::read(path).await?;
+        let mut buf = String::new();
+        strconv::format_bytes(&mut buf, &descriptors);
+        *schema = sql_parser::ast::Schema::Inline(buf);
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct Schema {
     pub key_schema: Option<String>,
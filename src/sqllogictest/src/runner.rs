@@ -53,6 +53,7 @@ use tokio_postgres::{NoTls, Row, SimpleQueryMessage};
 use uuid::Uuid;
 
 use pgrepr::{Interval, Jsonb, Numeric, Value};
+use repr::adt::range::RangeBound;
 use repr::ColumnName;
 use sql::ast::Statement;
 
@@ -288,9 +289,12 @@ impl<'a> FromSql<'a> for Slt {
     ) -> Result<Self, Box<dyn Error + 'static + Send + Sync>> {
         Ok(match *ty {
             PgType::BOOL => Self(Value::Bool(types::bool_from_sql(raw)?)),
+            PgType::BPCHAR => Self(Value::BpChar(types::text_from_sql(raw)?.to_string())),
             PgType::BYTEA => Self(Value::Bytea(types::bytea_from_sql(raw).to_vec())),
+            PgType::CIDR => Self(Value::decode_binary(&pgrepr::Type::Cidr, raw)?),
             PgType::FLOAT4 => Self(Value::Float4(types::float4_from_sql(raw)?)),
             PgType::FLOAT8 => Self(Value::Float8(types::float8_from_sql(raw)?)),
+            PgType::INET => Self(Value::decode_binary(&pgrepr::Type::Inet, raw)?),
             PgType::DATE => Self(Value::Date(NaiveDate::from_sql(ty, raw)?)),
             PgType::INT4 => Self(Value::Int4(types::int4_from_sql(raw)?)),
             PgType::INT8 => Self(Value::Int8(types::int8_from_sql(raw)?)),
@@ -302,7 +306,10 @@ impl<'a> FromSql<'a> for Slt {
             PgType::TIME => Self(Value::Time(NaiveTime::from_sql(ty, raw)?)),
             PgType::TIMESTAMP => Self(Value::Timestamp(NaiveDateTime::from_sql(ty, raw)?)),
             PgType::TIMESTAMPTZ => Self(Value::TimestampTz(DateTime::<Utc>::from_sql(ty, raw)?)),
+            PgType::TSQUERY => Self(Value::decode_binary(&pgrepr::Type::TsQuery, raw)?),
+            PgType::TS_VECTOR => Self(Value::decode_binary(&pgrepr::Type::TsVector, raw)?),
             PgType::UUID => Self(Value::Uuid(Uuid::from_sql(ty, raw)?)),
+            PgType::VARCHAR => Self(Value::VarChar(types::text_from_sql(raw)?.to_string())),
             PgType::RECORD => {
                 let num_fields = read_be_i32(&mut raw)?;
                 let mut tuple = vec![];
@@ -317,6 +324,11 @@ impl<'a> FromSql<'a> for Slt {
                 }
                 Self(Value::Record(tuple))
             }
+            // Lists and maps are sent using their text encoding, even when the
+            // binary format is requested.
+            _ if ty.oid() == pgrepr::LIST.oid() || ty.oid() == pgrepr::MAP.oid() => {
+                Self(Value::Text(types::text_from_sql(raw)?.to_string()))
+            }
 
             _ => match ty.kind() {
                 PgKind::Array(arr_type) => {
@@ -345,22 +357,35 @@ impl<'a> FromSql<'a> for Slt {
                         elements,
                     })
                 }
+                PgKind::Range(elem_type) => match types::range_from_sql(raw)? {
+                    types::Range::Empty => Self(Value::Range(None)),
+                    types::Range::Nonempty(lower, upper) => Self(Value::Range(Some((
+                        range_bound_from_sql(elem_type, lower)?,
+                        range_bound_from_sql(elem_type, upper)?,
+                    )))),
+                },
                 _ => unreachable!(),
             },
         })
     }
     fn accepts(ty: &PgType) -> bool {
         match ty.kind() {
-            PgKind::Array(_) | PgKind::Composite(_) => return true,
+            PgKind::Array(_) | PgKind::Composite(_) | PgKind::Range(_) => return true,
             _ => {}
         }
+        if ty.oid() == pgrepr::LIST.oid() || ty.oid() == pgrepr::MAP.oid() {
+            return true;
+        }
         matches!(
             *ty,
             PgType::BOOL
+                | PgType::BPCHAR
                 | PgType::BYTEA
+                | PgType::CIDR
                 | PgType::DATE
                 | PgType::FLOAT4
                 | PgType::FLOAT8
+                | PgType::INET
                 | PgType::INT2
                 | PgType::INT4
                 | PgType::INT8
@@ -373,11 +398,30 @@ impl<'a> FromSql<'a> for Slt {
                 | PgType::TIME
                 | PgType::TIMESTAMP
                 | PgType::TIMESTAMPTZ
+                | PgType::TSQUERY
+                | PgType::TS_VECTOR
                 | PgType::UUID
+                | PgType::VARCHAR
         )
     }
 }
 
+fn range_bound_from_sql(
+    elem_type: &PgType,
+    bound: types::RangeBound<Option<&[u8]>>,
+) -> Result<RangeBound<Box<Value>>, Box<dyn Error + Sync + Send>> {
+    let (value, inclusive) = match bound {
+        types::RangeBound::Inclusive(value) => (value, true),
+        types::RangeBound::Exclusive(value) => (value, false),
+        types::RangeBound::Unbounded => (None, false),
+    };
+    let value = match value {
+        Some(value) => Some(Box::new(Slt::from_sql(elem_type, value)?.0)),
+        None => None,
+    };
+    Ok(RangeBound { value, inclusive })
+}
+
 // From postgres-types/src/private.rs.
 fn read_be_i32(buf: &mut &[u8]) -> Result<i32, Box<dyn Error + Sync + Send>> {
     if buf.len() < 4 {
@@ -418,7 +462,9 @@ fn format_datum(d: Slt, typ: &Type, mode: Mode, col: usize) -> String {
         (Type::Integer, Value::Float4(f)) => format!("{:.0}", f.trunc()),
         (Type::Integer, Value::Float8(f)) => format!("{:.0}", f.trunc()),
         // This is so wrong, but sqlite needs it.
-        (Type::Integer, Value::Text(_)) => "0".to_string(),
+        (Type::Integer, Value::Text(_))
+        | (Type::Integer, Value::BpChar(_))
+        | (Type::Integer, Value::VarChar(_)) => "0".to_string(),
         (Type::Integer, Value::Bool(b)) => i8::from(b).to_string(),
 
         (Type::Real, Value::Int4(i)) => format!("{:.3}", i),
@@ -436,7 +482,9 @@ fn format_datum(d: Slt, typ: &Type, mode: Mode, col: usize) -> String {
             Mode::Cockroach => format!("{}", d),
         },
 
-        (Type::Text, Value::Text(s)) => {
+        (Type::Text, Value::Text(s))
+        | (Type::Text, Value::BpChar(s))
+        | (Type::Text, Value::VarChar(s)) => {
             if s.is_empty() {
                 "(empty)".to_string()
             } else {
@@ -504,13 +552,27 @@ impl Runner {
         let mz_config = materialized::Config {
             logging: None,
             timestamp_frequency: Duration::from_millis(10),
+            quotas: Default::default(),
+            slow_query_threshold: None,
+            statement_history_size: 0,
+            stalled_dataflow_threshold: None,
+            shared_dataflow_idle_timeout: None,
+            shared_dataflow_min_peeks: 2,
+            freshness_slo: None,
+            rehydration_budget: None,
+            lazy_view_idle_timeout: Duration::from_secs(300),
+            log_filter: None,
             cache: None,
             logical_compaction_window: None,
+            auto_tune_logical_compaction_window: false,
             workers: config.workers,
             timely_worker: timely::WorkerConfig::default(),
             data_directory: temp_dir.path().to_path_buf(),
+            init_sql: None,
             symbiosis_url: Some("postgres://".into()),
             listen_addr: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0),
+            flight_listen_addr: None,
+            control_listen_addr: None,
             tls: None,
             experimental_mode: true,
             telemetry_url: None,
@@ -128,6 +128,7 @@ END $$;
             catalog,
             ids: HashSet::new(),
             param_types: Rc::new(RefCell::new(BTreeMap::new())),
+            notices: Rc::new(RefCell::new(vec![])),
         };
         Ok(match stmt {
             Statement::CreateTable(CreateTableStatement {
@@ -369,7 +370,7 @@ fn push_column(
                     let bytes = get_column_inner::<Vec<u8>>(postgres_row, i, nullable)?;
                     row.push(bytes.as_deref().into());
                 }
-                "char" | "text" | "varchar" => {
+                "bpchar" | "char" | "text" | "varchar" => {
                     let string = get_column_inner::<String>(postgres_row, i, nullable)?;
                     row.push(string.as_deref().into());
                 }
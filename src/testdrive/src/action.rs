@@ -35,10 +35,12 @@ use crate::util;
 
 mod avro_ocf;
 mod file;
+mod http;
 mod kafka;
 mod kinesis;
 mod postgres;
 mod s3;
+mod schema_registry;
 mod sleep;
 mod sql;
 
@@ -352,6 +354,10 @@ pub fn build(cmds: Vec<PosCommand>, state: &State) -> Result<Vec<PosAction>, Err
             _ => "".into(),
         },
     );
+    vars.insert(
+        "testdrive.materialized-addr".into(),
+        state.materialized_addr.clone(),
+    );
     vars.insert(
         "testdrive.materialized-user".into(),
         state.materialized_user.clone(),
@@ -378,6 +384,7 @@ pub fn build(cmds: Vec<PosCommand>, state: &State) -> Result<Vec<PosAction>, Err
                     }
                     "file-append" => Box::new(file::build_append(builtin).map_err(wrap_err)?),
                     "file-delete" => Box::new(file::build_delete(builtin).map_err(wrap_err)?),
+                    "http-request" => Box::new(http::build_request(builtin).map_err(wrap_err)?),
                     "kafka-add-partitions" => {
                         Box::new(kafka::build_add_partitions(builtin).map_err(wrap_err)?)
                     }
@@ -407,6 +414,12 @@ pub fn build(cmds: Vec<PosCommand>, state: &State) -> Result<Vec<PosAction>, Err
                     "s3-add-notifications" => {
                         Box::new(s3::build_add_notifications(builtin).map_err(wrap_err)?)
                     }
+                    "schema-registry-delete-version" => {
+                        Box::new(schema_registry::build_delete_version(builtin).map_err(wrap_err)?)
+                    }
+                    "schema-registry-set-compatibility" => Box::new(
+                        schema_registry::build_set_compatibility(builtin).map_err(wrap_err)?,
+                    ),
                     "set-sql-timeout" => {
                         let duration = builtin.args.string("duration").map_err(wrap_err)?;
                         if duration.to_lowercase() == "default" {
@@ -10,7 +10,7 @@
 use std::path;
 use std::str::FromStr;
 
-use async_compression::tokio::write::GzipEncoder;
+use async_compression::tokio::write::{BzEncoder, GzipEncoder, ZstdEncoder};
 use async_trait::async_trait;
 use tokio::fs::OpenOptions;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
@@ -25,8 +25,10 @@ pub struct AppendAction {
     compression: Compression,
 }
 
-enum Compression {
+pub(crate) enum Compression {
     Gzip,
+    Zstd,
+    Bzip2,
     None,
 }
 
@@ -36,19 +38,44 @@ impl FromStr for Compression {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "gzip" => Ok(Compression::Gzip),
+            "zstd" => Ok(Compression::Zstd),
+            "bzip2" => Ok(Compression::Bzip2),
             "none" => Ok(Compression::None),
             f => Err(format!("unknown compression format: {}", f)),
         }
     }
 }
 
-fn build_compression(cmd: &mut BuiltinCommand) -> Result<Compression, String> {
+pub(crate) fn build_compression(cmd: &mut BuiltinCommand) -> Result<Compression, String> {
     match cmd.args.opt_string("compression") {
         Some(s) => s.parse(),
         None => Ok(Compression::None),
     }
 }
 
+/// Compresses `contents` into a complete stream of the specified format.
+pub(crate) async fn compress(
+    compression: &Compression,
+    contents: &[u8],
+) -> Result<Vec<u8>, String> {
+    async fn write<W: AsyncWrite + Unpin>(mut writer: W, contents: &[u8]) -> Result<(), String> {
+        writer
+            .write_all(contents)
+            .await
+            .map_err(|e| e.to_string())?;
+        writer.shutdown().await.map_err(|e| e.to_string())
+    }
+
+    let mut out = vec![];
+    match compression {
+        Compression::Gzip => write(GzipEncoder::new(&mut out), contents).await?,
+        Compression::Zstd => write(ZstdEncoder::new(&mut out), contents).await?,
+        Compression::Bzip2 => write(BzEncoder::new(&mut out), contents).await?,
+        Compression::None => return Ok(contents.to_vec()),
+    }
+    Ok(out)
+}
+
 fn build_path(cmd: &mut BuiltinCommand) -> Result<String, String> {
     let path = cmd.args.string("path")?;
     if path.contains(path::MAIN_SEPARATOR) {
@@ -86,22 +113,15 @@ impl Action for AppendAction {
     async fn redo(&self, state: &mut State) -> Result<(), String> {
         let path = state.temp_dir.path().join(&self.path);
         println!("Appending to file {}", path.display());
-        let file = OpenOptions::new()
+        let mut file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&path)
             .await
             .map_err(|e| e.to_string())?;
 
-        let mut file: Box<dyn AsyncWrite + Unpin + Send> = match self.compression {
-            Compression::Gzip => Box::new(GzipEncoder::new(file)),
-            Compression::None => Box::new(file),
-        };
-
-        file.write_all(&self.contents)
-            .await
-            .map_err(|e| e.to_string())?;
-
+        let contents = compress(&self.compression, &self.contents).await?;
+        file.write_all(&contents).await.map_err(|e| e.to_string())?;
         file.shutdown().await.map_err(|e| e.to_string())?;
         Ok(())
     }
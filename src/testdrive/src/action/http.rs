@@ -0,0 +1,80 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::{Method, StatusCode, Url};
+
+use crate::action::{Action, State};
+use crate::parser::BuiltinCommand;
+
+pub struct RequestAction {
+    method: Method,
+    url: Url,
+    content_type: Option<String>,
+    body: String,
+    status: Option<StatusCode>,
+}
+
+pub fn build_request(mut cmd: BuiltinCommand) -> Result<RequestAction, String> {
+    let method = cmd.args.string("method")?;
+    let method = Method::from_bytes(method.to_uppercase().as_bytes())
+        .map_err(|_| format!("invalid HTTP method: {}", method))?;
+    let url = cmd.args.parse("url")?;
+    let content_type = cmd.args.opt_string("content-type");
+    let status = cmd
+        .args
+        .opt_parse::<u16>("status")?
+        .map(|code| {
+            StatusCode::from_u16(code).map_err(|_| format!("invalid HTTP status code: {}", code))
+        })
+        .transpose()?;
+    cmd.args.done()?;
+    Ok(RequestAction {
+        method,
+        url,
+        content_type,
+        body: cmd.input.join("\n"),
+        status,
+    })
+}
+
+#[async_trait]
+impl Action for RequestAction {
+    async fn undo(&self, _: &mut State) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn redo(&self, _: &mut State) -> Result<(), String> {
+        println!("Sending HTTP {} request to {}", self.method, self.url);
+        let client = reqwest::Client::new();
+        let mut req = client.request(self.method.clone(), self.url.clone());
+        if let Some(content_type) = &self.content_type {
+            req = req.header(CONTENT_TYPE, content_type);
+        }
+        let res = req
+            .body(self.body.clone())
+            .send()
+            .await
+            .map_err(|e| format!("sending HTTP request: {}", e))?;
+        let status = res.status();
+        let ok = match self.status {
+            Some(expected) => status == expected,
+            None => status.is_success(),
+        };
+        if !ok {
+            let body = res.text().await.unwrap_or_default();
+            return Err(format!(
+                "HTTP request returned unexpected status {}: {}",
+                status, body
+            ));
+        }
+        Ok(())
+    }
+}
@@ -23,6 +23,7 @@ use rusoto_sqs::{
     ReceiveMessageRequest, SetQueueAttributesRequest, Sqs,
 };
 
+use crate::action::file::{self, Compression};
 use crate::action::{Action, State};
 use crate::parser::BuiltinCommand;
 
@@ -68,6 +69,7 @@ impl Action for CreateBucketAction {
 pub struct PutObjectAction {
     bucket: String,
     key: String,
+    compression: Compression,
     contents: String,
 }
 
@@ -75,6 +77,7 @@ pub fn build_put_object(mut cmd: BuiltinCommand) -> Result<PutObjectAction, Stri
     Ok(PutObjectAction {
         bucket: cmd.args.string("bucket")?,
         key: cmd.args.string("key")?,
+        compression: file::build_compression(&mut cmd)?,
         contents: cmd.input.join("\n"),
     })
 }
@@ -88,11 +91,12 @@ impl Action for PutObjectAction {
     async fn redo(&self, state: &mut State) -> Result<(), String> {
         println!("Creating S3 Bucket {}", self.bucket);
 
+        let contents = file::compress(&self.compression, self.contents.as_bytes()).await?;
         state
             .s3_client
             .put_object(PutObjectRequest {
                 bucket: self.bucket.clone(),
-                body: Some(self.contents.clone().into_bytes().into()),
+                body: Some(contents.into()),
                 key: self.key.clone(),
                 ..Default::default()
             })
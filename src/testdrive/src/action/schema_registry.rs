@@ -0,0 +1,77 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use async_trait::async_trait;
+use ccsr::CompatibilityLevel;
+
+use crate::action::{Action, State};
+use crate::parser::BuiltinCommand;
+
+pub struct DeleteVersionAction {
+    subject: String,
+    version: i32,
+}
+
+pub fn build_delete_version(mut cmd: BuiltinCommand) -> Result<DeleteVersionAction, String> {
+    let subject = cmd.args.string("subject")?;
+    let version = cmd.args.parse("version")?;
+    cmd.args.done()?;
+    Ok(DeleteVersionAction { subject, version })
+}
+
+#[async_trait]
+impl Action for DeleteVersionAction {
+    async fn undo(&self, _: &mut State) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn redo(&self, state: &mut State) -> Result<(), String> {
+        println!(
+            "Deleting version {} of schema registry subject {}",
+            self.version, self.subject
+        );
+        state
+            .ccsr_client
+            .delete_subject_version(&self.subject, self.version)
+            .await
+            .map_err(|e| format!("deleting schema version: {}", e))
+    }
+}
+
+pub struct SetCompatibilityAction {
+    subject: String,
+    level: CompatibilityLevel,
+}
+
+pub fn build_set_compatibility(mut cmd: BuiltinCommand) -> Result<SetCompatibilityAction, String> {
+    let subject = cmd.args.string("subject")?;
+    let level = cmd.args.parse("level")?;
+    cmd.args.done()?;
+    Ok(SetCompatibilityAction { subject, level })
+}
+
+#[async_trait]
+impl Action for SetCompatibilityAction {
+    async fn undo(&self, _: &mut State) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn redo(&self, state: &mut State) -> Result<(), String> {
+        println!(
+            "Setting compatibility level of schema registry subject {} to {}",
+            self.subject,
+            self.level.as_str()
+        );
+        state
+            .ccsr_client
+            .set_subject_compatibility_level(&self.subject, self.level)
+            .await
+            .map_err(|e| format!("setting compatibility level: {}", e))
+    }
+}
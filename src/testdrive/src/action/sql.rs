@@ -148,7 +148,7 @@ impl Action for SqlAction {
                     .text()
                     .await
                     .map_err(|e| e.to_string())?;
-                    if disk_state != mem_state {
+                    if strip_oids(&disk_state)? != strip_oids(&mem_state)? {
                         return Err(format!(
                             "the on-disk state of the catalog does not match its in-memory state\n\
                              disk:{}\n\
@@ -165,6 +165,20 @@ impl Action for SqlAction {
     }
 }
 
+/// Parses a catalog dump, removing the object OIDs, which are reassigned when
+/// the catalog is reopened.
+fn strip_oids(dump: &str) -> Result<serde_json::Value, String> {
+    let mut dump: serde_json::Value = serde_json::from_str(dump).map_err(|e| e.to_string())?;
+    if let Some(objects) = dump.get_mut("objects").and_then(|o| o.as_array_mut()) {
+        for object in objects {
+            if let Some(object) = object.as_object_mut() {
+                object.remove("oid");
+            }
+        }
+    }
+    Ok(dump)
+}
+
 impl SqlAction {
     async fn try_drop(
         &self,
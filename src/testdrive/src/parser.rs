@@ -507,7 +507,6 @@ impl ArgMap {
         }
     }
 
-    #[allow(dead_code)]
     pub fn parse<T>(&mut self, name: &str) -> Result<T, String>
     where
         T: FromStr,
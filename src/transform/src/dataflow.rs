@@ -83,8 +83,13 @@ fn inline_views(dataflow: &mut DataflowDesc) {
                     occurrences_in_later_views.push(other);
                 }
             }
-            // Inline if the view is referenced in one view and no exports.
-            if !occurs_in_export && occurrences_in_later_views.len() == 1 {
+            // Inline if the view is referenced in one view and no exports,
+            // unless the two views would render their reductions differently.
+            if !occurs_in_export
+                && occurrences_in_later_views.len() == 1
+                && dataflow.objects_to_build[index].reduce_hot_set_size
+                    == dataflow.objects_to_build[occurrences_in_later_views[0]].reduce_hot_set_size
+            {
                 let other = occurrences_in_later_views[0];
                 // We can remove this view and insert it in the later view,
                 // but are not able to relocate the later view `other`.
@@ -263,20 +268,25 @@ fn optimize_dataflow_filters(dataflow: &mut DataflowDesc) {
 /// Analysis to identify monotonic collections, especially TopK inputs.
 pub mod monotonic {
 
-    use dataflow_types::{DataflowDesc, SourceConnector, SourceEnvelope};
+    use dataflow_types::{DataflowDesc, DeduplicateKeep, SourceConnector, SourceEnvelope};
+    use expr::Id;
     use expr::MirRelationExpr;
-    use expr::{GlobalId, Id};
     use std::collections::HashSet;
 
     // Determines if a relation is monotonic, and applies any optimizations along the way.
-    fn is_monotonic(expr: &mut MirRelationExpr, sources: &HashSet<GlobalId>) -> bool {
+    //
+    // `sources` contains the identifiers of collections, global or local, that
+    // are known to be monotonic.
+    fn is_monotonic(expr: &mut MirRelationExpr, sources: &mut HashSet<Id>) -> bool {
         match expr {
-            MirRelationExpr::Get { id, .. } => {
-                if let Id::Global(id) = id {
-                    sources.contains(id)
-                } else {
-                    false
+            MirRelationExpr::Get { id, .. } => sources.contains(id),
+            MirRelationExpr::Let { id, value, body } => {
+                if is_monotonic(value, sources) {
+                    sources.insert(Id::Local(*id));
                 }
+                let is_monotonic = is_monotonic(body, sources);
+                sources.remove(&Id::Local(*id));
+                is_monotonic
             }
             MirRelationExpr::Project { input, .. } => is_monotonic(input, sources),
             MirRelationExpr::Filter { input, predicates } => {
@@ -331,7 +341,7 @@ pub mod monotonic {
                 rows.iter().all(|(_, diff)| diff > &0)
             }
             MirRelationExpr::Threshold { input } => is_monotonic(input, sources),
-            // Let and Negate remain
+            // Negate remains
             _ => {
                 expr.visit1_mut(|e| {
                     is_monotonic(e, sources);
@@ -348,15 +358,27 @@ pub mod monotonic {
             if let SourceConnector::External {
                 envelope: SourceEnvelope::None,
                 ..
+            }
+            | SourceConnector::External {
+                envelope:
+                    SourceEnvelope::Deduplicate {
+                        keep: DeduplicateKeep::First,
+                        ..
+                    },
+                ..
             } = source_desc.connector
             {
-                monotonic.insert(source_id.clone());
+                monotonic.insert(Id::Global(*source_id));
             }
         }
 
-        // Propagate predicate information from outputs to inputs.
+        // Propagate monotonicity from inputs to outputs. Objects are built in
+        // dependency order, so a monotonic view is known to be so before any
+        // subsequent object that reads from it is analyzed.
         for build_desc in dataflow.objects_to_build.iter_mut() {
-            is_monotonic(build_desc.relation_expr.as_mut(), &monotonic);
+            if is_monotonic(build_desc.relation_expr.as_mut(), &mut monotonic) {
+                monotonic.insert(Id::Global(build_desc.id));
+            }
         }
     }
 }
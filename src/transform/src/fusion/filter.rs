@@ -35,6 +35,7 @@
 //! Filter.transform(&mut expr, TransformArgs {
 //!   id_gen: &mut Default::default(),
 //!   indexes: &std::collections::HashMap::new(),
+//!   foreign_keys: &std::collections::HashMap::new(),
 //! });
 //!
 //! let correct = input.filter(vec![predicate0]);
@@ -17,13 +17,30 @@
 //! and identifying opportunities to use indexes to replace filters.
 
 use std::collections::HashMap;
+use std::fmt;
 
 use crate::TransformArgs;
 use expr::{Id, JoinInputMapper, MirRelationExpr, MirScalarExpr};
 
 /// Determines the join implementation for join operators.
-#[derive(Debug)]
-pub struct JoinImplementation;
+#[derive(Default)]
+pub struct JoinImplementation {
+    /// Plan joins as delta queries even when some of the input arrangements
+    /// they require do not yet exist, rather than only when all of them do.
+    ///
+    /// Delta queries arrange each input by each key it is joined on, but
+    /// never arrange intermediate results, which usually makes them the
+    /// better choice when the inputs are large and the results are small.
+    pub prefer_delta_queries: bool,
+}
+
+// Transforms are printed by name when tracing optimization steps, so leave the
+// option out of the debug representation.
+impl fmt::Debug for JoinImplementation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("JoinImplementation")
+    }
+}
 
 impl crate::Transform for JoinImplementation {
     fn transform(
@@ -166,6 +183,7 @@ impl JoinImplementation {
                 &input_mapper,
                 &available_arrangements,
                 &unique_keys,
+                self.prefer_delta_queries,
             );
             let differential_plan = differential::plan(
                 relation,
@@ -188,11 +206,14 @@ mod delta_queries {
     /// Creates a delta query plan, and any predicates that need to be lifted.
     ///
     /// The method returns `None` if it fails to find a sufficiently pleasing plan.
+    /// Unless `prefer` is set, a plan is only pleasing if it uses existing
+    /// arrangements exclusively.
     pub fn plan(
         join: &MirRelationExpr,
         input_mapper: &JoinInputMapper,
         available: &[Vec<Vec<MirScalarExpr>>],
         unique_keys: &[Vec<Vec<usize>>],
+        prefer: bool,
     ) -> Option<MirRelationExpr> {
         let mut new_join = join.clone();
 
@@ -217,10 +238,12 @@ mod delta_queries {
 
             // A viable delta query requires that, for every order,
             // there is an arrangement for every input except for
-            // the starting one.
-            if !orders
-                .iter()
-                .all(|o| o.iter().skip(1).all(|(c, _, _)| c.arranged))
+            // the starting one. If delta queries are preferred, we
+            // are willing to build the missing input arrangements.
+            if !prefer
+                && !orders
+                    .iter()
+                    .all(|o| o.iter().skip(1).all(|(c, _, _)| c.arranged))
             {
                 return None;
             }
@@ -27,7 +27,7 @@ use std::fmt;
 
 use expr::MirRelationExpr;
 use expr::MirScalarExpr;
-use expr::{GlobalId, IdGen};
+use expr::{ForeignKey, GlobalId, IdGen};
 
 pub mod column_knowledge;
 pub mod cse;
@@ -62,6 +62,8 @@ pub struct TransformArgs<'a> {
     pub id_gen: &'a mut IdGen,
     /// The indexes accessible.
     pub indexes: &'a HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+    /// The foreign keys declared on accessible collections.
+    pub foreign_keys: &'a HashMap<GlobalId, Vec<ForeignKey>>,
 }
 
 /// Types capable of transforming relation expressions.
@@ -119,6 +121,7 @@ impl Transform for Fixpoint {
                     TransformArgs {
                         id_gen: args.id_gen,
                         indexes: args.indexes,
+                        foreign_keys: args.foreign_keys,
                     },
                 )?;
             }
@@ -133,6 +136,7 @@ impl Transform for Fixpoint {
                 TransformArgs {
                     id_gen: args.id_gen,
                     indexes: args.indexes,
+                    foreign_keys: args.foreign_keys,
                 },
             )?;
         }
@@ -163,6 +167,7 @@ impl Optimizer {
         &self,
         relation: &mut MirRelationExpr,
         indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+        foreign_keys: &HashMap<GlobalId, Vec<ForeignKey>>,
     ) -> Result<(), TransformError> {
         let mut id_gen = Default::default();
         for transform in self.transforms.iter() {
@@ -171,6 +176,7 @@ impl Optimizer {
                 TransformArgs {
                     id_gen: &mut id_gen,
                     indexes,
+                    foreign_keys,
                 },
             )?;
         }
@@ -180,6 +186,17 @@ impl Optimizer {
 
 impl Default for Optimizer {
     fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl Optimizer {
+    /// Builds an optimizer with the standard sequence of transforms.
+    ///
+    /// If `prefer_delta_joins` is set, joins are planned as delta queries
+    /// even when that requires arranging join inputs that are not already
+    /// arranged. See `JoinImplementation` for details.
+    pub fn new(prefer_delta_joins: bool) -> Self {
         let transforms: Vec<Box<dyn crate::Transform + Send>> = vec![
             // The first block are peep-hole optimizations that simplify
             // the representation of the query and are largely uncontentious.
@@ -244,7 +261,9 @@ impl Default for Optimizer {
                 limit: 100,
                 transforms: vec![
                     Box::new(crate::projection_lifting::ProjectionLifting),
-                    Box::new(crate::join_implementation::JoinImplementation),
+                    Box::new(crate::join_implementation::JoinImplementation {
+                        prefer_delta_queries: prefer_delta_joins,
+                    }),
                     Box::new(crate::fusion::filter::Filter),
                     Box::new(crate::demand::Demand),
                     Box::new(crate::map_lifting::LiteralLifting),
@@ -253,22 +272,23 @@ impl Default for Optimizer {
             Box::new(crate::reduction_pushdown::ReductionPushdown),
             Box::new(crate::cse::map::Map),
             Box::new(crate::projection_lifting::ProjectionLifting),
-            Box::new(crate::join_implementation::JoinImplementation),
+            Box::new(crate::join_implementation::JoinImplementation {
+                prefer_delta_queries: prefer_delta_joins,
+            }),
             Box::new(crate::fusion::project::Project),
             Box::new(crate::reduction::FoldConstants),
         ];
         Self { transforms }
     }
-}
 
-impl Optimizer {
     /// Optimizes the supplied relation expression.
     pub fn optimize(
         &mut self,
         mut relation: MirRelationExpr,
         indexes: &HashMap<GlobalId, Vec<(GlobalId, Vec<MirScalarExpr>)>>,
+        foreign_keys: &HashMap<GlobalId, Vec<ForeignKey>>,
     ) -> Result<expr::OptimizedMirRelationExpr, TransformError> {
-        self.transform(&mut relation, indexes)?;
+        self.transform(&mut relation, indexes, foreign_keys)?;
         Ok(expr::OptimizedMirRelationExpr(relation))
     }
 
@@ -377,6 +377,7 @@ impl LiteralLifting {
                 limit: _,
                 offset: _,
                 monotonic: _,
+                expected_group_size: _,
             } => {
                 let literals = self.action(input, gets);
                 if !literals.is_empty() {
@@ -276,7 +276,8 @@ mod tests {
                 &mut test_expr,
                 TransformArgs {
                     id_gen: &mut IdGen::default(),
-                    indexes: &mut HashMap::new()
+                    indexes: &mut HashMap::new(),
+                    foreign_keys: &mut HashMap::new(),
                 }
             )
             .is_ok());
@@ -49,6 +49,7 @@
 //! PredicatePushdown.transform(&mut expr, TransformArgs {
 //!   id_gen: &mut Default::default(),
 //!   indexes: &std::collections::HashMap::new(),
+//!   foreign_keys: &std::collections::HashMap::new(),
 //! });
 //! ```
 
@@ -194,7 +195,7 @@ impl PredicatePushdown {
                             let mut pushed = false;
                             // Attempt to push down each predicate to each input.
                             for (index, push_down) in push_downs.iter_mut().enumerate() {
-                                if let MirRelationExpr::ArrangeBy { .. } = inputs[index] {
+                                if is_imported_arrangement(&inputs[index]) {
                                     // do nothing. We do not want to push down a filter and block
                                     // usage of an index
                                 } else if let Some(localized) = input_mapper
@@ -396,6 +397,20 @@ impl PredicatePushdown {
                         let mut pushdown = Vec::new();
                         let mut retained = Vec::new();
                         for mut predicate in predicates.drain(..) {
+                            // Temporal predicates are pushed down whenever possible, no matter
+                            // how expensive the expressions they reference, as the filter bounds
+                            // the lifetime of each record and so the state that the operators
+                            // below it must maintain.
+                            if predicate.contains_temporal() {
+                                if let Some(inlined) = PredicatePushdown::inline_temporal(
+                                    &predicate,
+                                    input_arity,
+                                    scalars,
+                                ) {
+                                    pushdown.push(inlined);
+                                    continue;
+                                }
+                            }
                             // First, check if we can push this predicate down. We can do so if each
                             // column it references is either from the input or is generated by an
                             // expression that can be inlined.
@@ -446,6 +461,67 @@ impl PredicatePushdown {
                         *relation = inner.take_dangerous().filter(predicates).negate();
                         self.action(relation, get_predicates);
                     }
+                    MirRelationExpr::ArrangeBy { input: inner, keys }
+                        if !matches!(**inner, MirRelationExpr::Get { .. }) =>
+                    {
+                        // The dataflow builds this arrangement itself, so
+                        // filtering its input does not block the use of an
+                        // index. Filtering before arranging bounds the records
+                        // that the arrangement retains: with a temporal
+                        // predicate, records leave the arrangement once the
+                        // predicate no longer holds.
+                        let predicates = std::mem::replace(predicates, Vec::new());
+                        let keys = std::mem::replace(keys, Vec::new());
+                        *relation = inner.take_dangerous().filter(predicates).arrange_by(&keys);
+                        self.action(relation, get_predicates);
+                    }
+                    MirRelationExpr::Threshold { input: inner } => {
+                        // Thresholding acts on each record independently, and
+                        // so commutes with any filter.
+                        let predicates = std::mem::replace(predicates, Vec::new());
+                        *relation = inner.take_dangerous().filter(predicates).threshold();
+                        self.action(relation, get_predicates);
+                    }
+                    MirRelationExpr::TopK {
+                        input: inner,
+                        group_key,
+                        ..
+                    } => {
+                        // A predicate that only references the group key
+                        // removes entire groups, and so can be applied before
+                        // the groups are limited.
+                        let (push_down, retain): (Vec<_>, Vec<_>) = predicates
+                            .drain(..)
+                            .partition(|p| p.support().iter().all(|c| group_key.contains(c)));
+                        if !push_down.is_empty() {
+                            *inner = Box::new(inner.take_dangerous().filter(push_down));
+                        }
+                        self.action(inner, get_predicates);
+
+                        if !retain.is_empty() {
+                            *predicates = retain;
+                        } else {
+                            *relation = input.take_dangerous();
+                        }
+                    }
+                    MirRelationExpr::FlatMap { input: inner, .. } => {
+                        // A predicate that only references the columns of the
+                        // input can be applied before the function is.
+                        let input_arity = inner.arity();
+                        let (push_down, retain): (Vec<_>, Vec<_>) = predicates
+                            .drain(..)
+                            .partition(|p| p.support().iter().all(|c| *c < input_arity));
+                        if !push_down.is_empty() {
+                            *inner = Box::new(inner.take_dangerous().filter(push_down));
+                        }
+                        self.action(inner, get_predicates);
+
+                        if !retain.is_empty() {
+                            *predicates = retain;
+                        } else {
+                            *relation = input.take_dangerous();
+                        }
+                    }
                     x => {
                         x.visit1_mut(|e| self.action(e, get_predicates));
                     }
@@ -465,6 +541,41 @@ impl PredicatePushdown {
         }
     }
 
+    /// Rewrites the temporal `predicate` over the output of a `Map` to refer
+    /// only to the columns of the map's input, by inlining the map's
+    /// `scalars`, however complex.
+    ///
+    /// Returns `None` if the predicate references a scalar that is itself
+    /// temporal, as inlining that scalar would produce a predicate that
+    /// compares the logical timestamp against itself.
+    fn inline_temporal(
+        predicate: &MirScalarExpr,
+        input_arity: usize,
+        scalars: &[MirScalarExpr],
+    ) -> Option<MirScalarExpr> {
+        let mut predicate = predicate.clone();
+        let mut inlinable = true;
+        // Each scalar only references the columns before it, so repeatedly
+        // inlining the referenced scalars eventually leaves only references
+        // to the input.
+        while inlinable && predicate.support().iter().any(|c| *c >= input_arity) {
+            predicate.visit_mut(&mut |e| {
+                if let MirScalarExpr::Column(c) = e {
+                    if *c >= input_arity {
+                        let scalar = &scalars[*c - input_arity];
+                        inlinable &= !scalar.contains_temporal();
+                        *e = scalar.clone();
+                    }
+                }
+            });
+        }
+        if inlinable {
+            Some(predicate)
+        } else {
+            None
+        }
+    }
+
     /// Defines a criteria for inlining scalar expressions.
     // TODO(justin): create a list of which functions are acceptable to inline. We shouldn't
     // inline ones that are "expensive."
@@ -492,3 +603,15 @@ impl PredicatePushdown {
         }
     }
 }
+
+/// Reports whether `relation` arranges a collection that may already be
+/// arranged by an index, in which case the arrangement is imported rather than
+/// built.
+fn is_imported_arrangement(relation: &MirRelationExpr) -> bool {
+    match relation {
+        MirRelationExpr::ArrangeBy { input, .. } => {
+            matches!(**input, MirRelationExpr::Get { .. })
+        }
+        _ => false,
+    }
+}
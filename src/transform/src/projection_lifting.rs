@@ -223,6 +223,7 @@ impl ProjectionLifting {
                 limit,
                 offset,
                 monotonic: _,
+                expected_group_size,
             } => {
                 self.action(input, gets);
                 if let MirRelationExpr::Project {
@@ -243,6 +244,7 @@ impl ProjectionLifting {
                             order_key.clone(),
                             limit.clone(),
                             offset.clone(),
+                            expected_group_size.clone(),
                         )
                         .project(outputs.clone());
                 }
@@ -82,6 +82,42 @@ impl ReduceElision {
                             exprs: vec![a.expr.clone()],
                         },
 
+                        // RangeAgg takes a range as input, but must output an
+                        // array that omits the range if it is empty.
+                        AggregateFunc::RangeAgg => {
+                            let column_type = a.typ(&input_type);
+                            let elem_type = column_type.scalar_type.unwrap_array_element_type();
+                            let array = |exprs: Vec<MirScalarExpr>| MirScalarExpr::CallVariadic {
+                                func: VariadicFunc::ArrayCreate {
+                                    elem_type: elem_type.clone(),
+                                },
+                                exprs,
+                            };
+                            a.expr.clone().call_unary(UnaryFunc::IsNull).if_then_else(
+                                MirScalarExpr::literal_null(column_type.scalar_type.clone()),
+                                a.expr
+                                    .clone()
+                                    .call_unary(UnaryFunc::RangeIsEmpty)
+                                    .if_then_else(array(vec![]), array(vec![a.expr.clone()])),
+                            )
+                        }
+
+                        // ArrayAgg takes records of a value and its sort keys as
+                        // input, but must output an array of the value.
+                        AggregateFunc::ArrayAgg { .. } => {
+                            let column_type = a.typ(&input_type);
+                            let elem_type = column_type.scalar_type.unwrap_array_element_type();
+                            a.expr.clone().call_unary(UnaryFunc::IsNull).if_then_else(
+                                MirScalarExpr::literal_null(column_type.scalar_type.clone()),
+                                MirScalarExpr::CallVariadic {
+                                    func: VariadicFunc::ArrayCreate {
+                                        elem_type: elem_type.clone(),
+                                    },
+                                    exprs: vec![a.expr.clone().call_unary(UnaryFunc::RecordGet(0))],
+                                },
+                            )
+                        }
+
                         // All other variants should return the argument to the aggregation.
                         _ => a.expr.clone(),
                     })
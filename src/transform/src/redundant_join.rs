@@ -17,14 +17,20 @@
 //! This type of optimization shows up often in subqueries, where distinct
 //! collections are used in decorrelation, and afterwards often distinct
 //! collections are then joined against the results.
+//!
+//! It also shows up in joins along declared foreign keys, where each record
+//! of the referencing collection is promised to match exactly one record of
+//! the referenced collection. If no columns of the referenced collection are
+//! observed other than its key, the join is redundant.
 
 // If statements seem a bit clearer in this case. Specialized methods
 // that replace simple and common alternatives frustrate developers.
 #![allow(clippy::comparison_chain, clippy::filter_next)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use expr::{Id, JoinInputMapper, MirRelationExpr, MirScalarExpr};
+use expr::{ForeignKey, GlobalId, Id, JoinInputMapper, MirRelationExpr, MirScalarExpr};
+use repr::{Datum, Row};
 
 use crate::TransformArgs;
 
@@ -36,9 +42,9 @@ impl crate::Transform for RedundantJoin {
     fn transform(
         &self,
         relation: &mut MirRelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
-        self.action(relation, &mut HashMap::new());
+        self.action(relation, &mut HashMap::new(), args.foreign_keys);
         Ok(())
     }
 }
@@ -57,17 +63,22 @@ impl RedundantJoin {
     /// variant to remove "redundant" joins, those that can be determined to
     /// neither restrict nor augment one of the input relations. Consult the
     /// `find_redundancy` method and its documentation for more detail.
+    ///
+    /// The foreign keys declared on imported collections in `foreign_keys`
+    /// contribute provenance information relating the referencing columns
+    /// to the referenced collection.
     pub fn action(
         &self,
         relation: &mut MirRelationExpr,
         lets: &mut HashMap<Id, Vec<ProvInfo>>,
+        foreign_keys: &HashMap<GlobalId, Vec<ForeignKey>>,
     ) -> Vec<ProvInfo> {
         match relation {
             MirRelationExpr::Let { id, value, body } => {
                 // Recursively determine provenance of the value.
-                let value_prov = self.action(value, lets);
+                let value_prov = self.action(value, lets, foreign_keys);
                 let old = lets.insert(Id::Local(*id), value_prov);
-                let result = self.action(body, lets);
+                let result = self.action(body, lets, foreign_keys);
                 if let Some(old) = old {
                     lets.insert(Id::Local(*id), old);
                 } else {
@@ -84,6 +95,25 @@ impl RedundantJoin {
                     binding: (0..typ.arity()).map(|c| (c, c)).collect::<Vec<_>>(),
                     exact: true,
                 });
+                // A declared foreign key promises that the referencing columns,
+                // when none of them are null, hold values of the referenced
+                // columns of the parent collection.
+                if let Id::Global(global_id) = id {
+                    for fk in foreign_keys.get(global_id).into_iter().flatten() {
+                        if fk.columns.iter().all(|c| !typ.column_types[*c].nullable) {
+                            val_info.push(ProvInfo {
+                                id: Id::Global(fk.parent),
+                                binding: fk
+                                    .parent_columns
+                                    .iter()
+                                    .cloned()
+                                    .zip(fk.columns.iter().cloned())
+                                    .collect(),
+                                exact: false,
+                            });
+                        }
+                    }
+                }
                 val_info
             }
 
@@ -102,13 +132,26 @@ impl RedundantJoin {
                 // Recursively apply transformation, and determine the provenance of inputs.
                 let input_prov = inputs
                     .iter_mut()
-                    .map(|i| self.action(i, lets))
+                    .map(|i| self.action(i, lets, foreign_keys))
                     .collect::<Vec<_>>();
 
                 // Determine useful information about the structure of the inputs.
                 let mut input_types = inputs.iter().map(|i| i.typ()).collect::<Vec<_>>();
                 let old_input_mapper = JoinInputMapper::new_from_input_types(&input_types);
 
+                // Determine the columns whose values are observed, either by the
+                // equivalences or, as reported by `Demand`, in the output. Other
+                // columns may be replaced with dummy values.
+                let mut observed = match demand {
+                    Some(demand) => demand.iter().cloned().collect::<HashSet<_>>(),
+                    None => (0..old_input_mapper.total_columns()).collect(),
+                };
+                for equivalence in equivalences.iter() {
+                    for expr in equivalence.iter() {
+                        observed.extend(expr.support());
+                    }
+                }
+
                 // If we find an input that can be removed, we should do so!
                 // We only do this once per invocation to keep our sanity, but we could
                 // rewrite it to iterate. We can avoid looking for any relation that
@@ -122,6 +165,7 @@ impl RedundantJoin {
                             &input_types[i].keys,
                             &old_input_mapper,
                             equivalences,
+                            &observed,
                             &input_prov[..],
                         )
                         .map(|b| (i, b))
@@ -129,7 +173,7 @@ impl RedundantJoin {
                     .next()
                 {
                     inputs.remove(input);
-                    input_types.remove(input);
+                    let removed_type = input_types.remove(input);
 
                     let new_input_mapper = JoinInputMapper::new_from_input_types(&input_types);
                     // From `binding`, we produce the projection we will apply to the join
@@ -147,7 +191,8 @@ impl RedundantJoin {
                             // When we reach the removed relation, we should introduce
                             // references to the columns that are meant to replace these.
                             // This should happen only once, and `.drain(..)` would be correct.
-                            projection.extend(bindings.clone());
+                            // Unbound columns are fixed up below.
+                            projection.extend(bindings.iter().map(|b| b.unwrap_or(0)));
                         }
                     }
                     // The references introduced from `bindings` need to be refreshed, now that we
@@ -155,8 +200,20 @@ impl RedundantJoin {
                     // have been to columns *after* `input`, and our original take on where they
                     // would be is no longer correct. References before `input` should stay as they
                     // are, and references afterwards will likely be decreased.
-                    for c in old_input_mapper.global_columns(input) {
-                        projection[c] = projection[projection[c]];
+                    //
+                    // Columns without a binding are not observed, and are replaced with dummy
+                    // values that are appended to the output of the join.
+                    let mut dummies = Vec::new();
+                    for (local, c) in old_input_mapper.global_columns(input).enumerate() {
+                        if bindings[local].is_some() {
+                            projection[c] = projection[projection[c]];
+                        } else {
+                            projection[c] = new_input_mapper.total_columns() + dummies.len();
+                            dummies.push(MirScalarExpr::Literal(
+                                Ok(Row::pack_slice(&[Datum::Dummy])),
+                                removed_type.column_types[local].clone(),
+                            ));
+                        }
                     }
 
                     // Tidy up equivalences rewriting column references with `projection` and
@@ -175,7 +232,11 @@ impl RedundantJoin {
                     *demand = None;
                     *implementation = expr::JoinImplementation::Unimplemented;
 
-                    *relation = relation.take_dangerous().project(projection);
+                    let mut result = relation.take_dangerous();
+                    if !dummies.is_empty() {
+                        result = result.map(dummies);
+                    }
+                    *relation = result.project(projection);
                     // The projection will gum up provenance reasoning anyhow, so don't work hard.
                     // We will return to this expression again with the same analysis.
                     Vec::new()
@@ -199,20 +260,20 @@ impl RedundantJoin {
 
             MirRelationExpr::Filter { input, .. } => {
                 // Filter may drop records, and so we unset `exact`.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, foreign_keys);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
                 result
             }
 
-            MirRelationExpr::Map { input, .. } => self.action(input, lets),
-            MirRelationExpr::DeclareKeys { input, .. } => self.action(input, lets),
+            MirRelationExpr::Map { input, .. } => self.action(input, lets, foreign_keys),
+            MirRelationExpr::DeclareKeys { input, .. } => self.action(input, lets, foreign_keys),
 
             MirRelationExpr::Union { base, inputs } => {
-                let mut prov = self.action(base, lets);
+                let mut prov = self.action(base, lets, foreign_keys);
                 for input in inputs {
-                    let input_prov = self.action(input, lets);
+                    let input_prov = self.action(input, lets, foreign_keys);
                     // To merge a new list of provenances, we look at the cross
                     // produce of things we might know about each source.
                     // TODO(mcsherry): this can be optimized to use datastructures
@@ -233,7 +294,7 @@ impl RedundantJoin {
             } => {
                 // Reduce yields its first few columns as a key, and produces
                 // all key tuples that were present in its input.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, foreign_keys);
                 for prov in result.iter_mut() {
                     // update the bindings. no need to update `exact`.
                     let new_bindings = group_key
@@ -266,7 +327,7 @@ impl RedundantJoin {
 
             MirRelationExpr::Threshold { input } => {
                 // Threshold may drop records, and so we unset `exact`.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, foreign_keys);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
@@ -275,7 +336,7 @@ impl RedundantJoin {
 
             MirRelationExpr::TopK { input, .. } => {
                 // TopK may drop records, and so we unset `exact`.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, foreign_keys);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
@@ -285,7 +346,7 @@ impl RedundantJoin {
             MirRelationExpr::Project { input, outputs } => {
                 // Projections re-order, drop, and duplicate columns,
                 // but they neither drop rows nor invent values.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, foreign_keys);
                 for provenance in result.iter_mut() {
                     let new_binding = outputs
                         .iter()
@@ -306,7 +367,7 @@ impl RedundantJoin {
 
             MirRelationExpr::FlatMap { input, .. } => {
                 // FlatMap may drop records, and so we unset `exact`.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, foreign_keys);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
@@ -319,14 +380,14 @@ impl RedundantJoin {
                 // been a problem in `Union`, where we might report
                 // that the union of positive and negative records is
                 // "exact": cancellations would make this false.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, foreign_keys);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
                 result
             }
 
-            MirRelationExpr::ArrangeBy { input, .. } => self.action(input, lets),
+            MirRelationExpr::ArrangeBy { input, .. } => self.action(input, lets, foreign_keys),
         }
     }
 }
@@ -388,16 +449,21 @@ impl ProvInfo {
 /// determine non-key columns, any matches between `input` and
 /// `other` will neither introduce new information to `other`, nor restrict the rows
 /// of `other`, nor alter their multplicity.
+///
+/// Every column of `input` that is `observed` must be bound to a column of `other`.
+/// The returned bindings hold, for each column of `input`, the column of `other`
+/// that can replace it, or `None` if the column is not observed and not bound.
 fn find_redundancy(
     input: usize,
     keys: &[Vec<usize>],
     input_mapper: &JoinInputMapper,
     equivalences: &[Vec<MirScalarExpr>],
+    observed: &HashSet<usize>,
     input_prov: &[Vec<ProvInfo>],
-) -> Option<Vec<usize>> {
+) -> Option<Vec<Option<usize>>> {
     for provenance in input_prov[input].iter() {
-        // We can only elide if the input contains all records, and binds all columns.
-        if provenance.exact && provenance.binding.len() == input_mapper.input_arity(input) {
+        // We can only elide if the input contains all records.
+        if provenance.exact {
             // examine all *other* inputs that have not been removed...
             for other in (0..input_mapper.total_inputs()).filter(|other| other != &input) {
                 for other_prov in input_prov[other].iter().filter(|p| p.id == provenance.id) {
@@ -414,28 +480,35 @@ fn find_redundancy(
                     // True iff `col = binding[col]` is in `equivalences` for all `col` in `cols`.
                     let all_columns_equated =
                         |cols: &Vec<usize>| {
-                            cols.iter().all(|input_col| {
-                                let other_col = bindings[&input_col];
-                                equivalences.iter().any(|e| {
+                            cols.iter().all(|input_col| match bindings.get(input_col) {
+                                Some(other_col) => equivalences.iter().any(|e| {
                                     e.contains(&input_mapper.map_expr_to_global(
                                         MirScalarExpr::Column(*input_col),
                                         input,
                                     )) && e.contains(&input_mapper.map_expr_to_global(
-                                        MirScalarExpr::Column(other_col),
+                                        MirScalarExpr::Column(*other_col),
                                         other,
                                     ))
-                                })
+                                }),
+                                None => false,
                             })
                         };
 
-                    // If all columns of `input` are bound, and any key columns of `input` are equated,
-                    // the binding can be returned as mapping replacements for each input column.
-                    if bindings.len() == input_mapper.input_arity(input)
-                        && keys.iter().any(|key| all_columns_equated(key))
-                    {
+                    // If all observed columns of `input` are bound, and any key columns of `input`
+                    // are equated, the binding can be returned as mapping replacements for each
+                    // input column.
+                    let all_observed_bound = input_mapper.local_columns(input).all(|c| {
+                        bindings.contains_key(&c)
+                            || !observed.contains(&input_mapper.map_column_to_global(c, input))
+                    });
+                    if all_observed_bound && keys.iter().any(|key| all_columns_equated(key)) {
                         let binding = input_mapper
                             .local_columns(input)
-                            .map(|c| input_mapper.map_column_to_global(bindings[&c], other))
+                            .map(|c| {
+                                bindings
+                                    .get(&c)
+                                    .map(|b| input_mapper.map_column_to_global(*b, other))
+                            })
                             .collect::<Vec<_>>();
                         return Some(binding);
                     }
@@ -135,8 +135,8 @@ mod tests {
 
     use expr::explain::Explanation;
     use expr::{
-        DummyHumanizer, ExprHumanizer, GlobalId, Id, JoinImplementation, LocalId, MirRelationExpr,
-        MirScalarExpr,
+        BinaryFunc, ColumnOrder, DummyHumanizer, ExprHumanizer, GlobalId, Id, JoinImplementation,
+        LocalId, MirRelationExpr, MirScalarExpr, NullaryFunc, TableFunc,
     };
     use repr::{ColumnType, Datum, RelationType, Row, ScalarType};
     use transform::{Optimizer, Transform, TransformArgs};
@@ -393,11 +393,49 @@ mod tests {
             "negate" => Ok(MirRelationExpr::Negate {
                 input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
             }),
+            // (threshold <input>)
+            "threshold" => Ok(MirRelationExpr::Threshold {
+                input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
+            }),
             // (filter <input> <predicate>)
             "filter" => Ok(MirRelationExpr::Filter {
                 input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
                 predicates: build_scalar_list(nth(&s, 2)?)?,
             }),
+            // (top-k <input> [<group key>] [<order key>] <limit>)
+            "top-k" => Ok(MirRelationExpr::TopK {
+                input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
+                group_key: try_list(nth(&s, 2)?)?
+                    .into_iter()
+                    .map(extract_idx)
+                    .collect::<Result<Vec<usize>, Error>>()?,
+                order_key: try_list(nth(&s, 3)?)?
+                    .into_iter()
+                    .map(|s| {
+                        Ok(ColumnOrder {
+                            column: extract_idx(s)?,
+                            desc: false,
+                        })
+                    })
+                    .collect::<Result<Vec<ColumnOrder>, Error>>()?,
+                limit: Some(try_atom(&nth(&s, 4)?)?.parse()?),
+                offset: 0,
+                monotonic: false,
+                expected_group_size: None,
+            }),
+            // (flat-map <input> generate_series [<exprs>])
+            "flat-map" => {
+                let func = match try_atom(&nth(&s, 2)?)?.as_str() {
+                    "generate_series" => TableFunc::GenerateSeriesInt64,
+                    func => bail!("unknown table function {}", func),
+                };
+                Ok(MirRelationExpr::FlatMap {
+                    input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
+                    func,
+                    exprs: build_scalar_list(nth(&s, 3)?)?,
+                    demand: None,
+                })
+            }
             // (arrange-by <input> [<keys>])
             "arrange-by" => Ok(MirRelationExpr::ArrangeBy {
                 input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
@@ -425,6 +463,9 @@ mod tests {
             Sexp::Atom(s) => match s.as_str() {
                 "true" => Ok(MirScalarExpr::literal(Ok(Datum::True), ScalarType::Bool)),
                 "false" => Ok(MirScalarExpr::literal(Ok(Datum::False), ScalarType::Bool)),
+                "mz_logical_timestamp" => {
+                    Ok(MirScalarExpr::CallNullary(NullaryFunc::MzLogicalTimestamp))
+                }
                 s => {
                     match s.chars().next() {
                         None => {
@@ -445,7 +486,21 @@ mod tests {
                     }
                 }
             },
-            s => Err(anyhow!("expected {} to be a scalar", s)),
+            // (<binary func> <expr1> <expr2>)
+            Sexp::List(_) => {
+                let func = match try_atom(&nth(&s, 0)?)?.as_str() {
+                    "add_int64" => BinaryFunc::AddInt64,
+                    "mul_int64" => BinaryFunc::MulInt64,
+                    "lt" => BinaryFunc::Lt,
+                    "gte" => BinaryFunc::Gte,
+                    func => bail!("unknown binary function {}", func),
+                };
+                Ok(MirScalarExpr::CallBinary {
+                    func,
+                    expr1: Box::new(build_scalar(nth(&s, 1)?)?),
+                    expr2: Box::new(build_scalar(nth(&s, 2)?)?),
+                })
+            }
         }
     }
 
@@ -525,12 +580,14 @@ mod tests {
 
         let mut id_gen = Default::default();
         let indexes = HashMap::new();
+        let foreign_keys = HashMap::new();
         for t in args.get("apply").cloned().unwrap_or_else(Vec::new).iter() {
             get_transform(t)?.transform(
                 &mut rel,
                 TransformArgs {
                     id_gen: &mut id_gen,
                     indexes: &indexes,
+                    foreign_keys: &foreign_keys,
                 },
             )?;
         }
@@ -538,7 +595,10 @@ mod tests {
         match test_type {
             TestType::Opt => {
                 let mut opt: Optimizer = Default::default();
-                rel = opt.optimize(rel, &HashMap::new()).unwrap().into_inner();
+                rel = opt
+                    .optimize(rel, &indexes, &foreign_keys)
+                    .unwrap()
+                    .into_inner();
 
                 Ok(generate_explanation(&rel, &cat, args.get("format")))
             }
@@ -565,6 +625,7 @@ mod tests {
                         TransformArgs {
                             id_gen: &mut id_gen,
                             indexes: &indexes,
+                            foreign_keys: &foreign_keys,
                         },
                     )?;
 
@@ -0,0 +1,58 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::time::Duration;
+
+use structopt::StructOpt;
+
+/// Peek and ingest latency benchmark for Materialize.
+#[derive(Clone, Debug, StructOpt)]
+pub struct Args {
+    /// The materialized host
+    #[structopt(long, default_value = "localhost")]
+    pub materialized_host: String,
+
+    /// The materialized port
+    #[structopt(long, default_value = "6875")]
+    pub materialized_port: u16,
+
+    /// Number of seconds to run the workloads for before measuring
+    #[structopt(long, default_value = "10")]
+    pub warmup_secs: u64,
+
+    /// Number of seconds to measure the workloads for
+    #[structopt(long, default_value = "60")]
+    pub duration_secs: u64,
+
+    /// Number of rows to insert per second
+    #[structopt(long, default_value = "1000")]
+    pub ingest_rate: u64,
+
+    /// Number of clients that run the closed-loop peek workload
+    #[structopt(long, default_value = "4")]
+    pub peek_clients: usize,
+
+    /// Number of distinct keys in the inserted rows
+    #[structopt(long, default_value = "1000")]
+    pub keys: i32,
+
+    /// Seed for the random number generators that produce rows and peeks
+    #[structopt(long, default_value = "0")]
+    pub seed: u64,
+}
+
+impl Args {
+    pub fn warmup(&self) -> Duration {
+        Duration::from_secs(self.warmup_secs)
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.duration_secs)
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Peek and ingest latency benchmark for Materialize.
+//!
+//! The benchmark creates a table, which stands in for a source, and a fixed
+//! set of materialized views over it. It then runs two workloads at once:
+//!
+//!   * The ingest workload inserts batches of rows into the table at a fixed
+//!     rate, and measures the time from the start of each insert until the
+//!     batch is reflected in the views.
+//!   * The peek workload runs a number of clients that each query the views in
+//!     a closed loop, issuing the next query as soon as the previous one
+//!     returns, and measures the latency of each query.
+//!
+//! After a warmup period, latencies are recorded for a fixed duration and
+//! reported as percentiles. Rows and peeks are generated from seeded random
+//! number generators, so that runs with the same arguments do the same work
+//! and their results can be compared across versions.
+
+#![deny(missing_debug_implementations, missing_docs)]
+
+use std::process;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use structopt::StructOpt;
+use tokio::time;
+use tokio_postgres::Client;
+
+use test_util::mz_client;
+
+use crate::config::Args;
+
+mod config;
+
+/// The table that the ingest workload inserts into.
+const EVENTS_TABLE: &str = "perf_latency_events";
+
+/// A view that aggregates the entire table.
+const TOTALS_VIEW: &str = "perf_latency_totals";
+
+/// A view that aggregates the table by key.
+const BY_KEY_VIEW: &str = "perf_latency_by_key";
+
+/// How often the ingest workload inserts a batch of rows.
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run().await {
+        eprintln!("ERROR: {:#}", e);
+        process::exit(1);
+    }
+}
+
+async fn run() -> Result<()> {
+    let args = Args::from_args();
+    env_logger::init();
+
+    log::info!(
+        "starting up mzd={}:{} ingest_rate={} peek_clients={} keys={} seed={}",
+        args.materialized_host,
+        args.materialized_port,
+        args.ingest_rate,
+        args.peek_clients,
+        args.keys,
+        args.seed,
+    );
+
+    let client = mz_client::client(&args.materialized_host, args.materialized_port).await?;
+    create_objects(&client).await?;
+
+    let measure_from = Instant::now() + args.warmup();
+    let until = measure_from + args.duration();
+
+    let ingest = tokio::spawn(ingest(args.clone(), measure_from, until));
+    let peeks: Vec<_> = (0..args.peek_clients)
+        .map(|i| tokio::spawn(peek(args.clone(), i, measure_from, until)))
+        .collect();
+
+    let ingest_latencies = ingest.await??;
+    let mut lookup_latencies = vec![];
+    let mut scan_latencies = vec![];
+    for peek in futures::future::join_all(peeks).await {
+        let (lookups, scans) = peek??;
+        lookup_latencies.extend(lookups);
+        scan_latencies.extend(scans);
+    }
+
+    println!(
+        "{:<12} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        "workload", "count", "per sec", "p50", "p90", "p99", "p99.9", "max"
+    );
+    report("ingest", ingest_latencies, args.duration());
+    report("peek-lookup", lookup_latencies, args.duration());
+    report("peek-scan", scan_latencies, args.duration());
+    Ok(())
+}
+
+/// Drops and recreates the table and views that the workloads use, so that
+/// every run starts from the same state.
+async fn create_objects(client: &Client) -> Result<()> {
+    client
+        .batch_execute(&format!("DROP TABLE IF EXISTS {} CASCADE", EVENTS_TABLE))
+        .await?;
+    client
+        .batch_execute(&format!(
+            "CREATE TABLE {} (id bigint NOT NULL, key int NOT NULL, value double precision)",
+            EVENTS_TABLE
+        ))
+        .await?;
+    client
+        .batch_execute(&format!(
+            "CREATE MATERIALIZED VIEW {} AS
+             SELECT count(*) AS count, max(id) AS max_id, sum(value) AS total FROM {}",
+            TOTALS_VIEW, EVENTS_TABLE
+        ))
+        .await?;
+    client
+        .batch_execute(&format!(
+            "CREATE MATERIALIZED VIEW {} AS
+             SELECT key, count(*) AS count, sum(value) AS total FROM {} GROUP BY key",
+            BY_KEY_VIEW, EVENTS_TABLE
+        ))
+        .await?;
+    Ok(())
+}
+
+/// Inserts batches of rows at the configured rate until `until`, returning
+/// the ingest latencies of the batches inserted after `measure_from`.
+async fn ingest(args: Args, measure_from: Instant, until: Instant) -> Result<Vec<Duration>> {
+    let client = mz_client::client(&args.materialized_host, args.materialized_port).await?;
+    let mut rng = StdRng::seed_from_u64(args.seed);
+    let batches_per_sec = Duration::from_secs(1).as_millis() / BATCH_INTERVAL.as_millis();
+    let batch_size = std::cmp::max(1, args.ingest_rate / batches_per_sec as u64);
+    let poll = format!("SELECT max_id FROM {}", TOTALS_VIEW);
+
+    let mut interval = time::interval(BATCH_INTERVAL);
+    let mut next_id: i64 = 0;
+    let mut latencies = vec![];
+    loop {
+        interval.tick().await;
+        if Instant::now() >= until {
+            break;
+        }
+        let values: Vec<_> = (0..batch_size)
+            .map(|_| {
+                let row = format!(
+                    "({}, {}, {})",
+                    next_id,
+                    rng.gen_range(0..args.keys),
+                    rng.gen::<f64>()
+                );
+                next_id += 1;
+                row
+            })
+            .collect();
+        let last_id = next_id - 1;
+
+        let start = Instant::now();
+        client
+            .batch_execute(&format!(
+                "INSERT INTO {} VALUES {}",
+                EVENTS_TABLE,
+                values.join(", ")
+            ))
+            .await?;
+        // Wait for the batch to be reflected in the views.
+        loop {
+            let rows = mz_client::try_query(&client, &poll, Duration::from_millis(1)).await?;
+            let max_id: Option<i64> = rows.first().and_then(|row| row.get(0));
+            if max_id >= Some(last_id) {
+                break;
+            }
+        }
+        if start >= measure_from {
+            latencies.push(start.elapsed());
+        }
+    }
+    Ok(latencies)
+}
+
+/// Queries the views in a closed loop until `until`, returning the latencies
+/// of the point lookups and the scans issued after `measure_from`.
+async fn peek(
+    args: Args,
+    client_id: usize,
+    measure_from: Instant,
+    until: Instant,
+) -> Result<(Vec<Duration>, Vec<Duration>)> {
+    let client = mz_client::client(&args.materialized_host, args.materialized_port).await?;
+    let mut rng = StdRng::seed_from_u64(args.seed.wrapping_add(1 + client_id as u64));
+    let lookup = client
+        .prepare(&format!(
+            "SELECT count, total FROM {} WHERE key = $1",
+            BY_KEY_VIEW
+        ))
+        .await?;
+    let scan = client
+        .prepare(&format!("SELECT count, total FROM {}", TOTALS_VIEW))
+        .await?;
+
+    let mut lookups = vec![];
+    let mut scans = vec![];
+    loop {
+        let start = Instant::now();
+        if start >= until {
+            break;
+        }
+        // Issue roughly nine point lookups for every scan.
+        let latencies = if rng.gen_ratio(1, 10) {
+            client.query(&scan, &[]).await?;
+            &mut scans
+        } else {
+            let key: i32 = rng.gen_range(0..args.keys);
+            client.query(&lookup, &[&key]).await?;
+            &mut lookups
+        };
+        if start >= measure_from {
+            latencies.push(start.elapsed());
+        }
+    }
+    Ok((lookups, scans))
+}
+
+/// Prints a line of latency percentiles for the workload named `name`.
+fn report(name: &str, mut latencies: Vec<Duration>, duration: Duration) {
+    if latencies.is_empty() {
+        println!("{:<12} {:>10}", name, 0);
+        return;
+    }
+    latencies.sort();
+    let percentile = |p: f64| {
+        let i = (p * latencies.len() as f64).ceil() as usize;
+        format_latency(latencies[i.saturating_sub(1).min(latencies.len() - 1)])
+    };
+    println!(
+        "{:<12} {:>10} {:>10.1} {:>10} {:>10} {:>10} {:>10} {:>10}",
+        name,
+        latencies.len(),
+        latencies.len() as f64 / duration.as_secs_f64(),
+        percentile(0.5),
+        percentile(0.9),
+        percentile(0.99),
+        percentile(0.999),
+        format_latency(latencies[latencies.len() - 1]),
+    );
+}
+
+fn format_latency(latency: Duration) -> String {
+    format!("{:.3}ms", latency.as_secs_f64() * 1000.0)
+}